@@ -4,7 +4,7 @@ use chrono::DateTime;
 use near_primitives::time::Utc;
 
 use near_primitives::block::BlockValidityError;
-use near_primitives::challenge::{ChunkProofs, ChunkState};
+use near_primitives::challenge::{ChunkProofs, ChunkStateV2};
 use near_primitives::errors::{EpochError, StorageError};
 use near_primitives::shard_layout::ShardLayoutError;
 use near_primitives::sharding::{ChunkHash, ShardChunkHeader};
@@ -133,7 +133,7 @@ pub enum Error {
     InvalidChunkProofs(Box<ChunkProofs>),
     /// Invalid chunk state.
     #[error("Invalid Chunk State")]
-    InvalidChunkState(Box<ChunkState>),
+    InvalidChunkState(Box<ChunkStateV2>),
     /// Invalid chunk mask
     #[error("Invalid Chunk Mask")]
     InvalidChunkMask,
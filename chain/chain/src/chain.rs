@@ -17,8 +17,8 @@ use tracing::{debug, error, info, warn, Span};
 use near_chain_primitives::error::{BlockKnownError, Error, LogTransientStorageError};
 use near_primitives::block::{genesis_chunks, Tip};
 use near_primitives::challenge::{
-    BlockDoubleSign, Challenge, ChallengeBody, ChallengesResult, ChunkProofs, ChunkState,
-    MaybeEncodedShardChunk, PartialState, SlashedValidator,
+    BlockDoubleSign, Challenge, ChallengeBody, ChallengesResult, ChunkProofs,
+    ChunkStateTransitionProof, ChunkStateV2, MaybeEncodedShardChunk, SlashedValidator,
 };
 use near_primitives::checked_feature;
 use near_primitives::hash::{hash, CryptoHash};
@@ -48,7 +48,7 @@ use near_primitives::utils::MaybeValidated;
 use near_primitives::views::{
     BlockStatusView, DroppedReason, ExecutionOutcomeWithIdView, ExecutionStatusView,
     FinalExecutionOutcomeView, FinalExecutionOutcomeWithReceiptView, FinalExecutionStatus,
-    LightClientBlockView, SignedTransactionView,
+    LightClientBlockView, ReceiptExecutionTraceView, SignedTransactionView,
 };
 #[cfg(feature = "protocol_feature_flat_state")]
 use near_store::{flat_state, StorageError};
@@ -70,8 +70,9 @@ use crate::types::{
     ChainConfig, ChainGenesis, Provenance, RuntimeWithEpochManagerAdapter,
 };
 use crate::validate::{
-    validate_challenge, validate_chunk_proofs, validate_chunk_with_chunk_extra,
-    validate_transactions_order,
+    get_chunk_apply_context, get_incoming_receipts_from_store,
+    prepare_chunk_state_transition_proof, validate_challenge, validate_chunk_proofs,
+    validate_chunk_with_chunk_extra, validate_transactions_order, ChunkStateTransition,
 };
 use crate::{byzantine_assert, create_light_client_block_view, Doomslug};
 use crate::{metrics, DoomslugThresholdMode};
@@ -470,6 +471,10 @@ pub struct Chain {
     /// Used to store state parts already requested along with elapsed time
     /// to create the parts. This information is used for debugging
     pub(crate) requested_state_parts: StateRequestTracker,
+
+    /// Whether to record storage proofs of applied chunks, see
+    /// `ChainConfig::record_state_transition_proofs`.
+    record_state_transition_proofs: bool,
 }
 
 impl Drop for Chain {
@@ -534,6 +539,7 @@ impl Chain {
             invalid_blocks: LruCache::new(INVALID_CHUNKS_POOL_SIZE),
             pending_state_patch: Default::default(),
             requested_state_parts: StateRequestTracker::new(),
+            record_state_transition_proofs: false,
         })
     }
 
@@ -683,6 +689,7 @@ impl Chain {
             last_time_head_updated: Clock::instant(),
             pending_state_patch: Default::default(),
             requested_state_parts: StateRequestTracker::new(),
+            record_state_transition_proofs: chain_config.record_state_transition_proofs,
         })
     }
 
@@ -1376,7 +1383,13 @@ impl Chain {
         let mut result = vec![];
         let mut challenged_blocks = vec![];
         for challenge in challenges.iter() {
-            match validate_challenge(&*self.runtime_adapter, epoch_id, prev_block_hash, challenge) {
+            match validate_challenge(
+                self.store(),
+                &*self.runtime_adapter,
+                epoch_id,
+                prev_block_hash,
+                challenge,
+            ) {
                 Ok((hash, account_ids)) => {
                     let is_double_sign = match challenge.body {
                         // If it's double signed block, we don't invalidate blocks just slash.
@@ -1589,7 +1602,7 @@ impl Chain {
         Ok(receipt_proofs_by_shard_id)
     }
 
-    fn shuffle_receipt_proofs<ReceiptProofType>(
+    pub(crate) fn shuffle_receipt_proofs<ReceiptProofType>(
         receipt_proofs: &mut Vec<ReceiptProofType>,
         block_hash: &CryptoHash,
     ) {
@@ -3591,71 +3604,125 @@ impl Chain {
         prev_block: &Block,
         block: &Block,
         chunk_header: &ShardChunkHeader,
-    ) -> Result<ChunkState, Error> {
+    ) -> Result<ChunkStateV2, Error> {
         let chunk_shard_id = chunk_header.shard_id();
         let prev_merkle_proofs = Block::compute_chunk_headers_root(prev_block.chunks().iter()).1;
         let merkle_proofs = Block::compute_chunk_headers_root(block.chunks().iter()).1;
-        let prev_chunk = self
-            .get_chunk_clone_from_header(&prev_block.chunks()[chunk_shard_id as usize].clone())
-            .unwrap();
-
-        // TODO (#6316): enable storage proof generation
-        // let prev_chunk_header = &prev_block.chunks()[chunk_shard_id as usize];
-        // let receipt_proof_response: Vec<ReceiptProofResponse> =
-        //     self.chain_store_update.get_incoming_receipts_for_shard(
-        //         chunk_shard_id,
-        //         *prev_block.hash(),
-        //         prev_chunk_header.height_included(),
-        //     )?;
-        // let receipts = collect_receipts_from_response(&receipt_proof_response);
-        //
-        // let challenges_result = self.verify_challenges(
-        //     block.challenges(),
-        //     block.header().epoch_id(),
-        //     block.header().prev_hash(),
-        //     Some(block.hash()),
-        // )?;
-        // let prev_chunk_inner = prev_chunk.cloned_header().take_inner();
-        // let is_first_block_with_chunk_of_version = check_if_block_is_first_with_chunk_of_version(
-        //     &mut self.chain_store_update,
-        //     self.runtime_adapter.as_ref(),
-        //     prev_block.hash(),
-        //     chunk_shard_id,
-        // )?;
-        // let apply_result = self
-        //     .runtime_adapter
-        //     .apply_transactions_with_optional_storage_proof(
-        //         chunk_shard_id,
-        //         prev_chunk_inner.prev_state_root(),
-        //         prev_chunk.height_included(),
-        //         prev_block.header().raw_timestamp(),
-        //         prev_chunk_inner.prev_block_hash(),
-        //         prev_block.hash(),
-        //         &receipts,
-        //         prev_chunk.transactions(),
-        //         prev_chunk_inner.validator_proposals(),
-        //         prev_block.header().gas_price(),
-        //         prev_chunk_inner.gas_limit(),
-        //         &challenges_result,
-        //         *block.header().random_value(),
-        //         true,
-        //         true,
-        //         is_first_block_with_chunk_of_version,
-        //         None,
-        //     )
-        //     .unwrap();
-        // let partial_state = apply_result.proof.unwrap().nodes;
-        Ok(ChunkState {
+        let prev_chunk_header = prev_block
+            .chunks()
+            .get(chunk_shard_id as usize)
+            .cloned()
+            .ok_or(Error::InvalidShardId(chunk_shard_id))?;
+        // Only the results of a chunk which was applied in `prev_block` are committed to by
+        // `chunk_header`, so there is nothing to re-execute otherwise.
+        if prev_chunk_header.height_included() != prev_block.header().height() {
+            return Err(Error::Other(format!(
+                "No new chunk for shard {} in block {}",
+                chunk_shard_id,
+                prev_block.hash()
+            )));
+        }
+        let prev_chunk = self.get_chunk_clone_from_header(&prev_chunk_header)?;
+
+        let context = get_chunk_apply_context(
+            self.store(),
+            &*self.runtime_adapter,
+            prev_block.header(),
+            chunk_shard_id,
+        )?;
+        let (prev_chunk_incoming_receipts, receipts) = get_incoming_receipts_from_store(
+            self.store(),
+            prev_block.hash(),
+            chunk_shard_id,
+            context.prev_chunk_height_included,
+        )?;
+        let apply_result = self.runtime_adapter.apply_transactions_with_optional_storage_proof(
+            chunk_shard_id,
+            &prev_chunk_header.prev_state_root(),
+            prev_chunk_header.height_included(),
+            prev_block.header().raw_timestamp(),
+            prev_chunk_header.prev_block_hash(),
+            prev_block.hash(),
+            &receipts,
+            prev_chunk.transactions(),
+            prev_chunk_header.validator_proposals(),
+            context.gas_price,
+            prev_chunk_header.gas_limit(),
+            prev_block.header().challenges_result(),
+            *prev_block.header().random_value(),
+            true,
+            true,
+            context.is_first_block_with_chunk_of_version,
+            Default::default(),
+            false,
+        )?;
+        let partial_state = apply_result
+            .proof
+            .ok_or_else(|| Error::Other("Storage proof was not generated".to_string()))?
+            .nodes;
+        Ok(ChunkStateV2 {
             prev_block_header: prev_block.header().try_to_vec()?,
             block_header: block.header().try_to_vec()?,
             prev_merkle_proof: prev_merkle_proofs[chunk_shard_id as usize].clone(),
             merkle_proof: merkle_proofs[chunk_shard_id as usize].clone(),
             prev_chunk,
             chunk_header: chunk_header.clone(),
-            partial_state: PartialState(vec![]),
+            partial_state,
+            prev_chunk_incoming_receipts,
         })
     }
 
+    /// Builds the proof that applying the new chunk of `chunk_header`'s shard in `prev_block`
+    /// results in the post-state `chunk_header` commits to, from the trie nodes recorded while
+    /// applying it. Returns `None` if `prev_block` has no new chunk for the shard, as nothing was
+    /// applied then.
+    pub fn create_chunk_state_transition_proof(
+        &self,
+        prev_block: &Block,
+        chunk_header: &ShardChunkHeader,
+    ) -> Result<Option<ChunkStateTransitionProof>, Error> {
+        let shard_id = chunk_header.shard_id();
+        let prev_chunk_header = prev_block
+            .chunks()
+            .get(shard_id as usize)
+            .cloned()
+            .ok_or(Error::InvalidShardId(shard_id))?;
+        if prev_chunk_header.height_included() != prev_block.header().height() {
+            return Ok(None);
+        }
+        let partial_state = self.store.get_state_transition_proof(prev_block.hash(), shard_id)?;
+        let prev_chunk = self.get_chunk(&prev_chunk_header.chunk_hash())?;
+        let context = get_chunk_apply_context(
+            self.store(),
+            &*self.runtime_adapter,
+            prev_block.header(),
+            shard_id,
+        )?;
+        let (prev_chunk_incoming_receipts, _) = get_incoming_receipts_from_store(
+            self.store(),
+            prev_block.hash(),
+            shard_id,
+            context.prev_chunk_height_included,
+        )?;
+        Ok(Some(ChunkStateTransitionProof {
+            prev_block_hash: *prev_block.hash(),
+            prev_chunk_transactions: prev_chunk.transactions().to_vec(),
+            prev_chunk_incoming_receipts,
+            chunk_header: chunk_header.clone(),
+            partial_state,
+        }))
+    }
+
+    /// Verifies the inputs of a state transition proof received from a chunk producer against the
+    /// chain. The returned state transition re-executes the previous chunk on the trie nodes from
+    /// the proof, so the shard state is not needed and it works for shards the node doesn't track.
+    pub fn prepare_chunk_state_transition(
+        &self,
+        proof: &ChunkStateTransitionProof,
+    ) -> Result<ChunkStateTransition, Error> {
+        prepare_chunk_state_transition_proof(self.store(), &*self.runtime_adapter, proof)
+    }
+
     fn get_split_state_roots(
         &self,
         block: &Block,
@@ -3791,7 +3858,10 @@ impl Chain {
                             Ok(chunk_state) => {
                                 Error::InvalidChunkState(Box::new(chunk_state))
                             }
-                            Err(err) => err,
+                            Err(err) => {
+                                warn!(target: "chain", ?err, "Failed to create chunk state challenge");
+                                e
+                            }
                         }
                     })?;
                     // we can't use hash from the current block here yet because the incoming receipts
@@ -3861,6 +3931,7 @@ impl Chain {
                     let random_seed = *block.header().random_value();
                     let height = chunk_header.height_included();
                     let prev_block_hash = chunk_header.prev_block_hash().clone();
+                    let generate_storage_proof = self.record_state_transition_proofs;
 
                     Ok(Some(Box::new(move |parent_span| -> Result<ApplyChunkResult, Error> {
                         let _span = tracing::debug_span!(
@@ -3870,7 +3941,10 @@ impl Chain {
                             shard_id)
                         .entered();
                         let _timer = CryptoHashTimer::new(chunk.chunk_hash().0);
-                        match runtime_adapter.apply_transactions(
+                        let _apply_timer = metrics::APPLYING_CHUNKS_TIME
+                            .with_label_values(&[&shard_id.to_string()])
+                            .start_timer();
+                        match runtime_adapter.apply_transactions_with_optional_storage_proof(
                             shard_id,
                             chunk_inner.prev_state_root(),
                             height,
@@ -3884,6 +3958,7 @@ impl Chain {
                             gas_limit,
                             &challenges_result,
                             random_seed,
+                            generate_storage_proof,
                             true,
                             is_first_block_with_chunk_of_version,
                             state_patch,
@@ -4506,6 +4581,68 @@ impl Chain {
             .ok_or_else(|| Error::DBNotFoundErr(format!("EXECUTION OUTCOME: {}", id)))
     }
 
+    /// Re-applies the chunk in which receipt `receipt_id` was executed and returns traces of the
+    /// function calls the receipt made.  Receipts and transactions which precede it in the chunk
+    /// are applied as well, so the receipt observes exactly the state it saw originally.
+    pub fn trace_receipt(
+        &self,
+        receipt_id: &CryptoHash,
+        extended_gas_profile: bool,
+    ) -> Result<ReceiptExecutionTraceView, Error> {
+        let outcome = self.get_execution_outcome(receipt_id)?;
+        let block = self.get_block(&outcome.block_hash)?;
+        let prev_block = self.get_block(block.header().prev_hash())?;
+        let shard_id = self.runtime_adapter.account_id_to_shard_id(
+            &outcome.outcome_with_id.outcome.executor_id,
+            block.header().epoch_id(),
+        )?;
+        let chunk_header = &block.chunks()[shard_id as usize];
+        if chunk_header.height_included() != block.header().height() {
+            return Err(Error::Other(format!(
+                "receipt {} is not executed in a new chunk of block {}",
+                receipt_id,
+                block.hash()
+            )));
+        }
+        let chunk = self.get_chunk(&chunk_header.chunk_hash())?;
+        let prev_chunk_height_included = prev_block.chunks()[shard_id as usize].height_included();
+        let receipts =
+            collect_receipts_from_response(&self.store.get_incoming_receipts_for_shard(
+                shard_id,
+                *block.hash(),
+                prev_chunk_height_included,
+            )?);
+        let is_first_block_with_chunk_of_version = check_if_block_is_first_with_chunk_of_version(
+            &self.store,
+            self.runtime_adapter.as_ref(),
+            prev_block.hash(),
+            shard_id,
+        )?;
+        let function_calls = self.runtime_adapter.trace_receipt(
+            receipt_id,
+            extended_gas_profile,
+            shard_id,
+            &chunk_header.prev_state_root(),
+            block.header().height(),
+            block.header().raw_timestamp(),
+            prev_block.hash(),
+            block.hash(),
+            &receipts,
+            chunk.transactions(),
+            chunk_header.validator_proposals(),
+            prev_block.header().gas_price(),
+            chunk_header.gas_limit(),
+            block.header().challenges_result(),
+            *block.header().random_value(),
+            is_first_block_with_chunk_of_version,
+        )?;
+        Ok(ReceiptExecutionTraceView {
+            receipt_id: *receipt_id,
+            block_hash: *block.hash(),
+            function_calls,
+        })
+    }
+
     /// Retrieve the up to `max_headers_returned` headers on the main chain
     /// `hashes`: a list of block "locators". `hashes` should be ordered from older blocks to
     ///           more recent blocks. This function will find the first block in `hashes`
@@ -4982,6 +5119,13 @@ impl<'a> ChainUpdate<'a> {
                     apply_result.outcomes,
                     outcome_paths,
                 );
+                if let Some(proof) = apply_result.proof {
+                    self.chain_store_update.save_state_transition_proof(
+                        block_hash,
+                        shard_id,
+                        proof.nodes,
+                    );
+                }
                 if let Some(apply_results_or_state_changes) = apply_split_result_or_state_changes {
                     self.process_split_state(
                         &block_hash,
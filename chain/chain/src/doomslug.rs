@@ -2,14 +2,19 @@ use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use near_client_primitives::debug::{ApprovalAtHeightStatus, ApprovalHistoryEntry};
+use near_client_primitives::debug::{
+    ApprovalAtHeightStatus, ApprovalHistoryEntry, DoomslugApprovalView, DoomslugApprovalsView,
+    DoomslugReadiness, DoomslugReadinessCheck, DoomslugTimerView,
+};
 use near_crypto::Signature;
 use near_primitives::block::{Approval, ApprovalInner};
 use near_primitives::hash::CryptoHash;
 use near_primitives::time::Clock;
-use near_primitives::types::{AccountId, ApprovalStake, Balance, BlockHeight, BlockHeightDelta};
+use near_primitives::types::{
+    AccountId, ApprovalStake, Balance, BlockHeight, BlockHeightDelta, EpochId,
+};
 use near_primitives::validator_signer::ValidatorSigner;
-use tracing::info;
+use tracing::{info, warn};
 
 /// Have that many iterations in the timer instead of `loop` to prevent potential bugs from blocking
 /// the node
@@ -59,6 +64,8 @@ struct DoomslugTimer {
 struct DoomslugTip {
     block_hash: CryptoHash,
     height: BlockHeight,
+    /// Epoch of the block built on top of the tip, which the approvals are signed for.
+    next_block_epoch_id: EpochId,
 }
 
 struct DoomslugApprovalsTracker {
@@ -118,6 +125,9 @@ pub struct Doomslug {
     /// Approvals that were created by this doomslug instance (for debugging only).
     /// Keeps up to MAX_HISTORY_SIZE entries.
     history: VecDeque<ApprovalHistoryEntry>,
+    /// Result of the last `ready_to_produce_block` call per target height (for debugging only).
+    /// Kept for the same heights as `approval_tracking`.
+    readiness_checks: HashMap<BlockHeight, DoomslugReadinessCheck>,
 }
 
 impl DoomslugTimer {
@@ -232,6 +242,65 @@ impl DoomslugApprovalsTracker {
             .map(|(key, (_, arrival_time))| (key.clone(), arrival_time.clone()))
             .collect::<Vec<_>>()
     }
+
+    /// Returns the approvals ordered by arrival time together with the stake they accumulated,
+    /// and the block producers that haven't approved (for debugging only).
+    fn debug_view(&self, inner: &ApprovalInner) -> DoomslugApprovalsView {
+        let mut witnesses = self.get_witnesses();
+        witnesses.sort_by_key(|(_, arrival_time)| *arrival_time);
+        let mut approved_stake = 0;
+        let approvals = witnesses
+            .into_iter()
+            .map(|(account_id, arrival_time)| {
+                let stake = self.account_id_to_stakes.get(&account_id).map_or(0, |x| x.0);
+                approved_stake += stake;
+                DoomslugApprovalView {
+                    account_id,
+                    arrival_time,
+                    stake_fraction: stake_fraction(stake, self.total_stake_this_epoch),
+                    approved_stake_fraction: stake_fraction(
+                        approved_stake,
+                        self.total_stake_this_epoch,
+                    ),
+                }
+            })
+            .collect();
+        let mut missing = self
+            .account_id_to_stakes
+            .keys()
+            .filter(|account_id| !self.witness.contains_key(*account_id))
+            .cloned()
+            .collect::<Vec<_>>();
+        missing.sort();
+        DoomslugApprovalsView {
+            inner: inner.clone(),
+            approvals,
+            missing,
+            approved_stake_this_epoch_fraction: stake_fraction(
+                self.approved_stake_this_epoch,
+                self.total_stake_this_epoch,
+            ),
+            approved_stake_next_epoch_fraction: stake_fraction(
+                self.approved_stake_next_epoch,
+                self.total_stake_next_epoch,
+            ),
+            ready_at: self.time_passed_threshold.map(instant_to_utc),
+        }
+    }
+}
+
+fn stake_fraction(stake: Balance, total_stake: Balance) -> f64 {
+    if total_stake == 0 {
+        0.0
+    } else {
+        stake as f64 / total_stake as f64
+    }
+}
+
+/// Converts a past `Instant` to the wall clock time (for debugging only).
+fn instant_to_utc(instant: Instant) -> chrono::DateTime<chrono::Utc> {
+    chrono::Utc::now()
+        - chrono::Duration::from_std(instant.elapsed()).unwrap_or(chrono::Duration::days(1))
 }
 
 impl DoomslugApprovalsTrackersAtHeight {
@@ -319,12 +388,23 @@ impl DoomslugApprovalsTrackersAtHeight {
             .iter()
             .filter_map(|(_, tracker)| tracker.time_passed_threshold)
             .min()
-            .map(|ts| {
-                chrono::Utc::now()
-                    - chrono::Duration::from_std(ts.elapsed()).unwrap_or(chrono::Duration::days(1))
-            });
+            .map(instant_to_utc);
         ApprovalAtHeightStatus { approvals, ready_at: threshold_approval }
     }
+
+    /// Returns the approvals for every tracked approval inner, the ones with most approved stake
+    /// first (for debugging only).
+    fn debug_view(&self) -> Vec<DoomslugApprovalsView> {
+        let mut views = self
+            .approval_trackers
+            .iter()
+            .map(|(inner, tracker)| tracker.debug_view(inner))
+            .collect::<Vec<_>>();
+        views.sort_by(|a, b| {
+            b.approved_stake_this_epoch_fraction.total_cmp(&a.approved_stake_this_epoch_fraction)
+        });
+        views
+    }
 }
 
 impl Doomslug {
@@ -343,7 +423,11 @@ impl Doomslug {
             largest_approval_height: 0,
             largest_final_height: 0,
             largest_threshold_height: 0,
-            tip: DoomslugTip { block_hash: CryptoHash::default(), height: 0 },
+            tip: DoomslugTip {
+                block_hash: CryptoHash::default(),
+                height: 0,
+                next_block_epoch_id: EpochId::default(),
+            },
             endorsement_pending: false,
             timer: DoomslugTimer {
                 started: Clock::instant(),
@@ -357,6 +441,7 @@ impl Doomslug {
             signer,
             threshold_mode,
             history: VecDeque::new(),
+            readiness_checks: HashMap::new(),
         }
     }
 
@@ -403,6 +488,42 @@ impl Doomslug {
         self.history.iter().cloned().collect::<Vec<_>>()
     }
 
+    /// Returns the state of the timers and the largest heights doomslug has seen (for debugging only).
+    pub fn get_timer_status(&self, now: Instant) -> DoomslugTimerView {
+        let skip_delay =
+            self.timer.get_delay(self.timer.height.saturating_sub(self.largest_final_height));
+        DoomslugTimerView {
+            tip_hash: self.tip.block_hash,
+            tip_height: self.tip.height,
+            endorsement_pending: self.endorsement_pending,
+            endorsement_delay_millis: self.timer.endorsement_delay.as_millis() as u64,
+            timer_height: self.timer.height,
+            timer_started_ago_millis: now.saturating_duration_since(self.timer.started).as_millis()
+                as u64,
+            skip_delay_millis: skip_delay.as_millis() as u64,
+            largest_target_height: self.largest_target_height,
+            largest_final_height: self.largest_final_height,
+            largest_threshold_height: self.largest_threshold_height,
+            largest_approval_height: self.largest_approval_height,
+        }
+    }
+
+    /// Returns the approvals received for a given target height, ordered by arrival time
+    /// (for debugging only). Same as `approval_status_at_height`, it only works for the heights
+    /// that we have in memory.
+    pub fn approvals_debug_view_at_height(
+        &self,
+        height: BlockHeight,
+    ) -> Vec<DoomslugApprovalsView> {
+        self.approval_tracking.get(&height).map(|it| it.debug_view()).unwrap_or_default()
+    }
+
+    /// Returns the result of the last check whether a block can be produced at a given height
+    /// (for debugging only).
+    pub fn readiness_check_at_height(&self, height: BlockHeight) -> Option<DoomslugReadinessCheck> {
+        self.readiness_checks.get(&height).cloned()
+    }
+
     /// Adds new approval to the history.
     fn update_history(&mut self, entry: ApprovalHistoryEntry) {
         while self.history.len() >= MAX_HISTORY_SIZE {
@@ -495,9 +616,20 @@ impl Doomslug {
     }
 
     fn create_approval(&self, target_height: BlockHeight) -> Option<Approval> {
-        self.signer.as_ref().map(|signer| {
-            Approval::new(self.tip.block_hash, self.tip.height, target_height, &**signer)
-        })
+        let signer = self.signer.as_ref()?;
+        match Approval::new(
+            self.tip.block_hash,
+            self.tip.height,
+            target_height,
+            &self.tip.next_block_epoch_id,
+            &**signer,
+        ) {
+            Ok(approval) => Some(approval),
+            Err(err) => {
+                warn!(target: "doomslug", target_height, ?err, "Failed to sign approval");
+                None
+            }
+        }
     }
 
     /// Determines whether a block has enough approvals to be produced.
@@ -565,24 +697,28 @@ impl Doomslug {
     /// * `block_hash`     - the hash of the new tip
     /// * `height`         - the height of the tip
     /// * `last_ds_final_height` - last height at which a block in this chain has doomslug finality
+    /// * `next_block_epoch_id` - the epoch of the next block built on top of the tip
     pub fn set_tip(
         &mut self,
         now: Instant,
         block_hash: CryptoHash,
         height: BlockHeight,
         last_final_height: BlockHeight,
+        next_block_epoch_id: EpochId,
     ) {
         debug_assert!(height > self.tip.height || self.tip.height == 0);
-        self.tip = DoomslugTip { block_hash, height };
+        self.tip = DoomslugTip { block_hash, height, next_block_epoch_id };
 
         self.largest_final_height = last_final_height;
         self.timer.height = height + 1;
         self.timer.started = now;
 
-        self.approval_tracking.retain(|h, _| {
+        let is_height_retained = |h: &BlockHeight| {
             *h > height.saturating_sub(MAX_HEIGHTS_BEFORE_TO_STORE_APPROVALS)
                 && *h <= height + MAX_HEIGHTS_AHEAD_TO_STORE_APPROVALS
-        });
+        };
+        self.approval_tracking.retain(|h, _| is_height_retained(h));
+        self.readiness_checks.retain(|h, _| is_height_retained(h));
 
         self.endorsement_pending = true;
     }
@@ -663,44 +799,77 @@ impl Doomslug {
         has_enough_chunks: bool,
         log_block_production_info: bool,
     ) -> bool {
+        let readiness = self.get_block_production_readiness(
+            now,
+            target_height,
+            has_enough_chunks,
+            log_block_production_info,
+        );
+        let ready = matches!(readiness, DoomslugReadiness::Ready { .. });
+        self.readiness_checks.insert(
+            target_height,
+            DoomslugReadinessCheck {
+                parent_height: self.tip.height,
+                check_time: chrono::Utc::now(),
+                readiness,
+            },
+        );
+        ready
+    }
+
+    /// Same as `ready_to_produce_block`, but returns why the block can or can't be produced.
+    fn get_block_production_readiness(
+        &mut self,
+        now: Instant,
+        target_height: BlockHeight,
+        has_enough_chunks: bool,
+        log_block_production_info: bool,
+    ) -> DoomslugReadiness {
         let hash_or_height =
             ApprovalInner::new(&self.tip.block_hash, self.tip.height, target_height);
-        if let Some(approval_trackers_at_height) = self.approval_tracking.get_mut(&target_height) {
-            if let Some(approval_tracker) =
-                approval_trackers_at_height.approval_trackers.get_mut(&hash_or_height)
-            {
-                let block_production_readiness =
-                    approval_tracker.get_block_production_readiness(now);
-                match block_production_readiness {
-                    DoomslugBlockProductionReadiness::NotReady => false,
-                    DoomslugBlockProductionReadiness::ReadySince(when) => {
-                        if has_enough_chunks {
-                            if log_block_production_info {
-                                info!("ready to produce block @ {}, has enough approvals for {:?}, has enough chunks", target_height, now.saturating_duration_since(when));
-                            }
-                            true
-                        } else {
-                            let delay = self.timer.get_delay(
-                                self.timer.height.saturating_sub(self.largest_final_height),
-                            ) / 6;
-
-                            let ready = now > when + delay;
-                            if log_block_production_info {
-                                if ready {
-                                    info!("ready to produce block @ {}, has enough approvals for {:?}, does not have enough chunks", target_height, now.saturating_duration_since(when));
-                                } else {
-                                    info!("not ready to produce block @ {}, need to wait {:?}, has enough approvals for {:?}", target_height, (when + delay).saturating_duration_since(now), now.saturating_duration_since(when));
-                                }
-                            }
-                            ready
-                        }
-                    }
-                }
+        let approval_tracker = match self
+            .approval_tracking
+            .get_mut(&target_height)
+            .and_then(|it| it.approval_trackers.get_mut(&hash_or_height))
+        {
+            Some(approval_tracker) => approval_tracker,
+            None => return DoomslugReadiness::NotEnoughApprovals,
+        };
+        let when = match approval_tracker.get_block_production_readiness(now) {
+            DoomslugBlockProductionReadiness::NotReady => {
+                return DoomslugReadiness::NotEnoughApprovals
+            }
+            DoomslugBlockProductionReadiness::ReadySince(when) => when,
+        };
+        let approvals_ready_for_millis = now.saturating_duration_since(when).as_millis() as u64;
+        if has_enough_chunks {
+            if log_block_production_info {
+                info!(
+                    "ready to produce block @ {}, has enough approvals for {:?}, has enough chunks",
+                    target_height,
+                    now.saturating_duration_since(when)
+                );
+            }
+            return DoomslugReadiness::Ready { has_enough_chunks, approvals_ready_for_millis };
+        }
+        let delay =
+            self.timer.get_delay(self.timer.height.saturating_sub(self.largest_final_height)) / 6;
+
+        let ready = now > when + delay;
+        if log_block_production_info {
+            if ready {
+                info!("ready to produce block @ {}, has enough approvals for {:?}, does not have enough chunks", target_height, now.saturating_duration_since(when));
             } else {
-                false
+                info!("not ready to produce block @ {}, need to wait {:?}, has enough approvals for {:?}", target_height, (when + delay).saturating_duration_since(now), now.saturating_duration_since(when));
             }
+        }
+        if ready {
+            DoomslugReadiness::Ready { has_enough_chunks, approvals_ready_for_millis }
         } else {
-            false
+            DoomslugReadiness::WaitingForChunks {
+                wait_remaining_millis: (when + delay).saturating_duration_since(now).as_millis()
+                    as u64,
+            }
         }
     }
 }
@@ -710,12 +879,13 @@ mod tests {
     use std::sync::Arc;
     use std::time::Duration;
 
+    use near_client_primitives::debug::DoomslugReadiness;
     use near_crypto::{KeyType, SecretKey};
     use near_primitives::block::{Approval, ApprovalInner};
     use near_primitives::hash::hash;
     use near_primitives::test_utils::create_test_signer;
     use near_primitives::time::Clock;
-    use near_primitives::types::ApprovalStake;
+    use near_primitives::types::{ApprovalStake, EpochId};
 
     use crate::doomslug::{
         DoomslugApprovalsTrackersAtHeight, DoomslugBlockProductionReadiness, DoomslugThresholdMode,
@@ -737,7 +907,7 @@ mod tests {
         let mut now = Clock::instant(); // For the test purposes the absolute value of the initial instant doesn't matter
 
         // Set a new tip, must produce an endorsement
-        ds.set_tip(now, hash(&[1]), 1, 1);
+        ds.set_tip(now, hash(&[1]), 1, 1, EpochId::default());
         assert_eq!(ds.process_timer(now + Duration::from_millis(399)).len(), 0);
         let approval =
             ds.process_timer(now + Duration::from_millis(400)).into_iter().nth(0).unwrap();
@@ -763,14 +933,14 @@ mod tests {
         now += Duration::from_millis(1000);
 
         // Not processing a block at height 2 should not produce an appoval
-        ds.set_tip(now, hash(&[2]), 2, 0);
+        ds.set_tip(now, hash(&[2]), 2, 0, EpochId::default());
         assert_eq!(ds.process_timer(now + Duration::from_millis(400)), vec![]);
 
         // Shift now 1 second forward
         now += Duration::from_millis(1000);
 
         // But at height 3 should (also neither block has finality set, keep last final at 0 for now)
-        ds.set_tip(now, hash(&[3]), 3, 0);
+        ds.set_tip(now, hash(&[3]), 3, 0, EpochId::default());
         let approval =
             ds.process_timer(now + Duration::from_millis(400)).into_iter().nth(0).unwrap();
         assert_eq!(approval.inner, ApprovalInner::Endorsement(hash(&[3])));
@@ -822,7 +992,7 @@ mod tests {
         now += Duration::from_millis(1000);
 
         // Accept block at 5 with finality on the prev block, expect it to not produce an approval
-        ds.set_tip(now, hash(&[5]), 5, 4);
+        ds.set_tip(now, hash(&[5]), 5, 4, EpochId::default());
         assert_eq!(ds.process_timer(now + Duration::from_millis(400)), vec![]);
 
         // Skip a whole bunch of heights by moving 100 seconds ahead
@@ -834,7 +1004,7 @@ mod tests {
         now += Duration::from_millis(17);
 
         // No approval, since we skipped 6
-        ds.set_tip(now, hash(&[6]), 6, 4);
+        ds.set_tip(now, hash(&[6]), 6, 4, EpochId::default());
         assert_eq!(ds.process_timer(now + Duration::from_millis(400)), vec![]);
 
         // The block height was less than the timer height, and thus the timer was reset.
@@ -890,7 +1060,7 @@ mod tests {
         assert_eq!(
             ds.on_approval_message_internal(
                 now,
-                &Approval::new(hash(&[1]), 1, 2, &signers[0]),
+                &Approval::new(hash(&[1]), 1, 2, &EpochId::default(), &signers[0]).unwrap(),
                 &stakes,
             ),
             DoomslugBlockProductionReadiness::NotReady,
@@ -900,7 +1070,7 @@ mod tests {
         assert_eq!(
             ds.on_approval_message_internal(
                 now,
-                &Approval::new(hash(&[1]), 1, 4, &signers[2]),
+                &Approval::new(hash(&[1]), 1, 4, &EpochId::default(), &signers[2]).unwrap(),
                 &stakes,
             ),
             DoomslugBlockProductionReadiness::NotReady,
@@ -910,7 +1080,7 @@ mod tests {
         assert_eq!(
             ds.on_approval_message_internal(
                 now,
-                &Approval::new(hash(&[1]), 1, 4, &signers[3]),
+                &Approval::new(hash(&[1]), 1, 4, &EpochId::default(), &signers[3]).unwrap(),
                 &stakes,
             ),
             DoomslugBlockProductionReadiness::NotReady,
@@ -920,7 +1090,7 @@ mod tests {
         assert_eq!(
             ds.on_approval_message_internal(
                 now + Duration::from_millis(100),
-                &Approval::new(hash(&[1]), 1, 4, &signers[3]),
+                &Approval::new(hash(&[1]), 1, 4, &EpochId::default(), &signers[3]).unwrap(),
                 &stakes,
             ),
             DoomslugBlockProductionReadiness::NotReady,
@@ -930,7 +1100,7 @@ mod tests {
         assert_eq!(
             ds.on_approval_message_internal(
                 now,
-                &Approval::new(hash(&[1]), 1, 4, &signers[1]),
+                &Approval::new(hash(&[1]), 1, 4, &EpochId::default(), &signers[1]).unwrap(),
                 &stakes,
             ),
             DoomslugBlockProductionReadiness::ReadySince(now),
@@ -940,7 +1110,7 @@ mod tests {
         assert_eq!(
             ds.on_approval_message_internal(
                 now,
-                &Approval::new(hash(&[1]), 1, 4, &signers[0]),
+                &Approval::new(hash(&[1]), 1, 4, &EpochId::default(), &signers[0]).unwrap(),
                 &stakes,
             ),
             DoomslugBlockProductionReadiness::ReadySince(now),
@@ -950,7 +1120,7 @@ mod tests {
         assert_eq!(
             ds.on_approval_message_internal(
                 now,
-                &Approval::new(hash(&[1]), 1, 2, &signers[3]),
+                &Approval::new(hash(&[1]), 1, 2, &EpochId::default(), &signers[3]).unwrap(),
                 &stakes,
             ),
             DoomslugBlockProductionReadiness::NotReady,
@@ -962,7 +1132,7 @@ mod tests {
         assert_eq!(
             ds.on_approval_message_internal(
                 now,
-                &Approval::new(hash(&[1]), 1, 2, &signers[2]),
+                &Approval::new(hash(&[1]), 1, 2, &EpochId::default(), &signers[2]).unwrap(),
                 &stakes,
             ),
             DoomslugBlockProductionReadiness::ReadySince(now),
@@ -972,7 +1142,7 @@ mod tests {
         assert_eq!(
             ds.on_approval_message_internal(
                 now,
-                &Approval::new(hash(&[2]), 2, 4, &signers[1]),
+                &Approval::new(hash(&[2]), 2, 4, &EpochId::default(), &signers[1]).unwrap(),
                 &stakes,
             ),
             DoomslugBlockProductionReadiness::NotReady,
@@ -998,13 +1168,13 @@ mod tests {
             .collect::<Vec<_>>();
         let mut tracker = DoomslugApprovalsTrackersAtHeight::new();
 
-        let a1_1 = Approval::new(hash(&[1]), 1, 4, &signers[0]);
-        let a1_2 = Approval::new(hash(&[1]), 1, 4, &signers[1]);
-        let a1_3 = Approval::new(hash(&[1]), 1, 4, &signers[2]);
+        let a1_1 = Approval::new(hash(&[1]), 1, 4, &EpochId::default(), &signers[0]).unwrap();
+        let a1_2 = Approval::new(hash(&[1]), 1, 4, &EpochId::default(), &signers[1]).unwrap();
+        let a1_3 = Approval::new(hash(&[1]), 1, 4, &EpochId::default(), &signers[2]).unwrap();
 
-        let a2_1 = Approval::new(hash(&[3]), 3, 4, &signers[0]);
-        let a2_2 = Approval::new(hash(&[3]), 3, 4, &signers[1]);
-        let a2_3 = Approval::new(hash(&[3]), 3, 4, &signers[2]);
+        let a2_1 = Approval::new(hash(&[3]), 3, 4, &EpochId::default(), &signers[0]).unwrap();
+        let a2_2 = Approval::new(hash(&[3]), 3, 4, &EpochId::default(), &signers[1]).unwrap();
+        let a2_3 = Approval::new(hash(&[3]), 3, 4, &EpochId::default(), &signers[2]).unwrap();
 
         // Process first approval, and then process it again and make sure it works
         tracker.process_approval(
@@ -1196,4 +1366,93 @@ mod tests {
             5
         );
     }
+
+    #[test]
+    fn test_doomslug_debug_view() {
+        let accounts: Vec<(&str, u128, u128)> =
+            vec![("test1", 2, 0), ("test2", 1, 0), ("test3", 3, 0), ("test4", 1, 0)];
+        let stakes = accounts
+            .iter()
+            .map(|(account_id, stake_this_epoch, stake_next_epoch)| ApprovalStake {
+                account_id: account_id.parse().unwrap(),
+                stake_this_epoch: *stake_this_epoch,
+                stake_next_epoch: *stake_next_epoch,
+                public_key: SecretKey::from_seed(KeyType::ED25519, account_id).public_key(),
+            })
+            .map(|stake| (stake, false))
+            .collect::<Vec<_>>();
+        let signers = accounts
+            .iter()
+            .map(|(account_id, _, _)| create_test_signer(account_id))
+            .collect::<Vec<_>>();
+
+        let signer = Arc::new(create_test_signer("test"));
+        let mut ds = Doomslug::new(
+            0,
+            Duration::from_millis(400),
+            Duration::from_millis(1000),
+            Duration::from_millis(100),
+            Duration::from_millis(3000),
+            Some(signer),
+            DoomslugThresholdMode::TwoThirds,
+        );
+
+        let now = Clock::instant();
+        ds.set_tip(now, hash(&[1]), 1, 0, EpochId::default());
+        assert!(ds.readiness_check_at_height(2).is_none());
+
+        // "test3" endorses the tip, 3 out of 7 is not enough to produce the block
+        let _ = ds.on_approval_message_internal(
+            now,
+            &Approval::new(hash(&[1]), 1, 2, &EpochId::default(), &signers[2]).unwrap(),
+            &stakes,
+        );
+        assert!(!ds.ready_to_produce_block(now, 2, true, false));
+        assert_eq!(
+            ds.readiness_check_at_height(2).unwrap().readiness,
+            DoomslugReadiness::NotEnoughApprovals
+        );
+
+        // Make sure the arrival times differ
+        std::thread::sleep(Duration::from_millis(1));
+
+        // "test1" endorses the tip, 5 out of 7 crosses the threshold
+        let _ = ds.on_approval_message_internal(
+            now,
+            &Approval::new(hash(&[1]), 1, 2, &EpochId::default(), &signers[0]).unwrap(),
+            &stakes,
+        );
+        assert!(ds.ready_to_produce_block(now, 2, true, false));
+        let check = ds.readiness_check_at_height(2).unwrap();
+        assert_eq!(check.parent_height, 1);
+        assert_eq!(
+            check.readiness,
+            DoomslugReadiness::Ready { has_enough_chunks: true, approvals_ready_for_millis: 0 }
+        );
+
+        let views = ds.approvals_debug_view_at_height(2);
+        assert_eq!(views.len(), 1);
+        let view = &views[0];
+        assert_eq!(view.inner, ApprovalInner::Endorsement(hash(&[1])));
+        assert_eq!(
+            view.approvals
+                .iter()
+                .map(|approval| (
+                    approval.account_id.as_str(),
+                    approval.stake_fraction,
+                    approval.approved_stake_fraction
+                ))
+                .collect::<Vec<_>>(),
+            vec![("test3", 3.0 / 7.0, 3.0 / 7.0), ("test1", 2.0 / 7.0, 5.0 / 7.0)]
+        );
+        assert_eq!(
+            view.missing.iter().map(|account_id| account_id.as_str()).collect::<Vec<_>>(),
+            vec!["test2", "test4"]
+        );
+        assert_eq!(view.approved_stake_this_epoch_fraction, 5.0 / 7.0);
+        assert!(view.ready_at.is_some());
+
+        assert!(ds.approvals_debug_view_at_height(3).is_empty());
+        assert_eq!(ds.get_timer_status(now).timer_height, 2);
+    }
 }
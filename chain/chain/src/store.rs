@@ -8,6 +8,7 @@ use near_primitives::time::Utc;
 
 use near_chain_primitives::error::Error;
 use near_primitives::block::Tip;
+use near_primitives::challenge::PartialState;
 use near_primitives::errors::InvalidTxError;
 use near_primitives::hash::CryptoHash;
 use near_primitives::merkle::{MerklePath, PartialMerkleTree};
@@ -448,6 +449,20 @@ impl ChainStore {
         )
     }
 
+    /// Get the trie nodes recorded while applying the chunk of shard `shard_id` in block
+    /// `block_hash`. Only present if the node runs with `record_state_transition_proofs`.
+    pub fn get_state_transition_proof(
+        &self,
+        block_hash: &CryptoHash,
+        shard_id: ShardId,
+    ) -> Result<PartialState, Error> {
+        let key = &get_block_shard_id(block_hash, shard_id);
+        option_to_not_found(
+            self.store.get_ser(DBCol::StateTransitionProofs, key),
+            format_args!("STATE TRANSITION PROOF: {}:{}", block_hash, shard_id),
+        )
+    }
+
     /// Get outgoing receipts that will be *sent* from shard `shard_id` from block whose prev block
     /// is `prev_block_hash`
     /// Note that the meaning of outgoing receipts here are slightly different from
@@ -1191,6 +1206,8 @@ pub struct ChainStoreUpdate<'a> {
     // All state changes made by a chunk, this is only used for splitting states
     add_state_changes_for_split_states: HashMap<(CryptoHash, ShardId), StateChangesForSplitStates>,
     remove_state_changes_for_split_states: HashSet<(CryptoHash, ShardId)>,
+    // Trie nodes recorded while applying a chunk, used to re-validate the state transition
+    add_state_transition_proofs: HashMap<(CryptoHash, ShardId), PartialState>,
     add_blocks_to_catchup: Vec<(CryptoHash, CryptoHash)>,
     // A pair (prev_hash, hash) to be removed from blocks to catchup
     remove_blocks_to_catchup: Vec<(CryptoHash, CryptoHash)>,
@@ -1217,6 +1234,7 @@ impl<'a> ChainStoreUpdate<'a> {
             trie_changes: vec![],
             add_state_changes_for_split_states: HashMap::new(),
             remove_state_changes_for_split_states: HashSet::new(),
+            add_state_transition_proofs: HashMap::new(),
             add_blocks_to_catchup: vec![],
             remove_blocks_to_catchup: vec![],
             remove_prev_blocks_to_catchup: vec![],
@@ -1841,6 +1859,15 @@ impl<'a> ChainStoreUpdate<'a> {
         assert!(prev.is_none());
     }
 
+    pub fn save_state_transition_proof(
+        &mut self,
+        block_hash: CryptoHash,
+        shard_id: ShardId,
+        proof: PartialState,
+    ) {
+        self.add_state_transition_proofs.insert((block_hash, shard_id), proof);
+    }
+
     pub fn remove_state_changes_for_split_states(
         &mut self,
         block_hash: CryptoHash,
@@ -2113,6 +2140,7 @@ impl<'a> ChainStoreUpdate<'a> {
             let block_shard_id = get_block_shard_id(&block_hash, shard_id);
             self.gc_outgoing_receipts(&block_hash, shard_id);
             self.gc_col(DBCol::IncomingReceipts, &block_shard_id);
+            self.gc_col(DBCol::StateTransitionProofs, &block_shard_id);
 
             // For incoming State Parts it's done in chain.clear_downloaded_parts()
             // The following code is mostly for outgoing State Parts.
@@ -2389,6 +2417,9 @@ impl<'a> ChainStoreUpdate<'a> {
             DBCol::HeaderHashesByHeight => {
                 store_update.delete(col, key);
             }
+            DBCol::StateTransitionProofs => {
+                store_update.delete(col, key);
+            }
             DBCol::DbVersion
             | DBCol::BlockMisc
             | DBCol::_GCCount
@@ -2780,6 +2811,13 @@ impl<'a> ChainStoreUpdate<'a> {
                 &get_block_shard_id(&block_hash, shard_id),
             );
         }
+        for ((block_hash, shard_id), proof) in self.add_state_transition_proofs.drain() {
+            store_update.set_ser(
+                DBCol::StateTransitionProofs,
+                &get_block_shard_id(&block_hash, shard_id),
+                &proof,
+            )?;
+        }
 
         let mut affected_catchup_blocks = HashSet::new();
         for (prev_hash, hash) in self.remove_blocks_to_catchup.drain(..) {
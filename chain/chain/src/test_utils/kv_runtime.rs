@@ -16,12 +16,12 @@ use near_crypto::{KeyType, PublicKey, SecretKey, Signature};
 use near_pool::types::PoolIterator;
 use near_primitives::account::{AccessKey, Account};
 use near_primitives::block_header::{Approval, ApprovalInner};
-use near_primitives::challenge::ChallengesResult;
+use near_primitives::challenge::{ChallengesResult, PartialState};
 use near_primitives::epoch_manager::block_info::BlockInfo;
 use near_primitives::epoch_manager::epoch_info::EpochInfo;
 use near_primitives::epoch_manager::EpochConfig;
 use near_primitives::epoch_manager::ValidatorSelectionConfig;
-use near_primitives::errors::{EpochError, InvalidTxError};
+use near_primitives::errors::{EpochError, InvalidTxError, StorageError};
 use near_primitives::hash::{hash, CryptoHash};
 use near_primitives::receipt::{ActionReceipt, Receipt, ReceiptEnum};
 use near_primitives::shard_layout;
@@ -38,8 +38,9 @@ use near_primitives::types::{
 };
 use near_primitives::version::{ProtocolVersion, PROTOCOL_VERSION};
 use near_primitives::views::{
-    AccessKeyInfoView, AccessKeyList, CallResult, ContractCodeView, EpochValidatorInfo,
-    QueryRequest, QueryResponse, QueryResponseKind, ViewStateResult,
+    AccessKeyInfoView, AccessKeyList, CallResult, ContractCodeView, EpochRewardsView,
+    EpochValidatorInfo, FunctionCallTraceView, QueryRequest, QueryResponse, QueryResponseKind,
+    ValidatorKickoutPredictionView, ViewStateResult,
 };
 use near_store::{
     DBCol, PartialStorage, ShardTries, Store, StoreUpdate, Trie, TrieChanges, WrappedTrieChanges,
@@ -652,6 +653,31 @@ impl EpochManagerAdapter for KeyValueRuntime {
         })
     }
 
+    fn get_validator_kickout_prediction(
+        &self,
+        last_block_hash: &CryptoHash,
+    ) -> Result<ValidatorKickoutPredictionView, Error> {
+        let (epoch_id, _, _) = self.get_epoch_and_valset(*last_block_hash)?;
+        Ok(ValidatorKickoutPredictionView {
+            epoch_id: epoch_id.0,
+            epoch_height: 1,
+            epoch_start_height: 0,
+            height: 0,
+            estimated_epoch_end_height: 0,
+            block_producer_kickout_threshold: 0,
+            chunk_producer_kickout_threshold: 0,
+            validator_max_kickout_stake_perc: 0,
+            validators: vec![],
+            next_epoch_seat_price: 0,
+            estimated_seat_price: None,
+        })
+    }
+
+    fn get_epoch_rewards(&self, last_block_hash: &CryptoHash) -> Result<EpochRewardsView, Error> {
+        // KeyValueRuntime keeps no block info to recompute rewards from.
+        Err(EpochError::MissingBlock(*last_block_hash).into())
+    }
+
     fn get_epoch_minted_amount(&self, _epoch_id: &EpochId) -> Result<Balance, Error> {
         Ok(0)
     }
@@ -973,10 +999,15 @@ impl RuntimeAdapter for KeyValueRuntime {
         _state_patch: SandboxStatePatch,
         _use_flat_storage: bool,
     ) -> Result<ApplyTransactionResult, Error> {
-        assert!(!generate_storage_proof);
         let mut tx_results = vec![];
 
         let mut state = self.state.read().unwrap().get(state_root).cloned().unwrap();
+        // The whole state of the shard is a single value, so it's the entire proof.
+        let proof = if generate_storage_proof {
+            Some(PartialStorage { nodes: PartialState(vec![state.try_to_vec()?.into()]) })
+        } else {
+            None
+        };
 
         let mut balance_transfers = vec![];
 
@@ -1124,31 +1155,66 @@ impl RuntimeAdapter for KeyValueRuntime {
             validator_proposals: vec![],
             total_gas_burnt: 0,
             total_balance_burnt: 0,
-            proof: None,
+            proof,
             processed_delayed_receipts: vec![],
         })
     }
 
     fn check_state_transition(
         &self,
-        _partial_storage: PartialStorage,
-        _shard_id: ShardId,
-        _state_root: &StateRoot,
-        _height: BlockHeight,
-        _block_timestamp: u64,
-        _prev_block_hash: &CryptoHash,
-        _block_hash: &CryptoHash,
-        _receipts: &[Receipt],
-        _transactions: &[SignedTransaction],
-        _last_validator_proposals: ValidatorStakeIter,
-        _gas_price: Balance,
-        _gas_limit: Gas,
-        _challenges: &ChallengesResult,
-        _random_value: CryptoHash,
-        _is_new_chunk: bool,
-        _is_first_block_with_chunk_of_version: bool,
+        partial_storage: PartialStorage,
+        shard_id: ShardId,
+        state_root: &StateRoot,
+        height: BlockHeight,
+        block_timestamp: u64,
+        prev_block_hash: &CryptoHash,
+        block_hash: &CryptoHash,
+        receipts: &[Receipt],
+        transactions: &[SignedTransaction],
+        last_validator_proposals: ValidatorStakeIter,
+        gas_price: Balance,
+        gas_limit: Gas,
+        challenges: &ChallengesResult,
+        random_value: CryptoHash,
+        is_new_chunk: bool,
+        is_first_block_with_chunk_of_version: bool,
     ) -> Result<ApplyTransactionResult, Error> {
-        unimplemented!();
+        // Genesis state is keyed by `Trie::EMPTY_ROOT` rather than by its hash.
+        let expected_hash = match self.state.read().unwrap().get(&Trie::EMPTY_ROOT) {
+            Some(genesis_state) if *state_root == Trie::EMPTY_ROOT => {
+                hash(&genesis_state.try_to_vec()?)
+            }
+            _ => *state_root,
+        };
+        let data = partial_storage
+            .nodes
+            .0
+            .into_iter()
+            .find(|data| hash(data) == expected_hash)
+            .ok_or(Error::StorageError(StorageError::TrieNodeMissing))?;
+        let state = KVState::try_from_slice(&data)?;
+        self.state.write().unwrap().insert(*state_root, state);
+        self.state_size.write().unwrap().insert(*state_root, data.len() as u64);
+        self.apply_transactions_with_optional_storage_proof(
+            shard_id,
+            state_root,
+            height,
+            block_timestamp,
+            prev_block_hash,
+            block_hash,
+            receipts,
+            transactions,
+            last_validator_proposals,
+            gas_price,
+            gas_limit,
+            challenges,
+            random_value,
+            false,
+            is_new_chunk,
+            is_first_block_with_chunk_of_version,
+            Default::default(),
+            false,
+        )
     }
 
     fn query(
@@ -1206,6 +1272,7 @@ impl RuntimeAdapter for KeyValueRuntime {
                 kind: QueryResponseKind::ViewState(ViewStateResult {
                     values: Default::default(),
                     proof: vec![],
+                    next_key: None,
                 }),
                 block_height,
                 block_hash: *block_hash,
@@ -1221,6 +1288,66 @@ impl RuntimeAdapter for KeyValueRuntime {
         }
     }
 
+    fn trace_call(
+        &self,
+        _shard_uid: ShardUId,
+        _state_root: &StateRoot,
+        _block_height: BlockHeight,
+        _block_timestamp: u64,
+        _prev_block_hash: &CryptoHash,
+        _block_hash: &CryptoHash,
+        _epoch_id: &EpochId,
+        contract_id: &AccountId,
+        method_name: &str,
+        _args: &[u8],
+        _extended_gas_profile: bool,
+    ) -> Result<FunctionCallTraceView, near_chain_primitives::error::QueryError> {
+        Ok(FunctionCallTraceView {
+            contract_id: contract_id.clone(),
+            method_name: method_name.to_string(),
+            gas_burnt: 0,
+            logs: vec![],
+            error: None,
+            host_calls: vec![],
+            extended_gas_profile: None,
+        })
+    }
+
+    fn trace_receipt(
+        &self,
+        _receipt_id: &CryptoHash,
+        _extended_gas_profile: bool,
+        _shard_id: ShardId,
+        _state_root: &StateRoot,
+        _height: BlockHeight,
+        _block_timestamp: u64,
+        _prev_block_hash: &CryptoHash,
+        _block_hash: &CryptoHash,
+        _receipts: &[Receipt],
+        _transactions: &[SignedTransaction],
+        _last_validator_proposals: ValidatorStakeIter,
+        _gas_price: Balance,
+        _gas_limit: Gas,
+        _challenges_result: &ChallengesResult,
+        _random_seed: CryptoHash,
+        _is_first_block_with_chunk_of_version: bool,
+    ) -> Result<Vec<FunctionCallTraceView>, Error> {
+        Ok(vec![])
+    }
+
+    fn estimate_gas(
+        &self,
+        _transaction: &SignedTransaction,
+        _state_roots: &[StateRoot],
+        _block_height: BlockHeight,
+        _block_timestamp: u64,
+        _block_hash: &CryptoHash,
+        _gas_price: Balance,
+        _random_seed: CryptoHash,
+    ) -> Result<Vec<ExecutionOutcomeWithId>, Error> {
+        Ok(vec![])
+    }
+
     fn obtain_state_part(
         &self,
         _shard_id: ShardId,
@@ -9,7 +9,7 @@ use crate::{Doomslug, DoomslugThresholdMode};
 use near_crypto::{KeyType, SecretKey};
 use near_primitives::block::Approval;
 use near_primitives::hash::{hash, CryptoHash};
-use near_primitives::types::{ApprovalStake, BlockHeight};
+use near_primitives::types::{ApprovalStake, BlockHeight, EpochId};
 
 fn block_hash(height: BlockHeight, ord: usize) -> CryptoHash {
     hash(([height.to_le_bytes(), ord.to_le_bytes()].concat()).as_ref())
@@ -82,7 +82,7 @@ fn one_iter(
     chain_lengths.insert(block_hash(1, 0), 1);
 
     for ds in doomslugs.iter_mut() {
-        ds.set_tip(now, block_hash(1, 0), 1, 1);
+        ds.set_tip(now, block_hash(1, 0), 1, 1, EpochId::default());
         hash_to_block_info.insert(block_hash(1, 0), (1, 1, block_hash(1, 0)));
     }
 
@@ -146,6 +146,7 @@ fn one_iter(
                                 block_info.2,
                                 block_info.0 as BlockHeight,
                                 block_info.1,
+                                EpochId::default(),
                             );
                         }
                     }
@@ -243,6 +244,7 @@ fn one_iter(
                                 block_hash,
                                 target_height as BlockHeight,
                                 last_final_height,
+                                EpochId::default(),
                             );
                         }
                     }
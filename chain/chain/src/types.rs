@@ -30,7 +30,7 @@ use near_primitives::version::{
     ProtocolVersion, MIN_GAS_PRICE_NEP_92, MIN_GAS_PRICE_NEP_92_FIX, MIN_PROTOCOL_VERSION_NEP_92,
     MIN_PROTOCOL_VERSION_NEP_92_FIX,
 };
-use near_primitives::views::{QueryRequest, QueryResponse};
+use near_primitives::views::{FunctionCallTraceView, QueryRequest, QueryResponse};
 use near_store::flat_state::ChainAccessForFlatStorage;
 use near_store::flat_state::{FlatStorageCreationStatus, FlatStorageState};
 use near_store::{PartialStorage, ShardTries, Store, StoreUpdate, Trie, WrappedTrieChanges};
@@ -243,11 +243,18 @@ pub struct ChainConfig {
     /// Number of threads to execute background migration work.
     /// Currently used for flat storage background creation.
     pub background_migration_threads: usize,
+    /// Whether to record the trie nodes touched while applying chunks, so that
+    /// the state transitions can later be validated without the shard state.
+    pub record_state_transition_proofs: bool,
 }
 
 impl ChainConfig {
     pub fn test() -> Self {
-        Self { save_trie_changes: true, background_migration_threads: 1 }
+        Self {
+            save_trie_changes: true,
+            background_migration_threads: 1,
+            record_state_transition_proofs: false,
+        }
     }
 }
 
@@ -514,6 +521,65 @@ pub trait RuntimeAdapter: Send + Sync {
         request: &QueryRequest,
     ) -> Result<QueryResponse, near_chain_primitives::error::QueryError>;
 
+    /// Executes a view function call like `query` does for `QueryRequest::CallFunction`, but
+    /// records every host function call made by the contract and, if `extended_gas_profile` is
+    /// set, a per host function and per wasm function breakdown of the burnt gas.
+    fn trace_call(
+        &self,
+        shard_uid: ShardUId,
+        state_root: &StateRoot,
+        block_height: BlockHeight,
+        block_timestamp: u64,
+        prev_block_hash: &CryptoHash,
+        block_hash: &CryptoHash,
+        epoch_id: &EpochId,
+        contract_id: &AccountId,
+        method_name: &str,
+        args: &[u8],
+        extended_gas_profile: bool,
+    ) -> Result<FunctionCallTraceView, near_chain_primitives::error::QueryError>;
+
+    /// Applies a chunk the same way `apply_transactions` does, but with tracing enabled for the
+    /// function calls of receipt `receipt_id`.  Returns the recorded traces; the resulting state
+    /// changes are discarded.
+    fn trace_receipt(
+        &self,
+        receipt_id: &CryptoHash,
+        extended_gas_profile: bool,
+        shard_id: ShardId,
+        state_root: &StateRoot,
+        height: BlockHeight,
+        block_timestamp: u64,
+        prev_block_hash: &CryptoHash,
+        block_hash: &CryptoHash,
+        receipts: &[Receipt],
+        transactions: &[SignedTransaction],
+        last_validator_proposals: ValidatorStakeIter,
+        gas_price: Balance,
+        gas_limit: Gas,
+        challenges_result: &ChallengesResult,
+        random_seed: CryptoHash,
+        is_first_block_with_chunk_of_version: bool,
+    ) -> Result<Vec<FunctionCallTraceView>, Error>;
+
+    /// Executes `transaction` and all receipts it produces on top of the state after block
+    /// `block_hash`, without verifying the transaction signature and without persisting any
+    /// state changes.  `state_roots` are the post-state roots of all shards, indexed by shard id.
+    /// Receipts are executed in rounds, each round applying one chunk to every shard with
+    /// pending receipts.  Delayed receipts already queued on the touched shards are not executed,
+    /// and execution stops after a bounded number of rounds or once the maximum prepaid gas of
+    /// a transaction is burnt.  Returns outcomes of everything executed.
+    fn estimate_gas(
+        &self,
+        transaction: &SignedTransaction,
+        state_roots: &[StateRoot],
+        block_height: BlockHeight,
+        block_timestamp: u64,
+        block_hash: &CryptoHash,
+        gas_price: Balance,
+        random_seed: CryptoHash,
+    ) -> Result<Vec<ExecutionOutcomeWithId>, Error>;
+
     /// Get the part of the state from given state root.
     /// `block_hash` is a block whose `prev_state_root` is `state_root`
     fn obtain_state_part(
@@ -623,7 +689,9 @@ mod tests {
         let b1 = TestBlockBuilder::new(&genesis, signer.clone()).build();
         assert!(b1.header().verify_block_producer(&signer.public_key()));
         let other_signer = create_test_signer("other2");
-        let approvals = vec![Some(Approval::new(*b1.hash(), 1, 2, &other_signer).signature)];
+        let approvals = vec![Some(
+            Approval::new(*b1.hash(), 1, 2, &EpochId::default(), &other_signer).unwrap().signature,
+        )];
         let b2 = TestBlockBuilder::new(&b1, signer.clone()).approvals(approvals).build();
         b2.header().verify_block_producer(&signer.public_key());
     }
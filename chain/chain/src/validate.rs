@@ -1,23 +1,32 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use borsh::BorshDeserialize;
 
 use near_crypto::PublicKey;
 use near_primitives::block::{Block, BlockHeader};
 use near_primitives::challenge::{
-    BlockDoubleSign, Challenge, ChallengeBody, ChunkProofs, ChunkState, MaybeEncodedShardChunk,
+    BlockDoubleSign, Challenge, ChallengeBody, ChunkProofs, ChunkStateTransitionProof,
+    ChunkStateV2, MaybeEncodedShardChunk, PartialState,
 };
+use near_primitives::checked_feature;
 use near_primitives::hash::CryptoHash;
-use near_primitives::merkle::merklize;
+use near_primitives::merkle::{merklize, verify_path};
+use near_primitives::receipt::Receipt;
+use near_primitives::shard_layout::ShardLayout;
 use near_primitives::sharding::{
-    ShardChunk, ShardChunkHeader, ShardChunkHeaderV1, ShardChunkHeaderV2, ShardChunkHeaderV3,
+    ReceiptList, ReceiptProof, ShardChunk, ShardChunkHeader, ShardChunkHeaderV1,
+    ShardChunkHeaderV2, ShardChunkHeaderV3, ShardProof,
 };
+use near_primitives::syncing::ReceiptProofResponse;
 use near_primitives::transaction::SignedTransaction;
 use near_primitives::types::chunk_extra::ChunkExtra;
-use near_primitives::types::{AccountId, BlockHeight, EpochId, Nonce};
+use near_primitives::types::{AccountId, Balance, BlockHeight, EpochId, Nonce, ShardId};
+use near_store::PartialStorage;
 
+use crate::migrations::check_if_block_is_first_with_chunk_of_version;
+use crate::types::ApplyTransactionResult;
 use crate::{byzantine_assert, Chain};
-use crate::{ChainStore, Error, RuntimeWithEpochManagerAdapter};
+use crate::{ChainStore, ChainStoreAccess, Error, RuntimeWithEpochManagerAdapter};
 
 /// Gas limit cannot be adjusted for more than 0.1% at a time.
 const GAS_LIMIT_ADJUSTMENT_FACTOR: u64 = 1000;
@@ -302,87 +311,344 @@ fn validate_chunk_proofs_challenge(
     return Err(Error::MaliciousChallenge);
 }
 
+/// Data needed to re-execute the new chunk of a shard included in a block, which is not part of
+/// the chunk itself and is taken from the chain instead.
+pub(crate) struct ChunkApplyContext {
+    /// Height of the block including the previous chunk of the shard. The chunk applies the
+    /// receipts produced in the blocks after it.
+    pub prev_chunk_height_included: BlockHeight,
+    pub gas_price: Balance,
+    pub is_first_block_with_chunk_of_version: bool,
+}
+
+/// Collects the gas price and protocol upgrade flag used when the new chunk of `shard_id`
+/// included in the block with `block_header` was applied.
+pub(crate) fn get_chunk_apply_context(
+    chain_store: &ChainStore,
+    runtime_adapter: &dyn RuntimeWithEpochManagerAdapter,
+    block_header: &BlockHeader,
+    shard_id: ShardId,
+) -> Result<ChunkApplyContext, Error> {
+    let prev_block = chain_store.get_block(block_header.prev_hash())?;
+    let prev_chunk_header = Chain::get_prev_chunk_header(runtime_adapter, &prev_block, shard_id)?;
+    let is_first_block_with_chunk_of_version = check_if_block_is_first_with_chunk_of_version(
+        chain_store,
+        runtime_adapter,
+        block_header.prev_hash(),
+        shard_id,
+    )?;
+    Ok(ChunkApplyContext {
+        prev_chunk_height_included: prev_chunk_header.height_included(),
+        gas_price: prev_block.header().gas_price(),
+        is_first_block_with_chunk_of_version,
+    })
+}
+
+/// Returns the incoming receipts of the new chunk of `shard_id` included in the block with
+/// `block_hash` as they are stored, together with the receipts in the order they were applied.
+/// Fails if the receipts are not known, e.g. because the node doesn't track the shard.
+pub(crate) fn get_incoming_receipts_from_store(
+    chain_store: &ChainStore,
+    block_hash: &CryptoHash,
+    shard_id: ShardId,
+    prev_chunk_height_included: BlockHeight,
+) -> Result<(Vec<ReceiptProofResponse>, Vec<Receipt>), Error> {
+    let incoming_receipts = chain_store.get_incoming_receipts_for_shard(
+        shard_id,
+        *block_hash,
+        prev_chunk_height_included,
+    )?;
+    let receipts = validate_incoming_receipts(
+        chain_store,
+        block_hash,
+        shard_id,
+        prev_chunk_height_included,
+        &incoming_receipts,
+    )?
+    .ok_or_else(|| {
+        Error::Other(format!(
+            "Incoming receipts of shard {} for block {} are not known",
+            shard_id, block_hash
+        ))
+    })?;
+    Ok((incoming_receipts, receipts))
+}
+
+/// Checks that `incoming_receipts` are all the receipts sent to `shard_id` in the blocks starting
+/// from `block_hash` down to, but excluding, the one at `prev_chunk_height_included`. Every
+/// receipt proof must match the outgoing receipts root of a distinct chunk included in the block,
+/// and there must be a proof for every such chunk, so no receipts can be hidden.
+/// Returns the receipts in the order they are applied, or `None` if the proofs are invalid.
+pub(crate) fn validate_incoming_receipts(
+    chain_store: &ChainStore,
+    block_hash: &CryptoHash,
+    shard_id: ShardId,
+    prev_chunk_height_included: BlockHeight,
+    incoming_receipts: &[ReceiptProofResponse],
+) -> Result<Option<Vec<Receipt>>, Error> {
+    let mut block_hash = *block_hash;
+    let mut incoming_receipts = incoming_receipts.iter();
+    let mut receipts = vec![];
+    loop {
+        let block = chain_store.get_block(&block_hash)?;
+        if block.header().height() <= prev_chunk_height_included {
+            break;
+        }
+        let ReceiptProofResponse(response_block_hash, receipt_proofs) =
+            match incoming_receipts.next() {
+                Some(response) => response,
+                None => return Ok(None),
+            };
+        if response_block_hash != &block_hash
+            || receipt_proofs.len() != block.header().chunks_included() as usize
+        {
+            return Ok(None);
+        }
+        let mut visited_shard_ids = HashSet::new();
+        for ReceiptProof(shard_receipts, shard_proof) in receipt_proofs.iter() {
+            let ShardProof { from_shard_id, to_shard_id, proof } = shard_proof;
+            let chunk_header = match block.chunks().get(*from_shard_id as usize) {
+                Some(chunk_header) => chunk_header.clone(),
+                None => return Ok(None),
+            };
+            if *to_shard_id != shard_id
+                || chunk_header.height_included() != block.header().height()
+                || !visited_shard_ids.insert(*from_shard_id)
+                || !verify_path(
+                    chunk_header.outgoing_receipts_root(),
+                    proof,
+                    &CryptoHash::hash_borsh(ReceiptList(shard_id, shard_receipts)),
+                )
+            {
+                return Ok(None);
+            }
+        }
+        // Receipt proofs are applied in the order `Chain::collect_incoming_receipts_from_block`
+        // stores them in.
+        let mut receipt_proofs = receipt_proofs.to_vec();
+        receipt_proofs.sort_by_key(|ReceiptProof(_, shard_proof)| shard_proof.from_shard_id);
+        Chain::shuffle_receipt_proofs(&mut receipt_proofs, &block_hash);
+        receipts.push(receipt_proofs);
+        block_hash = *block.header().prev_hash();
+    }
+    if incoming_receipts.next().is_some() {
+        return Ok(None);
+    }
+    Ok(Some(
+        receipts
+            .into_iter()
+            .flatten()
+            .flat_map(|ReceiptProof(shard_receipts, _)| shard_receipts)
+            .collect(),
+    ))
+}
+
+/// Validates a chunk state challenge by re-executing the previous chunk on the partial state
+/// and incoming receipts from the challenge and comparing the results with what the challenged
+/// chunk claims. Other data which is not part of the chunk is taken from the chain.
 fn validate_chunk_state_challenge(
-    _runtime_adapter: &dyn RuntimeWithEpochManagerAdapter,
-    _chunk_state: &ChunkState,
+    chain_store: &ChainStore,
+    runtime_adapter: &dyn RuntimeWithEpochManagerAdapter,
+    chunk_state: &ChunkStateV2,
 ) -> Result<(CryptoHash, Vec<AccountId>), Error> {
-    // TODO (#2445): Enable challenges when they are working correctly.
-    // let prev_block_header = BlockHeader::try_from_slice(&chunk_state.prev_block_header)?;
-    // let block_header = BlockHeader::try_from_slice(&chunk_state.block_header)?;
-    //
-    // // Validate previous chunk and block header.
-    // validate_header_authorship(runtime_adapter, &prev_block_header)?;
-    // let prev_chunk_header = chunk_state.prev_chunk.cloned_header();
-    // let _ = validate_chunk_authorship(runtime_adapter, &prev_chunk_header)?;
-    // if !Block::validate_chunk_header_proof(
-    //     &prev_chunk_header,
-    //     prev_block_header.chunk_headers_root(),
-    //     &chunk_state.prev_merkle_proof,
-    // ) {
-    //     return Err(ErrorKind::MaliciousChallenge.into());
-    // }
-    //
-    // // Validate current chunk and block header.
-    // validate_header_authorship(runtime_adapter, &block_header)?;
-    // let chunk_producer = validate_chunk_authorship(runtime_adapter, &chunk_state.chunk_header)?;
-    // if !Block::validate_chunk_header_proof(
-    //     &chunk_state.chunk_header,
-    //     block_header.chunk_headers_root(),
-    //     &chunk_state.merkle_proof,
-    // ) {
-    //     return Err(ErrorKind::MaliciousChallenge.into());
-    // }
+    let prev_block_header = BlockHeader::try_from_slice(&chunk_state.prev_block_header)?;
+    let block_header = BlockHeader::try_from_slice(&chunk_state.block_header)?;
+
+    // Validate previous chunk and block header.
+    validate_header_authorship(runtime_adapter, &prev_block_header)?;
+    let prev_chunk_header = chunk_state.prev_chunk.cloned_header();
+    let _ = validate_chunk_authorship(runtime_adapter, &prev_chunk_header)?;
+    if !Block::validate_chunk_header_proof(
+        &prev_chunk_header,
+        prev_block_header.chunk_headers_root(),
+        &chunk_state.prev_merkle_proof,
+    ) {
+        return Err(Error::MaliciousChallenge);
+    }
+    if !validate_chunk_proofs(&chunk_state.prev_chunk, runtime_adapter)? {
+        return Err(Error::MaliciousChallenge);
+    }
+
+    // Validate current chunk and block header.
+    validate_header_authorship(runtime_adapter, &block_header)?;
+    let chunk_producer = validate_chunk_authorship(runtime_adapter, &chunk_state.chunk_header)?;
+    if !Block::validate_chunk_header_proof(
+        &chunk_state.chunk_header,
+        block_header.chunk_headers_root(),
+        &chunk_state.merkle_proof,
+    ) {
+        return Err(Error::MaliciousChallenge);
+    }
+
+    // The previous chunk must be the one whose results the challenged chunk commits to.
+    let shard_id = prev_chunk_header.shard_id();
+    if block_header.prev_hash() != prev_block_header.hash()
+        || chunk_state.chunk_header.shard_id() != shard_id
+        || prev_chunk_header.height_included() != prev_block_header.height()
+    {
+        return Err(Error::MaliciousChallenge);
+    }
 
     // Apply state transition and check that the result state and other data doesn't match.
-    // TODO (#6316): enable storage proof generation
-    // let partial_storage = PartialStorage { nodes: chunk_state.partial_state.clone() };
-    // let result = runtime_adapter
-    //     .check_state_transition(
-    //         partial_storage,
-    //         prev_chunk_header.shard_id(),
-    //         &prev_chunk_header.prev_state_root(),
-    //         block_header.height(),
-    //         block_header.raw_timestamp(),
-    //         block_header.prev_hash(),
-    //         block_header.hash(),
-    //         chunk_state.prev_chunk.receipts(),
-    //         chunk_state.prev_chunk.transactions(),
-    //         ValidatorStakeIter::empty(),
-    //         prev_block_header.gas_price(),
-    //         prev_chunk_header.gas_limit(),
-    //         &ChallengesResult::default(),
-    //         *block_header.random_value(),
-    //         // TODO: set it properly when challenges are enabled
-    //         true,
-    //         false,
-    //     )
-    //     .map_err(|_| Error::from(ErrorKind::MaliciousChallenge))?;
-    // let outcome_root = ApplyTransactionResult::compute_outcomes_proof(&result.outcomes).0;
-    // let proposals_match = result.validator_proposals.len()
-    //     == chunk_state.chunk_header.validator_proposals().len()
-    //     && result
-    //         .validator_proposals
-    //         .iter()
-    //         .zip(chunk_state.chunk_header.validator_proposals())
-    //         .all(|(x, y)| x == &y);
-    // if result.new_root != chunk_state.chunk_header.prev_state_root()
-    //     || outcome_root != chunk_state.chunk_header.outcome_root()
-    //     || !proposals_match
-    //     || result.total_gas_burnt != chunk_state.chunk_header.gas_used()
-    // {
-    //     Ok((*block_header.hash(), vec![chunk_producer]))
-    // } else {
-    //     // If all the data matches, this is actually valid chunk and challenge is malicious.
-    //     Err(ErrorKind::MaliciousChallenge.into())
-    // }
-    // Ok((*block_header.hash(), vec![chunk_producer]))
-
-    Err(Error::MaliciousChallenge)
+    match ChunkStateTransition::new(
+        chain_store,
+        runtime_adapter,
+        &prev_block_header,
+        &prev_chunk_header,
+        chunk_state.prev_chunk.transactions(),
+        &chunk_state.prev_chunk_incoming_receipts,
+        &chunk_state.partial_state,
+        &chunk_state.chunk_header,
+    )
+    .and_then(|transition| transition.check(runtime_adapter))
+    {
+        Ok(false) => Ok((*block_header.hash(), vec![chunk_producer])),
+        // If all the data matches, this is actually valid chunk and challenge is malicious.
+        Ok(true) | Err(Error::InvalidReceiptsProof) | Err(Error::InvalidStatePayload) => {
+            Err(Error::MaliciousChallenge)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// State transition of a chunk, with all inputs verified against the chain, which can be checked
+/// without access to the chain store. Checking it re-executes the chunk, so it may be done off the
+/// client thread.
+pub struct ChunkStateTransition {
+    prev_block_header: BlockHeader,
+    prev_chunk_header: ShardChunkHeader,
+    transactions: Vec<SignedTransaction>,
+    receipts: Vec<Receipt>,
+    partial_storage: PartialStorage,
+    gas_price: Balance,
+    is_first_block_with_chunk_of_version: bool,
+    shard_layout: ShardLayout,
+    chunk_header: ShardChunkHeader,
+}
+
+impl ChunkStateTransition {
+    /// Prepares re-execution of the chunk with `prev_chunk_header` and `transactions`, included in
+    /// the block with `prev_block_header`, on `partial_state` and `incoming_receipts`. Returns
+    /// `Error::InvalidReceiptsProof` if the incoming receipts can't be verified against the chain.
+    pub fn new(
+        chain_store: &ChainStore,
+        runtime_adapter: &dyn RuntimeWithEpochManagerAdapter,
+        prev_block_header: &BlockHeader,
+        prev_chunk_header: &ShardChunkHeader,
+        transactions: &[SignedTransaction],
+        incoming_receipts: &[ReceiptProofResponse],
+        partial_state: &PartialState,
+        chunk_header: &ShardChunkHeader,
+    ) -> Result<Self, Error> {
+        let shard_id = prev_chunk_header.shard_id();
+        let context =
+            get_chunk_apply_context(chain_store, runtime_adapter, prev_block_header, shard_id)?;
+        let receipts = validate_incoming_receipts(
+            chain_store,
+            prev_block_header.hash(),
+            shard_id,
+            context.prev_chunk_height_included,
+            incoming_receipts,
+        )?
+        .ok_or(Error::InvalidReceiptsProof)?;
+        let shard_layout =
+            runtime_adapter.get_shard_layout_from_prev_block(prev_block_header.hash())?;
+        Ok(Self {
+            prev_block_header: prev_block_header.clone(),
+            prev_chunk_header: prev_chunk_header.clone(),
+            transactions: transactions.to_vec(),
+            receipts,
+            partial_storage: PartialStorage { nodes: partial_state.clone() },
+            gas_price: context.gas_price,
+            is_first_block_with_chunk_of_version: context.is_first_block_with_chunk_of_version,
+            shard_layout,
+            chunk_header: chunk_header.clone(),
+        })
+    }
+
+    /// Re-executes the chunk and checks whether the results match what the next chunk commits to.
+    /// Returns `Error::InvalidStatePayload` if the chunk can't be applied on the partial state.
+    pub fn check(
+        self,
+        runtime_adapter: &dyn RuntimeWithEpochManagerAdapter,
+    ) -> Result<bool, Error> {
+        let prev_block_header = &self.prev_block_header;
+        let prev_chunk_header = &self.prev_chunk_header;
+        let result = runtime_adapter
+            .check_state_transition(
+                self.partial_storage,
+                prev_chunk_header.shard_id(),
+                &prev_chunk_header.prev_state_root(),
+                prev_chunk_header.height_included(),
+                prev_block_header.raw_timestamp(),
+                prev_chunk_header.prev_block_hash(),
+                prev_block_header.hash(),
+                &self.receipts,
+                &self.transactions,
+                prev_chunk_header.validator_proposals(),
+                self.gas_price,
+                prev_chunk_header.gas_limit(),
+                prev_block_header.challenges_result(),
+                *prev_block_header.random_value(),
+                true,
+                self.is_first_block_with_chunk_of_version,
+            )
+            .map_err(|_| Error::InvalidStatePayload)?;
+        let outcome_root = ApplyTransactionResult::compute_outcomes_proof(&result.outcomes).0;
+        let outgoing_receipts_root =
+            merklize(&Chain::build_receipts_hashes(&result.outgoing_receipts, &self.shard_layout))
+                .0;
+        let chunk_header = &self.chunk_header;
+        let proposals_match =
+            result.validator_proposals.iter().cloned().eq(chunk_header.validator_proposals());
+        Ok(result.new_root == chunk_header.prev_state_root()
+            && outcome_root == chunk_header.outcome_root()
+            && proposals_match
+            && result.total_gas_burnt == chunk_header.gas_used()
+            && result.total_balance_burnt == chunk_header.balance_burnt()
+            && outgoing_receipts_root == chunk_header.outgoing_receipts_root())
+    }
+}
+
+/// Verifies the inputs of a state transition proof received from a chunk producer: the previous
+/// chunk must be the new chunk of the shard in the previous block and contain the transactions
+/// from the proof, the next chunk must be built on top of that block by its chunk producer, and
+/// the incoming receipts must match the chain. Returns the state transition to re-execute; the
+/// proof is valid if it results in what the next chunk commits to.
+pub fn prepare_chunk_state_transition_proof(
+    chain_store: &ChainStore,
+    runtime_adapter: &dyn RuntimeWithEpochManagerAdapter,
+    proof: &ChunkStateTransitionProof,
+) -> Result<ChunkStateTransition, Error> {
+    let prev_block = chain_store.get_block(&proof.prev_block_hash)?;
+    let shard_id = proof.chunk_header.shard_id();
+    let prev_chunk_header = match prev_block.chunks().get(shard_id as usize) {
+        Some(header) if header.height_included() == prev_block.header().height() => header.clone(),
+        _ => return Err(Error::InvalidChunk),
+    };
+    if merklize(&proof.prev_chunk_transactions).0 != prev_chunk_header.tx_root() {
+        return Err(Error::InvalidTransactions);
+    }
+    validate_chunk_authorship(runtime_adapter, &proof.chunk_header)?;
+    if proof.chunk_header.prev_block_hash() != &proof.prev_block_hash {
+        return Err(Error::InvalidChunk);
+    }
+    ChunkStateTransition::new(
+        chain_store,
+        runtime_adapter,
+        prev_block.header(),
+        &prev_chunk_header,
+        &proof.prev_chunk_transactions,
+        &proof.prev_chunk_incoming_receipts,
+        &proof.partial_state,
+        &proof.chunk_header,
+    )
 }
 
 /// Returns `Some(block_hash, vec![account_id])` of invalid block and who to
 /// slash if challenge is correct and None if incorrect.
 pub fn validate_challenge(
+    chain_store: &ChainStore,
     runtime_adapter: &dyn RuntimeWithEpochManagerAdapter,
     epoch_id: &EpochId,
     last_block_hash: &CryptoHash,
@@ -405,8 +671,20 @@ pub fn validate_challenge(
         ChallengeBody::ChunkProofs(chunk_proofs) => {
             validate_chunk_proofs_challenge(runtime_adapter, chunk_proofs)
         }
-        ChallengeBody::ChunkState(chunk_state) => {
-            validate_chunk_state_challenge(runtime_adapter, chunk_state)
+        // Challenges without the incoming receipts can't be verified by nodes which don't track
+        // the shard.
+        ChallengeBody::ChunkState(_) => Err(Error::MaliciousChallenge),
+        ChallengeBody::ChunkStateV2(chunk_state) => {
+            let protocol_version = runtime_adapter.get_epoch_protocol_version(epoch_id)?;
+            if checked_feature!(
+                "protocol_feature_chunk_state_challenges",
+                ChunkStateChallenges,
+                protocol_version
+            ) {
+                validate_chunk_state_challenge(chain_store, runtime_adapter, chunk_state)
+            } else {
+                Err(Error::MaliciousChallenge)
+            }
         }
     }
 }
@@ -2075,6 +2075,7 @@ impl ShardsManager {
         outgoing_receipts: &[Receipt],
         outgoing_receipts_root: CryptoHash,
         tx_root: CryptoHash,
+        epoch_id: &EpochId,
         signer: &dyn ValidatorSigner,
         rs: &mut ReedSolomonWrapper,
         protocol_version: ProtocolVersion,
@@ -2094,6 +2095,7 @@ impl ShardsManager {
             transactions,
             outgoing_receipts,
             outgoing_receipts_root,
+            epoch_id,
             signer,
             protocol_version,
         )
@@ -2296,6 +2298,7 @@ mod test {
             &[],
             merklize(&Chain::build_receipts_hashes(&[], &shard_layout)).0,
             CryptoHash::default(),
+            &EpochId::default(),
             &signer,
             &mut rs,
             PROTOCOL_VERSION,
@@ -253,6 +253,7 @@ impl ChunkTestFixture {
             &receipts,
             receipts_root,
             MerkleHash::default(),
+            &EpochId::default(),
             &signer,
             &mut rs,
             PROTOCOL_VERSION,
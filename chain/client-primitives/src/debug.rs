@@ -9,7 +9,7 @@ use chrono::DateTime;
 use near_primitives::types::EpochId;
 use near_primitives::views::{
     CatchupStatusView, ChainProcessingInfo, EpochValidatorInfo, RequestedStatePartsView,
-    SyncStatusView,
+    SyncStatusView, ValidatorKickoutPredictionView,
 };
 use near_primitives::{
     block_header::ApprovalInner,
@@ -170,6 +170,91 @@ pub struct ValidatorStatus {
     pub banned_chunk_producers: Vec<(EpochId, Vec<AccountId>)>,
 }
 
+// Approval received from a single block producer.
+#[derive(Serialize, Debug, Clone)]
+pub struct DoomslugApprovalView {
+    pub account_id: AccountId,
+    pub arrival_time: DateTime<chrono::Utc>,
+    // Stake of the block producer as a fraction of the total stake in this epoch.
+    pub stake_fraction: f64,
+    // Fraction of the total stake in this epoch that approved once this approval arrived.
+    pub approved_stake_fraction: f64,
+}
+
+// Approvals for the same target height and the same approval inner (endorsement of a given block
+// or skip from a given height).
+#[derive(Serialize, Debug, Clone)]
+pub struct DoomslugApprovalsView {
+    pub inner: ApprovalInner,
+    // Sorted by arrival time.
+    pub approvals: Vec<DoomslugApprovalView>,
+    // Block producers that didn't send this approval (yet).
+    pub missing: Vec<AccountId>,
+    pub approved_stake_this_epoch_fraction: f64,
+    pub approved_stake_next_epoch_fraction: f64,
+    // Time at which the approvals crossed the doomslug threshold.
+    pub ready_at: Option<DateTime<chrono::Utc>>,
+}
+
+// Result of the last check whether a block can be produced at a given height.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub enum DoomslugReadiness {
+    // Approvals for the block on top of the current tip didn't cross the threshold.
+    NotEnoughApprovals,
+    // Approvals crossed the threshold, but some chunks are missing, so we wait a bit longer.
+    WaitingForChunks { wait_remaining_millis: u64 },
+    // The block can be produced.
+    Ready { has_enough_chunks: bool, approvals_ready_for_millis: u64 },
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DoomslugReadinessCheck {
+    // Height of the tip on top of which the block would be produced.
+    pub parent_height: BlockHeight,
+    pub check_time: DateTime<chrono::Utc>,
+    pub readiness: DoomslugReadiness,
+}
+
+// Doomslug timers and the largest heights it has seen.
+#[derive(Serialize, Debug)]
+pub struct DoomslugTimerView {
+    pub tip_hash: CryptoHash,
+    pub tip_height: BlockHeight,
+    // Whether the endorsement of the tip wasn't sent yet.
+    pub endorsement_pending: bool,
+    pub endorsement_delay_millis: u64,
+    // Skip to timer_height + 1 is sent once skip_delay_millis passes since the timer started.
+    pub timer_height: BlockHeight,
+    pub timer_started_ago_millis: u64,
+    pub skip_delay_millis: u64,
+    pub largest_target_height: BlockHeight,
+    pub largest_final_height: BlockHeight,
+    pub largest_threshold_height: BlockHeight,
+    pub largest_approval_height: BlockHeight,
+}
+
+// Approvals and block production at a given height.
+#[derive(Serialize, Debug)]
+pub struct DoomslugHeightView {
+    pub height: BlockHeight,
+    pub block_producer: Option<AccountId>,
+    // Block at this height on the canonical chain.
+    pub block_hash: Option<CryptoHash>,
+    // Time when this node produced a block at this height.
+    pub block_production_time: Option<DateTime<chrono::Utc>>,
+    // Approvals are only sent to the block producer of the target height, so this is empty
+    // unless this node is the block producer.
+    pub approvals: Vec<DoomslugApprovalsView>,
+    pub last_readiness_check: Option<DoomslugReadinessCheck>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DoomslugStatusView {
+    pub timer: DoomslugTimerView,
+    // Sorted by height inversely (high to low).
+    pub heights: Vec<DoomslugHeightView>,
+}
+
 // Different debug requests that can be sent by HTML pages, via GET.
 pub enum DebugStatus {
     // Request for the current sync status
@@ -188,6 +273,11 @@ pub enum DebugStatus {
     ChainProcessingStatus,
     // The state parts already requested.
     RequestedStateParts,
+    // Projected block and chunk production of current validators and
+    // predicted kickouts.
+    ValidatorKickoutPrediction,
+    // Approval timelines, doomslug timers and block production readiness per height.
+    DoomslugStatus,
 }
 
 impl Message for DebugStatus {
@@ -209,4 +299,9 @@ pub enum DebugStatusResponse {
     ChainProcessingStatus(ChainProcessingInfo),
     // The state parts already requested.
     RequestedStateParts(Vec<RequestedStatePartsView>),
+    // Projected block and chunk production of current validators and
+    // predicted kickouts.
+    ValidatorKickoutPrediction(ValidatorKickoutPredictionView),
+    // Approval timelines, doomslug timers and block production readiness per height.
+    DoomslugStatus(DoomslugStatusView),
 }
@@ -8,20 +8,25 @@ use chrono::DateTime;
 use near_primitives::time::Utc;
 
 use near_chain_configs::{ClientConfig, ProtocolConfigView};
+use near_crypto::PublicKey;
+use near_primitives::errors::InvalidTxError;
 use near_primitives::hash::CryptoHash;
 use near_primitives::merkle::{MerklePath, PartialMerkleTree};
 use near_primitives::network::PeerId;
 use near_primitives::sharding::ChunkHash;
+use near_primitives::transaction::Action;
 use near_primitives::types::{
-    AccountId, BlockHeight, BlockReference, EpochId, EpochReference, MaybeBlockId, ShardId,
-    TransactionOrReceiptId,
+    AccountId, BlockHeight, BlockReference, EpochId, EpochReference, FunctionArgs, MaybeBlockId,
+    Nonce, ShardId, TransactionOrReceiptId,
 };
 use near_primitives::views::validator_stake_view::ValidatorStakeView;
 use near_primitives::views::{
-    BlockView, ChunkView, DownloadStatusView, EpochValidatorInfo, ExecutionOutcomeWithIdView,
-    FinalExecutionOutcomeViewEnum, GasPriceView, LightClientBlockLiteView, LightClientBlockView,
-    MaintenanceWindowsView, QueryRequest, QueryResponse, ReceiptView, ShardSyncDownloadView,
-    StateChangesKindsView, StateChangesRequestView, StateChangesView, SyncStatusView,
+    BlockView, ChunkView, DownloadStatusView, EpochRewardsView, EpochValidatorInfo,
+    ExecutionOutcomeWithIdView, FinalExecutionOutcomeViewEnum, FunctionCallTraceView,
+    GasEstimateView, GasPriceView, LightClientBlockLiteView, LightClientBlockView,
+    MaintenanceWindowsView, QueryRequest, QueryResponse, ReceiptExecutionTraceView, ReceiptView,
+    ShardSyncDownloadView, StateChangesKindsView, StateChangesRequestView, StateChangesView,
+    SyncStatusView,
 };
 pub use near_primitives::views::{StatusResponse, StatusSyncInfo};
 use serde::Serialize;
@@ -639,6 +644,16 @@ impl Message for GetValidatorInfo {
     type Result = Result<EpochValidatorInfo, GetValidatorInfoError>;
 }
 
+/// Recomputes the rewards of a finished epoch.  `EpochReference::Latest`
+/// refers to the last finished epoch.
+pub struct GetValidatorRewards {
+    pub epoch_reference: EpochReference,
+}
+
+impl Message for GetValidatorRewards {
+    type Result = Result<EpochRewardsView, GetValidatorInfoError>;
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum GetValidatorInfoError {
     #[error("IO Error: {0}")]
@@ -940,6 +955,106 @@ impl From<near_chain_primitives::Error> for GetMaintenanceWindowsError {
     }
 }
 
+/// Executes a view function call with execution tracing enabled.
+pub struct TraceCall {
+    pub block_reference: BlockReference,
+    pub account_id: AccountId,
+    pub method_name: String,
+    pub args: FunctionArgs,
+    /// Whether to also collect an extended gas profile of the call.
+    pub extended_gas_profile: bool,
+}
+
+impl Message for TraceCall {
+    type Result = Result<FunctionCallTraceView, TraceError>;
+}
+
+/// Re-executes all receipts of a transaction with execution tracing enabled.
+pub struct TraceTransaction {
+    pub tx_hash: CryptoHash,
+    /// Whether to also collect extended gas profiles of the function calls.
+    pub extended_gas_profile: bool,
+}
+
+impl Message for TraceTransaction {
+    type Result = Result<Vec<ReceiptExecutionTraceView>, TraceError>;
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum TraceError {
+    #[error("IO Error: {0}")]
+    IOError(String),
+    #[error(
+        "Block either has never been observed on the node or has been garbage collected: {0:?}"
+    )]
+    UnknownBlock(BlockReference),
+    #[error("Transaction or receipt {0} has not been executed on this node")]
+    UnknownTransaction(CryptoHash),
+    #[error("The node does not track the shard ID {0}")]
+    UnavailableShard(ShardId),
+    #[error("Function call cannot be traced: {0}")]
+    InvalidCall(String),
+    #[error("Transaction cannot be traced within the limit of {0}")]
+    LimitExceeded(String),
+    // NOTE: Currently, the underlying errors are too broad, and while we tried to handle
+    // expected cases, we cannot statically guarantee that no other errors will be returned
+    // in the future.
+    // TODO #3851: Remove this variant once we can exhaustively match all the underlying errors
+    #[error("It is a bug if you receive this error type, please, report this incident: https://github.com/near/nearcore/issues/new/choose. Details: {0}")]
+    Unreachable(String),
+}
+
+impl From<near_chain_primitives::Error> for TraceError {
+    fn from(error: near_chain_primitives::Error) -> Self {
+        match error {
+            near_chain_primitives::Error::IOErr(error) => Self::IOError(error.to_string()),
+            _ => Self::Unreachable(error.to_string()),
+        }
+    }
+}
+
+/// Executes a transaction and all receipts it produces on top of the latest state, without
+/// verifying its signature and without committing the changes.
+pub struct EstimateGas {
+    pub signer_id: AccountId,
+    pub public_key: PublicKey,
+    /// Defaults to the next nonce of the access key.
+    pub nonce: Option<Nonce>,
+    pub receiver_id: AccountId,
+    pub actions: Vec<Action>,
+}
+
+impl Message for EstimateGas {
+    type Result = Result<GasEstimateView, EstimateGasError>;
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum EstimateGasError {
+    #[error("IO Error: {0}")]
+    IOError(String),
+    #[error("There are no fully synchronized blocks yet")]
+    NoSyncedBlocks,
+    #[error("The node does not track the shard ID {0}")]
+    UnavailableShard(ShardId),
+    #[error("Transaction is invalid: {0}")]
+    InvalidTransaction(InvalidTxError),
+    // NOTE: Currently, the underlying errors are too broad, and while we tried to handle
+    // expected cases, we cannot statically guarantee that no other errors will be returned
+    // in the future.
+    // TODO #3851: Remove this variant once we can exhaustively match all the underlying errors
+    #[error("It is a bug if you receive this error type, please, report this incident: https://github.com/near/nearcore/issues/new/choose. Details: {0}")]
+    Unreachable(String),
+}
+
+impl From<near_chain_primitives::Error> for EstimateGasError {
+    fn from(error: near_chain_primitives::Error) -> Self {
+        match error {
+            near_chain_primitives::Error::IOErr(error) => Self::IOError(error.to_string()),
+            _ => Self::Unreachable(error.to_string()),
+        }
+    }
+}
+
 pub struct GetClientConfig {}
 
 impl Message for GetClientConfig {
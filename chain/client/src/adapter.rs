@@ -7,7 +7,7 @@ use near_network::types::{
 };
 use near_o11y::WithSpanContextExt;
 use near_primitives::block::{Approval, Block, BlockHeader};
-use near_primitives::challenge::Challenge;
+use near_primitives::challenge::{Challenge, ChunkStateTransitionProof};
 use near_primitives::errors::InvalidTxError;
 use near_primitives::hash::CryptoHash;
 use near_primitives::network::{AnnounceAccount, PeerId};
@@ -102,6 +102,10 @@ pub(crate) struct RecvPartialEncodedChunkForward(pub PartialEncodedChunkForwardM
 #[rtype(result = "()")]
 pub(crate) struct RecvPartialEncodedChunk(pub PartialEncodedChunk);
 
+#[derive(actix::Message, Debug)]
+#[rtype(result = "()")]
+pub(crate) struct RecvChunkStateTransitionProof(pub ChunkStateTransitionProof, pub PeerId);
+
 #[derive(actix::Message, Debug)]
 #[rtype(result = "()")]
 pub(crate) struct RecvPartialEncodedChunkResponse(
@@ -310,6 +314,21 @@ impl near_network::client::Client for Adapter {
         }
     }
 
+    async fn chunk_state_transition_proof(
+        &self,
+        proof: ChunkStateTransitionProof,
+        peer_id: PeerId,
+    ) {
+        match self
+            .client_addr
+            .send(RecvChunkStateTransitionProof(proof, peer_id).with_span_context())
+            .await
+        {
+            Ok(()) => {}
+            Err(err) => tracing::error!("mailbox error: {err}"),
+        }
+    }
+
     async fn block_request(&self, hash: CryptoHash) -> Option<Box<Block>> {
         match self.view_client_addr.send(BlockRequest(hash).with_span_context()).await {
             Ok(res) => res,
@@ -34,7 +34,8 @@ use near_network::types::{
     HighestHeightPeerInfo, NetworkRequests, PeerManagerAdapter, ReasonForBan,
 };
 use near_primitives::block::{Approval, ApprovalInner, ApprovalMessage, Block, BlockHeader, Tip};
-use near_primitives::challenge::{Challenge, ChallengeBody};
+use near_primitives::challenge::{Challenge, ChallengeBody, ChunkStateTransitionProof};
+use near_primitives::checked_feature;
 use near_primitives::hash::CryptoHash;
 use near_primitives::merkle::{merklize, MerklePath, PartialMerkleTree};
 use near_primitives::receipt::Receipt;
@@ -44,12 +45,15 @@ use near_primitives::sharding::{
 };
 use near_primitives::transaction::SignedTransaction;
 use near_primitives::types::chunk_extra::ChunkExtra;
-use near_primitives::types::{AccountId, ApprovalStake, BlockHeight, EpochId, NumBlocks, ShardId};
+use near_primitives::types::{
+    AccountId, ApprovalStake, BlockHeight, EpochId, NumBlocks, ShardId, ValidatorInfoIdentifier,
+};
 use near_primitives::unwrap_or_return;
 use near_primitives::utils::MaybeValidated;
 use near_primitives::validator_signer::ValidatorSigner;
 
 use crate::adapter::ProcessTxResponse;
+use crate::config_updater::ValidatorKeyStatus;
 use crate::debug::BlockProductionTracker;
 use crate::debug::PRODUCTION_TIMES_CACHE_SIZE;
 use crate::sync::block::BlockSync;
@@ -69,6 +73,10 @@ use near_primitives::views::{CatchupStatusView, DroppedReason};
 const NUM_REBROADCAST_BLOCKS: usize = 30;
 const CHUNK_HEADERS_FOR_INCLUSION_CACHE_SIZE: usize = 2048;
 const NUM_EPOCH_CHUNK_PRODUCERS_TO_KEEP_IN_BLOCKLIST: usize = 1000;
+const CHECKED_CHUNK_STATE_TRANSITIONS_CACHE_SIZE: usize = 2048;
+/// Maximum number of state transition proofs re-executed in the rayon thread pool at once.
+/// Proofs received while the limit is reached are dropped.
+const MAX_CHUNK_STATE_TRANSITIONS_IN_PROCESSING: usize = 16;
 
 /// The time we wait for the response to a Epoch Sync request before retrying
 // TODO #3488 set 30_000
@@ -92,6 +100,8 @@ pub struct Client {
     pub produce_invalid_chunks: bool,
     #[cfg(feature = "test_features")]
     pub produce_invalid_tx_in_chunks: bool,
+    #[cfg(feature = "test_features")]
+    pub produce_invalid_state_root_chunks: bool,
 
     /// Fast Forward accrued delta height used to calculate fast forwarded timestamps for each block.
     #[cfg(feature = "sandbox")]
@@ -149,11 +159,93 @@ pub struct Client {
     tier1_accounts_cache: Option<(EpochId, Arc<AccountKeys>)>,
     /// Used when it is needed to create flat storage in background for some shards.
     flat_storage_creator: Option<FlatStorageCreator>,
+    /// Chunks whose state transition proofs were already checked or are being checked.
+    checked_chunk_state_transitions: LruCache<ChunkHash, ()>,
+    /// Number of state transition proofs currently re-executed in the rayon thread pool.
+    num_chunk_state_transitions_in_processing: usize,
+}
+
+/// Result of checking a state transition proof off the client thread: whether re-executing the
+/// previous chunk results in what the chunk with `chunk_header` commits to.
+pub struct ChunkStateTransitionChecked {
+    pub chunk_header: ShardChunkHeader,
+    /// Peer which sent the proof.
+    pub peer_id: PeerId,
+    pub result: Result<bool, near_chain::Error>,
 }
 
+/// Called in the rayon thread pool once a state transition proof is checked.
+pub type ChunkStateTransitionCheckedCallback =
+    Box<dyn FnOnce(ChunkStateTransitionChecked) + Send + 'static>;
+
 impl Client {
     pub(crate) fn update_client_config(&self, update_client_config: UpdateableClientConfig) {
         self.config.expected_shutdown.update(update_client_config.expected_shutdown);
+        let mut gc = update_client_config.gc;
+        let gc_num_epochs_to_keep = self.config.gc.get().gc_num_epochs_to_keep;
+        if gc.gc_num_epochs_to_keep != gc_num_epochs_to_keep {
+            warn!(
+                target: "config",
+                gc_num_epochs_to_keep,
+                new_gc_num_epochs_to_keep = gc.gc_num_epochs_to_keep,
+                "gc_num_epochs_to_keep can't change while the node is running, restart the node to apply it");
+            gc.gc_num_epochs_to_keep = gc_num_epochs_to_keep;
+        }
+        self.config.gc.update(gc);
+        self.config.state_sync_timeout.update(update_client_config.state_sync_timeout);
+        self.config.max_gas_burnt_view.update(update_client_config.max_gas_burnt_view);
+        // The runtime shares these values and applies them from the first
+        // epoch whose tracked shards weren't determined yet.
+        self.config.tracked_accounts.update(update_client_config.tracked_accounts);
+        self.config.tracked_shards.update(update_client_config.tracked_shards);
+        let tries = self.runtime_adapter.get_tries();
+        tries.update_cache_config(
+            update_client_config.trie_cache,
+            update_client_config.view_trie_cache,
+        );
+        tries.update_adaptive_cache_config(update_client_config.adaptive_trie_cache);
+        self.config.peer_limits.update(update_client_config.peer_limits);
+        self.network_adapter.do_send(
+            PeerManagerMessageRequest::SetPeerLimits(update_client_config.peer_limits)
+                .with_span_context(),
+        );
+    }
+
+    /// Returns whether `public_key` is staked by `account_id` for the epoch of
+    /// the next block, for the epoch after it or, if `check_proposals` is set,
+    /// proposed in the current epoch.  Checking proposals is expensive.
+    pub fn validator_key_status(
+        &self,
+        account_id: &AccountId,
+        public_key: &near_crypto::PublicKey,
+        check_proposals: bool,
+    ) -> Result<ValidatorKeyStatus, Error> {
+        let head = self.chain.head()?;
+        let is_staked = |epoch_id: &EpochId| {
+            self.runtime_adapter
+                .get_validator_by_account_id(epoch_id, &head.last_block_hash, account_id)
+                .map_or(false, |(validator_stake, _)| validator_stake.public_key() == public_key)
+        };
+        if is_staked(&self.runtime_adapter.get_epoch_id_from_prev_block(&head.last_block_hash)?) {
+            return Ok(ValidatorKeyStatus::Active);
+        }
+        if is_staked(
+            &self.runtime_adapter.get_next_epoch_id_from_prev_block(&head.last_block_hash)?,
+        ) {
+            return Ok(ValidatorKeyStatus::Pending);
+        }
+        if check_proposals {
+            let validator_info = self
+                .runtime_adapter
+                .get_validator_info(ValidatorInfoIdentifier::BlockHash(head.last_block_hash))?;
+            if validator_info.current_proposals.into_iter().any(|proposal| {
+                let proposal = proposal.into_validator_stake();
+                proposal.account_id() == account_id && proposal.public_key() == public_key
+            }) {
+                return Ok(ValidatorKeyStatus::Pending);
+            }
+        }
+        Ok(ValidatorKeyStatus::NotStaked)
     }
 }
 
@@ -200,6 +292,7 @@ impl Client {
         let chain_config = ChainConfig {
             save_trie_changes: !config.archive,
             background_migration_threads: config.client_background_migration_threads,
+            record_state_transition_proofs: config.record_state_transition_proofs,
         };
         let chain = Chain::new(
             runtime_adapter.clone(),
@@ -250,7 +343,7 @@ impl Client {
         );
         let block_sync =
             BlockSync::new(network_adapter.clone(), config.block_fetch_horizon, config.archive);
-        let state_sync = StateSync::new(network_adapter.clone(), config.state_sync_timeout);
+        let state_sync = StateSync::new(network_adapter.clone(), config.state_sync_timeout.clone());
         let num_block_producer_seats = config.num_block_producer_seats as usize;
         let data_parts = runtime_adapter.num_data_parts();
         let parity_parts = runtime_adapter.num_total_parts() - data_parts;
@@ -273,6 +366,8 @@ impl Client {
             produce_invalid_chunks: false,
             #[cfg(feature = "test_features")]
             produce_invalid_tx_in_chunks: false,
+            #[cfg(feature = "test_features")]
+            produce_invalid_state_root_chunks: false,
             #[cfg(feature = "sandbox")]
             accrued_fastforward_delta: 0,
             config,
@@ -304,6 +399,10 @@ impl Client {
             chunk_production_info: lru::LruCache::new(PRODUCTION_TIMES_CACHE_SIZE),
             tier1_accounts_cache: None,
             flat_storage_creator,
+            checked_chunk_state_transitions: LruCache::new(
+                CHECKED_CHUNK_STATE_TRANSITIONS_CACHE_SIZE,
+            ),
+            num_chunk_state_transitions_in_processing: 0,
         })
     }
 
@@ -703,7 +802,8 @@ impl Client {
             next_bp_hash,
             block_merkle_root,
             timestamp_override,
-        );
+        )
+        .map_err(|err| Error::BlockProducer(err.to_string()))?;
 
         // Update latest known even before returning block out, to prevent race conditions.
         self.chain.mut_store().save_latest_known(LatestKnown {
@@ -805,9 +905,16 @@ impl Client {
         let gas_used = chunk_extra.gas_used();
         #[cfg(feature = "test_features")]
         let gas_used = if self.produce_invalid_chunks { gas_used + 1 } else { gas_used };
+        let state_root = *chunk_extra.state_root();
+        #[cfg(feature = "test_features")]
+        let state_root = if self.produce_invalid_state_root_chunks {
+            near_primitives::hash::hash(state_root.as_ref())
+        } else {
+            state_root
+        };
         let (encoded_chunk, merkle_paths) = ShardsManager::create_encoded_shard_chunk(
             prev_block_hash,
-            *chunk_extra.state_root(),
+            state_root,
             *chunk_extra.outcome_root(),
             next_height,
             shard_id,
@@ -819,6 +926,7 @@ impl Client {
             &outgoing_receipts,
             outgoing_receipts_root,
             tx_root,
+            epoch_id,
             &*validator_signer,
             &mut self.rs_for_chunk_production,
             protocol_version,
@@ -918,7 +1026,13 @@ impl Client {
     pub fn send_challenges(&mut self, challenges: Vec<ChallengeBody>) {
         if let Some(validator_signer) = &self.validator_signer {
             for body in challenges {
-                let challenge = Challenge::produce(body, &**validator_signer);
+                let challenge = match Challenge::produce(body, &**validator_signer) {
+                    Ok(challenge) => challenge,
+                    Err(err) => {
+                        warn!(target: "client", ?err, "Failed to sign challenge");
+                        continue;
+                    }
+                };
                 self.challenges.insert(challenge.hash, challenge.clone());
                 self.network_adapter.do_send(
                     PeerManagerMessageRequest::NetworkRequests(NetworkRequests::Challenge(
@@ -1110,6 +1224,7 @@ impl Client {
         apply_chunks_done_callback: DoneApplyChunkCallback,
     ) -> Result<(), near_chain::Error> {
         let mut block_processing_artifacts = BlockProcessingArtifact::default();
+        let epoch_id = block.get_inner().header().epoch_id().clone();
 
         let result = {
             let me = self
@@ -1132,26 +1247,50 @@ impl Client {
             if let Err(e) = &result {
                 match e {
                     near_chain::Error::InvalidChunkProofs(chunk_proofs) => {
-                        self.network_adapter.do_send(
-                            PeerManagerMessageRequest::NetworkRequests(NetworkRequests::Challenge(
-                                Challenge::produce(
-                                    ChallengeBody::ChunkProofs(*chunk_proofs.clone()),
-                                    &**validator_signer,
-                                ),
-                            ))
-                            .with_span_context(),
-                        );
+                        match Challenge::produce(
+                            ChallengeBody::ChunkProofs(*chunk_proofs.clone()),
+                            &**validator_signer,
+                        ) {
+                            Ok(challenge) => self.network_adapter.do_send(
+                                PeerManagerMessageRequest::NetworkRequests(
+                                    NetworkRequests::Challenge(challenge),
+                                )
+                                .with_span_context(),
+                            ),
+                            Err(err) => {
+                                warn!(target: "client", ?err, "Failed to sign challenge")
+                            }
+                        }
                     }
                     near_chain::Error::InvalidChunkState(chunk_state) => {
-                        self.network_adapter.do_send(
-                            PeerManagerMessageRequest::NetworkRequests(NetworkRequests::Challenge(
-                                Challenge::produce(
-                                    ChallengeBody::ChunkState(*chunk_state.clone()),
-                                    &**validator_signer,
-                                ),
-                            ))
-                            .with_span_context(),
-                        );
+                        // Nodes running an older protocol version can't decode challenges with
+                        // the incoming receipts, so only send them once the feature is enabled.
+                        let with_incoming_receipts = self
+                            .runtime_adapter
+                            .get_epoch_protocol_version(&epoch_id)
+                            .map_or(false, |protocol_version| {
+                                checked_feature!(
+                                    "protocol_feature_chunk_state_challenges",
+                                    ChunkStateChallenges,
+                                    protocol_version
+                                )
+                            });
+                        let body = if with_incoming_receipts {
+                            ChallengeBody::ChunkStateV2(chunk_state.clone())
+                        } else {
+                            ChallengeBody::ChunkState((*chunk_state.clone()).into())
+                        };
+                        match Challenge::produce(body, &**validator_signer) {
+                            Ok(challenge) => self.network_adapter.do_send(
+                                PeerManagerMessageRequest::NetworkRequests(
+                                    NetworkRequests::Challenge(challenge),
+                                )
+                                .with_span_context(),
+                            ),
+                            Err(err) => {
+                                warn!(target: "client", ?err, "Failed to sign challenge")
+                            }
+                        }
                     }
                     _ => {}
                 }
@@ -1332,11 +1471,14 @@ impl Client {
             } else {
                 self.chain.get_block_header(&last_final_hash)?.height()
             };
+            let next_block_epoch_id =
+                self.runtime_adapter.get_epoch_id_from_prev_block(&tip.last_block_hash)?;
             self.doomslug.set_tip(
                 Clock::instant(),
                 tip.last_block_hash,
                 tip.height,
                 last_final_height,
+                next_block_epoch_id,
             );
         }
 
@@ -1354,7 +1496,15 @@ impl Client {
         } else {
             self.chain.get_block_header(&last_final_hash)?.height()
         };
-        self.doomslug.set_tip(Clock::instant(), tip.last_block_hash, height, last_final_height);
+        let next_block_epoch_id =
+            self.runtime_adapter.get_epoch_id_from_prev_block(&tip.last_block_hash)?;
+        self.doomslug.set_tip(
+            Clock::instant(),
+            tip.last_block_hash,
+            height,
+            last_final_height,
+            next_block_epoch_id,
+        );
 
         Ok(())
     }
@@ -1460,6 +1610,10 @@ impl Client {
                 log_assert!(result.is_ok(), "Can't clear old data, {:?}", result);
             }
 
+            if let Err(err) = self.maybe_refresh_view_trie_snapshots(last_final_block) {
+                warn!(target: "client", ?err, "Failed to refresh view trie snapshots");
+            }
+
             // send_network_chain_info should be called whenever the chain head changes.
             // See send_network_chain_info() for more details.
             if let Err(err) = self.send_network_chain_info() {
@@ -1563,6 +1717,7 @@ impl Client {
                             shard_id,
                         ) {
                             Ok(Some((encoded_chunk, merkle_paths, receipts))) => {
+                                let chunk_header = encoded_chunk.cloned_header();
                                 self.persist_and_distribute_encoded_chunk(
                                     encoded_chunk,
                                     merkle_paths,
@@ -1570,6 +1725,13 @@ impl Client {
                                     validator_id.clone(),
                                 )
                                 .expect("Failed to process produced chunk");
+                                if self.config.record_state_transition_proofs {
+                                    if let Err(err) = self
+                                        .send_chunk_state_transition_proof(&block, &chunk_header)
+                                    {
+                                        warn!(target: "client", ?err, ?shard_id, "Failed to send state transition proof");
+                                    }
+                                }
                             }
                             Ok(None) => {}
                             Err(err) => {
@@ -1610,6 +1772,159 @@ impl Client {
         Ok(())
     }
 
+    /// Sends the proof of the state transition committed to by the newly produced chunk with
+    /// `chunk_header` to the block producers of the epoch which aren't assigned to the shard, so
+    /// that they can check it without tracking the shard.
+    pub fn send_chunk_state_transition_proof(
+        &mut self,
+        prev_block: &Block,
+        chunk_header: &ShardChunkHeader,
+    ) -> Result<(), Error> {
+        let shard_id = chunk_header.shard_id();
+        let me = self.validator_signer.as_ref().map(|signer| signer.validator_id().clone());
+        let epoch_id = self.runtime_adapter.get_epoch_id_from_prev_block(prev_block.hash())?;
+        let recipients: Vec<AccountId> = self
+            .runtime_adapter
+            .get_epoch_block_producers_ordered(&epoch_id, prev_block.hash())?
+            .into_iter()
+            .map(|(bp, _)| bp.take_account_id())
+            .filter(|account_id| {
+                Some(account_id) != me.as_ref()
+                    && !self.runtime_adapter.cares_about_shard(
+                        Some(account_id),
+                        prev_block.hash(),
+                        shard_id,
+                        false,
+                    )
+            })
+            .collect();
+        if recipients.is_empty() {
+            return Ok(());
+        }
+        let proof =
+            match self.chain.create_chunk_state_transition_proof(prev_block, chunk_header)? {
+                Some(proof) => Box::new(proof),
+                None => return Ok(()),
+            };
+        for account_id in recipients {
+            self.network_adapter.do_send(
+                PeerManagerMessageRequest::NetworkRequests(
+                    NetworkRequests::ChunkStateTransitionProof { account_id, proof: proof.clone() },
+                )
+                .with_span_context(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Verifies the inputs of a state transition proof received from `peer_id` and schedules
+    /// re-execution of the chunk in the rayon thread pool. `done_callback` is called with the
+    /// result, which must be passed to `on_chunk_state_transition_checked`. Proofs for shards the
+    /// node tracks are ignored, since it applies their chunks itself, and every chunk is checked
+    /// at most once.
+    pub fn process_chunk_state_transition_proof(
+        &mut self,
+        proof: ChunkStateTransitionProof,
+        peer_id: PeerId,
+        done_callback: ChunkStateTransitionCheckedCallback,
+    ) -> Result<(), Error> {
+        let me = self.validator_signer.as_ref().map(|signer| signer.validator_id().clone());
+        let chunk_hash = proof.chunk_header.chunk_hash();
+        let shard_id = proof.chunk_header.shard_id();
+        if self.checked_chunk_state_transitions.contains(&chunk_hash)
+            || self.runtime_adapter.cares_about_shard(
+                me.as_ref(),
+                &proof.prev_block_hash,
+                shard_id,
+                true,
+            )
+        {
+            return Ok(());
+        }
+        if self.num_chunk_state_transitions_in_processing
+            >= MAX_CHUNK_STATE_TRANSITIONS_IN_PROCESSING
+        {
+            debug!(target: "client", ?chunk_hash, "Too many state transition proofs in processing, dropping");
+            return Ok(());
+        }
+        // Proofs are only useful until the block including the next chunk is final.
+        let prev_block_header = self.chain.get_block_header(&proof.prev_block_hash)?;
+        if prev_block_header.height() < self.chain.final_head()?.height {
+            return Ok(());
+        }
+        let transition = match self.chain.prepare_chunk_state_transition(&proof) {
+            Ok(transition) => transition,
+            Err(err) => {
+                self.on_invalid_chunk_state_transition_proof(peer_id, shard_id, &err);
+                return Err(err.into());
+            }
+        };
+        self.checked_chunk_state_transitions.put(chunk_hash, ());
+        self.num_chunk_state_transitions_in_processing += 1;
+        let runtime_adapter = self.runtime_adapter.clone();
+        let chunk_header = proof.chunk_header;
+        rayon::spawn(move || {
+            let result = transition.check(runtime_adapter.as_ref());
+            done_callback(ChunkStateTransitionChecked { chunk_header, peer_id, result });
+        });
+        Ok(())
+    }
+
+    /// Acts on the result of checking a state transition proof: the chunk producer of a chunk
+    /// that doesn't match the state transition is banned for the epoch, so its chunks aren't
+    /// included in blocks, and the peer which sent a malformed proof is banned.
+    pub fn on_chunk_state_transition_checked(
+        &mut self,
+        checked: ChunkStateTransitionChecked,
+    ) -> Result<(), Error> {
+        let ChunkStateTransitionChecked { chunk_header, peer_id, result } = checked;
+        self.num_chunk_state_transitions_in_processing -= 1;
+        let shard_id = chunk_header.shard_id();
+        match result {
+            Ok(true) => {
+                metrics::CHUNK_STATE_TRANSITION_PROOFS_TOTAL
+                    .with_label_values(&[&shard_id.to_string(), "valid"])
+                    .inc();
+                Ok(())
+            }
+            Ok(false) => {
+                metrics::CHUNK_STATE_TRANSITION_PROOFS_TOTAL
+                    .with_label_values(&[&shard_id.to_string(), "invalid"])
+                    .inc();
+                self.ban_chunk_producer_for_producing_invalid_chunk(chunk_header)?;
+                Err(near_chain::Error::InvalidStateRoot.into())
+            }
+            Err(err) => {
+                // The proof can't be checked, so a correct one may still arrive from the chunk
+                // producer.
+                self.checked_chunk_state_transitions.pop(&chunk_header.chunk_hash());
+                self.on_invalid_chunk_state_transition_proof(peer_id, shard_id, &err);
+                Err(err.into())
+            }
+        }
+    }
+
+    fn on_invalid_chunk_state_transition_proof(
+        &mut self,
+        peer_id: PeerId,
+        shard_id: ShardId,
+        err: &near_chain::Error,
+    ) {
+        match err {
+            near_chain::Error::InvalidChunk
+            | near_chain::Error::InvalidTransactions
+            | near_chain::Error::InvalidReceiptsProof
+            | near_chain::Error::InvalidStatePayload
+            | near_chain::Error::InvalidChallenge => {
+                metrics::CHUNK_STATE_TRANSITION_PROOFS_TOTAL
+                    .with_label_values(&[&shard_id.to_string(), "malformed"])
+                    .inc();
+                self.ban_peer(peer_id, ReasonForBan::Abusive);
+            }
+            _ => {}
+        }
+    }
+
     pub fn request_missing_chunks(
         &mut self,
         blocks_missing_chunks: Vec<BlockMissingChunks>,
@@ -2131,7 +2446,7 @@ impl Client {
                     HashMap::new()
                 }
             };
-            let state_sync_timeout = self.config.state_sync_timeout;
+            let state_sync_timeout = self.config.state_sync_timeout.clone();
             let epoch_id = self.chain.get_block(&sync_hash)?.header().epoch_id().clone();
             let (state_sync, new_shard_sync, blocks_catch_up_state) =
                 self.catchup_state_syncs.entry(sync_hash).or_insert_with(|| {
@@ -2193,29 +2508,39 @@ impl Client {
     }
 
     /// When accepting challenge, we verify that it's valid given signature with current validators.
-    pub fn process_challenge(&mut self, _challenge: Challenge) -> Result<(), Error> {
-        // TODO(2445): Enable challenges when they are working correctly.
-        //        if self.challenges.contains_key(&challenge.hash) {
-        //            return Ok(());
-        //        }
-        //        debug!(target: "client", "Received challenge: {:?}", challenge);
-        //        let head = self.chain.head()?;
-        //        if self.runtime_adapter.verify_validator_or_fisherman_signature(
-        //            &head.epoch_id,
-        //            &head.prev_block_hash,
-        //            &challenge.account_id,
-        //            challenge.hash.as_ref(),
-        //            &challenge.signature,
-        //        )? {
-        //            // If challenge is not double sign, we should process it right away to invalidate the chain.
-        //            match challenge.body {
-        //                ChallengeBody::BlockDoubleSign(_) => {}
-        //                _ => {
-        //                    self.chain.process_challenge(&challenge);
-        //                }
-        //            }
-        //            self.challenges.insert(challenge.hash, challenge);
-        //        }
+    pub fn process_challenge(&mut self, challenge: Challenge) -> Result<(), Error> {
+        if self.challenges.contains_key(&challenge.hash) {
+            return Ok(());
+        }
+        let head = self.chain.head()?;
+        if let ChallengeBody::ChunkStateV2(_) = challenge.body {
+            let protocol_version =
+                self.runtime_adapter.get_epoch_protocol_version(&head.epoch_id)?;
+            if !checked_feature!(
+                "protocol_feature_chunk_state_challenges",
+                ChunkStateChallenges,
+                protocol_version
+            ) {
+                return Ok(());
+            }
+        }
+        debug!(target: "client", "Received challenge: {:?}", challenge);
+        if self.runtime_adapter.verify_validator_or_fisherman_signature(
+            &head.epoch_id,
+            &head.last_block_hash,
+            &challenge.account_id,
+            challenge.hash.as_ref(),
+            &challenge.signature,
+        )? {
+            // If challenge is not double sign, we should process it right away to invalidate the chain.
+            match challenge.body {
+                ChallengeBody::BlockDoubleSign(_) => {}
+                _ => {
+                    self.chain.process_challenge(&challenge);
+                }
+            }
+            self.challenges.insert(challenge.hash, challenge);
+        }
         Ok(())
     }
 
@@ -2230,11 +2555,35 @@ impl Client {
         Ok(result)
     }
 
+    /// Starts rebuilding view trie snapshots of shards tracked by this node at
+    /// the last final block if snapshots are enabled and it’s time to do so.
+    fn maybe_refresh_view_trie_snapshots(
+        &self,
+        last_final_block: &CryptoHash,
+    ) -> Result<(), Error> {
+        let tries = self.runtime_adapter.get_tries();
+        if last_final_block == &CryptoHash::default() || !tries.view_snapshots_refresh_due() {
+            return Ok(());
+        }
+        let epoch_id = self.chain.get_block_header(last_final_block)?.epoch_id().clone();
+        let shard_layout = self.runtime_adapter.get_shard_layout(&epoch_id)?;
+        let mut state_roots = vec![];
+        for shard_uid in shard_layout.get_shard_uids() {
+            match self.chain.get_chunk_extra(last_final_block, &shard_uid) {
+                Ok(chunk_extra) => state_roots.push((shard_uid, *chunk_extra.state_root())),
+                Err(near_chain::Error::DBNotFoundErr(_)) => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        tries.refresh_view_snapshots(state_roots);
+        Ok(())
+    }
+
     fn clear_data(&mut self) -> Result<(), near_chain::Error> {
         // A RPC node should do regular garbage collection.
         if !self.config.archive {
             let tries = self.runtime_adapter.get_tries();
-            return self.chain.clear_data(tries, &self.config.gc);
+            return self.chain.clear_data(tries, &self.config.gc.get());
         }
 
         // An archival node with split storage should perform garbage collection
@@ -2245,12 +2594,12 @@ impl Client {
         let kind = store.get_db_kind()?;
         if kind == Some(DbKind::Hot) {
             let tries = self.runtime_adapter.get_tries();
-            return self.chain.clear_data(tries, &self.config.gc);
+            return self.chain.clear_data(tries, &self.config.gc.get());
         }
 
         // An archival node with legacy storage or in the midst of migration to split
         // storage should do the legacy clear_archive_data.
-        self.chain.clear_archive_data(self.config.gc.gc_blocks_limit)
+        self.chain.clear_archive_data(self.config.gc.get().gc_blocks_limit)
     }
 }
 
@@ -2375,7 +2724,7 @@ impl Client {
         // convert config tracked shards
         // runtime will track all shards if config tracked shards is not empty
         // https://github.com/near/nearcore/issues/4930
-        let tracked_shards = if self.config.tracked_shards.is_empty() {
+        let tracked_shards = if self.config.tracked_shards.get().is_empty() {
             vec![]
         } else {
             let num_shards = self.runtime_adapter.num_shards(&tip.epoch_id)?;
@@ -7,10 +7,11 @@
 
 use crate::adapter::{
     BlockApproval, BlockHeadersResponse, BlockResponse, ProcessTxRequest, ProcessTxResponse,
-    RecvChallenge, RecvPartialEncodedChunk, RecvPartialEncodedChunkForward,
-    RecvPartialEncodedChunkRequest, RecvPartialEncodedChunkResponse, SetNetworkInfo, StateResponse,
+    RecvChallenge, RecvChunkStateTransitionProof, RecvPartialEncodedChunk,
+    RecvPartialEncodedChunkForward, RecvPartialEncodedChunkRequest,
+    RecvPartialEncodedChunkResponse, SetNetworkInfo, StateResponse,
 };
-use crate::client::{Client, EPOCH_START_INFO_BLOCKS};
+use crate::client::{ChunkStateTransitionChecked, Client, EPOCH_START_INFO_BLOCKS};
 use crate::config_updater::ConfigUpdater;
 use crate::debug::new_network_info_view;
 use crate::info::{display_sync_status, InfoHelper};
@@ -627,6 +628,63 @@ impl Handler<WithSpanContext<RecvPartialEncodedChunkForward>> for ClientActor {
     }
 }
 
+impl Handler<WithSpanContext<RecvChunkStateTransitionProof>> for ClientActor {
+    type Result = ();
+
+    fn handle(
+        &mut self,
+        msg: WithSpanContext<RecvChunkStateTransitionProof>,
+        ctx: &mut Context<Self>,
+    ) {
+        self.wrap(msg, ctx, "RecvChunkStateTransitionProof", |this, msg| {
+            let RecvChunkStateTransitionProof(proof, peer_id) = msg;
+            let prev_block_hash = proof.prev_block_hash;
+            let chunk_hash = proof.chunk_header.chunk_hash();
+            let addr = this.my_address.clone();
+            let done_callback = Box::new(move |checked: ChunkStateTransitionChecked| {
+                addr.do_send(ChunkStateTransitionCheckedMessage(checked).with_span_context());
+            });
+            if let Err(err) = this.client.process_chunk_state_transition_proof(
+                proof,
+                peer_id.clone(),
+                done_callback,
+            ) {
+                warn!(
+                    target: "client",
+                    ?err,
+                    ?peer_id,
+                    ?prev_block_hash,
+                    ?chunk_hash,
+                    "Invalid state transition proof"
+                );
+            }
+        })
+    }
+}
+
+/// Signals that checking a state transition proof in the rayon thread pool has finished.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct ChunkStateTransitionCheckedMessage(pub ChunkStateTransitionChecked);
+
+impl Handler<WithSpanContext<ChunkStateTransitionCheckedMessage>> for ClientActor {
+    type Result = ();
+
+    fn handle(
+        &mut self,
+        msg: WithSpanContext<ChunkStateTransitionCheckedMessage>,
+        _ctx: &mut Self::Context,
+    ) -> Self::Result {
+        let (_span, msg) = handler_debug_span!(target: "client", msg);
+        let ChunkStateTransitionCheckedMessage(checked) = msg;
+        let peer_id = checked.peer_id.clone();
+        let chunk_hash = checked.chunk_header.chunk_hash();
+        if let Err(err) = self.client.on_chunk_state_transition_checked(checked) {
+            warn!(target: "client", ?err, ?peer_id, ?chunk_hash, "Invalid state transition proof");
+        }
+    }
+}
+
 impl Handler<WithSpanContext<RecvChallenge>> for ClientActor {
     type Result = ();
 
@@ -747,6 +805,10 @@ impl Handler<WithSpanContext<Status>> for ClientActor {
             None => (None, None),
         };
         let node_key = validator_public_key.clone();
+        let pending_validator_public_key = self
+            .config_updater
+            .as_ref()
+            .and_then(|config_updater| config_updater.pending_validator_public_key());
 
         let mut earliest_block_hash = None;
         let mut earliest_block_height = None;
@@ -804,6 +866,7 @@ impl Handler<WithSpanContext<Status>> for ClientActor {
             },
             validator_account_id,
             validator_public_key,
+            pending_validator_public_key,
             node_public_key,
             node_key,
             uptime_sec,
@@ -923,11 +986,11 @@ impl ClientActor {
             debug!(target: "client", "Sending announce account for {}", validator_signer.validator_id());
             self.last_validator_announce_time = Some(now);
 
-            let signature = validator_signer.sign_account_announce(
+            let signature = unwrap_or_return!(validator_signer.sign_account_announce(
                 validator_signer.validator_id(),
                 &self.node_id,
                 &next_epoch_id,
-            );
+            ));
             self.network_adapter.do_send(
                 PeerManagerMessageRequest::NetworkRequests(NetworkRequests::AnnounceAccount(
                     AnnounceAccount {
@@ -1139,6 +1202,11 @@ impl ClientActor {
             config_updater.try_update(&|updateable_client_config| {
                 self.client.update_client_config(updateable_client_config)
             });
+            config_updater.try_update_validator_signer(
+                &|account_id, public_key, check_proposals| {
+                    self.client.validator_key_status(account_id, public_key, check_proposals)
+                },
+            );
         }
 
         // Check block height to trigger expected shutdown
@@ -1,11 +1,26 @@
 use near_chain_configs::UpdateableClientConfig;
+use near_client_primitives::types::Error;
+use near_crypto::PublicKey;
 use near_dyn_configs::{UpdateableConfigLoaderError, UpdateableConfigs};
+use near_primitives::types::AccountId;
+use near_primitives::validator_signer::{UpdateableValidatorSigner, ValidatorSigner};
 use std::sync::Arc;
 use tokio::sync::broadcast::Receiver;
 
 #[derive(Debug)]
 pub enum ClientConfigUpdateError {}
 
+/// Staking status of a validator key, see `Client::validator_key_status()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidatorKeyStatus {
+    /// The key is staked for the epoch of the next block.
+    Active,
+    /// The key is staked for a later epoch or proposed in the current one.
+    Pending,
+    /// The key is neither staked nor proposed.
+    NotStaked,
+}
+
 /// Manages updating the config encapsulating.
 pub struct ConfigUpdater {
     /// Receives config updates while the node is running.
@@ -13,13 +28,31 @@ pub struct ConfigUpdater {
 
     /// Represents the latest Error of reading the dynamically reloadable configs.
     updateable_configs_error: Option<Arc<UpdateableConfigLoaderError>>,
+
+    /// Validator signer shared by the client and the network, whose key gets
+    /// replaced when the validator key is rotated.
+    validator_signer: Option<UpdateableValidatorSigner>,
+
+    /// New validator key which replaces the active one at the epoch boundary
+    /// where it gets staked.
+    pending_validator_signer: Option<Arc<dyn ValidatorSigner>>,
+
+    /// Whether `pending_validator_signer` was found to be staked or proposed.
+    pending_validator_signer_staked: bool,
 }
 
 impl ConfigUpdater {
     pub fn new(
         rx_config_update: Receiver<Result<UpdateableConfigs, Arc<UpdateableConfigLoaderError>>>,
+        validator_signer: Option<UpdateableValidatorSigner>,
     ) -> Self {
-        Self { rx_config_update, updateable_configs_error: None }
+        Self {
+            rx_config_update,
+            updateable_configs_error: None,
+            validator_signer,
+            pending_validator_signer: None,
+            pending_validator_signer_staked: false,
+        }
     }
 
     /// Check if any of the configs were updated.
@@ -32,6 +65,7 @@ impl ConfigUpdater {
                         update_client_config_fn(client_config);
                         tracing::info!(target: "config", "Updated ClientConfig");
                     }
+                    self.set_pending_validator_signer(updateable_configs.validator_signer);
                     self.updateable_configs_error = None;
                 }
                 Err(err) => {
@@ -41,6 +75,93 @@ impl ConfigUpdater {
         }
     }
 
+    fn set_pending_validator_signer(&mut self, signer: Option<Arc<dyn ValidatorSigner>>) {
+        let (active, signer) = match (&self.validator_signer, signer) {
+            (Some(active), Some(signer)) => (active, signer),
+            (None, Some(_)) => {
+                tracing::warn!(target: "config", "Node was started without a validator key, restart it to start validating");
+                return;
+            }
+            // Removing the validator key requires a restart as well.
+            (_, None) => return,
+        };
+        if signer.validator_id() != active.validator_id() {
+            tracing::error!(
+                target: "config",
+                active = %active.validator_id(),
+                new = %signer.validator_id(),
+                "Validator account can't change while the node is running, ignoring the new validator key");
+            return;
+        }
+        let public_key = signer.public_key();
+        if public_key == active.public_key() {
+            // Also cancels a pending rotation when the old key is restored.
+            self.pending_validator_signer = None;
+            return;
+        }
+        if self.pending_validator_signer.as_ref().map(|pending| pending.public_key())
+            == Some(public_key.clone())
+        {
+            return;
+        }
+        tracing::info!(target: "config", %public_key, "Loaded new validator key");
+        self.pending_validator_signer = Some(signer);
+        self.pending_validator_signer_staked = false;
+    }
+
+    /// Replaces the active validator key with the pending one as soon as it's
+    /// staked for the epoch of the next block.  A pending key which is neither
+    /// staked nor proposed when it's loaded is rejected.
+    pub fn try_update_validator_signer(
+        &mut self,
+        validator_key_status_fn: &dyn Fn(
+            &AccountId,
+            &PublicKey,
+            bool,
+        ) -> Result<ValidatorKeyStatus, Error>,
+    ) {
+        let (active, signer) = match (&self.validator_signer, &self.pending_validator_signer) {
+            (Some(active), Some(signer)) => (active, signer),
+            _ => return,
+        };
+        let public_key = signer.public_key();
+        // Proposals only need to be checked once, after that the key has to
+        // show up among the validators of the next epochs.
+        let check_proposals = !self.pending_validator_signer_staked;
+        match validator_key_status_fn(active.validator_id(), &public_key, check_proposals) {
+            Ok(ValidatorKeyStatus::Active) => {
+                tracing::info!(
+                    target: "config",
+                    old_public_key = %active.public_key(),
+                    new_public_key = %public_key,
+                    "Switched to the new validator key");
+                active.update(signer.clone());
+                self.pending_validator_signer = None;
+            }
+            Ok(ValidatorKeyStatus::Pending) if check_proposals => {
+                tracing::info!(target: "config", %public_key, "New validator key will be used from the epoch it's staked for");
+                self.pending_validator_signer_staked = true;
+            }
+            Ok(ValidatorKeyStatus::NotStaked) if check_proposals => {
+                tracing::error!(
+                    target: "config",
+                    account_id = %active.validator_id(),
+                    %public_key,
+                    "New validator key doesn't match any stake or proposal of the account, ignoring it");
+                self.pending_validator_signer = None;
+            }
+            Ok(_) => {}
+            Err(err) => {
+                tracing::debug!(target: "config", ?err, "Failed to check the new validator key");
+            }
+        }
+    }
+
+    /// Public key which replaces the active validator key once it's staked.
+    pub fn pending_validator_public_key(&self) -> Option<PublicKey> {
+        self.pending_validator_signer.as_ref().map(|signer| signer.public_key())
+    }
+
     /// Prints an error if it's present.
     pub fn report_status(&self) {
         if let Some(updateable_configs_error) = &self.updateable_configs_error {
@@ -49,5 +170,127 @@ impl ConfigUpdater {
                 "Dynamically updateable configs are not valid. Please fix this ASAP otherwise the node will probably crash after restart: {}",
                 *updateable_configs_error);
         }
+        if let Some(public_key) = self.pending_validator_public_key() {
+            tracing::info!(target: "stats", %public_key, "New validator key is waiting for the epoch it's staked for");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_crypto::KeyType;
+    use near_primitives::validator_signer::InMemoryValidatorSigner;
+    use std::cell::Cell;
+    use tokio::sync::broadcast;
+
+    fn signer(account_id: &str, seed: &str) -> Arc<dyn ValidatorSigner> {
+        Arc::new(InMemoryValidatorSigner::from_seed(
+            account_id.parse().unwrap(),
+            KeyType::ED25519,
+            seed,
+        ))
+    }
+
+    fn config_updater(
+        validator_signer: Option<UpdateableValidatorSigner>,
+    ) -> (
+        broadcast::Sender<Result<UpdateableConfigs, Arc<UpdateableConfigLoaderError>>>,
+        ConfigUpdater,
+    ) {
+        let (tx, rx) = broadcast::channel(16);
+        (tx, ConfigUpdater::new(rx, validator_signer))
+    }
+
+    #[test]
+    fn test_set_pending_validator_signer() {
+        let active = UpdateableValidatorSigner::new(signer("test0", "test0"));
+        let (_tx, mut updater) = config_updater(Some(active.clone()));
+
+        // Neither the active key nor a key of another account become pending.
+        updater.set_pending_validator_signer(Some(signer("test0", "test0")));
+        assert_eq!(updater.pending_validator_public_key(), None);
+        updater.set_pending_validator_signer(Some(signer("test1", "test1")));
+        assert_eq!(updater.pending_validator_public_key(), None);
+
+        let new_signer = signer("test0", "new");
+        updater.set_pending_validator_signer(Some(new_signer.clone()));
+        assert_eq!(updater.pending_validator_public_key(), Some(new_signer.public_key()));
+        // A missing key leaves the pending one alone.
+        updater.set_pending_validator_signer(None);
+        assert_eq!(updater.pending_validator_public_key(), Some(new_signer.public_key()));
+        // Restoring the active key cancels the rotation.
+        updater.set_pending_validator_signer(Some(signer("test0", "test0")));
+        assert_eq!(updater.pending_validator_public_key(), None);
+        assert_eq!(active.public_key(), signer("test0", "test0").public_key());
+
+        // A node started without a validator key ignores new ones.
+        let (_tx, mut updater) = config_updater(None);
+        updater.set_pending_validator_signer(Some(new_signer));
+        assert_eq!(updater.pending_validator_public_key(), None);
+    }
+
+    #[test]
+    fn test_try_update_validator_signer() {
+        let active = UpdateableValidatorSigner::new(signer("test0", "test0"));
+        let new_signer = signer("test0", "new");
+        let (_tx, mut updater) = config_updater(Some(active.clone()));
+        updater.set_pending_validator_signer(Some(new_signer.clone()));
+
+        // Proposals are only checked until the key is found to be staked.
+        let status = Cell::new(ValidatorKeyStatus::Pending);
+        let checked_proposals = Cell::new(false);
+        let status_fn = |account_id: &AccountId, public_key: &PublicKey, check_proposals| {
+            assert_eq!(account_id.as_str(), "test0");
+            assert_eq!(public_key, &new_signer.public_key());
+            checked_proposals.set(check_proposals);
+            Ok::<_, Error>(status.get())
+        };
+        updater.try_update_validator_signer(&status_fn);
+        assert!(checked_proposals.get());
+        assert_eq!(updater.pending_validator_public_key(), Some(new_signer.public_key()));
+
+        // The key may go missing from the next epochs until it's active, e.g.
+        // while the chain is syncing, without being dropped.
+        status.set(ValidatorKeyStatus::NotStaked);
+        updater.try_update_validator_signer(&status_fn);
+        assert!(!checked_proposals.get());
+        assert_eq!(updater.pending_validator_public_key(), Some(new_signer.public_key()));
+        assert_eq!(active.public_key(), signer("test0", "test0").public_key());
+
+        status.set(ValidatorKeyStatus::Active);
+        updater.try_update_validator_signer(&status_fn);
+        assert_eq!(updater.pending_validator_public_key(), None);
+        assert_eq!(active.public_key(), new_signer.public_key());
+    }
+
+    #[test]
+    fn test_try_update_validator_signer_not_staked() {
+        let active = UpdateableValidatorSigner::new(signer("test0", "test0"));
+        let (_tx, mut updater) = config_updater(Some(active.clone()));
+        updater.set_pending_validator_signer(Some(signer("test0", "new")));
+
+        // Errors are retried.
+        updater.try_update_validator_signer(&|_, _, _| Err(Error::Other("test".to_string())));
+        assert!(updater.pending_validator_public_key().is_some());
+
+        updater.try_update_validator_signer(&|_, _, _| Ok(ValidatorKeyStatus::NotStaked));
+        assert_eq!(updater.pending_validator_public_key(), None);
+        assert_eq!(active.public_key(), signer("test0", "test0").public_key());
+    }
+
+    #[test]
+    fn test_try_update_without_client_config() {
+        let active = UpdateableValidatorSigner::new(signer("test0", "test0"));
+        let new_signer = signer("test0", "new");
+        let (tx, mut updater) = config_updater(Some(active));
+        assert!(tx
+            .send(Ok(UpdateableConfigs {
+                validator_signer: Some(new_signer.clone()),
+                ..Default::default()
+            }))
+            .is_ok());
+        updater.try_update(&|_| panic!("there is no client config to update"));
+        assert_eq!(updater.pending_validator_public_key(), Some(new_signer.public_key()));
     }
 }
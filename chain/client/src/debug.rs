@@ -8,7 +8,8 @@ use near_chain::crypto_hash_timer::CryptoHashTimer;
 use near_chain::{near_chain_primitives, Chain, ChainStoreAccess, RuntimeWithEpochManagerAdapter};
 use near_client_primitives::debug::{
     ApprovalAtHeightStatus, BlockProduction, ChunkCollection, DebugBlockStatusData, DebugStatus,
-    DebugStatusResponse, MissedHeightInfo, ProductionAtHeight, ValidatorStatus,
+    DebugStatusResponse, DoomslugHeightView, DoomslugStatusView, MissedHeightInfo,
+    ProductionAtHeight, ValidatorStatus,
 };
 use near_client_primitives::types::Error;
 use near_client_primitives::{
@@ -47,6 +48,10 @@ const DEBUG_PRODUCTION_OLD_BLOCKS_TO_SHOW: u64 = 50;
 // Maximum number of blocks to show.
 const DEBUG_MAX_PRODUCTION_BLOCKS_TO_SHOW: u64 = 1000;
 
+// How many old heights (before HEAD) should be shown in doomslug debug page. Doomslug doesn't keep
+// approvals for older heights anyway.
+const DEBUG_DOOMSLUG_OLD_HEIGHTS_TO_SHOW: u64 = 20;
+
 /// Number of blocks (and chunks) for which to keep the detailed timing information for debug purposes.
 pub const PRODUCTION_TIMES_CACHE_SIZE: usize = 1000;
 
@@ -178,6 +183,17 @@ impl Handler<WithSpanContext<DebugStatus>> for ClientActor {
             DebugStatus::ChainProcessingStatus => Ok(DebugStatusResponse::ChainProcessingStatus(
                 self.client.chain.get_chain_processing_info(),
             )),
+            DebugStatus::ValidatorKickoutPrediction => {
+                let head = self.client.chain.head()?;
+                Ok(DebugStatusResponse::ValidatorKickoutPrediction(
+                    self.client
+                        .runtime_adapter
+                        .get_validator_kickout_prediction(&head.last_block_hash)?,
+                ))
+            }
+            DebugStatus::DoomslugStatus => {
+                Ok(DebugStatusResponse::DoomslugStatus(self.get_doomslug_status()?))
+            }
         }
     }
 }
@@ -519,6 +535,63 @@ impl ClientActor {
         })
     }
 
+    /// Returns the doomslug timers together with the approvals received and the block production
+    /// readiness for recent heights and the heights we've already received approvals for.
+    fn get_doomslug_status(&mut self) -> Result<DoomslugStatusView, near_chain_primitives::Error> {
+        let head = self.client.chain.head()?;
+        let max_height = self
+            .client
+            .doomslug
+            .get_largest_approval_height()
+            .clamp(head.height + 1, head.height + DEBUG_MAX_PRODUCTION_BLOCKS_TO_SHOW);
+
+        let mut heights = vec![];
+        // A height without a block belongs to the epoch following the last
+        // block before it.  Going from the top, that's the previous block of
+        // the closest block above or the head for future heights.
+        let mut prev_block_hash = head.last_block_hash;
+        for height in
+            (head.height.saturating_sub(DEBUG_DOOMSLUG_OLD_HEIGHTS_TO_SHOW)..=max_height).rev()
+        {
+            let block_hash = self.client.chain.get_block_hash_by_height(height).ok();
+            let header = self.client.chain.get_block_header_by_height(height).ok();
+            let approvals = self.client.doomslug.approvals_debug_view_at_height(height);
+            let last_readiness_check = self.client.doomslug.readiness_check_at_height(height);
+            // Future heights are only interesting once we've heard about them.
+            if height > head.height && approvals.is_empty() && last_readiness_check.is_none() {
+                continue;
+            }
+            let epoch_id = match &header {
+                Some(header) => Ok(header.epoch_id().clone()),
+                None => self.client.runtime_adapter.get_epoch_id_from_prev_block(&prev_block_hash),
+            };
+            if let Some(header) = &header {
+                prev_block_hash = *header.prev_hash();
+            }
+            heights.push(DoomslugHeightView {
+                height,
+                block_producer: epoch_id
+                    .and_then(|epoch_id| {
+                        self.client.runtime_adapter.get_block_producer(&epoch_id, height)
+                    })
+                    .ok(),
+                block_hash,
+                block_production_time: self
+                    .client
+                    .block_production_info
+                    .get(height)
+                    .block_production_time,
+                approvals,
+                last_readiness_check,
+            });
+        }
+
+        Ok(DoomslugStatusView {
+            timer: self.client.doomslug.get_timer_status(Clock::instant()),
+            heights,
+        })
+    }
+
     /// Returns debugging information about the validator - including things like which approvals were received, which blocks/chunks will be
     /// produced and some detailed timing information.
     fn get_validator_status(&mut self) -> Result<ValidatorStatus, near_chain_primitives::Error> {
@@ -381,10 +381,12 @@ impl InfoHelper {
         };
         // Sign telemetry if there is a signer present.
         if let Some(vs) = self.validator_signer.as_ref() {
-            vs.sign_telemetry(&info)
-        } else {
-            serde_json::to_value(&info).expect("Telemetry must serialize to json")
+            match vs.sign_telemetry(&info) {
+                Ok(value) => return value,
+                Err(err) => tracing::warn!(target: "stats", ?err, "Failed to sign telemetry"),
+            }
         }
+        serde_json::to_value(&info).expect("Telemetry must serialize to json")
     }
 
     fn log_chain_processing_info(&mut self, client: &crate::Client, epoch_id: &EpochId) {
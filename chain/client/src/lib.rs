@@ -1,11 +1,12 @@
 pub use near_client_primitives::types::{
-    Error, GetBlock, GetBlockProof, GetBlockProofResponse, GetBlockWithMerkleTree, GetChunk,
-    GetClientConfig, GetExecutionOutcome, GetExecutionOutcomeResponse,
+    Error, EstimateGas, GetBlock, GetBlockProof, GetBlockProofResponse, GetBlockWithMerkleTree,
+    GetChunk, GetClientConfig, GetExecutionOutcome, GetExecutionOutcomeResponse,
     GetExecutionOutcomesForBlock, GetGasPrice, GetMaintenanceWindows, GetNetworkInfo,
     GetNextLightClientBlock, GetProtocolConfig, GetReceipt, GetStateChanges,
     GetStateChangesInBlock, GetStateChangesWithCauseInBlock,
-    GetStateChangesWithCauseInBlockForTrackedShards, GetValidatorInfo, GetValidatorOrdered, Query,
-    QueryError, Status, StatusResponse, SyncStatus, TxStatus, TxStatusError,
+    GetStateChangesWithCauseInBlockForTrackedShards, GetValidatorInfo, GetValidatorOrdered,
+    GetValidatorRewards, Query, QueryError, Status, StatusResponse, SyncStatus, TraceCall,
+    TraceTransaction, TxStatus, TxStatusError,
 };
 
 pub use near_client_primitives::debug::DebugStatus;
@@ -15,7 +16,7 @@ pub use crate::adapter::{
 };
 pub use crate::client::Client;
 pub use crate::client_actor::{start_client, ClientActor};
-pub use crate::config_updater::ConfigUpdater;
+pub use crate::config_updater::{ConfigUpdater, ValidatorKeyStatus};
 pub use crate::view_client::{start_view_client, ViewClientActor};
 
 pub mod adapter;
@@ -156,6 +156,15 @@ pub(crate) static CHUNK_SKIPPED_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
     .unwrap()
 });
 
+pub(crate) static CHUNK_STATE_TRANSITION_PROOFS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "near_chunk_state_transition_proofs_total",
+        "Number of checked state transition proofs received from chunk producers",
+        &["shard_id", "result"],
+    )
+    .unwrap()
+});
+
 pub(crate) static CHUNK_PRODUCER_BANNED_FOR_EPOCH: Lazy<IntCounter> = Lazy::new(|| {
     try_create_int_counter(
         "near_chunk_producer_banned_for_epoch",
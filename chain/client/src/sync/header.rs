@@ -668,6 +668,22 @@ mod test {
                         last_block.header().next_epoch_id().clone(),
                     )
                 };
+            let approvals = signers2
+                .iter()
+                .map(|signer| {
+                    Some(
+                        Approval::new(
+                            *last_block.hash(),
+                            last_block.header().height(),
+                            this_height,
+                            &epoch_id,
+                            signer.as_ref(),
+                        )
+                        .unwrap()
+                        .signature,
+                    )
+                })
+                .collect();
             let block = Block::produce(
                 PROTOCOL_VERSION,
                 PROTOCOL_VERSION,
@@ -678,20 +694,7 @@ mod test {
                 epoch_id,
                 next_epoch_id,
                 None,
-                signers2
-                    .iter()
-                    .map(|signer| {
-                        Some(
-                            Approval::new(
-                                *last_block.hash(),
-                                last_block.header().height(),
-                                this_height,
-                                signer.as_ref(),
-                            )
-                            .signature,
-                        )
-                    })
-                    .collect(),
+                approvals,
                 Ratio::new(0, 1),
                 0,
                 100,
@@ -702,7 +705,8 @@ mod test {
                 *last_block.header().next_bp_hash(),
                 block_merkle_tree.root(),
                 None,
-            );
+            )
+            .unwrap();
             block_merkle_tree.insert(*block.hash());
             chain2.process_block_header(block.header(), &mut Vec::new()).unwrap(); // just to validate
             process_block_sync(
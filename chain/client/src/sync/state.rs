@@ -21,6 +21,7 @@
 //!
 
 use near_chain::{near_chain_primitives, Error};
+use near_chain_configs::MutableConfigValue;
 use near_primitives::state_part::PartId;
 use std::collections::HashMap;
 use std::ops::Add;
@@ -109,7 +110,7 @@ pub struct StateSync {
 
     /// Timeout (set in config - by default to 60 seconds) is used to figure out how long we should wait
     /// for the answer from the other node before giving up.
-    timeout: Duration,
+    timeout: MutableConfigValue<TimeDuration>,
 
     /// Maps shard_id to result of applying downloaded state
     state_parts_apply_results: HashMap<ShardId, Result<(), near_chain_primitives::error::Error>>,
@@ -119,18 +120,25 @@ pub struct StateSync {
 }
 
 impl StateSync {
-    pub fn new(network_adapter: Arc<dyn PeerManagerAdapter>, timeout: TimeDuration) -> Self {
+    pub fn new(
+        network_adapter: Arc<dyn PeerManagerAdapter>,
+        timeout: MutableConfigValue<TimeDuration>,
+    ) -> Self {
         StateSync {
             network_adapter,
             last_time_block_requested: None,
             last_part_id_requested: Default::default(),
             requested_target: lru::LruCache::new(MAX_PENDING_PART as usize),
-            timeout: Duration::from_std(timeout).unwrap(),
+            timeout,
             state_parts_apply_results: HashMap::new(),
             split_state_roots: HashMap::new(),
         }
     }
 
+    fn timeout(&self) -> Duration {
+        Duration::from_std(self.timeout.get()).unwrap()
+    }
+
     fn sync_block_status(
         &mut self,
         prev_hash: &CryptoHash,
@@ -141,8 +149,8 @@ impl StateSync {
             match self.last_time_block_requested {
                 None => (true, false),
                 Some(last_time) => {
-                    if now - last_time >= self.timeout {
-                        error!(target: "sync", "State sync: block request for {} timed out in {} seconds", prev_hash, self.timeout.num_seconds());
+                    if now - last_time >= self.timeout() {
+                        error!(target: "sync", "State sync: block request for {} timed out in {} seconds", prev_hash, self.timeout().num_seconds());
                         (true, false)
                     } else {
                         (false, false)
@@ -230,7 +238,7 @@ impl StateSync {
                     } else {
                         let prev = shard_sync_download.downloads[0].prev_update_time;
                         let error = shard_sync_download.downloads[0].error;
-                        download_timeout = now - prev > self.timeout;
+                        download_timeout = now - prev > self.timeout();
                         // Retry in case of timeout or failure.
                         if download_timeout || error {
                             shard_sync_download.downloads[0].run_me.store(true, Ordering::SeqCst);
@@ -250,7 +258,7 @@ impl StateSync {
                             parts_done = false;
                             let prev = part_download.prev_update_time;
                             let error = part_download.error;
-                            let part_timeout = now - prev > self.timeout;
+                            let part_timeout = now - prev > self.timeout();
                             // Retry parts that failed.
                             if part_timeout || error {
                                 download_timeout |= part_timeout;
@@ -385,7 +393,7 @@ impl StateSync {
             all_done &= this_done;
 
             if download_timeout {
-                warn!(target: "sync", "State sync didn't download the state for shard {} in {} seconds, sending StateRequest again", shard_id, self.timeout.num_seconds());
+                warn!(target: "sync", "State sync didn't download the state for shard {} in {} seconds, sending StateRequest again", shard_id, self.timeout().num_seconds());
                 info!(target: "sync", "State sync status: me {:?}, sync_hash {}, phase {}",
                       me,
                       sync_hash,
@@ -478,7 +486,7 @@ impl StateSync {
         // FIXME: something is wrong - the index should have a shard_id too.
         self.requested_target.put((part_id, sync_hash), target.clone());
 
-        let timeout = self.timeout;
+        let timeout = self.timeout();
         self.last_part_id_requested
             .entry((target, shard_id))
             .and_modify(|pending_request| {
@@ -896,7 +904,10 @@ mod test {
     // Start a new state sync - and check that it asks for a header.
     fn test_ask_for_header() {
         let mock_peer_manager = Arc::new(MockPeerManagerAdapter::default());
-        let mut state_sync = StateSync::new(mock_peer_manager.clone(), TimeDuration::from_secs(1));
+        let mut state_sync = StateSync::new(
+            mock_peer_manager.clone(),
+            MutableConfigValue::new(TimeDuration::from_secs(1), "state_sync_timeout"),
+        );
         let mut new_shard_sync = HashMap::new();
 
         let (mut chain, kv, signer) = test_utils::setup();
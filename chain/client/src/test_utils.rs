@@ -224,7 +224,11 @@ pub fn setup(
         runtime.clone(),
         &chain_genesis,
         doomslug_threshold_mode,
-        ChainConfig { save_trie_changes: true, background_migration_threads: 1 },
+        ChainConfig {
+            save_trie_changes: true,
+            background_migration_threads: 1,
+            record_state_transition_proofs: false,
+        },
     )
     .unwrap();
     let genesis_block = chain.get_block(&chain.genesis().hash().clone()).unwrap();
@@ -312,7 +316,11 @@ pub fn setup_only_view(
         runtime.clone(),
         &chain_genesis,
         doomslug_threshold_mode,
-        ChainConfig { save_trie_changes: true, background_migration_threads: 1 },
+        ChainConfig {
+            save_trie_changes: true,
+            background_migration_threads: 1,
+            record_state_transition_proofs: false,
+        },
     )
     .unwrap();
 
@@ -1011,7 +1019,8 @@ pub fn setup_mock_all_validators(
                         NetworkRequests::ForwardTx(_, _)
                         | NetworkRequests::BanPeer { .. }
                         | NetworkRequests::TxStatus(_, _, _)
-                        | NetworkRequests::Challenge(_) => {}
+                        | NetworkRequests::Challenge(_)
+                        | NetworkRequests::ChunkStateTransitionProof { .. } => {}
                     };
                 }
                 resp
@@ -1096,12 +1105,14 @@ pub fn setup_client_with_runtime(
     rng_seed: RngSeed,
     archive: bool,
     save_trie_changes: bool,
+    record_state_transition_proofs: bool,
 ) -> Client {
     let validator_signer =
         account_id.map(|x| Arc::new(create_test_signer(x.as_str())) as Arc<dyn ValidatorSigner>);
     let mut config =
         ClientConfig::test(true, 10, 20, num_validator_seats, archive, save_trie_changes, true);
     config.epoch_length = chain_genesis.epoch_length;
+    config.record_state_transition_proofs = record_state_transition_proofs;
     let mut client = Client::new(
         config,
         chain_genesis,
@@ -1143,6 +1154,7 @@ pub fn setup_client(
         rng_seed,
         archive,
         save_trie_changes,
+        false,
     )
 }
 
@@ -1161,6 +1173,7 @@ pub struct TestEnv {
     seeds: HashMap<AccountId, RngSeed>,
     archive: bool,
     save_trie_changes: bool,
+    record_state_transition_proofs: bool,
 }
 
 /// A builder for the TestEnv structure.
@@ -1175,6 +1188,7 @@ pub struct TestEnvBuilder {
     seeds: HashMap<AccountId, RngSeed>,
     archive: bool,
     save_trie_changes: bool,
+    record_state_transition_proofs: bool,
 }
 
 /// Builder for the [`TestEnv`] structure.
@@ -1193,6 +1207,7 @@ impl TestEnvBuilder {
             seeds,
             archive: false,
             save_trie_changes: true,
+            record_state_transition_proofs: false,
         }
     }
 
@@ -1268,6 +1283,14 @@ impl TestEnvBuilder {
         self
     }
 
+    /// Makes clients record and send state transition proofs of the chunks they apply.  Only
+    /// has an effect together with [`Self::runtime_adapters`], since `KeyValueRuntime` doesn't
+    /// generate storage proofs.
+    pub fn record_state_transition_proofs(mut self, record_state_transition_proofs: bool) -> Self {
+        self.record_state_transition_proofs = record_state_transition_proofs;
+        self
+    }
+
     /// Constructs new `TestEnv` structure.
     ///
     /// If no clients were configured (either through count or vector) one
@@ -1340,6 +1363,7 @@ impl TestEnvBuilder {
                             rng_seed,
                             self.archive,
                             self.save_trie_changes,
+                            self.record_state_transition_proofs,
                         )
                     })
                     .collect()
@@ -1362,6 +1386,7 @@ impl TestEnvBuilder {
             seeds,
             archive: self.archive,
             save_trie_changes: self.save_trie_changes,
+            record_state_transition_proofs: self.record_state_transition_proofs,
         }
     }
 
@@ -1627,6 +1652,8 @@ impl TestEnv {
                     account_id,
                     prefix: vec![].into(),
                     include_proof: false,
+                    start_key: None,
+                    limit: None,
                 },
             )
             .unwrap();
@@ -1666,6 +1693,7 @@ impl TestEnv {
             rng_seed,
             self.archive,
             self.save_trie_changes,
+            self.record_state_transition_proofs,
         )
     }
 
@@ -1827,6 +1855,7 @@ pub fn create_chunk(
             transactions,
             decoded_chunk.receipts(),
             header.outgoing_receipts_root(),
+            last_block.header().epoch_id(),
             &*signer,
             PROTOCOL_VERSION,
         )
@@ -1866,7 +1895,8 @@ pub fn create_chunk(
         *last_block.header().next_bp_hash(),
         block_merkle_tree.root(),
         None,
-    );
+    )
+    .unwrap();
     (chunk, merkle_paths, receipts, block)
 }
 
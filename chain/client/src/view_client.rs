@@ -2,7 +2,7 @@
 //! Useful for querying from RPC.
 
 use actix::{Actor, Addr, Handler, SyncArbiter, SyncContext};
-use near_primitives::receipt::Receipt;
+use near_primitives::receipt::{Receipt, ReceiptEnum};
 use near_primitives::time::Clock;
 use std::cmp::Ordering;
 use std::collections::{HashMap, VecDeque};
@@ -18,14 +18,16 @@ use near_chain::{
 };
 use near_chain_configs::{ClientConfig, ProtocolConfigView};
 use near_client_primitives::types::{
-    Error, GetBlock, GetBlockError, GetBlockProof, GetBlockProofError, GetBlockProofResponse,
-    GetBlockWithMerkleTree, GetChunkError, GetExecutionOutcome, GetExecutionOutcomeError,
-    GetExecutionOutcomesForBlock, GetGasPrice, GetGasPriceError, GetMaintenanceWindows,
-    GetMaintenanceWindowsError, GetNextLightClientBlockError, GetProtocolConfig,
-    GetProtocolConfigError, GetReceipt, GetReceiptError, GetStateChangesError,
-    GetStateChangesWithCauseInBlock, GetStateChangesWithCauseInBlockForTrackedShards,
-    GetValidatorInfoError, Query, QueryError, TxStatus, TxStatusError,
+    Error, EstimateGas, EstimateGasError, GetBlock, GetBlockError, GetBlockProof,
+    GetBlockProofError, GetBlockProofResponse, GetBlockWithMerkleTree, GetChunkError,
+    GetExecutionOutcome, GetExecutionOutcomeError, GetExecutionOutcomesForBlock, GetGasPrice,
+    GetGasPriceError, GetMaintenanceWindows, GetMaintenanceWindowsError,
+    GetNextLightClientBlockError, GetProtocolConfig, GetProtocolConfigError, GetReceipt,
+    GetReceiptError, GetStateChangesError, GetStateChangesWithCauseInBlock,
+    GetStateChangesWithCauseInBlockForTrackedShards, GetValidatorInfoError, Query, QueryError,
+    TraceCall, TraceError, TraceTransaction, TxStatus, TxStatusError,
 };
+use near_crypto::Signature;
 #[cfg(feature = "test_features")]
 use near_network::types::NetworkAdversarialMessage;
 use near_network::types::{
@@ -36,6 +38,7 @@ use near_o11y::{handler_debug_span, OpenTelemetrySpanExt, WithSpanContext, WithS
 use near_performance_metrics_macros::perf;
 use near_primitives::block::{Block, BlockHeader};
 use near_primitives::epoch_manager::epoch_info::EpochInfo;
+use near_primitives::errors::{InvalidAccessKeyError, InvalidTxError};
 use near_primitives::hash::CryptoHash;
 use near_primitives::merkle::{merklize, PartialMerkleTree};
 use near_primitives::network::AnnounceAccount;
@@ -44,16 +47,18 @@ use near_primitives::syncing::{
     ShardStateSyncResponse, ShardStateSyncResponseHeader, ShardStateSyncResponseV1,
     ShardStateSyncResponseV2,
 };
+use near_primitives::transaction::{Action, SignedTransaction, Transaction};
 use near_primitives::types::{
-    AccountId, BlockHeight, BlockId, BlockReference, EpochReference, Finality, MaybeBlockId,
-    ShardId, SyncCheckpoint, TransactionOrReceiptId, ValidatorInfoIdentifier,
+    AccountId, BlockHeight, BlockId, BlockReference, EpochId, EpochReference, Finality,
+    MaybeBlockId, ShardId, SyncCheckpoint, TransactionOrReceiptId, ValidatorInfoIdentifier,
 };
 use near_primitives::views::validator_stake_view::ValidatorStakeView;
 use near_primitives::views::{
-    BlockView, ChunkView, EpochValidatorInfo, ExecutionOutcomeWithIdView,
-    FinalExecutionOutcomeView, FinalExecutionOutcomeViewEnum, GasPriceView, LightClientBlockView,
-    MaintenanceWindowsView, QueryRequest, QueryResponse, ReceiptView, StateChangesKindsView,
-    StateChangesView,
+    BlockView, ChunkView, EpochRewardsView, EpochValidatorInfo, ExecutionOutcomeWithIdView,
+    ExecutionStatusView, FinalExecutionOutcomeView, FinalExecutionOutcomeViewEnum,
+    FinalExecutionStatus, FunctionCallTraceView, GasEstimateView, GasPriceView,
+    LightClientBlockView, MaintenanceWindowsView, QueryRequest, QueryResponse, QueryResponseKind,
+    ReceiptExecutionTraceView, ReceiptView, StateChangesKindsView, StateChangesView,
 };
 
 use crate::adapter::{
@@ -62,13 +67,17 @@ use crate::adapter::{
 };
 use crate::{
     metrics, sync, GetChunk, GetExecutionOutcomeResponse, GetNextLightClientBlock, GetStateChanges,
-    GetStateChangesInBlock, GetValidatorInfo, GetValidatorOrdered,
+    GetStateChangesInBlock, GetValidatorInfo, GetValidatorOrdered, GetValidatorRewards,
 };
 
 /// Max number of queries that we keep.
 const QUERY_REQUEST_LIMIT: usize = 500;
 /// Waiting time between requests, in ms
 const REQUEST_WAIT_TIME: u64 = 1000;
+/// Max depth of the receipt tree followed when tracing a transaction.
+const MAX_TRACE_RECEIPT_DEPTH: usize = 32;
+/// Max number of chunks re-applied when tracing a transaction.
+const MAX_TRACED_CHUNKS: usize = 64;
 
 const POISONED_LOCK_ERR: &str = "The lock was poisoned.";
 
@@ -158,6 +167,30 @@ impl ViewClientActor {
         }
     }
 
+    /// Returns the hash of the last block of a finished epoch, walking back
+    /// from the head one epoch at a time.
+    fn get_epoch_last_block_hash(
+        &self,
+        epoch_id: &EpochId,
+    ) -> Result<CryptoHash, GetValidatorInfoError> {
+        let head = self.chain.head()?;
+        if &head.epoch_id == epoch_id {
+            return Err(GetValidatorInfoError::ValidatorInfoUnavailable);
+        }
+        let mut block_hash = head.last_block_hash;
+        loop {
+            let epoch_first_block =
+                *self.runtime_adapter.get_block_info(&block_hash)?.epoch_first_block();
+            block_hash = *self.runtime_adapter.get_block_info(&epoch_first_block)?.prev_hash();
+            if block_hash == CryptoHash::default() {
+                return Err(GetValidatorInfoError::UnknownEpoch);
+            }
+            if self.runtime_adapter.get_block_info(&block_hash)?.epoch_id() == epoch_id {
+                return Ok(block_hash);
+            }
+        }
+    }
+
     fn need_request<K: Hash + Eq + Clone>(key: K, cache: &mut lru::LruCache<K, Instant>) -> bool {
         let now = Clock::instant();
         let need_request = match cache.get(&key) {
@@ -298,6 +331,274 @@ impl ViewClientActor {
         Ok(windows)
     }
 
+    fn trace_call(&self, msg: TraceCall) -> Result<FunctionCallTraceView, TraceError> {
+        let header = match self.get_block_header_by_reference(&msg.block_reference) {
+            Ok(Some(header)) => header,
+            Ok(None) | Err(near_chain::near_chain_primitives::Error::DBNotFoundErr(_)) => {
+                return Err(TraceError::UnknownBlock(msg.block_reference))
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let shard_id =
+            self.runtime_adapter.account_id_to_shard_id(&msg.account_id, header.epoch_id())?;
+        let shard_uid = self.runtime_adapter.shard_id_to_uid(shard_id, header.epoch_id())?;
+        let chunk_extra =
+            self.chain.get_chunk_extra(header.hash(), &shard_uid).map_err(|err| match err {
+                near_chain::near_chain_primitives::Error::DBNotFoundErr(_) => {
+                    TraceError::UnavailableShard(shard_id)
+                }
+                err => err.into(),
+            })?;
+        self.runtime_adapter
+            .trace_call(
+                shard_uid,
+                chunk_extra.state_root(),
+                header.height(),
+                header.raw_timestamp(),
+                header.prev_hash(),
+                header.hash(),
+                header.epoch_id(),
+                &msg.account_id,
+                &msg.method_name,
+                msg.args.as_ref(),
+                msg.extended_gas_profile,
+            )
+            .map_err(|err| match err {
+                near_chain::near_chain_primitives::error::QueryError::InternalError {
+                    error_message,
+                    ..
+                } => TraceError::IOError(error_message),
+                err => TraceError::InvalidCall(err.to_string()),
+            })
+    }
+
+    /// Re-executes all receipts spawned by the transaction, in the blocks they were executed
+    /// in.  Receipts without function calls are skipped when the node knows their contents.
+    /// Fails with `TraceError::LimitExceeded` rather than following more than
+    /// `MAX_TRACE_RECEIPT_DEPTH` levels of receipts or re-applying more than `MAX_TRACED_CHUNKS`
+    /// chunks.
+    fn trace_transaction(
+        &self,
+        tx_hash: CryptoHash,
+        extended_gas_profile: bool,
+    ) -> Result<Vec<ReceiptExecutionTraceView>, TraceError> {
+        let tx_outcome = self.chain.get_execution_outcome(&tx_hash).map_err(|err| match err {
+            near_chain::near_chain_primitives::Error::DBNotFoundErr(_) => {
+                TraceError::UnknownTransaction(tx_hash)
+            }
+            err => err.into(),
+        })?;
+        let mut pending: VecDeque<(CryptoHash, usize)> = tx_outcome
+            .outcome_with_id
+            .outcome
+            .receipt_ids
+            .into_iter()
+            .map(|receipt_id| (receipt_id, 1))
+            .collect();
+        let mut traces = vec![];
+        while let Some((receipt_id, depth)) = pending.pop_front() {
+            let outcome = match self.chain.get_execution_outcome(&receipt_id) {
+                Ok(outcome) => outcome,
+                // The receipt has not been executed yet.
+                Err(near_chain::near_chain_primitives::Error::DBNotFoundErr(_)) => continue,
+                Err(err) => return Err(err.into()),
+            };
+            let receipt_ids = outcome.outcome_with_id.outcome.receipt_ids;
+            if !receipt_ids.is_empty() && depth == MAX_TRACE_RECEIPT_DEPTH {
+                return Err(TraceError::LimitExceeded(format!(
+                    "{} levels of receipts",
+                    MAX_TRACE_RECEIPT_DEPTH
+                )));
+            }
+            pending.extend(receipt_ids.into_iter().map(|receipt_id| (receipt_id, depth + 1)));
+            // Local receipts are not stored separately, so they are always traced.
+            if let Some(receipt) = self.chain.store().get_receipt(&receipt_id)? {
+                let has_function_call = match &receipt.receipt {
+                    ReceiptEnum::Action(action_receipt) => action_receipt
+                        .actions
+                        .iter()
+                        .any(|action| matches!(action, Action::FunctionCall(_))),
+                    ReceiptEnum::Data(_) => false,
+                };
+                if !has_function_call {
+                    continue;
+                }
+            }
+            if traces.len() == MAX_TRACED_CHUNKS {
+                return Err(TraceError::LimitExceeded(format!(
+                    "{} re-applied chunks",
+                    MAX_TRACED_CHUNKS
+                )));
+            }
+            traces.push(self.chain.trace_receipt(&receipt_id, extended_gas_profile)?);
+        }
+        Ok(traces)
+    }
+
+    /// Executes the transaction on top of the chain head.  Only outcomes of the receipts spawned
+    /// by the transaction are returned; if they didn't finish within the simulated blocks, the
+    /// status is `Started`.
+    fn estimate_gas(&self, msg: EstimateGas) -> Result<GasEstimateView, EstimateGasError> {
+        let head = self.chain.head().map_err(|err| match err {
+            near_chain::near_chain_primitives::Error::DBNotFoundErr(_) => {
+                EstimateGasError::NoSyncedBlocks
+            }
+            err => err.into(),
+        })?;
+        let header = self.chain.get_block_header(&head.last_block_hash)?;
+        let epoch_id = header.epoch_id();
+        let num_shards = self.runtime_adapter.num_shards(epoch_id)?;
+        let state_roots = (0..num_shards)
+            .map(|shard_id| {
+                let shard_uid = self.runtime_adapter.shard_id_to_uid(shard_id, epoch_id)?;
+                match self.chain.get_chunk_extra(header.hash(), &shard_uid) {
+                    Ok(chunk_extra) => Ok(*chunk_extra.state_root()),
+                    Err(near_chain::near_chain_primitives::Error::DBNotFoundErr(_)) => {
+                        Err(EstimateGasError::UnavailableShard(shard_id))
+                    }
+                    Err(err) => Err(err.into()),
+                }
+            })
+            .collect::<Result<Vec<_>, EstimateGasError>>()?;
+        let signer_shard_id =
+            self.runtime_adapter.account_id_to_shard_id(&msg.signer_id, epoch_id)?;
+        let signer_state_root = state_roots[signer_shard_id as usize];
+
+        let nonce = match msg.nonce {
+            Some(nonce) => nonce,
+            None => {
+                let shard_uid = self.runtime_adapter.shard_id_to_uid(signer_shard_id, epoch_id)?;
+                let request = QueryRequest::ViewAccessKey {
+                    account_id: msg.signer_id.clone(),
+                    public_key: msg.public_key.clone(),
+                };
+                match self.runtime_adapter.query(
+                    shard_uid,
+                    &signer_state_root,
+                    header.height(),
+                    header.raw_timestamp(),
+                    header.prev_hash(),
+                    header.hash(),
+                    epoch_id,
+                    &request,
+                ) {
+                    Ok(QueryResponse {
+                        kind: QueryResponseKind::AccessKey(access_key), ..
+                    }) => access_key.nonce + 1,
+                    Ok(response) => {
+                        return Err(EstimateGasError::Unreachable(format!(
+                            "Unexpected response to an access key query: {:?}",
+                            response.kind
+                        )))
+                    }
+                    Err(
+                        near_chain::near_chain_primitives::error::QueryError::UnknownAccessKey {
+                            ..
+                        },
+                    ) => {
+                        return Err(EstimateGasError::InvalidTransaction(
+                            InvalidTxError::InvalidAccessKeyError(
+                                InvalidAccessKeyError::AccessKeyNotFound {
+                                    account_id: msg.signer_id,
+                                    public_key: msg.public_key,
+                                },
+                            ),
+                        ))
+                    }
+                    Err(err) => return Err(EstimateGasError::Unreachable(err.to_string())),
+                }
+            }
+        };
+        let transaction = SignedTransaction::new(
+            Signature::empty(msg.public_key.key_type()),
+            Transaction {
+                signer_id: msg.signer_id,
+                public_key: msg.public_key,
+                nonce,
+                receiver_id: msg.receiver_id,
+                block_hash: *header.hash(),
+                actions: msg.actions,
+            },
+        );
+        let protocol_version = self.runtime_adapter.get_epoch_protocol_version(epoch_id)?;
+        if let Some(err) = self.runtime_adapter.validate_tx(
+            header.gas_price(),
+            Some(signer_state_root),
+            &transaction,
+            false,
+            epoch_id,
+            protocol_version,
+        )? {
+            return Err(EstimateGasError::InvalidTransaction(err));
+        }
+
+        let outcomes = self.runtime_adapter.estimate_gas(
+            &transaction,
+            &state_roots,
+            header.height(),
+            header.raw_timestamp(),
+            header.hash(),
+            header.gas_price(),
+            *header.random_value(),
+        )?;
+        let mut outcomes: HashMap<CryptoHash, ExecutionOutcomeWithIdView> = outcomes
+            .into_iter()
+            .map(|outcome| {
+                let view = ExecutionOutcomeWithIdView {
+                    proof: vec![],
+                    block_hash: *header.hash(),
+                    id: outcome.id,
+                    outcome: outcome.outcome.into(),
+                };
+                (outcome.id, view)
+            })
+            .collect();
+        let tx_hash = transaction.get_hash();
+        let transaction_outcome = outcomes.remove(&tx_hash).ok_or_else(|| {
+            EstimateGasError::Unreachable(format!("Transaction {} was not executed", tx_hash))
+        })?;
+        // Same order as in `Chain::get_final_transaction_result`.
+        let mut receipts_outcome = vec![];
+        let mut pending: Vec<CryptoHash> =
+            transaction_outcome.outcome.receipt_ids.iter().rev().copied().collect();
+        while let Some(receipt_id) = pending.pop() {
+            if let Some(outcome) = outcomes.remove(&receipt_id) {
+                pending.extend(outcome.outcome.receipt_ids.iter().rev());
+                receipts_outcome.push(outcome);
+            }
+        }
+
+        let mut looking_for_id = tx_hash;
+        let status = loop {
+            let outcome = std::iter::once(&transaction_outcome)
+                .chain(&receipts_outcome)
+                .find(|outcome| outcome.id == looking_for_id);
+            match outcome.map(|outcome| &outcome.outcome.status) {
+                Some(ExecutionStatusView::SuccessReceiptId(id)) => looking_for_id = *id,
+                Some(ExecutionStatusView::SuccessValue(value)) => {
+                    break FinalExecutionStatus::SuccessValue(value.clone())
+                }
+                Some(ExecutionStatusView::Failure(err)) => {
+                    break FinalExecutionStatus::Failure(err.clone())
+                }
+                Some(ExecutionStatusView::Unknown) | None => break FinalExecutionStatus::Started,
+            }
+        };
+        let all_outcomes = || std::iter::once(&transaction_outcome).chain(&receipts_outcome);
+        let gas_burnt = all_outcomes().map(|outcome| outcome.outcome.gas_burnt).sum();
+        let tokens_burnt = all_outcomes().map(|outcome| outcome.outcome.tokens_burnt).sum();
+        Ok(GasEstimateView {
+            final_outcome: FinalExecutionOutcomeView {
+                status,
+                transaction: transaction.into(),
+                transaction_outcome,
+                receipts_outcome,
+            },
+            gas_burnt,
+            tokens_burnt,
+        })
+    }
+
     fn handle_query(&mut self, msg: Query) -> Result<QueryResponse, QueryError> {
         let header = self.get_block_header_by_reference(&msg.block_reference);
         let header = match header {
@@ -704,6 +1005,54 @@ impl Handler<WithSpanContext<GetValidatorInfo>> for ViewClientActor {
     }
 }
 
+impl Handler<WithSpanContext<GetValidatorRewards>> for ViewClientActor {
+    type Result = Result<EpochRewardsView, GetValidatorInfoError>;
+
+    #[perf]
+    fn handle(
+        &mut self,
+        msg: WithSpanContext<GetValidatorRewards>,
+        _: &mut Self::Context,
+    ) -> Self::Result {
+        let (_span, msg) = handler_debug_span!(target: "client", msg);
+        let _timer = metrics::VIEW_CLIENT_MESSAGE_TIME
+            .with_label_values(&["GetValidatorRewards"])
+            .start_timer();
+        let last_block_hash = match msg.epoch_reference {
+            EpochReference::EpochId(id) => self.get_epoch_last_block_hash(&id)?,
+            EpochReference::BlockId(block_id) => {
+                // Same as for `GetValidatorInfo`, the block has to be the last
+                // block of its epoch.
+                let block_header = match block_id {
+                    BlockId::Hash(h) => self.chain.get_block_header(&h)?,
+                    BlockId::Height(h) => self.chain.get_block_header_by_height(h)?,
+                };
+                let next_block_hash =
+                    self.chain.store().get_next_block_hash(block_header.hash())?;
+                let next_block_header = self.chain.get_block_header(&next_block_hash)?;
+                if block_header.epoch_id() != next_block_header.epoch_id()
+                    && block_header.next_epoch_id() == next_block_header.epoch_id()
+                {
+                    *block_header.hash()
+                } else {
+                    return Err(GetValidatorInfoError::ValidatorInfoUnavailable);
+                }
+            }
+            EpochReference::Latest => {
+                let head = self.chain.head()?;
+                let epoch_first_block = *self
+                    .runtime_adapter
+                    .get_block_info(&head.last_block_hash)?
+                    .epoch_first_block();
+                *self.runtime_adapter.get_block_info(&epoch_first_block)?.prev_hash()
+            }
+        };
+        self.runtime_adapter
+            .get_epoch_rewards(&last_block_hash)
+            .map_err(GetValidatorInfoError::from)
+    }
+}
+
 impl Handler<WithSpanContext<GetValidatorOrdered>> for ViewClientActor {
     type Result = Result<Vec<ValidatorStakeView>, GetValidatorInfoError>;
 
@@ -1422,6 +1771,48 @@ impl Handler<WithSpanContext<GetMaintenanceWindows>> for ViewClientActor {
     }
 }
 
+impl Handler<WithSpanContext<TraceCall>> for ViewClientActor {
+    type Result = Result<FunctionCallTraceView, TraceError>;
+
+    #[perf]
+    fn handle(
+        &mut self,
+        msg: WithSpanContext<TraceCall>,
+        _ctx: &mut Self::Context,
+    ) -> Self::Result {
+        let (_span, msg) = handler_debug_span!(target: "client", msg);
+        self.trace_call(msg)
+    }
+}
+
+impl Handler<WithSpanContext<TraceTransaction>> for ViewClientActor {
+    type Result = Result<Vec<ReceiptExecutionTraceView>, TraceError>;
+
+    #[perf]
+    fn handle(
+        &mut self,
+        msg: WithSpanContext<TraceTransaction>,
+        _ctx: &mut Self::Context,
+    ) -> Self::Result {
+        let (_span, msg) = handler_debug_span!(target: "client", msg);
+        self.trace_transaction(msg.tx_hash, msg.extended_gas_profile)
+    }
+}
+
+impl Handler<WithSpanContext<EstimateGas>> for ViewClientActor {
+    type Result = Result<GasEstimateView, EstimateGasError>;
+
+    #[perf]
+    fn handle(
+        &mut self,
+        msg: WithSpanContext<EstimateGas>,
+        _ctx: &mut Self::Context,
+    ) -> Self::Result {
+        let (_span, msg) = handler_debug_span!(target: "client", msg);
+        self.estimate_gas(msg)
+    }
+}
+
 /// Starts the View Client in a new arbiter (thread).
 pub fn start_view_client(
     validator_account_id: Option<AccountId>,
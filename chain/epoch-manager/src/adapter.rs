@@ -16,7 +16,9 @@ use near_primitives::types::{
     ValidatorInfoIdentifier,
 };
 use near_primitives::version::ProtocolVersion;
-use near_primitives::views::EpochValidatorInfo;
+use near_primitives::views::{
+    EpochRewardsView, EpochValidatorInfo, ValidatorKickoutPredictionView,
+};
 use near_store::ShardUId;
 use std::cmp::Ordering;
 use std::sync::{Arc, RwLockReadGuard, RwLockWriteGuard};
@@ -189,6 +191,20 @@ pub trait EpochManagerAdapter: Send + Sync {
         epoch_id: ValidatorInfoIdentifier,
     ) -> Result<EpochValidatorInfo, Error>;
 
+    /// Predicts validator kickouts as if the epoch ended at the given block,
+    /// which should be the latest block of the current epoch.
+    ///
+    /// WARNING: this call may be expensive and is intended for diagnostic use
+    /// only.
+    fn get_validator_kickout_prediction(
+        &self,
+        last_block_hash: &CryptoHash,
+    ) -> Result<ValidatorKickoutPredictionView, Error>;
+
+    /// Recomputes the rewards of the finished epoch whose last block is
+    /// `last_block_hash`, returning all the inputs of the calculation.
+    fn get_epoch_rewards(&self, last_block_hash: &CryptoHash) -> Result<EpochRewardsView, Error>;
+
     /// Amount of tokens minted in given epoch.
     fn get_epoch_minted_amount(&self, epoch_id: &EpochId) -> Result<Balance, Error>;
 
@@ -620,6 +636,19 @@ impl<T: HasEpochMangerHandle + Send + Sync> EpochManagerAdapter for T {
         epoch_manager.get_validator_info(epoch_id).map_err(|e| e.into())
     }
 
+    fn get_validator_kickout_prediction(
+        &self,
+        last_block_hash: &CryptoHash,
+    ) -> Result<ValidatorKickoutPredictionView, Error> {
+        let epoch_manager = self.read();
+        Ok(epoch_manager.get_validator_kickout_prediction(last_block_hash)?)
+    }
+
+    fn get_epoch_rewards(&self, last_block_hash: &CryptoHash) -> Result<EpochRewardsView, Error> {
+        let epoch_manager = self.read();
+        Ok(epoch_manager.get_epoch_rewards(last_block_hash)?)
+    }
+
     fn get_epoch_minted_amount(&self, epoch_id: &EpochId) -> Result<Balance, Error> {
         let epoch_manager = self.read();
         Ok(epoch_manager.get_epoch_info(epoch_id)?.minted_amount())
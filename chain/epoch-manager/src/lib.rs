@@ -19,7 +19,9 @@ use near_primitives::types::{
 };
 use near_primitives::version::{ProtocolVersion, UPGRADABILITY_FIX_PROTOCOL_VERSION};
 use near_primitives::views::{
-    CurrentEpochValidatorInfo, EpochValidatorInfo, NextEpochValidatorInfo, ValidatorKickoutView,
+    CurrentEpochValidatorInfo, EpochRewardsView, EpochValidatorInfo, NextEpochValidatorInfo,
+    ProductionPredictionView, ValidatorKickoutPredictionView, ValidatorKickoutView,
+    ValidatorPerformancePredictionView, ValidatorRewardView,
 };
 use near_store::{DBCol, Store, StoreUpdate};
 use num_rational::Rational64;
@@ -504,7 +506,7 @@ impl EpochManager {
     }
 
     fn collect_blocks_info(
-        &mut self,
+        &self,
         last_block_info: &BlockInfo,
         last_block_hash: &CryptoHash,
     ) -> Result<EpochSummary, EpochError> {
@@ -1375,6 +1377,244 @@ impl EpochManager {
         let stake_divisor = { config.minimum_stake_divisor as Balance };
         Ok(seat_price / stake_divisor)
     }
+
+    /// Predicts which validators would be kicked out if the epoch ended at
+    /// `last_block_hash` and projects their block and chunk production to the
+    /// end of the epoch.
+    ///
+    /// The block hash passed as argument should be the latest block belonging
+    /// to current epoch, see [`Self::get_epoch_info_aggregator_upto_last`].
+    pub fn get_validator_kickout_prediction(
+        &self,
+        last_block_hash: &CryptoHash,
+    ) -> Result<ValidatorKickoutPredictionView, EpochError> {
+        let block_info = self.get_block_info(last_block_hash)?;
+        let epoch_id = block_info.epoch_id().clone();
+        let epoch_info = self.get_epoch_info(&epoch_id)?;
+        let next_epoch_info = self.get_epoch_info(&self.get_next_epoch_id(last_block_hash)?)?;
+        let config = self.config.for_protocol_version(epoch_info.protocol_version());
+        let epoch_start_height = self.get_epoch_start_from_epoch_id(&epoch_id)?;
+        let estimated_epoch_end_height = epoch_start_height + config.epoch_length - 1;
+
+        // Blocks and chunks assigned to validators from the next height until
+        // the end of the epoch.
+        let mut remaining_blocks: HashMap<ValidatorId, u64> = HashMap::new();
+        let mut remaining_chunks: HashMap<ValidatorId, u64> = HashMap::new();
+        let num_shards = epoch_info.chunk_producers_settlement().len() as ShardId;
+        for height in block_info.height() + 1..=estimated_epoch_end_height {
+            *remaining_blocks
+                .entry(Self::block_producer_from_info(&epoch_info, height))
+                .or_default() += 1;
+            for shard_id in 0..num_shards {
+                *remaining_chunks
+                    .entry(Self::chunk_producer_from_info(&epoch_info, height, shard_id))
+                    .or_default() += 1;
+            }
+        }
+
+        let aggregator = self.get_epoch_info_aggregator_upto_last(last_block_hash)?;
+        let epoch_summary = self.collect_blocks_info(&block_info, last_block_hash)?;
+        let validators = epoch_info
+            .validators_iter()
+            .enumerate()
+            .map(|(validator_id, info)| {
+                let validator_id = validator_id as ValidatorId;
+                let block_stats = aggregator
+                    .block_tracker
+                    .get(&validator_id)
+                    .cloned()
+                    .unwrap_or(ValidatorStats { produced: 0, expected: 0 });
+                let mut chunk_stats = ValidatorStats { produced: 0, expected: 0 };
+                for tracker in aggregator.shard_tracker.values() {
+                    if let Some(stats) = tracker.get(&validator_id) {
+                        chunk_stats.produced += stats.produced;
+                        chunk_stats.expected += stats.expected;
+                    }
+                }
+                let expected_blocks_at_epoch_end = block_stats.expected
+                    + remaining_blocks.get(&validator_id).copied().unwrap_or_default();
+                let expected_chunks_at_epoch_end = chunk_stats.expected
+                    + remaining_chunks.get(&validator_id).copied().unwrap_or_default();
+                let (account_id, _, stake) = info.destructure();
+                ValidatorPerformancePredictionView {
+                    kickout_reason: epoch_summary.validator_kickout.get(&account_id).cloned(),
+                    account_id,
+                    stake,
+                    blocks: ProductionPredictionView::new(
+                        block_stats.produced,
+                        block_stats.expected,
+                        expected_blocks_at_epoch_end,
+                        config.block_producer_kickout_threshold,
+                    ),
+                    chunks: ProductionPredictionView::new(
+                        chunk_stats.produced,
+                        chunk_stats.expected,
+                        expected_chunks_at_epoch_end,
+                        config.chunk_producer_kickout_threshold,
+                    ),
+                }
+            })
+            .collect();
+
+        let estimated_seat_price = match self.simulate_validator_selection(last_block_hash, vec![])
+        {
+            Ok(next_next_epoch_info) => Some(next_next_epoch_info.seat_price()),
+            Err(EpochError::ThresholdError { .. } | EpochError::NotEnoughValidators { .. }) => None,
+            Err(err) => return Err(err),
+        };
+
+        Ok(ValidatorKickoutPredictionView {
+            epoch_id: epoch_id.0,
+            epoch_height: epoch_info.epoch_height(),
+            epoch_start_height,
+            height: block_info.height(),
+            estimated_epoch_end_height,
+            block_producer_kickout_threshold: config.block_producer_kickout_threshold,
+            chunk_producer_kickout_threshold: config.chunk_producer_kickout_threshold,
+            validator_max_kickout_stake_perc: config.validator_max_kickout_stake_perc,
+            validators,
+            next_epoch_seat_price: next_epoch_info.seat_price(),
+            estimated_seat_price,
+        })
+    }
+
+    /// Runs the validator selection for the epoch after next as if the
+    /// current epoch ended at `last_block_hash` and `extra_proposals` were made
+    /// in addition to the proposals made in the epoch so far.  An extra
+    /// proposal replaces the proposal of the same account made in the epoch,
+    /// a proposal with zero stake unstakes.
+    ///
+    /// Rewards for the epoch are not known until it ends, so they are left
+    /// out.  The block hash passed as argument should be the latest block
+    /// belonging to current epoch, see
+    /// [`Self::get_epoch_info_aggregator_upto_last`].
+    pub fn simulate_validator_selection(
+        &self,
+        last_block_hash: &CryptoHash,
+        extra_proposals: Vec<ValidatorStake>,
+    ) -> Result<EpochInfo, EpochError> {
+        let block_info = self.get_block_info(last_block_hash)?;
+        let epoch_info = self.get_epoch_info(block_info.epoch_id())?;
+        let next_epoch_info = self.get_epoch_info(&self.get_next_epoch_id(last_block_hash)?)?;
+        let EpochSummary { mut all_proposals, mut validator_kickout, next_version, .. } =
+            self.collect_blocks_info(&block_info, last_block_hash)?;
+
+        // Same as in `collect_blocks_info`.
+        for proposal in extra_proposals {
+            let account_id = proposal.account_id();
+            if block_info.slashed().contains_key(account_id) {
+                continue;
+            }
+            if proposal.stake() == 0
+                && *next_epoch_info.stake_change().get(account_id).unwrap_or(&0) != 0
+            {
+                validator_kickout.insert(account_id.clone(), ValidatorKickoutReason::Unstaked);
+            } else if validator_kickout.get(account_id) == Some(&ValidatorKickoutReason::Unstaked) {
+                validator_kickout.remove(account_id);
+            }
+            all_proposals.retain(|p| p.account_id() != account_id);
+            all_proposals.push(proposal);
+        }
+
+        // The seed is only used to sample producers of each height, it doesn't
+        // affect the selection and the shard assignment.
+        let next_next_epoch_config = self.config.for_protocol_version(next_version);
+        proposals_to_epoch_info(
+            &next_next_epoch_config,
+            [0; 32],
+            &next_epoch_info,
+            all_proposals,
+            validator_kickout,
+            HashMap::new(),
+            0,
+            next_version,
+            epoch_info.protocol_version(),
+        )
+    }
+
+    /// Recomputes the rewards of the epoch ending at `last_block_hash` from
+    /// the stored epoch and block info, the same way `finalize_epoch` does,
+    /// and returns them with the inputs of the calculation and the rewards
+    /// actually recorded in the epoch info of the epoch after next.
+    pub fn get_epoch_rewards(
+        &self,
+        last_block_hash: &CryptoHash,
+    ) -> Result<EpochRewardsView, EpochError> {
+        let block_info = self.get_block_info(last_block_hash)?;
+        let epoch_id = block_info.epoch_id().clone();
+        if !self.is_next_block_epoch_start(last_block_hash)? {
+            return Err(EpochError::EpochOutOfBounds(epoch_id));
+        }
+        let epoch_info = self.get_epoch_info(&epoch_id)?;
+        let EpochSummary { validator_kickout, validator_block_chunk_stats, .. } =
+            self.get_epoch_validator_info(&epoch_id)?;
+        let validator_stake =
+            epoch_info.validators_iter().map(|r| r.account_and_stake()).collect::<HashMap<_, _>>();
+        let last_epoch_last_block_hash =
+            *self.get_block_info(block_info.epoch_first_block())?.prev_hash();
+        let last_block_in_last_epoch = self.get_block_info(&last_epoch_last_block_hash)?;
+        let epoch_duration = block_info
+            .timestamp_nanosec()
+            .saturating_sub(*last_block_in_last_epoch.timestamp_nanosec());
+        let breakdown = self.reward_calculator.calculate_reward_breakdown(
+            validator_block_chunk_stats,
+            &validator_stake,
+            *block_info.total_supply(),
+            epoch_info.protocol_version(),
+            self.genesis_protocol_version,
+            epoch_duration,
+        );
+
+        // Rewards of epoch T are stored in the info of epoch T + 2, whose id
+        // is the hash of the last block of T.
+        let recorded_epoch_info = self.get_epoch_info(&EpochId(*last_block_hash)).ok();
+        let mut validators: Vec<_> = breakdown
+            .validators
+            .into_iter()
+            .map(|validator| ValidatorRewardView {
+                recorded_reward: recorded_epoch_info.as_ref().map(|info| {
+                    info.validator_reward().get(&validator.account_id).copied().unwrap_or(0)
+                }),
+                account_id: validator.account_id,
+                stake: validator.stake,
+                num_produced_blocks: validator.stats.block_stats.produced,
+                num_expected_blocks: validator.stats.block_stats.expected,
+                num_produced_chunks: validator.stats.chunk_stats.produced,
+                num_expected_chunks: validator.stats.chunk_stats.expected,
+                uptime: validator.uptime,
+                reward: validator.reward,
+            })
+            .collect();
+        validators.sort_by(|a, b| a.account_id.cmp(&b.account_id));
+        let validator_kickout = validator_kickout
+            .into_iter()
+            .collect::<BTreeMap<_, _>>()
+            .into_iter()
+            .map(|(account_id, reason)| ValidatorKickoutView { account_id, reason })
+            .collect();
+
+        Ok(EpochRewardsView {
+            epoch_id: epoch_id.0,
+            epoch_height: epoch_info.epoch_height(),
+            protocol_version: epoch_info.protocol_version(),
+            last_block_hash: *last_block_hash,
+            total_supply: *block_info.total_supply(),
+            epoch_duration_ns: epoch_duration,
+            max_inflation_rate: breakdown.max_inflation_rate,
+            protocol_reward_rate: breakdown.protocol_reward_rate,
+            online_min_threshold: self.reward_calculator.online_min_threshold,
+            online_max_threshold: self.reward_calculator.online_max_threshold,
+            epoch_total_reward: breakdown.epoch_total_reward,
+            protocol_treasury_account: self.reward_calculator.protocol_treasury_account.clone(),
+            protocol_reward: breakdown.epoch_protocol_treasury,
+            epoch_validator_reward: breakdown.epoch_validator_reward,
+            total_stake: breakdown.total_stake,
+            minted_amount: breakdown.minted_amount,
+            recorded_minted_amount: recorded_epoch_info.map(|info| info.minted_amount()),
+            validators,
+            validator_kickout,
+        })
+    }
 }
 
 /// Private utilities for EpochManager.
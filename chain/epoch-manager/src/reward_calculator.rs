@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use num_rational::Rational32;
+use num_rational::{Ratio, Rational32};
 use primitive_types::U256;
 
 use near_chain_configs::GenesisConfig;
@@ -11,6 +11,34 @@ use near_primitives::version::{ProtocolVersion, ENABLE_INFLATION_PROTOCOL_VERSIO
 pub(crate) const NUM_NS_IN_SECOND: u64 = 1_000_000_000;
 pub const NUM_SECONDS_IN_A_YEAR: u64 = 24 * 60 * 60 * 365;
 
+/// Intermediate values of the reward calculation for an epoch.
+#[derive(Debug)]
+pub struct RewardBreakdown {
+    /// Inflation rate actually used, which may differ from the genesis config.
+    pub max_inflation_rate: Rational32,
+    /// Protocol treasury share actually used.
+    pub protocol_reward_rate: Rational32,
+    pub epoch_total_reward: Balance,
+    pub epoch_protocol_treasury: Balance,
+    /// Part of `epoch_total_reward` shared between the validators.
+    pub epoch_validator_reward: Balance,
+    pub total_stake: Balance,
+    pub validators: Vec<ValidatorRewardBreakdown>,
+    /// Newly minted tokens, including the protocol treasury reward.
+    pub minted_amount: Balance,
+}
+
+/// Reward of a single validator together with the inputs it was computed from.
+#[derive(Debug)]
+pub struct ValidatorRewardBreakdown {
+    pub account_id: AccountId,
+    pub stats: BlockChunkValidatorStats,
+    /// Average of produced / expected blocks and chunks.
+    pub uptime: Ratio<u128>,
+    pub stake: Balance,
+    pub reward: Balance,
+}
+
 #[derive(Clone, Debug)]
 pub struct RewardCalculator {
     pub max_inflation_rate: Rational32,
@@ -48,7 +76,33 @@ impl RewardCalculator {
         genesis_protocol_version: ProtocolVersion,
         epoch_duration: u64,
     ) -> (HashMap<AccountId, Balance>, Balance) {
+        let breakdown = self.calculate_reward_breakdown(
+            validator_block_chunk_stats,
+            validator_stake,
+            total_supply,
+            protocol_version,
+            genesis_protocol_version,
+            epoch_duration,
+        );
         let mut res = HashMap::new();
+        res.insert(self.protocol_treasury_account.clone(), breakdown.epoch_protocol_treasury);
+        for validator in breakdown.validators {
+            res.insert(validator.account_id, validator.reward);
+        }
+        (res, breakdown.minted_amount)
+    }
+
+    /// Same as `calculate_reward` but also returns the intermediate values of
+    /// the calculation, so that rewards of an epoch can be audited.
+    pub fn calculate_reward_breakdown(
+        &self,
+        validator_block_chunk_stats: HashMap<AccountId, BlockChunkValidatorStats>,
+        validator_stake: &HashMap<AccountId, Balance>,
+        total_supply: Balance,
+        protocol_version: ProtocolVersion,
+        genesis_protocol_version: ProtocolVersion,
+        epoch_duration: u64,
+    ) -> RewardBreakdown {
         let num_validators = validator_block_chunk_stats.len();
         let use_hardcoded_value = genesis_protocol_version < protocol_version
             && protocol_version >= ENABLE_INFLATION_PROTOCOL_VERSION;
@@ -80,13 +134,22 @@ impl RewardCalculator {
             * U256::from(*protocol_reward_rate.numer() as u64)
             / U256::from(*protocol_reward_rate.denom() as u64))
         .as_u128();
-        res.insert(self.protocol_treasury_account.clone(), epoch_protocol_treasury);
+        let epoch_validator_reward = epoch_total_reward - epoch_protocol_treasury;
+        let total_stake: Balance = validator_stake.values().sum();
+        let mut breakdown = RewardBreakdown {
+            max_inflation_rate,
+            protocol_reward_rate,
+            epoch_total_reward,
+            epoch_protocol_treasury,
+            epoch_validator_reward,
+            total_stake,
+            validators: Vec::with_capacity(num_validators),
+            minted_amount: 0,
+        };
         if num_validators == 0 {
-            return (res, 0);
+            return breakdown;
         }
-        let epoch_validator_reward = epoch_total_reward - epoch_protocol_treasury;
         let mut epoch_actual_reward = epoch_protocol_treasury;
-        let total_stake: Balance = validator_stake.values().sum();
         for (account_id, stats) in validator_block_chunk_stats {
             // Uptime is an average of block produced / expected and chunk produced / expected.
             let (average_produced_numer, average_produced_denom) =
@@ -110,6 +173,7 @@ impl RewardCalculator {
             // If average of produced blocks below online min threshold, validator gets 0 reward.
             let chunk_only_producers_enabled =
                 checked_feature!("stable", ChunkOnlyProducers, protocol_version);
+            let stake = validator_stake.get(&account_id).copied();
             let reward = if average_produced_numer * online_min_denom
                 < online_min_numer * average_produced_denom
                 || (chunk_only_producers_enabled
@@ -123,9 +187,7 @@ impl RewardCalculator {
             {
                 0
             } else {
-                let stake = *validator_stake
-                    .get(&account_id)
-                    .unwrap_or_else(|| panic!("{} is not a validator", account_id));
+                let stake = stake.unwrap_or_else(|| panic!("{} is not a validator", account_id));
                 // Online reward multiplier is min(1., (uptime - online_threshold_min) / (online_threshold_max - online_threshold_min).
                 let online_max_numer = U256::from(*self.online_max_threshold.numer() as u64);
                 let online_max_denom = U256::from(*self.online_max_threshold.denom() as u64);
@@ -143,10 +205,20 @@ impl RewardCalculator {
                     / U256::from(total_stake))
                 .as_u128()
             };
-            res.insert(account_id, reward);
+            breakdown.validators.push(ValidatorRewardBreakdown {
+                account_id,
+                stats,
+                uptime: Ratio::new(
+                    average_produced_numer.as_u128(),
+                    average_produced_denom.as_u128(),
+                ),
+                stake: stake.unwrap_or(0),
+                reward,
+            });
             epoch_actual_reward += reward;
         }
-        (res, epoch_actual_reward)
+        breakdown.minted_amount = epoch_actual_reward;
+        breakdown
     }
 }
 
@@ -273,6 +345,64 @@ mod tests {
         assert_eq!(result.1, 4_999_999u128);
     }
 
+    /// Test that the breakdown exposes the inputs used to compute the rewards.
+    #[test]
+    fn test_reward_breakdown() {
+        let epoch_length = 1000;
+        let reward_calculator = RewardCalculator {
+            max_inflation_rate: Ratio::new(1, 100),
+            num_blocks_per_year: 1000,
+            epoch_length,
+            protocol_reward_rate: Ratio::new(1, 10),
+            protocol_treasury_account: "near".parse().unwrap(),
+            online_min_threshold: Ratio::new(9, 10),
+            online_max_threshold: Ratio::new(99, 100),
+            num_seconds_per_year: 1000,
+        };
+        let validator_block_chunk_stats = HashMap::from([
+            (
+                "test1".parse().unwrap(),
+                BlockChunkValidatorStats {
+                    block_stats: ValidatorStats { produced: 945, expected: 1000 },
+                    chunk_stats: ValidatorStats { produced: 945, expected: 1000 },
+                },
+            ),
+            (
+                "test2".parse().unwrap(),
+                BlockChunkValidatorStats {
+                    block_stats: ValidatorStats { produced: 999, expected: 1000 },
+                    chunk_stats: ValidatorStats { produced: 999, expected: 1000 },
+                },
+            ),
+        ]);
+        let validator_stake = HashMap::from([
+            ("test1".parse().unwrap(), 500_000),
+            ("test2".parse().unwrap(), 500_000),
+        ]);
+        let breakdown = reward_calculator.calculate_reward_breakdown(
+            validator_block_chunk_stats,
+            &validator_stake,
+            1_000_000_000,
+            PROTOCOL_VERSION,
+            PROTOCOL_VERSION,
+            epoch_length * NUM_NS_IN_SECOND,
+        );
+        assert_eq!(breakdown.max_inflation_rate, Ratio::new(1, 100));
+        assert_eq!(breakdown.protocol_reward_rate, Ratio::new(1, 10));
+        assert_eq!(breakdown.epoch_total_reward, 10_000_000);
+        assert_eq!(breakdown.epoch_protocol_treasury, 1_000_000);
+        assert_eq!(breakdown.epoch_validator_reward, 9_000_000);
+        assert_eq!(breakdown.total_stake, 1_000_000);
+        let mut validators = breakdown.validators;
+        validators.sort_by(|a, b| a.account_id.cmp(&b.account_id));
+        assert_eq!(validators[0].uptime, Ratio::new(945, 1000));
+        assert_eq!(validators[0].stake, 500_000);
+        assert_eq!(validators[0].reward, 2_250_000);
+        assert_eq!(validators[1].uptime, Ratio::new(999, 1000));
+        assert_eq!(validators[1].reward, 4_500_000);
+        assert_eq!(breakdown.minted_amount, 1_000_000 + 2_250_000 + 4_500_000);
+    }
+
     /// Test reward calculation for chunk only or block only producers
     #[test]
     fn test_reward_chunk_only_producer() {
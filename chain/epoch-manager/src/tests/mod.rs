@@ -370,6 +370,105 @@ fn test_validator_kickout() {
     );
 }
 
+#[test]
+fn test_validator_kickout_prediction() {
+    let amount_staked = 1_000_000;
+    let validators =
+        vec![("test1".parse().unwrap(), amount_staked), ("test2".parse().unwrap(), amount_staked)];
+    let epoch_length = 10;
+    let mut epoch_manager = setup_default_epoch_manager(validators, epoch_length, 1, 2, 0, 90, 60);
+    let h = hash_range(epoch_length as usize - 2);
+
+    record_block(&mut epoch_manager, CryptoHash::default(), h[0], 0, vec![]);
+    let mut prev_block = h[0];
+    let mut test2_expected_blocks = 0;
+    for (i, curr_block) in h.iter().enumerate().skip(1) {
+        let height = i as u64;
+        let epoch_id = epoch_manager.get_epoch_id_from_prev_block(&prev_block).unwrap();
+        let block_producer = epoch_manager.get_block_producer_info(&epoch_id, height).unwrap();
+        if block_producer.account_id().as_ref() == "test2" {
+            // test2 skips its blocks
+            test2_expected_blocks += 1;
+        } else {
+            record_block(&mut epoch_manager, prev_block, *curr_block, height, vec![]);
+            prev_block = *curr_block;
+        }
+    }
+
+    let prediction = epoch_manager.get_validator_kickout_prediction(&prev_block).unwrap();
+    assert_eq!(prediction.block_producer_kickout_threshold, 90);
+    assert_eq!(prediction.chunk_producer_kickout_threshold, 60);
+    assert_eq!(prediction.height, epoch_manager.get_block_info(&prev_block).unwrap().height());
+    let validators: HashMap<_, _> =
+        prediction.validators.iter().map(|v| (v.account_id.as_str(), v)).collect();
+    let test1 = validators["test1"];
+    assert_eq!(test1.blocks.produced, test1.blocks.expected);
+    assert_eq!(test1.kickout_reason, None);
+    let test2 = validators["test2"];
+    assert_eq!(test2.blocks.produced, 0);
+    assert_eq!(test2.blocks.expected, test2_expected_blocks);
+    assert_eq!(test2.blocks.projected_produced_at_epoch_end, 0);
+    if test2_expected_blocks > 0 {
+        assert_eq!(
+            test2.kickout_reason,
+            Some(NotEnoughBlocks { produced: 0, expected: test2_expected_blocks })
+        );
+    }
+    // Every remaining height of the epoch is assigned to one of the validators.
+    let remaining_blocks: u64 = prediction
+        .validators
+        .iter()
+        .map(|v| v.blocks.expected_at_epoch_end - v.blocks.expected)
+        .sum();
+    assert_eq!(remaining_blocks, prediction.estimated_epoch_end_height - prediction.height);
+    for v in &prediction.validators {
+        assert_eq!(
+            v.blocks.required_at_epoch_end,
+            (v.blocks.expected_at_epoch_end * 90 + 99) / 100
+        );
+    }
+    let next_epoch_id = epoch_manager.get_next_epoch_id(&prev_block).unwrap();
+    assert_eq!(
+        prediction.next_epoch_seat_price,
+        epoch_manager.get_epoch_info(&next_epoch_id).unwrap().seat_price()
+    );
+    assert!(prediction.estimated_seat_price.is_some());
+}
+
+#[test]
+fn test_simulate_validator_selection() {
+    let amount_staked = 1_000_000;
+    let validators =
+        vec![("test1".parse().unwrap(), amount_staked), ("test2".parse().unwrap(), amount_staked)];
+    let mut epoch_manager = setup_default_epoch_manager(validators, 10, 1, 2, 0, 90, 60);
+    let h = hash_range(3);
+    record_block(&mut epoch_manager, CryptoHash::default(), h[0], 0, vec![]);
+    record_block(&mut epoch_manager, h[0], h[1], 1, vec![]);
+    record_block(&mut epoch_manager, h[1], h[2], 2, vec![]);
+
+    let epoch_info = epoch_manager.simulate_validator_selection(&h[2], vec![]).unwrap();
+    check_validators(&epoch_info, &[("test1", amount_staked), ("test2", amount_staked)]);
+    check_kickout(&epoch_info, &[]);
+
+    // test1 unstakes and test3 takes its seat with a higher stake.
+    let epoch_info = epoch_manager
+        .simulate_validator_selection(
+            &h[2],
+            vec![
+                stake("test1".parse().unwrap(), 0),
+                stake("test3".parse().unwrap(), amount_staked + 1),
+            ],
+        )
+        .unwrap();
+    check_validators(&epoch_info, &[("test3", amount_staked + 1), ("test2", amount_staked)]);
+    check_kickout(&epoch_info, &[("test1", ValidatorKickoutReason::Unstaked)]);
+    assert!(!epoch_info.account_is_validator(&"test1".parse().unwrap()));
+
+    // Simulation doesn't change the state of the epoch manager.
+    let epoch_info = epoch_manager.simulate_validator_selection(&h[2], vec![]).unwrap();
+    check_validators(&epoch_info, &[("test1", amount_staked), ("test2", amount_staked)]);
+}
+
 #[test]
 fn test_validator_unstake() {
     let store = create_test_store();
@@ -754,6 +853,94 @@ fn test_validator_reward_one_validator() {
     assert_eq!(epoch_info.minted_amount(), inflation);
 }
 
+#[test]
+fn test_epoch_rewards() {
+    let stake_amount = 1_000_000;
+    let validators =
+        vec![("test1".parse().unwrap(), stake_amount), ("test2".parse().unwrap(), stake_amount)];
+    let epoch_length = 2;
+    let total_supply = validators.iter().map(|(_, stake)| stake).sum();
+    let reward_calculator = RewardCalculator {
+        max_inflation_rate: Ratio::new(5, 100),
+        num_blocks_per_year: 50,
+        epoch_length,
+        protocol_reward_rate: Ratio::new(1, 10),
+        protocol_treasury_account: "near".parse().unwrap(),
+        online_min_threshold: Ratio::new(90, 100),
+        online_max_threshold: Ratio::new(99, 100),
+        num_seconds_per_year: 50,
+    };
+    let mut epoch_manager = setup_epoch_manager(
+        validators,
+        epoch_length,
+        1,
+        2,
+        0,
+        90,
+        60,
+        100,
+        reward_calculator.clone(),
+    );
+    let rng_seed = [0; 32];
+    let h = hash_range(5);
+    epoch_manager
+        .record_block_info(
+            block_info(
+                h[0],
+                0,
+                0,
+                Default::default(),
+                Default::default(),
+                h[0],
+                vec![true],
+                total_supply,
+            ),
+            rng_seed,
+        )
+        .unwrap()
+        .commit()
+        .unwrap();
+    epoch_manager
+        .record_block_info(
+            block_info(h[1], 1, 1, h[0], h[0], h[1], vec![true], total_supply),
+            rng_seed,
+        )
+        .unwrap()
+        .commit()
+        .unwrap();
+    // The epoch isn't finished yet.
+    assert!(epoch_manager.get_epoch_rewards(&h[1]).is_err());
+    epoch_manager
+        .record_block_info(
+            block_info(h[2], 2, 2, h[1], h[1], h[1], vec![true], total_supply),
+            rng_seed,
+        )
+        .unwrap()
+        .commit()
+        .unwrap();
+
+    let rewards = epoch_manager.get_epoch_rewards(&h[2]).unwrap();
+    let epoch_info = epoch_manager.get_epoch_info(&EpochId(h[2])).unwrap();
+    assert_eq!(rewards.epoch_id, CryptoHash::default());
+    assert_eq!(rewards.total_supply, total_supply);
+    assert_eq!(rewards.epoch_duration_ns, epoch_length * NUM_NS_IN_SECOND);
+    assert_eq!(
+        rewards.protocol_reward,
+        epoch_info.validator_reward()[&"near".parse::<AccountId>().unwrap()]
+    );
+    assert_eq!(rewards.minted_amount, epoch_info.minted_amount());
+    assert_eq!(rewards.recorded_minted_amount, Some(epoch_info.minted_amount()));
+    assert_eq!(rewards.total_stake, 2 * stake_amount);
+    assert_eq!(rewards.validators.len(), 2);
+    for validator in rewards.validators {
+        assert_eq!(validator.stake, stake_amount);
+        assert_eq!(validator.uptime, Ratio::new(1, 1));
+        assert_eq!(validator.reward, epoch_info.validator_reward()[&validator.account_id]);
+        assert_eq!(validator.recorded_reward, Some(validator.reward));
+    }
+    assert!(rewards.validator_kickout.is_empty());
+}
+
 #[test]
 fn test_validator_reward_weight_by_stake() {
     let stake_amount1 = 1_000_000;
@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RpcEstimateGasRequest {
+    pub signer_id: near_primitives::types::AccountId,
+    pub public_key: near_crypto::PublicKey,
+    /// Defaults to the next nonce of the access key.
+    #[serde(default)]
+    pub nonce: Option<near_primitives::types::Nonce>,
+    pub receiver_id: near_primitives::types::AccountId,
+    pub actions: Vec<near_primitives::views::ActionView>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RpcEstimateGasResponse {
+    #[serde(flatten)]
+    pub estimate: near_primitives::views::GasEstimateView,
+}
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcEstimateGasError {
+    #[error("There are no fully synchronized blocks on the node yet")]
+    NoSyncedBlocks,
+    #[error("The node does not track the shard ID {requested_shard_id}")]
+    UnavailableShard { requested_shard_id: near_primitives::types::ShardId },
+    #[error("Action cannot be executed: {error_message}")]
+    InvalidAction { error_message: String },
+    #[error("An error happened during transaction execution: {context:?}")]
+    InvalidTransaction {
+        #[serde(skip_serializing)]
+        context: near_primitives::errors::InvalidTxError,
+    },
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl From<RpcEstimateGasError> for crate::errors::RpcError {
+    fn from(error: RpcEstimateGasError) -> Self {
+        let error_data = match &error {
+            RpcEstimateGasError::InvalidTransaction { context } => {
+                if let Ok(value) =
+                    serde_json::to_value(crate::errors::ServerError::TxExecutionError(
+                        near_primitives::errors::TxExecutionError::InvalidTxError(context.clone()),
+                    ))
+                {
+                    value
+                } else {
+                    Value::String(error.to_string())
+                }
+            }
+            _ => Value::String(error.to_string()),
+        };
+
+        let error_data_value = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcEstimateGasError: {:?}", err),
+                )
+            }
+        };
+
+        Self::new_internal_or_handler_error(Some(error_data), error_data_value)
+    }
+}
@@ -3,6 +3,7 @@ pub mod changes;
 pub mod chunks;
 pub mod client_config;
 pub mod config;
+pub mod estimate_gas;
 pub mod gas_price;
 pub mod light_client;
 pub mod maintenance;
@@ -11,5 +12,6 @@ pub mod query;
 pub mod receipts;
 pub mod sandbox;
 pub mod status;
+pub mod trace;
 pub mod transactions;
 pub mod validator;
@@ -1,9 +1,9 @@
 use near_client_primitives::debug::{
-    DebugBlockStatusData, EpochInfoView, TrackedShardsView, ValidatorStatus,
+    DebugBlockStatusData, DoomslugStatusView, EpochInfoView, TrackedShardsView, ValidatorStatus,
 };
 use near_primitives::views::{
     CatchupStatusView, ChainProcessingInfo, NetworkGraphView, PeerStoreView,
-    RequestedStatePartsView, SyncStatusView,
+    RequestedStatePartsView, SyncStatusView, ValidatorKickoutPredictionView,
 };
 use serde::{Deserialize, Serialize};
 
@@ -29,6 +29,11 @@ pub enum DebugStatusResponse {
     // The state parts already requested.
     RequestedStateParts(Vec<RequestedStatePartsView>),
     NetworkGraph(NetworkGraphView),
+    // Projected block and chunk production of current validators and
+    // predicted kickouts.
+    ValidatorKickoutPrediction(ValidatorKickoutPredictionView),
+    // Approval timelines, doomslug timers and block production readiness per height.
+    DoomslugStatus(DoomslugStatusView),
 }
 
 #[cfg(feature = "debug_types")]
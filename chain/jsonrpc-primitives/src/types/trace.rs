@@ -0,0 +1,82 @@
+use near_primitives::serialize::base64_format;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RpcTraceCallRequest {
+    #[serde(flatten)]
+    pub block_reference: near_primitives::types::BlockReference,
+    pub account_id: near_primitives::types::AccountId,
+    pub method_name: String,
+    #[serde(rename = "args_base64", with = "base64_format")]
+    pub args: near_primitives::types::FunctionArgs,
+    /// Whether to include a per host function and per wasm function breakdown of the burnt gas.
+    #[serde(default)]
+    pub extended_gas_profile: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RpcTraceCallResponse {
+    #[serde(flatten)]
+    pub trace: near_primitives::views::FunctionCallTraceView,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RpcTraceTransactionRequest {
+    pub tx_hash: near_primitives::hash::CryptoHash,
+    /// Whether to include a per host function and per wasm function breakdown of the burnt gas.
+    #[serde(default)]
+    pub extended_gas_profile: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RpcTraceTransactionResponse {
+    pub receipts: Vec<near_primitives::views::ReceiptExecutionTraceView>,
+}
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcTraceError {
+    #[error("Block not found: {error_message}")]
+    UnknownBlock {
+        #[serde(skip_serializing)]
+        error_message: String,
+    },
+    #[error("Transaction {transaction_hash} has not been executed on this node")]
+    UnknownTransaction { transaction_hash: near_primitives::hash::CryptoHash },
+    #[error("The node does not track the shard ID {requested_shard_id}")]
+    UnavailableShard { requested_shard_id: near_primitives::types::ShardId },
+    #[error("Function call cannot be traced: {vm_error}")]
+    InvalidCall { vm_error: String },
+    #[error("Transaction cannot be traced within the limit of {limit}")]
+    LimitExceeded { limit: String },
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl From<RpcTraceError> for crate::errors::RpcError {
+    fn from(error: RpcTraceError) -> Self {
+        let error_data = match &error {
+            RpcTraceError::UnknownBlock { error_message } => {
+                Some(Value::String(format!("DB Not Found Error: {}", error_message)))
+            }
+            RpcTraceError::UnknownTransaction { .. }
+            | RpcTraceError::UnavailableShard { .. }
+            | RpcTraceError::InvalidCall { .. }
+            | RpcTraceError::LimitExceeded { .. }
+            | RpcTraceError::InternalError { .. } => Some(Value::String(error.to_string())),
+        };
+
+        let error_data_value = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcTraceError: {:?}", err),
+                )
+            }
+        };
+
+        Self::new_internal_or_handler_error(error_data, error_data_value)
+    }
+}
@@ -32,6 +32,18 @@ pub struct RpcValidatorResponse {
     pub validator_info: near_primitives::views::EpochValidatorInfo,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RpcValidatorRewardsRequest {
+    #[serde(flatten)]
+    pub epoch_reference: near_primitives::types::EpochReference,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RpcValidatorRewardsResponse {
+    #[serde(flatten)]
+    pub rewards: near_primitives::views::EpochRewardsView,
+}
+
 impl From<RpcValidatorError> for crate::errors::RpcError {
     fn from(error: RpcValidatorError) -> Self {
         let error_data = match &error {
@@ -251,6 +251,30 @@ impl JsonRpcClient {
         call_method(&self.client, &self.server_addr, "EXPERIMENTAL_receipt", request)
     }
 
+    #[allow(non_snake_case)]
+    pub fn EXPERIMENTAL_estimate_gas(
+        &self,
+        request: near_jsonrpc_primitives::types::estimate_gas::RpcEstimateGasRequest,
+    ) -> RpcRequest<near_jsonrpc_primitives::types::estimate_gas::RpcEstimateGasResponse> {
+        call_method(&self.client, &self.server_addr, "EXPERIMENTAL_estimate_gas", request)
+    }
+
+    #[allow(non_snake_case)]
+    pub fn EXPERIMENTAL_trace_call(
+        &self,
+        request: near_jsonrpc_primitives::types::trace::RpcTraceCallRequest,
+    ) -> RpcRequest<near_jsonrpc_primitives::types::trace::RpcTraceCallResponse> {
+        call_method(&self.client, &self.server_addr, "EXPERIMENTAL_trace_call", request)
+    }
+
+    #[allow(non_snake_case)]
+    pub fn EXPERIMENTAL_trace_transaction(
+        &self,
+        request: near_jsonrpc_primitives::types::trace::RpcTraceTransactionRequest,
+    ) -> RpcRequest<near_jsonrpc_primitives::types::trace::RpcTraceTransactionResponse> {
+        call_method(&self.client, &self.server_addr, "EXPERIMENTAL_trace_transaction", request)
+    }
+
     #[allow(non_snake_case)]
     pub fn EXPERIMENTAL_protocol_config(
         &self,
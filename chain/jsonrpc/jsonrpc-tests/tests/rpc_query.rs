@@ -8,7 +8,9 @@ use serde_json::json;
 use near_actix_test_utils::run_actix;
 use near_crypto::{KeyType, PublicKey, Signature};
 use near_jsonrpc::client::{new_client, ChunkId};
+use near_jsonrpc_primitives::errors::RpcErrorKind;
 use near_jsonrpc_primitives::types::query::QueryResponseKind;
+use near_jsonrpc_primitives::types::trace::{RpcTraceCallRequest, RpcTraceTransactionRequest};
 use near_jsonrpc_primitives::types::validator::RpcValidatorsOrderedRequest;
 use near_network::test_utils::wait_or_timeout;
 use near_o11y::testonly::init_test_logger;
@@ -299,6 +301,8 @@ fn test_query_state() {
                     account_id: "test".parse().unwrap(),
                     prefix: vec![].into(),
                     include_proof: false,
+                    start_key: None,
+                    limit: None,
                 },
             })
             .await
@@ -342,6 +346,46 @@ fn test_query_call_function() {
     });
 }
 
+/// Trace a function call via json rpc
+#[test]
+fn test_trace_call() {
+    test_with_client!(test_utils::NodeType::NonValidator, client, async move {
+        let response = client
+            .EXPERIMENTAL_trace_call(RpcTraceCallRequest {
+                block_reference: BlockReference::latest(),
+                account_id: "test".parse().unwrap(),
+                method_name: "method".to_string(),
+                args: vec![].into(),
+                extended_gas_profile: false,
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.trace.contract_id, "test".parse().unwrap());
+        assert_eq!(response.trace.method_name, "method");
+        assert!(response.trace.error.is_none());
+    });
+}
+
+/// Tracing a transaction unknown to the node fails
+#[test]
+fn test_trace_unknown_transaction() {
+    test_with_client!(test_utils::NodeType::NonValidator, client, async move {
+        let error = client
+            .EXPERIMENTAL_trace_transaction(RpcTraceTransactionRequest {
+                tx_hash: CryptoHash::default(),
+                extended_gas_profile: false,
+            })
+            .await
+            .unwrap_err();
+        let cause = if let Some(RpcErrorKind::HandlerError(cause)) = error.error_struct {
+            cause
+        } else {
+            panic!("expected a handler error, but received: {:?}", error);
+        };
+        assert_eq!(cause["name"], "UNKNOWN_TRANSACTION");
+    });
+}
+
 /// query contract code
 #[test]
 fn test_query_contract_code() {
@@ -7,13 +7,14 @@ use futures::{future, FutureExt, TryFutureExt};
 use near_actix_test_utils::run_actix;
 use near_crypto::{InMemorySigner, KeyType};
 use near_jsonrpc::client::new_client;
+use near_jsonrpc_primitives::types::estimate_gas::RpcEstimateGasRequest;
 use near_network::test_utils::WaitOrTimeoutActor;
 use near_o11y::testonly::{init_integration_logger, init_test_logger};
 use near_primitives::hash::{hash, CryptoHash};
 use near_primitives::serialize::to_base64;
 use near_primitives::transaction::SignedTransaction;
 use near_primitives::types::BlockReference;
-use near_primitives::views::FinalExecutionStatus;
+use near_primitives::views::{ActionView, FinalExecutionOutcomeView, FinalExecutionStatus};
 
 use near_jsonrpc_tests::{self as test_utils, test_with_client};
 
@@ -97,6 +98,51 @@ fn test_send_tx_commit() {
     });
 }
 
+/// Test that the gas estimate of a transaction matches its actual execution.
+#[test]
+fn test_estimate_gas() {
+    test_with_client!(test_utils::NodeType::Validator, client, async move {
+        let signer = InMemorySigner::from_seed("test1".parse().unwrap(), KeyType::ED25519, "test1");
+        let estimate = client
+            .EXPERIMENTAL_estimate_gas(RpcEstimateGasRequest {
+                signer_id: "test1".parse().unwrap(),
+                public_key: signer.public_key.clone(),
+                nonce: None,
+                receiver_id: "test2".parse().unwrap(),
+                actions: vec![ActionView::Transfer { deposit: 100 }],
+            })
+            .await
+            .unwrap()
+            .estimate;
+        assert_eq!(estimate.final_outcome.status, FinalExecutionStatus::SuccessValue(Vec::new()));
+
+        let block_hash = client.block(BlockReference::latest()).await.unwrap().header.hash;
+        let tx = SignedTransaction::send_money(
+            1,
+            "test1".parse().unwrap(),
+            "test2".parse().unwrap(),
+            &signer,
+            100,
+            block_hash,
+        );
+        let bytes = tx.try_to_vec().unwrap();
+        let result = client.broadcast_tx_commit(to_base64(&bytes)).await.unwrap();
+        assert_eq!(result.status, estimate.final_outcome.status);
+        assert_eq!(
+            result.transaction_outcome.outcome.gas_burnt,
+            estimate.final_outcome.transaction_outcome.outcome.gas_burnt
+        );
+        let gas_burnt = |outcome: &FinalExecutionOutcomeView| {
+            std::iter::once(&outcome.transaction_outcome)
+                .chain(&outcome.receipts_outcome)
+                .map(|outcome| outcome.outcome.gas_burnt)
+                .sum::<u64>()
+        };
+        assert_eq!(gas_burnt(&result), gas_burnt(&estimate.final_outcome));
+        assert_eq!(gas_burnt(&result), estimate.gas_burnt);
+    });
+}
+
 /// Test that expired transaction should be rejected
 #[test]
 fn test_expired_tx() {
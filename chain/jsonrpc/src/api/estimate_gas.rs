@@ -0,0 +1,36 @@
+use serde_json::Value;
+
+use near_client_primitives::types::EstimateGasError;
+use near_jsonrpc_primitives::errors::RpcParseError;
+use near_jsonrpc_primitives::types::estimate_gas::{RpcEstimateGasError, RpcEstimateGasRequest};
+
+use super::{parse_params, RpcFrom, RpcRequest};
+
+impl RpcRequest for RpcEstimateGasRequest {
+    fn parse(value: Option<Value>) -> Result<Self, RpcParseError> {
+        parse_params::<Self>(value)
+    }
+}
+
+impl RpcFrom<actix::MailboxError> for RpcEstimateGasError {
+    fn rpc_from(error: actix::MailboxError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl RpcFrom<EstimateGasError> for RpcEstimateGasError {
+    fn rpc_from(error: EstimateGasError) -> Self {
+        match error {
+            EstimateGasError::IOError(error_message) => Self::InternalError { error_message },
+            EstimateGasError::NoSyncedBlocks => Self::NoSyncedBlocks,
+            EstimateGasError::UnavailableShard(requested_shard_id) => {
+                Self::UnavailableShard { requested_shard_id }
+            }
+            EstimateGasError::InvalidTransaction(context) => Self::InvalidTransaction { context },
+            EstimateGasError::Unreachable(ref error_message) => {
+                tracing::warn!(target: "jsonrpc", "Unreachable error occurred: {}", error_message);
+                Self::InternalError { error_message: error.to_string() }
+            }
+        }
+    }
+}
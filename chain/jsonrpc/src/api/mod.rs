@@ -10,6 +10,7 @@ mod changes;
 mod chunks;
 mod client_config;
 mod config;
+mod estimate_gas;
 mod gas_price;
 mod light_client;
 mod maintenance;
@@ -18,6 +19,7 @@ mod query;
 mod receipts;
 mod sandbox;
 mod status;
+mod trace;
 mod transactions;
 mod validator;
 
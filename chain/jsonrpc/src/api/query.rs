@@ -68,6 +68,8 @@ impl RpcRequest for RpcQueryRequest {
                     account_id,
                     prefix: parse_data()?.into(),
                     include_proof: false,
+                    start_key: None,
+                    limit: None,
                 },
                 "call" => match maybe_extra_arg {
                     Some(method_name) => QueryRequest::CallFunction {
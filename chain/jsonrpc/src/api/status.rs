@@ -49,6 +49,12 @@ impl RpcFrom<near_client_primitives::debug::DebugStatusResponse>
                     x,
                 )
             }
+            near_client_primitives::debug::DebugStatusResponse::ValidatorKickoutPrediction(x) => {
+                near_jsonrpc_primitives::types::status::DebugStatusResponse::ValidatorKickoutPrediction(x)
+            }
+            near_client_primitives::debug::DebugStatusResponse::DoomslugStatus(x) => {
+                near_jsonrpc_primitives::types::status::DebugStatusResponse::DoomslugStatus(x)
+            }
         }
     }
 }
@@ -0,0 +1,48 @@
+use serde_json::Value;
+
+use near_client_primitives::types::TraceError;
+use near_jsonrpc_primitives::errors::RpcParseError;
+use near_jsonrpc_primitives::types::trace::{
+    RpcTraceCallRequest, RpcTraceError, RpcTraceTransactionRequest,
+};
+
+use super::{parse_params, RpcFrom, RpcRequest};
+
+impl RpcRequest for RpcTraceCallRequest {
+    fn parse(value: Option<Value>) -> Result<Self, RpcParseError> {
+        parse_params::<Self>(value)
+    }
+}
+
+impl RpcRequest for RpcTraceTransactionRequest {
+    fn parse(value: Option<Value>) -> Result<Self, RpcParseError> {
+        parse_params::<Self>(value)
+    }
+}
+
+impl RpcFrom<actix::MailboxError> for RpcTraceError {
+    fn rpc_from(error: actix::MailboxError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl RpcFrom<TraceError> for RpcTraceError {
+    fn rpc_from(error: TraceError) -> Self {
+        match error {
+            TraceError::IOError(error_message) => Self::InternalError { error_message },
+            TraceError::UnknownBlock(_) => Self::UnknownBlock { error_message: error.to_string() },
+            TraceError::UnknownTransaction(transaction_hash) => {
+                Self::UnknownTransaction { transaction_hash }
+            }
+            TraceError::UnavailableShard(requested_shard_id) => {
+                Self::UnavailableShard { requested_shard_id }
+            }
+            TraceError::InvalidCall(vm_error) => Self::InvalidCall { vm_error },
+            TraceError::LimitExceeded(limit) => Self::LimitExceeded { limit },
+            TraceError::Unreachable(ref error_message) => {
+                tracing::warn!(target: "jsonrpc", "Unreachable error occurred: {}", error_message);
+                Self::InternalError { error_message: error.to_string() }
+            }
+        }
+    }
+}
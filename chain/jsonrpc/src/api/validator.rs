@@ -3,7 +3,7 @@ use serde_json::Value;
 use near_client_primitives::types::GetValidatorInfoError;
 use near_jsonrpc_primitives::errors::RpcParseError;
 use near_jsonrpc_primitives::types::validator::{
-    RpcValidatorError, RpcValidatorRequest, RpcValidatorsOrderedRequest,
+    RpcValidatorError, RpcValidatorRequest, RpcValidatorRewardsRequest, RpcValidatorsOrderedRequest,
 };
 use near_primitives::types::{EpochReference, MaybeBlockId};
 
@@ -24,6 +24,13 @@ impl RpcRequest for RpcValidatorRequest {
     }
 }
 
+impl RpcRequest for RpcValidatorRewardsRequest {
+    fn parse(value: Option<Value>) -> Result<Self, RpcParseError> {
+        let RpcValidatorRequest { epoch_reference } = RpcValidatorRequest::parse(value)?;
+        Ok(Self { epoch_reference })
+    }
+}
+
 impl RpcRequest for RpcValidatorsOrderedRequest {
     fn parse(value: Option<Value>) -> Result<Self, RpcParseError> {
         parse_params::<Self>(value)
@@ -18,11 +18,12 @@ use tracing::info;
 
 use near_chain_configs::GenesisConfig;
 use near_client::{
-    ClientActor, DebugStatus, GetBlock, GetBlockProof, GetChunk, GetClientConfig,
+    ClientActor, DebugStatus, EstimateGas, GetBlock, GetBlockProof, GetChunk, GetClientConfig,
     GetExecutionOutcome, GetGasPrice, GetMaintenanceWindows, GetNetworkInfo,
     GetNextLightClientBlock, GetProtocolConfig, GetReceipt, GetStateChanges,
-    GetStateChangesInBlock, GetValidatorInfo, GetValidatorOrdered, ProcessTxRequest,
-    ProcessTxResponse, Query, Status, TxStatus, ViewClientActor,
+    GetStateChangesInBlock, GetValidatorInfo, GetValidatorOrdered, GetValidatorRewards,
+    ProcessTxRequest, ProcessTxResponse, Query, Status, TraceCall, TraceTransaction, TxStatus,
+    ViewClientActor,
 };
 pub use near_jsonrpc_client as client;
 use near_jsonrpc_primitives::errors::RpcError;
@@ -30,7 +31,7 @@ use near_jsonrpc_primitives::message::{Message, Request};
 use near_jsonrpc_primitives::types::config::RpcProtocolConfigResponse;
 use near_o11y::metrics::{prometheus, Encoder, TextEncoder};
 use near_primitives::hash::CryptoHash;
-use near_primitives::transaction::SignedTransaction;
+use near_primitives::transaction::{Action, SignedTransaction};
 use near_primitives::types::{AccountId, BlockHeight};
 use near_primitives::views::FinalExecutionOutcomeViewEnum;
 
@@ -329,6 +330,9 @@ impl JsonRpcHandler {
             "EXPERIMENTAL_check_tx" => {
                 process_method_call(request, |params| self.check_tx(params)).await
             }
+            "EXPERIMENTAL_estimate_gas" => {
+                process_method_call(request, |params| self.estimate_gas(params)).await
+            }
             "EXPERIMENTAL_genesis_config" => {
                 process_method_call(request, |_params: ()| async {
                     Result::<_, std::convert::Infallible>::Ok(&self.genesis_config)
@@ -347,12 +351,21 @@ impl JsonRpcHandler {
             "EXPERIMENTAL_receipt" => {
                 process_method_call(request, |params| self.receipt(params)).await
             }
+            "EXPERIMENTAL_trace_call" => {
+                process_method_call(request, |params| self.trace_call(params)).await
+            }
+            "EXPERIMENTAL_trace_transaction" => {
+                process_method_call(request, |params| self.trace_transaction(params)).await
+            }
             "EXPERIMENTAL_tx_status" => {
                 process_method_call(request, |params| self.tx_status_common(params, true)).await
             }
             "EXPERIMENTAL_validators_ordered" => {
                 process_method_call(request, |params| self.validators_ordered(params)).await
             }
+            "EXPERIMENTAL_validator_rewards" => {
+                process_method_call(request, |params| self.validator_rewards(params)).await
+            }
             "EXPERIMENTAL_maintenance_windows" => {
                 process_method_call(request, |params| self.maintenance_windows(params)).await
             }
@@ -801,6 +814,12 @@ impl JsonRpcHandler {
                     "/debug/api/requested_state_parts" => {
                         self.client_send(DebugStatus::RequestedStateParts).await?.rpc_into()
                     }
+                    "/debug/api/validator_kickout_prediction" => {
+                        self.client_send(DebugStatus::ValidatorKickoutPrediction).await?.rpc_into()
+                    }
+                    "/debug/api/doomslug_status" => {
+                        self.client_send(DebugStatus::DoomslugStatus).await?.rpc_into()
+                    }
                     "/debug/api/peer_store" => self
                         .peer_manager_send(near_network::debug::GetDebugStatus::PeerStore)
                         .await?
@@ -1066,6 +1085,21 @@ impl JsonRpcHandler {
         Ok(validators)
     }
 
+    /// Recomputes the rewards of a finished epoch from the stored epoch and
+    /// block info, so that they can be checked against the recorded ones.
+    async fn validator_rewards(
+        &self,
+        request_data: near_jsonrpc_primitives::types::validator::RpcValidatorRewardsRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::validator::RpcValidatorRewardsResponse,
+        near_jsonrpc_primitives::types::validator::RpcValidatorError,
+    > {
+        let rewards = self
+            .view_client_send(GetValidatorRewards { epoch_reference: request_data.epoch_reference })
+            .await?;
+        Ok(near_jsonrpc_primitives::types::validator::RpcValidatorRewardsResponse { rewards })
+    }
+
     /// If experimental_debug_pages_src_path config is set, reads the html file from that
     /// directory. Otherwise, returns None.
     fn read_html_file_override(&self, html_file: &'static str) -> Option<String> {
@@ -1094,6 +1128,77 @@ impl JsonRpcHandler {
         Ok(windows.iter().map(|r| (r.start, r.end)).collect())
     }
 
+    async fn estimate_gas(
+        &self,
+        request: near_jsonrpc_primitives::types::estimate_gas::RpcEstimateGasRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::estimate_gas::RpcEstimateGasResponse,
+        near_jsonrpc_primitives::types::estimate_gas::RpcEstimateGasError,
+    > {
+        let near_jsonrpc_primitives::types::estimate_gas::RpcEstimateGasRequest {
+            signer_id,
+            public_key,
+            nonce,
+            receiver_id,
+            actions,
+        } = request;
+        let actions = actions
+            .into_iter()
+            .map(Action::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| {
+                near_jsonrpc_primitives::types::estimate_gas::RpcEstimateGasError::InvalidAction {
+                    error_message: err.to_string(),
+                }
+            })?;
+        let estimate = self
+            .view_client_send(EstimateGas { signer_id, public_key, nonce, receiver_id, actions })
+            .await?;
+        Ok(near_jsonrpc_primitives::types::estimate_gas::RpcEstimateGasResponse { estimate })
+    }
+
+    async fn trace_call(
+        &self,
+        request: near_jsonrpc_primitives::types::trace::RpcTraceCallRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::trace::RpcTraceCallResponse,
+        near_jsonrpc_primitives::types::trace::RpcTraceError,
+    > {
+        let near_jsonrpc_primitives::types::trace::RpcTraceCallRequest {
+            block_reference,
+            account_id,
+            method_name,
+            args,
+            extended_gas_profile,
+        } = request;
+        let trace = self
+            .view_client_send(TraceCall {
+                block_reference,
+                account_id,
+                method_name,
+                args,
+                extended_gas_profile,
+            })
+            .await?;
+        Ok(near_jsonrpc_primitives::types::trace::RpcTraceCallResponse { trace })
+    }
+
+    async fn trace_transaction(
+        &self,
+        request: near_jsonrpc_primitives::types::trace::RpcTraceTransactionRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::trace::RpcTraceTransactionResponse,
+        near_jsonrpc_primitives::types::trace::RpcTraceError,
+    > {
+        let near_jsonrpc_primitives::types::trace::RpcTraceTransactionRequest {
+            tx_hash,
+            extended_gas_profile,
+        } = request;
+        let receipts =
+            self.view_client_send(TraceTransaction { tx_hash, extended_gas_profile }).await?;
+        Ok(near_jsonrpc_primitives::types::trace::RpcTraceTransactionResponse { receipts })
+    }
+
     async fn client_config(
         &self,
     ) -> Result<
@@ -1481,6 +1586,7 @@ async fn display_debug_html(
         "chain_n_chunk_info" => Some(debug_page_string!("chain_n_chunk_info.html", handler)),
         "sync" => Some(debug_page_string!("sync.html", handler)),
         "validator" => Some(debug_page_string!("validator.html", handler)),
+        "doomslug" => Some(debug_page_string!("doomslug.html", handler)),
         _ => None,
     };
 
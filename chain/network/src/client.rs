@@ -4,7 +4,7 @@ use crate::network_protocol::{
 };
 use crate::types::{NetworkInfo, ReasonForBan};
 use near_primitives::block::{Approval, Block, BlockHeader};
-use near_primitives::challenge::Challenge;
+use near_primitives::challenge::{Challenge, ChunkStateTransitionProof};
 use near_primitives::hash::CryptoHash;
 use near_primitives::network::{AnnounceAccount, PeerId};
 use near_primitives::sharding::PartialEncodedChunk;
@@ -60,6 +60,8 @@ pub trait Client: Send + Sync + 'static {
 
     async fn partial_encoded_chunk_forward(&self, msg: PartialEncodedChunkForwardMsg);
 
+    async fn chunk_state_transition_proof(&self, proof: ChunkStateTransitionProof, peer_id: PeerId);
+
     async fn block_request(&self, hash: CryptoHash) -> Option<Box<Block>>;
 
     async fn block_headers_request(&self, hashes: Vec<CryptoHash>) -> Option<Vec<BlockHeader>>;
@@ -137,6 +139,13 @@ impl Client for Noop {
 
     async fn partial_encoded_chunk_forward(&self, _msg: PartialEncodedChunkForwardMsg) {}
 
+    async fn chunk_state_transition_proof(
+        &self,
+        _proof: ChunkStateTransitionProof,
+        _peer_id: PeerId,
+    ) {
+    }
+
     async fn block_request(&self, _hash: CryptoHash) -> Option<Box<Block>> {
         None
     }
@@ -9,10 +9,10 @@ use crate::time;
 use crate::types::ROUTED_MESSAGE_TTL;
 use anyhow::Context;
 use near_crypto::{KeyType, SecretKey};
-use near_primitives::network::PeerId;
+use near_primitives::network::{PeerId, PeerLimits};
 use near_primitives::test_utils::create_test_signer;
 use near_primitives::types::AccountId;
-use near_primitives::validator_signer::ValidatorSigner;
+use near_primitives::validator_signer::{UpdateableValidatorSigner, ValidatorSigner};
 use std::collections::HashSet;
 use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
 use std::sync::Arc;
@@ -57,7 +57,8 @@ pub enum ValidatorProxies {
 
 #[derive(Clone)]
 pub struct ValidatorConfig {
-    pub signer: Arc<dyn ValidatorSigner>,
+    /// Shared with the client, which replaces the key when it's rotated.
+    pub signer: UpdateableValidatorSigner,
     pub proxies: ValidatorProxies,
 }
 
@@ -168,7 +169,7 @@ impl NetworkConfig {
     pub fn new(
         cfg: crate::config_json::Config,
         node_key: SecretKey,
-        validator_signer: Option<Arc<dyn ValidatorSigner>>,
+        validator_signer: Option<UpdateableValidatorSigner>,
         archive: bool,
     ) -> anyhow::Result<Self> {
         if cfg.public_addrs.len() > MAX_PEER_ADDRS {
@@ -301,7 +302,7 @@ impl NetworkConfig {
         let node_key = SecretKey::from_seed(KeyType::ED25519, seed);
         let node_addr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, port));
         let validator = ValidatorConfig {
-            signer: Arc::new(create_test_signer(seed)),
+            signer: UpdateableValidatorSigner::new(Arc::new(create_test_signer(seed))),
             proxies: ValidatorProxies::Static(vec![PeerAddr {
                 addr: node_addr,
                 peer_id: PeerId::new(node_key.public_key()),
@@ -354,29 +355,32 @@ impl NetworkConfig {
         }
     }
 
-    pub fn verify(self) -> anyhow::Result<VerifiedConfig> {
-        if !(self.ideal_connections_lo <= self.ideal_connections_hi) {
-            anyhow::bail!(
-                "Invalid ideal_connections values. lo({}) > hi({}).",
-                self.ideal_connections_lo,
-                self.ideal_connections_hi
-            );
-        }
-
-        if !(self.ideal_connections_hi <= self.max_num_peers) {
-            anyhow::bail!(
-                "max_num_peers({}) < ideal_connections_hi({}) which may lead to connection saturation and declining new connections.",
-                self.max_num_peers, self.ideal_connections_hi
-            );
+    /// Limits on the number of connections the node starts with.
+    pub fn peer_limits(&self) -> PeerLimits {
+        PeerLimits {
+            max_num_peers: self.max_num_peers,
+            minimum_outbound_peers: self.minimum_outbound_peers,
+            ideal_connections_lo: self.ideal_connections_lo,
+            ideal_connections_hi: self.ideal_connections_hi,
         }
+    }
 
-        if !(self.safe_set_size > self.minimum_outbound_peers) {
+    /// Checks `peer_limits` against the parts of the config which can't be
+    /// changed while the node is running.
+    pub fn verify_peer_limits(&self, peer_limits: &PeerLimits) -> anyhow::Result<()> {
+        peer_limits.verify().map_err(anyhow::Error::msg)?;
+        if !(self.safe_set_size > peer_limits.minimum_outbound_peers) {
             anyhow::bail!(
                 "safe_set_size({}) must be larger than minimum_outbound_peers({}).",
                 self.safe_set_size,
-                self.minimum_outbound_peers
+                peer_limits.minimum_outbound_peers
             );
         }
+        Ok(())
+    }
+
+    pub fn verify(self) -> anyhow::Result<VerifiedConfig> {
+        self.verify_peer_limits(&self.peer_limits())?;
 
         if UPDATE_INTERVAL_LAST_TIME_RECEIVED_MESSAGE * 2 > self.peer_recent_time_window {
             anyhow::bail!(
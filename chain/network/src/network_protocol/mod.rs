@@ -28,7 +28,7 @@ use near_crypto::PublicKey;
 use near_crypto::Signature;
 use near_o11y::OpenTelemetrySpanExt;
 use near_primitives::block::{Approval, Block, BlockHeader, GenesisId};
-use near_primitives::challenge::Challenge;
+use near_primitives::challenge::{Challenge, ChunkStateTransitionProof};
 use near_primitives::hash::CryptoHash;
 use near_primitives::merkle::combine_hash;
 use near_primitives::network::{AnnounceAccount, PeerId};
@@ -128,7 +128,7 @@ impl AccountData {
                 MAX_ACCOUNT_DATA_SIZE_BYTES
             );
         }
-        let signature = signer.sign_account_key_payload(&payload);
+        let signature = signer.sign_account_key_payload(&payload)?;
         Ok(SignedAccountData {
             account_data: self,
             payload: AccountKeySignedPayload { payload, signature },
@@ -193,18 +193,19 @@ impl OwnedAccount {
     /// Serializes OwnedAccount to proto and signs it using `signer`.
     /// Panics if OwnedAccount.account_key doesn't match signer.public_key(),
     /// as this would likely be a bug.
-    pub fn sign(self, signer: &dyn ValidatorSigner) -> SignedOwnedAccount {
+    /// Returns an error if the signer fails to sign the payload.
+    pub fn sign(self, signer: &dyn ValidatorSigner) -> anyhow::Result<SignedOwnedAccount> {
         assert_eq!(
             self.account_key,
             signer.public_key(),
             "OwnedAccount.account_key doesn't match the signer's account_key"
         );
         let payload = proto::AccountKeyPayload::from(&self).write_to_bytes().unwrap();
-        let signature = signer.sign_account_key_payload(&payload);
-        SignedOwnedAccount {
+        let signature = signer.sign_account_key_payload(&payload)?;
+        Ok(SignedOwnedAccount {
             owned_account: self,
             payload: AccountKeySignedPayload { payload, signature },
-        }
+        })
     }
 }
 
@@ -425,6 +426,7 @@ pub enum RoutedMessageBody {
     VersionedPartialEncodedChunk(PartialEncodedChunk),
     VersionedStateResponse(StateResponseInfo),
     PartialEncodedChunkForward(PartialEncodedChunkForwardMsg),
+    ChunkStateTransitionProof(Box<ChunkStateTransitionProof>),
 }
 
 impl RoutedMessageBody {
@@ -498,6 +500,12 @@ impl fmt::Debug for RoutedMessageBody {
             ),
             RoutedMessageBody::Ping(_) => write!(f, "Ping"),
             RoutedMessageBody::Pong(_) => write!(f, "Pong"),
+            RoutedMessageBody::ChunkStateTransitionProof(proof) => write!(
+                f,
+                "ChunkStateTransitionProof({}, {:?})",
+                proof.prev_block_hash,
+                proof.chunk_header.chunk_hash()
+            ),
         }
     }
 }
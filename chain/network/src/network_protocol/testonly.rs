@@ -69,6 +69,7 @@ pub fn make_block(
         // TODO: migrate to clock.now()
         Some(chrono::Utc::now()), // timestamp_override
     )
+    .unwrap()
 }
 
 pub fn make_account_id<R: Rng>(rng: &mut R) -> AccountId {
@@ -105,11 +106,9 @@ pub fn make_peer_info<R: Rng>(rng: &mut R) -> PeerInfo {
 pub fn make_announce_account<R: Rng>(rng: &mut R) -> AnnounceAccount {
     let peer_id = make_peer_id(rng);
     let validator_signer = make_validator_signer(rng);
-    let signature = validator_signer.sign_account_announce(
-        validator_signer.validator_id(),
-        &peer_id,
-        &EpochId::default(),
-    );
+    let signature = validator_signer
+        .sign_account_announce(validator_signer.validator_id(), &peer_id, &EpochId::default())
+        .unwrap();
     AnnounceAccount {
         account_id: validator_signer.validator_id().clone(),
         peer_id: peer_id,
@@ -178,6 +177,7 @@ pub fn make_challenge<R: Rng>(rng: &mut R) -> Challenge {
         }),
         &make_validator_signer(rng),
     )
+    .unwrap()
 }
 
 // Based on ShardsManager::prepare_partial_encoded_chunk_response_from_chunk.
@@ -399,13 +399,18 @@ impl PeerActor {
                 archival: self.network_state.config.archive,
             },
             partial_edge_info: spec.partial_edge_info,
-            owned_account: self.network_state.config.validator.as_ref().map(|vc| {
+            owned_account: self.network_state.config.validator.as_ref().and_then(|vc| {
+                let signer = vc.signer.get();
                 OwnedAccount {
-                    account_key: vc.signer.public_key().clone(),
+                    account_key: signer.public_key(),
                     peer_id: self.network_state.config.node_id(),
                     timestamp: self.clock.now_utc(),
                 }
-                .sign(vc.signer.as_ref())
+                .sign(signer.as_ref())
+                .map_err(
+                    |err| tracing::warn!(target: "network", ?err, "failed to sign OwnedAccount"),
+                )
+                .ok()
             }),
         };
         let msg = match spec.tier {
@@ -924,6 +929,10 @@ impl PeerActor {
                 network_state.client.partial_encoded_chunk_forward(msg).await;
                 None
             }
+            RoutedMessageBody::ChunkStateTransitionProof(proof) => {
+                network_state.client.chunk_state_transition_proof(*proof, peer_id).await;
+                None
+            }
             RoutedMessageBody::ReceiptOutcomeRequest(_) => {
                 // Silently ignore for the time being.  We’ve been still
                 // sending those messages at protocol version 56 so we
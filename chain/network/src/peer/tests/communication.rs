@@ -5,13 +5,14 @@ use crate::network_protocol::{
 use crate::peer::testonly::{Event, PeerConfig, PeerHandle};
 use crate::peer_manager::peer_manager_actor::Event as PME;
 use crate::tcp;
-use crate::testonly::make_rng;
 use crate::testonly::stream::Stream;
+use crate::testonly::{fake_client, make_rng};
 use crate::time;
 use crate::types::{PartialEncodedChunkRequestMsg, PartialEncodedChunkResponseMsg};
 use anyhow::Context as _;
 use assert_matches::assert_matches;
 use near_o11y::testonly::init_test_logger;
+use near_primitives::challenge::{ChunkStateTransitionProof, PartialState};
 use near_primitives::version::{PEER_MIN_ALLOWED_PROTOCOL_VERSION, PROTOCOL_VERSION};
 use std::sync::Arc;
 
@@ -137,6 +138,34 @@ async fn test_peer_communication(
     outbound.send(want.clone()).await;
     events.recv_until(message_processed(want)).await;
 
+    tracing::info!(target:"test","ChunkStateTransitionProof");
+    let mut events = inbound.events.from_now();
+    let prev_chunk_hash = chain.blocks[3].chunks()[0].chunk_hash();
+    let want_proof = ChunkStateTransitionProof {
+        prev_block_hash: chain.blocks[3].hash().clone(),
+        prev_chunk_transactions: chain.chunks[&prev_chunk_hash].transactions().to_vec(),
+        prev_chunk_incoming_receipts: vec![],
+        chunk_header: chain.blocks[4].chunks()[0].clone(),
+        partial_state: PartialState(vec![]),
+    };
+    let want = PeerMessage::Routed(Box::new(outbound.routed_message(
+        RoutedMessageBody::ChunkStateTransitionProof(Box::new(want_proof.clone())),
+        inbound.cfg.id(),
+        1,    // ttl
+        None, // TODO(gprusak): this should be clock.now_utc(), once borsh support is dropped.
+    )));
+    outbound.send(want.clone()).await;
+    events
+        .recv_until(|ev| match ev {
+            Event::Client(fake_client::Event::ChunkStateTransitionProof(got, peer_id))
+                if got == want_proof && peer_id == outbound.cfg.id() =>
+            {
+                Some(())
+            }
+            _ => None,
+        })
+        .await;
+
     tracing::info!(target:"test","Transaction");
     let mut events = inbound.events.from_now();
     let want = data::make_signed_transaction(&mut rng);
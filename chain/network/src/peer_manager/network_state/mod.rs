@@ -1,5 +1,6 @@
 use crate::accounts_data;
 use crate::client;
+use crate::concurrency::arc_mutex::ArcMutex;
 use crate::concurrency::demux;
 use crate::concurrency::runtime::Runtime;
 use crate::config;
@@ -21,11 +22,11 @@ use crate::types::{ChainInfo, PeerType, ReasonForBan};
 use arc_swap::ArcSwap;
 use near_primitives::block::GenesisId;
 use near_primitives::hash::CryptoHash;
-use near_primitives::network::PeerId;
+use near_primitives::network::{PeerId, PeerLimits};
 use near_primitives::types::AccountId;
 use parking_lot::Mutex;
 use std::net::SocketAddr;
-use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::atomic::AtomicUsize;
 use std::sync::Arc;
 use tracing::Instrument as _;
 
@@ -123,12 +124,10 @@ pub(crate) struct NetworkState {
     /// Whitelisted nodes, which are allowed to connect even if the connection limit has been
     /// reached.
     whitelist_nodes: Vec<WhitelistNode>,
-    /// Maximal allowed number of peer connections.
-    /// It is initialized with config.max_num_peers and is mutable
-    /// only so that it can be changed in tests.
-    /// TODO(gprusak): determine why tests need to change that dynamically
-    /// in the first place.
-    pub max_num_peers: AtomicU32,
+    /// Limits on the number of peer connections.
+    /// They are initialized from config and can be changed while the node
+    /// is running, see `set_peer_limits()`.
+    pub peer_limits: ArcMutex<PeerLimits>,
 
     /// Mutex which prevents overlapping calls to tier1_advertise_proxies.
     tier1_advertise_proxies_mutex: tokio::sync::Mutex<()>,
@@ -174,7 +173,7 @@ impl NetworkState {
             )),
             txns_since_last_block: AtomicUsize::new(0),
             whitelist_nodes,
-            max_num_peers: AtomicU32::new(config.max_num_peers),
+            peer_limits: ArcMutex::new(config.peer_limits()),
             add_edges_demux: demux::Demux::new(config.routing_table_update_rate_limit),
             set_chain_info_mutex: Mutex::new(()),
             config,
@@ -232,7 +231,7 @@ impl NetworkState {
         // Check if we have spare inbound connections capacity.
         let tier2 = self.tier2.load();
         if tier2.ready.len() + tier2.outbound_handshakes.len()
-            < self.max_num_peers.load(Ordering::Relaxed) as usize
+            < self.peer_limits.load().max_num_peers as usize
             && !self.config.inbound_disabled
         {
             return true;
@@ -300,7 +299,7 @@ impl NetworkState {
                             let tier2 = this.tier2.load();
                             tracing::debug!(target: "network",
                                 tier2 = tier2.ready.len(), outgoing_peers = tier2.outbound_handshakes.len(),
-                                max_num_peers = this.max_num_peers.load(Ordering::Relaxed),
+                                max_num_peers = this.peer_limits.load().max_num_peers,
                                 "Dropping handshake (network at max capacity)."
                             );
                             return Err(RegisterPeerError::ConnectionLimitExceeded);
@@ -631,6 +630,20 @@ impl NetworkState {
         .unwrap()
     }
 
+    /// Changes the limits on the number of peer connections.  Invalid limits
+    /// are ignored.  Connections above the new limits are closed gradually
+    /// by the periodic connection maintenance.
+    pub fn set_peer_limits(&self, peer_limits: PeerLimits) {
+        if let Err(err) = self.config.verify_peer_limits(&peer_limits) {
+            tracing::error!(target: "network", ?peer_limits, %err, "Ignoring invalid peer limits");
+            return;
+        }
+        let old = self.peer_limits.update(|old| (old, peer_limits));
+        if old != peer_limits {
+            tracing::info!(target: "network", ?old, new = ?peer_limits, "Updated peer limits");
+        }
+    }
+
     /// Sets the chain info, and updates the set of TIER1 keys.
     /// Returns true iff the set of TIER1 keys has changed.
     pub fn set_chain_info(self: &Arc<Self>, info: ChainInfo) -> bool {
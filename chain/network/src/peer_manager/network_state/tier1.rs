@@ -12,6 +12,7 @@ use crate::types::PeerType;
 use near_crypto::PublicKey;
 use near_o11y::log_assert;
 use near_primitives::network::PeerId;
+use near_primitives::validator_signer::ValidatorSigner;
 use rand::seq::IteratorRandom as _;
 use rand::seq::SliceRandom as _;
 use std::collections::{HashMap, HashSet};
@@ -148,22 +149,27 @@ impl super::NetworkState {
         };
         tracing::info!(target:"network","connected to proxies {my_proxies:?}");
         let now = clock.now_utc();
+        let signer = vc.signer.get();
         let version =
-            self.accounts_data.load().data.get(&vc.signer.public_key()).map_or(0, |d| d.version)
-                + 1;
-        // This unwrap is safe, because we did signed a sample payload during
-        // config validation. See config::Config::new().
-        let my_data = Arc::new(
-            AccountData {
-                peer_id: self.config.node_id(),
-                account_key: vc.signer.public_key(),
-                proxies: my_proxies.clone(),
-                timestamp: now,
-                version,
+            self.accounts_data.load().data.get(&signer.public_key()).map_or(0, |d| d.version) + 1;
+        // The payload size has been validated by signing a sample payload during
+        // config validation (see config::Config::new()), so signing can fail
+        // only if the validator signer refuses to sign or is unavailable.
+        let my_data = match (AccountData {
+            peer_id: self.config.node_id(),
+            account_key: signer.public_key(),
+            proxies: my_proxies.clone(),
+            timestamp: now,
+            version,
+        })
+        .sign(signer.as_ref())
+        {
+            Ok(data) => Arc::new(data),
+            Err(err) => {
+                tracing::warn!(target: "network", ?err, "failed to sign AccountData");
+                return vec![];
             }
-            .sign(vc.signer.as_ref())
-            .unwrap(),
-        );
+        };
         let (new_data, err) = self.accounts_data.insert(vec![my_data]).await;
         // Inserting node's own AccountData should never fail.
         if let Some(err) = err {
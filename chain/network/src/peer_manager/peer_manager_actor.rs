@@ -324,11 +324,11 @@ impl PeerManagerActor {
         let potential_outbound_connections =
             tier2.ready.values().filter(|peer| peer.peer_type == PeerType::Outbound).count()
                 + tier2.outbound_handshakes.len();
+        let peer_limits = self.state.peer_limits.load();
 
-        (total_connections < self.state.config.ideal_connections_lo as usize
-            || (total_connections < self.state.max_num_peers.load(Ordering::Relaxed) as usize
-                && potential_outbound_connections
-                    < self.state.config.minimum_outbound_peers as usize))
+        (total_connections < peer_limits.ideal_connections_lo as usize
+            || (total_connections < peer_limits.max_num_peers as usize
+                && potential_outbound_connections < peer_limits.minimum_outbound_peers as usize))
             && !self.state.config.outbound_disabled
     }
 
@@ -402,6 +402,7 @@ impl PeerManagerActor {
     ///    until safe set has safe_set_size elements.
     fn maybe_stop_active_connection(&self) {
         let tier2 = self.state.tier2.load();
+        let peer_limits = self.state.peer_limits.load();
         let filter_peers = |predicate: &dyn Fn(&connection::Connection) -> bool| -> Vec<_> {
             tier2
                 .ready
@@ -419,14 +420,14 @@ impl PeerManagerActor {
         safe_set.extend(whitelisted_peers);
 
         // If there is not enough non-whitelisted peers, return without disconnecting anyone.
-        if tier2.ready.len() - safe_set.len() <= self.state.config.ideal_connections_hi as usize {
+        if tier2.ready.len() - safe_set.len() <= peer_limits.ideal_connections_hi as usize {
             return;
         }
 
         // If there is not enough outbound peers, add them to the safe set.
         let outbound_peers = filter_peers(&|p| p.peer_type == PeerType::Outbound);
         if outbound_peers.len() + tier2.outbound_handshakes.len()
-            <= self.state.config.minimum_outbound_peers as usize
+            <= peer_limits.minimum_outbound_peers as usize
         {
             safe_set.extend(outbound_peers);
         }
@@ -469,7 +470,7 @@ impl PeerManagerActor {
         if let Some(p) = candidates.choose(&mut rand::thread_rng()) {
             tracing::debug!(target: "network", id = ?p.peer_info.id,
                 tier2_len = tier2.ready.len(),
-                ideal_connections_hi = self.state.config.ideal_connections_hi,
+                ideal_connections_hi = peer_limits.ideal_connections_hi,
                 "Stop active connection"
             );
             p.stop(None);
@@ -603,7 +604,7 @@ impl PeerManagerActor {
             connected_peers: tier2.ready.values().map(connected_peer).collect(),
             tier1_connections: tier1.ready.values().map(connected_peer).collect(),
             num_connected_peers: tier2.ready.len(),
-            peer_max_count: self.state.max_num_peers.load(Ordering::Relaxed),
+            peer_max_count: self.state.peer_limits.load().max_num_peers,
             highest_height_peers: self.highest_height_peers(),
             sent_bytes_per_sec: tier2
                 .ready
@@ -851,6 +852,17 @@ impl PeerManagerActor {
                     NetworkResponses::RouteNotFound
                 }
             }
+            NetworkRequests::ChunkStateTransitionProof { account_id, proof } => {
+                if self.state.send_message_to_account(
+                    &self.clock,
+                    &account_id,
+                    RoutedMessageBody::ChunkStateTransitionProof(proof),
+                ) {
+                    NetworkResponses::NoResponse
+                } else {
+                    NetworkResponses::RouteNotFound
+                }
+            }
             NetworkRequests::ForwardTx(account_id, tx) => {
                 if self.state.send_message_to_account(
                     &self.clock,
@@ -884,7 +896,10 @@ impl PeerManagerActor {
     #[perf]
     fn handle_msg_set_adv_options(&mut self, msg: crate::test_utils::SetAdvOptions) {
         if let Some(set_max_peers) = msg.set_max_peers {
-            self.state.max_num_peers.store(set_max_peers as u32, Ordering::Relaxed);
+            self.state.peer_limits.update(|mut peer_limits| {
+                peer_limits.max_num_peers = set_max_peers as u32;
+                ((), peer_limits)
+            });
         }
     }
 
@@ -908,6 +923,10 @@ impl PeerManagerActor {
                 }
                 PeerManagerMessageResponse::OutboundTcpConnect
             }
+            PeerManagerMessageRequest::SetPeerLimits(peer_limits) => {
+                self.state.set_peer_limits(peer_limits);
+                PeerManagerMessageResponse::SetPeerLimits
+            }
             // TEST-ONLY
             PeerManagerMessageRequest::SetAdvOptions(msg) => {
                 self.handle_msg_set_adv_options(msg);
@@ -21,6 +21,7 @@ use crate::PeerManagerActor;
 use near_o11y::WithSpanContextExt;
 use near_primitives::network::{AnnounceAccount, PeerId};
 use near_primitives::types::AccountId;
+use near_primitives::validator_signer::ValidatorSigner;
 use std::collections::HashSet;
 use std::future::Future;
 use std::pin::Pin;
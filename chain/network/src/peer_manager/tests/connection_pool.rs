@@ -13,6 +13,7 @@ use crate::testonly::make_rng;
 use crate::testonly::stream::Stream;
 use crate::time;
 use near_o11y::testonly::init_test_logger;
+use near_primitives::validator_signer::ValidatorSigner;
 use near_primitives::version::PROTOCOL_VERSION;
 use std::sync::Arc;
 
@@ -169,7 +170,8 @@ async fn owned_account_mismatch() {
                     peer_id: data::make_peer_id(rng),
                     timestamp: clock.now_utc(),
                 }
-                .sign(vc.signer.as_ref()),
+                .sign(&vc.signer)
+                .unwrap(),
             ),
         }))
         .await;
@@ -282,7 +284,8 @@ async fn invalid_edge() {
                         peer_id: cfg.node_id(),
                         timestamp: clock.now_utc(),
                     }
-                    .sign(vc.signer.as_ref()),
+                    .sign(&vc.signer)
+                    .unwrap(),
                 ),
             };
             let handshake = match tier {
@@ -12,6 +12,7 @@ use crate::types::{NetworkRequests, NetworkResponses, PeerManagerMessageRequest}
 use near_o11y::testonly::init_test_logger;
 use near_o11y::WithSpanContextExt;
 use near_primitives::block_header::{Approval, ApprovalInner, ApprovalMessage};
+use near_primitives::types::EpochId;
 use near_primitives::validator_signer::ValidatorSigner;
 use near_store::db::TestDB;
 use rand::Rng as _;
@@ -23,7 +24,7 @@ fn make_block_approval(rng: &mut Rng, signer: &dyn ValidatorSigner) -> Approval
     let inner = ApprovalInner::Endorsement(data::make_hash(rng));
     let target_height = rng.gen_range(0..100000);
     Approval {
-        signature: signer.sign_approval(&inner, target_height),
+        signature: signer.sign_approval(&inner, target_height, &EpochId::default()).unwrap(),
         account_id: signer.validator_id().clone(),
         target_height,
         inner,
@@ -53,8 +54,8 @@ async fn send_tier1_message(
     from: &peer_manager::testonly::ActorHandler,
     to: &peer_manager::testonly::ActorHandler,
 ) {
-    let from_signer = from.cfg.validator.as_ref().unwrap().signer.clone();
-    let to_signer = to.cfg.validator.as_ref().unwrap().signer.clone();
+    let from_signer = from.cfg.validator.as_ref().unwrap().signer.get();
+    let to_signer = to.cfg.validator.as_ref().unwrap().signer.get();
     let target = to_signer.validator_id().clone();
     let want = make_block_approval(rng, from_signer.as_ref());
     let req = NetworkRequests::Approval {
@@ -6,7 +6,7 @@ use crate::network_protocol::{
 use crate::sink::Sink;
 use crate::types::{NetworkInfo, ReasonForBan, StateResponseInfoV2};
 use near_primitives::block::{Approval, Block, BlockHeader};
-use near_primitives::challenge::Challenge;
+use near_primitives::challenge::{Challenge, ChunkStateTransitionProof};
 use near_primitives::hash::CryptoHash;
 use near_primitives::network::{AnnounceAccount, PeerId};
 use near_primitives::sharding::{ChunkHash, PartialEncodedChunk, PartialEncodedChunkPart};
@@ -26,6 +26,7 @@ pub enum Event {
     Challenge(Challenge),
     Chunk(Vec<PartialEncodedChunkPart>),
     ChunkRequest(ChunkHash),
+    ChunkStateTransitionProof(ChunkStateTransitionProof, PeerId),
     Transaction(SignedTransaction),
 }
 
@@ -106,6 +107,14 @@ impl client::Client for Fake {
         unimplemented!();
     }
 
+    async fn chunk_state_transition_proof(
+        &self,
+        proof: ChunkStateTransitionProof,
+        peer_id: PeerId,
+    ) {
+        self.event_sink.push(Event::ChunkStateTransitionProof(proof, peer_id));
+    }
+
     async fn block_request(&self, hash: CryptoHash) -> Option<Box<Block>> {
         self.event_sink.push(Event::BlockRequest(hash));
         None
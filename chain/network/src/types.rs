@@ -10,9 +10,9 @@ use futures::FutureExt;
 use near_crypto::PublicKey;
 use near_o11y::WithSpanContext;
 use near_primitives::block::{ApprovalMessage, Block, GenesisId};
-use near_primitives::challenge::Challenge;
+use near_primitives::challenge::{Challenge, ChunkStateTransitionProof};
 use near_primitives::hash::CryptoHash;
-use near_primitives::network::{AnnounceAccount, PeerId};
+use near_primitives::network::{AnnounceAccount, PeerId, PeerLimits};
 use near_primitives::sharding::PartialEncodedChunkWithArcReceipts;
 use near_primitives::transaction::SignedTransaction;
 use near_primitives::types::BlockHeight;
@@ -162,6 +162,8 @@ pub enum PeerManagerMessageRequest {
     /// Used in tests and internally by PeerManager.
     /// TODO: replace it with AsyncContext::spawn/run_later for internal use.
     OutboundTcpConnect(crate::tcp::Stream),
+    /// Changes the limits on the number of peer connections.
+    SetPeerLimits(PeerLimits),
     /// TEST-ONLY
     SetAdvOptions(crate::test_utils::SetAdvOptions),
     /// The following types of requests are used to trigger actions in the Peer Manager for testing.
@@ -198,6 +200,7 @@ pub enum PeerManagerMessageResponse {
     NetworkResponses(NetworkResponses),
     /// TEST-ONLY
     OutboundTcpConnect,
+    SetPeerLimits,
     SetAdvOptions,
     FetchRoutingTable(RoutingTableInfo),
     PingTo,
@@ -262,6 +265,8 @@ pub enum NetworkRequests {
     },
     /// Forwarding a chunk part to a validator tracking the shard
     PartialEncodedChunkForward { account_id: AccountId, forward: PartialEncodedChunkForwardMsg },
+    /// Proof of the state transition of a chunk for a validator not tracking the shard.
+    ChunkStateTransitionProof { account_id: AccountId, proof: Box<ChunkStateTransitionProof> },
 
     /// Valid transaction but since we are not validators we send this transaction to current validators.
     ForwardTx(AccountId, SignedTransaction),
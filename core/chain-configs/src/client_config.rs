@@ -6,6 +6,7 @@ use std::time::Duration;
 use serde::{Deserialize, Serialize};
 
 use crate::MutableConfigValue;
+use near_primitives::network::PeerLimits;
 use near_primitives::types::{
     AccountId, BlockHeight, BlockHeightDelta, Gas, NumBlocks, NumSeats, ShardId,
 };
@@ -55,6 +56,45 @@ impl Default for GCConfig {
     }
 }
 
+/// Configuration of adaptive sizing of trie shard caches.
+///
+/// When enabled, a memory budget shared by all shard caches, normal and view
+/// ones alike, is periodically redistributed between them according to the
+/// misses each cache observed since the previous rebalancing.  Static limits
+/// from `trie_cache` and `view_trie_cache` store configs only apply until the
+/// first rebalancing.
+///
+/// This config can be updated while the node is running.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+#[serde(default)]
+pub struct AdaptiveTrieCacheConfig {
+    /// Whether the adaptive mode is enabled.
+    pub enabled: bool,
+    /// Memory budget in bytes shared by all shard caches.
+    pub total_max_bytes: u64,
+    /// Memory limit in bytes below which no cache is ever shrunk.
+    pub min_bytes_per_cache: u64,
+    /// Cost of a view cache miss relative to a normal cache miss.  Misses of
+    /// normal caches slow down block processing so by default view caches get
+    /// memory only if they miss much more often.
+    pub view_miss_cost: f64,
+    /// Minimal time between two rebalancings.
+    #[serde(with = "humantime_serde")]
+    pub rebalance_period: Duration,
+}
+
+impl Default for AdaptiveTrieCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            total_max_bytes: 4_000_000_000,
+            min_bytes_per_cache: 50_000_000,
+            view_miss_cost: 0.25,
+            rebalance_period: Duration::from_secs(60),
+        }
+    }
+}
+
 fn default_gc_blocks_limit() -> NumBlocks {
     GCConfig::default().gc_blocks_limit
 }
@@ -111,7 +151,7 @@ pub struct ClientConfig {
     /// Expected increase of header head weight per second during header sync
     pub header_sync_expected_height_per_second: u64,
     /// How long to wait for a response during state sync
-    pub state_sync_timeout: Duration,
+    pub state_sync_timeout: MutableConfigValue<Duration>,
     /// Minimum number of peers to start syncing.
     pub min_num_peers: usize,
     /// Period between logging summary information.
@@ -140,12 +180,18 @@ pub struct ClientConfig {
     pub doosmslug_step_period: Duration,
     /// Behind this horizon header fetch kicks in.
     pub block_header_fetch_horizon: BlockHeightDelta,
-    /// Garbage collection configuration.
-    pub gc: GCConfig,
-    /// Accounts that this client tracks
-    pub tracked_accounts: Vec<AccountId>,
-    /// Shards that this client tracks
-    pub tracked_shards: Vec<ShardId>,
+    /// Garbage collection configuration.  `gc_num_epochs_to_keep` can't be
+    /// changed while the node is running.
+    pub gc: MutableConfigValue<GCConfig>,
+    /// Accounts that this client tracks.  Changes apply from the first epoch
+    /// for which tracked shards weren't determined yet.
+    pub tracked_accounts: MutableConfigValue<Vec<AccountId>>,
+    /// Shards that this client tracks.  Changes apply like the ones of
+    /// `tracked_accounts`.
+    pub tracked_shards: MutableConfigValue<Vec<ShardId>>,
+    /// Limits on the number of peer connections, taken from the `network`
+    /// section of the config.  Changes are forwarded to the peer manager.
+    pub peer_limits: MutableConfigValue<PeerLimits>,
     /// Not clear old data, set `true` for archive nodes.
     pub archive: bool,
     /// Save trie changes. Must be set to true if either of the following is true
@@ -163,13 +209,17 @@ pub struct ClientConfig {
     /// Max burnt gas per view method.  If present, overrides value stored in
     /// genesis file.  The value only affects the RPCs without influencing the
     /// protocol thus changing it per-node doesn’t affect the blockchain.
-    pub max_gas_burnt_view: Option<Gas>,
+    pub max_gas_burnt_view: MutableConfigValue<Option<Gas>>,
     /// Re-export storage layer statistics as prometheus metrics.
     pub enable_statistics_export: bool,
     /// Number of threads to execute background migration work in client.
     pub client_background_migration_threads: usize,
     /// Duration to perform background flat storage creation step.
     pub flat_storage_creation_period: Duration,
+    /// Record the trie nodes touched while applying chunks and send them to
+    /// the other block producers, so that state transitions can be validated
+    /// from them without the shard state.
+    pub record_state_transition_proofs: bool,
 }
 
 impl ClientConfig {
@@ -208,7 +258,10 @@ impl ClientConfig {
             header_sync_initial_timeout: Duration::from_secs(10),
             header_sync_progress_timeout: Duration::from_secs(2),
             header_sync_stall_ban_timeout: Duration::from_secs(30),
-            state_sync_timeout: Duration::from_secs(TEST_STATE_SYNC_TIMEOUT),
+            state_sync_timeout: MutableConfigValue::new(
+                Duration::from_secs(TEST_STATE_SYNC_TIMEOUT),
+                "state_sync_timeout",
+            ),
             header_sync_expected_height_per_second: 1,
             min_num_peers: 1,
             log_summary_period: Duration::from_secs(10),
@@ -226,9 +279,21 @@ impl ClientConfig {
             ),
             doosmslug_step_period: Duration::from_millis(100),
             block_header_fetch_horizon: 50,
-            gc: GCConfig { gc_blocks_limit: 100, ..GCConfig::default() },
-            tracked_accounts: vec![],
-            tracked_shards: vec![],
+            gc: MutableConfigValue::new(
+                GCConfig { gc_blocks_limit: 100, ..GCConfig::default() },
+                "gc",
+            ),
+            tracked_accounts: MutableConfigValue::new(vec![], "tracked_accounts"),
+            tracked_shards: MutableConfigValue::new(vec![], "tracked_shards"),
+            peer_limits: MutableConfigValue::new(
+                PeerLimits {
+                    max_num_peers: 40,
+                    minimum_outbound_peers: 5,
+                    ideal_connections_lo: 30,
+                    ideal_connections_hi: 35,
+                },
+                "peer_limits",
+            ),
             archive,
             save_trie_changes,
             log_summary_style: LogSummaryStyle::Colored,
@@ -236,10 +301,11 @@ impl ClientConfig {
             epoch_sync_enabled,
             view_client_throttle_period: Duration::from_secs(1),
             trie_viewer_state_size_limit: None,
-            max_gas_burnt_view: None,
+            max_gas_burnt_view: MutableConfigValue::new(None, "max_gas_burnt_view"),
             enable_statistics_export: true,
             client_background_migration_threads: 1,
             flat_storage_creation_period: Duration::from_secs(1),
+            record_state_transition_proofs: false,
         }
     }
 }
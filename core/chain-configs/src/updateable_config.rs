@@ -1,10 +1,13 @@
 use crate::metrics;
+use crate::{AdaptiveTrieCacheConfig, GCConfig};
 use chrono::{DateTime, Utc};
+use near_primitives::network::PeerLimits;
 use near_primitives::time::Clock;
-use near_primitives::types::BlockHeight;
+use near_primitives::types::{AccountId, BlockHeight, Gas, ShardId, TrieCacheConfig};
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 /// A wrapper for a config value that can be updated while the node is running.
 /// When initializing sub-objects (e.g. `ShardsManager`), please make sure to
@@ -19,58 +22,86 @@ pub struct MutableConfigValue<T> {
     field_name: String,
     // For metrics.
     // Mutable config values are exported to prometheus with labels [field_name][last_update][value].
-    last_update: DateTime<Utc>,
+    last_update: Arc<Mutex<DateTime<Utc>>>,
 }
 
-impl<T: Copy + PartialEq + Debug> MutableConfigValue<T> {
+impl<T: Clone + PartialEq + Debug> MutableConfigValue<T> {
     /// Initializes a value.
     /// `field_name` is needed to export the config value as a prometheus metric.
     pub fn new(val: T, field_name: &str) -> Self {
         let res = Self {
-            value: Arc::new(Mutex::new(val)),
+            value: Arc::new(Mutex::new(val.clone())),
             field_name: field_name.to_string(),
-            last_update: Clock::utc(),
+            last_update: Arc::new(Mutex::new(Clock::utc())),
         };
-        res.set_metric_value(val, 1);
+        res.set_metric_value(&val, 1);
         res
     }
 
     pub fn get(&self) -> T {
-        *self.value.lock().unwrap()
+        self.value.lock().unwrap().clone()
     }
 
-    pub fn update(&self, val: T) {
+    /// Sets a new value and returns whether it differs from the old one.
+    pub fn update(&self, val: T) -> bool {
         let mut lock = self.value.lock().unwrap();
         if *lock != val {
             tracing::info!(target: "config", "Updated config field '{}' from {:?} to {:?}", self.field_name, *lock, val);
-            self.set_metric_value(*lock, 0);
+            self.set_metric_value(&*lock, 0);
+            *self.last_update.lock().unwrap() = Clock::utc();
+            self.set_metric_value(&val, 1);
             *lock = val;
-            self.set_metric_value(val, 1);
+            true
         } else {
-            tracing::info!(target: "config", "Mutable config field '{}' remains the same: {:?}", self.field_name, val);
+            tracing::debug!(target: "config", "Mutable config field '{}' remains the same: {:?}", self.field_name, val);
+            false
         }
     }
 
-    fn set_metric_value(&self, value: T, metric_value: i64) {
+    fn set_metric_value(&self, value: &T, metric_value: i64) {
         // Use field_name as a label to tell different mutable config values apart.
         // Use timestamp as a label to give some idea to the node operator (or
         // people helping them debug their node) when exactly and what values
         // exactly were part of the config.
         // Use the config value as a label to make this work with config values
         // of any type: int, float, string or even a composite object.
+        let last_update = *self.last_update.lock().unwrap();
         metrics::CONFIG_MUTABLE_FIELD
             .with_label_values(&[
                 &self.field_name,
-                &self.last_update.timestamp().to_string(),
+                &last_update.timestamp().to_string(),
                 &format!("{:?}", value),
             ])
             .set(metric_value);
     }
 }
 
-#[derive(Default, Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 /// A subset of Config that can be updated white the node is running.
 pub struct UpdateableClientConfig {
     /// Graceful shutdown at expected block height.
     pub expected_shutdown: Option<BlockHeight>,
+    /// Garbage collection limits.  `gc_num_epochs_to_keep` has to stay the
+    /// same.
+    pub gc: GCConfig,
+    /// How long to wait for a response during state sync.
+    pub state_sync_timeout: Duration,
+    /// Max burnt gas per view method.
+    pub max_gas_burnt_view: Option<Gas>,
+    /// Accounts that this client tracks.
+    pub tracked_accounts: Vec<AccountId>,
+    /// Shards that this client tracks.
+    pub tracked_shards: Vec<ShardId>,
+    /// Memory limits of trie caches, taken from the `store` section of the
+    /// config.
+    pub trie_cache: TrieCacheConfig,
+    /// Memory limits of view trie caches, taken from the `store` section of
+    /// the config.
+    pub view_trie_cache: TrieCacheConfig,
+    /// Adaptive sizing of trie caches, taken from the `store` section of the
+    /// config.
+    pub adaptive_trie_cache: AdaptiveTrieCacheConfig,
+    /// Limits on the number of peer connections, taken from the `network`
+    /// section of the config.
+    pub peer_limits: PeerLimits,
 }
@@ -3,6 +3,7 @@
 use near_chain_configs::UpdateableClientConfig;
 use near_o11y::log_config::LogConfig;
 use near_primitives::time::Clock;
+use near_primitives::validator_signer::ValidatorSigner;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -17,6 +18,12 @@ pub struct UpdateableConfigs {
     pub log_config: Option<LogConfig>,
     /// Contents of the `config.json` corresponding to the mutable fields of `ClientConfig`.
     pub client_config: Option<UpdateableClientConfig>,
+    /// Validator key read from `validator_key_file` or the remote signer.  It
+    /// replaces the active key at the epoch boundary where it gets staked.
+    /// It's loaded separately from `client_config` so that a key which fails
+    /// to load doesn't prevent the other values from being updated.
+    #[serde(skip)]
+    pub validator_signer: Option<Arc<dyn ValidatorSigner>>,
 }
 
 /// Pushes the updates to listeners.
@@ -23,6 +23,10 @@ pub struct VMConfig {
     pub grow_mem_cost: u32,
     /// Gas cost of a regular operation.
     pub regular_op_cost: u32,
+    /// Gas cost of each 8-byte word processed by a bulk memory instruction
+    /// such as `memory.copy` or `memory.fill`, in units of `regular_op_cost`.
+    /// A trailing partial word is charged as a whole one.
+    pub bulk_memory_word_cost: u32,
 
     /// Describes limits for VM and Runtime.
     pub limit_config: VMLimitConfig,
@@ -109,12 +113,30 @@ pub struct VMLimitConfig {
     /// historically.
     #[serde(default = "AccountIdValidityRulesVersion::v0")]
     pub account_id_validity_rules_version: AccountIdValidityRulesVersion,
+    /// Which WebAssembly proposals beyond the MVP contracts may use, see
+    /// [`WasmFeaturesVersion`].
+    #[serde(default = "WasmFeaturesVersion::v0")]
+    pub wasm_features_version: WasmFeaturesVersion,
+    /// Max size of the payload a yielded promise can be resumed with.
+    #[serde(default = "max_yield_payload_size_default")]
+    pub max_yield_payload_size: u64,
+    /// Number of blocks after which a yielded promise that wasn't resumed times out.
+    #[serde(default = "yield_timeout_length_in_blocks_default")]
+    pub yield_timeout_length_in_blocks: u64,
 }
 
 fn wasmer2_stack_limit_default() -> i32 {
     100 * 1024
 }
 
+fn max_yield_payload_size_default() -> u64 {
+    1024
+}
+
+fn yield_timeout_length_in_blocks_default() -> u64 {
+    200
+}
+
 /// Our original code for limiting WASM stack was buggy. We fixed that, but we
 /// still have to use old (`V0`) limiter for old protocol versions.
 ///
@@ -170,12 +192,46 @@ impl AccountIdValidityRulesVersion {
     }
 }
 
+/// Set of WebAssembly proposals which contracts are allowed to use on top of
+/// the WebAssembly 1.0 (MVP) specification.
+///
+/// Multi-value is deliberately absent from all versions since the singlepass
+/// compiler used by wasmer2 can't compile it.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    serde_repr::Serialize_repr,
+    serde_repr::Deserialize_repr,
+)]
+#[repr(u8)]
+pub enum WasmFeaturesVersion {
+    /// Only the MVP.
+    V0,
+    /// Sign-extension operators and the `memory.fill` and `memory.copy` bulk
+    /// memory operations.  The rest of the bulk memory proposal is not
+    /// supported by the `parity_wasm` version used for instrumentation.
+    V1,
+}
+
+impl WasmFeaturesVersion {
+    fn v0() -> WasmFeaturesVersion {
+        WasmFeaturesVersion::V0
+    }
+}
+
 impl VMConfig {
     pub fn test() -> VMConfig {
         VMConfig {
             ext_costs: ExtCostsConfig::test(),
             grow_mem_cost: 1,
             regular_op_cost: (SAFETY_MULTIPLIER as u32) * 1285457,
+            bulk_memory_word_cost: 1,
             limit_config: VMLimitConfig::test(),
         }
     }
@@ -193,6 +249,7 @@ impl VMConfig {
             ext_costs: ExtCostsConfig::free(),
             grow_mem_cost: 0,
             regular_op_cost: 0,
+            bulk_memory_word_cost: 0,
             // We shouldn't have any costs in the limit config.
             limit_config: VMLimitConfig { max_gas_burnt: u64::MAX, ..VMLimitConfig::test() },
         }
@@ -251,6 +308,9 @@ impl VMLimitConfig {
             // is 4 bytes worth of code for each local.
             max_locals_per_contract: Some(max_contract_size / 4),
             account_id_validity_rules_version: AccountIdValidityRulesVersion::V1,
+            wasm_features_version: WasmFeaturesVersion::V0,
+            max_yield_payload_size: 1024,
+            yield_timeout_length_in_blocks: 200,
         }
     }
 }
@@ -343,6 +403,26 @@ impl ExtCostsConfig {
             ExtCosts::alt_bn128_pairing_check_element => 5_102_000_000_000,
             ExtCosts::alt_bn128_g1_sum_base => 3_000_000_000,
             ExtCosts::alt_bn128_g1_sum_element => 5_000_000_000,
+            ExtCosts::bls12381_p1_sum_base => 16_500_000_000,
+            ExtCosts::bls12381_p1_sum_element => 6_000_000_000,
+            ExtCosts::bls12381_p2_sum_base => 16_500_000_000,
+            ExtCosts::bls12381_p2_sum_element => 15_000_000_000,
+            ExtCosts::bls12381_g1_multiexp_base => 16_500_000_000,
+            ExtCosts::bls12381_g1_multiexp_element => 930_000_000_000,
+            ExtCosts::bls12381_g2_multiexp_base => 16_500_000_000,
+            ExtCosts::bls12381_g2_multiexp_element => 1_995_000_000_000,
+            ExtCosts::bls12381_map_fp_to_g1_base => 1_500_000_000,
+            ExtCosts::bls12381_map_fp_to_g1_element => 252_000_000_000,
+            ExtCosts::bls12381_map_fp2_to_g2_base => 1_500_000_000,
+            ExtCosts::bls12381_map_fp2_to_g2_element => 900_000_000_000,
+            ExtCosts::bls12381_pairing_base => 2_130_000_000_000,
+            ExtCosts::bls12381_pairing_element => 2_130_000_000_000,
+            ExtCosts::p256_verify_base => 700_000_000_000,
+            ExtCosts::p256_verify_byte => 9_000_000,
+            ExtCosts::yield_create_base => 153_411_779_276,
+            ExtCosts::yield_create_byte => 15_643_988,
+            ExtCosts::yield_resume_base => 1_195_627_285_210,
+            ExtCosts::yield_resume_byte => 47_683_715,
         };
         ExtCostsConfig { costs }
     }
@@ -436,6 +516,26 @@ pub enum ExtCosts {
     alt_bn128_g1_sum_element = 58,
     ed25519_verify_base = 59,
     ed25519_verify_byte = 60,
+    bls12381_p1_sum_base = 61,
+    bls12381_p1_sum_element = 62,
+    bls12381_p2_sum_base = 63,
+    bls12381_p2_sum_element = 64,
+    bls12381_g1_multiexp_base = 65,
+    bls12381_g1_multiexp_element = 66,
+    bls12381_g2_multiexp_base = 67,
+    bls12381_g2_multiexp_element = 68,
+    bls12381_map_fp_to_g1_base = 69,
+    bls12381_map_fp_to_g1_element = 70,
+    bls12381_map_fp2_to_g2_base = 71,
+    bls12381_map_fp2_to_g2_element = 72,
+    bls12381_pairing_base = 73,
+    bls12381_pairing_element = 74,
+    p256_verify_base = 75,
+    p256_verify_byte = 76,
+    yield_create_base = 77,
+    yield_create_byte = 78,
+    yield_resume_base = 79,
+    yield_resume_byte = 80,
 }
 
 // Type of an action, used in fees logic.
@@ -541,6 +641,26 @@ impl ExtCosts {
             ExtCosts::alt_bn128_pairing_check_element => Parameter::WasmAltBn128PairingCheckElement,
             ExtCosts::alt_bn128_g1_sum_base => Parameter::WasmAltBn128G1SumBase,
             ExtCosts::alt_bn128_g1_sum_element => Parameter::WasmAltBn128G1SumElement,
+            ExtCosts::bls12381_p1_sum_base => Parameter::WasmBls12381P1SumBase,
+            ExtCosts::bls12381_p1_sum_element => Parameter::WasmBls12381P1SumElement,
+            ExtCosts::bls12381_p2_sum_base => Parameter::WasmBls12381P2SumBase,
+            ExtCosts::bls12381_p2_sum_element => Parameter::WasmBls12381P2SumElement,
+            ExtCosts::bls12381_g1_multiexp_base => Parameter::WasmBls12381G1MultiexpBase,
+            ExtCosts::bls12381_g1_multiexp_element => Parameter::WasmBls12381G1MultiexpElement,
+            ExtCosts::bls12381_g2_multiexp_base => Parameter::WasmBls12381G2MultiexpBase,
+            ExtCosts::bls12381_g2_multiexp_element => Parameter::WasmBls12381G2MultiexpElement,
+            ExtCosts::bls12381_map_fp_to_g1_base => Parameter::WasmBls12381MapFpToG1Base,
+            ExtCosts::bls12381_map_fp_to_g1_element => Parameter::WasmBls12381MapFpToG1Element,
+            ExtCosts::bls12381_map_fp2_to_g2_base => Parameter::WasmBls12381MapFp2ToG2Base,
+            ExtCosts::bls12381_map_fp2_to_g2_element => Parameter::WasmBls12381MapFp2ToG2Element,
+            ExtCosts::bls12381_pairing_base => Parameter::WasmBls12381PairingBase,
+            ExtCosts::bls12381_pairing_element => Parameter::WasmBls12381PairingElement,
+            ExtCosts::p256_verify_base => Parameter::WasmP256VerifyBase,
+            ExtCosts::p256_verify_byte => Parameter::WasmP256VerifyByte,
+            ExtCosts::yield_create_base => Parameter::WasmYieldCreateBase,
+            ExtCosts::yield_create_byte => Parameter::WasmYieldCreateByte,
+            ExtCosts::yield_resume_base => Parameter::WasmYieldResumeBase,
+            ExtCosts::yield_resume_byte => Parameter::WasmYieldResumeByte,
         }
     }
 }
@@ -54,6 +54,7 @@ pub enum Parameter {
     // Smart contract dynamic gas costs
     WasmRegularOpCost,
     WasmGrowMemCost,
+    WasmBulkMemoryWordCost,
     /// Base cost for a host function
     WasmBase,
     WasmContractLoadingBase,
@@ -116,6 +117,26 @@ pub enum Parameter {
     WasmAltBn128PairingCheckElement,
     WasmAltBn128G1SumBase,
     WasmAltBn128G1SumElement,
+    WasmBls12381P1SumBase,
+    WasmBls12381P1SumElement,
+    WasmBls12381P2SumBase,
+    WasmBls12381P2SumElement,
+    WasmBls12381G1MultiexpBase,
+    WasmBls12381G1MultiexpElement,
+    WasmBls12381G2MultiexpBase,
+    WasmBls12381G2MultiexpElement,
+    WasmBls12381MapFpToG1Base,
+    WasmBls12381MapFpToG1Element,
+    WasmBls12381MapFp2ToG2Base,
+    WasmBls12381MapFp2ToG2Element,
+    WasmBls12381PairingBase,
+    WasmBls12381PairingElement,
+    WasmP256VerifyBase,
+    WasmP256VerifyByte,
+    WasmYieldCreateBase,
+    WasmYieldCreateByte,
+    WasmYieldResumeBase,
+    WasmYieldResumeByte,
 
     // Smart contract limits
     MaxGasBurnt,
@@ -145,6 +166,9 @@ pub enum Parameter {
     Wasmer2StackLimit,
     MaxLocalsPerContract,
     AccountIdValidityRulesVersion,
+    WasmFeaturesVersion,
+    MaxYieldPayloadSize,
+    YieldTimeoutLengthInBlocks,
 }
 
 #[derive(
@@ -210,6 +234,9 @@ impl Parameter {
             Parameter::Wasmer2StackLimit,
             Parameter::MaxLocalsPerContract,
             Parameter::AccountIdValidityRulesVersion,
+            Parameter::WasmFeaturesVersion,
+            Parameter::MaxYieldPayloadSize,
+            Parameter::YieldTimeoutLengthInBlocks,
         ]
         .iter()
     }
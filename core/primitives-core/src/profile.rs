@@ -1,3 +1,4 @@
+pub use extended::{ExtendedProfileData, HostFunctionStats, MEMORY_GROW_FUNCTION_INDEX};
 pub use profile_v2::ProfileDataV2;
 
 use crate::config::{ActionCosts, ExtCosts};
@@ -7,6 +8,7 @@ use enum_map::{enum_map, Enum, EnumMap};
 use std::fmt;
 use strum::IntoEnumIterator;
 
+mod extended;
 mod profile_v2;
 
 /// Profile of gas consumption.
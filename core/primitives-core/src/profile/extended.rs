@@ -0,0 +1,114 @@
+use crate::types::Gas;
+use std::collections::BTreeMap;
+
+/// Function index used by the gas instrumentation to attribute gas charged for
+/// `memory.grow` instructions.
+///
+/// The charge happens in a helper function shared by all growing call sites,
+/// so it cannot be attributed to the function which executed the instruction.
+pub const MEMORY_GROW_FUNCTION_INDEX: u32 = u32::MAX;
+
+/// Detailed breakdown of where a single function call spent its gas.
+///
+/// Unlike [`super::ProfileDataV3`], this is not part of the protocol and is
+/// never stored.  It is only collected when explicitly requested over RPC by
+/// re-executing the call with a differently instrumented contract, so the
+/// numbers are deterministic but collecting them is expensive.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExtendedProfileData {
+    /// Statistics of host function calls, keyed by host function name.
+    host_functions: BTreeMap<String, HostFunctionStats>,
+    /// Wasm instruction gas keyed by index of the function in the contract
+    /// module as it was deployed.
+    wasm_functions: BTreeMap<u32, Gas>,
+    /// Wasm instruction gas charged for growing memory.
+    memory_grow_gas: Gas,
+}
+
+/// Aggregated statistics of all calls to a single host function.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HostFunctionStats {
+    /// Number of times the host function was called.
+    pub calls: u64,
+    /// Gas burnt by the host function itself, excluding wasm instructions.
+    pub gas_burnt: Gas,
+    /// Bytes read from the contract memory.
+    pub bytes_read: u64,
+    /// Bytes written into the contract memory or registers.
+    pub bytes_written: u64,
+}
+
+impl ExtendedProfileData {
+    pub fn record_host_call(
+        &mut self,
+        name: &str,
+        gas_burnt: Gas,
+        bytes_read: u64,
+        bytes_written: u64,
+    ) {
+        let stats = match self.host_functions.get_mut(name) {
+            Some(stats) => stats,
+            None => self.host_functions.entry(name.to_string()).or_default(),
+        };
+        stats.calls = stats.calls.saturating_add(1);
+        stats.gas_burnt = stats.gas_burnt.saturating_add(gas_burnt);
+        stats.bytes_read = stats.bytes_read.saturating_add(bytes_read);
+        stats.bytes_written = stats.bytes_written.saturating_add(bytes_written);
+    }
+
+    pub fn add_wasm_gas(&mut self, function_index: u32, gas: Gas) {
+        if function_index == MEMORY_GROW_FUNCTION_INDEX {
+            self.memory_grow_gas = self.memory_grow_gas.saturating_add(gas);
+            return;
+        }
+        let function_gas = self.wasm_functions.entry(function_index).or_default();
+        *function_gas = function_gas.saturating_add(gas);
+    }
+
+    pub fn host_functions(&self) -> impl Iterator<Item = (&str, &HostFunctionStats)> {
+        self.host_functions.iter().map(|(name, stats)| (name.as_str(), stats))
+    }
+
+    pub fn wasm_functions(&self) -> impl Iterator<Item = (u32, Gas)> + '_ {
+        self.wasm_functions.iter().map(|(index, gas)| (*index, *gas))
+    }
+
+    pub fn memory_grow_gas(&self) -> Gas {
+        self.memory_grow_gas
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_extended_profile_aggregation() {
+        let mut profile = ExtendedProfileData::default();
+        profile.record_host_call("storage_write", 10, 3, 0);
+        profile.record_host_call("storage_write", 20, 4, 1);
+        profile.record_host_call("log_utf8", 5, 2, 0);
+        profile.add_wasm_gas(1, 7);
+        profile.add_wasm_gas(1, 8);
+        profile.add_wasm_gas(0, u64::MAX);
+        profile.add_wasm_gas(0, 1);
+        profile.add_wasm_gas(MEMORY_GROW_FUNCTION_INDEX, 100);
+
+        let host: Vec<_> = profile.host_functions().map(|(name, stats)| (name, *stats)).collect();
+        assert_eq!(
+            host,
+            [
+                (
+                    "log_utf8",
+                    HostFunctionStats { calls: 1, gas_burnt: 5, bytes_read: 2, bytes_written: 0 }
+                ),
+                (
+                    "storage_write",
+                    HostFunctionStats { calls: 2, gas_burnt: 30, bytes_read: 7, bytes_written: 1 }
+                ),
+            ]
+        );
+        assert_eq!(profile.wasm_functions().collect::<Vec<_>>(), [(0, u64::MAX), (1, 15)]);
+        assert_eq!(profile.memory_grow_gas(), 100);
+    }
+}
@@ -201,6 +201,38 @@ pub mod dec_format {
     }
 }
 
+/// Serialises `Ratio<u128>` as a pair of its numerator and denominator in
+/// [`dec_format`], so that large values don’t lose precision.
+pub mod dec_ratio_format {
+    use num_rational::Ratio;
+    use serde::de;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct Pair(
+        #[serde(with = "super::dec_format")] u128,
+        #[serde(with = "super::dec_format")] u128,
+    );
+
+    pub fn serialize<S>(ratio: &Ratio<u128>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Pair(*ratio.numer(), *ratio.denom()).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Ratio<u128>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let Pair(numer, denom) = Pair::deserialize(deserializer)?;
+        if denom == 0 {
+            return Err(de::Error::custom("denominator is zero"));
+        }
+        Ok(Ratio::new_raw(numer, denom))
+    }
+}
+
 #[test]
 fn test_u64_dec_format() {
     #[derive(PartialEq, Debug, serde::Deserialize, serde::Serialize)]
@@ -255,6 +287,25 @@ fn test_option_u128_dec_format() {
     assert_de_error::<Test>("{\"field\":42.0}");
 }
 
+#[test]
+fn test_dec_ratio_format() {
+    #[derive(PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+    struct Test {
+        #[serde(with = "dec_ratio_format")]
+        field: num_rational::Ratio<u128>,
+    }
+
+    let ratio = |numer, denom| Test { field: num_rational::Ratio::new_raw(numer, denom) };
+    assert_round_trip("{\"field\":[\"1\",\"2\"]}", ratio(1, 2));
+    assert_round_trip(
+        "{\"field\":[\"18446744073709551616\",\"3\"]}",
+        ratio(18446744073709551616, 3),
+    );
+    assert_deserialise("{\"field\":[1,2]}", ratio(1, 2));
+    assert_de_error::<Test>("{\"field\":[\"1\",\"0\"]}");
+    assert_de_error::<Test>("{\"field\":\"1/2\"}");
+}
+
 #[cfg(test)]
 #[track_caller]
 fn assert_round_trip<'a, T>(serialised: &'a str, obj: T)
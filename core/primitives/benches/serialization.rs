@@ -70,6 +70,7 @@ fn create_block() -> Block {
         CryptoHash::default(),
         None,
     )
+    .unwrap()
 }
 
 fn create_account() -> Account {
@@ -23,7 +23,7 @@ use crate::sharding::{
 };
 use crate::types::{Balance, BlockHeight, EpochId, Gas, NumBlocks, NumShards, StateRoot};
 use crate::utils::to_timestamp;
-use crate::validator_signer::{EmptyValidatorSigner, ValidatorSigner};
+use crate::validator_signer::{EmptyValidatorSigner, ValidatorSigner, ValidatorSignerError};
 use crate::version::{ProtocolVersion, SHARD_CHUNK_HEADER_UPGRADE_VERSION};
 use std::ops::Index;
 
@@ -102,6 +102,7 @@ pub fn genesis_chunks(
                 vec![],
                 &[],
                 CryptoHash::default(),
+                &EpochId::default(),
                 &EmptyValidatorSigner::default(),
                 genesis_protocol_version,
             )
@@ -211,7 +212,7 @@ impl Block {
         next_bp_hash: CryptoHash,
         block_merkle_root: CryptoHash,
         timestamp_override: Option<DateTime<chrono::Utc>>,
-    ) -> Self {
+    ) -> Result<Self, ValidatorSignerError> {
         // Collect aggregate of validators and gas usage/limits from chunks.
         let mut validator_proposals = vec![];
         let mut gas_used = 0;
@@ -243,7 +244,7 @@ impl Block {
         let now = to_timestamp(timestamp_override.unwrap_or_else(Clock::utc));
         let time = if now <= prev.raw_timestamp() { prev.raw_timestamp() + 1 } else { now };
 
-        let (vrf_value, vrf_proof) = signer.compute_vrf_with_proof(prev.random_value().as_ref());
+        let (vrf_value, vrf_proof) = signer.compute_vrf_with_proof(prev.random_value().as_ref())?;
         let random_value = hash(vrf_value.0.as_ref());
 
         let last_ds_final_block =
@@ -293,16 +294,16 @@ impl Block {
             next_bp_hash,
             block_merkle_root,
             prev.height(),
-        );
+        )?;
 
-        Self::block_from_protocol_version(
+        Ok(Self::block_from_protocol_version(
             next_epoch_protocol_version,
             header,
             chunks,
             challenges,
             vrf_value,
             vrf_proof,
-        )
+        ))
     }
 
     pub fn verify_gas_price(
@@ -6,8 +6,10 @@ use near_crypto::Signature;
 use crate::hash::CryptoHash;
 use crate::merkle::MerklePath;
 use crate::sharding::{EncodedShardChunk, ShardChunk, ShardChunkHeader};
+use crate::syncing::ReceiptProofResponse;
+use crate::transaction::SignedTransaction;
 use crate::types::AccountId;
-use crate::validator_signer::ValidatorSigner;
+use crate::validator_signer::{ValidatorSigner, ValidatorSignerError};
 
 /// Serialized TrieNodeWithSize
 pub type StateItem = std::sync::Arc<[u8]>;
@@ -68,6 +70,63 @@ pub struct ChunkState {
     pub partial_state: PartialState,
 }
 
+/// Same as `ChunkState`, but also carries the incoming receipts applied together with the
+/// previous chunk. Produced once the `ChunkStateChallenges` protocol feature is enabled.
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Eq, Clone, Debug)]
+pub struct ChunkStateV2 {
+    /// Encoded prev block header.
+    pub prev_block_header: Vec<u8>,
+    /// Encoded block header that contains invalid chunk.
+    pub block_header: Vec<u8>,
+    /// Merkle proof in inclusion of prev chunk.
+    pub prev_merkle_proof: MerklePath,
+    /// Previous chunk that contains transactions.
+    pub prev_chunk: ShardChunk,
+    /// Merkle proof of inclusion of this chunk.
+    pub merkle_proof: MerklePath,
+    /// Invalid chunk header.
+    pub chunk_header: ShardChunkHeader,
+    /// Partial state that was affected by transactions of given chunk.
+    pub partial_state: PartialState,
+    /// Incoming receipts applied together with the previous chunk, grouped by the block they
+    /// were produced in, starting from the previous block. Every group contains the proofs of
+    /// inclusion into the outgoing receipts of the chunks of that block, so the challenge can be
+    /// verified by nodes which don't track the shard.
+    pub prev_chunk_incoming_receipts: Vec<ReceiptProofResponse>,
+}
+
+impl From<ChunkStateV2> for ChunkState {
+    fn from(chunk_state: ChunkStateV2) -> Self {
+        ChunkState {
+            prev_block_header: chunk_state.prev_block_header,
+            block_header: chunk_state.block_header,
+            prev_merkle_proof: chunk_state.prev_merkle_proof,
+            prev_chunk: chunk_state.prev_chunk,
+            merkle_proof: chunk_state.merkle_proof,
+            chunk_header: chunk_state.chunk_header,
+            partial_state: chunk_state.partial_state,
+        }
+    }
+}
+
+/// Proof that applying the previous chunk of a shard results in the post-state committed to by
+/// the next chunk of that shard. Sent by chunk producers to block producers which don't track the
+/// shard, so they can check the state transition without having the state. The header of the
+/// previous chunk is taken from the previous block, so only its transactions are sent.
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Eq, Clone, Debug)]
+pub struct ChunkStateTransitionProof {
+    /// Hash of the block which includes the previous chunk.
+    pub prev_block_hash: CryptoHash,
+    /// Transactions of the previous chunk.
+    pub prev_chunk_transactions: Vec<SignedTransaction>,
+    /// Incoming receipts applied together with the previous chunk, see `ChunkStateV2`.
+    pub prev_chunk_incoming_receipts: Vec<ReceiptProofResponse>,
+    /// Header of the chunk built on top of the previous block.
+    pub chunk_header: ShardChunkHeader,
+    /// Trie nodes read while applying the previous chunk.
+    pub partial_state: PartialState,
+}
+
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Eq, Clone, Debug)]
 // TODO(#1313): Use Box
 #[allow(clippy::large_enum_variant)]
@@ -75,6 +134,7 @@ pub enum ChallengeBody {
     BlockDoubleSign(BlockDoubleSign),
     ChunkProofs(ChunkProofs),
     ChunkState(ChunkState),
+    ChunkStateV2(Box<ChunkStateV2>),
 }
 
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Eq, Clone, Debug)]
@@ -93,9 +153,12 @@ impl Challenge {
         self.hash = CryptoHash::hash_borsh(&self.body);
     }
 
-    pub fn produce(body: ChallengeBody, signer: &dyn ValidatorSigner) -> Self {
-        let (hash, signature) = signer.sign_challenge(&body);
-        Self { body, account_id: signer.validator_id().clone(), signature, hash }
+    pub fn produce(
+        body: ChallengeBody,
+        signer: &dyn ValidatorSigner,
+    ) -> Result<Self, ValidatorSignerError> {
+        let (hash, signature) = signer.sign_challenge(&body)?;
+        Ok(Self { body, account_id: signer.validator_id().clone(), signature, hash })
     }
 }
 
@@ -78,3 +78,36 @@ impl AnnounceAccount {
         AnnounceAccount::build_header_hash(&self.account_id, &self.peer_id, &self.epoch_id)
     }
 }
+
+/// Limits on the number of peer connections.  They can be changed while the
+/// node is running.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PeerLimits {
+    /// Maximum number of active peers.  Hard limit.
+    pub max_num_peers: u32,
+    /// Minimum outbound connections a peer should have to avoid eclipse attacks.
+    pub minimum_outbound_peers: u32,
+    /// Lower bound of the ideal number of connections.
+    pub ideal_connections_lo: u32,
+    /// Upper bound of the ideal number of connections.
+    pub ideal_connections_hi: u32,
+}
+
+impl PeerLimits {
+    /// Checks that the limits are consistent with each other.
+    pub fn verify(&self) -> Result<(), String> {
+        if self.ideal_connections_lo > self.ideal_connections_hi {
+            return Err(format!(
+                "Invalid ideal_connections values. lo({}) > hi({}).",
+                self.ideal_connections_lo, self.ideal_connections_hi
+            ));
+        }
+        if self.ideal_connections_hi > self.max_num_peers {
+            return Err(format!(
+                "max_num_peers({}) < ideal_connections_hi({}) which may lead to connection saturation and declining new connections.",
+                self.max_num_peers, self.ideal_connections_hi
+            ));
+        }
+        Ok(())
+    }
+}
@@ -11,7 +11,7 @@ use crate::borsh::maybestd::collections::HashMap;
 use crate::hash::CryptoHash;
 use crate::serialize::{dec_format, option_base64_format};
 use crate::transaction::{Action, TransferAction};
-use crate::types::{AccountId, Balance, ShardId};
+use crate::types::{AccountId, Balance, BlockHeight, ShardId};
 
 /// Receipts are used for a cross-shard communication.
 /// Receipts could be 2 types (determined by a `ReceiptEnum`): `ReceiptEnum::Action` of `ReceiptEnum::Data`.
@@ -171,5 +171,26 @@ pub struct DelayedReceiptIndices {
     pub next_available_index: u64,
 }
 
+/// Stores indices for a persistent queue of timeouts of yielded promises.
+#[derive(Default, BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct PromiseYieldIndices {
+    // First inclusive index in the queue.
+    pub first_index: u64,
+    // Exclusive end index of the queue
+    pub next_available_index: u64,
+}
+
+/// Entry of the queue of timeouts of yielded promises. If the promise `data_id` yielded by
+/// `account_id` isn't resumed by the block at `expires_at` height, it's resumed with no data.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct PromiseYieldTimeout {
+    /// The account which yielded the promise.
+    pub account_id: AccountId,
+    /// Data id of the yielded promise.
+    pub data_id: CryptoHash,
+    /// Block height at which the promise times out.
+    pub expires_at: BlockHeight,
+}
+
 /// Map of shard to list of receipts to send to it.
 pub type ReceiptResult = HashMap<ShardId, Vec<Receipt>>;
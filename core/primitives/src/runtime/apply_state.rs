@@ -41,4 +41,11 @@ pub struct ApplyState {
     pub migration_data: Arc<MigrationData>,
     /// Flags for migrations indicating whether they can be applied at this block
     pub migration_flags: MigrationFlags,
+    /// Whether signatures of transactions are verified.  Only disabled when estimating gas of
+    /// unsigned transactions.
+    pub verify_signatures: bool,
+    /// Whether delayed receipts and timed out promise yields left by earlier chunks are
+    /// processed.  Only disabled when estimating gas, which must execute just the given
+    /// transaction.
+    pub process_delayed_receipts: bool,
 }
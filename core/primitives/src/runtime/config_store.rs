@@ -28,6 +28,9 @@ static CONFIG_DIFFS: &[(ProtocolVersion, &str)] = &[
     // set read_cached_trie_node cost, decrease storage key limit
     (53, include_config!("53.yaml")),
     (57, include_config!("57.yaml")),
+    // Sign-extension operators and bulk memory operations
+    #[cfg(feature = "protocol_feature_wasm_extensions")]
+    (135, include_config!("135.yaml")),
 ];
 
 /// Testnet parameters for versions <= 29, which (incorrectly) differed from mainnet parameters
@@ -91,6 +94,14 @@ impl RuntimeConfigStore {
         Self::with_one_config(RuntimeConfig::free())
     }
 
+    /// Returns protocol versions at which the config changed along with the
+    /// config in effect from that version on, in increasing order.
+    pub fn versioned_configs(
+        &self,
+    ) -> impl Iterator<Item = (ProtocolVersion, &Arc<RuntimeConfig>)> + '_ {
+        self.store.iter().map(|(protocol_version, config)| (*protocol_version, config))
+    }
+
     /// Returns a `RuntimeConfig` for the corresponding protocol version.
     pub fn get_config(&self, protocol_version: ProtocolVersion) -> &Arc<RuntimeConfig> {
         self.store
@@ -143,6 +143,7 @@ impl TryFrom<&ParameterTable> for RuntimeConfig {
                 },
                 grow_mem_cost: params.get_number(Parameter::WasmGrowMemCost)?,
                 regular_op_cost: params.get_number(Parameter::WasmRegularOpCost)?,
+                bulk_memory_word_cost: params.get_number(Parameter::WasmBulkMemoryWordCost)?,
                 limit_config: serde_yaml::from_value(params.yaml_map(Parameter::vm_limits(), ""))
                     .map_err(InvalidConfigError::InvalidYaml)?,
             },
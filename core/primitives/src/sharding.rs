@@ -9,8 +9,8 @@ use crate::merkle::{combine_hash, merklize, MerklePath};
 use crate::receipt::Receipt;
 use crate::transaction::SignedTransaction;
 use crate::types::validator_stake::{ValidatorStake, ValidatorStakeIter, ValidatorStakeV1};
-use crate::types::{Balance, BlockHeight, Gas, MerkleHash, ShardId, StateRoot};
-use crate::validator_signer::ValidatorSigner;
+use crate::types::{Balance, BlockHeight, EpochId, Gas, MerkleHash, ShardId, StateRoot};
+use crate::validator_signer::{ValidatorSigner, ValidatorSignerError};
 use crate::version::{ProtocolFeature, ProtocolVersion, SHARD_CHUNK_HEADER_UPGRADE_VERSION};
 use reed_solomon_erasure::ReconstructShard;
 use std::sync::Arc;
@@ -126,8 +126,9 @@ impl ShardChunkHeaderV2 {
         outgoing_receipts_root: CryptoHash,
         tx_root: CryptoHash,
         validator_proposals: Vec<ValidatorStakeV1>,
+        epoch_id: &EpochId,
         signer: &dyn ValidatorSigner,
-    ) -> Self {
+    ) -> Result<Self, ValidatorSignerError> {
         let inner = ShardChunkHeaderInnerV1 {
             prev_block_hash,
             prev_state_root,
@@ -144,8 +145,9 @@ impl ShardChunkHeaderV2 {
             validator_proposals,
         };
         let hash = Self::compute_hash(&inner);
-        let signature = signer.sign_chunk_hash(&hash);
-        Self { inner, height_included: 0, signature, hash }
+        let signature =
+            signer.sign_chunk_header(&ChunkHeaderToSign::V2(inner.clone()), epoch_id)?;
+        Ok(Self { inner, height_included: 0, signature, hash })
     }
 }
 
@@ -190,8 +192,9 @@ impl ShardChunkHeaderV3 {
         outgoing_receipts_root: CryptoHash,
         tx_root: CryptoHash,
         validator_proposals: Vec<ValidatorStake>,
+        epoch_id: &EpochId,
         signer: &dyn ValidatorSigner,
-    ) -> Self {
+    ) -> Result<Self, ValidatorSignerError> {
         let inner = ShardChunkHeaderInner::V2(ShardChunkHeaderInnerV2 {
             prev_block_hash,
             prev_state_root,
@@ -208,8 +211,43 @@ impl ShardChunkHeaderV3 {
             validator_proposals,
         });
         let hash = Self::compute_hash(&inner);
-        let signature = signer.sign_chunk_hash(&hash);
-        Self { inner, height_included: 0, signature, hash }
+        let signature =
+            signer.sign_chunk_header(&ChunkHeaderToSign::V3(inner.clone()), epoch_id)?;
+        Ok(Self { inner, height_included: 0, signature, hash })
+    }
+}
+
+/// Part of a chunk header of any version which the chunk hash is computed from.  Validator
+/// signers get it instead of the bare hash, so they know what they sign and can check the height
+/// and the shard of the chunk.
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum ChunkHeaderToSign {
+    V1(ShardChunkHeaderInnerV1),
+    V2(ShardChunkHeaderInnerV1),
+    V3(ShardChunkHeaderInner),
+}
+
+impl ChunkHeaderToSign {
+    pub fn chunk_hash(&self) -> ChunkHash {
+        match self {
+            Self::V1(inner) => ShardChunkHeaderV1::compute_hash(inner),
+            Self::V2(inner) => ShardChunkHeaderV2::compute_hash(inner),
+            Self::V3(inner) => ShardChunkHeaderV3::compute_hash(inner),
+        }
+    }
+
+    pub fn height_created(&self) -> BlockHeight {
+        match self {
+            Self::V1(inner) | Self::V2(inner) => inner.height_created,
+            Self::V3(inner) => inner.height_created(),
+        }
+    }
+
+    pub fn shard_id(&self) -> ShardId {
+        match self {
+            Self::V1(inner) | Self::V2(inner) => inner.shard_id,
+            Self::V3(inner) => inner.shard_id(),
+        }
     }
 }
 
@@ -439,8 +477,9 @@ impl ShardChunkHeaderV1 {
         outgoing_receipts_root: CryptoHash,
         tx_root: CryptoHash,
         validator_proposals: Vec<ValidatorStakeV1>,
+        epoch_id: &EpochId,
         signer: &dyn ValidatorSigner,
-    ) -> Self {
+    ) -> Result<Self, ValidatorSignerError> {
         let inner = ShardChunkHeaderInnerV1 {
             prev_block_hash,
             prev_state_root,
@@ -457,8 +496,9 @@ impl ShardChunkHeaderV1 {
             validator_proposals,
         };
         let hash = Self::compute_hash(&inner);
-        let signature = signer.sign_chunk_hash(&hash);
-        Self { inner, height_included: 0, signature, hash }
+        let signature =
+            signer.sign_chunk_header(&ChunkHeaderToSign::V1(inner.clone()), epoch_id)?;
+        Ok(Self { inner, height_included: 0, signature, hash })
     }
 }
 
@@ -967,6 +1007,7 @@ impl EncodedShardChunk {
         transactions: Vec<SignedTransaction>,
         outgoing_receipts: &[Receipt],
         outgoing_receipts_root: CryptoHash,
+        epoch_id: &EpochId,
         signer: &dyn ValidatorSigner,
         protocol_version: ProtocolVersion,
     ) -> Result<(Self, Vec<MerklePath>), std::io::Error> {
@@ -996,8 +1037,10 @@ impl EncodedShardChunk {
                 outgoing_receipts_root,
                 tx_root,
                 validator_proposals,
+                epoch_id,
                 signer,
-            );
+            )
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
             let chunk = EncodedShardChunkV1 { header, content };
             Ok((Self::V1(chunk), merkle_paths))
         } else if block_header_v3_version.is_none()
@@ -1019,8 +1062,10 @@ impl EncodedShardChunk {
                 outgoing_receipts_root,
                 tx_root,
                 validator_proposals,
+                epoch_id,
                 signer,
-            );
+            )
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
             let chunk = EncodedShardChunkV2 { header: ShardChunkHeader::V2(header), content };
             Ok((Self::V2(chunk), merkle_paths))
         } else {
@@ -1038,8 +1083,10 @@ impl EncodedShardChunk {
                 outgoing_receipts_root,
                 tx_root,
                 validator_proposals,
+                epoch_id,
                 signer,
-            );
+            )
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
             let chunk = EncodedShardChunkV2 { header: ShardChunkHeader::V3(header), content };
             Ok((Self::V2(chunk), merkle_paths))
         }
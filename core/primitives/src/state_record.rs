@@ -6,13 +6,14 @@ use near_crypto::PublicKey;
 
 use crate::account::{AccessKey, Account};
 use crate::hash::{hash, CryptoHash};
-use crate::receipt::{Receipt, ReceivedData};
+use crate::receipt::{PromiseYieldTimeout, Receipt, ReceivedData};
 use crate::serialize::{base64_format, option_base64_format};
 use crate::trie_key::col;
 use crate::trie_key::trie_key_parsers::{
     parse_account_id_from_access_key_key, parse_account_id_from_account_key,
     parse_account_id_from_contract_code_key, parse_account_id_from_contract_data_key,
-    parse_account_id_from_received_data_key, parse_data_id_from_received_data_key,
+    parse_account_id_from_promise_yield_receipt_key, parse_account_id_from_received_data_key,
+    parse_data_id_from_promise_yield_receipt_key, parse_data_id_from_received_data_key,
     parse_data_key_from_contract_data_key, parse_public_key_from_access_key_key,
 };
 use crate::types::AccountId;
@@ -50,6 +51,11 @@ pub enum StateRecord {
     /// Delayed Receipt.
     /// The receipt was delayed because the shard was overwhelmed.
     DelayedReceipt(Box<Receipt>),
+    /// Promise with the given data_id yielded by the given account_id and waiting to be resumed.
+    PromiseYieldReceipt { account_id: AccountId, data_id: CryptoHash },
+    /// Timeout of a yielded promise.
+    /// The order of timeouts in the queue is restored by their expiration height.
+    PromiseYieldTimeout(PromiseYieldTimeout),
 }
 
 impl StateRecord {
@@ -95,6 +101,17 @@ impl StateRecord {
                 Some(StateRecord::DelayedReceipt(Box::new(receipt)))
             }
             col::DELAYED_RECEIPT_INDICES => None,
+            col::PROMISE_YIELD_INDICES => None,
+            col::PROMISE_YIELD_TIMEOUT => {
+                let timeout = PromiseYieldTimeout::try_from_slice(&value).unwrap();
+                Some(StateRecord::PromiseYieldTimeout(timeout))
+            }
+            col::PROMISE_YIELD_RECEIPT => {
+                let account_id = parse_account_id_from_promise_yield_receipt_key(&key).unwrap();
+                let data_id =
+                    parse_data_id_from_promise_yield_receipt_key(&key, &account_id).unwrap();
+                Some(StateRecord::PromiseYieldReceipt { account_id, data_id })
+            }
             _ => unreachable!(),
         }
     }
@@ -128,6 +145,12 @@ impl Display for StateRecord {
             ),
             StateRecord::PostponedReceipt(receipt) => write!(f, "Postponed receipt {:?}", receipt),
             StateRecord::DelayedReceipt(receipt) => write!(f, "Delayed receipt {:?}", receipt),
+            StateRecord::PromiseYieldReceipt { account_id, data_id } => {
+                write!(f, "Promise yield {:?},{:?}", account_id, data_id)
+            }
+            StateRecord::PromiseYieldTimeout(timeout) => {
+                write!(f, "Promise yield timeout {:?}", timeout)
+            }
         }
     }
 }
@@ -153,7 +176,9 @@ pub fn state_record_to_account_id(state_record: &StateRecord) -> &AccountId {
         | StateRecord::AccessKey { account_id, .. }
         | StateRecord::Contract { account_id, .. }
         | StateRecord::ReceivedData { account_id, .. }
+        | StateRecord::PromiseYieldReceipt { account_id, .. }
         | StateRecord::Data { account_id, .. } => account_id,
+        StateRecord::PromiseYieldTimeout(timeout) => &timeout.account_id,
         StateRecord::PostponedReceipt(receipt) | StateRecord::DelayedReceipt(receipt) => {
             &receipt.receiver_id
         }
@@ -169,3 +194,49 @@ pub fn is_delayed_receipt_key(key: &[u8]) -> bool {
     debug_assert!(!key.is_empty());
     key[0] == col::DELAYED_RECEIPT || key[0] == col::DELAYED_RECEIPT_INDICES
 }
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+
+    use crate::hash::hash;
+    use crate::receipt::{PromiseYieldIndices, PromiseYieldTimeout};
+    use crate::trie_key::TrieKey;
+    use crate::types::AccountId;
+
+    use super::StateRecord;
+
+    #[test]
+    fn test_promise_yield_records() {
+        let account_id: AccountId = "alice.near".parse().unwrap();
+        let data_id = hash(b"data_id");
+
+        let key = TrieKey::PromiseYieldReceipt { receiver_id: account_id.clone(), data_id };
+        match StateRecord::from_raw_key_value(key.to_vec(), vec![]) {
+            Some(StateRecord::PromiseYieldReceipt {
+                account_id: record_account_id,
+                data_id: record_data_id,
+            }) => {
+                assert_eq!(record_account_id, account_id);
+                assert_eq!(record_data_id, data_id);
+            }
+            record => panic!("unexpected record {:?}", record),
+        }
+
+        let timeout = PromiseYieldTimeout { account_id, data_id, expires_at: 10 };
+        let key = TrieKey::PromiseYieldTimeout { index: 3 };
+        match StateRecord::from_raw_key_value(key.to_vec(), timeout.try_to_vec().unwrap()) {
+            Some(StateRecord::PromiseYieldTimeout(record_timeout)) => {
+                assert_eq!(record_timeout, timeout);
+            }
+            record => panic!("unexpected record {:?}", record),
+        }
+
+        // The indices are restored from the timeouts.
+        let indices = PromiseYieldIndices { first_index: 3, next_available_index: 4 };
+        let key = TrieKey::PromiseYieldIndices;
+        assert!(
+            StateRecord::from_raw_key_value(key.to_vec(), indices.try_to_vec().unwrap()).is_none()
+        );
+    }
+}
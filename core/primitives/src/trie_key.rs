@@ -7,7 +7,7 @@ use std::mem::size_of;
 pub(crate) const ACCOUNT_DATA_SEPARATOR: u8 = b',';
 
 /// Type identifiers used for DB key generation to store values in the key-value storage.
-pub(crate) mod col {
+pub mod col {
     /// This column id is used when storing `primitives::account::Account` type about a given
     /// `account_id`.
     pub const ACCOUNT: u8 = 0;
@@ -35,8 +35,18 @@ pub(crate) mod col {
     pub const DELAYED_RECEIPT: u8 = 8;
     /// This column id is used when storing Key-Value data from a contract on an `account_id`.
     pub const CONTRACT_DATA: u8 = 9;
+    /// This column id is used when storing the indices of the queue of timeouts of yielded
+    /// promises.
+    /// NOTE: It is a singleton per shard.
+    pub const PROMISE_YIELD_INDICES: u8 = 10;
+    /// This column id is used when storing timeouts of yielded promises
+    /// (`primitives::receipt::PromiseYieldTimeout`).
+    pub const PROMISE_YIELD_TIMEOUT: u8 = 11;
+    /// This column id is used when marking a promise yielded by a given `account_id` as waiting
+    /// to be resumed.
+    pub const PROMISE_YIELD_RECEIPT: u8 = 12;
     /// All columns
-    pub const NON_DELAYED_RECEIPT_COLUMNS: [(u8, &str); 8] = [
+    pub const NON_DELAYED_RECEIPT_COLUMNS: [(u8, &str); 9] = [
         (ACCOUNT, "Account"),
         (CONTRACT_CODE, "ContractCode"),
         (ACCESS_KEY, "AccessKey"),
@@ -45,6 +55,7 @@ pub(crate) mod col {
         (PENDING_DATA_COUNT, "PendingDataCount"),
         (POSTPONED_RECEIPT, "PostponedReceipt"),
         (CONTRACT_DATA, "ContractData"),
+        (PROMISE_YIELD_RECEIPT, "PromiseYieldReceipt"),
     ];
 }
 
@@ -83,6 +94,17 @@ pub enum TrieKey {
     /// Used to store a key-value record `Vec<u8>` within a contract deployed on a given `AccountId`
     /// and a given key.
     ContractData { account_id: AccountId, key: Vec<u8> },
+    /// Used to store indices of the queue of timeouts of yielded promises
+    /// (`primitives::receipt::PromiseYieldIndices`).
+    /// NOTE: It is a singleton per shard.
+    PromiseYieldIndices,
+    /// Used to store a timeout of a yielded promise `primitives::receipt::PromiseYieldTimeout`
+    /// for a given index `u64` in the queue. The queue is unique per shard.
+    PromiseYieldTimeout { index: u64 },
+    /// Used to mark the promise yielded by a given receiver's `AccountId` with a given `data_id`
+    /// as waiting to be resumed. The value is empty.
+    /// NOTE: The yielded receipt itself is stored as a postponed receipt waiting for `data_id`.
+    PromiseYieldReceipt { receiver_id: AccountId, data_id: CryptoHash },
 }
 
 /// Provides `len` function.
@@ -140,6 +162,16 @@ impl TrieKey {
                     + ACCOUNT_DATA_SEPARATOR.len()
                     + key.len()
             }
+            TrieKey::PromiseYieldIndices => col::PROMISE_YIELD_INDICES.len(),
+            TrieKey::PromiseYieldTimeout { .. } => {
+                col::PROMISE_YIELD_TIMEOUT.len() + size_of::<u64>()
+            }
+            TrieKey::PromiseYieldReceipt { receiver_id, data_id } => {
+                col::PROMISE_YIELD_RECEIPT.len()
+                    + receiver_id.len()
+                    + ACCOUNT_DATA_SEPARATOR.len()
+                    + data_id.as_ref().len()
+            }
         }
     }
 
@@ -199,6 +231,19 @@ impl TrieKey {
                 buf.push(ACCOUNT_DATA_SEPARATOR);
                 buf.extend(key);
             }
+            TrieKey::PromiseYieldIndices => {
+                buf.push(col::PROMISE_YIELD_INDICES);
+            }
+            TrieKey::PromiseYieldTimeout { index } => {
+                buf.push(col::PROMISE_YIELD_TIMEOUT);
+                buf.extend(&index.to_le_bytes());
+            }
+            TrieKey::PromiseYieldReceipt { receiver_id, data_id } => {
+                buf.push(col::PROMISE_YIELD_RECEIPT);
+                buf.extend(receiver_id.as_ref().as_bytes());
+                buf.push(ACCOUNT_DATA_SEPARATOR);
+                buf.extend(data_id.as_ref());
+            }
         };
         debug_assert_eq!(expected_len, buf.len() - start_len);
     }
@@ -397,6 +442,35 @@ pub mod trie_key_parsers {
         })
     }
 
+    pub fn parse_account_id_from_promise_yield_receipt_key(
+        raw_key: &[u8],
+    ) -> Result<AccountId, std::io::Error> {
+        parse_account_id_from_trie_key_with_separator(
+            col::PROMISE_YIELD_RECEIPT,
+            raw_key,
+            "PromiseYieldReceipt",
+        )
+    }
+
+    pub fn parse_data_id_from_promise_yield_receipt_key(
+        raw_key: &[u8],
+        account_id: &AccountId,
+    ) -> Result<CryptoHash, std::io::Error> {
+        let prefix_len = col::PROMISE_YIELD_RECEIPT.len() * 2 + account_id.len();
+        if raw_key.len() < prefix_len {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "raw key is too short for TrieKey::PromiseYieldReceipt",
+            ));
+        }
+        CryptoHash::try_from(&raw_key[prefix_len..]).map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Can't parse CryptoHash for TrieKey::PromiseYieldReceipt",
+            )
+        })
+    }
+
     pub fn get_raw_prefix_for_access_keys(account_id: &AccountId) -> Vec<u8> {
         let mut res = Vec::with_capacity(col::ACCESS_KEY.len() * 2 + account_id.len());
         res.push(col::ACCESS_KEY);
@@ -624,4 +698,28 @@ mod tests {
         let raw_key = key.to_vec();
         assert!(trie_key_parsers::parse_account_id_from_raw_key(&raw_key).unwrap().is_none());
     }
+
+    #[test]
+    fn test_key_for_promise_yield_consistency() {
+        let key = TrieKey::PromiseYieldIndices;
+        let raw_key = key.to_vec();
+        assert_eq!(raw_key.len(), key.len());
+        assert!(trie_key_parsers::parse_account_id_from_raw_key(&raw_key).unwrap().is_none());
+        let key = TrieKey::PromiseYieldTimeout { index: 0 };
+        let raw_key = key.to_vec();
+        assert_eq!(raw_key.len(), key.len());
+        assert!(trie_key_parsers::parse_account_id_from_raw_key(&raw_key).unwrap().is_none());
+        for account_id in OK_ACCOUNT_IDS.iter().map(|x| x.parse::<AccountId>().unwrap()) {
+            let key = TrieKey::PromiseYieldReceipt {
+                receiver_id: account_id.clone(),
+                data_id: CryptoHash::default(),
+            };
+            let raw_key = key.to_vec();
+            assert_eq!(raw_key.len(), key.len());
+            assert_eq!(
+                trie_key_parsers::parse_account_id_from_raw_key(&raw_key).unwrap().unwrap(),
+                account_id
+            );
+        }
+    }
 }
@@ -345,6 +345,9 @@ impl StateChanges {
                 TrieKey::PostponedReceipt { .. } => {}
                 TrieKey::DelayedReceiptIndices => {}
                 TrieKey::DelayedReceipt { .. } => {}
+                TrieKey::PromiseYieldIndices => {}
+                TrieKey::PromiseYieldTimeout { .. } => {}
+                TrieKey::PromiseYieldReceipt { .. } => {}
             }
         }
 
@@ -946,6 +949,27 @@ pub enum TrieCacheMode {
     CachingChunk,
 }
 
+/// Memory limits of trie shard caches.
+///
+/// This config can be updated while the node is running.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct TrieCacheConfig {
+    /// Limit the memory consumption of the trie cache per shard.
+    ///
+    /// This is an approximate limit that attempts to factor in data structure
+    /// overhead also. It is supposed to be fairly accurate in the limit.
+    pub default_max_bytes: u64,
+    /// Overwrites `default_max_bytes` for specific shards.
+    pub per_shard_max_bytes: std::collections::HashMap<crate::shard_layout::ShardUId, u64>,
+}
+
+impl Default for TrieCacheConfig {
+    fn default() -> Self {
+        Self { default_max_bytes: 50_000_000, per_shard_max_bytes: Default::default() }
+    }
+}
+
 /// Counts trie nodes reads during tx/receipt execution for proper storage costs charging.
 #[derive(Debug, PartialEq)]
 pub struct TrieNodesCount {
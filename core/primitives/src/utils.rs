@@ -252,6 +252,17 @@ pub fn create_receipt_id_from_receipt(
     )
 }
 
+/// Creates a new Receipt ID for the data receipt which resolves a timed out yielded promise.
+/// This method is backward compatible, so it takes the current protocol version.
+pub fn create_receipt_id_from_promise_yield_timeout(
+    protocol_version: ProtocolVersion,
+    data_id: &CryptoHash,
+    prev_block_hash: &CryptoHash,
+    block_hash: &CryptoHash,
+) -> CryptoHash {
+    create_hash_upgradable(protocol_version, data_id, prev_block_hash, block_hash, 0)
+}
+
 /// Creates a new action_hash from a given receipt, a block hash and an action index.
 /// This method is backward compatible, so it takes the current protocol version.
 pub fn create_action_hash(
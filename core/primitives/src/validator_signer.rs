@@ -1,5 +1,5 @@
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
 use near_crypto::{InMemorySigner, KeyType, PublicKey, Signature, Signer};
 
@@ -7,10 +7,22 @@ use crate::block::{Approval, ApprovalInner, BlockHeader};
 use crate::challenge::ChallengeBody;
 use crate::hash::CryptoHash;
 use crate::network::{AnnounceAccount, PeerId};
-use crate::sharding::ChunkHash;
+use crate::sharding::ChunkHeaderToSign;
 use crate::telemetry::TelemetryInfo;
 use crate::types::{AccountId, BlockHeight, EpochId};
 
+/// Reason why a validator signer didn't sign a message.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum ValidatorSignerError {
+    /// Signing the message could get the validator slashed, e.g. because it conflicts with one
+    /// signed before.
+    #[error("refused to sign: {0}")]
+    Refused(String),
+    /// The signer can't sign anything at the moment, e.g. because it's unreachable.
+    #[error("signer is unavailable: {0}")]
+    Unavailable(String),
+}
+
 /// Validator signer that is used to sign blocks and approvals.
 pub trait ValidatorSigner: Sync + Send {
     /// Account id of the given validator.
@@ -20,7 +32,10 @@ pub trait ValidatorSigner: Sync + Send {
     fn public_key(&self) -> PublicKey;
 
     /// Serializes telemetry info to JSON and signs it, returning JSON with "signature" field.
-    fn sign_telemetry(&self, info: &TelemetryInfo) -> serde_json::Value;
+    fn sign_telemetry(
+        &self,
+        info: &TelemetryInfo,
+    ) -> Result<serde_json::Value, ValidatorSignerError>;
 
     /// Signs given parts of the header.
     fn sign_block_header_parts(
@@ -28,16 +43,29 @@ pub trait ValidatorSigner: Sync + Send {
         prev_hash: CryptoHash,
         inner_lite: &[u8],
         inner_rest: &[u8],
-    ) -> (CryptoHash, Signature);
+    ) -> Result<(CryptoHash, Signature), ValidatorSignerError>;
 
-    /// Signs given inner of the chunk header.
-    fn sign_chunk_hash(&self, chunk_hash: &ChunkHash) -> Signature;
+    /// Signs hash of the given inner of the chunk header.  `epoch_id` is the epoch of the chunk.
+    fn sign_chunk_header(
+        &self,
+        header: &ChunkHeaderToSign,
+        epoch_id: &EpochId,
+    ) -> Result<Signature, ValidatorSignerError>;
 
-    /// Signs approval of given parent hash and reference hash.
-    fn sign_approval(&self, inner: &ApprovalInner, target_height: BlockHeight) -> Signature;
+    /// Signs approval of given parent hash and reference hash.  `epoch_id` is the epoch of the
+    /// block at `target_height`.
+    fn sign_approval(
+        &self,
+        inner: &ApprovalInner,
+        target_height: BlockHeight,
+        epoch_id: &EpochId,
+    ) -> Result<Signature, ValidatorSignerError>;
 
     /// Signs challenge body.
-    fn sign_challenge(&self, challenge_body: &ChallengeBody) -> (CryptoHash, Signature);
+    fn sign_challenge(
+        &self,
+        challenge_body: &ChallengeBody,
+    ) -> Result<(CryptoHash, Signature), ValidatorSignerError>;
 
     /// Signs account announce.
     fn sign_account_announce(
@@ -45,7 +73,7 @@ pub trait ValidatorSigner: Sync + Send {
         account_id: &AccountId,
         peer_id: &PeerId,
         epoch_id: &EpochId,
-    ) -> Signature;
+    ) -> Result<Signature, ValidatorSignerError>;
 
     /// Signs a proto-serialized AccountKeyPayload (see
     /// chain/network/src/network_protocol/network.proto).
@@ -58,12 +86,15 @@ pub trait ValidatorSigner: Sync + Send {
     /// used only for networking purposes and are not persisted on chain.
     /// Moving to proto serialization for stuff stored on chain would be way
     /// harder.
-    fn sign_account_key_payload(&self, proto_bytes: &[u8]) -> Signature;
+    fn sign_account_key_payload(
+        &self,
+        proto_bytes: &[u8],
+    ) -> Result<Signature, ValidatorSignerError>;
 
     fn compute_vrf_with_proof(
         &self,
         data: &[u8],
-    ) -> (near_crypto::vrf::Value, near_crypto::vrf::Proof);
+    ) -> Result<(near_crypto::vrf::Value, near_crypto::vrf::Proof), ValidatorSignerError>;
 
     /// Used by test infrastructure, only implement if make sense for testing otherwise raise `unimplemented`.
     fn write_to_file(&self, path: &Path) -> std::io::Result<()>;
@@ -86,8 +117,11 @@ impl ValidatorSigner for EmptyValidatorSigner {
         PublicKey::empty(KeyType::ED25519)
     }
 
-    fn sign_telemetry(&self, _info: &TelemetryInfo) -> serde_json::Value {
-        serde_json::Value::default()
+    fn sign_telemetry(
+        &self,
+        _info: &TelemetryInfo,
+    ) -> Result<serde_json::Value, ValidatorSignerError> {
+        Ok(serde_json::Value::default())
     }
 
     fn sign_block_header_parts(
@@ -95,21 +129,33 @@ impl ValidatorSigner for EmptyValidatorSigner {
         prev_hash: CryptoHash,
         inner_lite: &[u8],
         inner_rest: &[u8],
-    ) -> (CryptoHash, Signature) {
+    ) -> Result<(CryptoHash, Signature), ValidatorSignerError> {
         let hash = BlockHeader::compute_hash(prev_hash, inner_lite, inner_rest);
-        (hash, Signature::default())
+        Ok((hash, Signature::default()))
     }
 
-    fn sign_chunk_hash(&self, _chunk_hash: &ChunkHash) -> Signature {
-        Signature::default()
+    fn sign_chunk_header(
+        &self,
+        _header: &ChunkHeaderToSign,
+        _epoch_id: &EpochId,
+    ) -> Result<Signature, ValidatorSignerError> {
+        Ok(Signature::default())
     }
 
-    fn sign_approval(&self, _inner: &ApprovalInner, _target_height: BlockHeight) -> Signature {
-        Signature::default()
+    fn sign_approval(
+        &self,
+        _inner: &ApprovalInner,
+        _target_height: BlockHeight,
+        _epoch_id: &EpochId,
+    ) -> Result<Signature, ValidatorSignerError> {
+        Ok(Signature::default())
     }
 
-    fn sign_challenge(&self, challenge_body: &ChallengeBody) -> (CryptoHash, Signature) {
-        (CryptoHash::hash_borsh(challenge_body), Signature::default())
+    fn sign_challenge(
+        &self,
+        challenge_body: &ChallengeBody,
+    ) -> Result<(CryptoHash, Signature), ValidatorSignerError> {
+        Ok((CryptoHash::hash_borsh(challenge_body), Signature::default()))
     }
 
     fn sign_account_announce(
@@ -117,18 +163,21 @@ impl ValidatorSigner for EmptyValidatorSigner {
         _account_id: &AccountId,
         _peer_id: &PeerId,
         _epoch_id: &EpochId,
-    ) -> Signature {
-        Signature::default()
+    ) -> Result<Signature, ValidatorSignerError> {
+        Ok(Signature::default())
     }
 
-    fn sign_account_key_payload(&self, _proto_bytes: &[u8]) -> Signature {
-        Signature::default()
+    fn sign_account_key_payload(
+        &self,
+        _proto_bytes: &[u8],
+    ) -> Result<Signature, ValidatorSignerError> {
+        Ok(Signature::default())
     }
 
     fn compute_vrf_with_proof(
         &self,
         _data: &[u8],
-    ) -> (near_crypto::vrf::Value, near_crypto::vrf::Proof) {
+    ) -> Result<(near_crypto::vrf::Value, near_crypto::vrf::Proof), ValidatorSignerError> {
         unimplemented!()
     }
 
@@ -174,11 +223,14 @@ impl ValidatorSigner for InMemoryValidatorSigner {
         self.signer.public_key()
     }
 
-    fn sign_telemetry(&self, info: &TelemetryInfo) -> serde_json::Value {
+    fn sign_telemetry(
+        &self,
+        info: &TelemetryInfo,
+    ) -> Result<serde_json::Value, ValidatorSignerError> {
         let mut value = serde_json::to_value(info).expect("Telemetry must serialize to JSON");
         let content = serde_json::to_string(&value).expect("Telemetry must serialize to JSON");
         value["signature"] = self.signer.sign(content.as_bytes()).to_string().into();
-        value
+        Ok(value)
     }
 
     fn sign_block_header_parts(
@@ -186,23 +238,35 @@ impl ValidatorSigner for InMemoryValidatorSigner {
         prev_hash: CryptoHash,
         inner_lite: &[u8],
         inner_rest: &[u8],
-    ) -> (CryptoHash, Signature) {
+    ) -> Result<(CryptoHash, Signature), ValidatorSignerError> {
         let hash = BlockHeader::compute_hash(prev_hash, inner_lite, inner_rest);
-        (hash, self.signer.sign(hash.as_ref()))
+        Ok((hash, self.signer.sign(hash.as_ref())))
     }
 
-    fn sign_chunk_hash(&self, chunk_hash: &ChunkHash) -> Signature {
-        self.signer.sign(chunk_hash.as_ref())
+    fn sign_chunk_header(
+        &self,
+        header: &ChunkHeaderToSign,
+        _epoch_id: &EpochId,
+    ) -> Result<Signature, ValidatorSignerError> {
+        Ok(self.signer.sign(header.chunk_hash().as_ref()))
     }
 
-    fn sign_approval(&self, inner: &ApprovalInner, target_height: BlockHeight) -> Signature {
-        self.signer.sign(&Approval::get_data_for_sig(inner, target_height))
+    fn sign_approval(
+        &self,
+        inner: &ApprovalInner,
+        target_height: BlockHeight,
+        _epoch_id: &EpochId,
+    ) -> Result<Signature, ValidatorSignerError> {
+        Ok(self.signer.sign(&Approval::get_data_for_sig(inner, target_height)))
     }
 
-    fn sign_challenge(&self, challenge_body: &ChallengeBody) -> (CryptoHash, Signature) {
+    fn sign_challenge(
+        &self,
+        challenge_body: &ChallengeBody,
+    ) -> Result<(CryptoHash, Signature), ValidatorSignerError> {
         let hash = CryptoHash::hash_borsh(challenge_body);
         let signature = self.signer.sign(hash.as_ref());
-        (hash, signature)
+        Ok((hash, signature))
     }
 
     fn sign_account_announce(
@@ -210,23 +274,129 @@ impl ValidatorSigner for InMemoryValidatorSigner {
         account_id: &AccountId,
         peer_id: &PeerId,
         epoch_id: &EpochId,
-    ) -> Signature {
+    ) -> Result<Signature, ValidatorSignerError> {
         let hash = AnnounceAccount::build_header_hash(account_id, peer_id, epoch_id);
-        self.signer.sign(hash.as_ref())
+        Ok(self.signer.sign(hash.as_ref()))
     }
 
-    fn sign_account_key_payload(&self, proto_bytes: &[u8]) -> Signature {
-        self.signer.sign(proto_bytes)
+    fn sign_account_key_payload(
+        &self,
+        proto_bytes: &[u8],
+    ) -> Result<Signature, ValidatorSignerError> {
+        Ok(self.signer.sign(proto_bytes))
     }
 
     fn compute_vrf_with_proof(
         &self,
         data: &[u8],
-    ) -> (near_crypto::vrf::Value, near_crypto::vrf::Proof) {
-        self.signer.compute_vrf_with_proof(data)
+    ) -> Result<(near_crypto::vrf::Value, near_crypto::vrf::Proof), ValidatorSignerError> {
+        Ok(self.signer.compute_vrf_with_proof(data))
     }
 
     fn write_to_file(&self, path: &Path) -> std::io::Result<()> {
         self.signer.write_to_file(path)
     }
 }
+
+/// Validator signer whose key can be replaced while the node is running, e.g. to rotate the
+/// validator key.  Clones share the key, so all of them switch to the new one at once.
+#[derive(Clone)]
+pub struct UpdateableValidatorSigner {
+    account_id: AccountId,
+    signer: Arc<RwLock<Arc<dyn ValidatorSigner>>>,
+}
+
+impl UpdateableValidatorSigner {
+    pub fn new(signer: Arc<dyn ValidatorSigner>) -> Self {
+        Self { account_id: signer.validator_id().clone(), signer: Arc::new(RwLock::new(signer)) }
+    }
+
+    /// Returns the active signer.  Use it when the public key is signed along with the data,
+    /// so that the key matches the signature even if it's replaced in between.
+    pub fn get(&self) -> Arc<dyn ValidatorSigner> {
+        self.signer.read().unwrap().clone()
+    }
+
+    /// Replaces the active signer, which has to belong to the same account.
+    pub fn update(&self, signer: Arc<dyn ValidatorSigner>) {
+        assert_eq!(&self.account_id, signer.validator_id(), "Validator account can't change");
+        *self.signer.write().unwrap() = signer;
+    }
+}
+
+impl ValidatorSigner for UpdateableValidatorSigner {
+    fn validator_id(&self) -> &AccountId {
+        &self.account_id
+    }
+
+    fn public_key(&self) -> PublicKey {
+        self.get().public_key()
+    }
+
+    fn sign_telemetry(
+        &self,
+        info: &TelemetryInfo,
+    ) -> Result<serde_json::Value, ValidatorSignerError> {
+        self.get().sign_telemetry(info)
+    }
+
+    fn sign_block_header_parts(
+        &self,
+        prev_hash: CryptoHash,
+        inner_lite: &[u8],
+        inner_rest: &[u8],
+    ) -> Result<(CryptoHash, Signature), ValidatorSignerError> {
+        self.get().sign_block_header_parts(prev_hash, inner_lite, inner_rest)
+    }
+
+    fn sign_chunk_header(
+        &self,
+        header: &ChunkHeaderToSign,
+        epoch_id: &EpochId,
+    ) -> Result<Signature, ValidatorSignerError> {
+        self.get().sign_chunk_header(header, epoch_id)
+    }
+
+    fn sign_approval(
+        &self,
+        inner: &ApprovalInner,
+        target_height: BlockHeight,
+        epoch_id: &EpochId,
+    ) -> Result<Signature, ValidatorSignerError> {
+        self.get().sign_approval(inner, target_height, epoch_id)
+    }
+
+    fn sign_challenge(
+        &self,
+        challenge_body: &ChallengeBody,
+    ) -> Result<(CryptoHash, Signature), ValidatorSignerError> {
+        self.get().sign_challenge(challenge_body)
+    }
+
+    fn sign_account_announce(
+        &self,
+        account_id: &AccountId,
+        peer_id: &PeerId,
+        epoch_id: &EpochId,
+    ) -> Result<Signature, ValidatorSignerError> {
+        self.get().sign_account_announce(account_id, peer_id, epoch_id)
+    }
+
+    fn sign_account_key_payload(
+        &self,
+        proto_bytes: &[u8],
+    ) -> Result<Signature, ValidatorSignerError> {
+        self.get().sign_account_key_payload(proto_bytes)
+    }
+
+    fn compute_vrf_with_proof(
+        &self,
+        data: &[u8],
+    ) -> Result<(near_crypto::vrf::Value, near_crypto::vrf::Proof), ValidatorSignerError> {
+        self.get().compute_vrf_with_proof(data)
+    }
+
+    fn write_to_file(&self, path: &Path) -> std::io::Result<()> {
+        self.get().write_to_file(path)
+    }
+}
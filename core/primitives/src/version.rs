@@ -151,6 +151,24 @@ pub enum ProtocolFeature {
     #[cfg(feature = "protocol_feature_zero_balance_account")]
     /// NEP 448: https://github.com/near/NEPs/pull/448
     ZeroBalanceAccount,
+    /// Allow contracts to use sign-extension operators and bulk memory
+    /// operations, see `WasmFeaturesVersion::V1`.
+    #[cfg(feature = "protocol_feature_wasm_extensions")]
+    WasmExtensions,
+    /// bls12381_* host functions for operations on the BLS12-381 curve.
+    #[cfg(feature = "protocol_feature_bls12381")]
+    Bls12381,
+    /// p256_verify host function to verify secp256r1 signatures.
+    #[cfg(feature = "protocol_feature_p256_verify")]
+    P256Verify,
+    /// promise_yield_create and promise_yield_resume host functions, allowing contracts to
+    /// postpone a callback until it's resumed with data from a later transaction.
+    #[cfg(feature = "protocol_feature_yield_resume")]
+    YieldResume,
+    /// Validate chunk state challenges by re-executing the challenged chunk on the partial state
+    /// and incoming receipts included in the challenge, and roll back the challenged blocks.
+    #[cfg(feature = "protocol_feature_chunk_state_challenges")]
+    ChunkStateChallenges,
 }
 
 /// Both, outgoing and incoming tcp connections to peers, will be rejected if `peer's`
@@ -165,7 +183,7 @@ const STABLE_PROTOCOL_VERSION: ProtocolVersion = 58;
 /// Largest protocol version supported by the current binary.
 pub const PROTOCOL_VERSION: ProtocolVersion = if cfg!(feature = "nightly_protocol") {
     // On nightly, pick big enough version to support all features.
-    134
+    139
 } else {
     // Enable all stable features.
     STABLE_PROTOCOL_VERSION
@@ -241,6 +259,16 @@ impl ProtocolFeature {
             ProtocolFeature::DelegateAction => 133,
             #[cfg(feature = "protocol_feature_zero_balance_account")]
             ProtocolFeature::ZeroBalanceAccount => 134,
+            #[cfg(feature = "protocol_feature_wasm_extensions")]
+            ProtocolFeature::WasmExtensions => 135,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            ProtocolFeature::Bls12381 => 136,
+            #[cfg(feature = "protocol_feature_p256_verify")]
+            ProtocolFeature::P256Verify => 137,
+            #[cfg(feature = "protocol_feature_yield_resume")]
+            ProtocolFeature::YieldResume => 138,
+            #[cfg(feature = "protocol_feature_chunk_state_challenges")]
+            ProtocolFeature::ChunkStateChallenges => 139,
         }
     }
 }
@@ -12,7 +12,7 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use chrono::DateTime;
 use near_primitives_core::config::{ActionCosts, ExtCosts, VMConfig};
 use near_primitives_core::runtime::fees::Fee;
-use num_rational::Rational32;
+use num_rational::{Ratio, Rational32};
 use serde::{Deserialize, Serialize};
 
 use near_crypto::{PublicKey, Signature};
@@ -33,7 +33,7 @@ use crate::merkle::{combine_hash, MerklePath};
 use crate::network::PeerId;
 use crate::receipt::{ActionReceipt, DataReceipt, DataReceiver, Receipt, ReceiptEnum};
 use crate::runtime::config::RuntimeConfig;
-use crate::serialize::{base64_format, dec_format, option_base64_format};
+use crate::serialize::{base64_format, dec_format, dec_ratio_format, option_base64_format};
 use crate::sharding::{
     ChunkHash, ShardChunk, ShardChunkHeader, ShardChunkHeaderInner, ShardChunkHeaderInnerV2,
     ShardChunkHeaderV3,
@@ -221,6 +221,15 @@ pub struct ViewStateResult {
     // set in the request) was deprecated in 1.30.  Add
     // `#[serde(skip(Vec::if_empty))` at 1.33 or something.
     pub proof: Vec<Arc<[u8]>>,
+    /// Key to pass as `start_key` to fetch the next page of results.  `None`
+    /// if there are no more items matching the prefix.
+    #[serde(
+        rename = "next_key_base64",
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "option_base64_format"
+    )]
+    pub next_key: Option<Vec<u8>>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
@@ -289,6 +298,21 @@ pub enum QueryRequest {
         prefix: StoreKey,
         #[serde(default, skip_serializing_if = "is_false")]
         include_proof: bool,
+        /// If set, only keys greater or equal to this one are returned.  Used
+        /// to continue listing from the `next_key` of a previous response.
+        #[serde(
+            rename = "start_key_base64",
+            default,
+            skip_serializing_if = "Option::is_none",
+            with = "option_base64_format"
+        )]
+        start_key: Option<Vec<u8>>,
+        /// Maximum number of items to return.  If either this or `start_key` is
+        /// set, the response is paginated and each page is cut short before
+        /// exceeding the state size limit of the node rather than the whole
+        /// account state being subject to it.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        limit: Option<u64>,
     },
     ViewAccessKey {
         account_id: AccountId,
@@ -621,8 +645,12 @@ pub struct StatusResponse {
     pub sync_info: StatusSyncInfo,
     /// Validator id of the node
     pub validator_account_id: Option<AccountId>,
-    /// Public key of the validator.
+    /// Public key the validator currently signs with.
     pub validator_public_key: Option<PublicKey>,
+    /// Validator key which replaces `validator_public_key` at the epoch
+    /// boundary where it gets staked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_validator_public_key: Option<PublicKey>,
     /// Public key of the node.
     pub node_public_key: PublicKey,
     /// Deprecated; same as `validator_public_key` which you should use instead.
@@ -1772,6 +1800,147 @@ pub struct NextEpochValidatorInfo {
     pub shards: Vec<ShardId>,
 }
 
+/// Projection of the performance of current epoch validators to the end of
+/// the epoch, used to warn validators before they get kicked out.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct ValidatorKickoutPredictionView {
+    pub epoch_id: CryptoHash,
+    pub epoch_height: EpochHeight,
+    pub epoch_start_height: BlockHeight,
+    /// Height of the block the prediction was made at.
+    pub height: BlockHeight,
+    /// Estimated height of the last block of the epoch.
+    pub estimated_epoch_end_height: BlockHeight,
+    /// Minimum percentage of expected blocks a block producer has to produce.
+    pub block_producer_kickout_threshold: u8,
+    /// Minimum percentage of expected chunks a chunk producer has to produce.
+    pub chunk_producer_kickout_threshold: u8,
+    /// Maximum percentage of the total stake which can be kicked out in one
+    /// epoch.  Validators with the best uptime are exempted from kickouts
+    /// until the rest of the stake is below this limit.
+    pub validator_max_kickout_stake_perc: u8,
+    pub validators: Vec<ValidatorPerformancePredictionView>,
+    /// Seat price of the next epoch, which is already decided.
+    #[serde(with = "dec_format")]
+    pub next_epoch_seat_price: Balance,
+    /// Seat price of the epoch after next estimated from the proposals made
+    /// so far in this epoch and the predicted kickouts, not counting the
+    /// rewards for this epoch.  None if the proposals aren't enough to fill
+    /// the seats.
+    #[serde(with = "dec_format")]
+    pub estimated_seat_price: Option<Balance>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct ValidatorPerformancePredictionView {
+    pub account_id: AccountId,
+    #[serde(with = "dec_format")]
+    pub stake: Balance,
+    pub blocks: ProductionPredictionView,
+    pub chunks: ProductionPredictionView,
+    /// Why the validator would be kicked out if the epoch ended at the block
+    /// the prediction was made at.  None if the validator keeps its seat.
+    pub kickout_reason: Option<ValidatorKickoutReason>,
+}
+
+/// Blocks or chunks produced by a validator in the current epoch.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct ProductionPredictionView {
+    pub produced: NumBlocks,
+    pub expected: NumBlocks,
+    /// Number expected at the end of the epoch, assuming no heights are
+    /// skipped until then.
+    pub expected_at_epoch_end: NumBlocks,
+    /// Number produced at the end of the epoch if the validator keeps
+    /// producing at its current rate.
+    pub projected_produced_at_epoch_end: NumBlocks,
+    /// Number the validator has to produce by the end of the epoch to stay
+    /// above the kickout threshold.
+    pub required_at_epoch_end: NumBlocks,
+}
+
+impl ProductionPredictionView {
+    pub fn new(
+        produced: NumBlocks,
+        expected: NumBlocks,
+        expected_at_epoch_end: NumBlocks,
+        kickout_threshold: u8,
+    ) -> Self {
+        let projected_produced_at_epoch_end = if expected == 0 {
+            expected_at_epoch_end
+        } else {
+            (produced as u128 * expected_at_epoch_end as u128 / expected as u128) as NumBlocks
+        };
+        let required_at_epoch_end = (expected_at_epoch_end * kickout_threshold as u64 + 99) / 100;
+        Self {
+            produced,
+            expected,
+            expected_at_epoch_end,
+            projected_produced_at_epoch_end,
+            required_at_epoch_end,
+        }
+    }
+}
+
+/// Rewards of a finished epoch recomputed from the stored epoch and block
+/// info, together with all the inputs of the calculation.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct EpochRewardsView {
+    pub epoch_id: CryptoHash,
+    pub epoch_height: EpochHeight,
+    pub protocol_version: ProtocolVersion,
+    /// Hash of the last block of the epoch, at which the rewards are computed.
+    pub last_block_hash: CryptoHash,
+    #[serde(with = "dec_format")]
+    pub total_supply: Balance,
+    /// Time between the last blocks of the previous epoch and this one.
+    pub epoch_duration_ns: u64,
+    pub max_inflation_rate: Rational32,
+    pub protocol_reward_rate: Rational32,
+    pub online_min_threshold: Rational32,
+    pub online_max_threshold: Rational32,
+    #[serde(with = "dec_format")]
+    pub epoch_total_reward: Balance,
+    pub protocol_treasury_account: AccountId,
+    #[serde(with = "dec_format")]
+    pub protocol_reward: Balance,
+    /// Part of the total reward shared between the validators.
+    #[serde(with = "dec_format")]
+    pub epoch_validator_reward: Balance,
+    #[serde(with = "dec_format")]
+    pub total_stake: Balance,
+    /// Newly minted tokens, including the protocol reward.
+    #[serde(with = "dec_format")]
+    pub minted_amount: Balance,
+    /// Minted amount stored in the epoch info of the epoch after next, for
+    /// comparison.  None if that epoch info is not available.
+    #[serde(with = "dec_format")]
+    pub recorded_minted_amount: Option<Balance>,
+    pub validators: Vec<ValidatorRewardView>,
+    /// Validators kicked out at the end of the epoch, which get no reward.
+    pub validator_kickout: Vec<ValidatorKickoutView>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct ValidatorRewardView {
+    pub account_id: AccountId,
+    #[serde(with = "dec_format")]
+    pub stake: Balance,
+    pub num_produced_blocks: NumBlocks,
+    pub num_expected_blocks: NumBlocks,
+    pub num_produced_chunks: NumBlocks,
+    pub num_expected_chunks: NumBlocks,
+    /// Average of produced / expected blocks and chunks.  Validators below
+    /// `online_min_threshold` get no reward.
+    #[serde(with = "dec_ratio_format")]
+    pub uptime: Ratio<u128>,
+    #[serde(with = "dec_format")]
+    pub reward: Balance,
+    /// Reward stored in the epoch info of the epoch after next.
+    #[serde(with = "dec_format")]
+    pub recorded_reward: Option<Balance>,
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, BorshDeserialize, BorshSerialize)]
 pub struct LightClientBlockView {
     pub prev_block_hash: CryptoHash,
@@ -2032,6 +2201,133 @@ pub type StateChangesView = Vec<StateChangeWithCauseView>;
 /// Maintenance windows view are a vector of maintenance window.
 pub type MaintenanceWindowsView = Vec<Range<BlockHeight>>;
 
+/// A single storage operation performed by a contract during a traced host function call.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StorageAccessView {
+    Read {
+        #[serde(rename = "key_base64", with = "base64_format")]
+        key: Vec<u8>,
+        #[serde(rename = "value_base64", with = "option_base64_format")]
+        value: Option<Vec<u8>>,
+    },
+    Write {
+        #[serde(rename = "key_base64", with = "base64_format")]
+        key: Vec<u8>,
+        #[serde(rename = "value_base64", with = "base64_format")]
+        value: Vec<u8>,
+        /// Value which was overwritten by this write, if any.
+        #[serde(rename = "evicted_base64", with = "option_base64_format")]
+        evicted: Option<Vec<u8>>,
+    },
+    Remove {
+        #[serde(rename = "key_base64", with = "base64_format")]
+        key: Vec<u8>,
+        #[serde(rename = "removed_base64", with = "option_base64_format")]
+        removed: Option<Vec<u8>>,
+    },
+    HasKey {
+        #[serde(rename = "key_base64", with = "base64_format")]
+        key: Vec<u8>,
+        exists: bool,
+    },
+}
+
+/// A promise created by a contract during a traced host function call.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CreatedPromiseView {
+    /// Index of the promise as seen by the contract.
+    pub promise_index: u64,
+    pub receiver_id: AccountId,
+}
+
+/// A host function invoked by a contract, recorded when execution tracing is enabled.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct HostFunctionCallView {
+    pub name: String,
+    /// Raw arguments as passed by the contract, in declaration order.
+    pub args: Vec<(String, u64)>,
+    /// Gas burnt by the call itself, including gas for nested operations such as storage access.
+    pub gas_burnt: Gas,
+    /// Value returned to the contract, if the host function returns one.
+    pub result: Option<u64>,
+    /// Error which aborted the execution, if the call failed.
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub storage: Vec<StorageAccessView>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub promises: Vec<CreatedPromiseView>,
+}
+
+/// Trace of a single contract function call.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FunctionCallTraceView {
+    pub contract_id: AccountId,
+    pub method_name: String,
+    pub gas_burnt: Gas,
+    pub logs: Vec<String>,
+    /// Error which made the function call fail, if any.
+    pub error: Option<String>,
+    pub host_calls: Vec<HostFunctionCallView>,
+    /// Only present if explicitly requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extended_gas_profile: Option<ExtendedGasProfileView>,
+}
+
+/// Breakdown of gas burnt by a function call per host function and per wasm
+/// function of the contract.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ExtendedGasProfileView {
+    pub host_functions: Vec<HostFunctionGasView>,
+    pub wasm_functions: Vec<WasmFunctionGasView>,
+    /// Wasm gas charged for growing the contract memory.
+    pub memory_grow_gas: Gas,
+}
+
+/// Aggregated gas usage of all calls to a single host function.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct HostFunctionGasView {
+    pub name: String,
+    pub calls: u64,
+    pub gas_burnt: Gas,
+    pub bytes_read: u64,
+    pub bytes_written: u64,
+}
+
+/// Gas burnt on wasm instructions of a single contract function.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WasmFunctionGasView {
+    /// Index of the function in the deployed contract module.
+    pub function_index: u32,
+    /// Export name of the function, or its name from the custom `name`
+    /// section, if the contract has been compiled with debug info.
+    pub name: Option<String>,
+    pub exported: bool,
+    pub gas: Gas,
+}
+
+/// Traces of all function calls executed as part of a receipt.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReceiptExecutionTraceView {
+    pub receipt_id: CryptoHash,
+    /// Block in which the receipt was executed.
+    pub block_hash: CryptoHash,
+    pub function_calls: Vec<FunctionCallTraceView>,
+}
+
+/// Outcome of executing a transaction and all receipts it produced on top of the latest
+/// state, without committing the changes.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct GasEstimateView {
+    #[serde(flatten)]
+    pub final_outcome: FinalExecutionOutcomeView,
+    /// Gas burnt by the transaction and all of its receipts.
+    pub gas_burnt: Gas,
+    /// Tokens burnt by the transaction and all of its receipts.
+    #[serde(with = "dec_format")]
+    pub tokens_burnt: Balance,
+}
+
 /// View that preserves JSON format of the runtime config.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RuntimeConfigView {
@@ -2282,6 +2578,9 @@ pub struct VMConfigView {
     pub grow_mem_cost: u32,
     /// Gas cost of a regular operation.
     pub regular_op_cost: u32,
+    /// Gas cost of each 8-byte word processed by a bulk memory instruction, in
+    /// units of `regular_op_cost`.
+    pub bulk_memory_word_cost: u32,
 
     /// Describes limits for VM and Runtime.
     ///
@@ -2296,6 +2595,7 @@ impl From<VMConfig> for VMConfigView {
             ext_costs: ExtCostsConfigView::from(config.ext_costs),
             grow_mem_cost: config.grow_mem_cost,
             regular_op_cost: config.regular_op_cost,
+            bulk_memory_word_cost: config.bulk_memory_word_cost,
             limit_config: config.limit_config,
         }
     }
@@ -2307,6 +2607,7 @@ impl From<VMConfigView> for VMConfig {
             ext_costs: near_primitives_core::config::ExtCostsConfig::from(view.ext_costs),
             grow_mem_cost: view.grow_mem_cost,
             regular_op_cost: view.regular_op_cost,
+            bulk_memory_word_cost: view.bulk_memory_word_cost,
             limit_config: view.limit_config,
         }
     }
@@ -2479,6 +2780,58 @@ pub struct ExtCostsConfigView {
     pub alt_bn128_pairing_check_base: Gas,
     /// Per element cost for pairing check
     pub alt_bn128_pairing_check_element: Gas,
+
+    // #############
+    // # BLS12-381 #
+    // #############
+    /// Base cost for sum of points on the BLS12-381 curve
+    pub bls12381_p1_sum_base: Gas,
+    /// Per element cost for sum of points on the BLS12-381 curve
+    pub bls12381_p1_sum_element: Gas,
+    /// Base cost for sum of points on the BLS12-381 twist
+    pub bls12381_p2_sum_base: Gas,
+    /// Per element cost for sum of points on the BLS12-381 twist
+    pub bls12381_p2_sum_element: Gas,
+    /// Base cost for multiexp in G1
+    pub bls12381_g1_multiexp_base: Gas,
+    /// Per element cost for multiexp in G1
+    pub bls12381_g1_multiexp_element: Gas,
+    /// Base cost for multiexp in G2
+    pub bls12381_g2_multiexp_base: Gas,
+    /// Per element cost for multiexp in G2
+    pub bls12381_g2_multiexp_element: Gas,
+    /// Base cost for mapping field elements to G1
+    pub bls12381_map_fp_to_g1_base: Gas,
+    /// Per element cost for mapping field elements to G1
+    pub bls12381_map_fp_to_g1_element: Gas,
+    /// Base cost for mapping field elements to G2
+    pub bls12381_map_fp2_to_g2_base: Gas,
+    /// Per element cost for mapping field elements to G2
+    pub bls12381_map_fp2_to_g2_element: Gas,
+    /// Base cost for pairing check
+    pub bls12381_pairing_base: Gas,
+    /// Per element cost for pairing check
+    pub bls12381_pairing_element: Gas,
+
+    // #########
+    // # P-256 #
+    // #########
+    /// Cost of verifying a P-256 signature
+    pub p256_verify_base: Gas,
+    /// Cost of verifying a P-256 signature per message byte
+    pub p256_verify_byte: Gas,
+
+    // ################
+    // # Yield/resume #
+    // ################
+    /// Base cost of creating a yielded promise
+    pub yield_create_base: Gas,
+    /// Per byte cost of the method name and arguments of a yielded promise
+    pub yield_create_byte: Gas,
+    /// Base cost of resuming a yielded promise
+    pub yield_resume_base: Gas,
+    /// Per byte cost of the payload a yielded promise is resumed with
+    pub yield_resume_byte: Gas,
 }
 
 impl From<near_primitives_core::config::ExtCostsConfig> for ExtCostsConfigView {
@@ -2545,6 +2898,26 @@ impl From<near_primitives_core::config::ExtCostsConfig> for ExtCostsConfigView {
             alt_bn128_g1_sum_element: config.cost(ExtCosts::alt_bn128_g1_sum_element),
             alt_bn128_pairing_check_base: config.cost(ExtCosts::alt_bn128_pairing_check_base),
             alt_bn128_pairing_check_element: config.cost(ExtCosts::alt_bn128_pairing_check_element),
+            bls12381_p1_sum_base: config.cost(ExtCosts::bls12381_p1_sum_base),
+            bls12381_p1_sum_element: config.cost(ExtCosts::bls12381_p1_sum_element),
+            bls12381_p2_sum_base: config.cost(ExtCosts::bls12381_p2_sum_base),
+            bls12381_p2_sum_element: config.cost(ExtCosts::bls12381_p2_sum_element),
+            bls12381_g1_multiexp_base: config.cost(ExtCosts::bls12381_g1_multiexp_base),
+            bls12381_g1_multiexp_element: config.cost(ExtCosts::bls12381_g1_multiexp_element),
+            bls12381_g2_multiexp_base: config.cost(ExtCosts::bls12381_g2_multiexp_base),
+            bls12381_g2_multiexp_element: config.cost(ExtCosts::bls12381_g2_multiexp_element),
+            bls12381_map_fp_to_g1_base: config.cost(ExtCosts::bls12381_map_fp_to_g1_base),
+            bls12381_map_fp_to_g1_element: config.cost(ExtCosts::bls12381_map_fp_to_g1_element),
+            bls12381_map_fp2_to_g2_base: config.cost(ExtCosts::bls12381_map_fp2_to_g2_base),
+            bls12381_map_fp2_to_g2_element: config.cost(ExtCosts::bls12381_map_fp2_to_g2_element),
+            bls12381_pairing_base: config.cost(ExtCosts::bls12381_pairing_base),
+            bls12381_pairing_element: config.cost(ExtCosts::bls12381_pairing_element),
+            p256_verify_base: config.cost(ExtCosts::p256_verify_base),
+            p256_verify_byte: config.cost(ExtCosts::p256_verify_byte),
+            yield_create_base: config.cost(ExtCosts::yield_create_base),
+            yield_create_byte: config.cost(ExtCosts::yield_create_byte),
+            yield_resume_base: config.cost(ExtCosts::yield_resume_base),
+            yield_resume_byte: config.cost(ExtCosts::yield_resume_byte),
             // removed parameters
             contract_compile_base: 0,
             contract_compile_bytes: 0,
@@ -2616,6 +2989,26 @@ impl From<ExtCostsConfigView> for near_primitives_core::config::ExtCostsConfig {
                 ExtCosts::alt_bn128_g1_sum_element => view.alt_bn128_g1_sum_element,
                 ExtCosts::alt_bn128_pairing_check_base => view.alt_bn128_pairing_check_base,
                 ExtCosts::alt_bn128_pairing_check_element => view.alt_bn128_pairing_check_element,
+                ExtCosts::bls12381_p1_sum_base => view.bls12381_p1_sum_base,
+                ExtCosts::bls12381_p1_sum_element => view.bls12381_p1_sum_element,
+                ExtCosts::bls12381_p2_sum_base => view.bls12381_p2_sum_base,
+                ExtCosts::bls12381_p2_sum_element => view.bls12381_p2_sum_element,
+                ExtCosts::bls12381_g1_multiexp_base => view.bls12381_g1_multiexp_base,
+                ExtCosts::bls12381_g1_multiexp_element => view.bls12381_g1_multiexp_element,
+                ExtCosts::bls12381_g2_multiexp_base => view.bls12381_g2_multiexp_base,
+                ExtCosts::bls12381_g2_multiexp_element => view.bls12381_g2_multiexp_element,
+                ExtCosts::bls12381_map_fp_to_g1_base => view.bls12381_map_fp_to_g1_base,
+                ExtCosts::bls12381_map_fp_to_g1_element => view.bls12381_map_fp_to_g1_element,
+                ExtCosts::bls12381_map_fp2_to_g2_base => view.bls12381_map_fp2_to_g2_base,
+                ExtCosts::bls12381_map_fp2_to_g2_element => view.bls12381_map_fp2_to_g2_element,
+                ExtCosts::bls12381_pairing_base => view.bls12381_pairing_base,
+                ExtCosts::bls12381_pairing_element => view.bls12381_pairing_element,
+                ExtCosts::p256_verify_base => view.p256_verify_base,
+                ExtCosts::p256_verify_byte => view.p256_verify_byte,
+                ExtCosts::yield_create_base => view.yield_create_base,
+                ExtCosts::yield_create_byte => view.yield_create_byte,
+                ExtCosts::yield_resume_base => view.yield_resume_base,
+                ExtCosts::yield_resume_byte => view.yield_resume_byte,
         };
         Self { costs }
     }
@@ -253,6 +253,11 @@ pub enum DBCol {
     /// *Rows*: OutcomeId (CryptoHash) || BlockHash (CryptoHash)
     /// *Column type*: ExecutionOutcomeWithProof
     TransactionResultForBlock,
+    /// Trie nodes touched while applying a chunk, recorded so that the state
+    /// transition can be re-executed and checked without the shard state.
+    /// - *Rows*: BlockShardId (BlockHash || ShardId) - 40 bytes
+    /// - *Column type*: PartialState
+    StateTransitionProofs,
     /// Flat state contents. Used to get `ValueRef` by trie key faster than doing a trie lookup.
     /// - *Rows*: trie key (Vec<u8>)
     /// - *Column type*: ValueRef
@@ -466,6 +471,7 @@ impl DBCol {
             DBCol::HeaderHashesByHeight => &[DBKeyType::BlockHeight],
             DBCol::StateChangesForSplitStates => &[DBKeyType::BlockHash, DBKeyType::ShardId],
             DBCol::TransactionResultForBlock => &[DBKeyType::OutcomeId, DBKeyType::BlockHash],
+            DBCol::StateTransitionProofs => &[DBKeyType::BlockHash, DBKeyType::ShardId],
             #[cfg(feature = "protocol_feature_flat_state")]
             DBCol::FlatState => &[DBKeyType::TrieKey],
             #[cfg(feature = "protocol_feature_flat_state")]
@@ -1,8 +1,9 @@
 use near_primitives::shard_layout::ShardUId;
+pub use near_primitives::types::TrieCacheConfig;
 use std::time::Duration;
 use std::{collections::HashMap, iter::FromIterator};
 
-use crate::trie::DEFAULT_SHARD_CACHE_TOTAL_SIZE_LIMIT;
+use crate::trie::{AdaptiveTrieCacheConfig, DEFAULT_SHARD_CACHE_TOTAL_SIZE_LIMIT};
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 #[serde(default)]
@@ -49,6 +50,13 @@ pub struct StoreConfig {
     pub trie_cache: TrieCacheConfig,
     /// Trie cache configuration per shard for view caches.
     pub view_trie_cache: TrieCacheConfig,
+    /// Adaptive sizing of trie caches.  If enabled, overrides `trie_cache` and
+    /// `view_trie_cache` limits based on observed cache misses.
+    pub adaptive_trie_cache: AdaptiveTrieCacheConfig,
+    /// Memory mapped trie snapshots used to serve view queries.
+    pub trie_view_snapshot: TrieViewSnapshotConfig,
+    /// Where compiled contracts are cached.
+    pub compiled_contract_cache: CompiledContractCacheConfig,
 
     /// Enable fetching account and access key data ahead of time to avoid IO latency.
     pub enable_receipt_prefetching: bool,
@@ -209,6 +217,9 @@ impl Default for StoreConfig {
                 )]),
             },
             view_trie_cache: TrieCacheConfig::default(),
+            adaptive_trie_cache: AdaptiveTrieCacheConfig::default(),
+            trie_view_snapshot: TrieViewSnapshotConfig::default(),
+            compiled_contract_cache: CompiledContractCacheConfig::default(),
 
             enable_receipt_prefetching: true,
             sweat_prefetch_receivers: vec![
@@ -273,21 +284,63 @@ impl Default for MigrationSnapshot {
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 #[serde(default)]
-pub struct TrieCacheConfig {
-    /// Limit the memory consumption of the trie cache per shard.
-    ///
-    /// This is an approximate limit that attempts to factor in data structure
-    /// overhead also. It is supposed to be fairly accurate in the limit.
-    pub default_max_bytes: u64,
-    /// Overwrites `default_max_bytes` for specific shards.
-    pub per_shard_max_bytes: HashMap<ShardUId, u64>,
+pub struct TrieViewSnapshotConfig {
+    /// Whether to materialise tries of tracked shards at the last final block
+    /// into memory mapped files and serve view queries from them.  This
+    /// isolates RPC view load from block processing at the cost of disk space
+    /// for a full copy of the state.
+    pub enabled: bool,
+    /// Directory where snapshots are stored.  If relative, resolved relative
+    /// to neard home directory.
+    pub path: std::path::PathBuf,
+    /// How often snapshots are rebuilt.  Queries at blocks other than the one
+    /// a snapshot was taken at read the trie nodes which changed since then
+    /// from the database.
+    pub refresh_period: Duration,
+}
+
+impl TrieViewSnapshotConfig {
+    /// Returns path to the snapshots directory or `None` if snapshots are
+    /// disabled.
+    pub fn get_path(&self, home_dir: &std::path::Path) -> Option<std::path::PathBuf> {
+        self.enabled.then(|| home_dir.join(&self.path))
+    }
 }
 
-impl Default for TrieCacheConfig {
+impl Default for TrieViewSnapshotConfig {
     fn default() -> Self {
         Self {
-            default_max_bytes: DEFAULT_SHARD_CACHE_TOTAL_SIZE_LIMIT,
-            per_shard_max_bytes: Default::default(),
+            enabled: false,
+            path: std::path::PathBuf::from("view-snapshots"),
+            refresh_period: Duration::from_secs(600),
         }
     }
 }
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct CompiledContractCacheConfig {
+    /// Directory where compiled contracts are cached instead of the database.
+    /// If relative, resolved relative to neard home directory.  The directory
+    /// may be shared by all nodes running on the same host, as long as they
+    /// run the same neard version, and can be populated ahead of time with
+    /// `neard precompile-contracts`.
+    pub path: Option<std::path::PathBuf>,
+    /// Maximum total size of compiled contracts stored in `path`.  Least
+    /// recently used contracts are removed when exceeded.
+    pub max_size: bytesize::ByteSize,
+}
+
+impl CompiledContractCacheConfig {
+    /// Returns path to the cache directory or `None` if contracts are cached
+    /// in the database.
+    pub fn get_path(&self, home_dir: &std::path::Path) -> Option<std::path::PathBuf> {
+        self.path.as_ref().map(|path| home_dir.join(path))
+    }
+}
+
+impl Default for CompiledContractCacheConfig {
+    fn default() -> Self {
+        Self { path: None, max_size: bytesize::ByteSize::gib(16) }
+    }
+}
@@ -22,7 +22,7 @@ use near_primitives::account::{AccessKey, Account};
 use near_primitives::contract::ContractCode;
 pub use near_primitives::errors::StorageError;
 use near_primitives::hash::CryptoHash;
-use near_primitives::receipt::{DelayedReceiptIndices, Receipt, ReceivedData};
+use near_primitives::receipt::{DelayedReceiptIndices, PromiseYieldIndices, Receipt, ReceivedData};
 pub use near_primitives::shard_layout::ShardUId;
 use near_primitives::trie_key::{trie_key_parsers, TrieKey};
 use near_primitives::types::{AccountId, CompiledContract, CompiledContractCache, StateRoot};
@@ -34,10 +34,10 @@ use crate::db::{
 pub use crate::trie::iterator::{TrieIterator, TrieTraversalItem};
 pub use crate::trie::update::{TrieUpdate, TrieUpdateIterator, TrieUpdateValuePtr};
 pub use crate::trie::{
-    estimator, split_state, ApplyStatePartResult, KeyForStateChanges, KeyLookupMode, NibbleSlice,
-    PartialStorage, PrefetchApi, PrefetchError, RawTrieNode, RawTrieNodeWithSize, ShardTries, Trie,
-    TrieAccess, TrieCache, TrieCachingStorage, TrieChanges, TrieConfig, TrieDBStorage, TrieStorage,
-    WrappedTrieChanges,
+    estimator, split_state, AdaptiveTrieCacheConfig, ApplyStatePartResult, KeyForStateChanges,
+    KeyLookupMode, NibbleSlice, PartialStorage, PrefetchApi, PrefetchError, RawTrieNode,
+    RawTrieNodeWithSize, ShardTries, Trie, TrieAccess, TrieCache, TrieCachingStorage, TrieChanges,
+    TrieConfig, TrieDBStorage, TrieStorage, WrappedTrieChanges,
 };
 pub use flat_state::FlatStateDelta;
 
@@ -54,7 +54,7 @@ mod sync_utils;
 pub mod test_utils;
 mod trie;
 
-pub use crate::config::{Mode, StoreConfig};
+pub use crate::config::{CompiledContractCacheConfig, Mode, StoreConfig};
 pub use crate::metrics::{flat_state_metrics, FLAT_STORAGE_HEAD_HEIGHT};
 pub use crate::opener::{StoreMigrator, StoreOpener, StoreOpenerError};
 
@@ -767,6 +767,12 @@ pub fn get_delayed_receipt_indices(
     Ok(get(trie, &TrieKey::DelayedReceiptIndices)?.unwrap_or_default())
 }
 
+pub fn get_promise_yield_indices(
+    trie: &dyn TrieAccess,
+) -> Result<PromiseYieldIndices, StorageError> {
+    Ok(get(trie, &TrieKey::PromiseYieldIndices)?.unwrap_or_default())
+}
+
 pub fn set_access_key(
     state_update: &mut TrieUpdate,
     account_id: AccountId,
@@ -78,6 +78,15 @@ pub static SHARD_CACHE_CURRENT_TOTAL_SIZE: Lazy<IntGaugeVec> = Lazy::new(|| {
     .unwrap()
 });
 
+pub static SHARD_CACHE_TOTAL_SIZE_LIMIT: Lazy<IntGaugeVec> = Lazy::new(|| {
+    try_create_int_gauge_vec(
+        "near_shard_cache_total_size_limit",
+        "Shard cache upper bound for the total size",
+        &["shard_id", "is_view"],
+    )
+    .unwrap()
+});
+
 pub static SHARD_CACHE_POP_HITS: Lazy<IntCounterVec> = Lazy::new(|| {
     try_create_int_counter_vec(
         "near_shard_cache_pop_hits",
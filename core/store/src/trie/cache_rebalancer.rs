@@ -0,0 +1,203 @@
+use crate::trie::config::AdaptiveTrieCacheConfig;
+use crate::trie::trie_storage::TrieCache;
+use std::time::Instant;
+
+/// Redistributes memory between shard caches in the adaptive mode.
+///
+/// Each cache gets `min_bytes_per_cache` and the rest of the budget is split
+/// proportionally to the cost of misses observed since the last rebalancing,
+/// where a miss of a view cache costs `view_miss_cost` of a normal one.  The
+/// misses are further weighted by the miss rate, i.e. one minus the hit rate,
+/// since a cache which already serves most lookups gains little from more
+/// memory.  To avoid oscillations, new limits move only halfway towards that
+/// target.
+pub(crate) struct TrieCacheRebalancer {
+    config: AdaptiveTrieCacheConfig,
+    last_rebalance: Instant,
+}
+
+impl TrieCacheRebalancer {
+    pub(crate) fn new(config: AdaptiveTrieCacheConfig) -> Self {
+        Self { config, last_rebalance: Instant::now() }
+    }
+
+    pub(crate) fn config(&self) -> AdaptiveTrieCacheConfig {
+        self.config
+    }
+
+    pub(crate) fn update_config(&mut self, config: AdaptiveTrieCacheConfig) {
+        if self.config != config {
+            tracing::info!(target: "store", ?config, "Updated adaptive trie cache config");
+            self.config = config;
+        }
+    }
+
+    /// Returns the config to rebalance the caches with if adaptive mode is
+    /// enabled and enough time has passed since the previous rebalancing.
+    pub(crate) fn start_rebalance(&mut self) -> Option<AdaptiveTrieCacheConfig> {
+        if !self.config.enabled || self.last_rebalance.elapsed() < self.config.rebalance_period {
+            return None;
+        }
+        self.last_rebalance = Instant::now();
+        Some(self.config)
+    }
+}
+
+/// Sets memory limits of the caches according to misses they observed since
+/// the previous rebalancing.
+///
+/// Each cache is locked on its own and only to take its statistics and later
+/// to set its new limit, so lookups aren’t blocked on all caches at once.
+pub(crate) fn rebalance(config: &AdaptiveTrieCacheConfig, caches: &[TrieCache]) {
+    let stats: Vec<_> = caches
+        .iter()
+        .map(|cache| {
+            let mut guard = cache.lock();
+            let (hits, misses) = guard.take_access_counts();
+            let miss_cost = if guard.is_view() { config.view_miss_cost } else { 1.0 };
+            (guard.total_size_limit(), weighted_miss_cost(hits, misses, miss_cost))
+        })
+        .collect();
+    let limits = compute_limits(config, &stats);
+    for (cache, limit) in caches.iter().zip(limits) {
+        cache.lock().set_total_size_limit(limit);
+    }
+    tracing::debug!(target: "store", num_caches = caches.len(), "Rebalanced trie caches");
+}
+
+/// Returns the cost of `misses` weighted by the miss rate of the cache.
+fn weighted_miss_cost(hits: u64, misses: u64, miss_cost: f64) -> f64 {
+    let accesses = hits + misses;
+    if accesses == 0 {
+        return 0.0;
+    }
+    let hit_rate = hits as f64 / accesses as f64;
+    misses as f64 * (1.0 - hit_rate) * miss_cost
+}
+
+/// Computes new memory limits given current limit and miss cost of each cache.
+fn compute_limits(config: &AdaptiveTrieCacheConfig, caches: &[(u64, f64)]) -> Vec<u64> {
+    let num_caches = caches.len() as u64;
+    if num_caches == 0 {
+        return vec![];
+    }
+    let budget = config.total_max_bytes.max(num_caches);
+    let min_bytes = config.min_bytes_per_cache.clamp(1, budget / num_caches);
+    let spare = budget - min_bytes * num_caches;
+    let total_cost: f64 = caches.iter().map(|(_, cost)| cost).sum();
+
+    // Limits may come from the static configuration which doesn’t respect the
+    // budget.  In that case start from an even split.
+    let current_total: u64 = caches.iter().map(|(limit, _)| limit).sum();
+    let even_share = budget / num_caches;
+    let current = |limit: u64| if current_total > budget { even_share } else { limit };
+
+    caches
+        .iter()
+        .map(|&(limit, cost)| {
+            let current = current(limit);
+            if total_cost <= 0.0 {
+                return current.max(min_bytes);
+            }
+            let target = min_bytes + (spare as f64 * cost / total_cost) as u64;
+            (current / 2 + target / 2).max(min_bytes)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compute_limits, rebalance, weighted_miss_cost};
+    use crate::trie::config::AdaptiveTrieCacheConfig;
+    use crate::trie::trie_storage::TrieCache;
+    use crate::TrieConfig;
+    use near_primitives::hash::hash;
+    use near_primitives::shard_layout::ShardUId;
+
+    fn config(total_max_bytes: u64, min_bytes_per_cache: u64) -> AdaptiveTrieCacheConfig {
+        AdaptiveTrieCacheConfig {
+            enabled: true,
+            total_max_bytes,
+            min_bytes_per_cache,
+            ..AdaptiveTrieCacheConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_compute_limits() {
+        let config = config(1000, 100);
+        assert_eq!(compute_limits(&config, &[]), Vec::<u64>::new());
+        // Over budget, no misses: even split.
+        assert_eq!(compute_limits(&config, &[(1000, 0.0), (1000, 0.0)]), vec![500, 500]);
+        // Within budget, no misses: limits are kept.
+        assert_eq!(compute_limits(&config, &[(300, 0.0), (600, 0.0)]), vec![300, 600]);
+        // All misses in the first cache: it moves halfway to 900 bytes.
+        assert_eq!(compute_limits(&config, &[(500, 10.0), (500, 0.0)]), vec![700, 300]);
+        // Limits converge to the target and never exceed the budget.
+        let mut caches = vec![(500, 3.0), (250, 1.0), (250, 0.0)];
+        for _ in 0..20 {
+            let limits = compute_limits(&config, &caches);
+            assert!(limits.iter().sum::<u64>() <= 1000);
+            assert!(limits.iter().all(|limit| *limit >= 100));
+            caches =
+                limits.into_iter().zip(caches).map(|(limit, (_, cost))| (limit, cost)).collect();
+        }
+        assert_eq!(caches.iter().map(|(limit, _)| *limit).collect::<Vec<_>>(), vec![699, 299, 100]);
+    }
+
+    #[test]
+    fn test_weighted_miss_cost() {
+        assert_eq!(weighted_miss_cost(0, 0, 1.0), 0.0);
+        assert_eq!(weighted_miss_cost(0, 100, 1.0), 100.0);
+        assert_eq!(weighted_miss_cost(0, 100, 0.5), 50.0);
+        // Same number of misses, but the cache already serves most lookups.
+        assert_eq!(weighted_miss_cost(300, 100, 1.0), 25.0);
+        assert_eq!(weighted_miss_cost(100, 0, 1.0), 0.0);
+    }
+
+    #[test]
+    fn test_config_serialization() {
+        let config = AdaptiveTrieCacheConfig::default();
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["rebalance_period"], "1m");
+        let config: AdaptiveTrieCacheConfig =
+            serde_json::from_str(r#"{"enabled": true, "rebalance_period": "2m 30s"}"#).unwrap();
+        assert!(config.enabled);
+        assert_eq!(config.rebalance_period, std::time::Duration::from_secs(150));
+    }
+
+    #[test]
+    fn test_rebalance() {
+        let trie_config = TrieConfig::default();
+        let hot = TrieCache::new(&trie_config, ShardUId::single_shard(), false);
+        let cold = TrieCache::new(&trie_config, ShardUId { version: 0, shard_id: 1 }, false);
+        let view = TrieCache::new(&trie_config, ShardUId::single_shard(), true);
+        let warm = TrieCache::new(&trie_config, ShardUId { version: 0, shard_id: 2 }, false);
+        for i in 0..100u64 {
+            let value: std::sync::Arc<[u8]> = vec![0; 100].into();
+            cold.lock().put(hash(&i.to_le_bytes()), value);
+            hot.lock().record_access(false);
+            view.lock().record_access(false);
+            warm.lock().record_access(false);
+            for _ in 0..9 {
+                warm.lock().record_access(true);
+            }
+        }
+        cold.lock().record_access(true);
+
+        let caches = [hot.clone(), cold.clone(), view.clone(), warm.clone()];
+        rebalance(&config(40_000, 5_000), &caches);
+        let hot_limit = hot.lock().total_size_limit();
+        let cold_limit = cold.lock().total_size_limit();
+        let view_limit = view.lock().total_size_limit();
+        let warm_limit = warm.lock().total_size_limit();
+        assert!(hot_limit > view_limit, "{hot_limit} <= {view_limit}");
+        // As many misses as the hot cache, but at a 90% hit rate.
+        assert!(hot_limit > warm_limit, "{hot_limit} <= {warm_limit}");
+        assert!(view_limit > cold_limit, "{view_limit} <= {cold_limit}");
+        assert!(hot_limit + cold_limit + view_limit + warm_limit <= 40_000);
+        // Cold cache had to evict entries to fit in its new limit.
+        assert!(cold.lock().current_total_size() <= cold_limit);
+        assert_eq!(hot.lock().take_access_counts(), (0, 0));
+    }
+}
@@ -1,8 +1,11 @@
 use crate::config::TrieCacheConfig;
 use crate::trie::trie_storage::TrieCacheInner;
 use crate::StoreConfig;
+pub use near_chain_configs::AdaptiveTrieCacheConfig;
 use near_primitives::types::AccountId;
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::time::Duration;
 use tracing::{error, warn};
 
 /// Default memory limit, if nothing else is configured.
@@ -25,7 +28,16 @@ const TRIE_LIMIT_CACHED_VALUE_SIZE: usize = 1000;
 pub struct TrieConfig {
     pub shard_cache_config: TrieCacheConfig,
     pub view_shard_cache_config: TrieCacheConfig,
+    /// Initial configuration of adaptive cache sizing.  Can later be changed
+    /// with `ShardTries::update_adaptive_cache_config`.
+    pub adaptive_cache_config: AdaptiveTrieCacheConfig,
     pub enable_receipt_prefetching: bool,
+    /// Directory with memory mapped trie snapshots used by view queries or
+    /// `None` if snapshots are disabled.  Not set by `from_store_config` since
+    /// the path is resolved relative to neard home directory.
+    pub view_snapshot_dir: Option<PathBuf>,
+    /// How often view trie snapshots are rebuilt.
+    pub view_snapshot_refresh_period: Duration,
 
     /// Configured accounts will be prefetched as SWEAT token account, if predecessor is listed as sender.
     pub sweat_prefetch_receivers: Vec<AccountId>,
@@ -48,6 +60,8 @@ impl TrieConfig {
 
         this.shard_cache_config = config.trie_cache.clone();
         this.view_shard_cache_config = config.view_trie_cache.clone();
+        this.adaptive_cache_config = config.adaptive_trie_cache;
+        this.view_snapshot_refresh_period = config.trie_view_snapshot.refresh_period;
 
         this.enable_receipt_prefetching = config.enable_receipt_prefetching;
         for account in &config.sweat_prefetch_receivers {
@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use std::fmt::Write;
 use std::io::Read;
 use std::str;
+use std::sync::Arc;
 
 use borsh::{BorshDeserialize, BorshSerialize};
 use byteorder::{LittleEndian, ReadBytesExt};
@@ -19,18 +20,21 @@ use near_primitives::trie_key::TrieKey;
 use near_primitives::types::{StateRoot, StateRootNode};
 
 use crate::flat_state::FlatState;
-pub use crate::trie::config::TrieConfig;
 pub(crate) use crate::trie::config::DEFAULT_SHARD_CACHE_TOTAL_SIZE_LIMIT;
+pub use crate::trie::config::{AdaptiveTrieCacheConfig, TrieConfig};
 use crate::trie::insert_delete::NodesStorage;
 use crate::trie::iterator::TrieIterator;
 pub use crate::trie::nibble_slice::NibbleSlice;
 pub use crate::trie::prefetching_trie_storage::{PrefetchApi, PrefetchError};
 pub use crate::trie::shard_tries::{KeyForStateChanges, ShardTries, WrappedTrieChanges};
 pub use crate::trie::trie_storage::{TrieCache, TrieCachingStorage, TrieDBStorage, TrieStorage};
-use crate::trie::trie_storage::{TrieMemoryPartialStorage, TrieRecordingStorage};
+use crate::trie::trie_storage::{
+    TrieChunkCache, TrieMemoryPartialStorage, TrieOverlayStorage, TrieRecordingStorage,
+};
 use crate::{FlatStateDelta, StorageError};
 pub use near_primitives::types::TrieNodesCount;
 
+mod cache_rebalancer;
 mod config;
 mod insert_delete;
 pub mod iterator;
@@ -43,6 +47,7 @@ mod trie_storage;
 #[cfg(test)]
 mod trie_tests;
 pub mod update;
+mod view_snapshot;
 
 const POISONED_LOCK_ERR: &str = "The lock was poisoned.";
 
@@ -577,17 +582,29 @@ impl Trie {
         Trie { storage, root, flat_state }
     }
 
+    /// Returns a trie at the same root which reads nodes directly from DB and
+    /// records them.
     pub fn recording_reads(&self) -> Self {
         let storage =
             self.storage.as_caching_storage().expect("Storage should be TrieCachingStorage");
         let storage = TrieRecordingStorage {
-            store: storage.store.clone(),
-            shard_uid: storage.shard_uid,
+            storage: Box::new(TrieDBStorage::new(storage.store.clone(), storage.shard_uid)),
             recorded: RefCell::new(Default::default()),
         };
         Trie { storage: Box::new(storage), root: self.root.clone(), flat_state: None }
     }
 
+    /// Records nodes read by this trie without changing how they are read:
+    /// the storage, its caches and flat state stay the same, so that the
+    /// recording doesn't affect gas charged for the reads.
+    pub fn into_recording(self) -> Self {
+        let storage = TrieRecordingStorage {
+            storage: self.storage,
+            recorded: RefCell::new(Default::default()),
+        };
+        Trie { storage: Box::new(storage), root: self.root, flat_state: self.flat_state }
+    }
+
     pub fn recorded_storage(&self) -> Option<PartialStorage> {
         let storage = self.storage.as_recording_storage()?;
         let mut nodes: Vec<_> =
@@ -602,10 +619,24 @@ impl Trie {
         let storage = Box::new(TrieMemoryPartialStorage {
             recorded_storage,
             visited_nodes: Default::default(),
+            chunk_cache: TrieChunkCache::new(),
         });
         Self::new(storage, root, None)
     }
 
+    /// Returns a trie at `root` which reads nodes and values from `overlay` before falling back
+    /// to the storage of this trie.  The overlay is filled with insertions of `TrieChanges` which
+    /// weren't written to DB, so several state transitions can be applied in a row in memory.
+    pub fn with_overlay(
+        self,
+        overlay: Arc<HashMap<CryptoHash, Arc<[u8]>>>,
+        root: StateRoot,
+    ) -> Self {
+        let storage =
+            TrieOverlayStorage { base: self.storage, overlay, overlay_reads: Default::default() };
+        Self::new(Box::new(storage), root, None)
+    }
+
     pub fn get_root(&self) -> &StateRoot {
         &self.root
     }
@@ -1523,6 +1554,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_trie_with_overlay() {
+        let tries = create_tries();
+        let changes = vec![
+            (b"doge".to_vec(), Some(b"coin".to_vec())),
+            (b"docu".to_vec(), Some(b"value".to_vec())),
+        ];
+        let root = test_populate_trie(&tries, &Trie::EMPTY_ROOT, ShardUId::single_shard(), changes);
+
+        // Changes are not applied to the store, only kept in the overlay.
+        let trie_changes = tries
+            .get_trie_for_shard(ShardUId::single_shard(), root)
+            .update(vec![(b"dog".to_vec(), Some(b"puppy".to_vec())), (b"doge".to_vec(), None)])
+            .unwrap();
+        let overlay: HashMap<_, _> = trie_changes
+            .insertions()
+            .iter()
+            .map(|change| (*change.hash(), Arc::from(change.payload())))
+            .collect();
+        let trie = tries
+            .get_trie_for_shard(ShardUId::single_shard(), root)
+            .with_overlay(Arc::new(overlay), trie_changes.new_root);
+        assert_eq!(trie.get(b"dog"), Ok(Some(b"puppy".to_vec())));
+        assert_eq!(trie.get(b"docu"), Ok(Some(b"value".to_vec())));
+        assert_eq!(trie.get(b"doge"), Ok(None));
+
+        let trie = tries.get_trie_for_shard(ShardUId::single_shard(), trie_changes.new_root);
+        assert!(trie.get(b"dog").is_err());
+    }
+
     #[test]
     fn test_dump_load_trie() {
         let store = create_test_store();
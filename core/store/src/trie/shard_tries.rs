@@ -1,6 +1,9 @@
 use std::io;
+use std::path::Path;
 use std::rc::Rc;
-use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
 
 use borsh::BorshSerialize;
 use near_primitives::borsh::maybestd::collections::HashMap;
@@ -11,10 +14,13 @@ use near_primitives::types::{
     NumShards, RawStateChange, RawStateChangesWithTrieKey, StateChangeCause, StateRoot,
 };
 
+use crate::config::TrieCacheConfig;
 use crate::flat_state::FlatStateFactory;
-use crate::trie::config::TrieConfig;
+use crate::trie::cache_rebalancer::{self, TrieCacheRebalancer};
+use crate::trie::config::{AdaptiveTrieCacheConfig, TrieConfig};
 use crate::trie::prefetching_trie_storage::PrefetchingThreadsHandle;
-use crate::trie::trie_storage::{TrieCache, TrieCachingStorage};
+use crate::trie::trie_storage::{TrieCache, TrieCachingStorage, TrieDBStorage};
+use crate::trie::view_snapshot::{TrieViewSnapshot, TrieViewSnapshotStorage};
 use crate::trie::{TrieRefcountChange, POISONED_LOCK_ERR};
 use crate::{metrics, DBCol, DBOp, DBTransaction, PrefetchApi};
 use crate::{Store, StoreUpdate, Trie, TrieChanges, TrieUpdate};
@@ -29,6 +35,19 @@ struct ShardTriesInner {
     flat_state_factory: FlatStateFactory,
     /// Prefetcher state, such as IO threads, per shard.
     prefetchers: RwLock<HashMap<ShardUId, (PrefetchApi, PrefetchingThreadsHandle)>>,
+    /// Memory limits of normal caches.  Initialized from `trie_config` and
+    /// can be changed while the node is running.
+    cache_config: RwLock<TrieCacheConfig>,
+    /// Memory limits of view caches, see `cache_config`.
+    view_cache_config: RwLock<TrieCacheConfig>,
+    /// Redistributes memory between caches if adaptive cache sizing is enabled.
+    cache_rebalancer: Mutex<TrieCacheRebalancer>,
+    /// Memory mapped trie snapshots serving view queries at their state roots.
+    view_snapshots: RwLock<HashMap<ShardUId, Arc<TrieViewSnapshot>>>,
+    /// When view snapshots refresh was last started.
+    view_snapshots_refreshed_at: Mutex<Option<Instant>>,
+    /// Set while view snapshots are being built in a background thread.
+    view_snapshots_building: AtomicBool,
 }
 
 #[derive(Clone)]
@@ -43,13 +62,23 @@ impl ShardTries {
     ) -> Self {
         let caches = Self::create_initial_caches(&trie_config, &shard_uids, false);
         let view_caches = Self::create_initial_caches(&trie_config, &shard_uids, true);
+        let cache_rebalancer = TrieCacheRebalancer::new(trie_config.adaptive_cache_config);
+        let view_snapshots = Self::open_view_snapshots(&trie_config, shard_uids);
+        let cache_config = RwLock::new(trie_config.shard_cache_config.clone());
+        let view_cache_config = RwLock::new(trie_config.view_shard_cache_config.clone());
         ShardTries(Arc::new(ShardTriesInner {
             store: store.clone(),
             trie_config,
+            cache_config,
+            view_cache_config,
             caches: RwLock::new(caches),
             view_caches: RwLock::new(view_caches),
             flat_state_factory,
             prefetchers: Default::default(),
+            cache_rebalancer: Mutex::new(cache_rebalancer),
+            view_snapshots: RwLock::new(view_snapshots),
+            view_snapshots_refreshed_at: Mutex::new(None),
+            view_snapshots_building: AtomicBool::new(false),
         }))
     }
 
@@ -86,6 +115,50 @@ impl ShardTries {
             .collect()
     }
 
+    /// Opens view snapshots left over from a previous run.  Nodes missing from
+    /// them are read from the database so stale snapshots are harmless.
+    fn open_view_snapshots(
+        config: &TrieConfig,
+        shard_uids: &[ShardUId],
+    ) -> HashMap<ShardUId, Arc<TrieViewSnapshot>> {
+        let dir = match &config.view_snapshot_dir {
+            Some(dir) => dir,
+            None => return HashMap::new(),
+        };
+        let mut snapshots = HashMap::new();
+        for &shard_uid in shard_uids {
+            let path = TrieViewSnapshot::file_path(dir, shard_uid);
+            match TrieViewSnapshot::open(&path) {
+                Ok(snapshot) if snapshot.shard_uid() == shard_uid => {
+                    snapshots.insert(shard_uid, Arc::new(snapshot));
+                }
+                Ok(_) => {
+                    let path = path.display();
+                    tracing::warn!(target: "store", %path, "View trie snapshot of another shard")
+                }
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => {
+                    tracing::warn!(target: "store", %err, "Failed to open view trie snapshot")
+                }
+            }
+        }
+        snapshots
+    }
+
+    fn cache_config(&self, is_view: bool) -> &RwLock<TrieCacheConfig> {
+        if is_view {
+            &self.0.view_cache_config
+        } else {
+            &self.0.cache_config
+        }
+    }
+
+    /// Creates a cache for a shard which didn't have one yet.
+    fn new_cache(&self, shard_uid: ShardUId, is_view: bool) -> TrieCache {
+        let cache_config = self.cache_config(is_view).read().expect(POISONED_LOCK_ERR);
+        TrieCache::with_cache_config(&self.0.trie_config, &cache_config, shard_uid, is_view)
+    }
+
     pub(crate) fn is_same(&self, other: &Self) -> bool {
         Arc::ptr_eq(&self.0, &other.0)
     }
@@ -109,10 +182,7 @@ impl ShardTries {
         let caches_to_use = if is_view { &self.0.view_caches } else { &self.0.caches };
         let cache = {
             let mut caches = caches_to_use.write().expect(POISONED_LOCK_ERR);
-            caches
-                .entry(shard_uid)
-                .or_insert_with(|| TrieCache::new(&self.0.trie_config, shard_uid, is_view))
-                .clone()
+            caches.entry(shard_uid).or_insert_with(|| self.new_cache(shard_uid, is_view)).clone()
         };
         // Do not enable prefetching on view caches.
         // 1) Performance of view calls is not crucial.
@@ -148,6 +218,18 @@ impl ShardTries {
             is_view,
             prefetch_api,
         ));
+        if is_view {
+            if let Some(snapshot) = self.get_view_snapshot(shard_uid) {
+                // Flat state is bypassed as well since it lives in the
+                // database which the snapshot is meant to keep view queries
+                // away from.
+                return Trie::new(
+                    Box::new(TrieViewSnapshotStorage::new(snapshot, storage)),
+                    state_root,
+                    None,
+                );
+            }
+        }
         let flat_state = self.0.flat_state_factory.new_flat_state_for_shard(
             shard_uid.shard_id(),
             block_hash,
@@ -186,7 +268,149 @@ impl ShardTries {
         &self.0.store.storage
     }
 
+    /// Returns the currently used adaptive cache sizing configuration.
+    pub fn adaptive_cache_config(&self) -> AdaptiveTrieCacheConfig {
+        self.0.cache_rebalancer.lock().expect(POISONED_LOCK_ERR).config()
+    }
+
+    /// Changes the adaptive cache sizing configuration while the node is
+    /// running.  New config takes effect at the next rebalancing.
+    pub fn update_adaptive_cache_config(&self, config: AdaptiveTrieCacheConfig) {
+        self.0.cache_rebalancer.lock().expect(POISONED_LOCK_ERR).update_config(config);
+    }
+
+    /// Changes memory limits of normal and view caches while the node is
+    /// running.  Limits of existing caches are adjusted right away unless
+    /// adaptive cache sizing is enabled, in which case it keeps managing them.
+    pub fn update_cache_config(
+        &self,
+        cache_config: TrieCacheConfig,
+        view_cache_config: TrieCacheConfig,
+    ) {
+        let adaptive = self.adaptive_cache_config().enabled;
+        for (is_view, new_config) in [(false, cache_config), (true, view_cache_config)] {
+            {
+                let mut config = self.cache_config(is_view).write().expect(POISONED_LOCK_ERR);
+                if *config == new_config {
+                    continue;
+                }
+                tracing::info!(target: "store", is_view, config = ?new_config, "Updated trie cache config");
+                *config = new_config.clone();
+            }
+            if adaptive {
+                continue;
+            }
+            // Caches created in the meantime already got the new limits.
+            let caches = if is_view { &self.0.view_caches } else { &self.0.caches };
+            for (&shard_uid, cache) in caches.read().expect(POISONED_LOCK_ERR).iter() {
+                cache.lock().set_total_size_limit(TrieCache::max_bytes(&new_config, shard_uid));
+            }
+        }
+    }
+
+    /// Redistributes memory between all normal and view caches if adaptive
+    /// cache sizing is enabled and it’s time to do so.
+    fn maybe_rebalance_caches(&self) {
+        let config =
+            match self.0.cache_rebalancer.lock().expect(POISONED_LOCK_ERR).start_rebalance() {
+                Some(config) => config,
+                None => return,
+            };
+        let all_caches: Vec<TrieCache> = {
+            let caches = self.0.caches.read().expect(POISONED_LOCK_ERR);
+            let view_caches = self.0.view_caches.read().expect(POISONED_LOCK_ERR);
+            caches.values().chain(view_caches.values()).cloned().collect()
+        };
+        cache_rebalancer::rebalance(&config, &all_caches);
+    }
+
+    /// Returns the latest view snapshot of given shard.
+    fn get_view_snapshot(&self, shard_uid: ShardUId) -> Option<Arc<TrieViewSnapshot>> {
+        let snapshots = self.0.view_snapshots.read().expect(POISONED_LOCK_ERR);
+        snapshots.get(&shard_uid).cloned()
+    }
+
+    /// Returns whether view snapshots are enabled, not being built at the
+    /// moment and weren’t refreshed within the configured period.
+    pub fn view_snapshots_refresh_due(&self) -> bool {
+        if self.0.trie_config.view_snapshot_dir.is_none()
+            || self.0.view_snapshots_building.load(Ordering::Acquire)
+        {
+            return false;
+        }
+        let refreshed_at = self.0.view_snapshots_refreshed_at.lock().expect(POISONED_LOCK_ERR);
+        refreshed_at.map_or(true, |refreshed_at| {
+            refreshed_at.elapsed() >= self.0.trie_config.view_snapshot_refresh_period
+        })
+    }
+
+    /// Rebuilds view snapshots of given shards at given state roots in
+    /// a background thread.  State roots should belong to a final block so
+    /// that the state isn’t garbage collected while the snapshot is built.
+    /// Does nothing if snapshots are disabled or already being built.
+    pub fn refresh_view_snapshots(&self, state_roots: Vec<(ShardUId, StateRoot)>) {
+        let dir = match &self.0.trie_config.view_snapshot_dir {
+            Some(dir) => dir.clone(),
+            None => return,
+        };
+        if self.0.view_snapshots_building.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        *self.0.view_snapshots_refreshed_at.lock().expect(POISONED_LOCK_ERR) = Some(Instant::now());
+        let tries = self.clone();
+        let result =
+            std::thread::Builder::new().name("view_snapshots".to_string()).spawn(move || {
+                tries.build_view_snapshots(&dir, state_roots);
+                tries.0.view_snapshots_building.store(false, Ordering::Release);
+            });
+        if let Err(err) = result {
+            tracing::error!(target: "store", %err, "Failed to spawn view trie snapshots thread");
+            self.0.view_snapshots_building.store(false, Ordering::Release);
+        }
+    }
+
+    /// Builds view snapshots which aren’t at requested state roots yet and
+    /// makes them available to view queries.
+    pub(crate) fn build_view_snapshots(&self, dir: &Path, state_roots: Vec<(ShardUId, StateRoot)>) {
+        if let Err(err) = std::fs::create_dir_all(dir) {
+            let path = dir.display();
+            tracing::error!(target: "store", %err, %path, "Failed to create view snapshots directory");
+            return;
+        }
+        for (shard_uid, state_root) in state_roots {
+            let snapshot = self.get_view_snapshot(shard_uid);
+            if snapshot.map_or(false, |snapshot| snapshot.state_root() == &state_root) {
+                continue;
+            }
+            let start = Instant::now();
+            // Read directly from the database so that building the snapshot
+            // doesn’t evict nodes used by block processing from the caches.
+            let storage = TrieDBStorage::new(self.0.store.clone(), shard_uid);
+            let trie = Trie::new(Box::new(storage), state_root, None);
+            let path = TrieViewSnapshot::file_path(dir, shard_uid);
+            let snapshot = TrieViewSnapshot::create(&trie, shard_uid, &path)
+                .and_then(|()| TrieViewSnapshot::open(&path));
+            match snapshot {
+                Ok(snapshot) => {
+                    let elapsed = start.elapsed();
+                    tracing::info!(target: "store", ?shard_uid, %state_root, ?elapsed, "Created view trie snapshot");
+                    let mut snapshots = self.0.view_snapshots.write().expect(POISONED_LOCK_ERR);
+                    snapshots.insert(shard_uid, Arc::new(snapshot));
+                }
+                Err(err) => {
+                    tracing::warn!(target: "store", ?shard_uid, %err, "Failed to create view trie snapshot");
+                }
+            }
+        }
+    }
+
     pub(crate) fn update_cache(&self, transaction: &DBTransaction) -> std::io::Result<()> {
+        self.update_cache_inner(transaction)?;
+        self.maybe_rebalance_caches();
+        Ok(())
+    }
+
+    fn update_cache_inner(&self, transaction: &DBTransaction) -> std::io::Result<()> {
         let mut caches = self.0.caches.write().expect(POISONED_LOCK_ERR);
         let mut shards = HashMap::new();
         for op in &transaction.ops {
@@ -218,10 +442,8 @@ impl ShardTries {
             }
         }
         for (shard_uid, ops) in shards {
-            let cache = caches
-                .entry(shard_uid)
-                .or_insert_with(|| TrieCache::new(&self.0.trie_config, shard_uid, false))
-                .clone();
+            let cache =
+                caches.entry(shard_uid).or_insert_with(|| self.new_cache(shard_uid, false)).clone();
             cache.update_cache(ops);
         }
         Ok(())
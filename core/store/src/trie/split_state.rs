@@ -10,7 +10,7 @@ use near_primitives::receipt::Receipt;
 use near_primitives::shard_layout::ShardUId;
 use near_primitives::state_part::PartId;
 use near_primitives::trie_key::trie_key_parsers::parse_account_id_from_raw_key;
-use near_primitives::trie_key::TrieKey;
+use near_primitives::trie_key::{col, TrieKey};
 use near_primitives::types::{
     ConsolidatedStateChange, StateChangeCause, StateChangesForSplitStates, StateRoot,
 };
@@ -52,6 +52,15 @@ impl ShardTries {
         for ConsolidatedStateChange { trie_key, value } in changes.changes {
             match &trie_key {
                 TrieKey::DelayedReceiptIndices => {}
+                // The promise yield timeout queue is kept in full by every new shard.
+                TrieKey::PromiseYieldIndices | TrieKey::PromiseYieldTimeout { .. } => {
+                    for trie_update in trie_updates.values_mut() {
+                        match &value {
+                            Some(value) => trie_update.set(trie_key.clone(), value.clone()),
+                            None => trie_update.remove(trie_key.clone()),
+                        }
+                    }
+                }
                 TrieKey::DelayedReceipt { index } => match value {
                     Some(value) => {
                         let receipt = Receipt::try_from_slice(&value).map_err(|err| {
@@ -72,6 +81,7 @@ impl ShardTries {
                 | TrieKey::PostponedReceiptId { receiver_id: account_id, .. }
                 | TrieKey::PendingDataCount { receiver_id: account_id, .. }
                 | TrieKey::PostponedReceipt { receiver_id: account_id, .. }
+                | TrieKey::PromiseYieldReceipt { receiver_id: account_id, .. }
                 | TrieKey::ContractData { account_id, .. } => {
                     let new_shard_uid = account_id_to_shard_id(account_id);
                     // we can safely unwrap here because the caller of this function guarantees trie_updates contains all shard_uids for the new shards
@@ -112,6 +122,7 @@ impl ShardTries {
     /// The caller must guarantee that `state_roots` contains all shard_ids
     /// that `key_to_shard_id` that may return
     /// Ignore changes on DelayedReceipts or DelayedReceiptsIndices
+    /// The promise yield timeout queue is copied to all new shards
     /// Returns `store_update` and the new state_roots for split states
     pub fn add_values_to_split_states(
         &self,
@@ -120,6 +131,14 @@ impl ShardTries {
         account_id_to_shard_id: &dyn Fn(&AccountId) -> ShardUId,
     ) -> Result<(StoreUpdate, HashMap<ShardUId, StateRoot>), StorageError> {
         self.add_values_to_split_states_impl(state_roots, values, &|raw_key| {
+            // The promise yield timeout queue is not keyed by account, so every new shard gets a
+            // copy of it. Timeouts of promises yielded on other shards are skipped when resolved.
+            if matches!(
+                raw_key.first(),
+                Some(&col::PROMISE_YIELD_INDICES | &col::PROMISE_YIELD_TIMEOUT)
+            ) {
+                return Ok(state_roots.keys().copied().collect());
+            }
             // Here changes on DelayedReceipts or DelayedReceiptsIndices will be excluded
             // This is because we cannot migrate delayed receipts part by part. They have to be
             // reconstructed in the new states after all DelayedReceipts are ready in the original
@@ -129,9 +148,9 @@ impl ShardTries {
                 StorageError::StorageInconsistentState(err)
             })? {
                 let new_shard_uid = account_id_to_shard_id(&account_id);
-                Ok(Some(new_shard_uid))
+                Ok(vec![new_shard_uid])
             } else {
-                Ok(None)
+                Ok(vec![])
             }
         })
     }
@@ -140,12 +159,15 @@ impl ShardTries {
         &self,
         state_roots: &HashMap<ShardUId, StateRoot>,
         values: Vec<(Vec<u8>, Option<Vec<u8>>)>,
-        key_to_shard_id: &dyn Fn(&[u8]) -> Result<Option<ShardUId>, StorageError>,
+        key_to_shard_ids: &dyn Fn(&[u8]) -> Result<Vec<ShardUId>, StorageError>,
     ) -> Result<(StoreUpdate, HashMap<ShardUId, StateRoot>), StorageError> {
         let mut changes_by_shard: HashMap<_, Vec<_>> = HashMap::new();
         for (raw_key, value) in values.into_iter() {
-            if let Some(new_shard_uid) = key_to_shard_id(&raw_key)? {
-                changes_by_shard.entry(new_shard_uid).or_default().push((raw_key, value));
+            for new_shard_uid in key_to_shard_ids(&raw_key)? {
+                changes_by_shard
+                    .entry(new_shard_uid)
+                    .or_default()
+                    .push((raw_key.clone(), value.clone()));
             }
         }
         let mut new_state_roots = state_roots.clone();
@@ -326,17 +348,22 @@ mod tests {
         simplify_changes, test_populate_trie,
     };
 
-    use crate::{get, get_delayed_receipt_indices, set, set_account, ShardTries, ShardUId, Trie};
+    use crate::{
+        get, get_delayed_receipt_indices, get_promise_yield_indices, set, set_account, ShardTries,
+        ShardUId, Trie,
+    };
     use near_primitives::account::id::AccountId;
     use near_primitives::account::Account;
     use near_primitives::borsh::BorshSerialize;
     use near_primitives::hash::{hash, CryptoHash};
-    use near_primitives::receipt::{DelayedReceiptIndices, Receipt};
+    use near_primitives::receipt::{
+        DelayedReceiptIndices, PromiseYieldIndices, PromiseYieldTimeout, Receipt,
+    };
     use near_primitives::state_part::PartId;
     use near_primitives::trie_key::trie_key_parsers::parse_account_id_from_raw_key;
     use near_primitives::trie_key::TrieKey;
     use near_primitives::types::{
-        NumShards, StateChangeCause, StateChangesForSplitStates, StateRoot,
+        ConsolidatedStateChange, NumShards, StateChangeCause, StateChangesForSplitStates, StateRoot,
     };
     use rand::seq::SliceRandom;
     use rand::Rng;
@@ -480,10 +507,10 @@ mod tests {
 
                 let (store_update, new_state_roots) = tries
                     .add_values_to_split_states_impl(&state_roots, changes, &|raw_key| {
-                        Ok(Some(ShardUId {
+                        Ok(vec![ShardUId {
                             version: 1,
                             shard_id: (hash(raw_key).0[0] as NumShards % num_shards) as u32,
-                        }))
+                        }])
                     })
                     .unwrap();
                 store_update.commit().unwrap();
@@ -502,6 +529,113 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_split_promise_yield_queue() {
+        let tries = create_tries();
+        let alice: AccountId = "alice.near".parse().unwrap();
+        let bob: AccountId = "bob.near".parse().unwrap();
+        let alice_shard = ShardUId { version: 1, shard_id: 0 };
+        let bob_shard = ShardUId { version: 1, shard_id: 1 };
+        let account_id_to_shard_id =
+            |account_id: &AccountId| if account_id == &alice { alice_shard } else { bob_shard };
+
+        let timeouts: Vec<_> = [&alice, &bob]
+            .into_iter()
+            .enumerate()
+            .map(|(i, account_id)| PromiseYieldTimeout {
+                account_id: account_id.clone(),
+                data_id: hash(account_id.as_ref().as_bytes()),
+                expires_at: 10 + i as u64,
+            })
+            .collect();
+        let yield_key = |timeout: &PromiseYieldTimeout| TrieKey::PromiseYieldReceipt {
+            receiver_id: timeout.account_id.clone(),
+            data_id: timeout.data_id,
+        };
+        let mut values = vec![(
+            TrieKey::PromiseYieldIndices.to_vec(),
+            Some(
+                PromiseYieldIndices { first_index: 0, next_available_index: 2 }
+                    .try_to_vec()
+                    .unwrap(),
+            ),
+        )];
+        for (index, timeout) in timeouts.iter().enumerate() {
+            values.push((
+                TrieKey::PromiseYieldTimeout { index: index as u64 }.to_vec(),
+                Some(timeout.try_to_vec().unwrap()),
+            ));
+            values.push((yield_key(timeout).to_vec(), Some(vec![])));
+        }
+
+        // Every new shard gets the whole timeout queue, but only the yielded promises of its own
+        // accounts.
+        let state_roots: HashMap<_, _> = [alice_shard, bob_shard]
+            .into_iter()
+            .map(|shard_uid| (shard_uid, Trie::EMPTY_ROOT))
+            .collect();
+        let (store_update, state_roots) = tries
+            .add_values_to_split_states(&state_roots, values, &account_id_to_shard_id)
+            .unwrap();
+        store_update.commit().unwrap();
+        for (shard_uid, state_root) in &state_roots {
+            let state_update = tries.new_trie_update(*shard_uid, *state_root);
+            assert_eq!(
+                get_promise_yield_indices(&state_update).unwrap(),
+                PromiseYieldIndices { first_index: 0, next_available_index: 2 }
+            );
+            for (index, timeout) in timeouts.iter().enumerate() {
+                let key = TrieKey::PromiseYieldTimeout { index: index as u64 };
+                assert_eq!(get(&state_update, &key).unwrap().as_ref(), Some(timeout));
+                let own_account = account_id_to_shard_id(&timeout.account_id) == *shard_uid;
+                assert_eq!(state_update.get(&yield_key(timeout)).unwrap().is_some(), own_account);
+            }
+        }
+
+        // Resolving the first timeout updates the queue in every new shard.
+        let changes = StateChangesForSplitStates {
+            changes: vec![
+                ConsolidatedStateChange {
+                    trie_key: TrieKey::PromiseYieldIndices,
+                    value: Some(
+                        PromiseYieldIndices { first_index: 1, next_available_index: 2 }
+                            .try_to_vec()
+                            .unwrap(),
+                    ),
+                },
+                ConsolidatedStateChange {
+                    trie_key: TrieKey::PromiseYieldTimeout { index: 0 },
+                    value: None,
+                },
+                ConsolidatedStateChange { trie_key: yield_key(&timeouts[0]), value: None },
+            ],
+            processed_delayed_receipts: vec![],
+        };
+        let trie_changes = tries
+            .apply_state_changes_to_split_states(&state_roots, changes, &account_id_to_shard_id)
+            .unwrap();
+        let mut store_update = tries.store_update();
+        let state_roots: HashMap<_, _> = trie_changes
+            .into_iter()
+            .map(|(shard_uid, trie_changes)| {
+                (shard_uid, tries.apply_all(&trie_changes, shard_uid, &mut store_update))
+            })
+            .collect();
+        store_update.commit().unwrap();
+        for (shard_uid, state_root) in state_roots {
+            let state_update = tries.new_trie_update(shard_uid, state_root);
+            assert_eq!(
+                get_promise_yield_indices(&state_update).unwrap(),
+                PromiseYieldIndices { first_index: 1, next_available_index: 2 }
+            );
+            let key = TrieKey::PromiseYieldTimeout { index: 0 };
+            assert!(state_update.get(&key).unwrap().is_none());
+            assert!(state_update.get(&yield_key(&timeouts[0])).unwrap().is_none());
+            let key = TrieKey::PromiseYieldTimeout { index: 1 };
+            assert_eq!(get(&state_update, &key).unwrap().as_ref(), Some(&timeouts[1]));
+        }
+    }
+
     #[test]
     fn test_get_delayed_receipts() {
         let mut rng = rand::thread_rng();
@@ -1,7 +1,8 @@
+use crate::config::TrieCacheConfig;
 use crate::db::refcount::decode_value_with_rc;
 use crate::trie::config::TrieConfig;
 use crate::trie::prefetching_trie_storage::PrefetcherResult;
-use crate::trie::POISONED_LOCK_ERR;
+use crate::trie::{DEFAULT_SHARD_CACHE_TOTAL_SIZE_LIMIT, POISONED_LOCK_ERR};
 use crate::{metrics, DBCol, PrefetchApi, StorageError, Store};
 use lru::LruCache;
 use near_o11y::log_assert;
@@ -75,6 +76,11 @@ pub struct TrieCacheInner {
     shard_id: ShardId,
     /// Whether cache is used for view calls execution.
     is_view: bool,
+    /// Number of lookups done by `TrieCachingStorage` which hit and missed the
+    /// cache respectively, since the last call to `take_access_counts`.  Used
+    /// to rebalance memory between caches in adaptive mode.
+    hits: u64,
+    misses: u64,
     // Counters tracking operations happening inside the shard cache.
     // Stored here to avoid overhead of looking them up on hot paths.
     metrics: TrieCacheMetrics,
@@ -87,6 +93,7 @@ struct TrieCacheMetrics {
     shard_cache_pop_lru: GenericCounter<prometheus::core::AtomicU64>,
     shard_cache_gc_pop_misses: GenericCounter<prometheus::core::AtomicU64>,
     shard_cache_deletions_size: GenericGauge<prometheus::core::AtomicI64>,
+    shard_cache_total_size_limit: GenericGauge<prometheus::core::AtomicI64>,
 }
 
 impl TrieCacheInner {
@@ -118,7 +125,10 @@ impl TrieCacheInner {
                 .with_label_values(&metrics_labels),
             shard_cache_deletions_size: metrics::SHARD_CACHE_DELETIONS_SIZE
                 .with_label_values(&metrics_labels),
+            shard_cache_total_size_limit: metrics::SHARD_CACHE_TOTAL_SIZE_LIMIT
+                .with_label_values(&metrics_labels),
         };
+        metrics.shard_cache_total_size_limit.set(total_size_limit as i64);
         Self {
             cache: LruCache::unbounded(),
             deletions: BoundedQueue::new(deletions_queue_capacity),
@@ -126,6 +136,8 @@ impl TrieCacheInner {
             total_size_limit,
             shard_id,
             is_view,
+            hits: 0,
+            misses: 0,
             metrics,
         }
     }
@@ -142,26 +154,7 @@ impl TrieCacheInner {
 
     pub(crate) fn put(&mut self, key: CryptoHash, value: Arc<[u8]>) {
         while self.total_size > self.total_size_limit || self.cache.len() == self.cache.cap() {
-            // First, try to evict value using the key from deletions queue.
-            match self.deletions.pop() {
-                Some(key) => match self.cache.pop(&key) {
-                    Some(value) => {
-                        self.metrics.shard_cache_pop_hits.inc();
-                        self.remove_value_of_size(value.len());
-                        continue;
-                    }
-                    None => {
-                        self.metrics.shard_cache_pop_misses.inc();
-                    }
-                },
-                None => {}
-            }
-
-            // Second, pop LRU value.
-            self.metrics.shard_cache_pop_lru.inc();
-            let (_, value) =
-                self.cache.pop_lru().expect("Cannot fail because total size capacity is > 0");
-            self.remove_value_of_size(value.len());
+            self.evict_one();
         }
 
         // Add value to the cache.
@@ -175,6 +168,30 @@ impl TrieCacheInner {
         };
     }
 
+    /// Evicts a single entry, preferring the ones from the deletions queue
+    /// over the least recently used one.
+    fn evict_one(&mut self) {
+        // First, try to evict value using the key from deletions queue.
+        if let Some(key) = self.deletions.pop() {
+            match self.cache.pop(&key) {
+                Some(value) => {
+                    self.metrics.shard_cache_pop_hits.inc();
+                    self.remove_value_of_size(value.len());
+                    return;
+                }
+                None => {
+                    self.metrics.shard_cache_pop_misses.inc();
+                }
+            }
+        }
+
+        // Second, pop LRU value.
+        self.metrics.shard_cache_pop_lru.inc();
+        let (_, value) =
+            self.cache.pop_lru().expect("Cannot fail because total size capacity is > 0");
+        self.remove_value_of_size(value.len());
+    }
+
     // Adds key to the deletions queue if it is present in cache.
     // Returns key-value pair which are popped if deletions queue is full.
     pub(crate) fn pop(&mut self, key: &CryptoHash) -> Option<(CryptoHash, Arc<[u8]>)> {
@@ -222,6 +239,42 @@ impl TrieCacheInner {
         self.total_size
     }
 
+    /// Upper bound for the approximate memory consumption of LRU cache.
+    pub fn total_size_limit(&self) -> u64 {
+        self.total_size_limit
+    }
+
+    /// Changes the upper bound for the memory consumption, evicting entries
+    /// if the cache is over the new limit.
+    pub(crate) fn set_total_size_limit(&mut self, total_size_limit: u64) {
+        assert!(total_size_limit > 0);
+        self.total_size_limit = total_size_limit;
+        self.metrics.shard_cache_total_size_limit.set(total_size_limit as i64);
+        while self.total_size > self.total_size_limit && !self.cache.is_empty() {
+            self.evict_one();
+        }
+    }
+
+    /// Whether cache is used for view calls execution.
+    pub(crate) fn is_view(&self) -> bool {
+        self.is_view
+    }
+
+    /// Records result of a lookup made while reading a trie node.
+    pub(crate) fn record_access(&mut self, hit: bool) {
+        if hit {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+    }
+
+    /// Returns number of hits and misses recorded since the last call and
+    /// resets the counters.
+    pub(crate) fn take_access_counts(&mut self) -> (u64, u64) {
+        (std::mem::take(&mut self.hits), std::mem::take(&mut self.misses))
+    }
+
     fn entry_size(len: usize) -> u64 {
         len as u64 + Self::PER_ENTRY_OVERHEAD
     }
@@ -235,11 +288,18 @@ impl TrieCache {
     pub fn new(config: &TrieConfig, shard_uid: ShardUId, is_view: bool) -> Self {
         let cache_config =
             if is_view { &config.view_shard_cache_config } else { &config.shard_cache_config };
-        let total_size_limit = cache_config
-            .per_shard_max_bytes
-            .get(&shard_uid)
-            .copied()
-            .unwrap_or(cache_config.default_max_bytes);
+        Self::with_cache_config(config, cache_config, shard_uid, is_view)
+    }
+
+    /// Creates a cache whose memory limit is taken from `cache_config` rather
+    /// than from `config`.
+    pub(crate) fn with_cache_config(
+        config: &TrieConfig,
+        cache_config: &TrieCacheConfig,
+        shard_uid: ShardUId,
+        is_view: bool,
+    ) -> Self {
+        let total_size_limit = Self::max_bytes(cache_config, shard_uid);
         let queue_capacity = config.deletions_queue_capacity();
         Self(Arc::new(Mutex::new(TrieCacheInner::new(
             queue_capacity,
@@ -249,6 +309,18 @@ impl TrieCache {
         ))))
     }
 
+    /// Memory limit of the cache of given shard according to `cache_config`.
+    pub(crate) fn max_bytes(cache_config: &TrieCacheConfig, shard_uid: ShardUId) -> u64 {
+        if cfg!(feature = "no_cache") {
+            return DEFAULT_SHARD_CACHE_TOTAL_SIZE_LIMIT;
+        }
+        cache_config
+            .per_shard_max_bytes
+            .get(&shard_uid)
+            .copied()
+            .unwrap_or(cache_config.default_max_bytes)
+    }
+
     pub fn get(&self, key: &CryptoHash) -> Option<Arc<[u8]>> {
         self.lock().get(key)
     }
@@ -308,32 +380,68 @@ pub trait TrieStorage {
     fn get_trie_nodes_count(&self) -> TrieNodesCount;
 }
 
-/// Records every value read by retrieve_raw_bytes.
-/// Used for obtaining state parts (and challenges in the future).
-/// TODO (#6316): implement proper nodes counting logic as in TrieCachingStorage
+/// Chunk cache and touched nodes counters working the same way as in
+/// `TrieCachingStorage`, so that applying a chunk with a partial storage
+/// charges exactly the same gas as with the caching storage.
+pub(crate) struct TrieChunkCache {
+    mode: Cell<TrieCacheMode>,
+    nodes: RefCell<HashMap<CryptoHash, Arc<[u8]>>>,
+    db_read_nodes: Cell<u64>,
+    mem_read_nodes: Cell<u64>,
+}
+
+impl TrieChunkCache {
+    pub(crate) fn new() -> Self {
+        Self {
+            mode: Cell::new(TrieCacheMode::CachingShard),
+            nodes: Default::default(),
+            db_read_nodes: Cell::new(0),
+            mem_read_nodes: Cell::new(0),
+        }
+    }
+
+    /// Returns the value from the chunk cache if present, otherwise reads it
+    /// with `read` and counts the access as a DB read.
+    fn retrieve(
+        &self,
+        hash: &CryptoHash,
+        read: impl FnOnce() -> Result<Arc<[u8]>, StorageError>,
+    ) -> Result<Arc<[u8]>, StorageError> {
+        if let Some(val) = self.nodes.borrow().get(hash) {
+            self.mem_read_nodes.set(self.mem_read_nodes.get() + 1);
+            return Ok(val.clone());
+        }
+        let val = read()?;
+        self.db_read_nodes.set(self.db_read_nodes.get() + 1);
+        if let TrieCacheMode::CachingChunk = self.mode.get() {
+            self.nodes.borrow_mut().insert(*hash, val.clone());
+        }
+        Ok(val)
+    }
+
+    fn get_trie_nodes_count(&self) -> TrieNodesCount {
+        TrieNodesCount { db_reads: self.db_read_nodes.get(), mem_reads: self.mem_read_nodes.get() }
+    }
+}
+
+/// Records every value read by retrieve_raw_bytes from the underlying storage.
+/// Used for obtaining state parts and storage proofs of state transitions.
 pub struct TrieRecordingStorage {
-    pub(crate) store: Store,
-    pub(crate) shard_uid: ShardUId,
+    pub(crate) storage: Box<dyn TrieStorage>,
     pub(crate) recorded: RefCell<HashMap<CryptoHash, Arc<[u8]>>>,
 }
 
 impl TrieStorage for TrieRecordingStorage {
     fn retrieve_raw_bytes(&self, hash: &CryptoHash) -> Result<Arc<[u8]>, StorageError> {
-        if let Some(val) = self.recorded.borrow().get(hash).cloned() {
-            return Ok(val);
-        }
-        let key = TrieCachingStorage::get_key_from_shard_uid_and_hash(self.shard_uid, hash);
-        let val = self
-            .store
-            .get(DBCol::State, key.as_ref())
-            .map_err(|_| StorageError::StorageInternalError)?;
-        if let Some(val) = val {
-            let val = Arc::from(val);
-            self.recorded.borrow_mut().insert(*hash, Arc::clone(&val));
-            Ok(val)
-        } else {
-            Err(StorageError::StorageInconsistentState("Trie node missing".to_string()))
-        }
+        let val = self.storage.retrieve_raw_bytes(hash)?;
+        self.recorded.borrow_mut().insert(*hash, Arc::clone(&val));
+        Ok(val)
+    }
+
+    /// Caching storage being recorded, so that cache modes and prefetching work
+    /// exactly as without recording.
+    fn as_caching_storage(&self) -> Option<&TrieCachingStorage> {
+        self.storage.as_caching_storage()
     }
 
     fn as_recording_storage(&self) -> Option<&TrieRecordingStorage> {
@@ -341,7 +449,7 @@ impl TrieStorage for TrieRecordingStorage {
     }
 
     fn get_trie_nodes_count(&self) -> TrieNodesCount {
-        unimplemented!();
+        self.storage.get_trie_nodes_count()
     }
 }
 
@@ -350,15 +458,26 @@ impl TrieStorage for TrieRecordingStorage {
 pub struct TrieMemoryPartialStorage {
     pub(crate) recorded_storage: HashMap<CryptoHash, Arc<[u8]>>,
     pub(crate) visited_nodes: RefCell<HashSet<CryptoHash>>,
+    pub(crate) chunk_cache: TrieChunkCache,
+}
+
+impl TrieMemoryPartialStorage {
+    /// Set cache mode.
+    pub fn set_mode(&self, state: TrieCacheMode) {
+        self.chunk_cache.mode.set(state);
+    }
 }
 
 impl TrieStorage for TrieMemoryPartialStorage {
     fn retrieve_raw_bytes(&self, hash: &CryptoHash) -> Result<Arc<[u8]>, StorageError> {
-        let result = self.recorded_storage.get(hash).cloned().ok_or(StorageError::TrieNodeMissing);
-        if result.is_ok() {
-            self.visited_nodes.borrow_mut().insert(*hash);
-        }
-        result
+        self.chunk_cache.retrieve(hash, || {
+            let result =
+                self.recorded_storage.get(hash).cloned().ok_or(StorageError::TrieNodeMissing);
+            if result.is_ok() {
+                self.visited_nodes.borrow_mut().insert(*hash);
+            }
+            result
+        })
     }
 
     fn as_partial_storage(&self) -> Option<&TrieMemoryPartialStorage> {
@@ -366,7 +485,39 @@ impl TrieStorage for TrieMemoryPartialStorage {
     }
 
     fn get_trie_nodes_count(&self) -> TrieNodesCount {
-        unimplemented!();
+        self.chunk_cache.get_trie_nodes_count()
+    }
+}
+
+/// Storage which serves trie nodes and values kept in memory before falling back to another
+/// storage.  Allows to read states produced by trie changes which aren't written to DB.
+pub struct TrieOverlayStorage {
+    pub(crate) base: Box<dyn TrieStorage>,
+    pub(crate) overlay: Arc<HashMap<CryptoHash, Arc<[u8]>>>,
+    /// Number of nodes served from the overlay.
+    pub(crate) overlay_reads: Cell<u64>,
+}
+
+impl TrieStorage for TrieOverlayStorage {
+    fn retrieve_raw_bytes(&self, hash: &CryptoHash) -> Result<Arc<[u8]>, StorageError> {
+        if let Some(val) = self.overlay.get(hash) {
+            self.overlay_reads.set(self.overlay_reads.get() + 1);
+            return Ok(Arc::clone(val));
+        }
+        self.base.retrieve_raw_bytes(hash)
+    }
+
+    fn as_caching_storage(&self) -> Option<&TrieCachingStorage> {
+        self.base.as_caching_storage()
+    }
+
+    fn get_trie_nodes_count(&self) -> TrieNodesCount {
+        // Nodes in the overlay would have been written to and read from DB otherwise.
+        let count = self.base.get_trie_nodes_count();
+        TrieNodesCount {
+            db_reads: count.db_reads + self.overlay_reads.get(),
+            mem_reads: count.mem_reads,
+        }
     }
 }
 
@@ -516,11 +667,13 @@ impl TrieStorage for TrieCachingStorage {
         self.metrics.shard_cache_current_total_size.set(guard.current_total_size() as i64);
         let val = match guard.get(hash) {
             Some(val) => {
+                guard.record_access(true);
                 self.metrics.shard_cache_hits.inc();
                 near_o11y::io_trace!(count: "shard_cache_hit");
                 val.clone()
             }
             None => {
+                guard.record_access(false);
                 self.metrics.shard_cache_misses.inc();
                 near_o11y::io_trace!(count: "shard_cache_miss");
                 let val;
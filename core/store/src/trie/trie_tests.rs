@@ -134,6 +134,7 @@ mod nodes_counter_tests {
     use super::*;
     use crate::test_utils::create_tries;
     use crate::trie::nibble_slice::NibbleSlice;
+    use near_primitives::types::TrieCacheMode;
 
     fn create_trie_key(nibbles: &[u8]) -> Vec<u8> {
         NibbleSlice::encode_nibbles(&nibbles, false).into_vec()
@@ -195,6 +196,50 @@ mod nodes_counter_tests {
         let storage = trie.storage.as_caching_storage().unwrap();
         assert_eq!(storage.shard_cache.len(), 5);
     }
+
+    // Check that recording doesn't change how touched nodes are counted and that the partial storage
+    // built from the recorded nodes counts them like the caching storage, so that applying a chunk
+    // from a storage proof is charged the same gas.
+    #[test]
+    fn test_recorded_storage_count() {
+        let trie_items = vec![
+            (create_trie_key(&[0, 0, 0]), Some(vec![0])),
+            (create_trie_key(&[0, 1, 1]), Some(vec![1])),
+            (create_trie_key(&[1, 0, 0]), Some(vec![2])),
+        ];
+        let get_nodes_counts = |trie: Rc<Trie>| -> Vec<TrieNodesCount> {
+            let trie_update = TrieUpdate::new(Rc::clone(&trie));
+            let mut counts = vec![];
+            for mode in [
+                TrieCacheMode::CachingShard,
+                TrieCacheMode::CachingChunk,
+                TrieCacheMode::CachingShard,
+            ] {
+                trie_update.set_trie_cache_mode(mode);
+                for (key, value) in trie_items.iter() {
+                    assert_eq!(trie.get(key).unwrap(), *value);
+                    counts.push(trie.get_trie_nodes_count());
+                }
+            }
+            counts
+        };
+        let tries = create_tries();
+        let shard_uid = ShardUId { version: 1, shard_id: 0 };
+        let state_root =
+            test_populate_trie(&tries, &Trie::EMPTY_ROOT, shard_uid, simplify_changes(&trie_items));
+        let trie = Rc::new(tries.get_trie_for_shard(shard_uid, state_root));
+        let expected = get_nodes_counts(Rc::clone(&trie));
+        // Nodes read in the chunk cache mode are served from memory afterwards.
+        assert_eq!(expected[8].db_reads, expected[5].db_reads);
+
+        let recording_trie =
+            Rc::new(tries.get_trie_for_shard(shard_uid, state_root).into_recording());
+        assert!(recording_trie.storage.as_caching_storage().is_some());
+        assert_eq!(get_nodes_counts(Rc::clone(&recording_trie)), expected);
+        let partial_storage = recording_trie.recorded_storage().unwrap();
+        let partial_trie = Trie::from_recorded_storage(partial_storage, *trie.get_root());
+        assert_eq!(get_nodes_counts(Rc::new(partial_trie)), expected);
+    }
 }
 
 #[cfg(test)]
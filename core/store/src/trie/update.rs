@@ -154,7 +154,10 @@ impl TrieUpdate {
     }
 
     pub fn set_trie_cache_mode(&self, state: TrieCacheMode) {
-        if let Some(storage) = self.trie.storage.as_caching_storage() {
+        let storage = &self.trie.storage;
+        if let Some(storage) = storage.as_caching_storage() {
+            storage.set_mode(state);
+        } else if let Some(storage) = storage.as_partial_storage() {
             storage.set_mode(state);
         }
     }
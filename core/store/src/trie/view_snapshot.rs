@@ -0,0 +1,438 @@
+//! Read-only snapshots of shard tries used to serve view queries.
+//!
+//! A snapshot materialises all nodes and values of a trie at a single state
+//! root into an immutable file which is then memory mapped.  View queries read
+//! from the snapshot rather than from RocksDB so that RPC load doesn’t compete
+//! for IO with block processing.
+//!
+//! The file consists of a header, the data section and the index:
+//!
+//! * header: magic bytes, format version, shard uid, state root, number of
+//!   index entries and offset of the index (all integers little endian);
+//! * data: raw bytes of trie nodes and values laid out contiguously in depth
+//!   first order so that nodes along a path tend to share pages;
+//! * index: `(hash, offset, length)` entries sorted by hash which are looked up
+//!   with binary search.
+//!
+//! Since nodes are looked up by hash, a snapshot can serve a trie at any state
+//! root: nodes which changed since the snapshot was taken are read from the
+//! database instead.
+
+use crate::trie::{TrieCachingStorage, TrieStorage};
+use crate::{RawTrieNode, RawTrieNodeWithSize, StorageError, Trie};
+use byteorder::{ByteOrder, LittleEndian, WriteBytesExt};
+use near_primitives::hash::CryptoHash;
+use near_primitives::shard_layout::ShardUId;
+use near_primitives::types::{StateRoot, TrieNodesCount};
+use std::cell::Cell;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const MAGIC: &[u8; 8] = b"NEARTRIE";
+const VERSION: u32 = 1;
+/// Magic, version, shard version, shard id, state root, entries, index offset.
+const HEADER_SIZE: usize = 8 + 4 + 4 + 4 + 32 + 8 + 8;
+/// Hash, offset and length.
+const INDEX_ENTRY_SIZE: usize = 32 + 8 + 4;
+/// Number of index entries sorted in memory at once while writing a snapshot.
+/// Sorted runs are spilled into temporary files and merged into the index so
+/// that memory used by writing doesn’t grow with the size of the trie.
+const INDEX_RUN_SIZE: usize = 1 << 20;
+/// Values at least this long are written only once even if several keys hold
+/// them, e.g. the same contract deployed to many accounts.  Shorter values and
+/// nodes are written again for every path leading to them.
+const DEDUP_MIN_VALUE_LEN: usize = 4096;
+
+/// Hash, offset and length of a node or value.
+type IndexEntry = (CryptoHash, u64, u32);
+
+/// Memory mapped snapshot of a single shard trie.
+pub struct TrieViewSnapshot {
+    mmap: memmap2::Mmap,
+    shard_uid: ShardUId,
+    state_root: StateRoot,
+    num_entries: usize,
+    index_offset: usize,
+}
+
+impl TrieViewSnapshot {
+    /// Returns name of the snapshot file of given shard inside of the snapshots
+    /// directory.
+    pub fn file_path(dir: &Path, shard_uid: ShardUId) -> PathBuf {
+        dir.join(format!("shard-{}-{}.trie", shard_uid.version, shard_uid.shard_id))
+    }
+
+    /// Writes all nodes and values reachable from the root of `trie` into
+    /// a snapshot file at `path`.
+    ///
+    /// The file is first written under a temporary name and then renamed so
+    /// readers never observe a partially written snapshot.  Snapshots already
+    /// opened from the old file remain valid.
+    pub fn create(trie: &Trie, shard_uid: ShardUId, path: &Path) -> io::Result<()> {
+        let tmp_path = path.with_extension("tmp");
+        let result = Self::write(trie, shard_uid, &tmp_path, INDEX_RUN_SIZE)
+            .and_then(|()| std::fs::rename(&tmp_path, path));
+        if result.is_err() {
+            let _ = std::fs::remove_file(&tmp_path);
+        }
+        result
+    }
+
+    fn write(
+        trie: &Trie,
+        shard_uid: ShardUId,
+        path: &Path,
+        index_run_size: usize,
+    ) -> io::Result<()> {
+        let mut file = io::BufWriter::new(File::create(path)?);
+        file.write_all(&[0; HEADER_SIZE])?;
+
+        let mut offset = HEADER_SIZE as u64;
+        let mut run = Vec::new();
+        let mut runs = Vec::new();
+        let mut large_values = HashSet::new();
+        let mut stack = vec![];
+        if trie.get_root() != &Trie::EMPTY_ROOT {
+            stack.push((*trie.get_root(), true));
+        }
+        while let Some((hash, is_node)) = stack.pop() {
+            if !is_node && large_values.contains(&hash) {
+                continue;
+            }
+            let bytes = trie.storage.retrieve_raw_bytes(&hash).map_err(storage_to_io_error)?;
+            file.write_all(&bytes)?;
+            if !is_node && bytes.len() >= DEDUP_MIN_VALUE_LEN {
+                large_values.insert(hash);
+            }
+            run.push((hash, offset, bytes.len() as u32));
+            offset += bytes.len() as u64;
+            if run.len() >= index_run_size {
+                runs.push(write_index_run(&mut run)?);
+            }
+            if !is_node {
+                continue;
+            }
+            let node = RawTrieNodeWithSize::decode(&bytes)?;
+            // Pushed in reverse so that the value directly follows its node and
+            // children are written in key order.
+            match node.node {
+                RawTrieNode::Leaf(_, _, value_hash) => stack.push((value_hash, false)),
+                RawTrieNode::Branch(children, value) => {
+                    for child in children.iter().rev().flatten() {
+                        stack.push((*child, true));
+                    }
+                    if let Some((_, value_hash)) = value {
+                        stack.push((value_hash, false));
+                    }
+                }
+                RawTrieNode::Extension(_, child) => stack.push((child, true)),
+            }
+        }
+        if !run.is_empty() {
+            runs.push(write_index_run(&mut run)?);
+        }
+        let num_entries = merge_index_runs(runs, &mut file)?;
+
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(MAGIC)?;
+        file.write_u32::<LittleEndian>(VERSION)?;
+        file.write_u32::<LittleEndian>(shard_uid.version)?;
+        file.write_u32::<LittleEndian>(shard_uid.shard_id)?;
+        file.write_all(&trie.get_root().0)?;
+        file.write_u64::<LittleEndian>(num_entries)?;
+        file.write_u64::<LittleEndian>(offset)?;
+        file.into_inner()?.sync_all()
+    }
+
+    /// Opens and memory maps an existing snapshot file.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: Snapshot files are never modified after they are created.
+        // New snapshots replace old ones by renaming so the mapped file stays
+        // intact.
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+        let invalid = |msg: &str| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("{}: {msg}", path.display()))
+        };
+        if mmap.len() < HEADER_SIZE || &mmap[..8] != MAGIC {
+            return Err(invalid("not a trie snapshot"));
+        }
+        let header = &mmap[8..HEADER_SIZE];
+        let version = LittleEndian::read_u32(&header[0..4]);
+        if version != VERSION {
+            return Err(invalid(&format!("unsupported snapshot version {version}")));
+        }
+        let shard_uid = ShardUId {
+            version: LittleEndian::read_u32(&header[4..8]),
+            shard_id: LittleEndian::read_u32(&header[8..12]),
+        };
+        let state_root = CryptoHash::try_from(&header[12..44]).unwrap();
+        let num_entries = LittleEndian::read_u64(&header[44..52]) as usize;
+        let index_offset = LittleEndian::read_u64(&header[52..60]) as usize;
+        if index_offset < HEADER_SIZE
+            || num_entries.checked_mul(INDEX_ENTRY_SIZE).and_then(|n| n.checked_add(index_offset))
+                != Some(mmap.len())
+        {
+            return Err(invalid("corrupted index"));
+        }
+        Ok(Self { mmap, shard_uid, state_root, num_entries, index_offset })
+    }
+
+    pub fn shard_uid(&self) -> ShardUId {
+        self.shard_uid
+    }
+
+    pub fn state_root(&self) -> &StateRoot {
+        &self.state_root
+    }
+
+    /// Returns raw bytes of the trie node or value with given hash.
+    pub fn get(&self, hash: &CryptoHash) -> Option<&[u8]> {
+        let index = &self.mmap[self.index_offset..];
+        let (mut lo, mut hi) = (0, self.num_entries);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let entry = &index[mid * INDEX_ENTRY_SIZE..(mid + 1) * INDEX_ENTRY_SIZE];
+            match entry[..32].cmp(&hash.0[..]) {
+                std::cmp::Ordering::Less => lo = mid + 1,
+                std::cmp::Ordering::Greater => hi = mid,
+                std::cmp::Ordering::Equal => {
+                    let offset = LittleEndian::read_u64(&entry[32..40]) as usize;
+                    let len = LittleEndian::read_u32(&entry[40..44]) as usize;
+                    return self.mmap.get(offset..offset + len);
+                }
+            }
+        }
+        None
+    }
+}
+
+fn storage_to_io_error(err: StorageError) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, err)
+}
+
+fn write_index_entry(out: &mut impl Write, (hash, offset, len): &IndexEntry) -> io::Result<()> {
+    out.write_all(&hash.0)?;
+    out.write_u64::<LittleEndian>(*offset)?;
+    out.write_u32::<LittleEndian>(*len)
+}
+
+fn read_index_entry(reader: &mut impl Read) -> io::Result<Option<IndexEntry>> {
+    let mut entry = [0; INDEX_ENTRY_SIZE];
+    match reader.read_exact(&mut entry) {
+        Ok(()) => Ok(Some((
+            CryptoHash::try_from(&entry[..32]).unwrap(),
+            LittleEndian::read_u64(&entry[32..40]),
+            LittleEndian::read_u32(&entry[40..44]),
+        ))),
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Sorts index entries by hash and spills them into a temporary file.
+fn write_index_run(run: &mut Vec<IndexEntry>) -> io::Result<File> {
+    run.sort_unstable();
+    let mut file = io::BufWriter::new(tempfile::tempfile()?);
+    for entry in run.drain(..) {
+        write_index_entry(&mut file, &entry)?;
+    }
+    let mut file = file.into_inner()?;
+    file.seek(SeekFrom::Start(0))?;
+    Ok(file)
+}
+
+/// Merges sorted runs of index entries into `out` keeping only the first entry
+/// of every hash.  Returns number of entries written.
+fn merge_index_runs(runs: Vec<File>, out: &mut impl Write) -> io::Result<u64> {
+    let mut readers: Vec<_> = runs.into_iter().map(io::BufReader::new).collect();
+    let mut heap = BinaryHeap::with_capacity(readers.len());
+    for (run, reader) in readers.iter_mut().enumerate() {
+        if let Some(entry) = read_index_entry(reader)? {
+            heap.push(Reverse((entry, run)));
+        }
+    }
+    let mut num_entries = 0;
+    let mut last_hash = None;
+    while let Some(Reverse((entry, run))) = heap.pop() {
+        if last_hash != Some(entry.0) {
+            write_index_entry(out, &entry)?;
+            last_hash = Some(entry.0);
+            num_entries += 1;
+        }
+        if let Some(entry) = read_index_entry(&mut readers[run])? {
+            heap.push(Reverse((entry, run)));
+        }
+    }
+    Ok(num_entries)
+}
+
+/// Storage serving trie nodes from a [`TrieViewSnapshot`] and falling back to
+/// another storage for nodes missing from the snapshot.
+pub struct TrieViewSnapshotStorage {
+    snapshot: Arc<TrieViewSnapshot>,
+    fallback: Box<dyn TrieStorage>,
+    /// Counts nodes read from the snapshot.  All of them are accounted as
+    /// database reads just like in view `TrieCachingStorage`.
+    db_read_nodes: Cell<u64>,
+}
+
+impl TrieViewSnapshotStorage {
+    pub fn new(snapshot: Arc<TrieViewSnapshot>, fallback: Box<dyn TrieStorage>) -> Self {
+        Self { snapshot, fallback, db_read_nodes: Cell::new(0) }
+    }
+}
+
+impl TrieStorage for TrieViewSnapshotStorage {
+    fn retrieve_raw_bytes(&self, hash: &CryptoHash) -> Result<Arc<[u8]>, StorageError> {
+        match self.snapshot.get(hash) {
+            Some(bytes) => {
+                self.db_read_nodes.set(self.db_read_nodes.get() + 1);
+                Ok(Arc::from(bytes))
+            }
+            None => self.fallback.retrieve_raw_bytes(hash),
+        }
+    }
+
+    fn as_caching_storage(&self) -> Option<&TrieCachingStorage> {
+        self.fallback.as_caching_storage()
+    }
+
+    fn get_trie_nodes_count(&self) -> TrieNodesCount {
+        let fallback = self.fallback.get_trie_nodes_count();
+        TrieNodesCount {
+            db_reads: fallback.db_reads + self.db_read_nodes.get(),
+            mem_reads: fallback.mem_reads,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TrieViewSnapshot, TrieViewSnapshotStorage};
+    use crate::flat_state::FlatStateFactory;
+    use crate::test_utils::{create_test_store, create_tries, gen_changes, test_populate_trie};
+    use crate::{ShardTries, ShardUId, Trie, TrieConfig, TrieDBStorage, TrieStorage};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let mut rng = rand::thread_rng();
+        let tries = create_tries();
+        let shard_uid = ShardUId::single_shard();
+        let dir = tempfile::tempdir().unwrap();
+        let path = TrieViewSnapshot::file_path(dir.path(), shard_uid);
+
+        let mut changes = gen_changes(&mut rng, 500);
+        // Values shared by several keys are stored once in the trie.
+        for key in [b"large0", b"large1"] {
+            changes.push((key.to_vec(), Some(vec![1; super::DEDUP_MIN_VALUE_LEN])));
+        }
+        for key in [b"small0", b"small1"] {
+            changes.push((key.to_vec(), Some(vec![2; 10])));
+        }
+        let root = test_populate_trie(&tries, &Trie::EMPTY_ROOT, shard_uid, changes.clone());
+        let trie = tries.get_trie_for_shard(shard_uid, root);
+        TrieViewSnapshot::create(&trie, shard_uid, &path).unwrap();
+        assert!(!path.with_extension("tmp").exists());
+        // Same snapshot written with the index merged from many small runs.
+        let small_runs_path = dir.path().join("small-runs.trie");
+        TrieViewSnapshot::write(&trie, shard_uid, &small_runs_path, 7).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), std::fs::read(&small_runs_path).unwrap());
+
+        let snapshot = Arc::new(TrieViewSnapshot::open(&path).unwrap());
+        assert_eq!(snapshot.shard_uid(), shard_uid);
+        assert_eq!(snapshot.state_root(), &root);
+        // Nothing is read from the fallback which is empty.
+        let fallback = Box::new(TrieDBStorage::new(create_test_store(), shard_uid));
+        let snapshot_trie =
+            Trie::new(Box::new(TrieViewSnapshotStorage::new(snapshot, fallback)), root, None);
+        let expected: Vec<_> = trie.iter().unwrap().map(Result::unwrap).collect();
+        let actual: Vec<_> = snapshot_trie.iter().unwrap().map(Result::unwrap).collect();
+        assert_eq!(expected, actual);
+        for (key, _) in changes {
+            assert_eq!(trie.get(&key).unwrap(), snapshot_trie.get(&key).unwrap());
+        }
+        assert!(snapshot_trie.get_trie_nodes_count().db_reads > 0);
+    }
+
+    #[test]
+    fn test_snapshot_empty_and_corrupted() {
+        let tries = create_tries();
+        let shard_uid = ShardUId::single_shard();
+        let dir = tempfile::tempdir().unwrap();
+        let path = TrieViewSnapshot::file_path(dir.path(), shard_uid);
+
+        let trie = tries.get_trie_for_shard(shard_uid, Trie::EMPTY_ROOT);
+        TrieViewSnapshot::create(&trie, shard_uid, &path).unwrap();
+        let snapshot = TrieViewSnapshot::open(&path).unwrap();
+        assert_eq!(snapshot.state_root(), &Trie::EMPTY_ROOT);
+        assert_eq!(snapshot.get(&Trie::EMPTY_ROOT), None);
+
+        let len = std::fs::metadata(&path).unwrap().len();
+        std::fs::OpenOptions::new().write(true).open(&path).unwrap().set_len(len + 1).unwrap();
+        assert!(TrieViewSnapshot::open(&path).is_err());
+        std::fs::write(&path, b"garbage").unwrap();
+        assert!(TrieViewSnapshot::open(&path).is_err());
+    }
+
+    /// Returns value of `key` and number of nodes read from the database rather
+    /// than from a snapshot.
+    fn get_with_db_reads(trie: Trie, key: &[u8]) -> (Option<Vec<u8>>, u64) {
+        let value = trie.get(key).unwrap();
+        let db_reads = trie.storage.as_caching_storage().unwrap().get_trie_nodes_count().db_reads;
+        (value, db_reads)
+    }
+
+    #[test]
+    fn test_view_queries_use_snapshot() {
+        let store = create_test_store();
+        let shard_uid = ShardUId::single_shard();
+        let dir = tempfile::tempdir().unwrap();
+        let trie_config =
+            TrieConfig { view_snapshot_dir: Some(dir.path().to_path_buf()), ..Default::default() };
+        let tries = ShardTries::new(
+            store.clone(),
+            trie_config,
+            &[shard_uid],
+            FlatStateFactory::new(store.clone()),
+        );
+        assert!(tries.view_snapshots_refresh_due());
+
+        let changes = vec![(b"key".to_vec(), Some(b"value".to_vec()))];
+        let root = test_populate_trie(&tries, &Trie::EMPTY_ROOT, shard_uid, changes);
+        let changes = vec![(b"key2".to_vec(), Some(b"value2".to_vec()))];
+        let new_root = test_populate_trie(&tries, &root, shard_uid, changes);
+        let trie = tries.get_view_trie_for_shard(shard_uid, root);
+        let (value, db_reads) = get_with_db_reads(trie, b"key");
+        assert_eq!(value, Some(b"value".to_vec()));
+        assert!(db_reads > 0);
+
+        tries.build_view_snapshots(dir.path(), vec![(shard_uid, root)]);
+        let trie = tries.get_view_trie_for_shard(shard_uid, root);
+        assert_eq!(get_with_db_reads(trie, b"key"), (Some(b"value".to_vec()), 0));
+        // Nodes which changed since the snapshot are read from the database.
+        let trie = tries.get_view_trie_for_shard(shard_uid, new_root);
+        let (value, db_reads) = get_with_db_reads(trie, b"key2");
+        assert_eq!(value, Some(b"value2".to_vec()));
+        assert!(db_reads > 0);
+        let trie = tries.get_view_trie_for_shard(shard_uid, new_root);
+        assert_eq!(get_with_db_reads(trie, b"key").0, Some(b"value".to_vec()));
+        // Non-view tries still read from the database.
+        let trie = tries.get_trie_for_shard(shard_uid, root);
+        let (_, db_reads) = get_with_db_reads(trie, b"key");
+        assert!(db_reads > 0);
+
+        // Snapshots are picked up again after restart.
+        let trie_config =
+            TrieConfig { view_snapshot_dir: Some(dir.path().to_path_buf()), ..Default::default() };
+        let tries =
+            ShardTries::new(store.clone(), trie_config, &[shard_uid], FlatStateFactory::new(store));
+        let trie = tries.get_view_trie_for_shard(shard_uid, root);
+        assert_eq!(get_with_db_reads(trie, b"key"), (Some(b"value".to_vec()), 0));
+    }
+}
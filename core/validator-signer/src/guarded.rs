@@ -0,0 +1,167 @@
+use crate::signing_history::SafetyChecker;
+use near_crypto::{PublicKey, Signature};
+use near_primitives::block::ApprovalInner;
+use near_primitives::challenge::ChallengeBody;
+use near_primitives::hash::CryptoHash;
+use near_primitives::network::PeerId;
+use near_primitives::sharding::ChunkHeaderToSign;
+use near_primitives::telemetry::TelemetryInfo;
+use near_primitives::types::{AccountId, BlockHeight, EpochId};
+use near_primitives::validator_signer::{ValidatorSigner, ValidatorSignerError};
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tracing::error;
+
+/// Validator signer which checks every block, chunk and approval against the
+/// signing history before signing it, and refuses to sign conflicting ones.
+pub struct GuardedValidatorSigner {
+    signer: Arc<dyn ValidatorSigner>,
+    checker: Mutex<SafetyChecker>,
+}
+
+impl GuardedValidatorSigner {
+    pub fn new(signer: Arc<dyn ValidatorSigner>, path: &Path) -> io::Result<Self> {
+        Ok(Self { signer, checker: Mutex::new(SafetyChecker::open(path)?) })
+    }
+
+    fn check(
+        &self,
+        check: impl FnOnce(&mut SafetyChecker) -> Result<(), ValidatorSignerError>,
+    ) -> Result<(), ValidatorSignerError> {
+        let result = check(&mut self.checker.lock().unwrap());
+        if let Err(err) = &result {
+            error!(target: "client", %err, "Not signing a message");
+        }
+        result
+    }
+}
+
+impl ValidatorSigner for GuardedValidatorSigner {
+    fn validator_id(&self) -> &AccountId {
+        self.signer.validator_id()
+    }
+
+    fn public_key(&self) -> PublicKey {
+        self.signer.public_key()
+    }
+
+    fn sign_telemetry(
+        &self,
+        info: &TelemetryInfo,
+    ) -> Result<serde_json::Value, ValidatorSignerError> {
+        self.signer.sign_telemetry(info)
+    }
+
+    fn sign_block_header_parts(
+        &self,
+        prev_hash: CryptoHash,
+        inner_lite: &[u8],
+        inner_rest: &[u8],
+    ) -> Result<(CryptoHash, Signature), ValidatorSignerError> {
+        self.check(|checker| checker.check_block(prev_hash, inner_lite, inner_rest))?;
+        self.signer.sign_block_header_parts(prev_hash, inner_lite, inner_rest)
+    }
+
+    fn sign_chunk_header(
+        &self,
+        header: &ChunkHeaderToSign,
+        epoch_id: &EpochId,
+    ) -> Result<Signature, ValidatorSignerError> {
+        self.check(|checker| checker.check_chunk(header, epoch_id))?;
+        self.signer.sign_chunk_header(header, epoch_id)
+    }
+
+    fn sign_approval(
+        &self,
+        inner: &ApprovalInner,
+        target_height: BlockHeight,
+        epoch_id: &EpochId,
+    ) -> Result<Signature, ValidatorSignerError> {
+        self.check(|checker| checker.check_approval(inner, target_height, epoch_id))?;
+        self.signer.sign_approval(inner, target_height, epoch_id)
+    }
+
+    fn sign_challenge(
+        &self,
+        challenge_body: &ChallengeBody,
+    ) -> Result<(CryptoHash, Signature), ValidatorSignerError> {
+        self.signer.sign_challenge(challenge_body)
+    }
+
+    fn sign_account_announce(
+        &self,
+        account_id: &AccountId,
+        peer_id: &PeerId,
+        epoch_id: &EpochId,
+    ) -> Result<Signature, ValidatorSignerError> {
+        self.signer.sign_account_announce(account_id, peer_id, epoch_id)
+    }
+
+    fn sign_account_key_payload(
+        &self,
+        proto_bytes: &[u8],
+    ) -> Result<Signature, ValidatorSignerError> {
+        self.signer.sign_account_key_payload(proto_bytes)
+    }
+
+    fn compute_vrf_with_proof(
+        &self,
+        data: &[u8],
+    ) -> Result<(near_crypto::vrf::Value, near_crypto::vrf::Proof), ValidatorSignerError> {
+        self.signer.compute_vrf_with_proof(data)
+    }
+
+    fn write_to_file(&self, path: &Path) -> io::Result<()> {
+        self.signer.write_to_file(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GuardedValidatorSigner;
+    use crate::signing_history::tests::{chunk_header, epoch_id};
+    use near_crypto::KeyType;
+    use near_primitives::block::{Approval, ApprovalInner};
+    use near_primitives::hash::CryptoHash;
+    use near_primitives::validator_signer::{
+        InMemoryValidatorSigner, ValidatorSigner, ValidatorSignerError,
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn test_guarded_signer() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("signing_history.json");
+        let epoch_id = epoch_id(b"e1");
+        let inner = Arc::new(InMemoryValidatorSigner::from_seed(
+            "test".parse().unwrap(),
+            KeyType::ED25519,
+            "test",
+        ));
+        let public_key = inner.public_key();
+        let signer = GuardedValidatorSigner::new(inner.clone(), &path).unwrap();
+
+        let endorsement = ApprovalInner::Endorsement(CryptoHash::hash_bytes(b"a"));
+        let data = Approval::get_data_for_sig(&endorsement, 10);
+        let signature = signer.sign_approval(&endorsement, 10, &epoch_id).unwrap();
+        assert!(signature.verify(&data, &public_key));
+        assert_eq!(signer.sign_approval(&endorsement, 10, &epoch_id), Ok(signature));
+        let skip = ApprovalInner::Skip(8);
+        assert!(matches!(
+            signer.sign_approval(&skip, 10, &epoch_id),
+            Err(ValidatorSignerError::Refused(_))
+        ));
+
+        let chunk = chunk_header(5, 0, b"chunk");
+        let signature = signer.sign_chunk_header(&chunk, &epoch_id).unwrap();
+        assert!(signature.verify(chunk.chunk_hash().as_ref(), &public_key));
+        let other = chunk_header(5, 0, b"other");
+        assert!(signer.sign_chunk_header(&other, &epoch_id).is_err());
+
+        // A signer restarted with the same history refuses conflicts as well.
+        let signer = GuardedValidatorSigner::new(inner, &path).unwrap();
+        assert!(signer.sign_approval(&skip, 10, &epoch_id).is_err());
+        assert!(signer.sign_chunk_header(&other, &epoch_id).is_err());
+    }
+}
@@ -0,0 +1,12 @@
+//! Validator signers shared by the node and the remote signer daemon, and the
+//! signing history which keeps both of them from signing conflicting blocks,
+//! chunks or approvals.
+
+mod guarded;
+pub mod protocol;
+mod remote;
+mod signing_history;
+
+pub use guarded::GuardedValidatorSigner;
+pub use remote::{RemoteSignerConfig, RemoteValidatorSigner};
+pub use signing_history::{EpochSigningHistory, SafetyChecker, SignedMessage, SigningHistory};
@@ -0,0 +1,94 @@
+//! Messages exchanged between the node and the signer.  Every message is
+//! borsh encoded and prefixed with its length as a little endian `u32`.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_crypto::{PublicKey, Signature};
+use near_primitives::block::ApprovalInner;
+use near_primitives::challenge::ChallengeBody;
+use near_primitives::hash::CryptoHash;
+use near_primitives::network::PeerId;
+use near_primitives::sharding::ChunkHeaderToSign;
+use near_primitives::types::{AccountId, BlockHeight, EpochId};
+use std::io::{self, Read, Write};
+
+/// Upper bound on the size of a single message, large enough for block
+/// headers with chunk headers of all shards.
+const MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub enum SignRequest {
+    /// Returns the account and the public key of the validator.
+    GetInfo,
+    Telemetry {
+        content: String,
+    },
+    BlockHeader {
+        prev_hash: CryptoHash,
+        inner_lite: Vec<u8>,
+        inner_rest: Vec<u8>,
+    },
+    /// Inner part of the chunk header.  The signer computes the chunk hash
+    /// itself, so that it knows the height and the shard of what it signs.
+    ChunkHeader {
+        header: ChunkHeaderToSign,
+        epoch_id: EpochId,
+    },
+    Approval {
+        inner: ApprovalInner,
+        target_height: BlockHeight,
+        epoch_id: EpochId,
+    },
+    Challenge {
+        challenge_body: Box<ChallengeBody>,
+    },
+    AccountAnnounce {
+        account_id: AccountId,
+        peer_id: PeerId,
+        epoch_id: EpochId,
+    },
+    AccountKeyPayload {
+        proto_bytes: Vec<u8>,
+    },
+    Vrf {
+        data: Vec<u8>,
+    },
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub enum SignResponse {
+    Info {
+        account_id: AccountId,
+        public_key: PublicKey,
+    },
+    Signature(Signature),
+    Vrf {
+        value: near_crypto::vrf::Value,
+        proof: near_crypto::vrf::Proof,
+    },
+    /// The signer refused to sign the message, e.g. because it conflicts
+    /// with a previously signed one.
+    Refused(String),
+}
+
+pub fn write_message<T: BorshSerialize>(stream: &mut impl Write, message: &T) -> io::Result<()> {
+    let bytes = message.try_to_vec()?;
+    let len = u32::try_from(bytes.len())
+        .ok()
+        .filter(|len| *len as usize <= MAX_MESSAGE_SIZE)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Message is too large"))?;
+    stream.write_all(&len.to_le_bytes())?;
+    stream.write_all(&bytes)?;
+    stream.flush()
+}
+
+pub fn read_message<T: BorshDeserialize>(stream: &mut impl Read) -> io::Result<T> {
+    let mut len = [0; 4];
+    stream.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_MESSAGE_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Message is too large"));
+    }
+    let mut bytes = vec![0; len];
+    stream.read_exact(&mut bytes)?;
+    T::try_from_slice(&bytes)
+}
@@ -0,0 +1,308 @@
+//! `ValidatorSigner` which forwards everything to the signer daemon.
+
+use crate::protocol::{read_message, write_message, SignRequest, SignResponse};
+use near_crypto::{PublicKey, Signature};
+use near_primitives::block::{ApprovalInner, BlockHeader};
+use near_primitives::challenge::ChallengeBody;
+use near_primitives::hash::CryptoHash;
+use near_primitives::network::PeerId;
+use near_primitives::sharding::ChunkHeaderToSign;
+use near_primitives::telemetry::TelemetryInfo;
+use near_primitives::types::{AccountId, BlockHeight, EpochId};
+use near_primitives::validator_signer::{ValidatorSigner, ValidatorSignerError};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RemoteSignerConfig {
+    /// Unix socket the signer listens on.
+    pub socket_path: PathBuf,
+    /// Timeout of a single request.
+    #[serde(default = "default_timeout")]
+    pub timeout: Duration,
+}
+
+fn default_timeout() -> Duration {
+    Duration::from_secs(1)
+}
+
+/// Request passed to the thread talking to the signer.
+struct Job {
+    request: SignRequest,
+    /// The caller stops waiting for the response at this point, so the
+    /// request is dropped if it's still queued by then.
+    deadline: Instant,
+    response: mpsc::SyncSender<io::Result<SignResponse>>,
+}
+
+/// Signer which holds no keys and asks the signer daemon for every signature.
+///
+/// Requests are sent from a dedicated thread, so a slow or unreachable signer
+/// delays the caller by at most `RemoteSignerConfig::timeout`, after which
+/// signing fails with `ValidatorSignerError::Unavailable`.
+pub struct RemoteValidatorSigner {
+    timeout: Duration,
+    account_id: AccountId,
+    public_key: PublicKey,
+    jobs: Mutex<mpsc::Sender<Job>>,
+}
+
+impl RemoteValidatorSigner {
+    /// Connects to the signer and fetches the validator account and key.
+    pub fn connect(config: RemoteSignerConfig) -> io::Result<Self> {
+        let timeout = config.timeout;
+        let (jobs, receiver) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("remote_signer".to_string())
+            .spawn(move || run_connection(config, receiver))?;
+        let jobs = Mutex::new(jobs);
+        match send_request(&jobs, timeout, SignRequest::GetInfo)? {
+            SignResponse::Info { account_id, public_key } => {
+                Ok(Self { timeout, account_id, public_key, jobs })
+            }
+            response => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unexpected response from the remote signer: {:?}", response),
+            )),
+        }
+    }
+
+    fn sign_response(&self, request: SignRequest) -> Result<SignResponse, ValidatorSignerError> {
+        match send_request(&self.jobs, self.timeout, request) {
+            Ok(SignResponse::Refused(reason)) => Err(ValidatorSignerError::Refused(reason)),
+            Ok(response) => Ok(response),
+            Err(err) => Err(ValidatorSignerError::Unavailable(err.to_string())),
+        }
+    }
+
+    /// Asks the signer to sign the message described by `request`.
+    fn sign(&self, request: SignRequest) -> Result<Signature, ValidatorSignerError> {
+        match self.sign_response(request)? {
+            SignResponse::Signature(signature) => Ok(signature),
+            response => Err(unexpected_response(response)),
+        }
+    }
+}
+
+fn unexpected_response(response: SignResponse) -> ValidatorSignerError {
+    ValidatorSignerError::Unavailable(format!("unexpected response: {:?}", response))
+}
+
+/// Passes the request to the connection thread and waits for the response
+/// until the timeout.
+fn send_request(
+    jobs: &Mutex<mpsc::Sender<Job>>,
+    timeout: Duration,
+    request: SignRequest,
+) -> io::Result<SignResponse> {
+    let (response, receiver) = mpsc::sync_channel(1);
+    let job = Job { request, deadline: Instant::now() + timeout, response };
+    jobs.lock().unwrap().send(job).map_err(|_| {
+        io::Error::new(io::ErrorKind::BrokenPipe, "remote signer connection thread has stopped")
+    })?;
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(mpsc::RecvTimeoutError::Timeout) => Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            format!("no response from the remote signer within {:?}", timeout),
+        )),
+        Err(mpsc::RecvTimeoutError::Disconnected) => Err(io::Error::new(
+            io::ErrorKind::BrokenPipe,
+            "remote signer connection thread has stopped",
+        )),
+    }
+}
+
+/// Sends queued requests to the signer one at a time until the
+/// `RemoteValidatorSigner` is dropped.
+fn run_connection(config: RemoteSignerConfig, jobs: mpsc::Receiver<Job>) {
+    let mut connection = None;
+    for job in jobs {
+        if job.deadline <= Instant::now() {
+            continue;
+        }
+        // The connection breaks when the signer restarts, so retry once on
+        // a fresh one.  The signer allows signing the same message twice.
+        let mut result = Err(io::Error::new(io::ErrorKind::NotConnected, "Not connected"));
+        for _ in 0..2 {
+            let stream = match connection.as_mut() {
+                Some(stream) => stream,
+                None => match open_stream(&config.socket_path, config.timeout) {
+                    Ok(stream) => connection.insert(stream),
+                    Err(err) => {
+                        result = Err(err);
+                        break;
+                    }
+                },
+            };
+            result = write_message(stream, &job.request).and_then(|()| read_message(stream));
+            if result.is_ok() {
+                break;
+            }
+            connection = None;
+        }
+        // The caller may have stopped waiting already.
+        let _ = job.response.send(result);
+    }
+}
+
+trait Stream: io::Read + io::Write + Send {}
+
+impl<T: io::Read + io::Write + Send> Stream for T {}
+
+#[cfg(unix)]
+fn open_stream(socket_path: &Path, timeout: Duration) -> io::Result<Box<dyn Stream>> {
+    let stream = std::os::unix::net::UnixStream::connect(socket_path)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    Ok(Box::new(stream))
+}
+
+#[cfg(not(unix))]
+fn open_stream(_socket_path: &Path, _timeout: Duration) -> io::Result<Box<dyn Stream>> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "Remote signer is only supported on Unix"))
+}
+
+impl ValidatorSigner for RemoteValidatorSigner {
+    fn validator_id(&self) -> &AccountId {
+        &self.account_id
+    }
+
+    fn public_key(&self) -> PublicKey {
+        self.public_key.clone()
+    }
+
+    fn sign_telemetry(
+        &self,
+        info: &TelemetryInfo,
+    ) -> Result<serde_json::Value, ValidatorSignerError> {
+        let mut value = serde_json::to_value(info).expect("Telemetry must serialize to JSON");
+        let content = serde_json::to_string(&value).expect("Telemetry must serialize to JSON");
+        value["signature"] = self.sign(SignRequest::Telemetry { content })?.to_string().into();
+        Ok(value)
+    }
+
+    fn sign_block_header_parts(
+        &self,
+        prev_hash: CryptoHash,
+        inner_lite: &[u8],
+        inner_rest: &[u8],
+    ) -> Result<(CryptoHash, Signature), ValidatorSignerError> {
+        let hash = BlockHeader::compute_hash(prev_hash, inner_lite, inner_rest);
+        let signature = self.sign(SignRequest::BlockHeader {
+            prev_hash,
+            inner_lite: inner_lite.to_vec(),
+            inner_rest: inner_rest.to_vec(),
+        })?;
+        Ok((hash, signature))
+    }
+
+    fn sign_chunk_header(
+        &self,
+        header: &ChunkHeaderToSign,
+        epoch_id: &EpochId,
+    ) -> Result<Signature, ValidatorSignerError> {
+        self.sign(SignRequest::ChunkHeader { header: header.clone(), epoch_id: epoch_id.clone() })
+    }
+
+    fn sign_approval(
+        &self,
+        inner: &ApprovalInner,
+        target_height: BlockHeight,
+        epoch_id: &EpochId,
+    ) -> Result<Signature, ValidatorSignerError> {
+        self.sign(SignRequest::Approval {
+            inner: inner.clone(),
+            target_height,
+            epoch_id: epoch_id.clone(),
+        })
+    }
+
+    fn sign_challenge(
+        &self,
+        challenge_body: &ChallengeBody,
+    ) -> Result<(CryptoHash, Signature), ValidatorSignerError> {
+        let hash = CryptoHash::hash_borsh(challenge_body);
+        let signature =
+            self.sign(SignRequest::Challenge { challenge_body: Box::new(challenge_body.clone()) })?;
+        Ok((hash, signature))
+    }
+
+    fn sign_account_announce(
+        &self,
+        account_id: &AccountId,
+        peer_id: &PeerId,
+        epoch_id: &EpochId,
+    ) -> Result<Signature, ValidatorSignerError> {
+        self.sign(SignRequest::AccountAnnounce {
+            account_id: account_id.clone(),
+            peer_id: peer_id.clone(),
+            epoch_id: epoch_id.clone(),
+        })
+    }
+
+    fn sign_account_key_payload(
+        &self,
+        proto_bytes: &[u8],
+    ) -> Result<Signature, ValidatorSignerError> {
+        self.sign(SignRequest::AccountKeyPayload { proto_bytes: proto_bytes.to_vec() })
+    }
+
+    fn compute_vrf_with_proof(
+        &self,
+        data: &[u8],
+    ) -> Result<(near_crypto::vrf::Value, near_crypto::vrf::Proof), ValidatorSignerError> {
+        match self.sign_response(SignRequest::Vrf { data: data.to_vec() })? {
+            SignResponse::Vrf { value, proof } => Ok((value, proof)),
+            response => Err(unexpected_response(response)),
+        }
+    }
+
+    fn write_to_file(&self, _path: &Path) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "The key is held by the remote signer"))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::{RemoteSignerConfig, RemoteValidatorSigner};
+    use crate::protocol::{read_message, write_message, SignRequest, SignResponse};
+    use near_crypto::{KeyType, SecretKey};
+    use near_primitives::block::ApprovalInner;
+    use near_primitives::hash::CryptoHash;
+    use near_primitives::types::EpochId;
+    use near_primitives::validator_signer::{ValidatorSigner, ValidatorSignerError};
+    use std::os::unix::net::UnixListener;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_unresponsive_signer() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket_path = dir.path().join("signer.sock");
+        let listener = UnixListener::bind(&socket_path).unwrap();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            assert_eq!(read_message::<SignRequest>(&mut stream).unwrap(), SignRequest::GetInfo);
+            let public_key = SecretKey::from_seed(KeyType::ED25519, "test").public_key();
+            let info = SignResponse::Info { account_id: "test".parse().unwrap(), public_key };
+            write_message(&mut stream, &info).unwrap();
+            // Keeps the connection open without ever answering.
+            while read_message::<SignRequest>(&mut stream).is_ok() {}
+        });
+
+        let timeout = Duration::from_millis(100);
+        let signer =
+            RemoteValidatorSigner::connect(RemoteSignerConfig { socket_path, timeout }).unwrap();
+        assert_eq!(signer.validator_id().as_str(), "test");
+        let endorsement = ApprovalInner::Endorsement(CryptoHash::default());
+        let start = Instant::now();
+        assert!(matches!(
+            signer.sign_approval(&endorsement, 10, &EpochId::default()),
+            Err(ValidatorSignerError::Unavailable(_))
+        ));
+        assert!(start.elapsed() < 10 * timeout);
+    }
+}
@@ -0,0 +1,471 @@
+//! Persistent record of what the validator has signed, which keeps a node
+//! restored from an old snapshot or running twice by mistake from signing
+//! conflicting blocks, chunks or approvals.
+
+use borsh::BorshDeserialize;
+use near_primitives::block::{Approval, ApprovalInner, BlockHeader};
+use near_primitives::block_header::BlockHeaderInnerLite;
+use near_primitives::hash::{hash, CryptoHash};
+use near_primitives::sharding::ChunkHeaderToSign;
+use near_primitives::types::{BlockHeight, EpochId, ShardId};
+use near_primitives::validator_signer::ValidatorSignerError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Number of the most recent epochs kept in the history.
+const NUM_KEPT_EPOCHS: usize = 4;
+
+/// Height and hash of a signed message.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignedMessage {
+    pub height: BlockHeight,
+    pub hash: CryptoHash,
+}
+
+/// Last message of each kind signed in an epoch.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EpochSigningHistory {
+    pub epoch_id: EpochId,
+    pub block: Option<SignedMessage>,
+    /// Target height and hash of the signed data of the last approval.
+    pub approval: Option<SignedMessage>,
+    #[serde(default)]
+    pub chunks: BTreeMap<ShardId, SignedMessage>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum MessageKind {
+    Block,
+    Approval,
+    Chunk(ShardId),
+}
+
+impl fmt::Display for MessageKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Block => write!(f, "Block"),
+            Self::Approval => write!(f, "Approval"),
+            Self::Chunk(shard_id) => write!(f, "Chunk of shard {shard_id}"),
+        }
+    }
+}
+
+impl EpochSigningHistory {
+    fn new(epoch_id: EpochId) -> Self {
+        Self { epoch_id, block: None, approval: None, chunks: BTreeMap::new() }
+    }
+
+    fn last(&self, kind: MessageKind) -> Option<SignedMessage> {
+        match kind {
+            MessageKind::Block => self.block,
+            MessageKind::Approval => self.approval,
+            MessageKind::Chunk(shard_id) => self.chunks.get(&shard_id).copied(),
+        }
+    }
+
+    fn set_last(&mut self, kind: MessageKind, message: SignedMessage) {
+        match kind {
+            MessageKind::Block => self.block = Some(message),
+            MessageKind::Approval => self.approval = Some(message),
+            MessageKind::Chunk(shard_id) => {
+                self.chunks.insert(shard_id, message);
+            }
+        }
+    }
+
+    fn max_height(&self) -> BlockHeight {
+        self.block
+            .iter()
+            .chain(self.approval.iter())
+            .chain(self.chunks.values())
+            .map(|message| message.height)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Keeps the latest message of each kind.
+    fn merge(&mut self, other: EpochSigningHistory) {
+        merge_message(&mut self.block, other.block);
+        merge_message(&mut self.approval, other.approval);
+        for (shard_id, message) in other.chunks {
+            let last = self.chunks.entry(shard_id).or_insert(message);
+            if last.height < message.height {
+                *last = message;
+            }
+        }
+    }
+}
+
+/// Last signed messages of each kind in the most recent epochs.  A validator
+/// only ever signs blocks, chunks of a shard and approvals at growing heights,
+/// so the last ones are enough to detect conflicts.  Since forks can put the
+/// same height in different epochs, messages are checked against all of them.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct SigningHistory {
+    /// Ordered by the largest height signed in the epoch.
+    pub epochs: Vec<EpochSigningHistory>,
+}
+
+impl SigningHistory {
+    /// Reads the history, which is empty if the file doesn't exist yet.
+    pub fn from_file(path: &Path) -> io::Result<Self> {
+        match Self::from_existing_file(path) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            result => result,
+        }
+    }
+
+    /// Reads the history, failing if the file doesn't exist.  Used for files
+    /// given by the operator, where a missing file is a mistake rather than
+    /// a fresh validator.
+    pub fn from_existing_file(path: &Path) -> io::Result<Self> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+
+    /// Replaces the file atomically, so that a crash never leaves a partially
+    /// written history behind.  Returns once the new history is durable,
+    /// including the rename, which would otherwise be lost on power failure
+    /// and bring back the previous history.
+    pub fn write_to_file(&self, path: &Path) -> io::Result<()> {
+        let tmp_path = path.with_extension("tmp");
+        {
+            let file = std::fs::File::create(&tmp_path)?;
+            serde_json::to_writer_pretty(&file, self)?;
+            file.sync_all()?;
+        }
+        std::fs::rename(&tmp_path, path)?;
+        sync_parent_dir(path)
+    }
+
+    /// Merges history exported on another machine, keeping the latest
+    /// message of each kind in every epoch.
+    pub fn merge(&mut self, other: SigningHistory) {
+        for other in other.epochs {
+            match self.epochs.iter_mut().find(|epoch| epoch.epoch_id == other.epoch_id) {
+                Some(epoch) => epoch.merge(other),
+                None => self.epochs.push(other),
+            }
+        }
+        self.prune();
+    }
+
+    /// Records the message unless it conflicts with the last one of its kind
+    /// in any epoch.  Signing the same message again is allowed.
+    fn record(
+        &mut self,
+        epoch_id: &EpochId,
+        kind: MessageKind,
+        height: BlockHeight,
+        hash: CryptoHash,
+    ) -> Result<(), String> {
+        for epoch in &self.epochs {
+            match epoch.last(kind) {
+                Some(last) if height < last.height => {
+                    return Err(format!(
+                        "{kind} at height {height} is below the height {} signed in epoch {:?}",
+                        last.height, epoch.epoch_id
+                    ));
+                }
+                Some(last) if height == last.height && hash != last.hash => {
+                    return Err(format!(
+                        "{kind} conflicts with {} signed at height {height} in epoch {:?}",
+                        last.hash, epoch.epoch_id
+                    ));
+                }
+                _ => {}
+            }
+        }
+        let message = SignedMessage { height, hash };
+        match self.epochs.iter_mut().find(|epoch| &epoch.epoch_id == epoch_id) {
+            Some(epoch) => epoch.set_last(kind, message),
+            None => {
+                let mut epoch = EpochSigningHistory::new(epoch_id.clone());
+                epoch.set_last(kind, message);
+                self.epochs.push(epoch);
+            }
+        }
+        self.prune();
+        Ok(())
+    }
+
+    fn prune(&mut self) {
+        self.epochs.sort_by_key(|epoch| epoch.max_height());
+        let num_pruned = self.epochs.len().saturating_sub(NUM_KEPT_EPOCHS);
+        self.epochs.drain(..num_pruned);
+    }
+}
+
+/// Persists the directory entries of the file's directory.  Directories
+/// can't be opened as files on Windows, where renames are durable anyway.
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    std::fs::File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+fn merge_message(last: &mut Option<SignedMessage>, other: Option<SignedMessage>) {
+    if let Some(other) = other {
+        if last.map_or(true, |last| last.height < other.height) {
+            *last = Some(other);
+        }
+    }
+}
+
+/// Signing history persisted to a file, which is updated before any block,
+/// chunk or approval gets signed.
+pub struct SafetyChecker {
+    path: PathBuf,
+    history: SigningHistory,
+}
+
+impl SafetyChecker {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Ok(Self { path: path.to_path_buf(), history: SigningHistory::from_file(path)? })
+    }
+
+    pub fn history(&self) -> &SigningHistory {
+        &self.history
+    }
+
+    /// Records the block about to be signed.  Signing the same block again is
+    /// allowed so that the node can retry after a failure.
+    pub fn check_block(
+        &mut self,
+        prev_hash: CryptoHash,
+        inner_lite: &[u8],
+        inner_rest: &[u8],
+    ) -> Result<(), ValidatorSignerError> {
+        let inner_lite_parsed =
+            BlockHeaderInnerLite::try_from_slice(inner_lite).map_err(|err| {
+                ValidatorSignerError::Refused(format!("malformed block header: {err}"))
+            })?;
+        let hash = BlockHeader::compute_hash(prev_hash, inner_lite, inner_rest);
+        self.update(&inner_lite_parsed.epoch_id, MessageKind::Block, inner_lite_parsed.height, hash)
+    }
+
+    /// Records the chunk about to be signed, see `check_block`.
+    pub fn check_chunk(
+        &mut self,
+        header: &ChunkHeaderToSign,
+        epoch_id: &EpochId,
+    ) -> Result<(), ValidatorSignerError> {
+        let kind = MessageKind::Chunk(header.shard_id());
+        self.update(epoch_id, kind, header.height_created(), header.chunk_hash().0)
+    }
+
+    /// Records the approval about to be signed, see `check_block`.
+    pub fn check_approval(
+        &mut self,
+        inner: &ApprovalInner,
+        target_height: BlockHeight,
+        epoch_id: &EpochId,
+    ) -> Result<(), ValidatorSignerError> {
+        let hash = hash(&Approval::get_data_for_sig(inner, target_height));
+        self.update(epoch_id, MessageKind::Approval, target_height, hash)
+    }
+
+    /// Persists the updated history before returning, so that the message is
+    /// never signed without being recorded.
+    fn update(
+        &mut self,
+        epoch_id: &EpochId,
+        kind: MessageKind,
+        height: BlockHeight,
+        hash: CryptoHash,
+    ) -> Result<(), ValidatorSignerError> {
+        let mut history = self.history.clone();
+        history.record(epoch_id, kind, height, hash).map_err(ValidatorSignerError::Refused)?;
+        if history != self.history {
+            history.write_to_file(&self.path).map_err(|err| {
+                ValidatorSignerError::Unavailable(format!(
+                    "failed to persist signing history to {}: {err}",
+                    self.path.display()
+                ))
+            })?;
+            self.history = history;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::{
+        EpochSigningHistory, SafetyChecker, SignedMessage, SigningHistory, NUM_KEPT_EPOCHS,
+    };
+    use borsh::BorshSerialize;
+    use near_primitives::block::ApprovalInner;
+    use near_primitives::block_header::BlockHeaderInnerLite;
+    use near_primitives::hash::CryptoHash;
+    use near_primitives::sharding::{ChunkHeaderToSign, ShardChunkHeaderInnerV1};
+    use near_primitives::types::{BlockHeight, EpochId, ShardId};
+
+    pub(crate) fn epoch_id(seed: &[u8]) -> EpochId {
+        EpochId(CryptoHash::hash_bytes(seed))
+    }
+
+    pub(crate) fn block_inner_lite(height: BlockHeight, epoch_id: EpochId, seed: &[u8]) -> Vec<u8> {
+        BlockHeaderInnerLite {
+            height,
+            epoch_id,
+            next_epoch_id: EpochId::default(),
+            prev_state_root: CryptoHash::hash_bytes(seed),
+            outcome_root: CryptoHash::default(),
+            timestamp: 0,
+            next_bp_hash: CryptoHash::default(),
+            block_merkle_root: CryptoHash::default(),
+        }
+        .try_to_vec()
+        .unwrap()
+    }
+
+    pub(crate) fn chunk_header(
+        height_created: BlockHeight,
+        shard_id: ShardId,
+        seed: &[u8],
+    ) -> ChunkHeaderToSign {
+        ChunkHeaderToSign::V1(ShardChunkHeaderInnerV1 {
+            prev_block_hash: CryptoHash::hash_bytes(seed),
+            prev_state_root: CryptoHash::default(),
+            outcome_root: CryptoHash::default(),
+            encoded_merkle_root: CryptoHash::default(),
+            encoded_length: 0,
+            height_created,
+            shard_id,
+            gas_used: 0,
+            gas_limit: 0,
+            balance_burnt: 0,
+            outgoing_receipts_root: CryptoHash::default(),
+            tx_root: CryptoHash::default(),
+            validator_proposals: vec![],
+        })
+    }
+
+    #[test]
+    fn test_block_conflicts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("signing_history.json");
+        let prev_hash = CryptoHash::hash_bytes(b"prev");
+        let (e1, e2) = (epoch_id(b"e1"), epoch_id(b"e2"));
+        assert!(SigningHistory::from_existing_file(&path).is_err());
+        let mut checker = SafetyChecker::open(&path).unwrap();
+        checker.check_block(prev_hash, &block_inner_lite(10, e1.clone(), b"a"), &[]).unwrap();
+        checker.check_block(prev_hash, &block_inner_lite(10, e1.clone(), b"a"), &[]).unwrap();
+        assert!(checker
+            .check_block(prev_hash, &block_inner_lite(10, e1.clone(), b"b"), &[])
+            .is_err());
+        assert!(checker
+            .check_block(prev_hash, &block_inner_lite(10, e1.clone(), b"a"), &[1])
+            .is_err());
+        assert!(checker
+            .check_block(prev_hash, &block_inner_lite(9, e1.clone(), b"a"), &[])
+            .is_err());
+        assert!(checker.check_block(prev_hash, &[1, 2, 3], &[]).is_err());
+        // A fork can put the same height into another epoch, which is still a conflict.
+        assert!(checker
+            .check_block(prev_hash, &block_inner_lite(10, e2.clone(), b"b"), &[])
+            .is_err());
+        checker.check_block(prev_hash, &block_inner_lite(11, e2.clone(), b"b"), &[]).unwrap();
+
+        let mut checker = SafetyChecker::open(&path).unwrap();
+        assert_eq!(checker.history(), &SigningHistory::from_existing_file(&path).unwrap());
+        assert_eq!(checker.history().epochs.len(), 2);
+        assert!(checker.check_block(prev_hash, &block_inner_lite(11, e2, b"a"), &[]).is_err());
+    }
+
+    #[test]
+    fn test_chunk_conflicts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("signing_history.json");
+        let epoch_id = epoch_id(b"e1");
+        let mut checker = SafetyChecker::open(&path).unwrap();
+        checker.check_chunk(&chunk_header(10, 0, b"a"), &epoch_id).unwrap();
+        checker.check_chunk(&chunk_header(10, 0, b"a"), &epoch_id).unwrap();
+        assert!(checker.check_chunk(&chunk_header(10, 0, b"b"), &epoch_id).is_err());
+        assert!(checker.check_chunk(&chunk_header(9, 0, b"a"), &epoch_id).is_err());
+        // Chunks of different shards don't conflict.
+        checker.check_chunk(&chunk_header(10, 1, b"b"), &epoch_id).unwrap();
+        checker.check_chunk(&chunk_header(11, 0, b"b"), &epoch_id).unwrap();
+
+        let mut checker = SafetyChecker::open(&path).unwrap();
+        assert!(checker.check_chunk(&chunk_header(11, 0, b"a"), &epoch_id).is_err());
+        assert!(checker.check_chunk(&chunk_header(10, 1, b"a"), &epoch_id).is_err());
+    }
+
+    #[test]
+    fn test_kept_epochs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("signing_history.json");
+        let endorsement = ApprovalInner::Endorsement(CryptoHash::hash_bytes(b"a"));
+        let mut checker = SafetyChecker::open(&path).unwrap();
+        for height in 0..2 * NUM_KEPT_EPOCHS as BlockHeight {
+            checker.check_approval(&endorsement, height, &epoch_id(&height.to_le_bytes())).unwrap();
+        }
+        let epochs = &checker.history().epochs;
+        assert_eq!(epochs.len(), NUM_KEPT_EPOCHS);
+        assert_eq!(epochs.last().unwrap().approval.unwrap().height, 2 * NUM_KEPT_EPOCHS as u64 - 1);
+    }
+
+    #[test]
+    fn test_merge() {
+        let message =
+            |height, seed: &[u8]| SignedMessage { height, hash: CryptoHash::hash_bytes(seed) };
+        let (e1, e2) = (epoch_id(b"e1"), epoch_id(b"e2"));
+        let mut history = SigningHistory {
+            epochs: vec![EpochSigningHistory {
+                epoch_id: e1.clone(),
+                block: Some(message(10, b"a")),
+                approval: Some(message(12, b"b")),
+                chunks: [(0, message(10, b"c"))].into_iter().collect(),
+            }],
+        };
+        history.merge(SigningHistory {
+            epochs: vec![
+                EpochSigningHistory {
+                    epoch_id: e1.clone(),
+                    block: Some(message(11, b"d")),
+                    approval: Some(message(9, b"e")),
+                    chunks: [(0, message(8, b"f")), (1, message(10, b"g"))].into_iter().collect(),
+                },
+                EpochSigningHistory {
+                    epoch_id: e2.clone(),
+                    block: Some(message(20, b"h")),
+                    approval: None,
+                    chunks: Default::default(),
+                },
+            ],
+        });
+        assert_eq!(
+            history,
+            SigningHistory {
+                epochs: vec![
+                    EpochSigningHistory {
+                        epoch_id: e1,
+                        block: Some(message(11, b"d")),
+                        approval: Some(message(12, b"b")),
+                        chunks: [(0, message(10, b"c")), (1, message(10, b"g"))]
+                            .into_iter()
+                            .collect(),
+                    },
+                    EpochSigningHistory {
+                        epoch_id: e2,
+                        block: Some(message(20, b"h")),
+                        approval: None,
+                        chunks: Default::default(),
+                    },
+                ],
+            }
+        );
+    }
+}
@@ -14,8 +14,9 @@ use near_network::test_utils::MockPeerManagerAdapter;
 use near_network::types::NetworkRequests;
 use near_o11y::testonly::init_test_logger;
 use near_primitives::challenge::{
-    BlockDoubleSign, Challenge, ChallengeBody, ChunkProofs, MaybeEncodedShardChunk, StateItem,
+    BlockDoubleSign, Challenge, ChallengeBody, ChunkProofs, MaybeEncodedShardChunk,
 };
+use near_primitives::checked_feature;
 use near_primitives::hash::CryptoHash;
 use near_primitives::merkle::{merklize, MerklePath, PartialMerkleTree};
 use near_primitives::num_rational::Ratio;
@@ -53,7 +54,7 @@ fn test_block_with_challenges() {
             left_block_header: genesis.header().try_to_vec().unwrap(),
             right_block_header: genesis.header().try_to_vec().unwrap(),
         });
-        let challenge = Challenge::produce(challenge_body, &*signer);
+        let challenge = Challenge::produce(challenge_body, &*signer).unwrap();
         body.challenges = vec![challenge];
         block.mut_header().get_mut().inner_rest.challenges_root =
             Block::compute_challenges_root(&body.challenges);
@@ -72,7 +73,9 @@ fn test_invalid_chunk_state() {
         .runtime_adapters(create_nightshade_runtimes(&genesis, 1))
         .build();
     env.produce_block(0, 1);
-    let block_hash = env.clients[0].chain.get_block_hash_by_height(1).unwrap();
+    // Block 1 doesn't contain a new chunk, so a challenge can only be built on top of block 2.
+    env.produce_block(0, 2);
+    let block_hash = env.clients[0].chain.get_block_hash_by_height(2).unwrap();
 
     {
         let mut chunk_extra = ChunkExtra::clone(
@@ -86,7 +89,7 @@ fn test_invalid_chunk_state() {
         store_update.commit().unwrap();
     }
 
-    let block = env.clients[0].produce_block(2).unwrap().unwrap();
+    let block = env.clients[0].produce_block(3).unwrap().unwrap();
     let result = env.clients[0].process_block_test(block.into(), Provenance::NONE);
     assert_matches!(result.unwrap_err(), Error::InvalidChunkState(_));
 }
@@ -124,7 +127,8 @@ fn test_verify_block_double_sign_challenge() {
         *b1.header().next_bp_hash(),
         block_merkle_tree.root(),
         None,
-    );
+    )
+    .unwrap();
     let epoch_id = b1.header().epoch_id().clone();
     let valid_challenge = Challenge::produce(
         ChallengeBody::BlockDoubleSign(BlockDoubleSign {
@@ -132,12 +136,19 @@ fn test_verify_block_double_sign_challenge() {
             right_block_header: b1.header().try_to_vec().unwrap(),
         }),
         &signer,
-    );
+    )
+    .unwrap();
     let runtime_adapter = env.clients[1].chain.runtime_adapter.clone();
     assert_eq!(
-        &validate_challenge(&*runtime_adapter, &epoch_id, genesis.hash(), &valid_challenge)
-            .unwrap()
-            .0,
+        &validate_challenge(
+            env.clients[1].chain.store(),
+            &*runtime_adapter,
+            &epoch_id,
+            genesis.hash(),
+            &valid_challenge,
+        )
+        .unwrap()
+        .0,
         if b1.hash() > b2.hash() { b1.hash() } else { b2.hash() }
     );
     let invalid_challenge = Challenge::produce(
@@ -146,10 +157,17 @@ fn test_verify_block_double_sign_challenge() {
             right_block_header: b1.header().try_to_vec().unwrap(),
         }),
         &signer,
-    );
+    )
+    .unwrap();
     let runtime_adapter = env.clients[1].chain.runtime_adapter.clone();
-    assert!(validate_challenge(&*runtime_adapter, &epoch_id, genesis.hash(), &invalid_challenge,)
-        .is_err());
+    assert!(validate_challenge(
+        env.clients[1].chain.store(),
+        &*runtime_adapter,
+        &epoch_id,
+        genesis.hash(),
+        &invalid_challenge,
+    )
+    .is_err());
     let b3 = env.clients[0].produce_block(3).unwrap().unwrap();
     let invalid_challenge = Challenge::produce(
         ChallengeBody::BlockDoubleSign(BlockDoubleSign {
@@ -157,10 +175,17 @@ fn test_verify_block_double_sign_challenge() {
             right_block_header: b3.header().try_to_vec().unwrap(),
         }),
         &signer,
-    );
+    )
+    .unwrap();
     let runtime_adapter = env.clients[1].chain.runtime_adapter.clone();
-    assert!(validate_challenge(&*runtime_adapter, &epoch_id, genesis.hash(), &invalid_challenge,)
-        .is_err());
+    assert!(validate_challenge(
+        env.clients[1].chain.store(),
+        &*runtime_adapter,
+        &epoch_id,
+        genesis.hash(),
+        &invalid_challenge,
+    )
+    .is_err());
 
     let result = env.clients[0].process_block_test(b2.into(), Provenance::SYNC);
     assert!(result.is_ok());
@@ -311,9 +336,11 @@ fn challenge(
             merkle_proof: merkle_paths[shard_id].clone(),
         }),
         &*env.clients[0].validator_signer.as_ref().unwrap().clone(),
-    );
+    )
+    .unwrap();
     let runtime_adapter = env.clients[0].chain.runtime_adapter.clone();
     validate_challenge(
+        env.clients[0].chain.store(),
         &*runtime_adapter,
         block.header().epoch_id(),
         block.header().prev_hash(),
@@ -371,6 +398,7 @@ fn test_verify_chunk_invalid_state_challenge() {
         &[],
         last_block.chunks()[0].outgoing_receipts_root(),
         CryptoHash::default(),
+        &EpochId::default(),
         &validator_signer,
         &mut rs,
         PROTOCOL_VERSION,
@@ -422,7 +450,8 @@ fn test_verify_chunk_invalid_state_challenge() {
         *last_block.header().next_bp_hash(),
         block_merkle_tree.root(),
         None,
-    );
+    )
+    .unwrap();
 
     let challenge_body =
         client.chain.create_chunk_state_challenge(&last_block, &block, &block.chunks()[0]).unwrap();
@@ -431,51 +460,30 @@ fn test_verify_chunk_invalid_state_challenge() {
         let merkle_proofs = Block::compute_chunk_headers_root(block.chunks().iter()).1;
         assert_eq!(prev_merkle_proofs[0], challenge_body.prev_merkle_proof);
         assert_eq!(merkle_proofs[0], challenge_body.merkle_proof);
-        // TODO (#6316): enable storage proof generation
-        assert_eq!(challenge_body.partial_state.0, Vec::<StateItem>::new());
-        // assert_eq!(
-        //     challenge_body.partial_state.0,
-        //     vec![
-        //         vec![
-        //             1, 5, 0, 10, 178, 228, 151, 124, 13, 70, 6, 146, 31, 193, 111, 108, 60, 102,
-        //             227, 106, 220, 133, 45, 144, 104, 255, 30, 155, 129, 215, 15, 43, 202, 26, 122,
-        //             171, 30, 7, 228, 175, 99, 17, 113, 5, 94, 136, 200, 39, 136, 37, 110, 166, 241,
-        //             148, 128, 55, 131, 173, 97, 98, 201, 68, 82, 244, 223, 70, 86, 161, 5, 0, 0, 0,
-        //             0, 0, 0
-        //         ],
-        //         vec![
-        //             3, 1, 0, 0, 0, 16, 49, 233, 115, 11, 86, 10, 193, 50, 45, 253, 137, 126, 230,
-        //             236, 254, 86, 230, 148, 94, 141, 44, 46, 130, 154, 189, 73, 179, 223, 178, 17,
-        //             133, 232, 213, 5, 0, 0, 0, 0, 0, 0
-        //         ]
-        //     ],
-        // );
+        assert!(!challenge_body.partial_state.0.is_empty());
     }
-    let challenge =
-        Challenge::produce(ChallengeBody::ChunkState(challenge_body), &validator_signer);
+    let challenge = Challenge::produce(
+        ChallengeBody::ChunkStateV2(Box::new(challenge_body)),
+        &validator_signer,
+    )
+    .unwrap();
     let runtime_adapter = client.chain.runtime_adapter.clone();
-    // Invalidate chunk state challenges because they are not supported yet.
-    // TODO (#2445): Enable challenges when they are working correctly.
-    assert_matches!(
-        validate_challenge(
-            &*runtime_adapter,
-            block.header().epoch_id(),
-            block.header().prev_hash(),
-            &challenge,
-        )
-        .unwrap_err(),
-        Error::MaliciousChallenge
+    let result = validate_challenge(
+        client.chain.store(),
+        &*runtime_adapter,
+        block.header().epoch_id(),
+        block.header().prev_hash(),
+        &challenge,
     );
-    // assert_eq!(
-    //     validate_challenge(
-    //         &*runtime_adapter,
-    //         block.header().epoch_id(),
-    //         block.header().prev_hash(),
-    //         &challenge,
-    //     )
-    //     .unwrap(),
-    //     (*block.hash(), vec!["test0".parse().unwrap()])
-    // );
+    if checked_feature!(
+        "protocol_feature_chunk_state_challenges",
+        ChunkStateChallenges,
+        PROTOCOL_VERSION
+    ) {
+        assert_eq!(result.unwrap(), (*block.hash(), vec!["test0".parse().unwrap()]));
+    } else {
+        assert_matches!(result.unwrap_err(), Error::MaliciousChallenge);
+    }
 
     // Process the block with invalid chunk and make sure it's marked as invalid at the end.
     // And the same challenge created and sent out.
@@ -550,8 +558,99 @@ fn test_receive_invalid_chunk_as_chunk_producer() {
 }
 
 /// Receive invalid state transition in chunk as a validator / non-producer.
+/// test0 tracks the shard and challenges the chunk with invalid state root. test1 doesn't track
+/// the shard and never sees the chunks, so it doesn't have their incoming receipts. It accepts the
+/// block and rolls it back once it receives the challenge, which carries the receipts.
 #[test]
-fn test_receive_invalid_chunk_as_validator() {}
+#[cfg(feature = "test_features")]
+fn test_receive_invalid_chunk_as_validator() {
+    init_test_logger();
+    let mut env =
+        TestEnv::builder(ChainGenesis::test()).clients_count(2).validator_seats(1).build();
+    for height in 1..4 {
+        env.clients[0].produce_invalid_state_root_chunks = height == 3;
+        let (chunk, merkle_paths, receipts, block) = create_chunk(&mut env.clients[0], None, None);
+        let client = &mut env.clients[0];
+        client
+            .persist_and_distribute_encoded_chunk(
+                chunk,
+                merkle_paths,
+                receipts,
+                client.validator_signer.as_ref().unwrap().validator_id().clone(),
+            )
+            .unwrap();
+        let result =
+            client.process_block_test_no_produce_chunk(block.clone().into(), Provenance::NONE);
+        if height < 3 {
+            result.unwrap();
+        } else {
+            assert_matches!(result.unwrap_err(), Error::InvalidChunkState(_));
+        }
+        env.clients[1].process_block_test_no_produce_chunk(block.into(), Provenance::NONE).unwrap();
+    }
+    assert_eq!(env.clients[0].chain.head().unwrap().height, 2);
+    assert_eq!(env.clients[1].chain.head().unwrap().height, 3);
+    let invalid_block_hash = env.clients[1].chain.head().unwrap().last_block_hash;
+    for height in 1..3 {
+        let block_hash = env.clients[1].chain.get_block_hash_by_height(height).unwrap();
+        assert!(env.clients[0].chain.store().get_incoming_receipts(&block_hash, 0).is_ok());
+        assert!(env.clients[1].chain.store().get_incoming_receipts(&block_hash, 0).is_err());
+    }
+
+    // A challenge against the valid chunk in block 2 which hides the incoming receipts of the
+    // chunk in block 1 must not be accepted.
+    let block1 = env.clients[0].chain.get_block_by_height(1).unwrap();
+    let block2 = env.clients[0].chain.get_block_by_height(2).unwrap();
+    let mut forged_chunk_state = env.clients[0]
+        .chain
+        .create_chunk_state_challenge(&block1, &block2, &block2.chunks()[0])
+        .unwrap();
+    assert!(!forged_chunk_state.prev_chunk_incoming_receipts.is_empty());
+    forged_chunk_state.prev_chunk_incoming_receipts.clear();
+    let forged_challenge = Challenge::produce(
+        ChallengeBody::ChunkStateV2(Box::new(forged_chunk_state)),
+        &*env.clients[0].validator_signer.as_ref().unwrap().clone(),
+    )
+    .unwrap();
+    let runtime_adapter = env.clients[1].chain.runtime_adapter.clone();
+    assert_matches!(
+        validate_challenge(
+            env.clients[1].chain.store(),
+            &*runtime_adapter,
+            block2.header().epoch_id(),
+            block2.header().prev_hash(),
+            &forged_challenge,
+        )
+        .unwrap_err(),
+        Error::MaliciousChallenge
+    );
+    env.clients[1].process_challenge(forged_challenge).unwrap();
+    assert_eq!(env.clients[1].chain.head().unwrap().height, 3);
+    assert!(!env.clients[1].chain.store().is_block_challenged(block2.hash()).unwrap());
+
+    let challenge = loop {
+        match env.network_adapters[0].pop().map(|request| request.as_network_requests()) {
+            Some(NetworkRequests::Challenge(challenge)) => break challenge,
+            Some(_) => {}
+            None => panic!("test0 didn't send out a challenge"),
+        }
+    };
+    env.clients[1].process_challenge(challenge.clone()).unwrap();
+    if checked_feature!(
+        "protocol_feature_chunk_state_challenges",
+        ChunkStateChallenges,
+        PROTOCOL_VERSION
+    ) {
+        assert_matches!(challenge.body, ChallengeBody::ChunkStateV2(_));
+        assert_eq!(env.clients[1].chain.head().unwrap().height, 2);
+        assert!(env.clients[1].chain.store().is_block_challenged(&invalid_block_hash).unwrap());
+    } else {
+        // Older nodes can't decode the incoming receipts, so they aren't sent.
+        assert_matches!(challenge.body, ChallengeBody::ChunkState(_));
+        assert_eq!(env.clients[1].chain.head().unwrap().height, 3);
+        assert!(!env.clients[1].chain.store().is_block_challenged(&invalid_block_hash).unwrap());
+    }
+}
 
 /// Receive two different chunks from the same chunk producer.
 #[test]
@@ -580,7 +679,8 @@ fn test_block_challenge() {
             merkle_proof: merkle_paths[shard_id as usize].clone(),
         }),
         &*env.clients[0].validator_signer.as_ref().unwrap().clone(),
-    );
+    )
+    .unwrap();
     env.clients[0].process_challenge(challenge.clone()).unwrap();
     env.produce_block(0, 2);
     assert_eq!(env.clients[0].chain.get_block_by_height(2).unwrap().challenges(), &[challenge]);
@@ -640,11 +740,13 @@ fn test_fishermen_challenge() {
     let challenge = Challenge::produce(
         challenge_body.clone(),
         &*env.clients[1].validator_signer.as_ref().unwrap().clone(),
-    );
+    )
+    .unwrap();
     let challenge1 = Challenge::produce(
         challenge_body,
         &*env.clients[2].validator_signer.as_ref().unwrap().clone(),
-    );
+    )
+    .unwrap();
     assert!(env.clients[0].process_challenge(challenge1).is_err());
     env.clients[0].process_challenge(challenge.clone()).unwrap();
     env.produce_block(0, 12);
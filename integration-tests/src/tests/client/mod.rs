@@ -11,3 +11,6 @@ mod sandbox;
 mod sharding_upgrade;
 #[cfg(feature = "test_features")]
 mod shards_manager;
+mod state_transition_proofs;
+mod trace_receipt;
+mod validator_key_rotation;
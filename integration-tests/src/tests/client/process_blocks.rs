@@ -408,7 +408,8 @@ fn receive_network_block() {
                 last_block.header.next_bp_hash,
                 block_merkle_tree.root(),
                 None,
-            );
+            )
+            .unwrap();
             client.do_send(
                 BlockResponse { block, peer_id: PeerInfo::random().id, was_requested: false }
                     .with_span_context(),
@@ -487,7 +488,8 @@ fn produce_block_with_approvals() {
                 last_block.header.next_bp_hash,
                 block_merkle_tree.root(),
                 None,
-            );
+            )
+            .unwrap();
             client.do_send(
                 BlockResponse {
                     block: block.clone(),
@@ -509,8 +511,10 @@ fn produce_block_with_approvals() {
                     *block.hash(),
                     block.header().height(),
                     10, // the height at which "test1" is producing
+                    &EpochId::default(),
                     &signer,
-                );
+                )
+                .unwrap();
                 client.do_send(BlockApproval(approval, PeerInfo::random().id).with_span_context());
             }
 
@@ -695,7 +699,8 @@ fn invalid_blocks_common(is_requested: bool) {
                 last_block.header.next_bp_hash,
                 block_merkle_tree.root(),
                 None,
-            );
+            )
+            .unwrap();
             // Send block with invalid chunk mask
             let mut block = valid_block.clone();
             block.mut_header().get_mut().inner_rest.chunk_mask = vec![];
@@ -1171,7 +1176,12 @@ fn test_invalid_approvals() {
             let account_id = AccountId::try_from(format!("test{}", i)).unwrap();
             Some(
                 create_test_signer(account_id.as_str())
-                    .sign_approval(&ApprovalInner::Endorsement(*genesis.hash()), 1),
+                    .sign_approval(
+                        &ApprovalInner::Endorsement(*genesis.hash()),
+                        1,
+                        &EpochId::default(),
+                    )
+                    .unwrap(),
             )
         })
         .collect();
@@ -3257,11 +3267,13 @@ fn test_header_version_downgrade() {
             BlockHeader::BlockHeaderV1(header) => {
                 let header = Arc::make_mut(header);
                 header.inner_rest.latest_protocol_version = PROTOCOL_VERSION;
-                let (hash, signature) = validator_signer.sign_block_header_parts(
-                    header.prev_hash,
-                    &header.inner_lite.try_to_vec().expect("Failed to serialize"),
-                    &header.inner_rest.try_to_vec().expect("Failed to serialize"),
-                );
+                let (hash, signature) = validator_signer
+                    .sign_block_header_parts(
+                        header.prev_hash,
+                        &header.inner_lite.try_to_vec().expect("Failed to serialize"),
+                        &header.inner_rest.try_to_vec().expect("Failed to serialize"),
+                    )
+                    .unwrap();
                 header.hash = hash;
                 header.signature = signature;
             }
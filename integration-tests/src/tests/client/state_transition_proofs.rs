@@ -0,0 +1,155 @@
+use assert_matches::assert_matches;
+use near_chain::{ChainGenesis, Error, Provenance};
+use near_chain_configs::Genesis;
+#[cfg(feature = "test_features")]
+use near_client::test_utils::create_chunk;
+use near_client::test_utils::TestEnv;
+use near_client::Client;
+use near_crypto::{InMemorySigner, KeyType};
+use near_o11y::testonly::init_test_logger;
+use near_primitives::challenge::ChunkStateTransitionProof;
+use near_primitives::network::PeerId;
+use near_primitives::shard_layout::ShardUId;
+use near_primitives::transaction::SignedTransaction;
+use nearcore::config::GenesisExt;
+
+use crate::tests::client::process_blocks::create_nightshade_runtimes;
+
+/// Sets up test0 as the only validator, which records state transition proofs of the chunks it
+/// applies, and test1, which doesn't track the shard. Produces blocks up to height 3 with a
+/// transaction applied in block 2.
+fn setup_env() -> TestEnv {
+    init_test_logger();
+    let genesis = Genesis::test(vec!["test0".parse().unwrap(), "test1".parse().unwrap()], 1);
+    let mut env = TestEnv::builder(ChainGenesis::new(&genesis))
+        .clients_count(2)
+        .runtime_adapters(create_nightshade_runtimes(&genesis, 2))
+        .record_state_transition_proofs(true)
+        .build();
+    let genesis_hash = *env.clients[0].chain.genesis().hash();
+    let signer = InMemorySigner::from_seed("test0".parse().unwrap(), KeyType::ED25519, "test0");
+    for height in 1..4 {
+        if height == 2 {
+            let tx = SignedTransaction::send_money(
+                1,
+                "test0".parse().unwrap(),
+                "test1".parse().unwrap(),
+                &signer,
+                100,
+                genesis_hash,
+            );
+            env.clients[0].process_tx(tx, false, false);
+        }
+        let block = env.clients[0].produce_block(height).unwrap().unwrap();
+        env.process_block(0, block.clone(), Provenance::PRODUCED);
+        env.process_block(1, block, Provenance::NONE);
+    }
+    let block3 = env.clients[0].chain.get_block_by_height(3).unwrap();
+    assert!(env.clients[1]
+        .chain
+        .get_chunk_extra(block3.hash(), &ShardUId::single_shard())
+        .is_err());
+    env
+}
+
+/// Passes `proof` received from a random peer to `client`, waits until the chunk is re-executed
+/// and returns whether the proof was valid. Returns `None` if the client ignored the proof.
+fn process_proof(client: &mut Client, proof: &ChunkStateTransitionProof) -> Option<bool> {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    if client
+        .process_chunk_state_transition_proof(
+            proof.clone(),
+            PeerId::random(),
+            Box::new(move |checked| sender.send(checked).unwrap()),
+        )
+        .is_err()
+    {
+        return Some(false);
+    }
+    let checked = receiver.recv().ok()?;
+    Some(client.on_chunk_state_transition_checked(checked).is_ok())
+}
+
+#[test]
+fn test_chunk_state_transition_proof() {
+    let mut env = setup_env();
+    let block2 = env.clients[0].chain.get_block_by_height(2).unwrap();
+    let block3 = env.clients[0].chain.get_block_by_height(3).unwrap();
+    let proof = env.clients[0]
+        .chain
+        .create_chunk_state_transition_proof(&block2, &block3.chunks()[0])
+        .unwrap()
+        .unwrap();
+    assert!(!proof.partial_state.0.is_empty());
+    assert!(!proof.prev_chunk_transactions.is_empty());
+    assert_ne!(block2.chunks()[0].prev_state_root(), block3.chunks()[0].prev_state_root());
+    let runtime_adapter = env.clients[1].runtime_adapter.clone();
+    assert!(env.clients[1]
+        .chain
+        .prepare_chunk_state_transition(&proof)
+        .unwrap()
+        .check(runtime_adapter.as_ref())
+        .unwrap());
+
+    // The proof doesn't contain all the nodes needed to apply the chunk.
+    let mut truncated_proof = proof.clone();
+    truncated_proof.partial_state.0.truncate(1);
+    assert_matches!(
+        env.clients[1]
+            .chain
+            .prepare_chunk_state_transition(&truncated_proof)
+            .unwrap()
+            .check(runtime_adapter.as_ref()),
+        Err(Error::InvalidStatePayload)
+    );
+    assert_eq!(process_proof(&mut env.clients[1], &truncated_proof), Some(false));
+    // test0 tracks the shard and ignores proofs.
+    assert_eq!(process_proof(&mut env.clients[0], &truncated_proof), None);
+    // A malformed proof doesn't prevent checking the correct one, which is checked only once.
+    assert_eq!(process_proof(&mut env.clients[1], &proof), Some(true));
+    assert_eq!(process_proof(&mut env.clients[1], &proof), None);
+
+    // The transactions don't match the previous chunk.
+    let mut wrong_transactions_proof = proof.clone();
+    wrong_transactions_proof.prev_chunk_transactions.clear();
+    assert_matches!(
+        env.clients[1].chain.prepare_chunk_state_transition(&wrong_transactions_proof).err(),
+        Some(Error::InvalidTransactions)
+    );
+
+    // The chunk isn't built on top of the block which includes the previous chunk.
+    let mut wrong_chunk_proof = proof;
+    wrong_chunk_proof.chunk_header = block2.chunks()[0].clone();
+    assert_matches!(
+        env.clients[1].chain.prepare_chunk_state_transition(&wrong_chunk_proof).err(),
+        Some(Error::InvalidChunk)
+    );
+}
+
+/// test0 produces a chunk which commits to a wrong state root, so test1 rejects its proof and
+/// stops including chunks from test0.
+#[test]
+#[cfg(feature = "test_features")]
+fn test_chunk_state_transition_proof_invalid_state_root() {
+    let mut env = setup_env();
+    let block3 = env.clients[0].chain.get_block_by_height(3).unwrap();
+    env.clients[0].produce_invalid_state_root_chunks = true;
+    let (chunk, _, _, _) = create_chunk(&mut env.clients[0], None, None);
+    let proof = env.clients[0]
+        .chain
+        .create_chunk_state_transition_proof(&block3, &chunk.cloned_header())
+        .unwrap()
+        .unwrap();
+    let runtime_adapter = env.clients[1].runtime_adapter.clone();
+    assert!(!env.clients[1]
+        .chain
+        .prepare_chunk_state_transition(&proof)
+        .unwrap()
+        .check(runtime_adapter.as_ref())
+        .unwrap());
+    assert_eq!(process_proof(&mut env.clients[1], &proof), Some(false));
+    let epoch_id = block3.header().epoch_id().clone();
+    assert!(env.clients[1]
+        .do_not_include_chunks_from
+        .contains(&(epoch_id, "test0".parse().unwrap())));
+}
@@ -0,0 +1,55 @@
+use near_chain::ChainGenesis;
+use near_chain_configs::Genesis;
+use near_client::test_utils::TestEnv;
+use near_crypto::{InMemorySigner, KeyType};
+use near_o11y::testonly::init_test_logger;
+use near_primitives::transaction::{Action, DeployContractAction, FunctionCallAction};
+use near_primitives::views::FinalExecutionStatus;
+use nearcore::config::GenesisExt;
+
+use crate::tests::client::process_blocks::create_nightshade_runtimes;
+
+/// Re-executing the receipt of a function call reproduces the call it made, including its logs.
+#[test]
+fn test_trace_receipt() {
+    init_test_logger();
+    let genesis = Genesis::test(vec!["test0".parse().unwrap(), "test1".parse().unwrap()], 1);
+    let mut env = TestEnv::builder(ChainGenesis::new(&genesis))
+        .runtime_adapters(create_nightshade_runtimes(&genesis, 1))
+        .build();
+    let signer = InMemorySigner::from_seed("test0".parse().unwrap(), KeyType::ED25519, "test0");
+
+    let actions = vec![Action::DeployContract(DeployContractAction {
+        code: near_test_contracts::rs_contract().to_vec(),
+    })];
+    let tx = env.tx_from_actions(actions, &signer, signer.account_id.clone());
+    let outcome = env.execute_tx(tx);
+    assert!(matches!(outcome.status, FinalExecutionStatus::SuccessValue(_)));
+
+    let actions = vec![Action::FunctionCall(FunctionCallAction {
+        method_name: "log_something".to_string(),
+        args: vec![],
+        gas: 100_000_000_000_000,
+        deposit: 0,
+    })];
+    let tx = env.tx_from_actions(actions, &signer, signer.account_id.clone());
+    let outcome = env.execute_tx(tx);
+    assert!(matches!(outcome.status, FinalExecutionStatus::SuccessValue(_)));
+    let receipt_outcome = &outcome.receipts_outcome[0];
+
+    let trace = env.clients[0].chain.trace_receipt(&receipt_outcome.id, true).unwrap();
+    assert_eq!(trace.receipt_id, receipt_outcome.id);
+    assert_eq!(trace.block_hash, receipt_outcome.block_hash);
+    assert_eq!(trace.function_calls.len(), 1);
+    let call = &trace.function_calls[0];
+    assert_eq!(call.contract_id, signer.account_id);
+    assert_eq!(call.method_name, "log_something");
+    assert_eq!(call.logs, receipt_outcome.outcome.logs);
+    assert_eq!(call.logs, vec!["hello".to_string()]);
+    assert!(call.error.is_none());
+    assert!(call.gas_burnt > 0);
+    assert!(call.extended_gas_profile.is_some());
+
+    let trace = env.clients[0].chain.trace_receipt(&receipt_outcome.id, false).unwrap();
+    assert!(trace.function_calls[0].extended_gas_profile.is_none());
+}
@@ -0,0 +1,206 @@
+use std::sync::Arc;
+
+use near_chain::{ChainGenesis, Provenance};
+use near_chain_configs::Genesis;
+use near_client::test_utils::TestEnv;
+use near_client::{Client, ConfigUpdater, ValidatorKeyStatus};
+use near_client_primitives::types::SyncStatus;
+use near_crypto::{InMemorySigner, KeyType, PublicKey};
+use near_dyn_configs::UpdateableConfigs;
+use near_epoch_manager::test_utils::TEST_SEED;
+use near_epoch_manager::EpochManagerAdapter;
+use near_o11y::testonly::init_test_logger;
+use near_primitives::transaction::SignedTransaction;
+use near_primitives::types::{AccountId, BlockHeight};
+use near_primitives::validator_signer::{
+    InMemoryValidatorSigner, UpdateableValidatorSigner, ValidatorSigner,
+};
+use nearcore::config::{GenesisExt, TESTING_INIT_STAKE};
+
+use crate::tests::client::process_blocks::create_nightshade_runtimes;
+
+const EPOCH_LENGTH: BlockHeight = 5;
+
+/// Sets up test0 as the only validator.
+fn setup_env() -> TestEnv {
+    init_test_logger();
+    let mut genesis = Genesis::test(vec!["test0".parse().unwrap()], 1);
+    genesis.config.epoch_length = EPOCH_LENGTH;
+    TestEnv::builder(ChainGenesis::new(&genesis))
+        .runtime_adapters(create_nightshade_runtimes(&genesis, 1))
+        .build()
+}
+
+fn new_signer(seed: &str) -> Arc<dyn ValidatorSigner> {
+    Arc::new(InMemoryValidatorSigner::from_seed("test0".parse().unwrap(), KeyType::ED25519, seed))
+}
+
+/// Submits a transaction restaking the stake of test0 with a new key.
+fn stake_new_key(env: &mut TestEnv) -> Arc<dyn ValidatorSigner> {
+    let new_signer = new_signer("test0-rotated");
+    let genesis_hash = *env.clients[0].chain.genesis().hash();
+    let signer = InMemorySigner::from_seed("test0".parse().unwrap(), KeyType::ED25519, "test0");
+    let tx = SignedTransaction::stake(
+        1,
+        "test0".parse().unwrap(),
+        &signer,
+        TESTING_INIT_STAKE,
+        new_signer.public_key(),
+        genesis_hash,
+    );
+    env.clients[0].process_tx(tx, false, false);
+    new_signer
+}
+
+/// Recreates the client with an updateable validator signer, which is shared
+/// by block production and doomslug like in a running node.
+fn use_updateable_signer(env: &mut TestEnv) -> UpdateableValidatorSigner {
+    let client = &env.clients[0];
+    let signer = UpdateableValidatorSigner::new(client.validator_signer.clone().unwrap());
+    let mut new_client = Client::new(
+        client.config.clone(),
+        env.chain_genesis.clone(),
+        client.runtime_adapter.clone(),
+        env.network_adapters[0].clone(),
+        env.client_adapters[0].clone(),
+        Some(Arc::new(signer.clone())),
+        false,
+        TEST_SEED,
+    )
+    .unwrap();
+    new_client.sync_status = SyncStatus::NoSync;
+    env.clients[0] = new_client;
+    signer
+}
+
+fn key_status(env: &TestEnv, public_key: &PublicKey, check_proposals: bool) -> ValidatorKeyStatus {
+    let account_id: AccountId = "test0".parse().unwrap();
+    env.clients[0].validator_key_status(&account_id, public_key, check_proposals).unwrap()
+}
+
+fn is_next_block_epoch_start(env: &TestEnv) -> bool {
+    let head = env.clients[0].chain.head().unwrap();
+    env.clients[0].runtime_adapter.is_next_block_epoch_start(&head.last_block_hash).unwrap()
+}
+
+/// Produces the block at `height` signed with `public_key`.
+fn produce_block(env: &mut TestEnv, height: BlockHeight, public_key: &PublicKey) {
+    let block = env.clients[0].produce_block(height).unwrap().unwrap();
+    assert!(block.header().verify_block_producer(public_key));
+    env.process_block(0, block, Provenance::PRODUCED);
+}
+
+/// Produces blocks until the last block of the current epoch and returns its
+/// height.
+fn produce_until_epoch_end(
+    env: &mut TestEnv,
+    mut height: BlockHeight,
+    public_key: &PublicKey,
+) -> BlockHeight {
+    loop {
+        height += 1;
+        produce_block(env, height, public_key);
+        if is_next_block_epoch_start(env) {
+            return height;
+        }
+    }
+}
+
+#[test]
+fn test_validator_key_status() {
+    let mut env = setup_env();
+    let new_key = stake_new_key(&mut env).public_key();
+    let old_key = env.clients[0].validator_signer.as_ref().unwrap().public_key();
+    assert_eq!(key_status(&env, &old_key, true), ValidatorKeyStatus::Active);
+    assert_eq!(key_status(&env, &new_key, true), ValidatorKeyStatus::NotStaked);
+
+    // The stake is proposed in the first epoch, so the new key is only found
+    // when looking at proposals.
+    for height in 1..EPOCH_LENGTH {
+        produce_block(&mut env, height, &old_key);
+    }
+    assert!(!is_next_block_epoch_start(&env));
+    assert_eq!(key_status(&env, &new_key, true), ValidatorKeyStatus::Pending);
+    assert_eq!(key_status(&env, &new_key, false), ValidatorKeyStatus::NotStaked);
+
+    // In the second epoch, the new key is staked for the third one.
+    let height = produce_until_epoch_end(&mut env, EPOCH_LENGTH - 1, &old_key);
+    produce_block(&mut env, height + 1, &old_key);
+    assert_eq!(key_status(&env, &new_key, false), ValidatorKeyStatus::Pending);
+    assert_eq!(key_status(&env, &old_key, false), ValidatorKeyStatus::Active);
+
+    // The next block after the second epoch is in the third one.
+    produce_until_epoch_end(&mut env, height + 1, &old_key);
+    assert_eq!(key_status(&env, &new_key, false), ValidatorKeyStatus::Active);
+    assert_eq!(key_status(&env, &old_key, false), ValidatorKeyStatus::NotStaked);
+}
+
+/// Rotates the key of test0 with a `ConfigUpdater` the way the client actor
+/// does, while producing blocks.
+#[test]
+fn test_validator_key_rotation() {
+    let mut env = setup_env();
+    let signer = use_updateable_signer(&mut env);
+    let new_key = stake_new_key(&mut env).public_key();
+    let old_key = signer.public_key();
+    for height in 1..EPOCH_LENGTH {
+        produce_block(&mut env, height, &old_key);
+    }
+
+    let (tx, rx) = tokio::sync::broadcast::channel(16);
+    let mut config_updater = ConfigUpdater::new(rx, Some(signer.clone()));
+    tx.send(Ok(UpdateableConfigs {
+        validator_signer: Some(new_signer("test0-rotated")),
+        ..Default::default()
+    }))
+    .unwrap();
+    config_updater.try_update(&|_| {});
+    assert_eq!(config_updater.pending_validator_public_key(), Some(new_key.clone()));
+
+    let mut switch_height = None;
+    for height in EPOCH_LENGTH..=4 * EPOCH_LENGTH {
+        let next_block_epoch_start = is_next_block_epoch_start(&env);
+        config_updater.try_update_validator_signer(&|account_id, public_key, check_proposals| {
+            env.clients[0].validator_key_status(account_id, public_key, check_proposals)
+        });
+        let public_key = signer.public_key();
+        if switch_height.is_none() && public_key == new_key {
+            // The key is switched right before the first block of the epoch
+            // in which it's staked.
+            assert!(next_block_epoch_start);
+            switch_height = Some(height);
+        }
+        produce_block(&mut env, height, &public_key);
+    }
+    assert!(switch_height.is_some());
+    assert_eq!(signer.public_key(), new_key);
+    assert_eq!(config_updater.pending_validator_public_key(), None);
+}
+
+/// A new key which is neither staked nor proposed is dropped and the node
+/// keeps validating with the active one.
+#[test]
+fn test_validator_key_rotation_not_staked() {
+    let mut env = setup_env();
+    let signer = use_updateable_signer(&mut env);
+    let old_key = signer.public_key();
+
+    let (tx, rx) = tokio::sync::broadcast::channel(16);
+    let mut config_updater = ConfigUpdater::new(rx, Some(signer.clone()));
+    tx.send(Ok(UpdateableConfigs {
+        validator_signer: Some(new_signer("test0-not-staked")),
+        ..Default::default()
+    }))
+    .unwrap();
+    config_updater.try_update(&|_| {});
+    assert!(config_updater.pending_validator_public_key().is_some());
+
+    config_updater.try_update_validator_signer(&|account_id, public_key, check_proposals| {
+        env.clients[0].validator_key_status(account_id, public_key, check_proposals)
+    });
+    assert_eq!(config_updater.pending_validator_public_key(), None);
+    for height in 1..=3 * EPOCH_LENGTH {
+        produce_block(&mut env, height, &old_key);
+    }
+    assert_eq!(signer.public_key(), old_key);
+}
@@ -49,7 +49,7 @@ fn start_nodes(
         }
         // if non validator, track all shards
         if i >= num_validator_seats && i < num_tracking_nodes {
-            near_config.client_config.tracked_shards = vec![0];
+            near_config.client_config.tracked_shards.update(vec![0]);
         }
         near_config.client_config.epoch_sync_enabled = false;
         near_configs.push(near_config);
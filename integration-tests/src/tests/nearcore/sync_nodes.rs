@@ -57,6 +57,14 @@ fn add_blocks(
             signer.public_key(),
             TESTING_INIT_STAKE,
         )]);
+        let approval = Approval::new(
+            *prev.hash(),
+            prev.header().height(),
+            prev.header().height() + 1,
+            &epoch_id,
+            signer,
+        )
+        .unwrap();
         let block = Block::produce(
             PROTOCOL_VERSION,
             PROTOCOL_VERSION,
@@ -67,15 +75,7 @@ fn add_blocks(
             epoch_id,
             next_epoch_id,
             None,
-            vec![Some(
-                Approval::new(
-                    *prev.hash(),
-                    prev.header().height(),
-                    prev.header().height() + 1,
-                    signer,
-                )
-                .signature,
-            )],
+            vec![Some(approval.signature)],
             Ratio::from_integer(0),
             0,
             1000,
@@ -86,7 +86,8 @@ fn add_blocks(
             next_bp_hash,
             block_merkle_tree.root(),
             None,
-        );
+        )
+        .unwrap();
         block_merkle_tree.insert(*block.hash());
         let _ = client.do_send(
             BlockResponse {
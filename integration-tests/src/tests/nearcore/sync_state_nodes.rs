@@ -331,7 +331,7 @@ fn sync_empty_state() {
                                             block_header_fetch_horizon;
                                         near2.client_config.block_fetch_horizon =
                                             block_fetch_horizon;
-                                        near2.client_config.tracked_shards = vec![0, 1, 2, 3];
+                                        near2.client_config.tracked_shards.update(vec![0, 1, 2, 3]);
                                         near2.client_config.epoch_sync_enabled = false;
 
                                         let nearcore::NearNode {
@@ -349,6 +349,85 @@ fn test_view_state() {
     }
 }
 
+#[test]
+fn test_view_state_paginated() {
+    let (_, tries, root) = get_runtime_and_trie();
+    let shard_uid = TEST_SHARD_UID;
+    let mut state_update = tries.new_trie_update(shard_uid, root);
+    let keys: Vec<Vec<u8>> = (0..5).map(|i| format!("test{i}").into_bytes()).collect();
+    for key in &keys {
+        state_update.set(
+            TrieKey::ContractData { account_id: alice_account(), key: key.clone() },
+            b"value".to_vec(),
+        );
+    }
+    state_update.set(
+        TrieKey::ContractData { account_id: alice_account(), key: b"xyz".to_vec() },
+        b"value".to_vec(),
+    );
+    state_update.commit(StateChangeCause::InitialState);
+    let trie_changes = state_update.finalize().unwrap().0;
+    let mut db_changes = tries.store_update();
+    let new_root = tries.apply_all(&trie_changes, shard_uid, &mut db_changes);
+    db_changes.commit().unwrap();
+    let state_update = tries.new_trie_update(shard_uid, new_root);
+    let alice = alice_account();
+
+    // Fetch all keys with the `test` prefix two at a time.
+    let trie_viewer = TrieViewer::default();
+    let mut got = vec![];
+    let mut start_key = None;
+    loop {
+        let result = trie_viewer
+            .view_state_page(&state_update, &alice, b"test", start_key.as_deref(), Some(2), true)
+            .unwrap();
+        assert!(result.values.len() <= 2);
+        let proof_verifier = ProofVerifier::new(result.proof).unwrap();
+        for item in &result.values {
+            assert!(proof_verifier.verify(&new_root, &alice, &item.key, Some(&item.value)));
+            got.push(item.key.clone());
+        }
+        match result.next_key {
+            Some(next_key) => start_key = Some(next_key),
+            None => break,
+        }
+    }
+    assert_eq!(keys, got);
+
+    // Zero limit gives an empty page which continues at the first key.
+    let result =
+        trie_viewer.view_state_page(&state_update, &alice, b"test", None, Some(0), false).unwrap();
+    assert_eq!(result.values, vec![]);
+    assert_eq!(result.next_key, Some(keys[0].clone()));
+
+    // Start key outside of the prefix range.
+    let result =
+        trie_viewer.view_state_page(&state_update, &alice, b"test", Some(&b"u"[..]), None, false);
+    assert_eq!(result.unwrap().values, vec![]);
+
+    // Pages are cut by the state size limit even though account is too large
+    // to be viewed without pagination.
+    let trie_viewer = TrieViewer::new(Some(20), None);
+    let result = trie_viewer.view_state(&state_update, &alice, b"", false);
+    assert!(matches!(result, Err(errors::ViewStateError::AccountStateTooLarge { .. })));
+    let result = trie_viewer
+        .view_state_page(&state_update, &alice, b"", Some(&b""[..]), None, false)
+        .unwrap();
+    assert_eq!(result.values.len(), 2);
+    assert_eq!(result.next_key, Some(keys[2].clone()));
+    // The limit applies to pages resumed from a previous one as well.
+    let result = trie_viewer
+        .view_state_page(&state_update, &alice, b"", Some(&keys[2]), None, false)
+        .unwrap();
+    assert_eq!(result.values.len(), 2);
+    assert_eq!(result.next_key, Some(keys[4].clone()));
+    // An item which doesn't fit into a page on its own can't be returned.
+    let trie_viewer = TrieViewer::new(Some(5), None);
+    let result =
+        trie_viewer.view_state_page(&state_update, &alice, b"", Some(&keys[2]), Some(1), false);
+    assert!(matches!(result, Err(errors::ViewStateError::AccountStateTooLarge { .. })));
+}
+
 #[test]
 fn test_view_state_too_large() {
     let (_, tries, root) = get_runtime_and_trie();
@@ -26,6 +26,7 @@ use near_network::config::NetworkConfig;
 use near_network::test_utils::open_port;
 use near_primitives::account::{AccessKey, Account};
 use near_primitives::hash::CryptoHash;
+use near_primitives::network::PeerLimits;
 #[cfg(test)]
 use near_primitives::shard_layout::account_id_to_shard_id;
 use near_primitives::shard_layout::ShardLayout;
@@ -35,11 +36,14 @@ use near_primitives::types::{
     NumSeats, NumShards, ShardId,
 };
 use near_primitives::utils::{generate_random_string, get_num_seats_per_shard};
-use near_primitives::validator_signer::{InMemoryValidatorSigner, ValidatorSigner};
+use near_primitives::validator_signer::{
+    InMemoryValidatorSigner, UpdateableValidatorSigner, ValidatorSigner,
+};
 use near_primitives::version::PROTOCOL_VERSION;
 #[cfg(feature = "rosetta_rpc")]
 use near_rosetta_rpc::RosettaRpcConfig;
 use near_telemetry::TelemetryConfig;
+use near_validator_signer::{GuardedValidatorSigner, RemoteSignerConfig, RemoteValidatorSigner};
 
 use crate::download_file::{run_download_file, FileDownloadError};
 
@@ -130,6 +134,7 @@ pub const CONFIG_FILENAME: &str = "config.json";
 pub const GENESIS_CONFIG_FILENAME: &str = "genesis.json";
 pub const NODE_KEY_FILE: &str = "node_key.json";
 pub const VALIDATOR_KEY_FILE: &str = "validator_key.json";
+pub const SIGNING_HISTORY_FILE: &str = "signing_history.json";
 
 pub const MAINNET_TELEMETRY_URL: &str = "https://explorer.mainnet.near.org/api/nodes";
 pub const NETWORK_TELEMETRY_URL: &str = "https://explorer.{}.near.org/api/nodes";
@@ -205,6 +210,14 @@ fn default_trie_viewer_state_size_limit() -> Option<u64> {
 pub enum ConfigValidationError {
     #[error("Configuration with archive = false and save_trie_changes = false is not supported because non-archival nodes must save trie changes in order to do do garbage collection.")]
     TrieChanges,
+    #[error("state_sync_timeout must be positive.")]
+    StateSyncTimeout,
+    #[error("Memory limits of {0} must be positive.")]
+    TrieCacheLimit(&'static str),
+    #[error("Invalid peer limits: {0}")]
+    PeerLimits(String),
+    #[error("Validator must track all shards. Please change `tracked_shards` field in config.json to be any non-empty vector")]
+    TrackedShards,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -312,6 +325,11 @@ pub struct Config {
     #[serde(skip_serializing_if = "is_false")]
     pub archive: bool,
     pub save_trie_changes: bool,
+    /// Record the trie nodes touched while applying chunks and send them to
+    /// the other block producers along with produced chunks, so that they can
+    /// validate state transitions without tracking the shard.
+    #[serde(skip_serializing_if = "is_false")]
+    pub record_state_transition_proofs: bool,
     pub log_summary_style: LogSummaryStyle,
     /// Garbage collection configuration.
     #[serde(default, flatten)]
@@ -346,6 +364,13 @@ pub struct Config {
     pub db_migration_snapshot_path: Option<PathBuf>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub expected_shutdown: Option<BlockHeight>,
+    /// If set, blocks, chunks and approvals are signed by a remote signer
+    /// holding the validator key instead of `validator_key_file`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_signer: Option<RemoteSignerConfig>,
+    /// File recording the last signed blocks, chunks and approvals, which
+    /// prevents the validator from signing conflicting ones.
+    pub signing_history_file: String,
 }
 
 fn is_false(value: &bool) -> bool {
@@ -370,6 +395,7 @@ impl Default for Config {
             tracked_shards: vec![],
             archive: false,
             save_trie_changes: true,
+            record_state_transition_proofs: false,
             log_summary_style: LogSummaryStyle::Colored,
             gc: GCConfig::default(),
             epoch_sync_enabled: true,
@@ -382,6 +408,8 @@ impl Default for Config {
             store: near_store::StoreConfig::default(),
             cold_store: None,
             expected_shutdown: None,
+            remote_signer: None,
+            signing_history_file: SIGNING_HISTORY_FILE.to_string(),
         }
     }
 }
@@ -428,14 +456,55 @@ impl Config {
     /// `validate()` is called every time `config.json` is read.
     fn validate(&self) -> Result<(), ConfigValidationError> {
         if !self.archive && !self.save_trie_changes {
-            Err(ConfigValidationError::TrieChanges)
-        } else {
-            Ok(())
+            return Err(ConfigValidationError::TrieChanges);
+        }
+        if self.consensus.state_sync_timeout.is_zero() {
+            return Err(ConfigValidationError::StateSyncTimeout);
+        }
+        for (name, cache) in [
+            ("trie_cache", &self.store.trie_cache),
+            ("view_trie_cache", &self.store.view_trie_cache),
+        ] {
+            if cache.default_max_bytes == 0 || cache.per_shard_max_bytes.values().any(|&v| v == 0) {
+                return Err(ConfigValidationError::TrieCacheLimit(name));
+            }
         }
+        let peer_limits = self.peer_limits();
+        peer_limits.verify().map_err(ConfigValidationError::PeerLimits)?;
+        if self.network.safe_set_size <= peer_limits.minimum_outbound_peers {
+            return Err(ConfigValidationError::PeerLimits(format!(
+                "safe_set_size({}) must be larger than minimum_outbound_peers({}).",
+                self.network.safe_set_size, peer_limits.minimum_outbound_peers
+            )));
+        }
+        Ok(())
         // TODO: Add more config validation.
         // TODO: Validate `ClientConfig` instead.
     }
 
+    /// Validation which depends on the chain the node runs, done on startup
+    /// and whenever `config.json` is reloaded.
+    pub fn validate_for_chain(&self, chain_id: &str) -> Result<(), ConfigValidationError> {
+        if matches!(chain_id, "mainnet" | "testnet" | "betanet") {
+            // Make sure validators tracks all shards, see
+            // https://github.com/near/nearcore/issues/7388
+            if self.tracked_shards.is_empty() {
+                return Err(ConfigValidationError::TrackedShards);
+            }
+        }
+        Ok(())
+    }
+
+    /// Limits on the number of peer connections from the `network` section.
+    pub fn peer_limits(&self) -> PeerLimits {
+        PeerLimits {
+            max_num_peers: self.network.max_num_peers,
+            minimum_outbound_peers: self.network.minimum_outbound_peers,
+            ideal_connections_lo: self.network.ideal_connections_lo,
+            ideal_connections_hi: self.network.ideal_connections_hi,
+        }
+    }
+
     pub fn write_to_file(&self, path: &Path) -> std::io::Result<()> {
         let mut file = File::create(path)?;
         let str = serde_json::to_string_pretty(self)?;
@@ -572,6 +641,9 @@ pub struct NearConfig {
     pub telemetry_config: TelemetryConfig,
     pub genesis: Genesis,
     pub validator_signer: Option<Arc<dyn ValidatorSigner>>,
+    /// Handle to replace the key of `validator_signer` while the node is
+    /// running, shared with the network.
+    pub updateable_validator_signer: Option<UpdateableValidatorSigner>,
 }
 
 impl NearConfig {
@@ -581,6 +653,8 @@ impl NearConfig {
         network_key_pair: KeyFile,
         validator_signer: Option<Arc<dyn ValidatorSigner>>,
     ) -> anyhow::Result<Self> {
+        let updateable_validator_signer = validator_signer.map(UpdateableValidatorSigner::new);
+        let peer_limits = config.peer_limits();
         Ok(NearConfig {
             config: config.clone(),
             client_config: ClientConfig {
@@ -606,7 +680,10 @@ impl NearConfig {
                 header_sync_expected_height_per_second: config
                     .consensus
                     .header_sync_expected_height_per_second,
-                state_sync_timeout: config.consensus.state_sync_timeout,
+                state_sync_timeout: MutableConfigValue::new(
+                    config.consensus.state_sync_timeout,
+                    "state_sync_timeout",
+                ),
                 min_num_peers: config.consensus.min_num_peers,
                 log_summary_period: Duration::from_secs(10),
                 produce_empty_blocks: config.consensus.produce_empty_blocks,
@@ -621,25 +698,33 @@ impl NearConfig {
                 catchup_step_period: config.consensus.catchup_step_period,
                 chunk_request_retry_period: config.consensus.chunk_request_retry_period,
                 doosmslug_step_period: config.consensus.doomslug_step_period,
-                tracked_accounts: config.tracked_accounts,
-                tracked_shards: config.tracked_shards,
+                tracked_accounts: MutableConfigValue::new(
+                    config.tracked_accounts,
+                    "tracked_accounts",
+                ),
+                tracked_shards: MutableConfigValue::new(config.tracked_shards, "tracked_shards"),
+                peer_limits: MutableConfigValue::new(peer_limits, "peer_limits"),
                 archive: config.archive,
                 save_trie_changes: config.save_trie_changes,
                 log_summary_style: config.log_summary_style,
-                gc: config.gc,
+                gc: MutableConfigValue::new(config.gc, "gc"),
                 view_client_threads: config.view_client_threads,
                 epoch_sync_enabled: config.epoch_sync_enabled,
                 view_client_throttle_period: config.view_client_throttle_period,
                 trie_viewer_state_size_limit: config.trie_viewer_state_size_limit,
-                max_gas_burnt_view: config.max_gas_burnt_view,
+                max_gas_burnt_view: MutableConfigValue::new(
+                    config.max_gas_burnt_view,
+                    "max_gas_burnt_view",
+                ),
                 enable_statistics_export: config.store.enable_statistics_export,
                 client_background_migration_threads: config.store.background_migration_threads,
                 flat_storage_creation_period: config.store.flat_storage_creation_period,
+                record_state_transition_proofs: config.record_state_transition_proofs,
             },
             network_config: NetworkConfig::new(
                 config.network,
                 network_key_pair.secret_key,
-                validator_signer.clone(),
+                updateable_validator_signer.clone(),
                 config.archive,
             )?,
             telemetry_config: config.telemetry,
@@ -648,7 +733,10 @@ impl NearConfig {
             #[cfg(feature = "rosetta_rpc")]
             rosetta_rpc_config: config.rosetta_rpc,
             genesis,
-            validator_signer,
+            validator_signer: updateable_validator_signer
+                .clone()
+                .map(|signer| Arc::new(signer) as Arc<dyn ValidatorSigner>),
+            updateable_validator_signer,
         })
     }
 
@@ -1311,21 +1399,65 @@ impl From<NodeKeyFile> for KeyFile {
     }
 }
 
+/// Connection to the remote signer kept between loads of the validator key, so
+/// that reloading the config reconnects only if `remote_signer` changes.
+#[derive(Default)]
+pub struct RemoteSignerConnection(Option<(RemoteSignerConfig, Arc<dyn ValidatorSigner>)>);
+
+/// Loads the validator key from `validator_key_file` or connects to the remote
+/// signer holding it.  Returns `None` if the node isn't a validator.
+pub(crate) fn load_validator_signer(
+    dir: &Path,
+    config: &Config,
+    connection: &mut RemoteSignerConnection,
+) -> anyhow::Result<Option<Arc<dyn ValidatorSigner>>> {
+    let validator_file = dir.join(&config.validator_key_file);
+    let remote_signer = match &config.remote_signer {
+        Some(remote_signer) => remote_signer,
+        None => {
+            connection.0 = None;
+            return load_validator_key_file(&validator_file);
+        }
+    };
+    anyhow::ensure!(
+        !validator_file.exists(),
+        "{} must not exist when remote_signer is configured",
+        validator_file.display()
+    );
+    if let Some((connected_config, signer)) = &connection.0 {
+        if connected_config == remote_signer {
+            return Ok(Some(signer.clone()));
+        }
+    }
+    let signer: Arc<dyn ValidatorSigner> =
+        Arc::new(RemoteValidatorSigner::connect(remote_signer.clone()).with_context(|| {
+            format!("Failed connecting to remote signer at {}", remote_signer.socket_path.display())
+        })?);
+    connection.0 = Some((remote_signer.clone(), signer.clone()));
+    Ok(Some(signer))
+}
+
+fn load_validator_key_file(
+    validator_file: &Path,
+) -> anyhow::Result<Option<Arc<dyn ValidatorSigner>>> {
+    if validator_file.exists() {
+        let signer = InMemoryValidatorSigner::from_file(validator_file).with_context(|| {
+            format!("Failed initializing validator signer from {}", validator_file.display())
+        })?;
+        Ok(Some(Arc::new(signer)))
+    } else {
+        Ok(None)
+    }
+}
+
 pub fn load_config(
     dir: &Path,
     genesis_validation: GenesisValidationMode,
 ) -> anyhow::Result<NearConfig> {
     let config = Config::from_file(&dir.join(CONFIG_FILENAME))?;
     let genesis_file = dir.join(&config.genesis_file);
-    let validator_file = dir.join(&config.validator_key_file);
-    let validator_signer = if validator_file.exists() {
-        let signer = InMemoryValidatorSigner::from_file(&validator_file).with_context(|| {
-            format!("Failed initializing validator signer from {}", validator_file.display())
-        })?;
-        Some(Arc::new(signer) as Arc<dyn ValidatorSigner>)
-    } else {
-        None
-    };
+    let validator_signer =
+        load_validator_signer(dir, &config, &mut RemoteSignerConnection::default())?;
     let node_key_path = dir.join(&config.node_key_file);
     let network_signer = NodeKeyFile::from_file(&node_key_path).with_context(|| {
         format!("Failed reading node key file from {}", node_key_path.display())
@@ -1338,14 +1470,20 @@ pub fn load_config(
         None => Genesis::from_file(&genesis_file, genesis_validation),
     };
 
-    if matches!(genesis.config.chain_id.as_ref(), "mainnet" | "testnet" | "betanet") {
-        // Make sure validators tracks all shards, see
-        // https://github.com/near/nearcore/issues/7388
-        anyhow::ensure!(!config.tracked_shards.is_empty(),
-                        "Validator must track all shards. Please change `tracked_shards` field in config.json to be any non-empty vector");
-    }
+    config.validate_for_chain(&genesis.config.chain_id)?;
 
-    NearConfig::new(config, genesis, network_signer.into(), validator_signer)
+    let history_file = dir.join(&config.signing_history_file);
+    let mut near_config =
+        NearConfig::new(config, genesis, network_signer.into(), validator_signer)?;
+    // The signing history guards whichever key is active, so it wraps the
+    // updateable signer rather than the key itself.
+    if let Some(signer) = near_config.validator_signer.take() {
+        let signer = GuardedValidatorSigner::new(signer, &history_file).with_context(|| {
+            format!("Failed reading signing history from {}", history_file.display())
+        })?;
+        near_config.validator_signer = Some(Arc::new(signer));
+    }
+    Ok(near_config)
 }
 
 pub fn load_test_config(seed: &str, port: u16, genesis: Genesis) -> NearConfig {
@@ -1450,6 +1588,21 @@ fn test_config_from_file() {
     }
 }
 
+#[test]
+fn test_validate_for_chain() {
+    let mut config = Config::default();
+    config.tracked_shards = vec![];
+    assert!(config.validate_for_chain("localnet").is_ok());
+    for chain_id in ["mainnet", "testnet", "betanet"] {
+        assert!(matches!(
+            config.validate_for_chain(chain_id),
+            Err(ConfigValidationError::TrackedShards)
+        ));
+    }
+    config.tracked_shards = vec![0];
+    assert!(config.validate_for_chain("mainnet").is_ok());
+}
+
 #[test]
 fn test_create_testnet_configs() {
     let num_shards = 4;
@@ -1,4 +1,4 @@
-use crate::config::Config;
+use crate::config::{Config, RemoteSignerConnection};
 use near_chain_configs::UpdateableClientConfig;
 use near_dyn_configs::{UpdateableConfigLoaderError, UpdateableConfigs};
 use near_o11y::log_config::LogConfig;
@@ -8,8 +8,14 @@ use std::path::{Path, PathBuf};
 const LOG_CONFIG_FILENAME: &str = "log_config.json";
 
 /// This function gets called at the startup and each time a config needs to be reloaded.
+///
+/// `chain_id` is the chain the node runs, some config values are only valid on
+/// some chains.  `remote_signer` keeps the connection to the remote signer
+/// between calls, so it's reused while `remote_signer` config stays the same.
 pub fn read_updateable_configs(
     home_dir: &Path,
+    chain_id: &str,
+    remote_signer: &mut RemoteSignerConnection,
 ) -> Result<UpdateableConfigs, UpdateableConfigLoaderError> {
     let mut errs = vec![];
     let log_config = match read_log_config(home_dir) {
@@ -19,10 +25,11 @@ pub fn read_updateable_configs(
             None
         }
     };
-    let updateable_client_config =
-        match Config::from_file(&home_dir.join(crate::config::CONFIG_FILENAME))
-            .map(get_updateable_client_config)
-        {
+    let config =
+        match Config::from_file(&home_dir.join(crate::config::CONFIG_FILENAME)).and_then(|config| {
+            config.validate_for_chain(chain_id)?;
+            Ok(config)
+        }) {
             Ok(config) => Some(config),
             Err(err) => {
                 errs.push(UpdateableConfigLoaderError::ConfigFileError {
@@ -32,9 +39,24 @@ pub fn read_updateable_configs(
                 None
             }
         };
+    // A validator key which fails to load keeps the active key in use without
+    // holding back the other values.
+    let mut validator_signer_correct = true;
+    let validator_signer = config.as_ref().and_then(|config| {
+        crate::config::load_validator_signer(home_dir, config, remote_signer).unwrap_or_else(|err| {
+            tracing::warn!(target: "neard", ?err, "Failed to load the validator key, keeping the active one. Please fix this ASAP otherwise the node will be unable to restart");
+            validator_signer_correct = false;
+            None
+        })
+    });
+    let updateable_client_config = config.map(get_updateable_client_config);
     if errs.is_empty() {
-        crate::metrics::CONFIG_CORRECT.set(1);
-        Ok(UpdateableConfigs { log_config, client_config: updateable_client_config })
+        crate::metrics::CONFIG_CORRECT.set(validator_signer_correct as i64);
+        Ok(UpdateableConfigs {
+            log_config,
+            client_config: updateable_client_config,
+            validator_signer,
+        })
     } else {
         tracing::warn!(target: "neard", "Dynamically updateable configs are not valid. Please fix this ASAP otherwise the node will be unable to restart: {:?}", &errs);
         crate::metrics::CONFIG_CORRECT.set(0);
@@ -43,9 +65,21 @@ pub fn read_updateable_configs(
 }
 
 pub fn get_updateable_client_config(config: Config) -> UpdateableClientConfig {
+    let peer_limits = config.peer_limits();
     // All fields that can be updated while the node is running should be explicitly set here.
     // Keep this list in-sync with `core/dyn-configs/README.md`.
-    UpdateableClientConfig { expected_shutdown: config.expected_shutdown }
+    UpdateableClientConfig {
+        expected_shutdown: config.expected_shutdown,
+        gc: config.gc,
+        state_sync_timeout: config.consensus.state_sync_timeout,
+        max_gas_burnt_view: config.max_gas_burnt_view,
+        tracked_accounts: config.tracked_accounts,
+        tracked_shards: config.tracked_shards,
+        trie_cache: config.store.trie_cache,
+        view_trie_cache: config.store.view_trie_cache,
+        adaptive_trie_cache: config.store.adaptive_trie_cache,
+        peer_limits,
+    }
 }
 
 fn read_log_config(home_dir: &Path) -> Result<Option<LogConfig>, UpdateableConfigLoaderError> {
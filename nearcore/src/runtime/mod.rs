@@ -10,7 +10,7 @@ use near_chain::types::{
 };
 use near_chain::{Error, RuntimeWithEpochManagerAdapter};
 use near_chain_configs::{
-    Genesis, GenesisConfig, ProtocolConfig, DEFAULT_GC_NUM_EPOCHS_TO_KEEP,
+    Genesis, GenesisConfig, MutableConfigValue, ProtocolConfig, DEFAULT_GC_NUM_EPOCHS_TO_KEEP,
     MIN_GC_NUM_EPOCHS_TO_KEEP,
 };
 use near_client_primitives::types::StateSplitApplyingStatus;
@@ -36,7 +36,7 @@ use near_primitives::shard_layout::{
 use near_primitives::state_part::PartId;
 use near_primitives::state_record::{state_record_to_account_id, StateRecord};
 use near_primitives::syncing::{get_num_state_parts, STATE_PART_MEMORY_LIMIT};
-use near_primitives::transaction::SignedTransaction;
+use near_primitives::transaction::{ExecutionOutcomeWithId, SignedTransaction};
 use near_primitives::types::validator_stake::ValidatorStakeIter;
 use near_primitives::types::{
     AccountId, Balance, BlockHeight, CompiledContractCache, EpochHeight, EpochId,
@@ -45,8 +45,8 @@ use near_primitives::types::{
 };
 use near_primitives::version::ProtocolVersion;
 use near_primitives::views::{
-    AccessKeyInfoView, CallResult, QueryRequest, QueryResponse, QueryResponseKind, ViewApplyState,
-    ViewStateResult,
+    AccessKeyInfoView, CallResult, FunctionCallTraceView, QueryRequest, QueryResponse,
+    QueryResponseKind, ViewApplyState, ViewStateResult,
 };
 use near_store::flat_state::ChainAccessForFlatStorage;
 use near_store::flat_state::{
@@ -59,15 +59,15 @@ use near_store::{
     ApplyStatePartResult, DBCol, PartialStorage, ShardTries, Store, StoreCompiledContractCache,
     StoreUpdate, Trie, TrieConfig, WrappedTrieChanges, COLD_HEAD_KEY,
 };
-use near_vm_runner::precompile_contract;
+use near_vm_runner::{precompile_contract, FilesystemCompiledContractCache};
 use node_runtime::adapter::ViewRuntimeAdapter;
 use node_runtime::config::RuntimeConfig;
 use node_runtime::state_viewer::TrieViewer;
 use node_runtime::{
-    validate_transaction, verify_and_charge_transaction, ApplyState, Runtime,
+    validate_transaction, verify_and_charge_transaction, ApplyState, ExecutionTracer, Runtime,
     ValidatorAccountsUpdate,
 };
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 use std::sync::{Arc, RwLockReadGuard, RwLockWriteGuard};
@@ -79,6 +79,9 @@ pub mod errors;
 const STATE_DUMP_FILE: &str = "state_dump";
 const GENESIS_ROOTS_FILE: &str = "genesis_roots";
 
+/// Maximum number of chunks per shard applied when estimating gas of a transaction.
+const MAX_ESTIMATE_GAS_ROUNDS: usize = 64;
+
 /// Defines Nightshade state transition and validator rotation.
 /// TODO: this possibly should be merged with the runtime cargo or at least reconciled on the interfaces.
 pub struct NightshadeRuntime {
@@ -87,7 +90,9 @@ pub struct NightshadeRuntime {
 
     store: Store,
     tries: ShardTries,
-    trie_viewer: TrieViewer,
+    trie_viewer_state_size_limit: Option<u64>,
+    /// Overrides max burnt gas per view method of the genesis config.
+    max_gas_burnt_view: MutableConfigValue<Option<Gas>>,
     flat_state_factory: FlatStateFactory,
     pub runtime: Runtime,
     epoch_manager: EpochManagerHandle,
@@ -95,21 +100,50 @@ pub struct NightshadeRuntime {
     genesis_state_roots: Vec<StateRoot>,
     migration_data: Arc<MigrationData>,
     gc_num_epochs_to_keep: u64,
+    /// Cache of compiled contracts shared with other nodes on the host.  If
+    /// not configured, compiled contracts are cached in the database.
+    compiled_contract_cache: Option<FilesystemCompiledContractCache>,
+}
+
+/// Opens the filesystem compiled contract cache if one is configured.
+fn open_compiled_contract_cache(
+    home_dir: &Path,
+    config: &NearConfig,
+) -> std::io::Result<Option<FilesystemCompiledContractCache>> {
+    let cache_config = &config.config.store.compiled_contract_cache;
+    match cache_config.get_path(home_dir) {
+        Some(path) => {
+            FilesystemCompiledContractCache::open(&path, cache_config.max_size.as_u64()).map(Some)
+        }
+        None => Ok(None),
+    }
 }
 
 impl NightshadeRuntime {
     pub fn from_config(home_dir: &Path, store: Store, config: &NearConfig) -> Self {
-        Self::new(
+        let mut trie_config = TrieConfig::from_store_config(&config.config.store);
+        trie_config.view_snapshot_dir = config.config.store.trie_view_snapshot.get_path(home_dir);
+        let mut runtime = Self::new(
             home_dir,
             store,
             &config.genesis,
             TrackedConfig::from_config(&config.client_config),
             config.client_config.trie_viewer_state_size_limit,
-            config.client_config.max_gas_burnt_view,
+            config.client_config.max_gas_burnt_view.clone(),
             None,
             config.config.gc.gc_num_epochs_to_keep(),
-            TrieConfig::from_store_config(&config.config.store),
-        )
+            trie_config,
+        );
+        match open_compiled_contract_cache(home_dir, config) {
+            Ok(cache) => runtime.compiled_contract_cache = cache,
+            Err(err) => error!(
+                target: "runtime",
+                path = ?config.config.store.compiled_contract_cache.path,
+                %err,
+                "Failed to open compiled contract cache, falling back to caching in the database"
+            ),
+        }
+        runtime
     }
 
     fn new(
@@ -118,7 +152,7 @@ impl NightshadeRuntime {
         genesis: &Genesis,
         tracked_config: TrackedConfig,
         trie_viewer_state_size_limit: Option<u64>,
-        max_gas_burnt_view: Option<Gas>,
+        max_gas_burnt_view: MutableConfigValue<Option<Gas>>,
         runtime_config_store: Option<RuntimeConfigStore>,
         gc_num_epochs_to_keep: u64,
         trie_config: TrieConfig,
@@ -129,7 +163,6 @@ impl NightshadeRuntime {
         };
 
         let runtime = Runtime::new();
-        let trie_viewer = TrieViewer::new(trie_viewer_state_size_limit, max_gas_burnt_view);
         let genesis_config = genesis.config.clone();
         assert_eq!(
             genesis_config.shard_layout.num_shards(),
@@ -157,13 +190,15 @@ impl NightshadeRuntime {
             store,
             tries,
             runtime,
-            trie_viewer,
+            trie_viewer_state_size_limit,
+            max_gas_burnt_view,
             epoch_manager,
             shard_tracker,
             flat_state_factory,
             genesis_state_roots: state_roots,
             migration_data: Arc::new(load_migration_data(&genesis.config.chain_id)),
             gc_num_epochs_to_keep: gc_num_epochs_to_keep.max(MIN_GC_NUM_EPOCHS_TO_KEEP),
+            compiled_contract_cache: None,
         }
     }
 
@@ -180,7 +215,7 @@ impl NightshadeRuntime {
             genesis,
             tracked_config,
             None,
-            None,
+            MutableConfigValue::new(None, "max_gas_burnt_view"),
             Some(runtime_config_store),
             DEFAULT_GC_NUM_EPOCHS_TO_KEEP,
             Default::default(),
@@ -197,13 +232,19 @@ impl NightshadeRuntime {
         )
     }
 
+    /// Returns viewer honouring the current `max_gas_burnt_view`, which can
+    /// change while the node is running.
+    fn trie_viewer(&self) -> TrieViewer {
+        TrieViewer::new(self.trie_viewer_state_size_limit, self.max_gas_burnt_view.get())
+    }
+
     /// Create store of runtime configs for the given chain id.
     ///
     /// For mainnet and other chains except testnet we don't need to override runtime config for
     /// first protocol versions.
     /// For testnet, runtime config for genesis block was (incorrectly) different, that's why we
     /// need to override it specifically to preserve compatibility.
-    fn create_runtime_config_store(chain_id: &str) -> RuntimeConfigStore {
+    pub fn create_runtime_config_store(chain_id: &str) -> RuntimeConfigStore {
         match chain_id {
             "testnet" => {
                 let genesis_runtime_config = RuntimeConfig::initial_testnet_config();
@@ -392,6 +433,7 @@ impl NightshadeRuntime {
         is_new_chunk: bool,
         is_first_block_with_chunk_of_version: bool,
         state_patch: SandboxStatePatch,
+        execution_tracer: Option<Arc<ExecutionTracer>>,
     ) -> Result<ApplyTransactionResult, Error> {
         let _span = tracing::debug_span!(target: "runtime", "process_state_update").entered();
         let epoch_id = self.get_epoch_id_from_prev_block(prev_block_hash)?;
@@ -492,18 +534,27 @@ impl NightshadeRuntime {
             random_seed,
             current_protocol_version,
             config: self.runtime_config_store.get_config(current_protocol_version).clone(),
-            cache: Some(Box::new(StoreCompiledContractCache::new(&self.store))),
+            cache: Some(self.compiled_contract_cache()),
             is_new_chunk,
             migration_data: Arc::clone(&self.migration_data),
             migration_flags: MigrationFlags {
                 is_first_block_of_version,
                 is_first_block_with_chunk_of_version,
             },
+            verify_signatures: true,
+            process_delayed_receipts: true,
         };
 
+        let traced_runtime;
+        let runtime = match execution_tracer {
+            Some(tracer) => {
+                traced_runtime = Runtime::with_execution_tracer(tracer);
+                &traced_runtime
+            }
+            None => &self.runtime,
+        };
         let instant = Instant::now();
-        let apply_result = self
-            .runtime
+        let apply_result = runtime
             .apply(
                 trie,
                 &validator_accounts_update,
@@ -573,6 +624,13 @@ impl NightshadeRuntime {
         Ok(result)
     }
 
+    fn compiled_contract_cache(&self) -> Box<dyn CompiledContractCache> {
+        match &self.compiled_contract_cache {
+            Some(cache) => Box::new(cache.clone()),
+            None => Box::new(StoreCompiledContractCache::new(&self.store)),
+        }
+    }
+
     fn precompile_contracts(
         &self,
         epoch_id: &EpochId,
@@ -586,7 +644,7 @@ impl NightshadeRuntime {
         let protocol_version = self.get_epoch_protocol_version(epoch_id)?;
         let runtime_config = self.runtime_config_store.get_config(protocol_version);
         let compiled_contract_cache: Option<Box<dyn CompiledContractCache>> =
-            Some(Box::new(StoreCompiledContractCache::new(&self.store)));
+            Some(self.compiled_contract_cache());
         // Execute precompile_contract in parallel but prevent it from using more than half of all
         // threads so that node will still function normally.
         rayon::scope(|scope| {
@@ -1006,11 +1064,7 @@ impl RuntimeAdapter for NightshadeRuntime {
             use_flat_storage,
         )?;
 
-        // TODO (#6316): support chunk nodes caching for TrieRecordingStorage
-        if generate_storage_proof {
-            panic!("Storage proof generation is not enabled yet");
-        }
-        // let trie = if generate_storage_proof { trie.recording_reads() } else { trie };
+        let trie = if generate_storage_proof { trie.into_recording() } else { trie };
         match self.process_state_update(
             trie,
             shard_id,
@@ -1028,6 +1082,7 @@ impl RuntimeAdapter for NightshadeRuntime {
             is_new_chunk,
             is_first_block_with_chunk_of_version,
             states_to_patch,
+            None,
         ) {
             Ok(result) => Ok(result),
             Err(e) => match e {
@@ -1077,6 +1132,7 @@ impl RuntimeAdapter for NightshadeRuntime {
             is_new_chunk,
             is_first_block_with_chunk_of_version,
             Default::default(),
+            None,
         )
     }
 
@@ -1159,13 +1215,15 @@ impl RuntimeAdapter for NightshadeRuntime {
                     block_hash: *block_hash,
                 })
             }
-            QueryRequest::ViewState { account_id, prefix, include_proof } => {
+            QueryRequest::ViewState { account_id, prefix, include_proof, start_key, limit } => {
                 let view_state_result = self
                     .view_state(
                         &shard_uid,
                         *state_root,
                         account_id,
                         prefix.as_ref(),
+                        start_key.as_deref(),
+                        *limit,
                         *include_proof,
                     )
                     .map_err(|err| {
@@ -1223,6 +1281,213 @@ impl RuntimeAdapter for NightshadeRuntime {
         }
     }
 
+    fn trace_call(
+        &self,
+        shard_uid: ShardUId,
+        state_root: &StateRoot,
+        block_height: BlockHeight,
+        block_timestamp: u64,
+        prev_block_hash: &CryptoHash,
+        block_hash: &CryptoHash,
+        epoch_id: &EpochId,
+        contract_id: &AccountId,
+        method_name: &str,
+        args: &[u8],
+        extended_gas_profile: bool,
+    ) -> Result<FunctionCallTraceView, near_chain::near_chain_primitives::error::QueryError> {
+        let (epoch_height, current_protocol_version) = {
+            let epoch_manager = self.epoch_manager.read();
+            let epoch_info = epoch_manager.get_epoch_info(epoch_id).map_err(|err| {
+                near_chain::near_chain_primitives::error::QueryError::from_epoch_error(
+                    err,
+                    block_height,
+                    *block_hash,
+                )
+            })?;
+            (epoch_info.epoch_height(), epoch_info.protocol_version())
+        };
+        let state_update = self.tries.new_trie_update_view(shard_uid, *state_root);
+        let view_state = ViewApplyState {
+            block_height,
+            prev_block_hash: *prev_block_hash,
+            block_hash: *block_hash,
+            epoch_id: epoch_id.clone(),
+            epoch_height,
+            block_timestamp,
+            current_protocol_version,
+            cache: Some(self.compiled_contract_cache()),
+        };
+        self.trie_viewer()
+            .trace_function_call(
+                state_update,
+                view_state,
+                contract_id,
+                method_name,
+                args,
+                &self.epoch_manager,
+                extended_gas_profile,
+            )
+            .map_err(|err| {
+                near_chain::near_chain_primitives::error::QueryError::from_call_function_error(
+                    err,
+                    block_height,
+                    *block_hash,
+                )
+            })
+    }
+
+    fn trace_receipt(
+        &self,
+        receipt_id: &CryptoHash,
+        extended_gas_profile: bool,
+        shard_id: ShardId,
+        state_root: &StateRoot,
+        height: BlockHeight,
+        block_timestamp: u64,
+        prev_block_hash: &CryptoHash,
+        block_hash: &CryptoHash,
+        receipts: &[Receipt],
+        transactions: &[SignedTransaction],
+        last_validator_proposals: ValidatorStakeIter,
+        gas_price: Balance,
+        gas_limit: Gas,
+        challenges_result: &ChallengesResult,
+        random_seed: CryptoHash,
+        is_first_block_with_chunk_of_version: bool,
+    ) -> Result<Vec<FunctionCallTraceView>, Error> {
+        // Flat storage only serves the latest state, while traced chunks are usually older.
+        let trie = self.get_trie_for_shard(shard_id, prev_block_hash, *state_root, false)?;
+        let tracer = Arc::new(ExecutionTracer::new(*receipt_id, extended_gas_profile));
+        self.process_state_update(
+            trie,
+            shard_id,
+            height,
+            block_hash,
+            block_timestamp,
+            prev_block_hash,
+            receipts,
+            transactions,
+            last_validator_proposals,
+            gas_price,
+            gas_limit,
+            challenges_result,
+            random_seed,
+            true,
+            is_first_block_with_chunk_of_version,
+            Default::default(),
+            Some(Arc::clone(&tracer)),
+        )?;
+        Ok(tracer.take_traces())
+    }
+
+    fn estimate_gas(
+        &self,
+        transaction: &SignedTransaction,
+        state_roots: &[StateRoot],
+        block_height: BlockHeight,
+        block_timestamp: u64,
+        block_hash: &CryptoHash,
+        gas_price: Balance,
+        random_seed: CryptoHash,
+    ) -> Result<Vec<ExecutionOutcomeWithId>, Error> {
+        let epoch_id = self.get_epoch_id_from_prev_block(block_hash)?;
+        let shard_layout = self.get_shard_layout(&epoch_id)?;
+        if state_roots.len() as NumShards != shard_layout.num_shards() {
+            return Err(Error::Other(
+                "Cannot estimate gas while the shard layout is changing".to_string(),
+            ));
+        }
+        let current_protocol_version = self.get_epoch_protocol_version(&epoch_id)?;
+        let config = self.runtime_config_store.get_config(current_protocol_version).clone();
+        let max_gas_burnt = config.wasm_config.limit_config.max_total_prepaid_gas;
+        let mut apply_state = ApplyState {
+            block_height: block_height + 1,
+            prev_block_hash: *block_hash,
+            block_hash: *block_hash,
+            epoch_id: epoch_id.clone(),
+            epoch_height: self.get_epoch_height_from_prev_block(block_hash)?,
+            gas_price,
+            block_timestamp,
+            // Everything spawned by the transaction is paid for by its prepaid gas, so more gas
+            // than that is never burnt by a single chunk.
+            gas_limit: Some(max_gas_burnt),
+            random_seed,
+            current_protocol_version,
+            config,
+            cache: Some(self.compiled_contract_cache()),
+            is_new_chunk: true,
+            migration_data: Arc::clone(&self.migration_data),
+            migration_flags: MigrationFlags::default(),
+            verify_signatures: false,
+            process_delayed_receipts: false,
+        };
+
+        // Trie nodes written by the previous rounds are kept in memory only.
+        let mut overlay = Arc::new(HashMap::new());
+        let mut state_roots = state_roots.to_vec();
+        let signer_shard_id =
+            account_id_to_shard_id(&transaction.transaction.signer_id, &shard_layout);
+        let mut incoming_receipts: BTreeMap<ShardId, Vec<Receipt>> = BTreeMap::new();
+        incoming_receipts.insert(signer_shard_id, vec![]);
+        let mut transactions = vec![transaction.clone()];
+        let mut outcomes = vec![];
+        let mut total_gas_burnt: Gas = 0;
+        for _ in 0..MAX_ESTIMATE_GAS_ROUNDS {
+            if incoming_receipts.is_empty() || total_gas_burnt >= max_gas_burnt {
+                break;
+            }
+            let mut outgoing_receipts: BTreeMap<ShardId, Vec<Receipt>> = BTreeMap::new();
+            for (shard_id, receipts) in std::mem::take(&mut incoming_receipts) {
+                let shard_uid = ShardUId::from_shard_id_and_layout(shard_id, &shard_layout);
+                let state_root = &mut state_roots[shard_id as usize];
+                let trie = self
+                    .tries
+                    .get_view_trie_for_shard(shard_uid, *state_root)
+                    .with_overlay(Arc::clone(&overlay), *state_root);
+                let shard_transactions = if shard_id == signer_shard_id {
+                    std::mem::take(&mut transactions)
+                } else {
+                    vec![]
+                };
+                let apply_result = self
+                    .runtime
+                    .apply(
+                        trie,
+                        &None,
+                        &apply_state,
+                        &receipts,
+                        &shard_transactions,
+                        &self.epoch_manager,
+                        Default::default(),
+                    )
+                    .map_err(|err| match err {
+                        RuntimeError::InvalidTxError(_) => Error::InvalidTransactions,
+                        RuntimeError::StorageError(err) => Error::StorageError(err),
+                        RuntimeError::ValidatorError(err) => err.into(),
+                        err => Error::Other(err.to_string()),
+                    })?;
+                let nodes = Arc::make_mut(&mut overlay);
+                for insertion in apply_result.trie_changes.insertions() {
+                    nodes.insert(*insertion.hash(), Arc::from(insertion.payload()));
+                }
+                *state_root = apply_result.state_root;
+                for outcome in &apply_result.outcomes {
+                    total_gas_burnt = total_gas_burnt.saturating_add(outcome.outcome.gas_burnt);
+                }
+                outcomes.extend(apply_result.outcomes);
+                for receipt in apply_result.outgoing_receipts {
+                    outgoing_receipts
+                        .entry(account_id_to_shard_id(&receipt.receiver_id, &shard_layout))
+                        .or_default()
+                        .push(receipt);
+                }
+            }
+            incoming_receipts = outgoing_receipts;
+            apply_state.block_height += 1;
+        }
+        Ok(outcomes)
+    }
+
     /// Returns StorageError when storage is inconsistent.
     /// This is possible with the used isolation level + running ViewClient in a separate thread
     /// `block_hash` is a block whose `prev_state_root` is `state_root`
@@ -1469,7 +1734,7 @@ impl node_runtime::adapter::ViewRuntimeAdapter for NightshadeRuntime {
         account_id: &AccountId,
     ) -> Result<Account, node_runtime::state_viewer::errors::ViewAccountError> {
         let state_update = self.tries.new_trie_update_view(*shard_uid, state_root);
-        self.trie_viewer.view_account(&state_update, account_id)
+        self.trie_viewer().view_account(&state_update, account_id)
     }
 
     fn view_contract_code(
@@ -1479,7 +1744,7 @@ impl node_runtime::adapter::ViewRuntimeAdapter for NightshadeRuntime {
         account_id: &AccountId,
     ) -> Result<ContractCode, node_runtime::state_viewer::errors::ViewContractCodeError> {
         let state_update = self.tries.new_trie_update_view(*shard_uid, state_root);
-        self.trie_viewer.view_contract_code(&state_update, account_id)
+        self.trie_viewer().view_contract_code(&state_update, account_id)
     }
 
     fn call_function(
@@ -1508,9 +1773,9 @@ impl node_runtime::adapter::ViewRuntimeAdapter for NightshadeRuntime {
             epoch_height,
             block_timestamp,
             current_protocol_version,
-            cache: Some(Box::new(StoreCompiledContractCache::new(&self.tries.get_store()))),
+            cache: Some(self.compiled_contract_cache()),
         };
-        self.trie_viewer.call_function(
+        self.trie_viewer().call_function(
             state_update,
             view_state,
             contract_id,
@@ -1529,7 +1794,7 @@ impl node_runtime::adapter::ViewRuntimeAdapter for NightshadeRuntime {
         public_key: &PublicKey,
     ) -> Result<AccessKey, node_runtime::state_viewer::errors::ViewAccessKeyError> {
         let state_update = self.tries.new_trie_update_view(*shard_uid, state_root);
-        self.trie_viewer.view_access_key(&state_update, account_id, public_key)
+        self.trie_viewer().view_access_key(&state_update, account_id, public_key)
     }
 
     fn view_access_keys(
@@ -1540,7 +1805,7 @@ impl node_runtime::adapter::ViewRuntimeAdapter for NightshadeRuntime {
     ) -> Result<Vec<(PublicKey, AccessKey)>, node_runtime::state_viewer::errors::ViewAccessKeyError>
     {
         let state_update = self.tries.new_trie_update_view(*shard_uid, state_root);
-        self.trie_viewer.view_access_keys(&state_update, account_id)
+        self.trie_viewer().view_access_keys(&state_update, account_id)
     }
 
     fn view_state(
@@ -1549,10 +1814,19 @@ impl node_runtime::adapter::ViewRuntimeAdapter for NightshadeRuntime {
         state_root: MerkleHash,
         account_id: &AccountId,
         prefix: &[u8],
+        start_key: Option<&[u8]>,
+        limit: Option<u64>,
         include_proof: bool,
     ) -> Result<ViewStateResult, node_runtime::state_viewer::errors::ViewStateError> {
         let state_update = self.tries.new_trie_update_view(*shard_uid, state_root);
-        self.trie_viewer.view_state(&state_update, account_id, prefix, include_proof)
+        self.trie_viewer().view_state_page(
+            &state_update,
+            account_id,
+            prefix,
+            start_key,
+            limit,
+            include_proof,
+        )
     }
 }
 
@@ -1620,7 +1894,7 @@ mod test {
             gas_price: Balance,
             gas_limit: Gas,
             challenges: &ChallengesResult,
-        ) -> (StateRoot, Vec<ValidatorStake>, Vec<Receipt>) {
+        ) -> (StateRoot, Vec<ValidatorStake>, Vec<Receipt>, Vec<ExecutionOutcomeWithId>) {
             let mut result = self
                 .apply_transactions(
                     shard_id,
@@ -1664,7 +1938,7 @@ mod test {
             }
             store_update.commit().unwrap();
 
-            (result.new_root, result.validator_proposals, result.outgoing_receipts)
+            (result.new_root, result.validator_proposals, result.outgoing_receipts, result.outcomes)
         }
     }
 
@@ -1715,6 +1989,7 @@ mod test {
         pub last_receipts: HashMap<ShardId, Vec<Receipt>>,
         pub last_shard_proposals: HashMap<ShardId, Vec<ValidatorStake>>,
         pub last_proposals: Vec<ValidatorStake>,
+        pub last_outcomes: Vec<ExecutionOutcomeWithId>,
         time: u64,
     }
 
@@ -1798,7 +2073,7 @@ mod test {
                 &genesis,
                 tracked_config,
                 None,
-                None,
+                MutableConfigValue::new(None, "max_gas_burnt_view"),
                 Some(RuntimeConfigStore::free()),
                 DEFAULT_GC_NUM_EPOCHS_TO_KEEP,
                 Default::default(),
@@ -1854,6 +2129,7 @@ mod test {
                 last_receipts: HashMap::default(),
                 last_proposals: vec![],
                 last_shard_proposals: HashMap::default(),
+                last_outcomes: vec![],
                 time: 0,
             }
         }
@@ -1870,8 +2146,9 @@ mod test {
             assert_eq!(chunk_mask.len() as NumShards, num_shards);
             let mut all_proposals = vec![];
             let mut all_receipts = vec![];
+            let mut all_outcomes = vec![];
             for i in 0..num_shards {
-                let (state_root, proposals, receipts) = self.runtime.update(
+                let (state_root, proposals, receipts, outcomes) = self.runtime.update(
                     &self.state_roots[i as usize],
                     i,
                     self.head.height + 1,
@@ -1887,6 +2164,7 @@ mod test {
                 );
                 self.state_roots[i as usize] = state_root;
                 all_receipts.extend(receipts);
+                all_outcomes.extend(outcomes);
                 all_proposals.append(&mut proposals.clone());
                 self.last_shard_proposals.insert(i as ShardId, proposals);
             }
@@ -1916,6 +2194,7 @@ mod test {
             }
             self.last_receipts = new_receipts;
             self.last_proposals = all_proposals;
+            self.last_outcomes = all_outcomes;
             self.time += 10u64.pow(9);
 
             self.head = Tip {
@@ -3144,6 +3423,66 @@ mod test {
     }
 
     /// Check that mainnet genesis hash still matches, to make sure that we're still backwards compatible.
+    /// Estimated gas of a transaction matches its actual execution, including the receipts it
+    /// spawns.
+    #[test]
+    fn test_estimate_gas() {
+        let validators: Vec<AccountId> =
+            (0..2).map(|i| format!("test{}", i + 1).parse().unwrap()).collect();
+        let mut env = TestEnv::new(vec![validators.clone()], 5, false);
+        env.step_default(vec![]);
+        let signer = InMemorySigner::from_seed(
+            validators[0].clone(),
+            KeyType::ED25519,
+            validators[0].as_ref(),
+        );
+        let transaction = SignedTransaction::send_money(
+            1,
+            validators[0].clone(),
+            validators[1].clone(),
+            &signer,
+            10,
+            env.head.last_block_hash,
+        );
+        let gas_price = env.runtime.genesis_config.min_gas_price;
+        let estimate = env
+            .runtime
+            .estimate_gas(
+                &transaction,
+                &env.state_roots,
+                env.head.height,
+                0,
+                &env.head.last_block_hash,
+                gas_price,
+                CryptoHash::default(),
+            )
+            .unwrap();
+        // The estimate doesn't change the state.
+        assert_eq!(
+            env.view_account(&validators[1]).amount,
+            TESTING_INIT_BALANCE - TESTING_INIT_STAKE
+        );
+
+        let mut outcomes = vec![];
+        env.step_default(vec![transaction.clone()]);
+        outcomes.extend(env.last_outcomes.clone());
+        while !env.last_receipts.is_empty() {
+            env.step_default(vec![]);
+            outcomes.extend(env.last_outcomes.clone());
+        }
+        assert_eq!(estimate.len(), outcomes.len());
+        for (estimated, executed) in estimate.iter().zip(&outcomes) {
+            assert_eq!(estimated.outcome.gas_burnt, executed.outcome.gas_burnt);
+            assert_eq!(estimated.outcome.tokens_burnt, executed.outcome.tokens_burnt);
+            assert_eq!(estimated.outcome.executor_id, executed.outcome.executor_id);
+        }
+        assert_eq!(estimate[0].id, transaction.get_hash());
+        assert_eq!(
+            env.view_account(&validators[1]).amount,
+            TESTING_INIT_BALANCE - TESTING_INIT_STAKE + 10
+        );
+    }
+
     #[test]
     fn test_genesis_hash() {
         let genesis = near_mainnet_res::mainnet_genesis();
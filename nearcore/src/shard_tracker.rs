@@ -1,14 +1,20 @@
 use crate::append_only_map::AppendOnlyMap;
-use near_chain_configs::ClientConfig;
+use near_chain_configs::{ClientConfig, MutableConfigValue};
 use near_epoch_manager::EpochManagerHandle;
 use near_primitives::errors::EpochError;
 use near_primitives::hash::CryptoHash;
-use near_primitives::shard_layout::account_id_to_shard_id;
+use near_primitives::shard_layout::{account_id_to_shard_id, ShardLayout};
 use near_primitives::types::{AccountId, EpochId, ShardId};
 
 pub enum TrackedConfig {
     Accounts(Vec<AccountId>),
     AllShards,
+    /// Follows `tracked_accounts` and `tracked_shards` of the client config,
+    /// which can be changed while the node is running.
+    Client {
+        tracked_accounts: MutableConfigValue<Vec<AccountId>>,
+        tracked_shards: MutableConfigValue<Vec<ShardId>>,
+    },
 }
 
 impl TrackedConfig {
@@ -17,12 +23,31 @@ impl TrackedConfig {
     }
 
     pub fn from_config(config: &ClientConfig) -> Self {
-        if config.tracked_shards.is_empty() {
-            TrackedConfig::Accounts(config.tracked_accounts.clone())
-        } else {
-            TrackedConfig::AllShards
+        TrackedConfig::Client {
+            tracked_accounts: config.tracked_accounts.clone(),
+            tracked_shards: config.tracked_shards.clone(),
         }
     }
+
+    /// Returns which shards of the layout are currently tracked.
+    fn tracking_mask(&self, shard_layout: &ShardLayout) -> BitMask {
+        let tracked_accounts = match self {
+            TrackedConfig::Accounts(tracked_accounts) => tracked_accounts.clone(),
+            TrackedConfig::AllShards => return vec![true; shard_layout.num_shards() as usize],
+            TrackedConfig::Client { tracked_accounts, tracked_shards } => {
+                if !tracked_shards.get().is_empty() {
+                    return vec![true; shard_layout.num_shards() as usize];
+                }
+                tracked_accounts.get()
+            }
+        };
+        let mut tracking_mask = vec![false; shard_layout.num_shards() as usize];
+        for account_id in &tracked_accounts {
+            let shard_id = account_id_to_shard_id(account_id, shard_layout);
+            *tracking_mask.get_mut(shard_id as usize).unwrap() = true;
+        }
+        tracking_mask
+    }
 }
 
 // bit mask for which shard to track
@@ -31,9 +56,12 @@ type BitMask = Vec<bool>;
 /// Tracker that tracks shard ids and accounts. Right now, it only supports two modes
 /// TrackedConfig::Accounts(accounts): track the shards where `accounts` belong to
 /// TrackedConfig::AllShards: track all shards
+/// TrackedConfig::Client follows the client config and picks one of the modes
+/// above for each epoch.
 pub struct ShardTracker {
     tracked_config: TrackedConfig,
-    /// Stores shard tracking information by epoch, only useful if TrackedState == Accounts
+    /// Stores shard tracking information by epoch.  Once tracked shards of an
+    /// epoch are determined, they don't change even if the config does.
     tracking_shards: AppendOnlyMap<EpochId, BitMask>,
     /// Epoch manager that for given block hash computes the epoch id.
     epoch_manager: EpochManagerHandle,
@@ -44,27 +72,21 @@ impl ShardTracker {
         ShardTracker { tracked_config, tracking_shards: AppendOnlyMap::new(), epoch_manager }
     }
 
+    fn tracking_mask(&self, epoch_id: &EpochId) -> Result<std::sync::Arc<BitMask>, EpochError> {
+        let epoch_manager = self.epoch_manager.read();
+        let shard_layout = epoch_manager.get_shard_layout(epoch_id)?;
+        Ok(self
+            .tracking_shards
+            .get_or_insert(epoch_id, || self.tracked_config.tracking_mask(&shard_layout)))
+    }
+
     fn tracks_shard_at_epoch(
         &self,
         shard_id: ShardId,
         epoch_id: &EpochId,
     ) -> Result<bool, EpochError> {
-        match &self.tracked_config {
-            TrackedConfig::Accounts(tracked_accounts) => {
-                let epoch_manager = self.epoch_manager.read();
-                let shard_layout = epoch_manager.get_shard_layout(epoch_id)?;
-                let tracking_mask = self.tracking_shards.get_or_insert(epoch_id, || {
-                    let mut tracking_mask = vec![false; shard_layout.num_shards() as usize];
-                    for account_id in tracked_accounts {
-                        let shard_id = account_id_to_shard_id(account_id, &shard_layout);
-                        *tracking_mask.get_mut(shard_id as usize).unwrap() = true;
-                    }
-                    tracking_mask
-                });
-                Ok(tracking_mask.get(shard_id as usize).copied().unwrap_or(false))
-            }
-            TrackedConfig::AllShards => Ok(true),
-        }
+        let tracking_mask = self.tracking_mask(epoch_id)?;
+        Ok(tracking_mask.get(shard_id as usize).copied().unwrap_or(false))
     }
 
     fn tracks_shard(&self, shard_id: ShardId, prev_hash: &CryptoHash) -> Result<bool, EpochError> {
@@ -75,6 +97,35 @@ impl ShardTracker {
         self.tracks_shard_at_epoch(shard_id, &epoch_id)
     }
 
+    /// Returns whether `shard_id` of the epoch of the block after `prev_hash`
+    /// is tracked in the next epoch.  If the shard layout changes, it's enough
+    /// for one of the shards it splits into to be tracked.
+    fn will_track_shard(
+        &self,
+        shard_id: ShardId,
+        prev_hash: &CryptoHash,
+    ) -> Result<bool, EpochError> {
+        let (epoch_id, next_epoch_id, shard_layout, next_shard_layout) = {
+            let epoch_manager = self.epoch_manager.read();
+            let epoch_id = epoch_manager.get_epoch_id_from_prev_block(prev_hash)?;
+            let next_epoch_id = epoch_manager.get_next_epoch_id_from_prev_block(prev_hash)?;
+            let shard_layout = epoch_manager.get_shard_layout(&epoch_id)?;
+            let next_shard_layout = epoch_manager.get_shard_layout(&next_epoch_id)?;
+            (epoch_id, next_epoch_id, shard_layout, next_shard_layout)
+        };
+        // This also fixes tracked shards of the next epoch so that the node
+        // has state of all shards it's going to track.
+        let next_tracking_mask = self.tracking_mask(&next_epoch_id)?;
+        let tracks_next =
+            |shard_id: ShardId| next_tracking_mask.get(shard_id as usize).copied().unwrap_or(false);
+        if shard_layout == next_shard_layout {
+            return Ok(tracks_next(shard_id));
+        }
+        let split_shard_ids = next_shard_layout.get_split_shard_ids(shard_id).unwrap_or_default();
+        Ok(self.tracks_shard_at_epoch(shard_id, &epoch_id)?
+            || split_shard_ids.into_iter().any(tracks_next))
+    }
+
     pub fn care_about_shard(
         &self,
         account_id: Option<&AccountId>,
@@ -97,8 +148,7 @@ impl ShardTracker {
                 return true;
             }
         }
-        matches!(self.tracked_config, TrackedConfig::AllShards)
-            || self.tracks_shard(shard_id, parent_hash).unwrap_or(false)
+        self.tracks_shard(shard_id, parent_hash).unwrap_or(false)
     }
 
     // `shard_id` always refers to a shard in the current epoch that the next block from `parent_hash` belongs
@@ -124,8 +174,7 @@ impl ShardTracker {
                 return true;
             }
         }
-        matches!(self.tracked_config, TrackedConfig::AllShards)
-            || self.tracks_shard(shard_id, parent_hash).unwrap_or(false)
+        self.will_track_shard(shard_id, parent_hash).unwrap_or(false)
     }
 }
 
@@ -133,6 +182,7 @@ impl ShardTracker {
 mod tests {
     use super::{account_id_to_shard_id, ShardTracker};
     use crate::shard_tracker::TrackedConfig;
+    use near_chain_configs::MutableConfigValue;
     use near_crypto::{KeyType, PublicKey};
     use near_epoch_manager::test_utils::hash_range;
     use near_epoch_manager::{EpochManager, EpochManagerHandle, RewardCalculator};
@@ -290,6 +340,65 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_track_shards_config_change() {
+        let num_shards = 4;
+        let epoch_manager = get_epoch_manager(PROTOCOL_VERSION, num_shards, false);
+        let tracked_accounts = MutableConfigValue::new(
+            vec!["test1".parse().unwrap()],
+            "test_track_shards_config_change_tracked_accounts",
+        );
+        let tracked_shards =
+            MutableConfigValue::new(vec![], "test_track_shards_config_change_tracked_shards");
+        let tracker = ShardTracker::new(
+            TrackedConfig::Client {
+                tracked_accounts: tracked_accounts.clone(),
+                tracked_shards: tracked_shards.clone(),
+            },
+            epoch_manager.clone(),
+        );
+
+        let h = hash_range(8);
+        {
+            let mut epoch_manager = epoch_manager.write();
+            record_block(
+                &mut epoch_manager,
+                CryptoHash::default(),
+                h[0],
+                0,
+                vec![],
+                PROTOCOL_VERSION,
+            );
+            for i in 1..8 {
+                record_block(
+                    &mut epoch_manager,
+                    h[i - 1],
+                    h[i],
+                    i as u64,
+                    vec![],
+                    PROTOCOL_VERSION,
+                );
+            }
+        }
+        let shard_layout = epoch_manager.read().get_shard_layout(&EpochId::default()).unwrap();
+        let account_shards: HashSet<_> =
+            [account_id_to_shard_id(&"test1".parse().unwrap(), &shard_layout)].into();
+        let all_shards: HashSet<_> = (0..num_shards).collect();
+
+        // Every block starts a new epoch, so the block after h[4] is in the
+        // epoch after the one of the block after h[3].
+        assert_eq!(get_all_shards_care_about(&tracker, num_shards, &h[3]), account_shards);
+        assert_eq!(get_all_shards_will_care_about(&tracker, num_shards, &h[3]), account_shards);
+
+        tracked_shards.update(vec![0]);
+        // Shards of the current and the next epoch are already determined.
+        assert_eq!(get_all_shards_care_about(&tracker, num_shards, &h[3]), account_shards);
+        assert_eq!(get_all_shards_will_care_about(&tracker, num_shards, &h[3]), account_shards);
+        assert_eq!(get_all_shards_care_about(&tracker, num_shards, &h[4]), account_shards);
+        assert_eq!(get_all_shards_will_care_about(&tracker, num_shards, &h[4]), all_shards);
+        assert_eq!(get_all_shards_care_about(&tracker, num_shards, &h[5]), all_shards);
+    }
+
     #[test]
     fn test_track_shards_shard_layout_change() {
         let simple_nightshade_version = SimpleNightshade.protocol_version();
@@ -1,10 +1,10 @@
-#[cfg(unix)]
 use anyhow::Context;
 use clap::{Args, Parser};
 use near_amend_genesis::AmendGenesisCommand;
 use near_chain_configs::GenesisValidationMode;
 use near_client::ConfigUpdater;
 use near_cold_store_tool::ColdStoreCommand;
+use near_contract_runner::cli::RunContractCommand;
 use near_dyn_configs::{UpdateableConfigLoader, UpdateableConfigLoaderError, UpdateableConfigs};
 use near_jsonrpc_primitives::types::light_client::RpcLightClientExecutionProofResponse;
 use near_mirror::MirrorCommand;
@@ -18,9 +18,10 @@ use near_primitives::hash::CryptoHash;
 use near_primitives::merkle::compute_root_from_path;
 use near_primitives::types::{Gas, NumSeats, NumShards};
 use near_state_parts::cli::StatePartsCommand;
-use near_state_viewer::StateViewerSubCommand;
+use near_state_viewer::{PrecompileContractsCommand, StateViewerSubCommand};
 use near_store::db::RocksDB;
 use near_store::Mode;
+use near_validator_signer::SigningHistory;
 use serde_json::Value;
 use std::fs::File;
 use std::io::BufReader;
@@ -116,6 +117,15 @@ impl NeardCmd {
             NeardSubCommand::StateParts(cmd) => {
                 cmd.run()?;
             }
+            NeardSubCommand::RunContract(cmd) => {
+                cmd.run()?;
+            }
+            NeardSubCommand::PrecompileContracts(cmd) => {
+                cmd.run(&home_dir, genesis_validation)?;
+            }
+            NeardSubCommand::SigningHistory(cmd) => {
+                cmd.run(&home_dir)?;
+            }
         };
         Ok(())
     }
@@ -225,6 +235,24 @@ pub(super) enum NeardSubCommand {
 
     /// Connects to a NEAR node and sends state parts requests after the handshake is completed.
     StateParts(StatePartsCommand),
+
+    /// Executes a contract method locally on top of a given state, without
+    /// a node, and prints the outcome including state changes, created
+    /// receipts and gas profile.
+    RunContract(RunContractCommand),
+
+    /// Compiles every distinct contract deployed in the state at the head for
+    /// the current protocol version into the filesystem compiled contract
+    /// cache, so that nodes using the cache don’t pay compilation latency on
+    /// the first call to any of those contracts.
+    ///
+    /// Contracts already in the cache are skipped, so the command can be rerun
+    /// to pick up newly deployed contracts.
+    PrecompileContracts(PrecompileContractsCommand),
+
+    /// Exports or imports the record of blocks, chunks and approvals signed
+    /// by the validator, e.g. when moving the validator to another machine.
+    SigningHistory(SigningHistoryCommand),
 }
 
 #[derive(Parser)]
@@ -437,7 +465,7 @@ impl RunCmd {
             near_config.client_config.archive = true;
         }
         if self.max_gas_burnt_view.is_some() {
-            near_config.client_config.max_gas_burnt_view = self.max_gas_burnt_view;
+            near_config.client_config.max_gas_burnt_view.update(self.max_gas_burnt_view);
         }
 
         #[cfg(feature = "sandbox")]
@@ -453,6 +481,7 @@ impl RunCmd {
             }
         }
 
+        let chain_id = near_config.client_config.chain_id.clone();
         let (tx_crash, mut rx_crash) = broadcast::channel::<()>(16);
         let (tx_config_update, rx_config_update) =
             broadcast::channel::<Result<UpdateableConfigs, Arc<UpdateableConfigLoaderError>>>(16);
@@ -474,11 +503,19 @@ impl RunCmd {
             .await
             .global();
 
-            let updateable_configs = nearcore::dyn_config::read_updateable_configs(home_dir)
-                .unwrap_or_else(|e| panic!("Error reading dynamic configs: {:#}", e));
+            let mut remote_signer = nearcore::config::RemoteSignerConnection::default();
+            let updateable_configs = nearcore::dyn_config::read_updateable_configs(
+                home_dir,
+                &chain_id,
+                &mut remote_signer,
+            )
+            .unwrap_or_else(|e| panic!("Error reading dynamic configs: {:#}", e));
             let mut updateable_config_loader =
                 UpdateableConfigLoader::new(updateable_configs.clone(), tx_config_update);
-            let config_updater = ConfigUpdater::new(rx_config_update);
+            let config_updater = ConfigUpdater::new(
+                rx_config_update,
+                near_config.updateable_validator_signer.clone(),
+            );
 
             let nearcore::NearNode { rpc_servers, .. } =
                 nearcore::start_with_config_and_synchronization(
@@ -492,8 +529,11 @@ impl RunCmd {
             let sig = loop {
                 let sig = wait_for_interrupt_signal(home_dir, &mut rx_crash).await;
                 if sig == "SIGHUP" {
-                    let maybe_updateable_configs =
-                        nearcore::dyn_config::read_updateable_configs(home_dir);
+                    let maybe_updateable_configs = nearcore::dyn_config::read_updateable_configs(
+                        home_dir,
+                        &chain_id,
+                        &mut remote_signer,
+                    );
                     updateable_config_loader.reload(maybe_updateable_configs);
                 } else {
                     break sig;
@@ -743,6 +783,65 @@ impl VerifyProofSubCommand {
     }
 }
 
+#[derive(Parser)]
+pub(super) struct SigningHistoryCommand {
+    #[clap(subcommand)]
+    subcmd: SigningHistorySubCommand,
+}
+
+#[derive(clap::Subcommand)]
+enum SigningHistorySubCommand {
+    /// Writes the signing history of the validator to the given file.
+    Export {
+        #[clap(long)]
+        output: PathBuf,
+    },
+    /// Merges signing history exported on another machine into the local
+    /// one.  The node must be stopped while importing.
+    Import {
+        #[clap(long)]
+        input: PathBuf,
+    },
+}
+
+impl SigningHistoryCommand {
+    pub(super) fn run(self, home_dir: &Path) -> anyhow::Result<()> {
+        let config =
+            nearcore::config::Config::from_file(&home_dir.join(nearcore::config::CONFIG_FILENAME))?;
+        let path = home_dir.join(&config.signing_history_file);
+        match self.subcmd {
+            SigningHistorySubCommand::Export { output } => export_signing_history(&path, &output),
+            SigningHistorySubCommand::Import { input } => import_signing_history(&path, &input),
+        }
+    }
+}
+
+/// Fails rather than exporting an empty history, which would leave the
+/// validator unprotected on the target machine.
+fn export_signing_history(path: &Path, output: &Path) -> anyhow::Result<()> {
+    let history = SigningHistory::from_existing_file(path)
+        .with_context(|| format!("Failed reading signing history from {}", path.display()))?;
+    anyhow::ensure!(!history.epochs.is_empty(), "Signing history in {} is empty", path.display());
+    history
+        .write_to_file(output)
+        .with_context(|| format!("Failed writing signing history to {}", output.display()))
+}
+
+/// Merges the exported history into the local one, which may not exist yet
+/// on a new machine.  The exported history must exist.
+fn import_signing_history(path: &Path, input: &Path) -> anyhow::Result<()> {
+    let other = SigningHistory::from_existing_file(input)
+        .with_context(|| format!("Failed reading signing history from {}", input.display()))?;
+    let mut history = SigningHistory::from_file(path)
+        .with_context(|| format!("Failed reading signing history from {}", path.display()))?;
+    history.merge(other);
+    history
+        .write_to_file(path)
+        .with_context(|| format!("Failed writing signing history to {}", path.display()))?;
+    info!(target: "neard", path = %path.display(), "Imported signing history");
+    Ok(())
+}
+
 fn make_env_filter(verbose: Option<&str>) -> Result<EnvFilter, BuildEnvFilterError> {
     let env_filter = EnvFilterBuilder::from_env().verbose(verbose).finish()?;
     // Sandbox node can log to sandbox logging target via sandbox_debug_log host function.
@@ -810,4 +909,21 @@ mod tests {
             VerifyProofError::InvalidOutcomeRootProof
         );
     }
+
+    #[test]
+    fn signing_history_missing_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("signing_history.json");
+        let missing = dir.path().join("missing.json");
+        assert!(import_signing_history(&path, &missing).is_err());
+        assert!(!path.exists());
+        assert!(export_signing_history(&missing, &path).is_err());
+        assert!(!path.exists());
+
+        // An empty history isn't exported either.
+        SigningHistory::default().write_to_file(&path).unwrap();
+        let output = dir.path().join("output.json");
+        assert!(export_signing_history(&path, &output).is_err());
+        assert!(!output.exists());
+    }
 }
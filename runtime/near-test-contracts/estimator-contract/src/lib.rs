@@ -719,6 +719,357 @@ pub unsafe fn alt_bn128_pairing_check_10_10() {
     }
 }
 
+// #####################
+// # BLS12-381 & P-256 #
+// #####################
+
+#[cfg(feature = "nightly")]
+#[allow(unused)]
+extern "C" {
+    fn bls12381_p1_sum(value_len: u64, value_ptr: u64, register_id: u64);
+    fn bls12381_p2_sum(value_len: u64, value_ptr: u64, register_id: u64);
+    fn bls12381_g1_multiexp(value_len: u64, value_ptr: u64, register_id: u64);
+    fn bls12381_g2_multiexp(value_len: u64, value_ptr: u64, register_id: u64);
+    fn bls12381_map_fp_to_g1(value_len: u64, value_ptr: u64, register_id: u64);
+    fn bls12381_map_fp2_to_g2(value_len: u64, value_ptr: u64, register_id: u64);
+    fn bls12381_pairing_check(value_len: u64, value_ptr: u64) -> u64;
+    fn p256_verify(
+        sig_len: u64,
+        sig_ptr: u64,
+        msg_len: u64,
+        msg_ptr: u64,
+        pub_key_len: u64,
+        pub_key_ptr: u64,
+    ) -> u64;
+}
+
+/// Generator of G1, uncompressed.
+#[cfg(feature = "nightly")]
+#[rustfmt::skip]
+const BLS12381_G1: [u8; 96] = [23, 241, 211, 167, 49, 151, 215, 148, 38, 149, 99, 140, 79, 169, 172, 15, 195, 104, 140, 79, 151, 116, 185, 5, 161, 78, 58, 63, 23, 27, 172, 88, 108, 85, 232, 63, 249, 122, 26, 239, 251, 58, 240, 10, 219, 34, 198, 187, 8, 179, 244, 129, 227, 170, 160, 241, 160, 158, 48, 237, 116, 29, 138, 228, 252, 245, 224, 149, 213, 208, 10, 246, 0, 219, 24, 203, 44, 4, 179, 237, 208, 60, 199, 68, 162, 136, 138, 228, 12, 170, 35, 41, 70, 197, 231, 225];
+
+/// Generator of G2, uncompressed.
+#[cfg(feature = "nightly")]
+#[rustfmt::skip]
+const BLS12381_G2: [u8; 192] = [19, 224, 43, 96, 82, 113, 159, 96, 125, 172, 211, 160, 136, 39, 79, 101, 89, 107, 208, 208, 153, 32, 182, 26, 181, 218, 97, 187, 220, 127, 80, 73, 51, 76, 241, 18, 19, 148, 93, 87, 229, 172, 125, 5, 93, 4, 43, 126, 2, 74, 162, 178, 240, 143, 10, 145, 38, 8, 5, 39, 45, 197, 16, 81, 198, 228, 122, 212, 250, 64, 59, 2, 180, 81, 11, 100, 122, 227, 209, 119, 11, 172, 3, 38, 168, 5, 187, 239, 212, 128, 86, 200, 193, 33, 189, 184, 6, 6, 196, 160, 46, 167, 52, 204, 50, 172, 210, 176, 43, 194, 139, 153, 203, 62, 40, 126, 133, 167, 99, 175, 38, 116, 146, 171, 87, 46, 153, 171, 63, 55, 13, 39, 92, 236, 29, 161, 170, 169, 7, 95, 240, 95, 121, 190, 12, 229, 213, 39, 114, 125, 110, 17, 140, 201, 205, 198, 218, 46, 53, 26, 173, 253, 155, 170, 140, 189, 211, 167, 109, 66, 154, 105, 81, 96, 209, 44, 146, 58, 201, 204, 59, 172, 162, 137, 225, 147, 84, 134, 8, 184, 40, 1];
+
+/// Scalar used for multiexp, chosen to have about as many bits set as a
+/// random scalar.
+#[cfg(feature = "nightly")]
+const BLS12381_SCALAR: [u8; 32] = [
+    0x9e, 0x37, 0x79, 0xb9, 0x7f, 0x4a, 0x7c, 0x15, 0xf3, 0x9c, 0xc0, 0x60, 0x5c, 0xed, 0xc8, 0x34,
+    0x10, 0x82, 0x27, 0x6b, 0xf3, 0xa2, 0x72, 0x51, 0xf8, 0x6c, 0x6a, 0x11, 0xd0, 0xc1, 0x8e, 0x15,
+];
+
+// Functions to measure the `bls12381_*_base` and `bls12381_*_element` costs.
+// Each calls the host function on a buffer of `$elements` copies of the
+// element `$loop_n` times. Also measures `base`, `write_register_base`, and
+// `write_register_byte`. However the curve operations are more expensive than
+// register writing so we are okay overcharging them.
+//
+// All elements are the same which is fine for the estimation because `blst`
+// operations don't take significantly different time depending on the points.
+#[cfg(feature = "nightly")]
+macro_rules! bls12381_bench {
+    ($exp_name:ident, $host_fn:ident, $elements:expr, $loop_n:expr, [$($part:expr),*]) => {
+        #[no_mangle]
+        pub unsafe fn $exp_name() {
+            let mut buffer = [0u8; 10 * 288];
+            let mut len = 0;
+            for _ in 0..$elements {
+                $(
+                    buffer[len..len + $part.len()].copy_from_slice(&$part);
+                    len += $part.len();
+                )*
+            }
+            for _ in 0..$loop_n {
+                $host_fn(len as u64, buffer.as_ptr() as u64, 0);
+            }
+        }
+    };
+}
+
+#[cfg(feature = "nightly")]
+bls12381_bench!(bls12381_p1_sum_1_1k, bls12381_p1_sum, 1, 1_000, [[0u8], BLS12381_G1]);
+#[cfg(feature = "nightly")]
+bls12381_bench!(bls12381_p1_sum_10_1k, bls12381_p1_sum, 10, 1_000, [[0u8], BLS12381_G1]);
+#[cfg(feature = "nightly")]
+bls12381_bench!(bls12381_p2_sum_1_1k, bls12381_p2_sum, 1, 1_000, [[0u8], BLS12381_G2]);
+#[cfg(feature = "nightly")]
+bls12381_bench!(bls12381_p2_sum_10_1k, bls12381_p2_sum, 10, 1_000, [[0u8], BLS12381_G2]);
+#[cfg(feature = "nightly")]
+bls12381_bench!(
+    bls12381_g1_multiexp_1_10,
+    bls12381_g1_multiexp,
+    1,
+    10,
+    [BLS12381_G1, BLS12381_SCALAR]
+);
+#[cfg(feature = "nightly")]
+bls12381_bench!(
+    bls12381_g1_multiexp_10_10,
+    bls12381_g1_multiexp,
+    10,
+    10,
+    [BLS12381_G1, BLS12381_SCALAR]
+);
+#[cfg(feature = "nightly")]
+bls12381_bench!(
+    bls12381_g2_multiexp_1_10,
+    bls12381_g2_multiexp,
+    1,
+    10,
+    [BLS12381_G2, BLS12381_SCALAR]
+);
+#[cfg(feature = "nightly")]
+bls12381_bench!(
+    bls12381_g2_multiexp_10_10,
+    bls12381_g2_multiexp,
+    10,
+    10,
+    [BLS12381_G2, BLS12381_SCALAR]
+);
+// The x coordinates of the generators are valid field elements.
+#[cfg(feature = "nightly")]
+bls12381_bench!(bls12381_map_fp_to_g1_1_10, bls12381_map_fp_to_g1, 1, 10, [BLS12381_G1[..48]]);
+#[cfg(feature = "nightly")]
+bls12381_bench!(bls12381_map_fp_to_g1_10_10, bls12381_map_fp_to_g1, 10, 10, [BLS12381_G1[..48]]);
+#[cfg(feature = "nightly")]
+bls12381_bench!(bls12381_map_fp2_to_g2_1_10, bls12381_map_fp2_to_g2, 1, 10, [BLS12381_G2[..96]]);
+#[cfg(feature = "nightly")]
+bls12381_bench!(bls12381_map_fp2_to_g2_10_10, bls12381_map_fp2_to_g2, 10, 10, [BLS12381_G2[..96]]);
+
+// Compute pairing_check on 1 element 10 times. The pairing of the generators
+// isn't one, so the check fails, but that doesn't change the cost.
+#[cfg(feature = "nightly")]
+#[no_mangle]
+pub unsafe fn bls12381_pairing_check_1_10() {
+    bls12381_pairing_check_n(1, 10);
+}
+
+// Compute pairing_check on 10 elements 10 times.
+#[cfg(feature = "nightly")]
+#[no_mangle]
+pub unsafe fn bls12381_pairing_check_10_10() {
+    bls12381_pairing_check_n(10, 10);
+}
+
+#[cfg(feature = "nightly")]
+unsafe fn bls12381_pairing_check_n(elements: usize, loop_n: usize) {
+    let mut buffer = [0u8; 10 * 288];
+    for i in 0..elements {
+        buffer[i * 288..i * 288 + 96].copy_from_slice(&BLS12381_G1);
+        buffer[i * 288 + 96..(i + 1) * 288].copy_from_slice(&BLS12381_G2);
+    }
+    for _ in 0..loop_n {
+        bls12381_pairing_check((elements * 288) as u64, buffer.as_ptr() as u64);
+    }
+}
+
+// private key: [0x2a; 32]
+#[cfg(feature = "nightly")]
+#[rustfmt::skip]
+const P256_PUBLIC_KEY: [u8; 33] = [2, 12, 144, 29, 66, 60, 131, 28, 168, 94, 39, 199, 60, 38, 59, 161, 50, 114, 27, 185, 215, 168, 76, 79, 3, 128, 178, 166, 117, 111, 214, 1, 51];
+
+/// Function to measure `p256_verify_base`. Also measures `base`,
+/// `read_memory_base`, and `read_memory_byte`. However `p256_verify_base`
+/// computation is more expensive than memory reading so we are okay
+/// overcharging it.
+#[cfg(feature = "nightly")]
+#[no_mangle]
+pub unsafe fn p256_verify_32b_200() {
+    // 32 bytes message ("kajdlfkjalkfjaklfjdkladjfkljadsk")
+    let message: [u8; 32] = [
+        107, 97, 106, 100, 108, 102, 107, 106, 97, 108, 107, 102, 106, 97, 107, 108, 102, 106, 100,
+        107, 108, 97, 100, 106, 102, 107, 108, 106, 97, 100, 115, 107,
+    ];
+
+    #[rustfmt::skip]
+    let signature: [u8; 64] = [197, 214, 199, 246, 175, 79, 151, 225, 67, 175, 146, 181, 130, 114, 125, 44, 142, 15, 231, 175, 138, 12, 226, 42, 233, 44, 76, 9, 31, 79, 232, 145, 0, 58, 2, 208, 93, 93, 34, 205, 59, 20, 21, 150, 135, 208, 75, 127, 183, 179, 38, 88, 9, 98, 178, 118, 159, 49, 93, 108, 215, 119, 242, 167];
+
+    for _ in 0..200 {
+        let result = p256_verify(
+            signature.len() as _,
+            signature.as_ptr() as _,
+            message.len() as _,
+            message.as_ptr() as _,
+            P256_PUBLIC_KEY.len() as _,
+            P256_PUBLIC_KEY.as_ptr() as _,
+        );
+        // check that result was positive, as negative results could have exited
+        // early and do not reflect the full cost.
+        assert!(result == 1);
+    }
+}
+
+/// Function to measure `p256_verify_byte`.
+#[cfg(feature = "nightly")]
+#[no_mangle]
+pub unsafe fn p256_verify_16kib_64() {
+    // 16kB bytes message
+    let message = [b'x'; 16384];
+
+    #[rustfmt::skip]
+    let signature: [u8; 64] = [236, 118, 117, 5, 228, 226, 14, 184, 132, 5, 209, 5, 203, 164, 95, 146, 73, 84, 251, 37, 188, 131, 181, 191, 6, 91, 151, 181, 74, 96, 217, 126, 119, 242, 158, 98, 88, 219, 84, 125, 193, 161, 211, 157, 3, 230, 125, 159, 163, 197, 44, 63, 186, 68, 195, 221, 199, 184, 196, 45, 98, 208, 80, 214];
+
+    for _ in 0..64 {
+        let result = p256_verify(
+            signature.len() as _,
+            signature.as_ptr() as _,
+            message.len() as _,
+            message.as_ptr() as _,
+            P256_PUBLIC_KEY.len() as _,
+            P256_PUBLIC_KEY.as_ptr() as _,
+        );
+        assert!(result == 1);
+    }
+}
+
+// ################
+// # Yield/Resume #
+// ################
+
+#[cfg(feature = "nightly")]
+#[allow(unused)]
+extern "C" {
+    fn promise_yield_create(
+        method_name_len: u64,
+        method_name_ptr: u64,
+        arguments_len: u64,
+        arguments_ptr: u64,
+        gas: u64,
+        gas_weight: u64,
+        register_id: u64,
+    ) -> u64;
+    fn promise_yield_resume(
+        data_id_len: u64,
+        data_id_ptr: u64,
+        payload_len: u64,
+        payload_ptr: u64,
+    ) -> u32;
+}
+
+/// Method called back by the yielded promises, 10 bytes long.
+#[cfg(feature = "nightly")]
+const YIELD_CALLBACK: &[u8] = b"noop_yield";
+
+#[cfg(feature = "nightly")]
+#[no_mangle]
+pub fn noop_yield() {}
+
+/// Number of promises yielded by `yield_create_for_resume_100`.
+#[cfg(feature = "nightly")]
+const NUM_YIELDS_FOR_RESUME: usize = 100;
+
+/// Storage key under which `yield_create_for_resume_100` stores the data ids
+/// of the yielded promises.
+#[cfg(feature = "nightly")]
+const YIELD_DATA_IDS_KEY: &[u8] = b"yield_data_ids";
+
+/// Function to measure `yield_create_base`. Also measures `base`,
+/// `read_memory_base`, `read_memory_byte`, `write_register_base`,
+/// `write_register_byte` and the fees of the created receipts.
+#[cfg(feature = "nightly")]
+#[no_mangle]
+pub unsafe fn yield_create_10b_100() {
+    for _ in 0..100 {
+        promise_yield_create(
+            YIELD_CALLBACK.len() as _,
+            YIELD_CALLBACK.as_ptr() as _,
+            0,
+            0,
+            0,
+            0,
+            0,
+        );
+    }
+}
+
+/// Function to measure `yield_create_byte`, with 10kib arguments passed to
+/// the callback.
+#[cfg(feature = "nightly")]
+#[no_mangle]
+pub unsafe fn yield_create_10kib_100() {
+    let arguments = [b'x'; 10240];
+    for _ in 0..100 {
+        promise_yield_create(
+            YIELD_CALLBACK.len() as _,
+            YIELD_CALLBACK.as_ptr() as _,
+            arguments.len() as _,
+            arguments.as_ptr() as _,
+            0,
+            0,
+            0,
+        );
+    }
+}
+
+/// Setup for `yield_resume_0b_100` and `yield_resume_1kib_100`: yields
+/// promises and stores their data ids, so that a later function call can
+/// resume them.
+#[cfg(feature = "nightly")]
+#[no_mangle]
+pub unsafe fn yield_create_for_resume_100() {
+    let mut data_ids = [0u8; NUM_YIELDS_FOR_RESUME * 32];
+    for data_id in data_ids.chunks_mut(32) {
+        promise_yield_create(
+            YIELD_CALLBACK.len() as _,
+            YIELD_CALLBACK.as_ptr() as _,
+            0,
+            0,
+            0,
+            0,
+            0,
+        );
+        read_register(0, data_id.as_mut_ptr() as _);
+    }
+    storage_write(
+        YIELD_DATA_IDS_KEY.len() as _,
+        YIELD_DATA_IDS_KEY.as_ptr() as _,
+        data_ids.len() as _,
+        data_ids.as_ptr() as _,
+        0,
+    );
+}
+
+/// Resumes all promises yielded by `yield_create_for_resume_100` with the
+/// given payload. Also measures reading the data ids from storage, which is
+/// cheap compared to resuming 100 promises.
+#[cfg(feature = "nightly")]
+unsafe fn yield_resume_100(payload: &[u8]) {
+    let data_ids = [0u8; NUM_YIELDS_FOR_RESUME * 32];
+    let found = storage_read(YIELD_DATA_IDS_KEY.len() as _, YIELD_DATA_IDS_KEY.as_ptr() as _, 0);
+    assert!(found == 1);
+    read_register(0, data_ids.as_ptr() as _);
+    for data_id in data_ids.chunks(32) {
+        let resumed = promise_yield_resume(
+            data_id.len() as _,
+            data_id.as_ptr() as _,
+            payload.len() as _,
+            payload.as_ptr() as _,
+        );
+        // Check that the promise was resumed, as failed attempts exit early
+        // and do not reflect the full cost.
+        assert!(resumed == 1);
+    }
+}
+
+/// Function to measure `yield_resume_base`, needs `yield_create_for_resume_100`
+/// to be called first.
+#[cfg(feature = "nightly")]
+#[no_mangle]
+pub unsafe fn yield_resume_0b_100() {
+    yield_resume_100(&[]);
+}
+
+/// Function to measure `yield_resume_byte`, with the largest allowed payload,
+/// needs `yield_create_for_resume_100` to be called first.
+#[cfg(feature = "nightly")]
+#[no_mangle]
+pub unsafe fn yield_resume_1kib_100() {
+    yield_resume_100(&[b'x'; 1024]);
+}
+
 // ###############
 // # Storage API #
 // ###############
@@ -287,6 +287,16 @@ pub enum HostError {
     /// Invalid input to ed25519 signature verification function (e.g. signature cannot be
     /// derived from bytes).
     Ed25519VerifyInvalidInput { msg: String },
+    /// Invalid input to bls12381 family of functions (e.g., point which isn't
+    /// on the curve).
+    Bls12381InvalidInput { msg: String },
+    /// Invalid input to P-256 signature verification function (e.g. public key
+    /// of a wrong length).
+    P256VerifyInvalidInput { msg: String },
+    /// The payload a yielded promise is resumed with exceeded the limit.
+    YieldPayloadLengthExceeded { length: u64, limit: u64 },
+    /// The data id passed to `promise_yield_resume` is not 32 bytes long.
+    DataIdMalformed,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -492,6 +502,10 @@ impl std::fmt::Display for HostError {
             AltBn128InvalidInput { msg } => write!(f, "AltBn128 invalid input: {}", msg),
             ECRecoverError { msg } => write!(f, "ECDSA recover error: {}", msg),
             Ed25519VerifyInvalidInput { msg } => write!(f, "ED25519 signature verification error: {}", msg),
+            Bls12381InvalidInput { msg } => write!(f, "BLS12-381 invalid input: {}", msg),
+            P256VerifyInvalidInput { msg } => write!(f, "P-256 signature verification error: {}", msg),
+            YieldPayloadLengthExceeded { length, limit } => write!(f, "The length of a yield resume payload {} exceeds the limit {}", length, limit),
+            DataIdMalformed => write!(f, "The data id of a yielded promise is malformed"),
         }
     }
 }
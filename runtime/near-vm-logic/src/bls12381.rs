@@ -0,0 +1,282 @@
+//! Operations on the BLS12-381 curve backed by `blst`.
+//!
+//! Points are encoded uncompressed in the big-endian format of the ZCash
+//! specification which is also used by `blst`, Ethereum and most other
+//! libraries: G1 points take 96 bytes, G2 points 192 bytes and the point at
+//! infinity has the infinity flag set and all other bits zero.  Field elements
+//! are big-endian, with `Fp2` elements encoded as `c1 || c0` like the
+//! coordinates of G2 points.  Scalars are 32 bytes little-endian, to match
+//! the `alt_bn128` functions.
+use blst::{
+    blst_bendian_from_fp, blst_final_exp, blst_fp, blst_fp12, blst_fp12_is_one, blst_fp12_mul,
+    blst_fp12_one, blst_fp2, blst_fp_from_bendian, blst_map_to_g1, blst_map_to_g2,
+    blst_miller_loop, blst_p1, blst_p1_add_or_double, blst_p1_affine, blst_p1_affine_in_g1,
+    blst_p1_affine_is_inf, blst_p1_cneg, blst_p1_deserialize, blst_p1_from_affine, blst_p1_mult,
+    blst_p1_serialize, blst_p2, blst_p2_add_or_double, blst_p2_affine, blst_p2_affine_in_g2,
+    blst_p2_affine_is_inf, blst_p2_cneg, blst_p2_deserialize, blst_p2_from_affine, blst_p2_mult,
+    blst_p2_serialize, BLST_ERROR,
+};
+use near_vm_errors::{HostError, VMLogicError};
+
+const BOOL_SIZE: usize = 1;
+const SCALAR_SIZE: usize = 256 / 8;
+const FP_SIZE: usize = 384 / 8;
+const FP2_SIZE: usize = FP_SIZE * 2;
+const G1_SIZE: usize = FP_SIZE * 2;
+const G2_SIZE: usize = FP2_SIZE * 2;
+
+/// Bit of the first byte of an encoded point signalling compression.
+const COMPRESSION_FLAG: u8 = 0x80;
+
+pub(crate) struct InvalidInput {
+    pub(crate) msg: String,
+}
+
+impl InvalidInput {
+    fn new(msg: &str, bad_value: &[u8]) -> InvalidInput {
+        let msg = format!("{msg}: {bad_value:X?}");
+        InvalidInput { msg }
+    }
+}
+
+impl From<InvalidInput> for VMLogicError {
+    fn from(err: InvalidInput) -> Self {
+        HostError::Bls12381InvalidInput { msg: err.msg }.into()
+    }
+}
+
+pub(crate) fn split_elements<const ELEMENT_SIZE: usize>(
+    data: &[u8],
+) -> Result<&[[u8; ELEMENT_SIZE]], InvalidInput> {
+    stdx::as_chunks_exact(data).map_err(|e| InvalidInput { msg: e.to_string() })
+}
+
+const P1_SUM_ELEMENT_SIZE: usize = BOOL_SIZE + G1_SIZE;
+
+pub(crate) fn p1_sum(
+    elements: &[[u8; P1_SUM_ELEMENT_SIZE]],
+) -> Result<[u8; G1_SIZE], InvalidInput> {
+    let mut res = blst_p1::default();
+    for chunk in elements {
+        let (sign, point) = stdx::split_array(chunk);
+        let sign = decode_bool(sign)?;
+        let point = decode_g1(point, false)?;
+        let mut point = p1_from_affine(&point);
+        // SAFETY: all pointers are derived from references to initialized values.
+        unsafe {
+            blst_p1_cneg(&mut point, sign);
+            let acc = res;
+            blst_p1_add_or_double(&mut res, &acc, &point);
+        }
+    }
+    Ok(encode_g1(&res))
+}
+
+const P2_SUM_ELEMENT_SIZE: usize = BOOL_SIZE + G2_SIZE;
+
+pub(crate) fn p2_sum(
+    elements: &[[u8; P2_SUM_ELEMENT_SIZE]],
+) -> Result<[u8; G2_SIZE], InvalidInput> {
+    let mut res = blst_p2::default();
+    for chunk in elements {
+        let (sign, point) = stdx::split_array(chunk);
+        let sign = decode_bool(sign)?;
+        let point = decode_g2(point, false)?;
+        let mut point = p2_from_affine(&point);
+        // SAFETY: all pointers are derived from references to initialized values.
+        unsafe {
+            blst_p2_cneg(&mut point, sign);
+            let acc = res;
+            blst_p2_add_or_double(&mut res, &acc, &point);
+        }
+    }
+    Ok(encode_g2(&res))
+}
+
+const G1_MULTIEXP_ELEMENT_SIZE: usize = G1_SIZE + SCALAR_SIZE;
+
+pub(crate) fn g1_multiexp(
+    elements: &[[u8; G1_MULTIEXP_ELEMENT_SIZE]],
+) -> Result<[u8; G1_SIZE], InvalidInput> {
+    let mut res = blst_p1::default();
+    for chunk in elements {
+        let (point, scalar): (&[u8; G1_SIZE], &[u8; SCALAR_SIZE]) = stdx::split_array(chunk);
+        let point = p1_from_affine(&decode_g1(point, true)?);
+        let mut product = blst_p1::default();
+        // SAFETY: all pointers are derived from references to initialized
+        // values and the scalar is exactly `SCALAR_SIZE * 8` bits long.
+        unsafe {
+            blst_p1_mult(&mut product, &point, scalar.as_ptr(), SCALAR_SIZE * 8);
+            let acc = res;
+            blst_p1_add_or_double(&mut res, &acc, &product);
+        }
+    }
+    Ok(encode_g1(&res))
+}
+
+const G2_MULTIEXP_ELEMENT_SIZE: usize = G2_SIZE + SCALAR_SIZE;
+
+pub(crate) fn g2_multiexp(
+    elements: &[[u8; G2_MULTIEXP_ELEMENT_SIZE]],
+) -> Result<[u8; G2_SIZE], InvalidInput> {
+    let mut res = blst_p2::default();
+    for chunk in elements {
+        let (point, scalar): (&[u8; G2_SIZE], &[u8; SCALAR_SIZE]) = stdx::split_array(chunk);
+        let point = p2_from_affine(&decode_g2(point, true)?);
+        let mut product = blst_p2::default();
+        // SAFETY: all pointers are derived from references to initialized
+        // values and the scalar is exactly `SCALAR_SIZE * 8` bits long.
+        unsafe {
+            blst_p2_mult(&mut product, &point, scalar.as_ptr(), SCALAR_SIZE * 8);
+            let acc = res;
+            blst_p2_add_or_double(&mut res, &acc, &product);
+        }
+    }
+    Ok(encode_g2(&res))
+}
+
+pub(crate) fn map_fp_to_g1(elements: &[[u8; FP_SIZE]]) -> Result<Vec<u8>, InvalidInput> {
+    let mut res = Vec::with_capacity(elements.len() * G1_SIZE);
+    for chunk in elements {
+        let fp = decode_fp(chunk)?;
+        let mut point = blst_p1::default();
+        // SAFETY: `fp` is initialized and a null `v` is explicitly allowed.
+        unsafe { blst_map_to_g1(&mut point, &fp, std::ptr::null()) };
+        res.extend_from_slice(&encode_g1(&point));
+    }
+    Ok(res)
+}
+
+pub(crate) fn map_fp2_to_g2(elements: &[[u8; FP2_SIZE]]) -> Result<Vec<u8>, InvalidInput> {
+    let mut res = Vec::with_capacity(elements.len() * G2_SIZE);
+    for chunk in elements {
+        let fp2 = decode_fp2(chunk)?;
+        let mut point = blst_p2::default();
+        // SAFETY: `fp2` is initialized and a null `v` is explicitly allowed.
+        unsafe { blst_map_to_g2(&mut point, &fp2, std::ptr::null()) };
+        res.extend_from_slice(&encode_g2(&point));
+    }
+    Ok(res)
+}
+
+const PAIRING_CHECK_ELEMENT_SIZE: usize = G1_SIZE + G2_SIZE;
+
+pub(crate) fn pairing_check(
+    elements: &[[u8; PAIRING_CHECK_ELEMENT_SIZE]],
+) -> Result<bool, InvalidInput> {
+    // SAFETY: `blst_fp12_one` returns a pointer to a static constant.
+    let mut acc: blst_fp12 = unsafe { *blst_fp12_one() };
+    for chunk in elements {
+        let (g1, g2) = stdx::split_array(chunk);
+        let g1 = decode_g1(g1, true)?;
+        let g2 = decode_g2(g2, true)?;
+        // SAFETY: all pointers are derived from references to initialized values.
+        unsafe {
+            // Pairings with the point at infinity are one and don't change the
+            // product.
+            if blst_p1_affine_is_inf(&g1) || blst_p2_affine_is_inf(&g2) {
+                continue;
+            }
+            let mut miller_loop = blst_fp12::default();
+            blst_miller_loop(&mut miller_loop, &g2, &g1);
+            let prev = acc;
+            blst_fp12_mul(&mut acc, &prev, &miller_loop);
+        }
+    }
+    let mut res = blst_fp12::default();
+    // SAFETY: all pointers are derived from references to initialized values.
+    Ok(unsafe {
+        blst_final_exp(&mut res, &acc);
+        blst_fp12_is_one(&res)
+    })
+}
+
+fn p1_from_affine(point: &blst_p1_affine) -> blst_p1 {
+    let mut res = blst_p1::default();
+    // SAFETY: all pointers are derived from references to initialized values.
+    unsafe { blst_p1_from_affine(&mut res, point) };
+    res
+}
+
+fn p2_from_affine(point: &blst_p2_affine) -> blst_p2 {
+    let mut res = blst_p2::default();
+    // SAFETY: all pointers are derived from references to initialized values.
+    unsafe { blst_p2_from_affine(&mut res, point) };
+    res
+}
+
+fn encode_g1(point: &blst_p1) -> [u8; G1_SIZE] {
+    let mut res = [0u8; G1_SIZE];
+    // SAFETY: `res` has the size of a serialized uncompressed G1 point.
+    unsafe { blst_p1_serialize(res.as_mut_ptr(), point) };
+    res
+}
+
+fn encode_g2(point: &blst_p2) -> [u8; G2_SIZE] {
+    let mut res = [0u8; G2_SIZE];
+    // SAFETY: `res` has the size of a serialized uncompressed G2 point.
+    unsafe { blst_p2_serialize(res.as_mut_ptr(), point) };
+    res
+}
+
+/// Decodes a point on the curve, optionally checking that it belongs to G1.
+fn decode_g1(raw: &[u8; G1_SIZE], check_subgroup: bool) -> Result<blst_p1_affine, InvalidInput> {
+    if raw[0] & COMPRESSION_FLAG != 0 {
+        return Err(InvalidInput::new("compressed g1 points are not supported", raw));
+    }
+    let mut point = blst_p1_affine::default();
+    // SAFETY: `raw` has the size of a serialized uncompressed G1 point.
+    if unsafe { blst_p1_deserialize(&mut point, raw.as_ptr()) } != BLST_ERROR::BLST_SUCCESS {
+        return Err(InvalidInput::new("invalid g1", raw));
+    }
+    // SAFETY: `point` is initialized.
+    if check_subgroup && !unsafe { blst_p1_affine_in_g1(&point) } {
+        return Err(InvalidInput::new("g1 point not in subgroup", raw));
+    }
+    Ok(point)
+}
+
+/// Decodes a point on the twist, optionally checking that it belongs to G2.
+fn decode_g2(raw: &[u8; G2_SIZE], check_subgroup: bool) -> Result<blst_p2_affine, InvalidInput> {
+    if raw[0] & COMPRESSION_FLAG != 0 {
+        return Err(InvalidInput::new("compressed g2 points are not supported", raw));
+    }
+    let mut point = blst_p2_affine::default();
+    // SAFETY: `raw` has the size of a serialized uncompressed G2 point.
+    if unsafe { blst_p2_deserialize(&mut point, raw.as_ptr()) } != BLST_ERROR::BLST_SUCCESS {
+        return Err(InvalidInput::new("invalid g2", raw));
+    }
+    // SAFETY: `point` is initialized.
+    if check_subgroup && !unsafe { blst_p2_affine_in_g2(&point) } {
+        return Err(InvalidInput::new("g2 point not in subgroup", raw));
+    }
+    Ok(point)
+}
+
+fn decode_fp(raw: &[u8; FP_SIZE]) -> Result<blst_fp, InvalidInput> {
+    let mut fp = blst_fp::default();
+    let mut canonical = [0u8; FP_SIZE];
+    // SAFETY: both buffers have the size of a serialized field element.
+    unsafe {
+        blst_fp_from_bendian(&mut fp, raw.as_ptr());
+        blst_bendian_from_fp(canonical.as_mut_ptr(), &fp);
+    }
+    // Values which are not smaller than the modulus are reduced by blst.
+    if &canonical != raw {
+        return Err(InvalidInput::new("invalid fp", raw));
+    }
+    Ok(fp)
+}
+
+fn decode_fp2(raw: &[u8; FP2_SIZE]) -> Result<blst_fp2, InvalidInput> {
+    let (c1, c0) = stdx::split_array(raw);
+    Ok(blst_fp2 { fp: [decode_fp(c0)?, decode_fp(c1)?] })
+}
+
+fn decode_bool(raw: &[u8; BOOL_SIZE]) -> Result<bool, InvalidInput> {
+    match raw {
+        [0] => Ok(false),
+        [1] => Ok(true),
+        _ => Err(InvalidInput::new("invalid bool", raw)),
+    }
+}
@@ -1,7 +1,9 @@
 //! External dependencies of the near-vm-logic.
 
 use near_primitives::hash::CryptoHash;
+use near_primitives::profile::ExtendedProfileData;
 use near_primitives::types::TrieNodesCount;
+use near_primitives::views::HostFunctionCallView;
 use near_primitives_core::types::{AccountId, Balance};
 use near_vm_errors::VMLogicError;
 
@@ -239,6 +241,13 @@ pub trait External {
 
     fn generate_data_id(&mut self) -> CryptoHash;
 
+    /// Checks whether a promise yielded by the current account with the given `data_id` is still
+    /// waiting to be resumed and, if it is, marks it as resumed so it can't be resumed twice.
+    ///
+    /// Returns `Ok(false)` if the promise was never created, was already resumed or has timed
+    /// out.
+    fn promise_yield_resume(&mut self, data_id: &CryptoHash) -> Result<bool>;
+
     /// Returns amount of touched trie nodes by storage operations
     fn get_trie_nodes_count(&self) -> TrieNodesCount;
 
@@ -248,4 +257,21 @@ pub trait External {
 
     /// Returns total stake of validators in the current epoch.
     fn validator_total_stake(&self) -> Result<Balance>;
+
+    /// Returns the buffer host function calls should be recorded into, or `None` if execution
+    /// tracing is disabled.
+    ///
+    /// Tracing is meant for debugging tools only and is disabled by default.
+    fn execution_trace(&mut self) -> Option<&mut Vec<HostFunctionCallView>> {
+        None
+    }
+
+    /// Returns the profile gas usage per host function and per wasm function should be
+    /// accumulated into, or `None` if extended profiling is disabled.
+    ///
+    /// The VM checks this before the contract is loaded, as collecting the per wasm function gas
+    /// requires the contract to be instrumented differently. Disabled by default.
+    fn extended_profile(&mut self) -> Option<&mut ExtendedProfileData> {
+        None
+    }
 }
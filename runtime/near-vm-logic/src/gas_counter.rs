@@ -274,6 +274,11 @@ impl GasCounter {
         self.prepaid_gas - self.used_gas()
     }
 
+    /// Gas burnt so far on the given host cost.
+    pub fn ext_cost_gas(&self, cost: ExtCosts) -> Gas {
+        self.profile.get_ext_cost(cost)
+    }
+
     pub fn profile_data(&self) -> ProfileDataV3 {
         self.profile.clone()
     }
@@ -1,6 +1,7 @@
 #![doc = include_str!("../README.md")]
 
 mod alt_bn128;
+mod bls12381;
 mod context;
 mod dependencies;
 pub mod gas_counter;
@@ -16,7 +17,7 @@ mod vmstate;
 
 pub use context::VMContext;
 pub use dependencies::{External, MemSlice, MemoryLike, StorageGetMode, ValuePtr};
-pub use logic::{VMLogic, VMOutcome};
+pub use logic::{TracedValue, VMLogic, VMOutcome};
 pub use near_primitives_core::config::*;
 pub use near_primitives_core::profile;
 pub use near_primitives_core::types::ProtocolVersion;
@@ -8,9 +8,11 @@ use crate::{ReceiptMetadata, StorageGetMode, ValuePtr};
 use near_crypto::Secp256K1Signature;
 use near_primitives::checked_feature;
 use near_primitives::config::ViewConfig;
+use near_primitives::hash::CryptoHash;
 use near_primitives::profile::ProfileDataV3;
 use near_primitives::runtime::fees::RuntimeFeesConfig;
 use near_primitives::version::is_implicit_account_creation_enabled;
+use near_primitives::views::{CreatedPromiseView, HostFunctionCallView, StorageAccessView};
 use near_primitives_core::config::ExtCosts::*;
 use near_primitives_core::config::{ActionCosts, ExtCosts, VMConfig};
 use near_primitives_core::runtime::fees::{transfer_exec_fee, transfer_send_fee};
@@ -67,6 +69,11 @@ pub struct VMLogic<'a> {
 
     /// Handles the receipts generated through execution.
     receipt_manager: ReceiptManager,
+
+    /// Whether host function calls are recorded into [`External::execution_trace`].
+    tracing: bool,
+    /// Whether gas usage is accumulated into [`External::extended_profile`].
+    profiling: bool,
 }
 
 /// Promises API allows to create a DAG-structure that defines dependencies between smart contract
@@ -143,6 +150,8 @@ impl<'a> VMLogic<'a> {
         };
 
         let current_account_locked_balance = context.account_locked_balance;
+        let tracing = ext.execution_trace().is_some();
+        let profiling = ext.extended_profile().is_some();
         let gas_counter = GasCounter::new(
             config.ext_costs.clone(),
             max_gas_burnt,
@@ -168,6 +177,8 @@ impl<'a> VMLogic<'a> {
             total_log_length: 0,
             current_protocol_version,
             receipt_manager: ReceiptManager::default(),
+            tracing,
+            profiling,
         }
     }
 
@@ -181,6 +192,90 @@ impl<'a> VMLogic<'a> {
         &self.receipt_manager.action_receipts
     }
 
+    /// Whether host function calls should be wrapped with [`Self::trace_host_call`].
+    #[inline]
+    pub fn is_tracing(&self) -> bool {
+        self.tracing
+    }
+
+    /// Whether the contract should be instrumented to call [`Self::profiled_gas`] instead of
+    /// [`Self::gas`] and host function calls should be wrapped with [`Self::trace_host_call`].
+    #[inline]
+    pub fn is_profiling(&self) -> bool {
+        self.profiling
+    }
+
+    /// Calls host function `f` and records the call, its arguments, burnt gas and outcome into
+    /// the execution trace and the extended profile, whichever are enabled.
+    ///
+    /// Storage accesses and promises created by `f` are attached to the recorded call.
+    pub fn trace_host_call<T: TracedValue>(
+        &mut self,
+        name: &str,
+        args: &[(&str, u64)],
+        f: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        if let Some(calls) = self.ext.execution_trace() {
+            calls.push(HostFunctionCallView {
+                name: name.to_string(),
+                args: args.iter().map(|(name, value)| (name.to_string(), *value)).collect(),
+                gas_burnt: 0,
+                result: None,
+                error: None,
+                storage: vec![],
+                promises: vec![],
+            });
+        }
+        let burnt_gas_before = self.gas_counter.burnt_gas();
+        let bytes_read_before = self.bytes_read();
+        let bytes_written_before = self.bytes_written();
+        let result = f(self);
+        let gas_burnt = self.gas_counter.burnt_gas().saturating_sub(burnt_gas_before);
+        if let Some(call) = current_host_call(&mut *self.ext) {
+            call.gas_burnt = gas_burnt;
+            match &result {
+                Ok(value) => call.result = value.traced_value(),
+                Err(err) => call.error = Some(err.to_string()),
+            }
+        }
+        if self.profiling {
+            let bytes_read = self.bytes_read().saturating_sub(bytes_read_before);
+            let bytes_written = self.bytes_written().saturating_sub(bytes_written_before);
+            if let Some(profile) = self.ext.extended_profile() {
+                profile.record_host_call(name, gas_burnt, bytes_read, bytes_written);
+            }
+        }
+        result
+    }
+
+    /// Number of bytes read from the guest memory so far, as derived from the gas profile.
+    fn bytes_read(&self) -> u64 {
+        self.bytes_charged(read_memory_byte)
+    }
+
+    /// Number of bytes written into the guest memory or registers so far, as derived from the
+    /// gas profile.
+    fn bytes_written(&self) -> u64 {
+        self.bytes_charged(write_memory_byte)
+            .saturating_add(self.bytes_charged(write_register_byte))
+    }
+
+    fn bytes_charged(&self, cost: ExtCosts) -> u64 {
+        let gas = self.gas_counter.ext_cost_gas(cost);
+        gas.checked_div(cost.value(&self.config.ext_costs)).unwrap_or(0)
+    }
+
+    /// Attaches a newly created promise to the host function call being traced.
+    fn trace_created_promise(&mut self, promise_index: PromiseIndex, receipt_idx: ReceiptIndex) {
+        if !self.tracing {
+            return;
+        }
+        let receiver_id = self.get_account_by_receipt(receipt_idx).clone();
+        if let Some(call) = current_host_call(&mut *self.ext) {
+            call.promises.push(CreatedPromiseView { promise_index, receiver_id });
+        }
+    }
+
     #[cfg(test)]
     pub(crate) fn receipt_manager(&self) -> &ReceiptManager {
         &self.receipt_manager
@@ -876,6 +971,274 @@ impl<'a> VMLogic<'a> {
         Ok(res as u64)
     }
 
+    /// Computes the sum of signed points on the BLS12-381 curve \sum_i
+    /// (-1)^{sign_i} p_i.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - sequence of (sign:bool, p:E1), where E1 is the BLS12-381
+    ///   curve Y^2 = X^3 + 4 over Fp.  The points don't have to be in G1.
+    ///
+    ///   `value` is encoded as packed `[(u8, [u8; 96])]` slice, with points
+    ///   serialized uncompressed as big-endian `(x, y)`.  `0u8` is positive
+    ///   sign, `1u8` -- negative.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers
+    /// use more memory than the limit, the function returns `MemoryAccessViolation`.
+    ///
+    /// If a point is not on the curve or not correctly encoded, sign is not 0
+    /// or 1, or `value.len()%97!=0`, the function returns `Bls12381InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes +
+    /// bls12381_p1_sum_base + bls12381_p1_sum_element * num_elements`
+    pub fn bls12381_p1_sum(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<()> {
+        self.gas_counter.pay_base(bls12381_p1_sum_base)?;
+        let data = get_memory_or_register!(self, value_ptr, value_len)?;
+
+        let elements = crate::bls12381::split_elements(&data)?;
+        self.gas_counter.pay_per(bls12381_p1_sum_element, elements.len() as u64)?;
+
+        let res = crate::bls12381::p1_sum(elements)?;
+
+        self.registers.set(&mut self.gas_counter, &self.config.limit_config, register_id, res)
+    }
+
+    /// Computes the sum of signed points on the BLS12-381 twist \sum_i
+    /// (-1)^{sign_i} p_i.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - sequence of (sign:bool, p:E2), where E2 is the BLS12-381
+    ///   twist Y^2 = X^3 + 4(u + 1) over Fp2.  The points don't have to be in
+    ///   G2.
+    ///
+    ///   `value` is encoded as packed `[(u8, [u8; 192])]` slice, with points
+    ///   serialized uncompressed as big-endian `(x.c1, x.c0, y.c1, y.c0)`.
+    ///   `0u8` is positive sign, `1u8` -- negative.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers
+    /// use more memory than the limit, the function returns `MemoryAccessViolation`.
+    ///
+    /// If a point is not on the twist or not correctly encoded, sign is not 0
+    /// or 1, or `value.len()%193!=0`, the function returns `Bls12381InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes +
+    /// bls12381_p2_sum_base + bls12381_p2_sum_element * num_elements`
+    pub fn bls12381_p2_sum(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<()> {
+        self.gas_counter.pay_base(bls12381_p2_sum_base)?;
+        let data = get_memory_or_register!(self, value_ptr, value_len)?;
+
+        let elements = crate::bls12381::split_elements(&data)?;
+        self.gas_counter.pay_per(bls12381_p2_sum_element, elements.len() as u64)?;
+
+        let res = crate::bls12381::p2_sum(elements)?;
+
+        self.registers.set(&mut self.gas_counter, &self.config.limit_config, register_id, res)
+    }
+
+    /// Computes multiexp \sum_i s_i g_{1 i} in the G1 group of BLS12-381.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - sequence of (g1:G1, s:u256), with points encoded like for
+    ///   [`Self::bls12381_p1_sum`] and scalars as little-endian u256.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers
+    /// use more memory than the limit, the function returns `MemoryAccessViolation`.
+    ///
+    /// If a point is not on the curve, not in G1 or not correctly encoded, or
+    /// `value.len()%128!=0`, the function returns `Bls12381InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes +
+    /// bls12381_g1_multiexp_base + bls12381_g1_multiexp_element * num_elements`
+    pub fn bls12381_g1_multiexp(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<()> {
+        self.gas_counter.pay_base(bls12381_g1_multiexp_base)?;
+        let data = get_memory_or_register!(self, value_ptr, value_len)?;
+
+        let elements = crate::bls12381::split_elements(&data)?;
+        self.gas_counter.pay_per(bls12381_g1_multiexp_element, elements.len() as u64)?;
+
+        let res = crate::bls12381::g1_multiexp(elements)?;
+
+        self.registers.set(&mut self.gas_counter, &self.config.limit_config, register_id, res)
+    }
+
+    /// Computes multiexp \sum_i s_i g_{2 i} in the G2 group of BLS12-381.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - sequence of (g2:G2, s:u256), with points encoded like for
+    ///   [`Self::bls12381_p2_sum`] and scalars as little-endian u256.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers
+    /// use more memory than the limit, the function returns `MemoryAccessViolation`.
+    ///
+    /// If a point is not on the twist, not in G2 or not correctly encoded, or
+    /// `value.len()%224!=0`, the function returns `Bls12381InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes +
+    /// bls12381_g2_multiexp_base + bls12381_g2_multiexp_element * num_elements`
+    pub fn bls12381_g2_multiexp(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<()> {
+        self.gas_counter.pay_base(bls12381_g2_multiexp_base)?;
+        let data = get_memory_or_register!(self, value_ptr, value_len)?;
+
+        let elements = crate::bls12381::split_elements(&data)?;
+        self.gas_counter.pay_per(bls12381_g2_multiexp_element, elements.len() as u64)?;
+
+        let res = crate::bls12381::g2_multiexp(elements)?;
+
+        self.registers.set(&mut self.gas_counter, &self.config.limit_config, register_id, res)
+    }
+
+    /// Maps each Fp element to a point in the G1 group of BLS12-381 using the
+    /// simplified SWU map of the hash-to-curve specification, including
+    /// cofactor clearing.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - sequence of big-endian Fp elements, 48 bytes each.
+    ///
+    /// The resulting points are written to the register one after another,
+    /// encoded like for [`Self::bls12381_p1_sum`].
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers
+    /// use more memory than the limit, the function returns `MemoryAccessViolation`.
+    ///
+    /// If an element is not smaller than the field modulus or
+    /// `value.len()%48!=0`, the function returns `Bls12381InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes +
+    /// bls12381_map_fp_to_g1_base + bls12381_map_fp_to_g1_element * num_elements`
+    pub fn bls12381_map_fp_to_g1(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<()> {
+        self.gas_counter.pay_base(bls12381_map_fp_to_g1_base)?;
+        let data = get_memory_or_register!(self, value_ptr, value_len)?;
+
+        let elements = crate::bls12381::split_elements(&data)?;
+        self.gas_counter.pay_per(bls12381_map_fp_to_g1_element, elements.len() as u64)?;
+
+        let res = crate::bls12381::map_fp_to_g1(elements)?;
+
+        self.registers.set(&mut self.gas_counter, &self.config.limit_config, register_id, res)
+    }
+
+    /// Maps each Fp2 element to a point in the G2 group of BLS12-381 using the
+    /// simplified SWU map of the hash-to-curve specification, including
+    /// cofactor clearing.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - sequence of Fp2 elements encoded as big-endian `(c1, c0)`,
+    ///   96 bytes each.
+    ///
+    /// The resulting points are written to the register one after another,
+    /// encoded like for [`Self::bls12381_p2_sum`].
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers
+    /// use more memory than the limit, the function returns `MemoryAccessViolation`.
+    ///
+    /// If a coefficient is not smaller than the field modulus or
+    /// `value.len()%96!=0`, the function returns `Bls12381InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes +
+    /// bls12381_map_fp2_to_g2_base + bls12381_map_fp2_to_g2_element * num_elements`
+    pub fn bls12381_map_fp2_to_g2(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<()> {
+        self.gas_counter.pay_base(bls12381_map_fp2_to_g2_base)?;
+        let data = get_memory_or_register!(self, value_ptr, value_len)?;
+
+        let elements = crate::bls12381::split_elements(&data)?;
+        self.gas_counter.pay_per(bls12381_map_fp2_to_g2_element, elements.len() as u64)?;
+
+        let res = crate::bls12381::map_fp2_to_g2(elements)?;
+
+        self.registers.set(&mut self.gas_counter, &self.config.limit_config, register_id, res)
+    }
+
+    /// Computes pairing check on BLS12-381.
+    /// \prod_i e(g_{1 i}, g_{2 i}) should be equal one, e(g1, g2) is the
+    /// optimal Ate pairing.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - sequence of (g1:G1, g2:G2), with points encoded like for
+    ///   [`Self::bls12381_p1_sum`] and [`Self::bls12381_p2_sum`].
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers
+    /// use more memory than the limit, the function returns `MemoryAccessViolation`.
+    ///
+    /// If a point is not in its group or not correctly encoded, or
+    /// `value.len()%288!=0`, the function returns `Bls12381InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + bls12381_pairing_base + bls12381_pairing_element * num_elements`
+    pub fn bls12381_pairing_check(&mut self, value_len: u64, value_ptr: u64) -> Result<u64> {
+        self.gas_counter.pay_base(bls12381_pairing_base)?;
+        let data = get_memory_or_register!(self, value_ptr, value_len)?;
+
+        let elements = crate::bls12381::split_elements(&data)?;
+        self.gas_counter.pay_per(bls12381_pairing_element, elements.len() as u64)?;
+
+        let res = crate::bls12381::pairing_check(elements)?;
+
+        Ok(res as u64)
+    }
+
     /// Writes random seed into the register.
     ///
     /// # Errors
@@ -1179,6 +1542,82 @@ impl<'a> VMLogic<'a> {
         }
     }
 
+    /// Verify an ECDSA signature on the P-256 (secp256r1) curve given a message
+    /// and a public key.  The message is hashed with SHA-256, as done by
+    /// WebAuthn authenticators and most other P-256 signers.
+    ///
+    /// Returns a bool indicating success (1) or failure (0) as a `u64`.
+    ///
+    /// # Arguments
+    ///
+    /// * The signature is the 64 bytes `r || s`, both big-endian.  Signatures
+    ///   with high `s` are accepted.
+    /// * The public key is a SEC1 encoded point, either compressed (33 bytes)
+    ///   or uncompressed (65 bytes).
+    ///
+    /// # Errors
+    ///
+    /// * If the public key's size is not 33 or 65, or signature size is not
+    ///   equal to 64, returns [HostError::P256VerifyInvalidInput].
+    /// * If any of the signature, message or public key arguments are out of
+    ///   memory bounds, returns [`HostError::MemoryAccessViolation`]
+    ///
+    /// # Cost
+    ///
+    /// Each input can either be in memory or in a register, see
+    /// [`Self::ed25519_verify`] for how `input_cost` is computed.
+    ///
+    /// `input_cost(num_bytes_signature) + input_cost(num_bytes_message) +
+    ///  input_cost(num_bytes_public_key) + p256_verify_base +
+    ///  p256_verify_byte * num_bytes_message`
+    pub fn p256_verify(
+        &mut self,
+        signature_len: u64,
+        signature_ptr: u64,
+        message_len: u64,
+        message_ptr: u64,
+        public_key_len: u64,
+        public_key_ptr: u64,
+    ) -> Result<u64> {
+        use p256::ecdsa::signature::Verifier;
+
+        self.gas_counter.pay_base(p256_verify_base)?;
+
+        let signature: p256::ecdsa::Signature = {
+            let vec = get_memory_or_register!(self, signature_ptr, signature_len)?;
+            if vec.len() != 64 {
+                return Err(VMLogicError::HostError(HostError::P256VerifyInvalidInput {
+                    msg: "invalid signature length".to_string(),
+                }));
+            }
+            match p256::ecdsa::Signature::try_from(vec.as_ref()) {
+                Ok(signature) => signature,
+                Err(_) => return Ok(false as u64),
+            }
+        };
+
+        let message = get_memory_or_register!(self, message_ptr, message_len)?;
+        self.gas_counter.pay_per(p256_verify_byte, message.len() as u64)?;
+
+        let public_key: p256::ecdsa::VerifyingKey = {
+            let vec = get_memory_or_register!(self, public_key_ptr, public_key_len)?;
+            if vec.len() != 33 && vec.len() != 65 {
+                return Err(VMLogicError::HostError(HostError::P256VerifyInvalidInput {
+                    msg: "invalid public key length".to_string(),
+                }));
+            }
+            match p256::ecdsa::VerifyingKey::from_sec1_bytes(&vec) {
+                Ok(public_key) => public_key,
+                Err(_) => return Ok(false as u64),
+            }
+        };
+
+        match public_key.verify(&message, &signature) {
+            Err(_) => Ok(false as u64),
+            Ok(()) => Ok(true as u64),
+        }
+    }
+
     /// Called by gas metering injected into Wasm. Counts both towards `burnt_gas` and `used_gas`.
     ///
     /// # Errors
@@ -1190,6 +1629,20 @@ impl<'a> VMLogic<'a> {
         self.gas_counter.pay_wasm_gas(opcodes)
     }
 
+    /// Same as [`Self::gas`], but additionally attributes the charged gas to the wasm function
+    /// with the given index in the extended profile.
+    ///
+    /// Only contracts instrumented for profiling call this, see [`Self::is_profiling`].
+    pub fn profiled_gas(&mut self, function_index: u32, opcodes: u32) -> Result<()> {
+        let burnt_gas_before = self.gas_counter.burnt_gas();
+        let result = self.gas_counter.pay_wasm_gas(opcodes);
+        let gas_burnt = self.gas_counter.burnt_gas().saturating_sub(burnt_gas_before);
+        if let Some(profile) = self.ext.extended_profile() {
+            profile.add_wasm_gas(function_index, gas_burnt);
+        }
+        result
+    }
+
     // ################
     // # Promises API #
     // ################
@@ -1438,7 +1891,9 @@ impl<'a> VMLogic<'a> {
         self.pay_gas_for_new_receipt(sir, &[])?;
         let new_receipt_idx = self.receipt_manager.create_receipt(self.ext, vec![], account_id)?;
 
-        self.checked_push_promise(Promise::Receipt(new_receipt_idx))
+        let new_promise_idx = self.checked_push_promise(Promise::Receipt(new_receipt_idx))?;
+        self.trace_created_promise(new_promise_idx, new_receipt_idx);
+        Ok(new_promise_idx)
     }
 
     /// Creates a new promise towards given `account_id` without any actions attached, that is
@@ -1496,7 +1951,9 @@ impl<'a> VMLogic<'a> {
         let new_receipt_idx =
             self.receipt_manager.create_receipt(self.ext, receipt_dependencies, account_id)?;
 
-        self.checked_push_promise(Promise::Receipt(new_receipt_idx))
+        let new_promise_idx = self.checked_push_promise(Promise::Receipt(new_receipt_idx))?;
+        self.trace_created_promise(new_promise_idx, new_receipt_idx);
+        Ok(new_promise_idx)
     }
 
     /// Helper function to return the account id towards which the receipt is directed.
@@ -2099,6 +2556,162 @@ impl<'a> VMLogic<'a> {
         }
     }
 
+    /// Creates a promise to call `method_name` on the current account with the given arguments
+    /// once the promise is resumed with `promise_yield_resume` by a later function call, or after
+    /// `yield_timeout_length_in_blocks` blocks if it's never resumed. The callback can read the
+    /// payload as the result of the single promise it depends on; on timeout that promise is
+    /// failed.
+    ///
+    /// The data id identifying the yielded promise is written into `register_id`. Contracts
+    /// typically store it, or hand it out to an off-chain service, to resume the promise later.
+    ///
+    /// `gas` and `gas_weight` are treated as in `promise_batch_action_function_call_weight`.
+    ///
+    /// # Errors
+    ///
+    /// * If `method_name_len + method_name_ptr` or `arguments_len + arguments_ptr` points outside
+    /// the memory of the guest or host returns `MemoryAccessViolation`.
+    /// * If `method_name` is empty returns `EmptyMethodName`.
+    /// * If called as view function returns `ProhibitedInView`.
+    /// * If the total number of promises exceeds `max_promises_per_function_call_action` limit
+    ///   returns `NumPromisesExceeded`.
+    ///
+    /// # Returns
+    ///
+    /// Index of the new promise that uniquely identifies it within the current execution of the
+    /// method.
+    ///
+    /// # Cost
+    ///
+    /// `burnt_gas := base + yield_create_base + yield_create_byte * num_bytes + dispatch cost of
+    /// the receipt and the data dependency + dispatch function call fee + dispatch function call
+    /// fee per byte * num bytes + write_register_base + write_register_byte * 32`
+    /// `used_gas := burnt_gas + exec cost of the receipt and the function call + prepaid gas`
+    pub fn promise_yield_create(
+        &mut self,
+        method_name_len: u64,
+        method_name_ptr: u64,
+        arguments_len: u64,
+        arguments_ptr: u64,
+        gas: Gas,
+        gas_weight: u64,
+        register_id: u64,
+    ) -> Result<u64> {
+        self.gas_counter.pay_base(base)?;
+        if self.context.is_view() {
+            return Err(HostError::ProhibitedInView {
+                method_name: "promise_yield_create".to_string(),
+            }
+            .into());
+        }
+        self.gas_counter.pay_base(yield_create_base)?;
+        let method_name = get_memory_or_register!(self, method_name_ptr, method_name_len)?;
+        if method_name.is_empty() {
+            return Err(HostError::EmptyMethodName.into());
+        }
+        let arguments = get_memory_or_register!(self, arguments_ptr, arguments_len)?;
+        let method_name = method_name.into_owned();
+        let arguments = arguments.into_owned();
+        // Input can't be large enough to overflow
+        let num_bytes = method_name.len() as u64 + arguments.len() as u64;
+        self.gas_counter.pay_per(yield_create_byte, num_bytes)?;
+
+        // The yielded receipt depends on a single data receipt, sent by the current account
+        // either when resuming the promise or when it times out.
+        self.pay_gas_for_new_receipt(true, &[true])?;
+        self.pay_action_base(ActionCosts::function_call_base, true)?;
+        self.pay_action_per_byte(ActionCosts::function_call_byte, num_bytes, true)?;
+        self.gas_counter.prepay_gas(gas)?;
+
+        let current_account_id = self.context.current_account_id.clone();
+        let (new_receipt_idx, data_id) =
+            self.receipt_manager.create_promise_yield_receipt(self.ext, current_account_id);
+        self.receipt_manager.append_action_function_call_weight(
+            new_receipt_idx,
+            method_name,
+            arguments,
+            0,
+            gas,
+            GasWeight(gas_weight),
+        )?;
+        let new_promise_idx = self.checked_push_promise(Promise::Receipt(new_receipt_idx))?;
+        self.trace_created_promise(new_promise_idx, new_receipt_idx);
+
+        self.registers.set(
+            &mut self.gas_counter,
+            &self.config.limit_config,
+            register_id,
+            data_id.as_ref(),
+        )?;
+        Ok(new_promise_idx)
+    }
+
+    /// Resumes the promise created by `promise_yield_create` on the current account with the
+    /// given data id, delivering `payload` to its callback.
+    ///
+    /// Resuming is only allowed from the account that yielded the promise, so contracts decide
+    /// themselves who may supply the payload, e.g. by checking the signer of the transaction.
+    ///
+    /// # Errors
+    ///
+    /// * If `data_id_len + data_id_ptr` or `payload_len + payload_ptr` points outside the memory
+    /// of the guest or host returns `MemoryAccessViolation`.
+    /// * If the data id isn't 32 bytes long returns `DataIdMalformed`.
+    /// * If the payload is longer than `max_yield_payload_size` returns
+    /// `YieldPayloadLengthExceeded`.
+    /// * If called as view function returns `ProhibitedInView`.
+    ///
+    /// # Returns
+    ///
+    /// `1` if the promise was resumed, `0` if there is no yielded promise with the given data id
+    /// waiting to be resumed: it was never created, has already been resumed or has timed out.
+    /// Promises yielded in the current function call can only be resumed by a later one.
+    ///
+    /// # Cost
+    ///
+    /// `burnt_gas := base + yield_resume_base + yield_resume_byte * num_bytes + dispatch data
+    /// receipt fee per byte * num_bytes + cost of reading the data id and the payload from memory
+    /// or register`
+    /// `used_gas := burnt_gas + exec data receipt fee per byte * num_bytes`
+    pub fn promise_yield_resume(
+        &mut self,
+        data_id_len: u64,
+        data_id_ptr: u64,
+        payload_len: u64,
+        payload_ptr: u64,
+    ) -> Result<u32> {
+        self.gas_counter.pay_base(base)?;
+        if self.context.is_view() {
+            return Err(HostError::ProhibitedInView {
+                method_name: "promise_yield_resume".to_string(),
+            }
+            .into());
+        }
+        self.gas_counter.pay_base(yield_resume_base)?;
+        let data_id = get_memory_or_register!(self, data_id_ptr, data_id_len)?;
+        let data_id = CryptoHash::try_from(&data_id[..]).map_err(|_| HostError::DataIdMalformed)?;
+        let payload = get_memory_or_register!(self, payload_ptr, payload_len)?;
+        let limit = self.config.limit_config.max_yield_payload_size;
+        if payload.len() as u64 > limit {
+            return Err(HostError::YieldPayloadLengthExceeded {
+                length: payload.len() as u64,
+                limit,
+            }
+            .into());
+        }
+        self.gas_counter.pay_per(yield_resume_byte, payload.len() as u64)?;
+        let payload = payload.into_owned();
+        // The payload is delivered to the yielded receipt in a data receipt sent by the current
+        // account. Its base cost is paid when the promise is created.
+        self.pay_action_per_byte(ActionCosts::new_data_receipt_byte, payload.len() as u64, true)?;
+
+        if !self.ext.promise_yield_resume(&data_id)? {
+            return Ok(0);
+        }
+        self.receipt_manager.resume_promise_yield(data_id, payload);
+        Ok(1)
+    }
+
     // #####################
     // # Miscellaneous API #
     // #####################
@@ -2377,6 +2990,15 @@ impl<'a> VMLogic<'a> {
 
         self.gas_counter.add_trie_fees(&nodes_delta)?;
         self.ext.storage_set(&key, &value)?;
+        if self.tracing {
+            if let Some(call) = current_host_call(&mut *self.ext) {
+                call.storage.push(StorageAccessView::Write {
+                    key: key.to_vec(),
+                    value: value.to_vec(),
+                    evicted: evicted.clone(),
+                });
+            }
+        }
         let storage_config = &self.fees_config.storage_usage_config;
         match evicted {
             Some(old_value) => {
@@ -2472,6 +3094,12 @@ impl<'a> VMLogic<'a> {
             tn_db_reads = nodes_delta.db_reads,
             tn_mem_reads = nodes_delta.mem_reads,
         );
+        if self.tracing {
+            if let Some(call) = current_host_call(&mut *self.ext) {
+                call.storage
+                    .push(StorageAccessView::Read { key: key.to_vec(), value: read.clone() });
+            }
+        }
         match read {
             Some(value) => {
                 self.registers.set(
@@ -2539,6 +3167,14 @@ impl<'a> VMLogic<'a> {
             tn_mem_reads = nodes_delta.mem_reads,
             tn_db_reads = nodes_delta.db_reads,
         );
+        if self.tracing {
+            if let Some(call) = current_host_call(&mut *self.ext) {
+                call.storage.push(StorageAccessView::Remove {
+                    key: key.to_vec(),
+                    removed: removed.clone(),
+                });
+            }
+        }
 
         self.gas_counter.add_trie_fees(&nodes_delta)?;
         let storage_config = &self.fees_config.storage_usage_config;
@@ -2601,7 +3237,13 @@ impl<'a> VMLogic<'a> {
         );
 
         self.gas_counter.add_trie_fees(&nodes_delta)?;
-        Ok(res? as u64)
+        let res = res?;
+        if self.tracing {
+            if let Some(call) = current_host_call(&mut *self.ext) {
+                call.storage.push(StorageAccessView::HasKey { key: key.to_vec(), exists: res });
+            }
+        }
+        Ok(res as u64)
     }
 
     /// Debug print given utf-8 string to node log. It's only available in Sandbox node
@@ -2744,6 +3386,8 @@ impl<'a> VMLogic<'a> {
             logs: self.logs,
             profile,
             action_receipts: self.receipt_manager.action_receipts,
+            promise_yields: self.receipt_manager.promise_yields,
+            promise_resumes: self.receipt_manager.promise_resumes,
             aborted: None,
         }
     }
@@ -2852,6 +3496,35 @@ impl<'a> VMLogic<'a> {
     }
 }
 
+/// Returns the most recent host function call recorded into the execution trace, if tracing is
+/// enabled.
+fn current_host_call(ext: &mut dyn External) -> Option<&mut HostFunctionCallView> {
+    ext.execution_trace().and_then(|calls| calls.last_mut())
+}
+
+/// Conversion of host function return values into the form recorded in execution traces.
+pub trait TracedValue {
+    fn traced_value(&self) -> Option<u64>;
+}
+
+impl TracedValue for () {
+    fn traced_value(&self) -> Option<u64> {
+        None
+    }
+}
+
+impl TracedValue for u32 {
+    fn traced_value(&self) -> Option<u64> {
+        Some(u64::from(*self))
+    }
+}
+
+impl TracedValue for u64 {
+    fn traced_value(&self) -> Option<u64> {
+        Some(*self)
+    }
+}
+
 #[derive(PartialEq)]
 pub struct VMOutcome {
     pub balance: Balance,
@@ -2863,6 +3536,10 @@ pub struct VMOutcome {
     /// Data collected from making a contract call
     pub profile: ProfileDataV3,
     pub action_receipts: Vec<(AccountId, ReceiptMetadata)>,
+    /// Data ids the receipts created by `promise_yield_create` are waiting for
+    pub promise_yields: Vec<CryptoHash>,
+    /// Data ids of yielded promises resumed by `promise_yield_resume` with their payloads
+    pub promise_resumes: Vec<(CryptoHash, Vec<u8>)>,
     pub aborted: Option<FunctionCallError>,
 }
 
@@ -2895,6 +3572,8 @@ impl VMOutcome {
             logs: Vec::new(),
             profile: ProfileDataV3::default(),
             action_receipts: Vec::new(),
+            promise_yields: Vec::new(),
+            promise_resumes: Vec::new(),
             aborted: Some(error),
         }
     }
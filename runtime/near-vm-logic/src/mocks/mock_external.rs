@@ -1,14 +1,22 @@
 use crate::{External, StorageGetMode, ValuePtr};
 use near_primitives::hash::{hash, CryptoHash};
+use near_primitives::profile::ExtendedProfileData;
 use near_primitives::types::TrieNodesCount;
+use near_primitives::views::HostFunctionCallView;
 use near_primitives_core::types::{AccountId, Balance};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Default, Clone)]
 /// Emulates the trie and the mock handling code.
 pub struct MockedExternal {
     pub fake_trie: HashMap<Vec<u8>, Vec<u8>>,
     pub validators: HashMap<AccountId, Balance>,
+    /// Host function calls recorded by `VMLogic`; tracing is enabled when this is `Some`.
+    pub execution_trace: Option<Vec<HostFunctionCallView>>,
+    /// Gas profile accumulated by `VMLogic`; profiling is enabled when this is `Some`.
+    pub extended_profile: Option<ExtendedProfileData>,
+    /// Data ids of yielded promises that can be resumed.
+    pub promise_yields: HashSet<CryptoHash>,
     data_count: u64,
 }
 
@@ -78,6 +86,10 @@ impl External for MockedExternal {
         data_id
     }
 
+    fn promise_yield_resume(&mut self, data_id: &CryptoHash) -> Result<bool> {
+        Ok(self.promise_yields.remove(data_id))
+    }
+
     fn get_trie_nodes_count(&self) -> TrieNodesCount {
         TrieNodesCount { db_reads: 0, mem_reads: 0 }
     }
@@ -89,4 +101,12 @@ impl External for MockedExternal {
     fn validator_total_stake(&self) -> Result<Balance> {
         Ok(self.validators.values().sum())
     }
+
+    fn execution_trace(&mut self) -> Option<&mut Vec<HostFunctionCallView>> {
+        self.execution_trace.as_mut()
+    }
+
+    fn extended_profile(&mut self) -> Option<&mut ExtendedProfileData> {
+        self.extended_profile.as_mut()
+    }
 }
@@ -33,6 +33,10 @@ pub struct ReceiptMetadata {
 #[derive(Default, Clone, PartialEq)]
 pub(crate) struct ReceiptManager {
     pub(crate) action_receipts: ActionReceipts,
+    /// Data ids the receipts created by `promise_yield_create` are waiting for.
+    pub(crate) promise_yields: Vec<CryptoHash>,
+    /// Data ids of yielded promises resumed during this execution with their payloads.
+    pub(crate) promise_resumes: Vec<(CryptoHash, Vec<u8>)>,
     gas_weights: Vec<(FunctionCallActionIndex, GasWeight)>,
 }
 
@@ -122,6 +126,40 @@ impl ReceiptManager {
         Ok(new_receipt_index)
     }
 
+    /// Create a receipt to the current account which will be executed once the data with the
+    /// returned data id is delivered, either by resuming the promise or when it times out.
+    ///
+    /// # Arguments
+    ///
+    /// * `receiver_id` - account id of the current account
+    pub(crate) fn create_promise_yield_receipt(
+        &mut self,
+        ext: &mut dyn External,
+        receiver_id: AccountId,
+    ) -> (ReceiptIndex, CryptoHash) {
+        let data_id = ext.generate_data_id();
+        let new_receipt = ReceiptMetadata {
+            output_data_receivers: vec![],
+            input_data_ids: vec![data_id],
+            actions: vec![],
+        };
+        let new_receipt_index = self.action_receipts.len() as ReceiptIndex;
+        self.action_receipts.push((receiver_id, new_receipt));
+        self.promise_yields.push(data_id);
+        (new_receipt_index, data_id)
+    }
+
+    /// Record the payload a yielded promise is resumed with. The runtime delivers it to the
+    /// yielded receipt as a data receipt.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_id` - data id of the yielded promise, as returned by `promise_yield_create`
+    /// * `data` - the payload to resume the promise with
+    pub(crate) fn resume_promise_yield(&mut self, data_id: CryptoHash, data: Vec<u8>) {
+        self.promise_resumes.push((data_id, data));
+    }
+
     /// Attach the [`CreateAccountAction`] action to an existing receipt.
     ///
     /// # Arguments
@@ -0,0 +1,243 @@
+use super::vm_logic_builder::VMLogicBuilder;
+use blst::{
+    blst_p1, blst_p1_affine, blst_p1_affine_serialize, blst_p1_cneg, blst_p1_double,
+    blst_p1_from_affine, blst_p1_serialize, blst_p2, blst_p2_affine, blst_p2_affine_serialize,
+    blst_p2_cneg, blst_p2_double, blst_p2_from_affine, blst_p2_serialize, BLS12_381_G1,
+    BLS12_381_G2,
+};
+use near_vm_errors::{HostError, VMLogicError};
+
+/// Uncompressed encoding of the point at infinity of both G1 and G2.
+fn infinity(size: usize) -> Vec<u8> {
+    let mut res = vec![0u8; size];
+    res[0] = 0x40;
+    res
+}
+
+fn g1_generator() -> Vec<u8> {
+    let mut res = vec![0u8; 96];
+    // SAFETY: `res` has the size of a serialized uncompressed G1 point.
+    unsafe { blst_p1_affine_serialize(res.as_mut_ptr(), &BLS12_381_G1) };
+    res
+}
+
+fn g2_generator() -> Vec<u8> {
+    let mut res = vec![0u8; 192];
+    // SAFETY: `res` has the size of a serialized uncompressed G2 point.
+    unsafe { blst_p2_affine_serialize(res.as_mut_ptr(), &BLS12_381_G2) };
+    res
+}
+
+/// Returns `(-G1, 2 * G1)`.
+fn g1_neg_and_double() -> (Vec<u8>, Vec<u8>) {
+    let (mut neg, mut double) = (vec![0u8; 96], vec![0u8; 96]);
+    let (mut g, mut p) = (blst_p1::default(), blst_p1::default());
+    // SAFETY: all pointers are derived from references to initialized values
+    // and the buffers have the size of serialized uncompressed G1 points.
+    unsafe {
+        blst_p1_from_affine(&mut g, &BLS12_381_G1 as *const blst_p1_affine);
+        blst_p1_double(&mut p, &g);
+        blst_p1_serialize(double.as_mut_ptr(), &p);
+        blst_p1_cneg(&mut g, true);
+        blst_p1_serialize(neg.as_mut_ptr(), &g);
+    }
+    (neg, double)
+}
+
+/// Returns `(-G2, 2 * G2)`.
+fn g2_neg_and_double() -> (Vec<u8>, Vec<u8>) {
+    let (mut neg, mut double) = (vec![0u8; 192], vec![0u8; 192]);
+    let (mut g, mut p) = (blst_p2::default(), blst_p2::default());
+    // SAFETY: all pointers are derived from references to initialized values
+    // and the buffers have the size of serialized uncompressed G2 points.
+    unsafe {
+        blst_p2_from_affine(&mut g, &BLS12_381_G2 as *const blst_p2_affine);
+        blst_p2_double(&mut p, &g);
+        blst_p2_serialize(double.as_mut_ptr(), &p);
+        blst_p2_cneg(&mut g, true);
+        blst_p2_serialize(neg.as_mut_ptr(), &g);
+    }
+    (neg, double)
+}
+
+fn scalar(value: u8) -> Vec<u8> {
+    let mut res = vec![0u8; 32];
+    res[0] = value;
+    res
+}
+
+#[track_caller]
+fn check_result<T>(actual: Result<T, VMLogicError>, expected: Result<(), &str>) -> Option<T> {
+    match (actual, expected) {
+        (Ok(actual), Ok(())) => Some(actual),
+        (Err(VMLogicError::HostError(HostError::Bls12381InvalidInput { msg: err })), Err(msg)) => {
+            assert!(err.contains(msg), "expected `{msg}` error, got {err}");
+            None
+        }
+        (Ok(_), Err(msg)) => panic!("expected `{msg}` error"),
+        (Err(err), _) => panic!("unexpected error: `{err}`"),
+    }
+}
+
+type RegisterFn = fn(&mut crate::VMLogic<'_>, u64, u64, u64) -> crate::logic::Result<()>;
+
+#[track_caller]
+fn check(f: RegisterFn, input: &[u8], expected: Result<&[u8], &str>) {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build();
+    let input = logic.internal_mem_write(input);
+
+    let res = f(&mut logic, input.len, input.ptr, 0);
+    if let Some(()) = check_result(res, expected.map(|_| ())) {
+        let got = logic.registers().get_for_free(0).unwrap();
+        assert_eq!(expected.unwrap(), got);
+    }
+}
+
+#[test]
+fn test_bls12381_p1_sum() {
+    let f: RegisterFn = |logic, len, ptr, register| logic.bls12381_p1_sum(len, ptr, register);
+    let g = g1_generator();
+    let (neg, double) = g1_neg_and_double();
+
+    check(f, &[], Ok(&infinity(96)));
+    check(f, &[&[0u8][..], &g].concat(), Ok(&g));
+    check(f, &[&[1u8][..], &g].concat(), Ok(&neg));
+    check(f, &[&[0u8][..], &g, &[0u8], &g].concat(), Ok(&double));
+    check(f, &[&[0u8][..], &g, &[1u8], &g].concat(), Ok(&infinity(96)));
+    check(f, &[&[0u8][..], &double, &[0u8], &neg].concat(), Ok(&g));
+
+    check(f, b"XXXX", Err("slice of size 4 cannot be precisely split into chunks of size 97"));
+    check(f, &[&[2u8][..], &g].concat(), Err("invalid bool"));
+    let mut not_on_curve = g.clone();
+    not_on_curve[95] ^= 1;
+    check(f, &[&[0u8][..], &not_on_curve].concat(), Err("invalid g1"));
+    let mut compressed = g;
+    compressed[0] |= 0x80;
+    check(f, &[&[0u8][..], &compressed].concat(), Err("compressed g1 points are not supported"));
+}
+
+#[test]
+fn test_bls12381_p2_sum() {
+    let f: RegisterFn = |logic, len, ptr, register| logic.bls12381_p2_sum(len, ptr, register);
+    let g = g2_generator();
+    let (neg, double) = g2_neg_and_double();
+
+    check(f, &[], Ok(&infinity(192)));
+    check(f, &[&[0u8][..], &g].concat(), Ok(&g));
+    check(f, &[&[1u8][..], &g].concat(), Ok(&neg));
+    check(f, &[&[0u8][..], &g, &[0u8], &g].concat(), Ok(&double));
+    check(f, &[&[0u8][..], &g, &[1u8], &g].concat(), Ok(&infinity(192)));
+
+    check(f, b"XXXX", Err("slice of size 4 cannot be precisely split into chunks of size 193"));
+    check(f, &[&[2u8][..], &g].concat(), Err("invalid bool"));
+    let mut not_on_curve = g;
+    not_on_curve[191] ^= 1;
+    check(f, &[&[0u8][..], &not_on_curve].concat(), Err("invalid g2"));
+}
+
+#[test]
+fn test_bls12381_g1_multiexp() {
+    let f: RegisterFn = |logic, len, ptr, register| logic.bls12381_g1_multiexp(len, ptr, register);
+    let g = g1_generator();
+    let (neg, double) = g1_neg_and_double();
+
+    check(f, &[], Ok(&infinity(96)));
+    check(f, &[&g[..], &scalar(1)].concat(), Ok(&g));
+    check(f, &[&g[..], &scalar(2)].concat(), Ok(&double));
+    check(f, &[&g[..], &scalar(0)].concat(), Ok(&infinity(96)));
+    check(f, &[&g[..], &scalar(3), &neg, &scalar(1)].concat(), Ok(&double));
+    check(f, &[&infinity(96)[..], &scalar(5)].concat(), Ok(&infinity(96)));
+
+    check(f, b"XXXX", Err("slice of size 4 cannot be precisely split into chunks of size 128"));
+    let mut not_on_curve = g;
+    not_on_curve[95] ^= 1;
+    check(f, &[&not_on_curve[..], &scalar(1)].concat(), Err("invalid g1"));
+}
+
+#[test]
+fn test_bls12381_g2_multiexp() {
+    let f: RegisterFn = |logic, len, ptr, register| logic.bls12381_g2_multiexp(len, ptr, register);
+    let g = g2_generator();
+    let (neg, double) = g2_neg_and_double();
+
+    check(f, &[], Ok(&infinity(192)));
+    check(f, &[&g[..], &scalar(2)].concat(), Ok(&double));
+    check(f, &[&g[..], &scalar(3), &neg, &scalar(1)].concat(), Ok(&double));
+
+    check(f, b"XXXX", Err("slice of size 4 cannot be precisely split into chunks of size 224"));
+}
+
+#[test]
+fn test_bls12381_map_fp_to_g1() {
+    let f: RegisterFn = |logic, len, ptr, register| logic.bls12381_map_fp_to_g1(len, ptr, register);
+
+    check(f, &[], Ok(&[]));
+
+    // The mapped points must be valid inputs of the functions checking that
+    // points belong to G1.
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build();
+    let input = logic.internal_mem_write(&[[0u8; 48], [7u8; 48]].concat());
+    logic.bls12381_map_fp_to_g1(input.len, input.ptr, 0).unwrap();
+    let points = logic.registers().get_for_free(0).unwrap().to_vec();
+    assert_eq!(points.len(), 2 * 96);
+    let input =
+        logic.internal_mem_write(&[&points[..96], &scalar(1), &points[96..], &scalar(1)].concat());
+    logic.bls12381_g1_multiexp(input.len, input.ptr, 1).unwrap();
+
+    check(f, b"XXXX", Err("slice of size 4 cannot be precisely split into chunks of size 48"));
+    check(f, &[0xffu8; 48], Err("invalid fp"));
+}
+
+#[test]
+fn test_bls12381_map_fp2_to_g2() {
+    let f: RegisterFn =
+        |logic, len, ptr, register| logic.bls12381_map_fp2_to_g2(len, ptr, register);
+
+    check(f, &[], Ok(&[]));
+
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build();
+    let input = logic.internal_mem_write(&[3u8; 96]);
+    logic.bls12381_map_fp2_to_g2(input.len, input.ptr, 0).unwrap();
+    let point = logic.registers().get_for_free(0).unwrap().to_vec();
+    assert_eq!(point.len(), 192);
+    let input = logic.internal_mem_write(&[&point[..], &scalar(1)].concat());
+    logic.bls12381_g2_multiexp(input.len, input.ptr, 1).unwrap();
+
+    check(f, b"XXXX", Err("slice of size 4 cannot be precisely split into chunks of size 96"));
+    check(f, &[0xffu8; 96], Err("invalid fp"));
+}
+
+#[test]
+fn test_bls12381_pairing_check() {
+    #[track_caller]
+    fn check(input: &[u8], expected: Result<bool, &str>) {
+        let mut logic_builder = VMLogicBuilder::default();
+        let mut logic = logic_builder.build();
+        let input = logic.internal_mem_write(input);
+
+        let res = logic.bls12381_pairing_check(input.len, input.ptr);
+        if let Some(res) = check_result(res, expected.map(|_| ())) {
+            assert_eq!(res, expected.unwrap() as u64);
+        }
+    }
+    let (g1, g2) = (g1_generator(), g2_generator());
+    let (g1_neg, g1_double) = g1_neg_and_double();
+    let (g2_neg, g2_double) = g2_neg_and_double();
+
+    check(&[], Ok(true));
+    check(&[&g1[..], &g2].concat(), Ok(false));
+    check(&[&g1[..], &g2, &g1_neg, &g2].concat(), Ok(true));
+    check(&[&g1[..], &g2, &g1, &g2_neg].concat(), Ok(true));
+    check(&[&g1_double[..], &g2, &g1_neg, &g2_double].concat(), Ok(true));
+    check(&[&g1_double[..], &g2, &g1_neg, &g2].concat(), Ok(false));
+    check(&[&g1_double[..], &g2, &g1, &g2_neg, &g1, &g2_neg].concat(), Ok(true));
+    check(&[&infinity(96)[..], &g2].concat(), Ok(true));
+
+    check(b"XXXX", Err("slice of size 4 cannot be precisely split into chunks of size 288"));
+    let mut not_on_curve = g2;
+    not_on_curve[191] ^= 1;
+    check(&[&g1[..], &not_on_curve].concat(), Err("invalid g2"));
+}
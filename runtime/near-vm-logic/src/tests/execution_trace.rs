@@ -0,0 +1,140 @@
+use crate::tests::vm_logic_builder::VMLogicBuilder;
+use near_primitives::profile::MEMORY_GROW_FUNCTION_INDEX;
+use near_primitives::views::{CreatedPromiseView, StorageAccessView};
+
+#[test]
+fn test_trace_disabled_by_default() {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build();
+    assert!(!logic.is_tracing());
+    let key = logic.internal_mem_write(b"foo");
+    let val = logic.internal_mem_write(b"bar");
+    logic.storage_write(key.len, key.ptr, val.len, val.ptr, 0).expect("storage write ok");
+    drop(logic);
+    assert!(logic_builder.ext.execution_trace.is_none());
+}
+
+#[test]
+fn test_trace_storage_access() {
+    let mut logic_builder = VMLogicBuilder::default();
+    logic_builder.ext.execution_trace = Some(vec![]);
+    let mut logic = logic_builder.build();
+    assert!(logic.is_tracing());
+    let key = logic.internal_mem_write(b"foo");
+    let val = logic.internal_mem_write(b"bar");
+
+    let args = [("key_len", key.len), ("key_ptr", key.ptr)];
+    logic
+        .trace_host_call("storage_write", &args, |logic| {
+            logic.storage_write(key.len, key.ptr, val.len, val.ptr, 0)
+        })
+        .expect("storage write ok");
+    logic
+        .trace_host_call("storage_read", &args, |logic| logic.storage_read(key.len, key.ptr, 0))
+        .expect("storage read ok");
+    logic
+        .trace_host_call("storage_remove", &args, |logic| logic.storage_remove(key.len, key.ptr, 0))
+        .expect("storage remove ok");
+    logic
+        .trace_host_call("storage_has_key", &args, |logic| logic.storage_has_key(key.len, key.ptr))
+        .expect("storage has key ok");
+    drop(logic);
+
+    let calls = logic_builder.ext.execution_trace.unwrap();
+    let names: Vec<_> = calls.iter().map(|call| call.name.as_str()).collect();
+    assert_eq!(names, ["storage_write", "storage_read", "storage_remove", "storage_has_key"]);
+    assert_eq!(calls[0].args, [("key_len".to_string(), 3), ("key_ptr".to_string(), 0)]);
+    assert!(calls.iter().all(|call| call.gas_burnt > 0 && call.error.is_none()));
+    assert_eq!(
+        calls[0].storage,
+        [StorageAccessView::Write { key: b"foo".to_vec(), value: b"bar".to_vec(), evicted: None }]
+    );
+    assert_eq!(calls[0].result, Some(0));
+    assert_eq!(
+        calls[1].storage,
+        [StorageAccessView::Read { key: b"foo".to_vec(), value: Some(b"bar".to_vec()) }]
+    );
+    assert_eq!(
+        calls[2].storage,
+        [StorageAccessView::Remove { key: b"foo".to_vec(), removed: Some(b"bar".to_vec()) }]
+    );
+    assert_eq!(
+        calls[3].storage,
+        [StorageAccessView::HasKey { key: b"foo".to_vec(), exists: false }]
+    );
+    assert_eq!(calls[3].result, Some(0));
+}
+
+#[test]
+fn test_trace_promises_and_errors() {
+    let mut logic_builder = VMLogicBuilder::default();
+    logic_builder.ext.execution_trace = Some(vec![]);
+    let mut logic = logic_builder.build();
+    let account_id = logic.internal_mem_write(b"rick.test");
+
+    let args = [("account_id_len", account_id.len), ("account_id_ptr", account_id.ptr)];
+    logic
+        .trace_host_call("promise_batch_create", &args, |logic| {
+            logic.promise_batch_create(account_id.len, account_id.ptr)
+        })
+        .expect("promise batch create ok");
+    logic
+        .trace_host_call("promise_batch_then", &[("promise_idx", 42)], |logic| {
+            logic.promise_batch_then(42, account_id.len, account_id.ptr)
+        })
+        .expect_err("invalid promise index");
+    drop(logic);
+
+    let calls = logic_builder.ext.execution_trace.unwrap();
+    assert_eq!(calls.len(), 2);
+    assert_eq!(
+        calls[0].promises,
+        [CreatedPromiseView { promise_index: 0, receiver_id: "rick.test".parse().unwrap() }]
+    );
+    assert_eq!(calls[0].result, Some(0));
+    assert!(calls[1].promises.is_empty());
+    assert!(calls[1].error.as_ref().unwrap().contains("InvalidPromiseIndex"));
+}
+
+#[test]
+fn test_extended_profile() {
+    let mut logic_builder = VMLogicBuilder::default();
+    logic_builder.ext.extended_profile = Some(Default::default());
+    let mut logic = logic_builder.build();
+    assert!(logic.is_profiling());
+    assert!(!logic.is_tracing());
+    let key = logic.internal_mem_write(b"foo");
+    let val = logic.internal_mem_write(b"bar");
+
+    let args = [("key_len", key.len), ("key_ptr", key.ptr)];
+    for _ in 0..2 {
+        logic
+            .trace_host_call("storage_write", &args, |logic| {
+                logic.storage_write(key.len, key.ptr, val.len, val.ptr, 0)
+            })
+            .expect("storage write ok");
+    }
+    logic
+        .trace_host_call("storage_read", &args, |logic| logic.storage_read(key.len, key.ptr, 0))
+        .expect("storage read ok");
+    logic.profiled_gas(3, 10).expect("gas ok");
+    logic.profiled_gas(3, 5).expect("gas ok");
+    logic.profiled_gas(MEMORY_GROW_FUNCTION_INDEX, 1).expect("gas ok");
+    let regular_op_cost = logic.config().regular_op_cost;
+    drop(logic);
+
+    assert!(logic_builder.ext.execution_trace.is_none());
+    let profile = logic_builder.ext.extended_profile.unwrap();
+    let host: Vec<_> = profile
+        .host_functions()
+        .map(|(name, stats)| (name, stats.calls, stats.bytes_read, stats.bytes_written))
+        .collect();
+    // The second write evicts the first value into the register.
+    assert_eq!(host, [("storage_read", 1, 3, 3), ("storage_write", 2, 12, 3)]);
+    assert!(profile.host_functions().all(|(_, stats)| stats.gas_burnt > 0));
+    assert_eq!(
+        profile.wasm_functions().collect::<Vec<_>>(),
+        [(3, 15 * u64::from(regular_op_cost))]
+    );
+    assert_eq!(profile.memory_grow_gas(), u64::from(regular_op_cost));
+}
@@ -1,11 +1,15 @@
 mod alt_bn128;
+mod bls12381;
 mod context;
 mod ed25519_verify;
+mod execution_trace;
 mod gas_counter;
 pub(crate) mod helpers;
 mod iterators;
 mod logs;
 mod miscs;
+mod p256_verify;
+mod promise_yield;
 mod promises;
 mod registers;
 mod storage_read_write;
@@ -0,0 +1,146 @@
+use crate::tests::helpers::*;
+use crate::tests::vm_logic_builder::VMLogicBuilder;
+use crate::{map, ExtCosts};
+use near_vm_errors::HostError;
+use near_vm_errors::VMLogicError;
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{Signature, SigningKey};
+use std::collections::HashMap;
+
+const MESSAGE: &[u8] = b"P-256 signed message";
+
+/// Returns a signature of `MESSAGE` and the public key which verifies it in
+/// uncompressed and compressed form.
+fn sign_message() -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+    let signing_key = SigningKey::from_bytes(&[7u8; 32]).unwrap();
+    let signature: Signature = signing_key.sign(MESSAGE);
+    let public_key = signing_key.verifying_key();
+    (
+        signature.as_ref().to_vec(),
+        public_key.to_encoded_point(false).as_bytes().to_vec(),
+        public_key.to_encoded_point(true).as_bytes().to_vec(),
+    )
+}
+
+#[track_caller]
+fn check_p256_verify(
+    signature: &[u8],
+    message: &[u8],
+    public_key: &[u8],
+    want: Result<u64, HostError>,
+    want_costs: HashMap<ExtCosts, u64>,
+) {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build();
+
+    let signature = logic.internal_mem_write(signature);
+    let message = logic.internal_mem_write(message);
+    let public_key = logic.internal_mem_write(public_key);
+
+    let result = logic.p256_verify(
+        signature.len,
+        signature.ptr,
+        message.len,
+        message.ptr,
+        public_key.len,
+        public_key.ptr,
+    );
+
+    let want = want.map_err(VMLogicError::HostError);
+    assert_eq!(want, result);
+    assert_costs(want_costs);
+}
+
+#[test]
+fn test_p256_verify_behavior_and_errors() {
+    let (signature, public_key, compressed_public_key) = sign_message();
+
+    check_p256_verify(
+        &signature,
+        MESSAGE,
+        &public_key,
+        Ok(1),
+        map! {
+            ExtCosts::read_memory_byte: 64 + 20 + 65,
+            ExtCosts::read_memory_base: 3,
+            ExtCosts::p256_verify_base: 1,
+            ExtCosts::p256_verify_byte: 20,
+        },
+    );
+    check_p256_verify(
+        &signature,
+        MESSAGE,
+        &compressed_public_key,
+        Ok(1),
+        map! {
+            ExtCosts::read_memory_byte: 64 + 20 + 33,
+            ExtCosts::read_memory_base: 3,
+            ExtCosts::p256_verify_base: 1,
+            ExtCosts::p256_verify_byte: 20,
+        },
+    );
+    check_p256_verify(
+        &signature,
+        b"P-256 signed massage",
+        &public_key,
+        Ok(0),
+        map! {
+            ExtCosts::read_memory_byte: 64 + 20 + 65,
+            ExtCosts::read_memory_base: 3,
+            ExtCosts::p256_verify_base: 1,
+            ExtCosts::p256_verify_byte: 20,
+        },
+    );
+
+    // Zero `r` and `s` are rejected when parsing the signature.
+    check_p256_verify(
+        &[0; 64],
+        MESSAGE,
+        &public_key,
+        Ok(0),
+        map! {
+            ExtCosts::read_memory_byte: 64,
+            ExtCosts::read_memory_base: 1,
+            ExtCosts::p256_verify_base: 1,
+        },
+    );
+    check_p256_verify(
+        &signature[1..],
+        MESSAGE,
+        &public_key,
+        Err(HostError::P256VerifyInvalidInput { msg: "invalid signature length".to_string() }),
+        map! {
+            ExtCosts::read_memory_byte: 63,
+            ExtCosts::read_memory_base: 1,
+            ExtCosts::p256_verify_base: 1,
+        },
+    );
+
+    let mut not_on_curve = public_key.clone();
+    not_on_curve[64] ^= 1;
+    check_p256_verify(
+        &signature,
+        MESSAGE,
+        &not_on_curve,
+        Ok(0),
+        map! {
+            ExtCosts::read_memory_byte: 64 + 20 + 65,
+            ExtCosts::read_memory_base: 3,
+            ExtCosts::p256_verify_base: 1,
+            ExtCosts::p256_verify_byte: 20,
+        },
+    );
+    check_p256_verify(
+        &signature,
+        MESSAGE,
+        &public_key[1..],
+        Err(HostError::P256VerifyInvalidInput { msg: "invalid public key length".to_string() }),
+        map! {
+            ExtCosts::read_memory_byte: 64 + 20 + 64,
+            ExtCosts::read_memory_base: 3,
+            ExtCosts::p256_verify_base: 1,
+            ExtCosts::p256_verify_byte: 20,
+        },
+    );
+}
+
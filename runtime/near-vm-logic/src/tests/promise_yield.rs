@@ -0,0 +1,135 @@
+use crate::tests::vm_logic_builder::VMLogicBuilder;
+use near_primitives::config::ActionCosts;
+use near_primitives::hash::CryptoHash;
+use near_primitives::transaction::{Action, FunctionCallAction};
+use near_vm_errors::{HostError, VMLogicError};
+
+#[test]
+fn test_promise_yield_create() {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build();
+
+    let method_name = logic.internal_mem_write(b"on_response");
+    let args = logic.internal_mem_write(b"{}");
+    let index = logic
+        .promise_yield_create(method_name.len, method_name.ptr, args.len, args.ptr, 1000, 0, 0)
+        .expect("should create a yielded promise");
+    assert_eq!(index, 0);
+
+    let receipt_manager = logic.receipt_manager();
+    assert_eq!(receipt_manager.promise_yields.len(), 1);
+    let data_id = receipt_manager.promise_yields[0];
+    let (receiver_id, receipt) = &receipt_manager.action_receipts[0];
+    assert_eq!(receiver_id.as_ref(), "alice.near");
+    assert_eq!(receipt.input_data_ids, vec![data_id]);
+    assert_eq!(
+        receipt.actions,
+        vec![Action::FunctionCall(FunctionCallAction {
+            method_name: "on_response".to_string(),
+            args: b"{}".to_vec(),
+            gas: 1000,
+            deposit: 0,
+        })]
+    );
+    logic.assert_read_register(data_id.as_ref(), 0);
+
+    let empty = logic.internal_mem_write(b"");
+    assert_eq!(
+        logic.promise_yield_create(empty.len, empty.ptr, args.len, args.ptr, 1000, 0, 0),
+        Err(VMLogicError::HostError(HostError::EmptyMethodName))
+    );
+}
+
+#[test]
+fn test_promise_yield_create_gas_weight() {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build();
+
+    let method_name = logic.internal_mem_write(b"on_response");
+    let args = logic.internal_mem_write(b"");
+    logic
+        .promise_yield_create(method_name.len, method_name.ptr, args.len, args.ptr, 1000, 1, 0)
+        .expect("should create a yielded promise");
+
+    let outcome = logic.compute_outcome_and_distribute_gas();
+    assert_eq!(outcome.used_gas, 10u64.pow(14));
+    match &outcome.action_receipts[0].1.actions[0] {
+        Action::FunctionCall(FunctionCallAction { gas, .. }) => assert!(*gas > 1000),
+        action => panic!("unexpected action {action:?}"),
+    }
+}
+
+#[test]
+fn test_promise_yield_resume() {
+    let data_id = CryptoHash::hash_bytes(b"yield");
+    let mut logic_builder = VMLogicBuilder::default();
+    logic_builder.ext.promise_yields.insert(data_id);
+    let mut logic = logic_builder.build();
+
+    let data_id_slice = logic.internal_mem_write(data_id.as_ref());
+    let payload = logic.internal_mem_write(b"response");
+    assert_eq!(
+        logic.promise_yield_resume(data_id_slice.len, data_id_slice.ptr, payload.len, payload.ptr),
+        Ok(1)
+    );
+    // A promise can be resumed only once.
+    assert_eq!(
+        logic.promise_yield_resume(data_id_slice.len, data_id_slice.ptr, payload.len, payload.ptr),
+        Ok(0)
+    );
+    let unknown = logic.internal_mem_write(CryptoHash::hash_bytes(b"unknown").as_ref());
+    assert_eq!(
+        logic.promise_yield_resume(unknown.len, unknown.ptr, payload.len, payload.ptr),
+        Ok(0)
+    );
+
+    let outcome = logic.compute_outcome_and_distribute_gas();
+    assert_eq!(outcome.promise_resumes, vec![(data_id, b"response".to_vec())]);
+}
+
+#[test]
+fn test_promise_yield_resume_data_receipt_fee() {
+    let data_id = CryptoHash::hash_bytes(b"yield");
+    let mut logic_builder = VMLogicBuilder::default();
+    logic_builder.ext.promise_yields.insert(data_id);
+    let fee = logic_builder.fees_config.fee(ActionCosts::new_data_receipt_byte).clone();
+    let mut logic = logic_builder.build();
+
+    let data_id = logic.internal_mem_write(data_id.as_ref());
+    let payload = logic.internal_mem_write(b"response");
+    assert_eq!(
+        logic.promise_yield_resume(data_id.len, data_id.ptr, payload.len, payload.ptr),
+        Ok(1)
+    );
+
+    // The payload is paid for as the data receipt delivering it.
+    let outcome = logic.compute_outcome_and_distribute_gas();
+    assert_eq!(
+        outcome.profile.get_action_cost(ActionCosts::new_data_receipt_byte),
+        payload.len * fee.send_fee(true)
+    );
+}
+
+#[test]
+fn test_promise_yield_resume_invalid_input() {
+    let mut logic_builder = VMLogicBuilder::default();
+    let limit = logic_builder.config.limit_config.max_yield_payload_size;
+    let mut logic = logic_builder.build();
+
+    let data_id = logic.internal_mem_write(&[0; 31]);
+    let payload = logic.internal_mem_write(b"response");
+    assert_eq!(
+        logic.promise_yield_resume(data_id.len, data_id.ptr, payload.len, payload.ptr),
+        Err(VMLogicError::HostError(HostError::DataIdMalformed))
+    );
+
+    let data_id = logic.internal_mem_write(&[0; 32]);
+    let payload = logic.internal_mem_write(&vec![0; limit as usize + 1]);
+    assert_eq!(
+        logic.promise_yield_resume(data_id.len, data_id.ptr, payload.len, payload.ptr),
+        Err(VMLogicError::HostError(HostError::YieldPayloadLengthExceeded {
+            length: limit + 1,
+            limit
+        }))
+    );
+}
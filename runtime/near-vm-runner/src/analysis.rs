@@ -0,0 +1,260 @@
+//! Static analysis of contracts, used to vet a contract before it's deployed.
+//!
+//! Deployment only tells whether [`crate::prepare::prepare_contract`] accepts
+//! the contract.  The analysis reports the properties of the module which are
+//! checked or affected by the preparation so that it's possible to tell how
+//! close a contract is to the limits and why it is rejected.
+
+use crate::instrument::stack_height;
+use near_vm_errors::PrepareError;
+use near_vm_logic::{VMConfig, WasmFeaturesVersion};
+use parity_wasm::elements::{self, External, Internal};
+
+/// Properties of a contract which don't depend on the protocol version.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ContractAnalysis {
+    /// Size of the contract code in bytes.
+    pub code_size: usize,
+    /// Names of functions imported from the `env` module, in order of import.
+    pub host_functions: Vec<String>,
+    /// All other imports as `module.field`, including memory and table
+    /// imports.  Current protocol versions reject any of them.
+    pub other_imports: Vec<String>,
+    /// Names of exported functions, which can be called as contract methods.
+    pub exported_methods: Vec<String>,
+    /// Memories declared or imported by the contract.  Preparation replaces
+    /// them with a memory import with limits taken from [`VMConfig`].
+    pub memories: Vec<MemoryLimits>,
+    /// Tables declared or imported by the contract.
+    pub tables: Vec<TableLimits>,
+    /// Number of functions including imports, `None` if the module is invalid
+    /// or the counter overflows.
+    pub function_count: Option<u64>,
+    /// Number of locals declared by all functions, `None` if the module is
+    /// invalid or the counter overflows.
+    pub local_count: Option<u64>,
+    /// The largest stack cost of a single function, which is what the stack
+    /// height instrumentation charges against the stack limit on each call.
+    pub max_function_stack_cost: Option<u32>,
+    /// Number of bytes the stack height instrumentation adds to the module.
+    pub stack_instrumentation_overhead: Option<usize>,
+    /// Why the module failed to deserialize or validate, if it did.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryLimits {
+    pub imported: bool,
+    /// Initial number of 64KiB pages.
+    pub initial: u32,
+    /// Maximum number of 64KiB pages.
+    pub maximum: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableLimits {
+    pub imported: bool,
+    /// Initial number of elements.
+    pub initial: u32,
+    /// Maximum number of elements.
+    pub maximum: Option<u32>,
+}
+
+/// Analyzes the contract code.
+///
+/// Never fails, whatever can’t be determined because the module is malformed
+/// is left empty and the reason is recorded in [`ContractAnalysis::error`].
+pub fn analyze_contract(code: &[u8]) -> ContractAnalysis {
+    let mut analysis = ContractAnalysis { code_size: code.len(), ..Default::default() };
+    // Validate with all the features any protocol version supports, whether
+    // a particular version accepts the contract is for `check_preparation`.
+    let features = crate::prepare::wasm_features(WasmFeaturesVersion::V1);
+    match crate::prepare::wasmparser_decode(code, features) {
+        Ok((function_count, local_count)) => {
+            analysis.function_count = function_count;
+            analysis.local_count = local_count;
+        }
+        Err(err) => analysis.error = Some(format!("invalid module: {err}")),
+    }
+    let module = match elements::deserialize_buffer::<elements::Module>(code) {
+        Ok(module) => module,
+        Err(err) => {
+            analysis.error.get_or_insert_with(|| format!("failed to deserialize module: {err}"));
+            return analysis;
+        }
+    };
+
+    for import in module.import_section().map(|section| section.entries()).unwrap_or(&[]) {
+        match import.external() {
+            External::Function(_) if import.module() == "env" => {
+                analysis.host_functions.push(import.field().to_string());
+                continue;
+            }
+            External::Memory(memory) => analysis.memories.push(MemoryLimits {
+                imported: true,
+                initial: memory.limits().initial(),
+                maximum: memory.limits().maximum(),
+            }),
+            External::Table(table) => analysis.tables.push(TableLimits {
+                imported: true,
+                initial: table.limits().initial(),
+                maximum: table.limits().maximum(),
+            }),
+            External::Function(_) | External::Global(_) => {}
+        }
+        analysis.other_imports.push(format!("{}.{}", import.module(), import.field()));
+    }
+    for memory in module.memory_section().map(|section| section.entries()).unwrap_or(&[]) {
+        analysis.memories.push(MemoryLimits {
+            imported: false,
+            initial: memory.limits().initial(),
+            maximum: memory.limits().maximum(),
+        });
+    }
+    for table in module.table_section().map(|section| section.entries()).unwrap_or(&[]) {
+        analysis.tables.push(TableLimits {
+            imported: false,
+            initial: table.limits().initial(),
+            maximum: table.limits().maximum(),
+        });
+    }
+    for export in module.export_section().map(|section| section.entries()).unwrap_or(&[]) {
+        if let Internal::Function(_) = export.internal() {
+            analysis.exported_methods.push(export.field().to_string());
+        }
+    }
+
+    // Stack analysis assumes a valid module.
+    if analysis.error.is_some() {
+        return analysis;
+    }
+    analysis.max_function_stack_cost = stack_height::compute_stack_costs(&module)
+        .ok()
+        .map(|costs| costs.into_iter().max().unwrap_or(0));
+    // The overhead doesn’t depend on the limit, it’s only embedded as
+    // a constant into the instrumentation.
+    let original_size = elements::serialize(module.clone()).map(|code| code.len());
+    let instrumented_size = stack_height::inject_limiter(module, u32::MAX)
+        .ok()
+        .and_then(|module| elements::serialize(module).ok())
+        .map(|code| code.len());
+    if let (Ok(original_size), Some(instrumented_size)) = (original_size, instrumented_size) {
+        analysis.stack_instrumentation_overhead =
+            Some(instrumented_size.saturating_sub(original_size));
+    }
+    analysis
+}
+
+/// Outcome of preparing the contract with a specific [`VMConfig`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreparationCheck {
+    /// Size of the prepared contract or the reason it was rejected.
+    pub result: Result<usize, PrepareError>,
+    /// Limits from the config which the analysis exceeds.  This is the
+    /// explanation for most rejections.
+    pub exceeded_limits: Vec<String>,
+}
+
+impl ContractAnalysis {
+    /// Runs [`crate::prepare::prepare_contract`] with given config and
+    /// compares the analysis with the config limits.
+    pub fn check_preparation(&self, code: &[u8], config: &VMConfig) -> PreparationCheck {
+        let limits = &config.limit_config;
+        let mut exceeded_limits = Vec::new();
+        if self.code_size as u64 > limits.max_contract_size {
+            exceeded_limits.push(format!(
+                "code size {} > max_contract_size {}",
+                self.code_size, limits.max_contract_size
+            ));
+        }
+        if let (Some(count), Some(max)) =
+            (self.function_count, limits.max_functions_number_per_contract)
+        {
+            if count > max {
+                exceeded_limits
+                    .push(format!("{count} functions > max_functions_number_per_contract {max}"));
+            }
+        }
+        if let (Some(count), Some(max)) = (self.local_count, limits.max_locals_per_contract) {
+            if count > max {
+                exceeded_limits.push(format!("{count} locals > max_locals_per_contract {max}"));
+            }
+        }
+        if let Some(cost) = self.max_function_stack_cost {
+            if cost > limits.max_stack_height {
+                exceeded_limits.push(format!(
+                    "function stack cost {cost} > max_stack_height {}",
+                    limits.max_stack_height
+                ));
+            }
+        }
+        for memory in &self.memories {
+            if memory.initial > limits.max_memory_pages {
+                exceeded_limits.push(format!(
+                    "initial memory {} pages > max_memory_pages {}",
+                    memory.initial, limits.max_memory_pages
+                ));
+            }
+        }
+        let result = crate::prepare::prepare_contract(code, config).map(|code| code.len());
+        PreparationCheck { result, exceeded_limits }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_contract() {
+        let code = wat::parse_str(
+            r#"
+            (module
+              (import "env" "input" (func $input (param i64)))
+              (import "env" "log_utf8" (func $log (param i64 i64)))
+              (memory 2 16)
+              (table 3 funcref)
+              (func (export "main") (local i64 i64) call $helper)
+              (func $helper (local i32)))
+            "#,
+        )
+        .unwrap();
+        let analysis = analyze_contract(&code);
+        assert_eq!(analysis.error, None);
+        assert_eq!(analysis.host_functions, ["input", "log_utf8"]);
+        assert!(analysis.other_imports.is_empty());
+        assert_eq!(analysis.exported_methods, ["main"]);
+        assert_eq!(
+            analysis.memories,
+            [MemoryLimits { imported: false, initial: 2, maximum: Some(16) }]
+        );
+        assert_eq!(analysis.tables, [TableLimits { imported: false, initial: 3, maximum: None }]);
+        assert_eq!(analysis.function_count, Some(4));
+        assert_eq!(analysis.local_count, Some(3));
+        assert_eq!(analysis.max_function_stack_cost, Some(2));
+        assert!(analysis.stack_instrumentation_overhead.unwrap() > 0);
+
+        let mut config = VMConfig::test();
+        let check = analysis.check_preparation(&code, &config);
+        assert!(check.result.is_ok());
+        assert!(check.exceeded_limits.is_empty());
+
+        config.limit_config.max_locals_per_contract = Some(2);
+        let check = analysis.check_preparation(&code, &config);
+        assert_eq!(check.result, Err(PrepareError::TooManyLocals));
+        assert_eq!(check.exceeded_limits, ["3 locals > max_locals_per_contract 2"]);
+    }
+
+    #[test]
+    fn test_analyze_invalid_contract() {
+        let code = wat::parse_str(r#"(module (import "foo" "bar" (func)))"#).unwrap();
+        let analysis = analyze_contract(&code);
+        assert_eq!(analysis.other_imports, ["foo.bar"]);
+        let check = analysis.check_preparation(&code, &VMConfig::test());
+        assert_eq!(check.result, Err(PrepareError::Instantiate));
+
+        let analysis = analyze_contract(b"not wasm");
+        assert!(analysis.error.is_some());
+        assert_eq!(analysis.function_count, None);
+    }
+}
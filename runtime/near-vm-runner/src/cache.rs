@@ -10,6 +10,10 @@ use std::collections::HashMap;
 use std::fmt;
 use std::sync::{Arc, Mutex};
 
+mod filesystem;
+
+pub use filesystem::FilesystemCompiledContractCache;
+
 #[derive(Debug, Clone, BorshSerialize)]
 enum ContractCacheKey {
     _Version1,
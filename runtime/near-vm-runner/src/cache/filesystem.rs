@@ -0,0 +1,395 @@
+//! Compiled contract cache which keeps artifacts as files in a directory.
+//!
+//! Unlike the cache in the node’s database, the directory can be shared by
+//! several nodes running on the same host, so that a contract is compiled only
+//! once per host, and it can be populated ahead of time by
+//! `neard precompile-contracts`.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use lru::LruCache;
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::{CompiledContract, CompiledContractCache};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+/// Magic bytes starting every cache file, changing them invalidates all files
+/// written in the previous format.
+const MAGIC: &[u8; 8] = b"nearcc01";
+/// Version of the directory layout, recorded in [`VERSION_FILE`] when the
+/// cache is created.  Directories with a different version are rejected.
+const VERSION: u32 = 1;
+const VERSION_FILE: &str = "VERSION";
+/// Length of the header preceding the serialized artifact: magic bytes
+/// followed by the hash of the artifact.
+const HEADER_LEN: usize = MAGIC.len() + 32;
+/// Age after which a temporary file is assumed to be left over by a process
+/// which crashed while writing it.  Writes take far less, so files of nodes
+/// sharing the directory which are still being written are never removed.
+const STALE_TMP_FILE_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// Filesystem backed [`CompiledContractCache`] with a limit on total size of
+/// the stored artifacts.
+///
+/// Each artifact is stored in a separate file named after its cache key and
+/// starting with a hash of the contents, which is verified on every read.  A
+/// corrupted file is removed and reported as a cache miss, so the contract is
+/// simply compiled again.  Files are written atomically by renaming a
+/// temporary file, so concurrent readers never observe a partial write.
+///
+/// When the total size exceeds the limit, the least recently used artifacts are
+/// removed.  Usage is tracked per process, so with several nodes sharing the
+/// directory each of them evicts based on its own view and the size may
+/// temporarily exceed the limit.  Files added by other processes are picked up
+/// on first access.
+///
+/// The handle is cheap to clone, all clones share the same index.
+#[derive(Clone)]
+pub struct FilesystemCompiledContractCache {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    dir: PathBuf,
+    max_size: u64,
+    /// Size of each known file, in order of use.
+    index: Mutex<Index>,
+    /// Used to generate unique names of temporary files.
+    next_tmp_id: AtomicU64,
+}
+
+struct Index {
+    entries: LruCache<CryptoHash, u64>,
+    total_size: u64,
+}
+
+impl FilesystemCompiledContractCache {
+    /// Opens the cache in given directory, creating it if needed.
+    ///
+    /// Fails if the directory holds a cache of a different version.  Artifacts
+    /// already present are indexed in order of their modification time and
+    /// evicted right away if they don’t fit into `max_size` bytes.  Temporary
+    /// files left over by crashed processes are removed.
+    pub fn open(dir: &Path, max_size: u64) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        check_version(dir)?;
+        remove_stale_tmp_files(dir, STALE_TMP_FILE_AGE)?;
+        let mut files = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let key = match entry.file_name().to_str().and_then(|name| name.parse().ok()) {
+                Some(key) => key,
+                // Temporary files and anything else which isn’t ours.
+                None => continue,
+            };
+            let metadata = entry.metadata()?;
+            if metadata.is_file() {
+                files.push((metadata.modified()?, key, metadata.len()));
+            }
+        }
+        files.sort();
+        let mut index = Index { entries: LruCache::unbounded(), total_size: 0 };
+        for (_, key, size) in files {
+            index.entries.put(key, size);
+            index.total_size += size;
+        }
+        let inner = Inner {
+            dir: dir.to_path_buf(),
+            max_size,
+            index: Mutex::new(index),
+            next_tmp_id: AtomicU64::new(0),
+        };
+        inner.evict(&mut inner.index.lock().unwrap());
+        Ok(Self { inner: Arc::new(inner) })
+    }
+
+    /// Total size in bytes of the artifacts known to this process.
+    pub fn size(&self) -> u64 {
+        self.inner.index.lock().unwrap().total_size
+    }
+
+    /// Number of artifacts known to this process.
+    pub fn len(&self) -> usize {
+        self.inner.index.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Inner {
+    fn path(&self, key: &CryptoHash) -> PathBuf {
+        self.dir.join(key.to_string())
+    }
+
+    /// Records use of the artifact and evicts others if over the limit.
+    fn touch(&self, key: &CryptoHash, size: u64) {
+        let mut index = self.index.lock().unwrap();
+        if let Some(old_size) = index.entries.put(*key, size) {
+            index.total_size -= old_size;
+        }
+        index.total_size += size;
+        self.evict(&mut index);
+    }
+
+    fn forget(&self, key: &CryptoHash) {
+        let mut index = self.index.lock().unwrap();
+        if let Some(size) = index.entries.pop(key) {
+            index.total_size -= size;
+        }
+    }
+
+    /// Removes least recently used artifacts until the total size fits into
+    /// the limit.  The most recently used artifact is always kept.
+    fn evict(&self, index: &mut Index) {
+        while index.total_size > self.max_size && index.entries.len() > 1 {
+            let (key, size) = index.entries.pop_lru().expect("cache is not empty");
+            index.total_size -= size;
+            if let Err(err) = remove_file(&self.path(&key)) {
+                tracing::warn!(target: "vm", %key, %err, "failed to evict compiled contract");
+            }
+        }
+    }
+}
+
+impl CompiledContractCache for FilesystemCompiledContractCache {
+    fn put(&self, key: &CryptoHash, value: CompiledContract) -> io::Result<()> {
+        let payload = value.try_to_vec()?;
+        let size = (HEADER_LEN + payload.len()) as u64;
+        if size > self.inner.max_size {
+            return Ok(());
+        }
+        let mut contents = Vec::with_capacity(size as usize);
+        contents.extend_from_slice(MAGIC);
+        contents.extend_from_slice(CryptoHash::hash_bytes(&payload).as_bytes());
+        contents.extend_from_slice(&payload);
+
+        let tmp_id = self.inner.next_tmp_id.fetch_add(1, Ordering::Relaxed);
+        let tmp_path = self.inner.dir.join(format!(".{key}.{}.{tmp_id}.tmp", std::process::id()));
+        if let Err(err) = fs::write(&tmp_path, &contents)
+            .and_then(|()| fs::rename(&tmp_path, self.inner.path(key)))
+        {
+            let _ = remove_file(&tmp_path);
+            return Err(err);
+        }
+        self.inner.touch(key, size);
+        Ok(())
+    }
+
+    fn get(&self, key: &CryptoHash) -> io::Result<Option<CompiledContract>> {
+        let path = self.inner.path(key);
+        let contents = match fs::read(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                // Possibly evicted by another node sharing the directory.
+                self.inner.forget(key);
+                return Ok(None);
+            }
+            Err(err) => return Err(err),
+        };
+        match decode(&contents) {
+            Some(value) => {
+                self.inner.touch(key, contents.len() as u64);
+                Ok(Some(value))
+            }
+            None => {
+                tracing::warn!(target: "vm", %key, "removing corrupted compiled contract");
+                self.inner.forget(key);
+                remove_file(&path)?;
+                Ok(None)
+            }
+        }
+    }
+
+    fn has(&self, key: &CryptoHash) -> io::Result<bool> {
+        match fs::metadata(self.inner.path(key)) {
+            Ok(_) => Ok(true),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Verifies that the directory holds a cache of the current version, recording
+/// the version if the cache is new.
+fn check_version(dir: &Path) -> io::Result<()> {
+    let path = dir.join(VERSION_FILE);
+    match fs::read_to_string(&path) {
+        Ok(version) if version.trim() == VERSION.to_string() => Ok(()),
+        Ok(version) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "compiled contract cache in {} has version {}, expected {VERSION}",
+                dir.display(),
+                version.trim()
+            ),
+        )),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            // Written through a temporary file so that a node opening the
+            // cache concurrently never reads an empty version.
+            let tmp_path = dir.join(format!(".{VERSION_FILE}.{}.tmp", std::process::id()));
+            fs::write(&tmp_path, format!("{VERSION}\n"))?;
+            fs::rename(&tmp_path, &path)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Removes temporary files not modified for at least `max_age`.
+fn remove_stale_tmp_files(dir: &Path, max_age: Duration) -> io::Result<()> {
+    let now = SystemTime::now();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let is_tmp =
+            name.to_str().map_or(false, |name| name.starts_with('.') && name.ends_with(".tmp"));
+        if !is_tmp {
+            continue;
+        }
+        let metadata = match entry.metadata() {
+            Ok(metadata) => metadata,
+            // Renamed by the node writing it in the meantime.
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+        // Modification time in the future counts as fresh.
+        let age = now.duration_since(metadata.modified()?).unwrap_or_default();
+        if metadata.is_file() && age >= max_age {
+            tracing::debug!(target: "vm", path = %entry.path().display(), "removing stale temporary file");
+            remove_file(&entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the artifact stored in a cache file or `None` if the file is
+/// corrupted.
+fn decode(contents: &[u8]) -> Option<CompiledContract> {
+    if contents.len() < HEADER_LEN || &contents[..MAGIC.len()] != MAGIC {
+        return None;
+    }
+    let (hash, payload) = contents[MAGIC.len()..].split_at(HEADER_LEN - MAGIC.len());
+    if CryptoHash::hash_bytes(payload).as_bytes() != hash {
+        return None;
+    }
+    CompiledContract::try_from_slice(payload).ok()
+}
+
+/// Removes the file, treating an already missing file as success.
+fn remove_file(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FilesystemCompiledContractCache;
+    use near_primitives::hash::CryptoHash;
+    use near_primitives::types::{CompiledContract, CompiledContractCache};
+
+    fn key(n: u8) -> CryptoHash {
+        CryptoHash::hash_bytes(&[n])
+    }
+
+    fn code(len: usize) -> CompiledContract {
+        CompiledContract::Code(vec![42; len])
+    }
+
+    #[test]
+    fn test_put_get() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = FilesystemCompiledContractCache::open(dir.path(), 1 << 20).unwrap();
+        assert_eq!(cache.get(&key(1)).unwrap(), None);
+        assert!(!cache.has(&key(1)).unwrap());
+        cache.put(&key(1), code(100)).unwrap();
+        assert!(cache.has(&key(1)).unwrap());
+        assert_eq!(cache.get(&key(1)).unwrap(), Some(code(100)));
+
+        // Another node sharing the directory sees the artifact.
+        let other = FilesystemCompiledContractCache::open(dir.path(), 1 << 20).unwrap();
+        assert_eq!(other.len(), 1);
+        assert_eq!(other.size(), cache.size());
+        assert_eq!(other.get(&key(1)).unwrap(), Some(code(100)));
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = FilesystemCompiledContractCache::open(dir.path(), 1100).unwrap();
+        cache.put(&key(1), code(300)).unwrap();
+        cache.put(&key(2), code(300)).unwrap();
+        cache.put(&key(3), code(300)).unwrap();
+        assert_eq!(cache.len(), 3);
+        // Use the oldest entry so that the second one gets evicted instead.
+        assert!(cache.get(&key(1)).unwrap().is_some());
+        cache.put(&key(4), code(300)).unwrap();
+        assert_eq!(cache.len(), 3);
+        assert!(cache.size() <= 1100);
+        assert!(!cache.has(&key(2)).unwrap());
+        for n in [1, 3, 4] {
+            assert!(cache.get(&key(n)).unwrap().is_some(), "{n}");
+        }
+
+        // Artifacts which can never fit are not stored at all.
+        cache.put(&key(5), code(2000)).unwrap();
+        assert!(!cache.has(&key(5)).unwrap());
+
+        // Reopening with a smaller limit evicts right away.
+        let cache = FilesystemCompiledContractCache::open(dir.path(), 700).unwrap();
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = FilesystemCompiledContractCache::open(dir.path(), 1 << 20).unwrap();
+        cache.put(&key(1), code(100)).unwrap();
+        let version = dir.path().join(super::VERSION_FILE);
+        assert_eq!(std::fs::read_to_string(&version).unwrap(), format!("{}\n", super::VERSION));
+        // The version file isn’t mistaken for an artifact.
+        assert_eq!(FilesystemCompiledContractCache::open(dir.path(), 1 << 20).unwrap().len(), 1);
+
+        std::fs::write(&version, "0\n").unwrap();
+        let err = FilesystemCompiledContractCache::open(dir.path(), 1 << 20).err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_stale_tmp_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = FilesystemCompiledContractCache::open(dir.path(), 1 << 20).unwrap();
+        cache.put(&key(1), code(100)).unwrap();
+        let tmp = dir.path().join(format!(".{}.1234.0.tmp", key(2)));
+        std::fs::write(&tmp, b"partial").unwrap();
+
+        // A fresh file may still be written by another node.
+        FilesystemCompiledContractCache::open(dir.path(), 1 << 20).unwrap();
+        assert!(tmp.exists());
+
+        super::remove_stale_tmp_files(dir.path(), std::time::Duration::ZERO).unwrap();
+        assert!(!tmp.exists());
+        assert!(dir.path().join(super::VERSION_FILE).exists());
+        assert_eq!(FilesystemCompiledContractCache::open(dir.path(), 1 << 20).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_corruption() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = FilesystemCompiledContractCache::open(dir.path(), 1 << 20).unwrap();
+        cache.put(&key(1), code(100)).unwrap();
+        let path = dir.path().join(key(1).to_string());
+        let mut contents = std::fs::read(&path).unwrap();
+        *contents.last_mut().unwrap() ^= 1;
+        std::fs::write(&path, contents).unwrap();
+
+        assert_eq!(cache.get(&key(1)).unwrap(), None);
+        assert!(!path.exists());
+        assert!(cache.is_empty());
+    }
+}
@@ -109,6 +109,13 @@ imports! {
         pub_key_len: u64,
         pub_key_ptr: u64
     ] -> [u64]>,
+    #["protocol_feature_p256_verify", P256Verify] p256_verify<[sig_len: u64,
+        sig_ptr: u64,
+        msg_len: u64,
+        msg_ptr: u64,
+        pub_key_len: u64,
+        pub_key_ptr: u64
+    ] -> [u64]>,
     #[MathExtension] ripemd160<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
     #[MathExtension] ecrecover<[hash_len: u64, hash_ptr: u64, sign_len: u64, sig_ptr: u64, v: u64, malleability_flag: u64, register_id: u64] -> [u64]>,
     // #####################
@@ -211,6 +218,24 @@ imports! {
     promise_results_count<[] -> [u64]>,
     promise_result<[result_idx: u64, register_id: u64] -> [u64]>,
     promise_return<[promise_idx: u64] -> []>,
+    // ####################
+    // # Yield/resume API #
+    // ####################
+    #["protocol_feature_yield_resume", YieldResume] promise_yield_create<[
+        method_name_len: u64,
+        method_name_ptr: u64,
+        arguments_len: u64,
+        arguments_ptr: u64,
+        gas: u64,
+        gas_weight: u64,
+        register_id: u64
+    ] -> [u64]>,
+    #["protocol_feature_yield_resume", YieldResume] promise_yield_resume<[
+        data_id_len: u64,
+        data_id_ptr: u64,
+        payload_len: u64,
+        payload_ptr: u64
+    ] -> [u32]>,
     // ###############
     // # Storage API #
     // ###############
@@ -222,6 +247,9 @@ imports! {
     storage_iter_range<[start_len: u64, start_ptr: u64, end_len: u64, end_ptr: u64] -> [u64]>,
     storage_iter_next<[iterator_id: u64, key_register_id: u64, value_register_id: u64] -> [u64]>,
     // Function for the injected gas counter. Automatically called by the gas meter.
+    //
+    // Contracts instrumented for extended gas profiles call `profiled_gas` instead, which is only
+    // linked when `VMLogic::is_profiling` and thus isn't listed here.
     gas<[gas_amount: u32] -> []>,
     // ###############
     // # Validator API #
@@ -235,6 +263,16 @@ imports! {
     #[AltBn128] alt_bn128_g1_sum<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
     #[AltBn128] alt_bn128_pairing_check<[value_len: u64, value_ptr: u64] -> [u64]>,
     // #############
+    // # BLS12-381 #
+    // #############
+    #["protocol_feature_bls12381", Bls12381] bls12381_p1_sum<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    #["protocol_feature_bls12381", Bls12381] bls12381_p2_sum<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    #["protocol_feature_bls12381", Bls12381] bls12381_g1_multiexp<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    #["protocol_feature_bls12381", Bls12381] bls12381_g2_multiexp<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    #["protocol_feature_bls12381", Bls12381] bls12381_map_fp_to_g1<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    #["protocol_feature_bls12381", Bls12381] bls12381_map_fp2_to_g2<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    #["protocol_feature_bls12381", Bls12381] bls12381_pairing_check<[value_len: u64, value_ptr: u64] -> [u64]>,
+    // #############
     // #  Sandbox  #
     // #############
     ##["sandbox"] sandbox_debug_log<[len: u64, ptr: u64] -> []>,
@@ -273,20 +311,28 @@ pub(crate) mod wasmer {
             ) => {
                 #[allow(unused_parens)]
                 fn $func( ctx: &mut wasmer_runtime::Ctx, $( $arg_name: $arg_type ),* ) -> Result<($( $returns ),*), VMLogicError> {
-                    const IS_GAS: bool = str_eq(stringify!($func), "gas");
+                    const IS_GAS: bool = str_eq(stringify!($func), "gas") || str_eq(stringify!($func), "profiled_gas");
                     let _span = if IS_GAS {
                         None
                     } else {
                         Some(tracing::trace_span!(target: "host-function", stringify!($func)).entered())
                     };
                     let logic: &mut VMLogic<'_> = unsafe { &mut *(ctx.data as *mut VMLogic<'_>) };
-                    logic.$func( $( $arg_name, )* )
+                    if !IS_GAS && (logic.is_tracing() || logic.is_profiling()) {
+                        let args: &[(&str, u64)] = &[$( (stringify!($arg_name), u64::from($arg_name)), )*];
+                        logic.trace_host_call(stringify!($func), args, |logic| logic.$func( $( $arg_name, )* ))
+                    } else {
+                        logic.$func( $( $arg_name, )* )
+                    }
                 }
 
                 ns.insert(stringify!($func), wasmer_runtime::func!($func));
             };
         }
         for_each_available_import!(protocol_version, add_import);
+        if logic.is_profiling() {
+            add_import!(profiled_gas<[function_index: u32, gas_amount: u32] -> []>);
+        }
 
         import_object.register("env", ns);
         import_object
@@ -370,7 +416,7 @@ pub(crate) mod wasmer2 {
                     extern "C" fn $func(env: *mut VMLogic<'_>, $( $arg_name: $arg_type ),* )
                     -> Ret {
                         let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                            const IS_GAS: bool = str_eq(stringify!($func), "gas");
+                            const IS_GAS: bool = str_eq(stringify!($func), "gas") || str_eq(stringify!($func), "profiled_gas");
                             let _span = if IS_GAS {
                                 None
                             } else {
@@ -384,7 +430,15 @@ pub(crate) mod wasmer2 {
                             // lifetime and so it is safe to dereference the `env` pointer which is
                             // known to be derived from a valid `&'vmlogic mut VMLogic<'_>` in the
                             // first place.
-                            unsafe { (*env).$func( $( $arg_name, )* ) }
+                            let logic = unsafe { &mut *env };
+                            if !IS_GAS && (logic.is_tracing() || logic.is_profiling()) {
+                                let args: &[(&str, u64)] = &[$( (stringify!($arg_name), u64::from($arg_name)), )*];
+                                logic.trace_host_call(stringify!($func), args, |logic| {
+                                    logic.$func( $( $arg_name, )* )
+                                })
+                            } else {
+                                logic.$func( $( $arg_name, )* )
+                            }
                         }));
                         // We want to ensure that the only kind of error that host function calls
                         // return are VMLogicError. This is important because we later attempt to
@@ -432,6 +486,9 @@ pub(crate) mod wasmer2 {
                 };
             }
             for_each_available_import!(self.protocol_version, add_import);
+            if self.vmlogic.is_profiling() {
+                add_import!(profiled_gas<[function_index: u32, gas_amount: u32] -> []>);
+            }
             return None;
         }
     }
@@ -492,6 +549,7 @@ pub(crate) mod wasmtime {
         memory: wasmtime::Memory,
         raw_logic: *mut c_void,
         protocol_version: ProtocolVersion,
+        profiling: bool,
     ) {
         CALLER_CONTEXT.with(|caller_context| unsafe { *caller_context.get() = raw_logic });
         linker.define("env", "memory", memory).expect("cannot define memory");
@@ -502,7 +560,7 @@ pub(crate) mod wasmtime {
             ) => {
                 #[allow(unused_parens)]
                 fn $func(caller: wasmtime::Caller<'_, ()>, $( $arg_name: $arg_type ),* ) -> anyhow::Result<($( $returns ),*)> {
-                    const IS_GAS: bool = str_eq(stringify!($func), "gas");
+                    const IS_GAS: bool = str_eq(stringify!($func), "gas") || str_eq(stringify!($func), "profiled_gas");
                     let _span = if IS_GAS {
                         None
                     } else {
@@ -521,7 +579,13 @@ pub(crate) mod wasmtime {
                         crate::wasmtime_runner::CALLER.with(|runner_caller| *runner_caller.borrow_mut() = std::mem::transmute(caller));
                     }
                     let logic: &mut VMLogic<'_> = unsafe { &mut *(data as *mut VMLogic<'_>) };
-                    match logic.$func( $( $arg_name as $arg_type, )* ) {
+                    let result = if !IS_GAS && (logic.is_tracing() || logic.is_profiling()) {
+                        let args: &[(&str, u64)] = &[$( (stringify!($arg_name), u64::from($arg_name)), )*];
+                        logic.trace_host_call(stringify!($func), args, |logic| logic.$func( $( $arg_name as $arg_type, )* ))
+                    } else {
+                        logic.$func( $( $arg_name as $arg_type, )* )
+                    };
+                    match result {
                         Ok(result) => Ok(result as ($( $returns ),* ) ),
                         Err(err) => {
                             Err(ErrorContainer(std::sync::Mutex::new(Some(err))).into())
@@ -533,6 +597,9 @@ pub(crate) mod wasmtime {
             };
         }
         for_each_available_import!(protocol_version, add_import);
+        if profiling {
+            add_import!(profiled_gas<[function_index: u32, gas_amount: u32] -> []>);
+        }
     }
 }
 
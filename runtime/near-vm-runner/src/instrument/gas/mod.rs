@@ -11,6 +11,7 @@ use parity_wasm::{builder, elements, elements::ValueType};
 use std::{cmp::min, mem};
 
 use super::rules::{self, Rules};
+use near_primitives::profile::MEMORY_GROW_FUNCTION_INDEX;
 
 pub fn update_call_index(instructions: &mut elements::Instructions, inserted_index: u32) {
     use parity_wasm::elements::Instruction::*;
@@ -231,6 +232,7 @@ fn add_grow_counter<R: Rules>(
     module: elements::Module,
     rules: &R,
     gas_func: u32,
+    profiling: bool,
 ) -> elements::Module {
     use parity_wasm::elements::Instruction::*;
     use rules::MemoryGrowCost;
@@ -240,6 +242,20 @@ fn add_grow_counter<R: Rules>(
         Some(MemoryGrowCost::Linear(val)) => val.get(),
     };
 
+    let mut instructions = vec![GetLocal(0)];
+    if profiling {
+        instructions.push(I32Const(MEMORY_GROW_FUNCTION_INDEX as i32));
+    }
+    instructions.extend([
+        GetLocal(0),
+        I32Const(cost as i32),
+        I32Mul,
+        // todo: there should be strong guarantee that it does not return anything on stack?
+        Call(gas_func),
+        GrowMemory(0),
+        End,
+    ]);
+
     let mut b = builder::from_module(module);
     b.push_function(
         builder::function()
@@ -248,16 +264,115 @@ fn add_grow_counter<R: Rules>(
             .with_result(ValueType::I32)
             .build()
             .body()
-            .with_instructions(elements::Instructions::new(vec![
-                GetLocal(0),
-                GetLocal(0),
-                I32Const(cost as i32),
-                I32Mul,
-                // todo: there should be strong guarantee that it does not return anything on stack?
-                Call(gas_func),
-                GrowMemory(0),
-                End,
-            ]))
+            .with_instructions(elements::Instructions::new(instructions))
+            .build()
+            .build(),
+    );
+
+    b.build()
+}
+
+/// Whether the instruction processes a number of bytes or table elements given by the operand on
+/// top of the stack.
+#[cfg(feature = "protocol_feature_wasm_extensions")]
+fn is_bulk_memory_instruction(instruction: &elements::Instruction) -> bool {
+    use parity_wasm::elements::{BulkInstruction::*, Instruction::Bulk};
+    matches!(instruction, Bulk(MemoryInit(_) | MemoryCopy | MemoryFill | TableInit(_) | TableCopy))
+}
+
+/// `parity_wasm` can't decode bulk memory instructions without
+/// `protocol_feature_wasm_extensions`.
+#[cfg(not(feature = "protocol_feature_wasm_extensions"))]
+fn is_bulk_memory_instruction(_instruction: &elements::Instruction) -> bool {
+    false
+}
+
+/// Inserts a call to the bulk memory counter before each bulk memory instruction.  The counter
+/// charges for the length operand and leaves it on the stack, so the instruction is unaffected.
+///
+/// If `func_index` is given, it is passed to the counter as the second argument.
+fn inject_bulk_memory_counter(
+    instructions: &mut elements::Instructions,
+    bulk_memory_counter_func: u32,
+    func_index: Option<u32>,
+) -> usize {
+    use parity_wasm::elements::Instruction::*;
+    let counter = instructions.elements().iter().filter(|i| is_bulk_memory_instruction(i)).count();
+    if counter == 0 {
+        return 0;
+    }
+    let instrs_per_call = if func_index.is_some() { 2 } else { 1 };
+    let new_instrs_len = instructions.elements().len() + instrs_per_call * counter;
+    let original_instrs =
+        mem::replace(instructions.elements_mut(), Vec::with_capacity(new_instrs_len));
+    let new_instrs = instructions.elements_mut();
+    for instr in original_instrs {
+        if is_bulk_memory_instruction(&instr) {
+            if let Some(func_index) = func_index {
+                new_instrs.push(I32Const(func_index as i32));
+            }
+            new_instrs.push(Call(bulk_memory_counter_func));
+        }
+        new_instrs.push(instr);
+    }
+    counter
+}
+
+fn add_bulk_memory_counter<R: Rules>(
+    module: elements::Module,
+    rules: &R,
+    gas_func: u32,
+    profiling: bool,
+) -> elements::Module {
+    use parity_wasm::elements::Instruction::*;
+
+    let cost = match rules.bulk_memory_word_cost() {
+        None => return module,
+        Some(val) => val.get(),
+    };
+
+    // The length is the first parameter, followed by the function index when profiling.  It's
+    // rounded up to whole 8-byte words.  The product can't overflow 64 bits, but it's capped at
+    // `u32::MAX` which the gas function takes and which is way more than the gas limit allows
+    // anyway.
+    let (params, product) = if profiling { (2, 2) } else { (1, 1) };
+    let mut instructions = vec![];
+    if profiling {
+        instructions.push(GetLocal(1));
+    }
+    instructions.extend([
+        GetLocal(0),
+        I64ExtendUI32,
+        I64Const(7),
+        I64Add,
+        I64Const(3),
+        I64ShrU,
+        I64Const(cost as i64),
+        I64Mul,
+        TeeLocal(product),
+        I64Const(u32::MAX as i64),
+        GetLocal(product),
+        I64Const(u32::MAX as i64),
+        I64LtU,
+        Select,
+        I32WrapI64,
+        Call(gas_func),
+        GetLocal(0),
+        End,
+    ]);
+
+    let mut signature = builder::function().signature();
+    for _ in 0..params {
+        signature = signature.with_param(ValueType::I32);
+    }
+    let mut b = builder::from_module(module);
+    b.push_function(
+        signature
+            .with_result(ValueType::I32)
+            .build()
+            .body()
+            .with_locals(vec![elements::Local::new(1, ValueType::I64)])
+            .with_instructions(elements::Instructions::new(instructions))
             .build()
             .build(),
     );
@@ -343,22 +458,27 @@ pub fn inject_counter<R: Rules>(
     instructions: &mut elements::Instructions,
     rules: &R,
     gas_func: u32,
+    func_index: Option<u32>,
 ) -> Result<(), ()> {
     let blocks = determine_metered_blocks(instructions, rules)?;
-    insert_metering_calls(instructions, blocks, gas_func)
+    insert_metering_calls(instructions, blocks, gas_func, func_index)
 }
 
 // Then insert metering calls into a sequence of instructions given the block locations and costs.
+//
+// If `func_index` is given, it is passed to the metering function as the first argument.
 fn insert_metering_calls(
     instructions: &mut elements::Instructions,
     blocks: Vec<MeteredBlock>,
     gas_func: u32,
+    func_index: Option<u32>,
 ) -> Result<(), ()> {
     use parity_wasm::elements::Instruction::*;
 
     // To do this in linear time, construct a new vector of instructions, copying over old
     // instructions one by one and injecting new ones as required.
-    let new_instrs_len = instructions.elements().len() + 2 * blocks.len();
+    let instrs_per_block = if func_index.is_some() { 3 } else { 2 };
+    let new_instrs_len = instructions.elements().len() + instrs_per_block * blocks.len();
     let original_instrs =
         mem::replace(instructions.elements_mut(), Vec::with_capacity(new_instrs_len));
     let new_instrs = instructions.elements_mut();
@@ -368,6 +488,9 @@ fn insert_metering_calls(
         // If there the next block starts at this position, inject metering instructions.
         let used_block = if let Some(block) = block_iter.peek() {
             if block.start_pos == original_pos {
+                if let Some(func_index) = func_index {
+                    new_instrs.push(I32Const(func_index as i32));
+                }
                 new_instrs.push(I32Const(block.cost as i32));
                 new_instrs.push(Call(gas_func));
                 true
@@ -417,7 +540,9 @@ fn insert_metering_calls(
 /// Additionally, each `memory.grow` instruction found in the module is instrumented to first make
 /// a call to charge gas for the additional pages requested. This cannot be done as part of the
 /// block level gas charges as the gas cost is not static and depends on the stack argument to
-/// `memory.grow`.
+/// `memory.grow`. For the same reason, each bulk memory instruction (`memory.copy`,
+/// `memory.fill`, `memory.init`, `table.copy` and `table.init`) is preceded by a call which
+/// charges gas proportional to its length operand.
 ///
 /// The above transformations are performed for every function body defined in the module. This
 /// function also rewrites all function indices references by code, table elements, etc., since
@@ -431,14 +556,45 @@ pub fn inject_gas_counter<R: Rules>(
     module: elements::Module,
     rules: &R,
     gas_module_name: &str,
+) -> Result<elements::Module, elements::Module> {
+    inject_gas_counter_impl(module, rules, gas_module_name, false)
+}
+
+/// Same as [`inject_gas_counter`], but the output module imports a function "profiled_gas" with
+/// type signature [i32, i32] -> [] instead of "gas". The additional first argument is the index
+/// of the function in the input module that the charged gas is attributed to, or
+/// [`MEMORY_GROW_FUNCTION_INDEX`] for the gas charged by `memory.grow`.
+///
+/// This is never used for contracts executed as part of the protocol, only for the extended gas
+/// profiles requested over RPC.
+pub fn inject_profiling_gas_counter<R: Rules>(
+    module: elements::Module,
+    rules: &R,
+    gas_module_name: &str,
+) -> Result<elements::Module, elements::Module> {
+    inject_gas_counter_impl(module, rules, gas_module_name, true)
+}
+
+fn inject_gas_counter_impl<R: Rules>(
+    module: elements::Module,
+    rules: &R,
+    gas_module_name: &str,
+    profiling: bool,
 ) -> Result<elements::Module, elements::Module> {
     // Injecting gas counting external
     let mut mbuilder = builder::from_module(module);
-    let import_sig =
-        mbuilder.push_signature(builder::signature().with_param(ValueType::I32).build_sig());
+    let (field, import_sig) = if profiling {
+        let sig = builder::signature().with_param(ValueType::I32).with_param(ValueType::I32);
+        ("profiled_gas", mbuilder.push_signature(sig.build_sig()))
+    } else {
+        (
+            "gas",
+            mbuilder.push_signature(builder::signature().with_param(ValueType::I32).build_sig()),
+        )
+    };
 
     mbuilder.push_import(
-        builder::import().module(gas_module_name).field("gas").external().func(import_sig).build(),
+        builder::import().module(gas_module_name).field(field).external().func(import_sig).build(),
     );
 
     // back to plain module
@@ -450,15 +606,33 @@ pub fn inject_gas_counter<R: Rules>(
     let gas_func = module.import_count(elements::ImportCountType::Function) as u32 - 1;
     let total_func = module.functions_space() as u32;
     let mut need_grow_counter = false;
+    let mut need_bulk_memory_counter = false;
     let mut error = false;
 
+    // The counter functions are appended after all functions of the module, the bulk memory
+    // counter after the grow counter if there's one.
+    let has_grow_memory = module.code_section().map_or(false, |code_section| {
+        code_section.bodies().iter().any(|func_body| {
+            func_body
+                .code()
+                .elements()
+                .iter()
+                .any(|instruction| matches!(instruction, elements::Instruction::GrowMemory(_)))
+        })
+    });
+    let bulk_memory_counter_func =
+        total_func + (rules.memory_grow_cost().is_some() && has_grow_memory) as u32;
+
     // Updating calling addresses (all calls to function index >= `gas_func` should be incremented)
     for section in module.sections_mut() {
         match section {
             elements::Section::Code(code_section) => {
-                for func_body in code_section.bodies_mut() {
+                for (body_index, func_body) in code_section.bodies_mut().iter_mut().enumerate() {
                     update_call_index(func_body.code_mut(), gas_func);
-                    if inject_counter(func_body.code_mut(), rules, gas_func).is_err() {
+                    // The gas import has been appended after all the original function imports,
+                    // so it takes the index of the first function defined in the original module.
+                    let func_index = profiling.then_some(gas_func + body_index as u32);
+                    if inject_counter(func_body.code_mut(), rules, gas_func, func_index).is_err() {
                         error = true;
                         break;
                     }
@@ -467,6 +641,15 @@ pub fn inject_gas_counter<R: Rules>(
                     {
                         need_grow_counter = true;
                     }
+                    if rules.bulk_memory_word_cost().is_some()
+                        && inject_bulk_memory_counter(
+                            func_body.code_mut(),
+                            bulk_memory_counter_func,
+                            func_index,
+                        ) > 0
+                    {
+                        need_bulk_memory_counter = true;
+                    }
                 }
             }
             elements::Section::Export(export_section) => {
@@ -504,10 +687,12 @@ pub fn inject_gas_counter<R: Rules>(
     }
 
     if need_grow_counter {
-        Ok(add_grow_counter(module, rules, gas_func))
-    } else {
-        Ok(module)
+        module = add_grow_counter(module, rules, gas_func, profiling);
+    }
+    if need_bulk_memory_counter {
+        module = add_bulk_memory_counter(module, rules, gas_func, profiling);
     }
+    Ok(module)
 }
 
 #[cfg(test)]
@@ -563,6 +748,58 @@ mod tests {
         wasmparser::validate(&binary).unwrap();
     }
 
+    #[test]
+    fn profiling_grow() {
+        let module = builder::module()
+            .global()
+            .value_type()
+            .i32()
+            .init_expr(I32Const(0))
+            .build()
+            .memory()
+            .build()
+            .function()
+            .signature()
+            .param()
+            .i32()
+            .build()
+            .body()
+            .with_instructions(elements::Instructions::new(vec![GetGlobal(0), GrowMemory(0), End]))
+            .build()
+            .build()
+            .build();
+
+        let injected_module = inject_profiling_gas_counter(
+            module,
+            &rules::Set::default().with_grow_cost(10000),
+            "env",
+        )
+        .unwrap();
+
+        let import = &injected_module.import_section().unwrap().entries()[0];
+        assert_eq!((import.module(), import.field()), ("env", "profiled_gas"));
+        assert_eq!(
+            get_function_body(&injected_module, 0).unwrap(),
+            &[I32Const(0), I32Const(2), Call(0), GetGlobal(0), Call(2), End][..]
+        );
+        assert_eq!(
+            get_function_body(&injected_module, 1).unwrap(),
+            &[
+                GetLocal(0),
+                I32Const(MEMORY_GROW_FUNCTION_INDEX as i32),
+                GetLocal(0),
+                I32Const(10000),
+                I32Mul,
+                Call(0),
+                GrowMemory(0),
+                End
+            ][..]
+        );
+
+        let binary = serialize(injected_module).expect("serialization failed");
+        wasmparser::validate(&binary).unwrap();
+    }
+
     #[test]
     fn grow_no_gas_no_track() {
         let module = builder::module()
@@ -597,6 +834,89 @@ mod tests {
         wasmparser::validate(&binary).unwrap();
     }
 
+    #[test]
+    #[cfg(feature = "protocol_feature_wasm_extensions")]
+    fn bulk_memory() {
+        let module = parse_wat(
+            r#"(module
+                (memory 1)
+                (func (param i32)
+                    (memory.fill (i32.const 0) (i32.const 1) (local.get 0))
+                    (drop (memory.grow (i32.const 1)))
+                    (memory.fill (i32.const 0) (i32.const 1) (i32.const 2))))"#,
+        );
+        let rules = rules::Set::default().with_grow_cost(10000).with_bulk_memory_word_cost(3);
+        let validate = |module: elements::Module| {
+            let binary = serialize(module).expect("serialization failed");
+            let mut validator = wasmparser::Validator::new();
+            validator.wasm_features(wasmparser::WasmFeatures {
+                bulk_memory: true,
+                ..crate::prepare::WASM_FEATURES
+            });
+            validator.validate_all(&binary).unwrap();
+        };
+
+        let injected_module = inject_gas_counter(module.clone(), &rules, "env").unwrap();
+        // The grow counter comes first, followed by the bulk memory counter.
+        assert_eq!(
+            get_function_body(&injected_module, 0).unwrap(),
+            &[
+                I32Const(11),
+                Call(0),
+                I32Const(0),
+                I32Const(1),
+                GetLocal(0),
+                Call(3),
+                Bulk(elements::BulkInstruction::MemoryFill),
+                I32Const(1),
+                Call(2),
+                Drop,
+                I32Const(0),
+                I32Const(1),
+                I32Const(2),
+                Call(3),
+                Bulk(elements::BulkInstruction::MemoryFill),
+                End
+            ][..]
+        );
+        assert_eq!(
+            get_function_body(&injected_module, 2).unwrap(),
+            &[
+                GetLocal(0),
+                I64ExtendUI32,
+                I64Const(7),
+                I64Add,
+                I64Const(3),
+                I64ShrU,
+                I64Const(3),
+                I64Mul,
+                TeeLocal(1),
+                I64Const(u32::MAX as i64),
+                GetLocal(1),
+                I64Const(u32::MAX as i64),
+                I64LtU,
+                Select,
+                I32WrapI64,
+                Call(0),
+                GetLocal(0),
+                End
+            ][..]
+        );
+        validate(injected_module);
+
+        let injected_module = inject_profiling_gas_counter(module, &rules, "env").unwrap();
+        let body = get_function_body(&injected_module, 0).unwrap();
+        assert_eq!(
+            &body[6..9],
+            &[I32Const(0), Call(3), Bulk(elements::BulkInstruction::MemoryFill)][..]
+        );
+        assert_eq!(
+            &get_function_body(&injected_module, 2).unwrap()[..2],
+            &[GetLocal(1), GetLocal(0)]
+        );
+        validate(injected_module);
+    }
+
     #[test]
     fn call_index() {
         let module = builder::module()
@@ -23,6 +23,15 @@ pub trait Rules {
     /// those costs depend on the stack and must be injected as code into the function calling
     /// `memory.grow`. Therefore returning `Some` comes with a performance cost.
     fn memory_grow_cost(&self) -> Option<MemoryGrowCost>;
+
+    /// Returns the cost for each 8-byte word (or table element) processed by bulk memory
+    /// instructions such as `memory.copy` and `memory.fill`. A trailing partial word is charged as
+    /// a whole one.
+    ///
+    /// Same as with `memory_grow_cost`, these costs are in addition to `instruction_cost` and are
+    /// charged by injected code since they depend on the length operand on the stack.
+    /// Specifying `None` leads to no additional charge.
+    fn bulk_memory_word_cost(&self) -> Option<NonZeroU32>;
 }
 
 /// Dynamic costs for memory growth.
@@ -63,6 +72,7 @@ pub enum InstructionType {
     Nop,
     CurrentMemory,
     GrowMemory,
+    BulkMemory,
 }
 
 impl FromStr for InstructionType {
@@ -90,6 +100,7 @@ impl FromStr for InstructionType {
             "nop" => Ok(InstructionType::Nop),
             "current_mem" => Ok(InstructionType::CurrentMemory),
             "grow_mem" => Ok(InstructionType::GrowMemory),
+            "bulk_mem" => Ok(InstructionType::BulkMemory),
             _ => Err(UnknownInstruction),
         }
     }
@@ -288,6 +299,12 @@ impl InstructionType {
             I64ReinterpretF64 => InstructionType::Reinterpretation,
             F32ReinterpretI32 => InstructionType::Reinterpretation,
             F64ReinterpretI64 => InstructionType::Reinterpretation,
+
+            #[cfg(feature = "protocol_feature_wasm_extensions")]
+            SignExt(_) => InstructionType::Conversion,
+
+            #[cfg(feature = "protocol_feature_wasm_extensions")]
+            Bulk(_) => InstructionType::BulkMemory,
         }
     }
 }
@@ -297,23 +314,29 @@ pub struct Set {
     regular: u32,
     entries: Map<InstructionType, Metering>,
     grow: u32,
+    bulk_memory_word: u32,
 }
 
 impl Default for Set {
     fn default() -> Self {
-        Set { regular: 1, entries: Map::new(), grow: 0 }
+        Set { regular: 1, entries: Map::new(), grow: 0, bulk_memory_word: 0 }
     }
 }
 
 impl Set {
     pub fn new(regular: u32, entries: Map<InstructionType, Metering>) -> Self {
-        Set { regular, entries, grow: 0 }
+        Set { regular, entries, grow: 0, bulk_memory_word: 0 }
     }
 
     pub fn with_grow_cost(mut self, val: u32) -> Self {
         self.grow = val;
         self
     }
+
+    pub fn with_bulk_memory_word_cost(mut self, val: u32) -> Self {
+        self.bulk_memory_word = val;
+        self
+    }
 }
 
 impl Rules for Set {
@@ -328,4 +351,8 @@ impl Rules for Set {
     fn memory_grow_cost(&self) -> Option<MemoryGrowCost> {
         NonZeroU32::new(self.grow).map(MemoryGrowCost::Linear)
     }
+
+    fn bulk_memory_word_cost(&self) -> Option<NonZeroU32> {
+        NonZeroU32::new(self.bulk_memory_word)
+    }
 }
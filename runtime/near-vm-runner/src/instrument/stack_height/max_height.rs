@@ -1,4 +1,6 @@
 use super::{Error, ModuleCtx};
+#[cfg(feature = "protocol_feature_wasm_extensions")]
+use parity_wasm::elements::BulkInstruction;
 use parity_wasm::elements::{BlockType, Type};
 
 /// Control stack frame.
@@ -394,6 +396,26 @@ pub(crate) fn compute(func_idx: u32, module_ctx: &ModuleCtx<'_>) -> Result<u32,
                 stack.pop_values(1)?;
                 stack.push_values(1)?;
             }
+
+            #[cfg(feature = "protocol_feature_wasm_extensions")]
+            SignExt(_) => {
+                // Sign extension operators take one value and produce one result.
+                stack.pop_values(1)?;
+                stack.push_values(1)?;
+            }
+
+            #[cfg(feature = "protocol_feature_wasm_extensions")]
+            Bulk(BulkInstruction::MemoryInit(_))
+            | Bulk(BulkInstruction::MemoryCopy)
+            | Bulk(BulkInstruction::MemoryFill)
+            | Bulk(BulkInstruction::TableInit(_))
+            | Bulk(BulkInstruction::TableCopy) => {
+                // These instructions take the destination, the source (or the fill value) and
+                // the length, and produce no result.
+                stack.pop_values(3)?;
+            }
+            #[cfg(feature = "protocol_feature_wasm_extensions")]
+            Bulk(BulkInstruction::MemoryDrop(_)) | Bulk(BulkInstruction::TableDrop(_)) => {}
         }
         pc += 1;
     }
@@ -573,4 +595,31 @@ mod tests {
         let height = compute(0, &module_ctx).unwrap();
         assert_eq!(height, 3);
     }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_wasm_extensions")]
+    fn bulk_memory_and_sign_ext() {
+        let module = parse_wat(
+            r#"
+(module
+    (memory 1)
+    (func $main
+        i32.const 0
+        i32.const 1
+        i32.extend8_s
+        i32.const 2
+        memory.fill
+        i32.const 0
+        i32.const 1
+        i32.const 2
+        memory.copy
+    )
+)
+"#,
+        );
+
+        let module_ctx = ModuleCtx::new(&module);
+        let height = compute(0, &module_ctx).unwrap();
+        assert_eq!(height, 3);
+    }
 }
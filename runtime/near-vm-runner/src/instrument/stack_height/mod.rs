@@ -123,12 +123,43 @@ impl Context {
 ///
 /// Returns `Err` if module is invalid and can't be
 pub fn inject_limiter(
+    module: elements::Module,
+    stack_limit: u32,
+) -> Result<elements::Module, Error> {
+    let func_stack_costs = compute_stack_costs(&module)?;
+    inject_limiter_with_costs(module, stack_limit, func_stack_costs)
+}
+
+/// Same as [`inject_limiter`], but the stack costs of functions are computed
+/// from `costs_module` rather than from `module`.
+///
+/// This allows instrumenting `module` for profiling without changing the stack
+/// costs: `costs_module` must define the same functions as `module` does.
+///
+/// # Errors
+///
+/// Returns `Err` if either module is invalid or if they define a different
+/// number of functions.
+pub fn inject_limiter_with_costs_of(
+    module: elements::Module,
+    stack_limit: u32,
+    costs_module: &elements::Module,
+) -> Result<elements::Module, Error> {
+    if module.functions_space() != costs_module.functions_space() {
+        return Err(Error("Modules define different numbers of functions".into()));
+    }
+    let func_stack_costs = compute_stack_costs(costs_module)?;
+    inject_limiter_with_costs(module, stack_limit, func_stack_costs)
+}
+
+fn inject_limiter_with_costs(
     mut module: elements::Module,
     stack_limit: u32,
+    func_stack_costs: Vec<u32>,
 ) -> Result<elements::Module, Error> {
     let mut ctx = Context {
         stack_height_global_idx: generate_stack_height_global(&mut module),
-        func_stack_costs: compute_stack_costs(&module)?,
+        func_stack_costs,
         stack_limit,
     };
 
@@ -203,7 +234,7 @@ impl<'a> ModuleCtx<'a> {
 /// Calculate stack costs for all functions.
 ///
 /// Returns a vector with a stack cost for each function, including imports.
-fn compute_stack_costs(module: &elements::Module) -> Result<Vec<u32>, Error> {
+pub(crate) fn compute_stack_costs(module: &elements::Module) -> Result<Vec<u32>, Error> {
     let module_ctx = ModuleCtx::new(module);
 
     // TODO: optimize!
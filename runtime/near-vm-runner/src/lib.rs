@@ -1,5 +1,6 @@
 #![doc = include_str!("../README.md")]
 
+mod analysis;
 mod cache;
 mod errors;
 mod imports;
@@ -7,6 +8,7 @@ mod instrument;
 #[cfg(all(feature = "wasmer0_vm", target_arch = "x86_64"))]
 mod memory;
 pub mod prepare;
+mod profile;
 mod runner;
 #[cfg(test)]
 mod tests;
@@ -20,7 +22,15 @@ mod wasmtime_runner;
 
 pub use near_vm_logic::with_ext_cost_counter;
 
-pub use cache::{get_contract_cache_key, precompile_contract, MockCompiledContractCache};
+pub use analysis::{
+    analyze_contract, ContractAnalysis, MemoryLimits, PreparationCheck, TableLimits,
+};
+pub use cache::{
+    get_contract_cache_key, precompile_contract, FilesystemCompiledContractCache,
+    MockCompiledContractCache,
+};
+pub use errors::ContractPrecompilatonResult;
+pub use profile::extended_gas_profile_view;
 pub use runner::{run, VM};
 
 /// This is public for internal experimentation use only, and should otherwise be considered an
@@ -2,10 +2,18 @@
 //! wasm module before execution.
 
 use near_vm_errors::PrepareError;
-use near_vm_logic::VMConfig;
+use near_vm_logic::{VMConfig, WasmFeaturesVersion};
 use parity_wasm::builder;
 use parity_wasm::elements::{self, External, MemorySection};
+#[cfg(feature = "protocol_feature_wasm_extensions")]
+use parity_wasm::elements::{BulkInstruction, Instruction};
 
+/// WebAssembly features accepted by [`WasmFeaturesVersion::V0`], i.e. the MVP.
+///
+/// Note that this version of `wasmparser` accepts sign-extension operators
+/// regardless of the features, they are rejected when the module is parsed by
+/// `parity_wasm` instead.  `parity_wasm` only decodes them, as well as bulk
+/// memory operations, when built with `protocol_feature_wasm_extensions`.
 pub(crate) const WASM_FEATURES: wasmparser::WasmFeatures = wasmparser::WasmFeatures {
     reference_types: false,
     // wasmer singlepass compiler requires multi_value return values to be disabled.
@@ -21,17 +29,32 @@ pub(crate) const WASM_FEATURES: wasmparser::WasmFeatures = wasmparser::WasmFeatu
     memory64: false,
 };
 
+/// WebAssembly features accepted with the given version.
+///
+/// [`WasmFeaturesVersion::V1`] adds sign-extension operators and the subset of
+/// the bulk memory proposal made of `memory.copy` and `memory.fill`, the other
+/// bulk memory constructs are rejected by [`validate_contract`].  Multi-value
+/// remains disabled in all versions because the wasmer2 singlepass compiler
+/// does not support it.
+pub(crate) fn wasm_features(version: WasmFeaturesVersion) -> wasmparser::WasmFeatures {
+    match version {
+        WasmFeaturesVersion::V0 => WASM_FEATURES,
+        WasmFeaturesVersion::V1 => wasmparser::WasmFeatures { bulk_memory: true, ..WASM_FEATURES },
+    }
+}
+
 /// Decode and validate the provided WebAssembly code with the `wasmparser` crate.
 ///
 /// This function will return the number of functions defined globally in the provided WebAssembly
 /// module as well as the number of locals declared by all functions. If either counter overflows,
 /// `None` is returned in its place.
-fn wasmparser_decode(
+pub(crate) fn wasmparser_decode(
     code: &[u8],
+    features: wasmparser::WasmFeatures,
 ) -> Result<(Option<u64>, Option<u64>), wasmparser::BinaryReaderError> {
     use wasmparser::{ImportSectionEntryType, ValidPayload};
     let mut validator = wasmparser::Validator::new();
-    validator.wasm_features(WASM_FEATURES);
+    validator.wasm_features(features);
     let mut function_count = Some(0u64);
     let mut local_count = Some(0u64);
     for payload in wasmparser::Parser::new(0).parse_all(code) {
@@ -74,10 +97,23 @@ fn wasmparser_decode(
 }
 
 fn validate_contract(code: &[u8], config: &VMConfig) -> Result<(), PrepareError> {
-    let (function_count, local_count) = wasmparser_decode(code).map_err(|e| {
-        tracing::debug!(err=?e, "wasmparser failed decoding a contract");
-        PrepareError::Deserialization
-    })?;
+    let (function_count, local_count) =
+        wasmparser_decode(code, wasm_features(config.limit_config.wasm_features_version)).map_err(
+            |e| {
+                tracing::debug!(err=?e, "wasmparser failed decoding a contract");
+                PrepareError::Deserialization
+            },
+        )?;
+    if config.limit_config.wasm_features_version >= WasmFeaturesVersion::V1 {
+        let unsupported = uses_unsupported_bulk_memory(code).map_err(|e| {
+            tracing::debug!(err=?e, "wasmparser failed decoding a contract");
+            PrepareError::Deserialization
+        })?;
+        if unsupported {
+            tracing::debug!("contract uses bulk memory operations which are not supported");
+            return Err(PrepareError::Deserialization);
+        }
+    }
     // Verify the number of functions does not exceed the limit we imposed. Note that the ordering
     // of this check is important. In the past we first validated the entire module and only then
     // verified that the limit is not exceeded. While it would be more efficient to check for this
@@ -97,6 +133,52 @@ fn validate_contract(code: &[u8], config: &VMConfig) -> Result<(), PrepareError>
     Ok(())
 }
 
+/// Whether the module uses parts of the bulk memory proposal other than
+/// `memory.copy` and `memory.fill`: passive segments, the data count section or
+/// the instructions operating on them and on tables.
+///
+/// `parity_wasm` implements a draft of the proposal which encodes these
+/// differently from the final specification, so the instrumented module would
+/// not match the original one.
+fn uses_unsupported_bulk_memory(code: &[u8]) -> Result<bool, wasmparser::BinaryReaderError> {
+    use wasmparser::{DataKind, ElementKind, Operator, Payload};
+    for payload in wasmparser::Parser::new(0).parse_all(code) {
+        match payload? {
+            Payload::DataCountSection { .. } => return Ok(true),
+            Payload::DataSection(reader) => {
+                for data in reader {
+                    if let DataKind::Passive = data?.kind {
+                        return Ok(true);
+                    }
+                }
+            }
+            Payload::ElementSection(reader) => {
+                for element in reader {
+                    if !matches!(element?.kind, ElementKind::Active { .. }) {
+                        return Ok(true);
+                    }
+                }
+            }
+            Payload::CodeSectionEntry(body) => {
+                for operator in body.get_operators_reader()? {
+                    if matches!(
+                        operator?,
+                        Operator::MemoryInit { .. }
+                            | Operator::DataDrop { .. }
+                            | Operator::TableInit { .. }
+                            | Operator::ElemDrop { .. }
+                            | Operator::TableCopy { .. }
+                    ) {
+                        return Ok(true);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(false)
+}
+
 /// Loads the given module given in `original_code`, performs some checks on it and
 /// does some preprocessing.
 ///
@@ -120,12 +202,45 @@ pub fn prepare_contract(original_code: &[u8], config: &VMConfig) -> Result<Vec<u
             .scan_imports()?
             .standardize_mem()
             .ensure_no_internal_memory()?
-            .inject_gas_metering()?
+            .inject_gas_metering(false)?
             .inject_stack_height_metering()?
             .into_wasm_code(),
     }
 }
 
+/// Same as [`prepare_contract`], but the injected gas metering attributes the
+/// charged gas to individual functions, see
+/// [`crate::instrument::gas::inject_profiling_gas_counter`].
+///
+/// Contracts prepared this way charge exactly the same gas as the ones
+/// prepared by [`prepare_contract`], but they must be linked with the
+/// `profiled_gas` host function and are never cached.  Stack costs of their
+/// functions are computed as if they were metered as usual, so the extra
+/// function index arguments don't make them hit the stack limit any earlier.
+/// Contracts executed with the legacy stack limiter are prepared as usual,
+/// without a per function breakdown.
+pub(crate) fn prepare_contract_for_profiling(
+    original_code: &[u8],
+    config: &VMConfig,
+) -> Result<Vec<u8>, PrepareError> {
+    match config.limit_config.stack_limiter_version {
+        near_vm_logic::StackLimiterVersion::V0 => prepare_contract(original_code, config),
+        near_vm_logic::StackLimiterVersion::V1 => {
+            validate_contract(original_code, config)?;
+            let module = ContractModule::init(original_code, config)?
+                .scan_imports()?
+                .standardize_mem()
+                .ensure_no_internal_memory()?;
+            let metered = module.clone().inject_gas_metering(false)?;
+            module
+                .inject_gas_metering(true)?
+                .inject_stack_height_metering_as(&metered)?
+                .into_wasm_code()
+        }
+    }
+}
+
+#[derive(Clone)]
 struct ContractModule<'a> {
     module: elements::Module,
     config: &'a VMConfig,
@@ -133,10 +248,17 @@ struct ContractModule<'a> {
 
 impl<'a> ContractModule<'a> {
     fn init(original_code: &[u8], config: &'a VMConfig) -> Result<Self, PrepareError> {
-        let module = parity_wasm::deserialize_buffer(original_code).map_err(|e| {
-            tracing::debug!(err=?e, "parity_wasm failed decoding a contract");
-            PrepareError::Deserialization
-        })?;
+        let module: elements::Module =
+            parity_wasm::deserialize_buffer(original_code).map_err(|e| {
+                tracing::debug!(err=?e, "parity_wasm failed decoding a contract");
+                PrepareError::Deserialization
+            })?;
+        #[cfg(feature = "protocol_feature_wasm_extensions")]
+        let module = check_wasm_extensions(
+            original_code,
+            module,
+            config.limit_config.wasm_features_version,
+        )?;
         Ok(ContractModule { module, config })
     }
 
@@ -175,16 +297,21 @@ impl<'a> ContractModule<'a> {
         }
     }
 
-    fn inject_gas_metering(self) -> Result<Self, PrepareError> {
+    fn inject_gas_metering(self, profiling: bool) -> Result<Self, PrepareError> {
         let Self { module, config } = self;
         // Free config, no need for gas metering.
         if config.regular_op_cost == 0 {
             return Ok(Self { module, config });
         }
         let gas_rules = crate::instrument::rules::Set::new(1, Default::default())
-            .with_grow_cost(config.grow_mem_cost);
-        let module = crate::instrument::gas::inject_gas_counter(module, &gas_rules, "env")
-            .map_err(|_| PrepareError::GasInstrumentation)?;
+            .with_grow_cost(config.grow_mem_cost)
+            .with_bulk_memory_word_cost(config.bulk_memory_word_cost);
+        let module = if profiling {
+            crate::instrument::gas::inject_profiling_gas_counter(module, &gas_rules, "env")
+        } else {
+            crate::instrument::gas::inject_gas_counter(module, &gas_rules, "env")
+        }
+        .map_err(|_| PrepareError::GasInstrumentation)?;
         Ok(Self { module, config })
     }
 
@@ -198,6 +325,20 @@ impl<'a> ContractModule<'a> {
         Ok(Self { module, config })
     }
 
+    /// Same as [`Self::inject_stack_height_metering`], but the stack costs of
+    /// functions are computed from `metered`, which must define the same
+    /// functions.
+    fn inject_stack_height_metering_as(self, metered: &Self) -> Result<Self, PrepareError> {
+        let Self { module, config } = self;
+        let module = crate::instrument::stack_height::inject_limiter_with_costs_of(
+            module,
+            config.limit_config.max_stack_height,
+            &metered.module,
+        )
+        .map_err(|_| PrepareError::StackHeightInstrumentation)?;
+        Ok(Self { module, config })
+    }
+
     /// Scan an import section if any.
     ///
     /// This accomplishes two tasks:
@@ -244,10 +385,147 @@ impl<'a> ContractModule<'a> {
     }
 
     fn into_wasm_code(self) -> Result<Vec<u8>, PrepareError> {
+        #[cfg(feature = "protocol_feature_wasm_extensions")]
+        if self.config.limit_config.wasm_features_version >= WasmFeaturesVersion::V1 {
+            return serialize_with_bulk_memory(self.module)
+                .map_err(|_| PrepareError::Serialization);
+        }
         elements::serialize(self.module).map_err(|_| PrepareError::Serialization)
     }
 }
 
+/// Rejects the instructions `parity_wasm` decodes which are not enabled with
+/// the given version, and decodes the function bodies again where needed, see
+/// [`decode_function_bodies`].
+#[cfg(feature = "protocol_feature_wasm_extensions")]
+fn check_wasm_extensions(
+    original_code: &[u8],
+    mut module: elements::Module,
+    version: WasmFeaturesVersion,
+) -> Result<elements::Module, PrepareError> {
+    match version {
+        // Before these instructions were allowed, `parity_wasm` was built
+        // without support for them and failed to decode modules using them.
+        WasmFeaturesVersion::V0 => {
+            let bodies = module.code_section().map(|section| section.bodies()).unwrap_or(&[]);
+            if bodies.iter().any(|body| {
+                body.code().elements().iter().any(|instruction| {
+                    matches!(instruction, Instruction::SignExt(_) | Instruction::Bulk(_))
+                })
+            }) {
+                tracing::debug!("contract uses instructions which are not enabled");
+                return Err(PrepareError::Deserialization);
+            }
+        }
+        // Unsupported bulk memory operations have been rejected by
+        // `uses_unsupported_bulk_memory` already.
+        WasmFeaturesVersion::V1 => decode_function_bodies(original_code, &mut module)?,
+    }
+    Ok(module)
+}
+
+/// Encoding of `memory.copy` in the final bulk memory proposal: the prefix
+/// and the opcode followed by the reserved destination and source memory
+/// indices.
+#[cfg(feature = "protocol_feature_wasm_extensions")]
+const MEMORY_COPY: [u8; 4] = [0xfc, 0x0a, 0x00, 0x00];
+
+/// `parity_wasm` implements a draft of the bulk memory proposal in which
+/// `memory.copy` has a single reserved byte rather than two, so it misreads
+/// the function bodies of modules using it.  Replaces the function bodies of
+/// `module` with ones decoded from `original_code` instruction by instruction:
+/// `wasmparser` delimits the instructions, and every instruction but
+/// `memory.copy` is decoded by `parity_wasm` from its own bytes.
+#[cfg(feature = "protocol_feature_wasm_extensions")]
+fn decode_function_bodies(
+    original_code: &[u8],
+    module: &mut elements::Module,
+) -> Result<(), PrepareError> {
+    fn deserialization_error(err: impl std::fmt::Debug) -> PrepareError {
+        tracing::debug!(?err, "failed decoding the function bodies of a contract");
+        PrepareError::Deserialization
+    }
+    let mut bodies = vec![];
+    for payload in wasmparser::Parser::new(0).parse_all(original_code) {
+        let body = match payload.map_err(deserialization_error)? {
+            wasmparser::Payload::CodeSectionEntry(body) => body,
+            _ => continue,
+        };
+        let mut locals = vec![];
+        let mut locals_reader = body.get_locals_reader().map_err(deserialization_error)?;
+        for _ in 0..locals_reader.get_count() {
+            let (count, ty) = locals_reader.read().map_err(deserialization_error)?;
+            let ty = match ty {
+                wasmparser::Type::I32 => elements::ValueType::I32,
+                wasmparser::Type::I64 => elements::ValueType::I64,
+                wasmparser::Type::F32 => elements::ValueType::F32,
+                wasmparser::Type::F64 => elements::ValueType::F64,
+                ty => return Err(deserialization_error(ty)),
+            };
+            locals.push(elements::Local::new(count, ty));
+        }
+        let mut instructions = vec![];
+        let mut operators_reader = body.get_operators_reader().map_err(deserialization_error)?;
+        while !operators_reader.eof() {
+            let (operator, start) =
+                operators_reader.read_with_offset().map_err(deserialization_error)?;
+            let end = operators_reader.original_position();
+            instructions.push(match operator {
+                wasmparser::Operator::MemoryCopy { .. } => {
+                    Instruction::Bulk(BulkInstruction::MemoryCopy)
+                }
+                _ => elements::deserialize_buffer(&original_code[start..end])
+                    .map_err(deserialization_error)?,
+            });
+        }
+        bodies.push(elements::FuncBody::new(locals, elements::Instructions::new(instructions)));
+    }
+    match module.code_section_mut() {
+        Some(section) if section.bodies().len() == bodies.len() => *section.bodies_mut() = bodies,
+        None if bodies.is_empty() => {}
+        _ => return Err(deserialization_error("function bodies don't match the code section")),
+    }
+    Ok(())
+}
+
+/// Serializes `module`, encoding `memory.copy` as in the final bulk memory
+/// proposal rather than in the draft implemented by `parity_wasm`.  The code
+/// section is encoded here and passed to `parity_wasm` as an unparsed section.
+#[cfg(feature = "protocol_feature_wasm_extensions")]
+fn serialize_with_bulk_memory(mut module: elements::Module) -> Result<Vec<u8>, elements::Error> {
+    for section in module.sections_mut() {
+        if let elements::Section::Code(code_section) = section {
+            let bodies = std::mem::take(code_section.bodies_mut());
+            let mut contents = elements::serialize(elements::VarUint32::from(bodies.len()))?;
+            for body in bodies {
+                let mut body_bytes =
+                    elements::serialize(elements::VarUint32::from(body.locals().len()))?;
+                for local in body.locals() {
+                    body_bytes.extend(elements::serialize(*local)?);
+                }
+                for instruction in body.code().elements() {
+                    match instruction {
+                        Instruction::Bulk(BulkInstruction::MemoryCopy) => {
+                            body_bytes.extend(MEMORY_COPY)
+                        }
+                        instruction => body_bytes.extend(elements::serialize(instruction.clone())?),
+                    }
+                }
+                contents.extend(elements::serialize(elements::VarUint32::from(body_bytes.len()))?);
+                contents.extend(body_bytes);
+            }
+            let mut payload = elements::serialize(elements::VarUint32::from(contents.len()))?;
+            payload.extend(contents);
+            *section = elements::Section::Unparsed { id: CODE_SECTION_ID, payload };
+        }
+    }
+    elements::serialize(module)
+}
+
+/// Id of the code section in the binary format.
+#[cfg(feature = "protocol_feature_wasm_extensions")]
+const CODE_SECTION_ID: u8 = 10;
+
 /// Legacy validation for old protocol versions.
 mod pwasm_12 {
     use near_vm_errors::PrepareError;
@@ -493,6 +771,41 @@ mod tests {
         */
     }
 
+    #[test]
+    #[cfg(feature = "protocol_feature_wasm_extensions")]
+    fn memory_copy_encoding() {
+        let wasm = wat::parse_str(
+            r#"(module
+              (memory 1)
+              (func (param i32 i32 i32) (local i64)
+                local.get 0
+                local.get 1
+                local.get 2
+                memory.copy
+                unreachable))"#,
+        )
+        .unwrap();
+        let mut config = VMConfig::test();
+        config.limit_config.wasm_features_version = WasmFeaturesVersion::V1;
+        let code = prepare_contract(&wasm, &config).unwrap();
+        wasmparser::Validator::new()
+            .wasm_features(wasm_features(WasmFeaturesVersion::V1))
+            .validate_all(&code)
+            .unwrap();
+        let module = ContractModule::init(&code, &config).unwrap().module;
+        let memory_copy = Instruction::Bulk(BulkInstruction::MemoryCopy);
+        let body = module
+            .code_section()
+            .unwrap()
+            .bodies()
+            .iter()
+            .map(|body| body.code().elements())
+            .find(|body| body.contains(&memory_copy))
+            .unwrap();
+        let position = body.iter().position(|i| *i == memory_copy).unwrap();
+        assert_eq!(body[position + 1..], [Instruction::Unreachable, Instruction::End]);
+    }
+
     #[test]
     fn preparation_generates_valid_contract() {
         bolero::check!().for_each(|input: &[u8]| {
@@ -0,0 +1,113 @@
+//! Presentation of extended gas profiles collected by contracts prepared with
+//! [`crate::prepare::prepare_contract_for_profiling`].
+
+use near_primitives::contract::ContractCode;
+use near_primitives::profile::ExtendedProfileData;
+use near_primitives::views::{ExtendedGasProfileView, HostFunctionGasView, WasmFunctionGasView};
+use parity_wasm::elements::{self, Internal};
+use std::collections::HashMap;
+
+/// Converts the profile into a view, resolving indices of wasm functions into
+/// their export names, or names from the custom `name` section for internal
+/// functions.
+pub fn extended_gas_profile_view(
+    code: &ContractCode,
+    profile: &ExtendedProfileData,
+) -> ExtendedGasProfileView {
+    let mut function_names = function_names(code.code());
+    let host_functions = profile
+        .host_functions()
+        .map(|(name, stats)| HostFunctionGasView {
+            name: name.to_string(),
+            calls: stats.calls,
+            gas_burnt: stats.gas_burnt,
+            bytes_read: stats.bytes_read,
+            bytes_written: stats.bytes_written,
+        })
+        .collect();
+    let wasm_functions = profile
+        .wasm_functions()
+        .map(|(function_index, gas)| {
+            let (name, exported) = match function_names.remove(&function_index) {
+                Some((name, exported)) => (Some(name), exported),
+                None => (None, false),
+            };
+            WasmFunctionGasView { function_index, name, exported, gas }
+        })
+        .collect();
+    ExtendedGasProfileView {
+        host_functions,
+        wasm_functions,
+        memory_grow_gas: profile.memory_grow_gas(),
+    }
+}
+
+/// Returns the name of each named function and whether it is exported.
+fn function_names(code: &[u8]) -> HashMap<u32, (String, bool)> {
+    let module = match parity_wasm::deserialize_buffer::<elements::Module>(code) {
+        Ok(module) => module,
+        // No gas can have been attributed to functions of a module which does
+        // not even deserialize.
+        Err(_) => return HashMap::new(),
+    };
+    // Failure to parse the debug names is not fatal, the contract still works.
+    let module = module.parse_names().unwrap_or_else(|(_, module)| module);
+    let mut names = HashMap::new();
+    if let Some(functions) = module.names_section().and_then(|section| section.functions()) {
+        for (index, name) in functions.names() {
+            names.insert(index, (name.clone(), false));
+        }
+    }
+    if let Some(exports) = module.export_section() {
+        for export in exports.entries() {
+            if let Internal::Function(index) = export.internal() {
+                names.insert(*index, (export.field().to_string(), true));
+            }
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extended_gas_profile_view() {
+        let code = wat::parse_str(
+            r#"
+            (module
+              (func $helper)
+              (func (export "main") call $helper)
+              (func $internal))
+            "#,
+        )
+        .unwrap();
+        let mut profile = ExtendedProfileData::default();
+        profile.record_host_call("log_utf8", 10, 5, 0);
+        profile.add_wasm_gas(0, 1);
+        profile.add_wasm_gas(1, 2);
+        profile.add_wasm_gas(2, 3);
+
+        let view = extended_gas_profile_view(&ContractCode::new(code, None), &profile);
+        assert_eq!(
+            view.host_functions,
+            [HostFunctionGasView {
+                name: "log_utf8".to_string(),
+                calls: 1,
+                gas_burnt: 10,
+                bytes_read: 5,
+                bytes_written: 0
+            }]
+        );
+        let functions: Vec<_> = view
+            .wasm_functions
+            .iter()
+            .map(|function| (function.function_index, function.name.as_deref(), function.exported))
+            .collect();
+        assert_eq!(
+            functions,
+            [(0, Some("helper"), false), (1, Some("main"), true), (2, Some("internal"), false)]
+        );
+    }
+}
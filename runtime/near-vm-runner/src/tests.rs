@@ -1,6 +1,7 @@
 mod cache;
 mod compile_errors;
 mod fuzzers;
+mod gas_profile;
 mod rs_contract;
 mod runtime_errors;
 pub(crate) mod test_builder;
@@ -0,0 +1,81 @@
+use near_primitives::contract::ContractCode;
+use near_primitives::profile::ExtendedProfileData;
+use near_primitives::runtime::fees::RuntimeFeesConfig;
+use near_vm_logic::mocks::mock_external::MockedExternal;
+use near_vm_logic::{VMConfig, VMOutcome};
+
+use crate::tests::{create_context, with_vm_variants, LATEST_PROTOCOL_VERSION};
+use crate::vm_kind::VMKind;
+
+/// `recurse` calls `$recurse` as deep as the stack limit allows: 260 times with 63 values on the
+/// stack each, so a single additional value in its stack cost would make it overflow.
+const CONTRACT: &str = r#"
+(module
+  (memory 1)
+  (global $depth (mut i32) (i32.const 0))
+  (func $sum (param $n i32) (result i32) (local $acc i32)
+    (block $done
+      (loop $loop
+        (br_if $done (i32.eqz (local.get $n)))
+        (local.set $acc (i32.add (local.get $acc) (local.get $n)))
+        (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+        (br $loop)))
+    (local.get $acc))
+  (func (export "main")
+    (drop (memory.grow (i32.const 1)))
+    (drop (call $sum (i32.const 100))))
+  (func $deep_enough (result i32)
+    (global.set $depth (i32.sub (global.get $depth) (i32.const 1)))
+    (i32.eqz (global.get $depth)))
+  (func $recurse
+    (local i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64)
+    (local i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64)
+    (local i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64)
+    (local i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64)
+    (block
+      (br_if 0 (call $deep_enough))
+      (call $recurse)))
+  (func (export "recurse")
+    (global.set $depth (i32.const 260))
+    (call $recurse)))
+"#;
+
+fn run(vm_kind: VMKind, method: &str, profiling: bool) -> (VMOutcome, Option<ExtendedProfileData>) {
+    let code = ContractCode::new(wat::parse_str(CONTRACT).unwrap(), None);
+    let mut fake_external = MockedExternal::new();
+    fake_external.extended_profile = profiling.then(ExtendedProfileData::default);
+    let runtime = vm_kind.runtime(VMConfig::test()).expect("runtime has not been compiled");
+    let outcome = runtime
+        .run(
+            &code,
+            method,
+            &mut fake_external,
+            create_context(vec![]),
+            &RuntimeFeesConfig::test(),
+            &[],
+            LATEST_PROTOCOL_VERSION,
+            None,
+        )
+        .expect("execution failed");
+    (outcome, fake_external.extended_profile)
+}
+
+#[test]
+fn test_profiling_charges_same_gas() {
+    with_vm_variants(|vm_kind: VMKind| {
+        for method in ["main", "recurse"] {
+            let (outcome, _) = run(vm_kind, method, false);
+            let (profiled_outcome, profile) = run(vm_kind, method, true);
+            assert_eq!(outcome.aborted, None, "{vm_kind:?} {method}");
+            assert_eq!(profiled_outcome.aborted, None, "{vm_kind:?} {method}");
+            assert_eq!(outcome.burnt_gas, profiled_outcome.burnt_gas, "{vm_kind:?} {method}");
+            assert_eq!(outcome.used_gas, profiled_outcome.used_gas, "{vm_kind:?} {method}");
+            assert!(profile.unwrap().wasm_functions().count() > 0, "{vm_kind:?} {method}");
+        }
+        let (_, profile) = run(vm_kind, "main", true);
+        let profile = profile.unwrap();
+        assert!(profile.memory_grow_gas() > 0);
+        let functions: Vec<_> = profile.wasm_functions().map(|(index, _)| index).collect();
+        assert_eq!(functions, [0, 1]);
+    });
+}
@@ -1,7 +1,7 @@
 use super::test_builder::test_builder;
 use crate::prepare::prepare_contract;
 use expect_test::expect;
-use near_vm_logic::VMConfig;
+use near_vm_logic::{VMConfig, WasmFeaturesVersion};
 
 static SIMD: &str = r#"
 (module
@@ -33,6 +33,7 @@ static REFERENCE_TYPES: &str = r#"
 
 static BULK_MEMORY: &str = r#"
 (module
+  (memory 1)
   (func $entry (result i32) i32.const 0)
   (func (export "memory.copy") (param i32 i32 i32)
     local.get 0
@@ -42,6 +43,15 @@ static BULK_MEMORY: &str = r#"
 )
 "#;
 
+static SIGN_EXT: &str = r#"
+(module
+  (func $entry (result i32) i32.const 0)
+  (func (export "i32.extend8_s") (param i32) (result i32)
+    local.get 0
+    i32.extend8_s)
+)
+"#;
+
 static MULTI_VALUE: &str = r#"
 (module
   (func $entry (result i32) i32.const 0)
@@ -109,11 +119,123 @@ fn ensure_fails_verification() {
     }
 }
 
+/// Features enabled by [`WasmFeaturesVersion::V1`].
+static WASM_EXTENSIONS: &[(&str, &str)] = &[("bulk_memory", BULK_MEMORY), ("sign_ext", SIGN_EXT)];
+
 #[test]
 fn ensure_fails_execution() {
-    for (_feature_name, wat) in EXPECTED_UNSUPPORTED {
+    for (feature_name, wat) in EXPECTED_UNSUPPORTED {
+        if cfg!(feature = "protocol_feature_wasm_extensions")
+            && WASM_EXTENSIONS.iter().any(|(name, _)| name == feature_name)
+        {
+            continue;
+        }
         test_builder().wat(wat).opaque_error().opaque_outcome().expect(expect![[r#"
             Err: ...
         "#]]);
     }
 }
+
+#[test]
+#[cfg(feature = "protocol_feature_wasm_extensions")]
+fn wasm_extensions_verification() {
+    for (feature_name, wat) in WASM_EXTENSIONS {
+        let wasm = wat::parse_str(wat).expect("parsing test wat should succeed");
+        let mut config = VMConfig::test();
+        if prepare_contract(&wasm, &config).is_ok() {
+            panic!("wasm containing use of {} feature did not fail to prepare", feature_name);
+        }
+        config.limit_config.wasm_features_version = WasmFeaturesVersion::V1;
+        if let Err(err) = prepare_contract(&wasm, &config) {
+            panic!("wasm containing use of {} feature failed to prepare: {}", feature_name, err);
+        }
+    }
+}
+
+static MEMORY_INIT: &str = r#"
+(module
+  (memory 1)
+  (data "abc")
+  (func $entry (result i32) i32.const 0)
+  (func (export "memory.init") (param i32)
+    local.get 0
+    i32.const 0
+    i32.const 3
+    memory.init 0)
+)
+"#;
+
+static PASSIVE_DATA: &str = r#"
+(module
+  (memory 1)
+  (data "abc")
+  (func $entry (result i32) i32.const 0)
+)
+"#;
+
+static TABLE_COPY: &str = r#"
+(module
+  (table 2 funcref)
+  (func $entry (result i32) i32.const 0)
+  (func (export "table.copy")
+    i32.const 0
+    i32.const 1
+    i32.const 1
+    table.copy)
+)
+"#;
+
+/// Features [`WasmFeaturesVersion::V1`] deliberately leaves out.
+static WASM_EXTENSIONS_UNSUPPORTED: &[(&str, &str)] = &[
+    ("multi_value", MULTI_VALUE),
+    ("memory.init", MEMORY_INIT),
+    ("passive data", PASSIVE_DATA),
+    ("table.copy", TABLE_COPY),
+];
+
+#[test]
+fn wasm_extensions_unsupported_verification() {
+    for (feature_name, wat) in WASM_EXTENSIONS_UNSUPPORTED {
+        let wasm = wat::parse_str(wat).expect("parsing test wat should succeed");
+        let mut config = VMConfig::test();
+        config.limit_config.wasm_features_version = WasmFeaturesVersion::V1;
+        if prepare_contract(&wasm, &config).is_ok() {
+            panic!("wasm containing use of {} did not fail to prepare", feature_name);
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "protocol_feature_wasm_extensions")]
+fn wasm_extensions_execution() {
+    use near_primitives::version::ProtocolFeature;
+
+    test_builder()
+        .wat(
+            r#"
+(module
+  (memory 1)
+  (func (export "main")
+    i32.const 0
+    i32.const 255
+    i32.extend8_s
+    i32.const 1000
+    memory.fill
+    i32.const 1000
+    i32.const 0
+    i32.const 1000
+    memory.copy)
+)"#,
+        )
+        .skip_wasmer0()
+        .protocol_features(&[ProtocolFeature::WasmExtensions])
+        .expects(&[
+            expect![[r#"
+                VMOutcome: balance 4 storage_usage 12 return data None burnt gas 49101213 used gas 49101213
+                Err: PrepareError: Error happened while deserializing the module.
+            "#]],
+            expect![[r#"
+                VMOutcome: balance 4 storage_usage 12 return data None burnt gas 262195017 used gas 262195017
+            "#]],
+        ]);
+}
@@ -1,7 +1,7 @@
 use crate::errors::ContractPrecompilatonResult;
 use crate::imports::wasmer2::Wasmer2Imports;
 use crate::internal::VMKind;
-use crate::prepare::{self, WASM_FEATURES};
+use crate::prepare;
 use crate::runner::VMResult;
 use crate::{get_contract_cache_key, imports};
 use memoffset::offset_of;
@@ -29,18 +29,21 @@ use wasmer_vm::{
     Artifact, Instantiatable, LinearMemory, LinearTable, Memory, MemoryStyle, TrapCode, VMMemory,
 };
 
-const WASMER_FEATURES: Features = Features {
-    threads: WASM_FEATURES.threads,
-    reference_types: WASM_FEATURES.reference_types,
-    simd: WASM_FEATURES.simd,
-    bulk_memory: WASM_FEATURES.bulk_memory,
-    multi_value: WASM_FEATURES.multi_value,
-    tail_call: WASM_FEATURES.tail_call,
-    module_linking: WASM_FEATURES.module_linking,
-    multi_memory: WASM_FEATURES.multi_memory,
-    memory64: WASM_FEATURES.memory64,
-    exceptions: WASM_FEATURES.exceptions,
-};
+fn wasmer_features(config: &VMConfig) -> Features {
+    let features = prepare::wasm_features(config.limit_config.wasm_features_version);
+    Features {
+        threads: features.threads,
+        reference_types: features.reference_types,
+        simd: features.simd,
+        bulk_memory: features.bulk_memory,
+        multi_value: features.multi_value,
+        tail_call: features.tail_call,
+        module_linking: features.module_linking,
+        multi_memory: features.multi_memory,
+        memory64: features.memory64,
+        exceptions: features.exceptions,
+    }
+}
 
 #[derive(Clone)]
 pub struct Wasmer2Memory(Arc<LinearMemory>);
@@ -256,10 +259,8 @@ impl Wasmer2VM {
         let compiler = Singlepass::new();
         // We only support universal engine at the moment.
         assert_eq!(WASMER2_CONFIG.engine, WasmerEngine::Universal);
-        Self {
-            config,
-            engine: Universal::new(compiler).target(target).features(WASMER_FEATURES).engine(),
-        }
+        let features = wasmer_features(&config);
+        Self { config, engine: Universal::new(compiler).target(target).features(features).engine() }
     }
 
     pub(crate) fn new(config: VMConfig) -> Self {
@@ -288,14 +289,20 @@ impl Wasmer2VM {
         let _span = tracing::debug_span!(target: "vm", "Wasmer2VM::compile_uncached").entered();
         let prepared_code = prepare::prepare_contract(code.code(), &self.config)
             .map_err(CompilationError::PrepareError)?;
+        self.compile_prepared(&prepared_code)
+    }
 
+    fn compile_prepared(
+        &self,
+        prepared_code: &[u8],
+    ) -> Result<UniversalExecutable, CompilationError> {
         debug_assert!(
-            matches!(self.engine.validate(&prepared_code), Ok(_)),
+            matches!(self.engine.validate(prepared_code), Ok(_)),
             "wasmer failed to validate the prepared code"
         );
         let executable = self
             .engine
-            .compile_universal(&prepared_code, &self)
+            .compile_universal(prepared_code, &self)
             .map_err(|err| {
                 tracing::error!(?err, "wasmer failed to compile the prepared code (this is defense-in-depth, the error was recovered from but should be reported to pagoda)");
                 CompilationError::WasmerCompileError { msg: err.to_string() }
@@ -327,6 +334,28 @@ impl Wasmer2VM {
         Ok(executable_or_error)
     }
 
+    /// Compiles and loads the contract instrumented for extended gas profiles.
+    ///
+    /// The result is never cached, as it differs from what the protocol executes.
+    fn compile_and_load_for_profiling(
+        &self,
+        code: &ContractCode,
+    ) -> VMResult<Result<VMArtifact, CompilationError>> {
+        let _span = tracing::debug_span!(target: "vm", "Wasmer2VM::compile_and_load_for_profiling")
+            .entered();
+        let executable = prepare::prepare_contract_for_profiling(code.code(), &self.config)
+            .map_err(CompilationError::PrepareError)
+            .and_then(|prepared_code| self.compile_prepared(&prepared_code));
+        Ok(match executable {
+            Ok(executable) => Ok(self
+                .engine
+                .load_universal_executable(&executable)
+                .map(Arc::new)
+                .map_err(|err| VMRunnerError::LoadingError(err.to_string()))?),
+            Err(err) => Err(err),
+        })
+    }
+
     fn compile_and_load(
         &self,
         code: &ContractCode,
@@ -625,7 +654,11 @@ impl crate::runner::VM for Wasmer2VM {
             return Ok(VMOutcome::abort(logic, e));
         }
 
-        let artifact = self.compile_and_load(code, cache)?;
+        let artifact = if logic.is_profiling() {
+            self.compile_and_load_for_profiling(code)?
+        } else {
+            self.compile_and_load(code, cache)?
+        };
         let artifact = match artifact {
             Ok(it) => it,
             Err(err) => {
@@ -227,6 +227,19 @@ fn run_method(
     Ok(Ok(()))
 }
 
+fn compile_prepared(prepared_code: &[u8]) -> Result<wasmer_runtime::Module, CompilationError> {
+    wasmer_runtime::compile(prepared_code).map_err(|err| match err {
+        wasmer_runtime::error::CompileError::ValidationError { .. } => {
+            CompilationError::WasmerCompileError { msg: err.to_string() }
+        }
+        // NOTE: Despite the `InternalError` name, this failure occurs if
+        // the input `code` is invalid wasm.
+        wasmer_runtime::error::CompileError::InternalError { .. } => {
+            CompilationError::WasmerCompileError { msg: err.to_string() }
+        }
+    })
+}
+
 pub(crate) fn wasmer0_vm_hash() -> u64 {
     // TODO: take into account compiler and engine used to compile the contract.
     42
@@ -248,16 +261,21 @@ impl Wasmer0VM {
         let _span = tracing::debug_span!(target: "vm", "Wasmer0VM::compile_uncached").entered();
         let prepared_code = prepare::prepare_contract(code.code(), &self.config)
             .map_err(CompilationError::PrepareError)?;
-        wasmer_runtime::compile(&prepared_code).map_err(|err| match err {
-            wasmer_runtime::error::CompileError::ValidationError { .. } => {
-                CompilationError::WasmerCompileError { msg: err.to_string() }
-            }
-            // NOTE: Despite the `InternalError` name, this failure occurs if
-            // the input `code` is invalid wasm.
-            wasmer_runtime::error::CompileError::InternalError { .. } => {
-                CompilationError::WasmerCompileError { msg: err.to_string() }
-            }
-        })
+        compile_prepared(&prepared_code)
+    }
+
+    /// Compiles the contract instrumented for extended gas profiles.
+    ///
+    /// The result is never cached, as it differs from what the protocol executes.
+    fn compile_for_profiling(
+        &self,
+        code: &ContractCode,
+    ) -> Result<wasmer_runtime::Module, CompilationError> {
+        let _span =
+            tracing::debug_span!(target: "vm", "Wasmer0VM::compile_for_profiling").entered();
+        let prepared_code = prepare::prepare_contract_for_profiling(code.code(), &self.config)
+            .map_err(CompilationError::PrepareError)?;
+        compile_prepared(&prepared_code)
     }
 
     pub(crate) fn compile_and_cache(
@@ -407,7 +425,11 @@ impl crate::runner::VM for Wasmer0VM {
         }
 
         // TODO: consider using get_module() here, once we'll go via deployment path.
-        let module = self.compile_and_load(code, cache)?;
+        let module = if logic.is_profiling() {
+            self.compile_for_profiling(code)
+        } else {
+            self.compile_and_load(code, cache)?
+        };
         let module = match module {
             Ok(x) => x,
             // Note on backwards-compatibility: This error used to be an error
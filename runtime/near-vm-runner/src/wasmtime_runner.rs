@@ -1,5 +1,4 @@
 use crate::errors::{ContractPrecompilatonResult, IntoVMError};
-use crate::prepare::WASM_FEATURES;
 use crate::{imports, prepare};
 use near_primitives::config::VMConfig;
 use near_primitives::contract::ContractCode;
@@ -129,17 +128,18 @@ pub fn get_engine(config: &mut wasmtime::Config) -> Engine {
     Engine::new(config.strategy(wasmtime::Strategy::Lightbeam).unwrap()).unwrap()
 }
 
-pub(super) fn default_config() -> wasmtime::Config {
+pub(super) fn default_config(vm_config: &VMConfig) -> wasmtime::Config {
+    let features = prepare::wasm_features(vm_config.limit_config.wasm_features_version);
     let mut config = wasmtime::Config::default();
     config.max_wasm_stack(1024 * 1024 * 1024); // wasm stack metering is implemented by instrumentation, we don't want wasmtime to trap before that
-    config.wasm_threads(WASM_FEATURES.threads);
-    config.wasm_reference_types(WASM_FEATURES.reference_types);
-    config.wasm_simd(WASM_FEATURES.simd);
-    config.wasm_bulk_memory(WASM_FEATURES.bulk_memory);
-    config.wasm_multi_value(WASM_FEATURES.multi_value);
-    config.wasm_multi_memory(WASM_FEATURES.multi_memory);
+    config.wasm_threads(features.threads);
+    config.wasm_reference_types(features.reference_types);
+    config.wasm_simd(features.simd);
+    config.wasm_bulk_memory(features.bulk_memory);
+    config.wasm_multi_value(features.multi_value);
+    config.wasm_multi_memory(features.multi_memory);
     assert_eq!(
-        WASM_FEATURES.module_linking, false,
+        features.module_linking, false,
         "wasmtime currently does not support the module-linking feature"
     );
     config
@@ -172,7 +172,7 @@ impl crate::runner::VM for WasmtimeVM {
         current_protocol_version: ProtocolVersion,
         _cache: Option<&dyn CompiledContractCache>,
     ) -> Result<VMOutcome, VMRunnerError> {
-        let mut config = default_config();
+        let mut config = default_config(&self.config);
         let engine = get_engine(&mut config);
         let mut store = Store::new(&engine, ());
         let mut memory = WasmtimeMemory::new(
@@ -201,7 +201,13 @@ impl crate::runner::VM for WasmtimeVM {
             return Ok(VMOutcome::abort(logic, e));
         }
 
-        let prepared_code = match prepare::prepare_contract(code.code(), &self.config) {
+        let profiling = logic.is_profiling();
+        let prepared_code = if profiling {
+            prepare::prepare_contract_for_profiling(code.code(), &self.config)
+        } else {
+            prepare::prepare_contract(code.code(), &self.config)
+        };
+        let prepared_code = match prepared_code {
             Ok(code) => code,
             Err(err) => return Ok(VMOutcome::abort(logic, FunctionCallError::from(err))),
         };
@@ -219,7 +225,13 @@ impl crate::runner::VM for WasmtimeVM {
         // Unfortunately, due to the Wasmtime implementation we have to do tricks with the
         // lifetimes of the logic instance and pass raw pointers here.
         let raw_logic = &mut logic as *mut _ as *mut c_void;
-        imports::wasmtime::link(&mut linker, memory_copy, raw_logic, current_protocol_version);
+        imports::wasmtime::link(
+            &mut linker,
+            memory_copy,
+            raw_logic,
+            current_protocol_version,
+            profiling,
+        );
         match module.get_export(method_name) {
             Some(export) => match export {
                 Func(func_type) => {
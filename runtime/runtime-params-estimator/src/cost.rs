@@ -224,6 +224,14 @@ pub enum Cost {
     /// attempt to cause slow loads and stores. The total time spent in the
     /// runtime is divided by the number of executed instructions.
     WasmInstruction,
+    /// Estimates `wasm_config.bulk_memory_word_cost`, the number of regular
+    /// operations charged per 8-byte word touched by a bulk memory instruction
+    /// such as `memory.fill` or `memory.copy`.
+    ///
+    /// Estimation: Run contracts that fill and copy memory regions of
+    /// increasing length. The per-word time is taken from a least-squares fit
+    /// and converted to regular operations by the runtime config generator.
+    WasmBulkMemoryWord,
 
     // # Reading and writing memory
     // The hosting runtime sometimes copies data between in and out of WASM
@@ -613,6 +621,74 @@ pub enum Cost {
     AltBn128PairingCheckElement,
     AltBn128G1SumBase,
     AltBn128G1SumElement,
+    /// Estimates `bls12381_p1_sum_base` and `bls12381_p1_sum_element`, the
+    /// costs charged per call and per summed point of the G1 sum host function.
+    ///
+    /// Estimation: Like for the `alt_bn128` costs, call the host function in a
+    /// loop on inputs of 1 and 10 elements. The base cost is the per call cost
+    /// of the 1 element input, the element cost is the per element cost of the
+    /// 10 elements input. This overcharges the base by one element, which is
+    /// fine given the element cost is low compared to the base.
+    ///
+    /// The same approach is used for all other BLS12-381 costs.
+    Bls12381P1SumBase,
+    Bls12381P1SumElement,
+    Bls12381P2SumBase,
+    Bls12381P2SumElement,
+    Bls12381G1MultiexpBase,
+    Bls12381G1MultiexpElement,
+    Bls12381G2MultiexpBase,
+    Bls12381G2MultiexpElement,
+    Bls12381MapFpToG1Base,
+    Bls12381MapFpToG1Element,
+    Bls12381MapFp2ToG2Base,
+    Bls12381MapFp2ToG2Element,
+    Bls12381PairingBase,
+    Bls12381PairingElement,
+    /// Estimates `p256_verify_base`, which covers the base cost of the host
+    /// function `p256_verify` to verify a P-256 ECDSA signature.
+    ///
+    /// Estimation: Use a fixed signature embedded in the test contract and
+    /// verify it `N` times in a loop and divide by `N`, like for
+    /// `ed25519_verify_base`.
+    P256VerifyBase,
+    /// Estimates `p256_verify_byte`, the cost charged per message byte in calls
+    /// to the `p256_verify` host function, which hashes the message with
+    /// SHA-256.
+    ///
+    /// Estimation: Verify a signature for a large message many times, subtract
+    /// the cost estimated for the base and divide the remainder by the total
+    /// bytes the message, like for `ed25519_verify_byte`.
+    P256VerifyByte,
+    /// Estimates `yield_create_base`, the base cost of the host function
+    /// `promise_yield_create`, which yields a promise waiting to be resumed.
+    /// It doesn't include the fees of the created receipts, which are charged
+    /// separately.
+    ///
+    /// Estimation: Yield a promise calling back a method with a 10 bytes name
+    /// `N` times in a loop and divide by `N`.
+    YieldCreateBase,
+    /// Estimates `yield_create_byte`, the cost charged per byte of the method
+    /// name and arguments of the callback in calls to `promise_yield_create`.
+    ///
+    /// Estimation: Yield promises with 10kib arguments many times, subtract
+    /// the cost estimated for the base and divide the remainder by the total
+    /// bytes of method names and arguments.
+    YieldCreateByte,
+    /// Estimates `yield_resume_base`, the base cost of the host function
+    /// `promise_yield_resume`, which looks up the yielded promise in the state.
+    ///
+    /// Estimation: In a setup function call, yield `N` promises and store
+    /// their data ids. In the measured function call of a later block, resume
+    /// all of them with an empty payload and divide by `N`.
+    YieldResumeBase,
+    /// Estimates `yield_resume_byte`, the cost charged per byte of the payload
+    /// in calls to `promise_yield_resume`.
+    ///
+    /// Estimation: Like `YieldResumeBase` but with the largest allowed
+    /// payload, subtract the cost estimated for the base and divide the
+    /// remainder by the total bytes of payload.
+    YieldResumeByte,
 
     // Costs used only in estimator
     //
@@ -20,6 +20,9 @@ pub fn costs_to_runtime_config(cost_table: &CostTable) -> anyhow::Result<Runtime
     let regular_op_cost = cost_table
         .get(Cost::WasmInstruction)
         .with_context(|| format!("undefined cost: {}", Cost::WasmInstruction))?;
+    let bulk_memory_word_cost = cost_table
+        .get(Cost::WasmBulkMemoryWord)
+        .with_context(|| format!("undefined cost: {}", Cost::WasmBulkMemoryWord))?;
 
     // Take latest VM limit config, because estimation doesn't affect it.
     // Note that if you run estimation against stable version, it doesn't catch updates of nightly
@@ -34,6 +37,9 @@ pub fn costs_to_runtime_config(cost_table: &CostTable) -> anyhow::Result<Runtime
             ext_costs: ext_costs_config(cost_table)?,
             grow_mem_cost: 1,
             regular_op_cost: u32::try_from(regular_op_cost).unwrap(),
+            // Bulk memory words are charged in units of regular operations.
+            bulk_memory_word_cost: u32::try_from(div_ceil(bulk_memory_word_cost, regular_op_cost))
+                .unwrap(),
             limit_config: vm_limit_config,
         },
         account_creation_config: AccountCreationConfig::default(),
@@ -92,6 +98,29 @@ fn ext_costs_config(cost_table: &CostTable) -> anyhow::Result<ExtCostsConfig> {
             // TODO: accurately price host functions that expose validator information.
             ExtCosts::validator_stake_base => 303944908800,
             ExtCosts::validator_total_stake_base => 303944908800,
+            // Host functions of disabled protocol features aren't estimated.
+            #[cfg(not(feature = "protocol_feature_bls12381"))]
+            ExtCosts::bls12381_p1_sum_base
+            | ExtCosts::bls12381_p1_sum_element
+            | ExtCosts::bls12381_p2_sum_base
+            | ExtCosts::bls12381_p2_sum_element
+            | ExtCosts::bls12381_g1_multiexp_base
+            | ExtCosts::bls12381_g1_multiexp_element
+            | ExtCosts::bls12381_g2_multiexp_base
+            | ExtCosts::bls12381_g2_multiexp_element
+            | ExtCosts::bls12381_map_fp_to_g1_base
+            | ExtCosts::bls12381_map_fp_to_g1_element
+            | ExtCosts::bls12381_map_fp2_to_g2_base
+            | ExtCosts::bls12381_map_fp2_to_g2_element
+            | ExtCosts::bls12381_pairing_base
+            | ExtCosts::bls12381_pairing_element => 0,
+            #[cfg(not(feature = "protocol_feature_p256_verify"))]
+            ExtCosts::p256_verify_base | ExtCosts::p256_verify_byte => 0,
+            #[cfg(not(feature = "protocol_feature_yield_resume"))]
+            ExtCosts::yield_create_base
+            | ExtCosts::yield_create_byte
+            | ExtCosts::yield_resume_base
+            | ExtCosts::yield_resume_byte => 0,
             cost => {
                 let estimation = estimation(cost).with_context(|| format!("external WASM cost has no estimation defined: {}", cost))?;
                 cost_table.get(estimation).with_context(|| format!("undefined external WASM cost: {}", cost))?
@@ -151,6 +180,33 @@ fn estimation(cost: ExtCosts) -> Option<Cost> {
         ExtCosts::alt_bn128_g1_multiexp_element => Cost::AltBn128G1MultiexpElement,
         ExtCosts::alt_bn128_pairing_check_base => Cost::AltBn128PairingCheckBase,
         ExtCosts::alt_bn128_pairing_check_element => Cost::AltBn128PairingCheckElement,
+        ExtCosts::bls12381_p1_sum_base => Cost::Bls12381P1SumBase,
+        ExtCosts::bls12381_p1_sum_element => Cost::Bls12381P1SumElement,
+        ExtCosts::bls12381_p2_sum_base => Cost::Bls12381P2SumBase,
+        ExtCosts::bls12381_p2_sum_element => Cost::Bls12381P2SumElement,
+        ExtCosts::bls12381_g1_multiexp_base => Cost::Bls12381G1MultiexpBase,
+        ExtCosts::bls12381_g1_multiexp_element => Cost::Bls12381G1MultiexpElement,
+        ExtCosts::bls12381_g2_multiexp_base => Cost::Bls12381G2MultiexpBase,
+        ExtCosts::bls12381_g2_multiexp_element => Cost::Bls12381G2MultiexpElement,
+        ExtCosts::bls12381_map_fp_to_g1_base => Cost::Bls12381MapFpToG1Base,
+        ExtCosts::bls12381_map_fp_to_g1_element => Cost::Bls12381MapFpToG1Element,
+        ExtCosts::bls12381_map_fp2_to_g2_base => Cost::Bls12381MapFp2ToG2Base,
+        ExtCosts::bls12381_map_fp2_to_g2_element => Cost::Bls12381MapFp2ToG2Element,
+        ExtCosts::bls12381_pairing_base => Cost::Bls12381PairingBase,
+        ExtCosts::bls12381_pairing_element => Cost::Bls12381PairingElement,
+        ExtCosts::p256_verify_base => Cost::P256VerifyBase,
+        ExtCosts::p256_verify_byte => Cost::P256VerifyByte,
+        ExtCosts::yield_create_base => Cost::YieldCreateBase,
+        ExtCosts::yield_create_byte => Cost::YieldCreateByte,
+        ExtCosts::yield_resume_base => Cost::YieldResumeBase,
+        ExtCosts::yield_resume_byte => Cost::YieldResumeByte,
         _ => return None,
     })
 }
+
+fn div_ceil(numerator: u64, denominator: u64) -> u64 {
+    if denominator == 0 {
+        return 0;
+    }
+    (numerator + denominator - 1) / denominator
+}
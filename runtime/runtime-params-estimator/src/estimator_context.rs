@@ -40,6 +40,12 @@ pub(crate) struct CachedCosts {
     pub(crate) touching_trie_node_read: Option<GasCost>,
     pub(crate) touching_trie_node_write: Option<GasCost>,
     pub(crate) ed25519_verify_base: Option<GasCost>,
+    #[cfg(feature = "protocol_feature_p256_verify")]
+    pub(crate) p256_verify_base: Option<GasCost>,
+    #[cfg(feature = "protocol_feature_yield_resume")]
+    pub(crate) yield_create_base: Option<GasCost>,
+    #[cfg(feature = "protocol_feature_yield_resume")]
+    pub(crate) yield_resume_base: Option<GasCost>,
 }
 
 impl<'c> EstimatorContext<'c> {
@@ -131,6 +137,8 @@ impl<'c> EstimatorContext<'c> {
             is_new_chunk: true,
             migration_data: Arc::new(MigrationData::default()),
             migration_flags: MigrationFlags::default(),
+            verify_signatures: true,
+            process_delayed_receipts: true,
         }
     }
 }
@@ -193,6 +193,7 @@ static ALL_COSTS: &[(Cost, fn(&mut EstimatorContext) -> GasCost)] = &[
     (Cost::ActionFunctionCallPerByteExec, action_costs::function_call_byte_exec),
     (Cost::HostFunctionCall, host_function_call),
     (Cost::WasmInstruction, wasm_instruction),
+    (Cost::WasmBulkMemoryWord, wasm_bulk_memory_word),
     (Cost::DataReceiptCreationBase, data_receipt_creation_base),
     (Cost::DataReceiptCreationPerByte, data_receipt_creation_per_byte),
     (Cost::ReadMemoryBase, read_memory_base),
@@ -226,6 +227,46 @@ static ALL_COSTS: &[(Cost, fn(&mut EstimatorContext) -> GasCost)] = &[
     (Cost::AltBn128G1SumElement, alt_bn128g1_sum_element),
     (Cost::AltBn128PairingCheckBase, alt_bn128_pairing_check_base),
     (Cost::AltBn128PairingCheckElement, alt_bn128_pairing_check_element),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381P1SumBase, bls12381_p1_sum_base),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381P1SumElement, bls12381_p1_sum_element),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381P2SumBase, bls12381_p2_sum_base),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381P2SumElement, bls12381_p2_sum_element),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381G1MultiexpBase, bls12381_g1_multiexp_base),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381G1MultiexpElement, bls12381_g1_multiexp_element),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381G2MultiexpBase, bls12381_g2_multiexp_base),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381G2MultiexpElement, bls12381_g2_multiexp_element),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381MapFpToG1Base, bls12381_map_fp_to_g1_base),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381MapFpToG1Element, bls12381_map_fp_to_g1_element),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381MapFp2ToG2Base, bls12381_map_fp2_to_g2_base),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381MapFp2ToG2Element, bls12381_map_fp2_to_g2_element),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381PairingBase, bls12381_pairing_base),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381PairingElement, bls12381_pairing_element),
+    #[cfg(feature = "protocol_feature_p256_verify")]
+    (Cost::P256VerifyBase, p256_verify_base),
+    #[cfg(feature = "protocol_feature_p256_verify")]
+    (Cost::P256VerifyByte, p256_verify_byte),
+    #[cfg(feature = "protocol_feature_yield_resume")]
+    (Cost::YieldCreateBase, yield_create_base),
+    #[cfg(feature = "protocol_feature_yield_resume")]
+    (Cost::YieldCreateByte, yield_create_byte),
+    #[cfg(feature = "protocol_feature_yield_resume")]
+    (Cost::YieldResumeBase, yield_resume_base),
+    #[cfg(feature = "protocol_feature_yield_resume")]
+    (Cost::YieldResumeByte, yield_resume_byte),
     (Cost::StorageHasKeyBase, storage_has_key_base),
     (Cost::StorageHasKeyByte, storage_has_key_byte),
     (Cost::StorageReadBase, storage_read_base),
@@ -882,6 +923,72 @@ fn wasm_instruction(ctx: &mut EstimatorContext) -> GasCost {
     per_instruction
 }
 
+fn wasm_bulk_memory_word(ctx: &mut EstimatorContext) -> GasCost {
+    let vm_kind = ctx.config.vm_kind;
+    let n_iters = ctx.config.iter_per_block as u64;
+
+    let mut config = VMConfig::free();
+    config.limit_config.wasm_features_version = near_vm_logic::WasmFeaturesVersion::V1;
+    let fees = RuntimeFeesConfig::test();
+    let cache = MockCompiledContractCache::default();
+    let runtime = vm_kind.runtime(config).unwrap();
+
+    let mut xs = vec![];
+    let mut ys = vec![];
+    for len in [0u64, 64 * 1024, 256 * 1024, 1024 * 1024, 4 * 1024 * 1024] {
+        let code = ContractCode::new(
+            wat::parse_str(format!(
+                r#"
+                (module
+                  (memory 128)
+                  (func (export "main")
+                    i32.const 0
+                    i32.const 42
+                    i32.const {len}
+                    memory.fill
+                    i32.const {len}
+                    i32.const 0
+                    i32.const {len}
+                    memory.copy))"#
+            ))
+            .unwrap(),
+            None,
+        );
+        let mut fake_external = MockedExternal::new();
+        let mut run = || {
+            let vm_result = runtime
+                .run(
+                    &code,
+                    "main",
+                    &mut fake_external,
+                    create_context(vec![]),
+                    &fees,
+                    &[],
+                    PROTOCOL_VERSION,
+                    Some(&cache),
+                )
+                .expect("fatal_error");
+            assert!(vm_result.aborted.is_none());
+        };
+        run();
+        let start = GasCost::measure(ctx.config.metric);
+        for _ in 0..n_iters {
+            run();
+        }
+        // Each iteration touches `len` bytes twice, once per instruction.
+        xs.push(2 * len / 8);
+        ys.push(start.elapsed() / n_iters);
+    }
+
+    let (_base, per_word) = GasCost::least_squares_method_gas_cost(
+        &xs,
+        &ys,
+        &LeastSquaresTolerance::default().factor_rel_nn_tolerance(0.001),
+        ctx.config.debug,
+    );
+    per_word
+}
+
 fn read_memory_base(ctx: &mut EstimatorContext) -> GasCost {
     fn_cost(ctx, "read_memory_10b_10k", ExtCosts::read_memory_base, 10_000)
 }
@@ -1025,6 +1132,147 @@ fn alt_bn128_pairing_check_element(ctx: &mut EstimatorContext) -> GasCost {
     )
 }
 
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_p1_sum_base(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_p1_sum_1_1k", ExtCosts::bls12381_p1_sum_base, 1000)
+}
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_p1_sum_element(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_p1_sum_10_1k", ExtCosts::bls12381_p1_sum_element, 10 * 1000)
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_p2_sum_base(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_p2_sum_1_1k", ExtCosts::bls12381_p2_sum_base, 1000)
+}
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_p2_sum_element(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_p2_sum_10_1k", ExtCosts::bls12381_p2_sum_element, 10 * 1000)
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_g1_multiexp_base(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_g1_multiexp_1_10", ExtCosts::bls12381_g1_multiexp_base, 10)
+}
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_g1_multiexp_element(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_g1_multiexp_10_10", ExtCosts::bls12381_g1_multiexp_element, 10 * 10)
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_g2_multiexp_base(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_g2_multiexp_1_10", ExtCosts::bls12381_g2_multiexp_base, 10)
+}
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_g2_multiexp_element(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_g2_multiexp_10_10", ExtCosts::bls12381_g2_multiexp_element, 10 * 10)
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_map_fp_to_g1_base(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_map_fp_to_g1_1_10", ExtCosts::bls12381_map_fp_to_g1_base, 10)
+}
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_map_fp_to_g1_element(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_map_fp_to_g1_10_10", ExtCosts::bls12381_map_fp_to_g1_element, 10 * 10)
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_map_fp2_to_g2_base(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_map_fp2_to_g2_1_10", ExtCosts::bls12381_map_fp2_to_g2_base, 10)
+}
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_map_fp2_to_g2_element(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_map_fp2_to_g2_10_10", ExtCosts::bls12381_map_fp2_to_g2_element, 10 * 10)
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_pairing_base(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_pairing_check_1_10", ExtCosts::bls12381_pairing_base, 10)
+}
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_pairing_element(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_pairing_check_10_10", ExtCosts::bls12381_pairing_element, 10 * 10)
+}
+
+#[cfg(feature = "protocol_feature_p256_verify")]
+fn p256_verify_base(ctx: &mut EstimatorContext) -> GasCost {
+    if ctx.cached.p256_verify_base.is_none() {
+        let cost = fn_cost(ctx, "p256_verify_32b_200", ExtCosts::p256_verify_base, 200);
+        ctx.cached.p256_verify_base = Some(cost);
+    }
+    ctx.cached.p256_verify_base.clone().unwrap()
+}
+
+#[cfg(feature = "protocol_feature_p256_verify")]
+fn p256_verify_byte(ctx: &mut EstimatorContext) -> GasCost {
+    let base = p256_verify_base(ctx);
+    // inside the WASM function, there are 64 calls to `p256_verify`.
+    let base_call_num = 64;
+    // each call checks a message of size 16kiB
+    let iteration_bytes = 16384;
+    let total_bytes = base_call_num * iteration_bytes;
+    let byte = fn_cost(ctx, "p256_verify_16kib_64", ExtCosts::p256_verify_byte, total_bytes);
+    // need to subtract the base cost, which has already been divided by the number of bytes per iteration
+    byte - base / iteration_bytes
+}
+
+#[cfg(feature = "protocol_feature_yield_resume")]
+fn yield_create_base(ctx: &mut EstimatorContext) -> GasCost {
+    if ctx.cached.yield_create_base.is_none() {
+        let cost = fn_cost(ctx, "yield_create_10b_100", ExtCosts::yield_create_base, 100);
+        ctx.cached.yield_create_base = Some(cost);
+    }
+    ctx.cached.yield_create_base.clone().unwrap()
+}
+
+#[cfg(feature = "protocol_feature_yield_resume")]
+fn yield_create_byte(ctx: &mut EstimatorContext) -> GasCost {
+    let base = yield_create_base(ctx);
+    // inside the WASM function, there are 100 calls to `promise_yield_create`.
+    let base_call_num = 100;
+    // each call passes a 10 bytes method name and 10kiB arguments
+    let iteration_bytes = 10 + 10240;
+    let total_bytes = base_call_num * iteration_bytes;
+    let byte = fn_cost(ctx, "yield_create_10kib_100", ExtCosts::yield_create_byte, total_bytes);
+    // need to subtract the base cost, which has already been divided by the number of bytes per iteration
+    byte - base / iteration_bytes
+}
+
+#[cfg(feature = "protocol_feature_yield_resume")]
+fn yield_resume_base(ctx: &mut EstimatorContext) -> GasCost {
+    if ctx.cached.yield_resume_base.is_none() {
+        let cost = fn_cost_with_setup(
+            ctx,
+            "yield_create_for_resume_100",
+            "yield_resume_0b_100",
+            ExtCosts::yield_resume_base,
+            100,
+        );
+        ctx.cached.yield_resume_base = Some(cost);
+    }
+    ctx.cached.yield_resume_base.clone().unwrap()
+}
+
+#[cfg(feature = "protocol_feature_yield_resume")]
+fn yield_resume_byte(ctx: &mut EstimatorContext) -> GasCost {
+    let base = yield_resume_base(ctx);
+    // inside the WASM function, there are 100 calls to `promise_yield_resume`.
+    let base_call_num = 100;
+    // each call resumes a promise with a 1kiB payload
+    let iteration_bytes = 1024;
+    let total_bytes = base_call_num * iteration_bytes;
+    let byte = fn_cost_with_setup(
+        ctx,
+        "yield_create_for_resume_100",
+        "yield_resume_1kib_100",
+        ExtCosts::yield_resume_byte,
+        total_bytes,
+    );
+    // need to subtract the base cost, which has already been divided by the number of bytes per iteration
+    byte - base / iteration_bytes
+}
+
 fn storage_has_key_base(ctx: &mut EstimatorContext) -> GasCost {
     fn_cost_with_setup(
         ctx,
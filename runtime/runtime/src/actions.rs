@@ -1,5 +1,6 @@
 use crate::config::{safe_add_gas, RuntimeConfig};
 use crate::ext::{ExternalError, RuntimeExt};
+use crate::ExecutionTracer;
 use crate::{metrics, ActionResult, ApplyState};
 use borsh::{BorshDeserialize, BorshSerialize};
 use near_crypto::PublicKey;
@@ -9,13 +10,16 @@ use near_primitives::config::ViewConfig;
 use near_primitives::contract::ContractCode;
 use near_primitives::errors::{ActionError, ActionErrorKind, RuntimeError};
 use near_primitives::hash::CryptoHash;
-use near_primitives::receipt::{ActionReceipt, Receipt, ReceiptEnum};
+use near_primitives::receipt::{
+    ActionReceipt, DataReceipt, PromiseYieldTimeout, Receipt, ReceiptEnum,
+};
 use near_primitives::runtime::config::AccountCreationConfig;
 use near_primitives::runtime::fees::RuntimeFeesConfig;
 use near_primitives::transaction::{
     Action, AddKeyAction, DeleteAccountAction, DeleteKeyAction, DeployContractAction,
     FunctionCallAction, StakeAction, TransferAction,
 };
+use near_primitives::trie_key::TrieKey;
 use near_primitives::types::validator_stake::ValidatorStake;
 use near_primitives::types::{AccountId, BlockHeight, EpochInfoProvider, TrieCacheMode};
 use near_primitives::utils::create_random_seed;
@@ -23,9 +27,10 @@ use near_primitives::version::{
     is_implicit_account_creation_enabled, ProtocolFeature, ProtocolVersion,
     DELETE_KEY_STORAGE_USAGE_PROTOCOL_VERSION,
 };
+use near_primitives::views::FunctionCallTraceView;
 use near_store::{
-    get_access_key, get_code, remove_access_key, remove_account, set_access_key, set_code,
-    StorageError, TrieUpdate,
+    get_access_key, get_code, get_promise_yield_indices, remove_access_key, remove_account, set,
+    set_access_key, set_code, StorageError, TrieUpdate,
 };
 use near_vm_errors::{
     CompilationError, FunctionCallError, FunctionCallErrorSer, InconsistentStateError,
@@ -178,6 +183,7 @@ pub(crate) fn action_function_call(
     config: &RuntimeConfig,
     is_last_action: bool,
     epoch_info_provider: &dyn EpochInfoProvider,
+    execution_tracer: Option<&ExecutionTracer>,
 ) -> Result<(), RuntimeError> {
     if account.amount().checked_add(function_call.deposit).is_none() {
         return Err(StorageError::StorageInconsistentState(
@@ -195,6 +201,13 @@ pub(crate) fn action_function_call(
         epoch_info_provider,
         apply_state.current_protocol_version,
     );
+    let tracer = execution_tracer.filter(|tracer| tracer.receipt_id == receipt.receipt_id);
+    if let Some(tracer) = tracer {
+        runtime_ext.enable_execution_trace();
+        if tracer.extended_gas_profile {
+            runtime_ext.enable_extended_profile();
+        }
+    }
     let outcome = execute_function_call(
         apply_state,
         &mut runtime_ext,
@@ -208,6 +221,17 @@ pub(crate) fn action_function_call(
         is_last_action,
         None,
     )?;
+    if let Some(tracer) = tracer {
+        tracer.record(FunctionCallTraceView {
+            contract_id: account_id.clone(),
+            method_name: function_call.method_name.clone(),
+            gas_burnt: outcome.burnt_gas,
+            logs: outcome.logs.clone(),
+            error: outcome.aborted.as_ref().map(ToString::to_string),
+            host_calls: runtime_ext.take_execution_trace(),
+            extended_gas_profile: runtime_ext.take_extended_gas_profile(account.code_hash()),
+        });
+    }
 
     match &outcome.aborted {
         None => {
@@ -284,10 +308,47 @@ pub(crate) fn action_function_call(
             })
             .collect();
 
+        if !outcome.promise_yields.is_empty() {
+            // Remember the yielded promises, so they can be resumed later, and enqueue their
+            // timeouts to be resolved in `Runtime::resolve_promise_yield_timeouts`.
+            let expires_at = apply_state.block_height
+                + config.wasm_config.limit_config.yield_timeout_length_in_blocks;
+            let mut indices = get_promise_yield_indices(state_update)?;
+            for data_id in outcome.promise_yields {
+                state_update.set(
+                    TrieKey::PromiseYieldReceipt { receiver_id: account_id.clone(), data_id },
+                    vec![],
+                );
+                set(
+                    state_update,
+                    TrieKey::PromiseYieldTimeout { index: indices.next_available_index },
+                    &PromiseYieldTimeout { account_id: account_id.clone(), data_id, expires_at },
+                );
+                indices.next_available_index =
+                    indices.next_available_index.checked_add(1).ok_or_else(|| {
+                        StorageError::StorageInconsistentState(
+                            "Next available index for promise yield timeout exceeded the \
+                             integer limit"
+                                .to_string(),
+                        )
+                    })?;
+            }
+            set(state_update, TrieKey::PromiseYieldIndices, &indices);
+        }
+        // Resumed promises receive their payload through a data receipt sent to the yielding
+        // account itself.
+        let resume_receipts = outcome.promise_resumes.into_iter().map(|(data_id, data)| Receipt {
+            predecessor_id: account_id.clone(),
+            receiver_id: account_id.clone(),
+            receipt_id: CryptoHash::default(),
+            receipt: ReceiptEnum::Data(DataReceipt { data_id, data: Some(data) }),
+        });
+
         account.set_amount(outcome.balance);
         account.set_storage_usage(outcome.storage_usage);
         result.result = Ok(outcome.return_data);
         result.new_receipts.extend(new_receipts);
+        result.new_receipts.extend(resume_receipts);
     }
 
     Ok(())
@@ -1230,6 +1291,8 @@ mod tests {
             is_new_chunk: false,
             migration_data: Arc::default(),
             migration_flags: MigrationFlags::default(),
+            verify_signatures: true,
+            process_delayed_receipts: true,
         }
     }
 
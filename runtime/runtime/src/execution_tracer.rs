@@ -0,0 +1,27 @@
+use near_primitives::hash::CryptoHash;
+use near_primitives::views::FunctionCallTraceView;
+use std::sync::Mutex;
+
+/// Collects traces of the function calls executed for a single receipt.  Only used when
+/// re-executing chunks for debugging.
+#[derive(Debug)]
+pub struct ExecutionTracer {
+    pub receipt_id: CryptoHash,
+    /// Whether to also collect an extended gas profile of each function call.
+    pub extended_gas_profile: bool,
+    traces: Mutex<Vec<FunctionCallTraceView>>,
+}
+
+impl ExecutionTracer {
+    pub fn new(receipt_id: CryptoHash, extended_gas_profile: bool) -> Self {
+        Self { receipt_id, extended_gas_profile, traces: Mutex::new(vec![]) }
+    }
+
+    pub fn record(&self, trace: FunctionCallTraceView) {
+        self.traces.lock().unwrap().push(trace);
+    }
+
+    pub fn take_traces(&self) -> Vec<FunctionCallTraceView> {
+        std::mem::take(&mut *self.traces.lock().unwrap())
+    }
+}
@@ -1,12 +1,14 @@
 use near_primitives::contract::ContractCode;
 use near_primitives::errors::{EpochError, StorageError};
 use near_primitives::hash::CryptoHash;
+use near_primitives::profile::ExtendedProfileData;
 use near_primitives::trie_key::{trie_key_parsers, TrieKey};
 use near_primitives::types::{
     AccountId, Balance, EpochId, EpochInfoProvider, TrieCacheMode, TrieNodesCount,
 };
 use near_primitives::utils::create_data_id;
 use near_primitives::version::ProtocolVersion;
+use near_primitives::views::{ExtendedGasProfileView, HostFunctionCallView};
 use near_store::{get_code, KeyLookupMode, TrieUpdate, TrieUpdateValuePtr};
 use near_vm_errors::{AnyError, VMLogicError};
 use near_vm_logic::{External, StorageGetMode, ValuePtr};
@@ -21,6 +23,8 @@ pub struct RuntimeExt<'a> {
     last_block_hash: &'a CryptoHash,
     epoch_info_provider: &'a dyn EpochInfoProvider,
     current_protocol_version: ProtocolVersion,
+    execution_trace: Option<Vec<HostFunctionCallView>>,
+    extended_profile: Option<ExtendedProfileData>,
 }
 
 /// Error used by `RuntimeExt`.
@@ -72,6 +76,8 @@ impl<'a> RuntimeExt<'a> {
             last_block_hash,
             epoch_info_provider,
             current_protocol_version,
+            execution_trace: None,
+            extended_profile: None,
         }
     }
 
@@ -96,6 +102,37 @@ impl<'a> RuntimeExt<'a> {
     pub fn protocol_version(&self) -> ProtocolVersion {
         self.current_protocol_version
     }
+
+    /// Makes the contract runtime record every host function call made through this `External`.
+    pub fn enable_execution_trace(&mut self) {
+        self.execution_trace = Some(vec![]);
+    }
+
+    /// Returns the host function calls recorded since tracing was enabled.
+    pub fn take_execution_trace(&mut self) -> Vec<HostFunctionCallView> {
+        self.execution_trace.take().unwrap_or_default()
+    }
+
+    /// Makes the contract runtime collect an extended gas profile of the calls made through this
+    /// `External`.
+    pub fn enable_extended_profile(&mut self) {
+        self.extended_profile = Some(Default::default());
+    }
+
+    /// Returns the extended gas profile collected since profiling was enabled, with wasm functions
+    /// named after the ones of the contract with the given code hash.
+    pub fn take_extended_gas_profile(
+        &mut self,
+        code_hash: CryptoHash,
+    ) -> Option<ExtendedGasProfileView> {
+        let profile = self.extended_profile.take()?;
+        let code = self
+            .get_code(code_hash)
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| ContractCode::new(vec![], None));
+        Some(near_vm_runner::extended_gas_profile_view(&code, &profile))
+    }
 }
 
 fn wrap_storage_error(error: StorageError) -> VMLogicError {
@@ -176,6 +213,18 @@ impl<'a> External for RuntimeExt<'a> {
         data_id
     }
 
+    fn promise_yield_resume(&mut self, data_id: &CryptoHash) -> ExtResult<bool> {
+        let key = TrieKey::PromiseYieldReceipt {
+            receiver_id: self.account_id.clone(),
+            data_id: *data_id,
+        };
+        if self.trie_update.get(&key).map_err(wrap_storage_error)?.is_none() {
+            return Ok(false);
+        }
+        self.trie_update.remove(key);
+        Ok(true)
+    }
+
     fn get_trie_nodes_count(&self) -> TrieNodesCount {
         self.trie_update.trie().get_trie_nodes_count()
     }
@@ -191,4 +240,12 @@ impl<'a> External for RuntimeExt<'a> {
             .validator_total_stake(self.epoch_id, self.prev_block_hash)
             .map_err(|e| ExternalError::ValidatorError(e).into())
     }
+
+    fn execution_trace(&mut self) -> Option<&mut Vec<HostFunctionCallView>> {
+        self.execution_trace.as_mut()
+    }
+
+    fn extended_profile(&mut self) -> Option<&mut ExtendedProfileData> {
+        self.extended_profile.as_mut()
+    }
 }
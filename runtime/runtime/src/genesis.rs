@@ -9,7 +9,10 @@ use near_primitives::shard_layout::ShardUId;
 use near_primitives::{
     account::{AccessKey, Account},
     contract::ContractCode,
-    receipt::{DelayedReceiptIndices, Receipt, ReceiptEnum, ReceivedData},
+    receipt::{
+        DelayedReceiptIndices, PromiseYieldIndices, PromiseYieldTimeout, Receipt, ReceiptEnum,
+        ReceivedData,
+    },
     state_record::{state_record_to_account_id, StateRecord},
     trie_key::TrieKey,
     types::{AccountId, Balance, MerkleHash, ShardId, StateChangeCause, StateRoot},
@@ -65,6 +68,8 @@ impl<'a> StorageComputer<'a> {
             StateRecord::PostponedReceipt(_) => None,
             StateRecord::ReceivedData { .. } => None,
             StateRecord::DelayedReceipt(_) => None,
+            StateRecord::PromiseYieldReceipt { .. } => None,
+            StateRecord::PromiseYieldTimeout(_) => None,
         };
         if let Some((account_id, storage_usage)) = account_and_storage {
             *self.result.entry(account_id).or_default() += storage_usage;
@@ -107,6 +112,7 @@ impl GenesisStateApplier {
     fn apply_batch(
         current_state_root: &mut StateRoot,
         delayed_receipts_indices: &mut DelayedReceiptIndices,
+        promise_yield_timeouts: &mut Vec<PromiseYieldTimeout>,
         tries: &mut ShardTries,
         shard_uid: ShardUId,
         validators: &[(AccountId, PublicKey, Balance)],
@@ -163,6 +169,16 @@ impl GenesisStateApplier {
                     )
                         .unwrap();
                 }
+                StateRecord::PromiseYieldReceipt { account_id, data_id } => {
+                    state_update.set(
+                        TrieKey::PromiseYieldReceipt { receiver_id: account_id, data_id },
+                        vec![],
+                    );
+                }
+                StateRecord::PromiseYieldTimeout(timeout) => {
+                    // Queued after all records are processed, once their order is known.
+                    promise_yield_timeouts.push(timeout);
+                }
             }
         });
 
@@ -242,6 +258,33 @@ impl GenesisStateApplier {
         }
     }
 
+    fn apply_promise_yield_timeouts(
+        mut promise_yield_timeouts: Vec<PromiseYieldTimeout>,
+        current_state_root: &mut StateRoot,
+        tries: &mut ShardTries,
+        shard_uid: ShardUId,
+    ) {
+        if promise_yield_timeouts.is_empty() {
+            return;
+        }
+        let mut state_update = tries.new_trie_update(shard_uid, *current_state_root);
+
+        // Records don't keep the position of timeouts in the queue, but timeouts are resolved in
+        // the order of their expiration, so it's restored from it.
+        promise_yield_timeouts.sort_by_key(|timeout| timeout.expires_at);
+        let mut promise_yield_indices = PromiseYieldIndices::default();
+        for timeout in promise_yield_timeouts {
+            set(
+                &mut state_update,
+                TrieKey::PromiseYieldTimeout { index: promise_yield_indices.next_available_index },
+                &timeout,
+            );
+            promise_yield_indices.next_available_index += 1;
+        }
+        set(&mut state_update, TrieKey::PromiseYieldIndices, &promise_yield_indices);
+        Self::commit(state_update, current_state_root, tries, shard_uid);
+    }
+
     pub fn apply(
         mut tries: ShardTries,
         shard_id: ShardId,
@@ -252,6 +295,7 @@ impl GenesisStateApplier {
     ) -> StateRoot {
         let mut current_state_root = MerkleHash::default();
         let mut delayed_receipts_indices = DelayedReceiptIndices::default();
+        let mut promise_yield_timeouts = vec![];
         let shard_uid =
             ShardUId { version: genesis.config.shard_layout.version(), shard_id: shard_id as u32 };
         for batch_account_ids in
@@ -260,6 +304,7 @@ impl GenesisStateApplier {
             Self::apply_batch(
                 &mut current_state_root,
                 &mut delayed_receipts_indices,
+                &mut promise_yield_timeouts,
                 &mut tries,
                 shard_uid,
                 validators,
@@ -274,6 +319,12 @@ impl GenesisStateApplier {
             &mut tries,
             shard_uid,
         );
+        Self::apply_promise_yield_timeouts(
+            promise_yield_timeouts,
+            &mut current_state_root,
+            &mut tries,
+            shard_uid,
+        );
         current_state_root
     }
 }
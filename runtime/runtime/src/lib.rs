@@ -26,7 +26,8 @@ use near_primitives::{
     errors::{ActionError, ActionErrorKind, RuntimeError, TxExecutionError},
     hash::CryptoHash,
     receipt::{
-        ActionReceipt, DataReceipt, DelayedReceiptIndices, Receipt, ReceiptEnum, ReceivedData,
+        ActionReceipt, DataReceipt, DelayedReceiptIndices, PromiseYieldTimeout, Receipt,
+        ReceiptEnum, ReceivedData,
     },
     state_record::StateRecord,
     transaction::{
@@ -39,13 +40,14 @@ use near_primitives::{
         RawStateChangesWithTrieKey, ShardId, StateChangeCause, StateRoot,
     },
     utils::{
-        create_action_hash, create_receipt_id_from_receipt, create_receipt_id_from_transaction,
+        create_action_hash, create_receipt_id_from_promise_yield_timeout,
+        create_receipt_id_from_receipt, create_receipt_id_from_transaction,
     },
 };
 use near_store::{
-    get, get_account, get_postponed_receipt, get_received_data, remove_postponed_receipt, set,
-    set_account, set_postponed_receipt, set_received_data, PartialStorage, ShardTries,
-    StorageError, Trie, TrieChanges, TrieUpdate,
+    get, get_account, get_postponed_receipt, get_promise_yield_indices, get_received_data,
+    remove_postponed_receipt, set, set_account, set_postponed_receipt, set_received_data,
+    PartialStorage, ShardTries, StorageError, Trie, TrieChanges, TrieUpdate,
 };
 use near_store::{set_access_key, set_code};
 use near_vm_logic::types::PromiseResult;
@@ -58,6 +60,7 @@ use crate::config::{
     exec_fee, safe_add_balance, safe_add_gas, safe_gas_to_balance, total_deposit,
     total_prepaid_exec_fees, total_prepaid_gas, RuntimeConfig,
 };
+pub use crate::execution_tracer::ExecutionTracer;
 use crate::genesis::{GenesisStateApplier, StorageComputer};
 use crate::prefetch::TriePrefetcher;
 use crate::verifier::{check_storage_stake, validate_receipt, StorageStakingError};
@@ -67,6 +70,7 @@ mod actions;
 pub mod adapter;
 mod balance_checker;
 pub mod config;
+mod execution_tracer;
 pub mod ext;
 mod genesis;
 mod metrics;
@@ -185,11 +189,19 @@ impl Default for ActionResult {
     }
 }
 
-pub struct Runtime {}
+pub struct Runtime {
+    /// If set, function calls of the traced receipt are executed with tracing enabled.
+    execution_tracer: Option<Arc<ExecutionTracer>>,
+}
 
 impl Runtime {
     pub fn new() -> Self {
-        Self {}
+        Self { execution_tracer: None }
+    }
+
+    /// Creates a runtime which traces the function calls of the receipt `tracer` is set up for.
+    pub fn with_execution_tracer(tracer: Arc<ExecutionTracer>) -> Self {
+        Self { execution_tracer: Some(tracer) }
     }
 
     fn print_log(log: &[LogEntry]) {
@@ -230,7 +242,7 @@ impl Runtime {
             state_update,
             apply_state.gas_price,
             signed_transaction,
-            true,
+            apply_state.verify_signatures,
             Some(apply_state.block_height),
             apply_state.current_protocol_version,
         ) {
@@ -368,6 +380,7 @@ impl Runtime {
                     &apply_state.config,
                     action_index + 1 == actions.len(),
                     epoch_info_provider,
+                    self.execution_tracer.as_deref(),
                 )?;
             }
             Action::Transfer(transfer) => {
@@ -1332,7 +1345,9 @@ impl Runtime {
         }
 
         // Then we process the delayed receipts. It's a backlog of receipts from the past blocks.
-        while delayed_receipts_indices.first_index < delayed_receipts_indices.next_available_index {
+        while apply_state.process_delayed_receipts
+            && delayed_receipts_indices.first_index < delayed_receipts_indices.next_available_index
+        {
             if total_gas_burnt >= gas_limit {
                 break;
             }
@@ -1390,6 +1405,22 @@ impl Runtime {
             prefetcher.clear();
         }
 
+        if apply_state.process_delayed_receipts
+            && checked_feature!(
+                "protocol_feature_yield_resume",
+                YieldResume,
+                apply_state.current_protocol_version
+            )
+        {
+            Self::resolve_promise_yield_timeouts(
+                &mut state_update,
+                apply_state,
+                &mut outgoing_receipts,
+                &mut total_gas_burnt,
+                gas_limit,
+            )?;
+        }
+
         if delayed_receipts_indices != initial_delayed_receipt_indices {
             set(&mut state_update, TrieKey::DelayedReceiptIndices, &delayed_receipts_indices);
         }
@@ -1457,6 +1488,74 @@ impl Runtime {
         Ok(())
     }
 
+    /// Resolves yielded promises whose timeout expired without being resumed. Each of them gets
+    /// a data receipt without data, so the callback is executed with a failed promise result.
+    ///
+    /// Every processed timeout counts the cost of sending and executing that data receipt
+    /// towards `total_gas_burnt`. Once `gas_limit` is reached, the remaining expired timeouts
+    /// stay in the queue and are resolved in the following chunks.
+    fn resolve_promise_yield_timeouts(
+        state_update: &mut TrieUpdate,
+        apply_state: &ApplyState,
+        outgoing_receipts: &mut Vec<Receipt>,
+        total_gas_burnt: &mut Gas,
+        gas_limit: Gas,
+    ) -> Result<(), RuntimeError> {
+        let data_receipt_fee = apply_state.config.fees.fee(ActionCosts::new_data_receipt_base);
+        let timeout_cost =
+            safe_add_gas(data_receipt_fee.send_fee(true), data_receipt_fee.exec_fee())?;
+        let mut promise_yield_indices = get_promise_yield_indices(state_update)?;
+        let initial_promise_yield_indices = promise_yield_indices.clone();
+        while promise_yield_indices.first_index < promise_yield_indices.next_available_index {
+            if *total_gas_burnt >= gas_limit {
+                break;
+            }
+            let key = TrieKey::PromiseYieldTimeout { index: promise_yield_indices.first_index };
+            let timeout: PromiseYieldTimeout = get(state_update, &key)?.ok_or_else(|| {
+                StorageError::StorageInconsistentState(format!(
+                    "Promise yield timeout #{} should be in the state",
+                    promise_yield_indices.first_index
+                ))
+            })?;
+            // Timeouts are queued in order of creation, so they expire in order as well.
+            if timeout.expires_at > apply_state.block_height {
+                break;
+            }
+
+            // The promise might have been resumed already, in which case there is nothing to do.
+            let yield_key = TrieKey::PromiseYieldReceipt {
+                receiver_id: timeout.account_id.clone(),
+                data_id: timeout.data_id,
+            };
+            if state_update.get(&yield_key)?.is_some() {
+                state_update.remove(yield_key);
+                outgoing_receipts.push(Receipt {
+                    predecessor_id: timeout.account_id.clone(),
+                    receiver_id: timeout.account_id,
+                    receipt_id: create_receipt_id_from_promise_yield_timeout(
+                        apply_state.current_protocol_version,
+                        &timeout.data_id,
+                        &apply_state.prev_block_hash,
+                        &apply_state.block_hash,
+                    ),
+                    receipt: ReceiptEnum::Data(DataReceipt {
+                        data_id: timeout.data_id,
+                        data: None,
+                    }),
+                });
+            }
+
+            state_update.remove(key);
+            // Math checked above: first_index is less than next_available_index
+            promise_yield_indices.first_index += 1;
+            *total_gas_burnt = safe_add_gas(*total_gas_burnt, timeout_cost)?;
+        }
+        if promise_yield_indices != initial_promise_yield_indices {
+            set(state_update, TrieKey::PromiseYieldIndices, &promise_yield_indices);
+        }
+        Ok(())
+    }
+
     fn apply_state_patch(&self, state_update: &mut TrieUpdate, state_patch: SandboxStatePatch) {
         if state_patch.is_empty() {
             return;
@@ -1656,6 +1755,8 @@ mod tests {
             is_new_chunk: true,
             migration_data: Arc::new(MigrationData::default()),
             migration_flags: MigrationFlags::default(),
+            verify_signatures: true,
+            process_delayed_receipts: true,
         };
 
         (runtime, tries, root, apply_state, signer, MockEpochInfoProvider::default())
@@ -1797,6 +1898,49 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_apply_without_delayed_receipts() {
+        let initial_balance = to_yocto(1_000_000);
+        let initial_locked = to_yocto(500_000);
+        let small_transfer = to_yocto(10_000);
+        let (runtime, tries, mut root, mut apply_state, _, epoch_info_provider) =
+            setup_runtime(initial_balance, initial_locked, 1);
+
+        // With the gas limit of 1 only the first receipt is executed and the rest are delayed.
+        let receipts = generate_receipts(small_transfer, 10);
+        let mut apply = |apply_state: &ApplyState, receipts: &[Receipt]| {
+            let apply_result = runtime
+                .apply(
+                    tries.get_trie_for_shard(ShardUId::single_shard(), root),
+                    &None,
+                    apply_state,
+                    receipts,
+                    &[],
+                    &epoch_info_provider,
+                    Default::default(),
+                )
+                .unwrap();
+            let mut store_update = tries.store_update();
+            root = tries.apply_all(
+                &apply_result.trie_changes,
+                ShardUId::single_shard(),
+                &mut store_update,
+            );
+            store_update.commit().unwrap();
+            apply_result
+        };
+        assert_eq!(apply(&apply_state, &receipts).outcomes.len(), 1);
+
+        apply_state.process_delayed_receipts = false;
+        let apply_result = apply(&apply_state, &[]);
+        assert!(apply_result.outcomes.is_empty());
+        assert!(apply_result.processed_delayed_receipts.is_empty());
+
+        apply_state.process_delayed_receipts = true;
+        let apply_result = apply(&apply_state, &[]);
+        assert_eq!(apply_result.processed_delayed_receipts, vec![receipts[1].clone()]);
+    }
+
     #[test]
     fn test_apply_delayed_receipts_add_more_using_chunks() {
         let initial_balance = to_yocto(1_000_000);
@@ -2479,6 +2623,404 @@ mod tests {
             .expect("Compiled contract should be cached")
             .expect("Compilation result should be non-empty");
     }
+
+    #[cfg(feature = "protocol_feature_yield_resume")]
+    mod promise_yield {
+        use near_primitives::receipt::PromiseYieldIndices;
+        use near_primitives::types::BlockHeight;
+        use near_primitives::version::ProtocolFeature;
+
+        use super::*;
+
+        /// Contract yielding a promise which calls back `callback`. The data id of the promise
+        /// is stored under "id" and the result of resuming it with "response" under "resumed".
+        /// The callback stores the status of the promise result under "status" and, if it
+        /// succeeded, the payload under "payload".
+        fn yield_resume_contract() -> Vec<u8> {
+            near_test_contracts::wat_contract(
+                r#"(module
+                  (import "env" "promise_yield_create"
+                    (func $promise_yield_create (param i64 i64 i64 i64 i64 i64 i64) (result i64)))
+                  (import "env" "promise_yield_resume"
+                    (func $promise_yield_resume (param i64 i64 i64 i64) (result i32)))
+                  (import "env" "promise_result"
+                    (func $promise_result (param i64 i64) (result i64)))
+                  (import "env" "storage_read"
+                    (func $storage_read (param i64 i64 i64) (result i64)))
+                  (import "env" "storage_write"
+                    (func $storage_write (param i64 i64 i64 i64 i64) (result i64)))
+                  (memory 1)
+                  (data (i32.const 0) "callback")
+                  (data (i32.const 8) "id")
+                  (data (i32.const 16) "status")
+                  (data (i32.const 24) "payload")
+                  (data (i32.const 32) "response")
+                  (data (i32.const 40) "resumed")
+                  (func (export "yield_create")
+                    (drop (call $promise_yield_create
+                      (i64.const 8) (i64.const 0) (i64.const 0) (i64.const 0)
+                      (i64.const 10000000000000) (i64.const 0) (i64.const 0)))
+                    (drop (call $storage_write
+                      (i64.const 2) (i64.const 8) (i64.const -1) (i64.const 0) (i64.const 1))))
+                  (func (export "yield_resume")
+                    (drop (call $storage_read (i64.const 2) (i64.const 8) (i64.const 0)))
+                    (i64.store (i32.const 64) (i64.extend_i32_u (call $promise_yield_resume
+                      (i64.const -1) (i64.const 0) (i64.const 8) (i64.const 32))))
+                    (drop (call $storage_write
+                      (i64.const 7) (i64.const 40) (i64.const 8) (i64.const 64) (i64.const 1))))
+                  (func (export "callback")
+                    (i64.store (i32.const 64) (call $promise_result (i64.const 0) (i64.const 0)))
+                    (drop (call $storage_write
+                      (i64.const 6) (i64.const 16) (i64.const 8) (i64.const 64) (i64.const 1)))
+                    (if (i64.eq (i64.load (i32.const 64)) (i64.const 1))
+                      (then (drop (call $storage_write
+                        (i64.const 7) (i64.const 24) (i64.const -1) (i64.const 0)
+                        (i64.const 1)))))))"#,
+            )
+        }
+
+        fn call_yield_resume_contract(
+            signer: Arc<InMemorySigner>,
+            method_name: &str,
+            receipt_id: CryptoHash,
+        ) -> Receipt {
+            let actions = vec![Action::FunctionCall(FunctionCallAction {
+                method_name: method_name.to_string(),
+                args: vec![],
+                gas: 10u64.pow(14),
+                deposit: 0,
+            })];
+            let mut receipts = create_receipts_with_actions(alice_account(), signer, actions);
+            receipts[0].receipt_id = receipt_id;
+            receipts.pop().unwrap()
+        }
+
+        /// Applies the receipts in a block at the given height and commits the new state.
+        fn apply_yield_resume_block(
+            runtime: &Runtime,
+            tries: &ShardTries,
+            root: &mut CryptoHash,
+            apply_state: &mut ApplyState,
+            block_height: BlockHeight,
+            receipts: &[Receipt],
+            epoch_info_provider: &dyn EpochInfoProvider,
+        ) -> ApplyResult {
+            apply_state.block_height = block_height;
+            apply_state.block_hash = hash(&block_height.to_le_bytes());
+            let apply_result = runtime
+                .apply(
+                    tries.get_trie_for_shard(ShardUId::single_shard(), *root),
+                    &None,
+                    apply_state,
+                    receipts,
+                    &[],
+                    epoch_info_provider,
+                    Default::default(),
+                )
+                .unwrap();
+            let mut store_update = tries.store_update();
+            *root = tries.apply_all(
+                &apply_result.trie_changes,
+                ShardUId::single_shard(),
+                &mut store_update,
+            );
+            store_update.commit().unwrap();
+            apply_state.prev_block_hash = apply_state.block_hash;
+            apply_result
+        }
+
+        fn get_yield_resume_contract_data(
+            tries: &ShardTries,
+            root: CryptoHash,
+            key: &[u8],
+        ) -> Option<Vec<u8>> {
+            let state_update = tries.new_trie_update(ShardUId::single_shard(), root);
+            state_update
+                .get(&TrieKey::ContractData { account_id: alice_account(), key: key.to_vec() })
+                .unwrap()
+        }
+
+        /// Deploys the yield/resume contract and yields a promise. Returns the data id of the
+        /// promise and the yielded receipt waiting for it.
+        fn yield_promise(
+            runtime: &Runtime,
+            tries: &ShardTries,
+            root: &mut CryptoHash,
+            apply_state: &mut ApplyState,
+            signer: Arc<InMemorySigner>,
+            epoch_info_provider: &dyn EpochInfoProvider,
+        ) -> (CryptoHash, Receipt) {
+            let mut receipts = create_receipts_with_actions(
+                alice_account(),
+                signer.clone(),
+                vec![Action::DeployContract(DeployContractAction {
+                    code: yield_resume_contract(),
+                })],
+            );
+            receipts.push(call_yield_resume_contract(
+                signer,
+                "yield_create",
+                hash(b"yield_create"),
+            ));
+            let apply_result = apply_yield_resume_block(
+                runtime,
+                tries,
+                root,
+                apply_state,
+                1,
+                &receipts,
+                epoch_info_provider,
+            );
+            let data_id = CryptoHash::try_from(
+                &get_yield_resume_contract_data(tries, *root, b"id").unwrap()[..],
+            )
+            .unwrap();
+            let yielded_receipt = apply_result
+                .outgoing_receipts
+                .into_iter()
+                .find(|receipt| match &receipt.receipt {
+                    ReceiptEnum::Action(action_receipt) => {
+                        action_receipt.input_data_ids == [data_id]
+                    }
+                    ReceiptEnum::Data(_) => false,
+                })
+                .expect("the yielded receipt should be sent");
+
+            let state_update = tries.new_trie_update(ShardUId::single_shard(), *root);
+            let yield_key = TrieKey::PromiseYieldReceipt { receiver_id: alice_account(), data_id };
+            assert!(state_update.get(&yield_key).unwrap().is_some());
+            assert_eq!(
+                get_promise_yield_indices(&state_update).unwrap(),
+                PromiseYieldIndices { first_index: 0, next_available_index: 1 }
+            );
+            (data_id, yielded_receipt)
+        }
+
+        #[test]
+        fn test_promise_yield_resume() {
+            let (runtime, tries, mut root, mut apply_state, signer, epoch_info_provider) =
+                setup_runtime(to_yocto(1_000_000), 0, 10u64.pow(15));
+            apply_state.current_protocol_version = ProtocolFeature::YieldResume.protocol_version();
+            let timeout_length =
+                apply_state.config.wasm_config.limit_config.yield_timeout_length_in_blocks;
+
+            let (data_id, yielded_receipt) = yield_promise(
+                &runtime,
+                &tries,
+                &mut root,
+                &mut apply_state,
+                signer.clone(),
+                &epoch_info_provider,
+            );
+            let receipts = [
+                yielded_receipt,
+                call_yield_resume_contract(signer, "yield_resume", hash(b"yield_resume")),
+            ];
+            let apply_result = apply_yield_resume_block(
+                &runtime,
+                &tries,
+                &mut root,
+                &mut apply_state,
+                2,
+                &receipts,
+                &epoch_info_provider,
+            );
+            assert_eq!(
+                get_yield_resume_contract_data(&tries, root, b"resumed"),
+                Some(1u64.to_le_bytes().to_vec())
+            );
+            assert!(apply_result.outgoing_receipts.iter().any(|receipt| receipt.receipt
+                == ReceiptEnum::Data(DataReceipt { data_id, data: Some(b"response".to_vec()) })));
+            let state_update = tries.new_trie_update(ShardUId::single_shard(), root);
+            let yield_key = TrieKey::PromiseYieldReceipt { receiver_id: alice_account(), data_id };
+            assert!(state_update.get(&yield_key).unwrap().is_none());
+
+            // The payload is delivered to the callback.
+            apply_yield_resume_block(
+                &runtime,
+                &tries,
+                &mut root,
+                &mut apply_state,
+                3,
+                &apply_result.outgoing_receipts,
+                &epoch_info_provider,
+            );
+            assert_eq!(
+                get_yield_resume_contract_data(&tries, root, b"status"),
+                Some(1u64.to_le_bytes().to_vec())
+            );
+            assert_eq!(
+                get_yield_resume_contract_data(&tries, root, b"payload"),
+                Some(b"response".to_vec())
+            );
+
+            // The timeout of a resumed promise is dropped without calling it back again.
+            let apply_result = apply_yield_resume_block(
+                &runtime,
+                &tries,
+                &mut root,
+                &mut apply_state,
+                1 + timeout_length,
+                &[],
+                &epoch_info_provider,
+            );
+            assert!(apply_result.outgoing_receipts.is_empty());
+            let state_update = tries.new_trie_update(ShardUId::single_shard(), root);
+            assert_eq!(
+                get_promise_yield_indices(&state_update).unwrap(),
+                PromiseYieldIndices { first_index: 1, next_available_index: 1 }
+            );
+            assert!(state_update
+                .get(&TrieKey::PromiseYieldTimeout { index: 0 })
+                .unwrap()
+                .is_none());
+        }
+
+        #[test]
+        fn test_promise_yield_timeout() {
+            let (runtime, tries, mut root, mut apply_state, signer, epoch_info_provider) =
+                setup_runtime(to_yocto(1_000_000), 0, 10u64.pow(15));
+            apply_state.current_protocol_version = ProtocolFeature::YieldResume.protocol_version();
+            let timeout_length =
+                apply_state.config.wasm_config.limit_config.yield_timeout_length_in_blocks;
+
+            let (data_id, yielded_receipt) = yield_promise(
+                &runtime,
+                &tries,
+                &mut root,
+                &mut apply_state,
+                signer.clone(),
+                &epoch_info_provider,
+            );
+            apply_yield_resume_block(
+                &runtime,
+                &tries,
+                &mut root,
+                &mut apply_state,
+                2,
+                &[yielded_receipt],
+                &epoch_info_provider,
+            );
+
+            // The promise doesn't time out before its expiration height.
+            let apply_result = apply_yield_resume_block(
+                &runtime,
+                &tries,
+                &mut root,
+                &mut apply_state,
+                timeout_length,
+                &[],
+                &epoch_info_provider,
+            );
+            assert!(apply_result.outgoing_receipts.is_empty());
+
+            let apply_result = apply_yield_resume_block(
+                &runtime,
+                &tries,
+                &mut root,
+                &mut apply_state,
+                1 + timeout_length,
+                &[],
+                &epoch_info_provider,
+            );
+            assert_eq!(apply_result.outgoing_receipts.len(), 1);
+            let timeout_receipt = &apply_result.outgoing_receipts[0];
+            assert_eq!(timeout_receipt.receiver_id, alice_account());
+            assert_eq!(
+                timeout_receipt.receipt,
+                ReceiptEnum::Data(DataReceipt { data_id, data: None })
+            );
+            let state_update = tries.new_trie_update(ShardUId::single_shard(), root);
+            let yield_key = TrieKey::PromiseYieldReceipt { receiver_id: alice_account(), data_id };
+            assert!(state_update.get(&yield_key).unwrap().is_none());
+            assert!(state_update
+                .get(&TrieKey::PromiseYieldTimeout { index: 0 })
+                .unwrap()
+                .is_none());
+            assert_eq!(
+                get_promise_yield_indices(&state_update).unwrap(),
+                PromiseYieldIndices { first_index: 1, next_available_index: 1 }
+            );
+
+            // The callback is called with a failed promise, and the promise can't be resumed
+            // anymore.
+            let receipts = [
+                timeout_receipt.clone(),
+                call_yield_resume_contract(signer, "yield_resume", hash(b"yield_resume")),
+            ];
+            apply_yield_resume_block(
+                &runtime,
+                &tries,
+                &mut root,
+                &mut apply_state,
+                2 + timeout_length,
+                &receipts,
+                &epoch_info_provider,
+            );
+            assert_eq!(
+                get_yield_resume_contract_data(&tries, root, b"status"),
+                Some(2u64.to_le_bytes().to_vec())
+            );
+            assert_eq!(get_yield_resume_contract_data(&tries, root, b"payload"), None);
+            assert_eq!(
+                get_yield_resume_contract_data(&tries, root, b"resumed"),
+                Some(0u64.to_le_bytes().to_vec())
+            );
+        }
+
+        /// Expired timeouts which don't fit into the gas limit of a chunk are resolved in the
+        /// following chunks.
+        #[test]
+        fn test_promise_yield_timeouts_gas_limit() {
+            let (runtime, tries, mut root, mut apply_state, signer, epoch_info_provider) =
+                setup_runtime(to_yocto(1_000_000), 0, 10u64.pow(15));
+            apply_state.current_protocol_version = ProtocolFeature::YieldResume.protocol_version();
+            let timeout_length =
+                apply_state.config.wasm_config.limit_config.yield_timeout_length_in_blocks;
+
+            let (_, yielded_receipt) = yield_promise(
+                &runtime,
+                &tries,
+                &mut root,
+                &mut apply_state,
+                signer.clone(),
+                &epoch_info_provider,
+            );
+            let receipts = [
+                yielded_receipt,
+                call_yield_resume_contract(signer, "yield_create", hash(b"yield_create_2")),
+            ];
+            apply_yield_resume_block(
+                &runtime,
+                &tries,
+                &mut root,
+                &mut apply_state,
+                2,
+                &receipts,
+                &epoch_info_provider,
+            );
+
+            // Both promises have timed out, but only one timeout fits into the chunk.
+            apply_state.gas_limit = Some(1);
+            for (height, first_index) in [(2 + timeout_length, 1), (3 + timeout_length, 2)] {
+                let apply_result = apply_yield_resume_block(
+                    &runtime,
+                    &tries,
+                    &mut root,
+                    &mut apply_state,
+                    height,
+                    &[],
+                    &epoch_info_provider,
+                );
+                assert_eq!(apply_result.outgoing_receipts.len(), 1);
+                let state_update = tries.new_trie_update(ShardUId::single_shard(), root);
+                assert_eq!(
+                    get_promise_yield_indices(&state_update).unwrap(),
+                    PromiseYieldIndices { first_index, next_available_index: 2 }
+                );
+            }
+        }
+    }
 }
 
 /// Interface provided for gas cost estimations.
@@ -2504,7 +3046,7 @@ pub mod estimator {
         stats: &mut ApplyStats,
         epoch_info_provider: &dyn EpochInfoProvider,
     ) -> Result<ExecutionOutcomeWithId, RuntimeError> {
-        Runtime {}.apply_action_receipt(
+        Runtime::new().apply_action_receipt(
             state_update,
             apply_state,
             receipt,
@@ -15,10 +15,13 @@ use near_primitives::{
     transaction::FunctionCallAction,
     trie_key::trie_key_parsers,
     types::{AccountId, EpochInfoProvider, Gas},
-    views::{StateItem, ViewApplyState, ViewStateResult},
+    views::{
+        ExtendedGasProfileView, FunctionCallTraceView, HostFunctionCallView, StateItem,
+        ViewApplyState, ViewStateResult,
+    },
 };
 use near_store::{get_access_key, get_account, get_code, TrieUpdate};
-use near_vm_logic::{ReturnData, ViewConfig};
+use near_vm_logic::{ReturnData, VMOutcome, ViewConfig};
 use std::{str, sync::Arc, time::Instant};
 use tracing::debug;
 
@@ -120,12 +123,38 @@ impl TrieViewer {
         prefix: &[u8],
         include_proof: bool,
     ) -> Result<ViewStateResult, errors::ViewStateError> {
+        self.view_state_page(state_update, account_id, prefix, None, None, include_proof)
+    }
+
+    /// Returns contract data of given account whose keys start with `prefix`.
+    ///
+    /// If `start_key` or `limit` is set, the result is paginated: iteration
+    /// starts at the first key no smaller than `start_key` and stops after
+    /// `limit` items or before the total size of returned keys and values
+    /// would exceed the state size limit, whichever comes first.  In that case
+    /// the `next_key` field of the result is set to the key the following page
+    /// should start at.  Each page comes with its own proof, which also covers
+    /// the `next_key` item.  A page can't be returned if its first item alone
+    /// exceeds the state size limit.
+    ///
+    /// Without pagination, the whole prefix is returned at once and the
+    /// request fails if the account’s state exceeds the state size limit.
+    pub fn view_state_page(
+        &self,
+        state_update: &TrieUpdate,
+        account_id: &AccountId,
+        prefix: &[u8],
+        start_key: Option<&[u8]>,
+        limit: Option<u64>,
+        include_proof: bool,
+    ) -> Result<ViewStateResult, errors::ViewStateError> {
+        let paginated = start_key.is_some() || limit.is_some();
         match get_account(state_update, account_id)? {
             Some(account) => {
-                let code_len = get_code(state_update, account_id, Some(account.code_hash()))?
-                    .map(|c| c.code().len() as u64)
-                    .unwrap_or_default();
-                if let Some(limit) = self.state_size_limit {
+                if let (Some(limit), false) = (self.state_size_limit, paginated) {
+                    let code_len = get_code(state_update, account_id, Some(account.code_hash()))?
+                        .map(|c| c.code().len() as u64)
+                        .unwrap_or_default();
                     if account.storage_usage().saturating_sub(code_len) > limit {
                         return Err(errors::ViewStateError::AccountStateTooLarge {
                             requested_account_id: account_id.clone(),
@@ -141,21 +170,41 @@ impl TrieViewer {
         };
 
         let mut values = vec![];
+        let mut next_key = None;
         let query = trie_key_parsers::get_raw_prefix_for_contract_data(account_id, prefix);
         let acc_sep_len = query.len() - prefix.len();
         let mut iter = state_update.trie().iter()?;
         iter.remember_visited_nodes(include_proof);
-        iter.seek_prefix(&query)?;
+        match start_key {
+            Some(start_key) if start_key > prefix => iter
+                .seek(trie_key_parsers::get_raw_prefix_for_contract_data(account_id, start_key))?,
+            _ => iter.seek_prefix(&query)?,
+        }
+        let mut total_size = 0u64;
         for item in &mut iter {
             let (key, value) = item?;
-            values.push(StateItem {
-                key: key[acc_sep_len..].to_vec(),
-                value: value,
-                proof: vec![],
-            });
+            if !key.starts_with(&query) {
+                break;
+            }
+            let key = key[acc_sep_len..].to_vec();
+            let size = (key.len() + value.len()) as u64;
+            let over_limit = limit.map_or(false, |limit| values.len() as u64 >= limit);
+            let over_size = paginated
+                && self.state_size_limit.map_or(false, |size_limit| total_size + size > size_limit);
+            if over_size && values.is_empty() {
+                return Err(errors::ViewStateError::AccountStateTooLarge {
+                    requested_account_id: account_id.clone(),
+                });
+            }
+            if over_limit || over_size {
+                next_key = Some(key);
+                break;
+            }
+            total_size += size;
+            values.push(StateItem { key, value, proof: vec![] });
         }
         let proof = iter.into_visited_nodes();
-        Ok(ViewStateResult { values, proof })
+        Ok(ViewStateResult { values, proof, next_key })
     }
 
     pub fn call_function(
@@ -169,8 +218,88 @@ impl TrieViewer {
         epoch_info_provider: &dyn EpochInfoProvider,
     ) -> Result<Vec<u8>, errors::CallFunctionError> {
         let now = Instant::now();
+        let (outcome, _, _) = self.execute_view_call(
+            &mut state_update,
+            view_state,
+            contract_id,
+            method_name,
+            args,
+            epoch_info_provider,
+            false,
+            false,
+        )?;
+        let elapsed = now.elapsed();
+        let time_ms =
+            (elapsed.as_secs() as f64 / 1_000.0) + f64::from(elapsed.subsec_nanos()) / 1_000_000.0;
+        let time_str = format!("{:.*}ms", 2, time_ms);
+
+        if let Some(err) = outcome.aborted {
+            logs.extend(outcome.logs);
+            let message = format!("wasm execution failed with error: {:?}", err);
+            debug!(target: "runtime", "(exec time {}) {}", time_str, message);
+            Err(errors::CallFunctionError::VMError { error_message: message })
+        } else {
+            debug!(target: "runtime", "(exec time {}) result of execution: {:?}", time_str, outcome);
+            logs.extend(outcome.logs);
+            let result = match outcome.return_data {
+                ReturnData::Value(buf) => buf,
+                ReturnData::ReceiptIndex(_) | ReturnData::None => vec![],
+            };
+            Ok(result)
+        }
+    }
+
+    /// Executes a view function call like [`Self::call_function`] does, but records every host
+    /// function call made by the contract and, if requested, an extended gas profile.
+    ///
+    /// Failure of the contract itself is not an error; it is reported in the returned trace.
+    pub fn trace_function_call(
+        &self,
+        mut state_update: TrieUpdate,
+        view_state: ViewApplyState,
+        contract_id: &AccountId,
+        method_name: &str,
+        args: &[u8],
+        epoch_info_provider: &dyn EpochInfoProvider,
+        extended_gas_profile: bool,
+    ) -> Result<FunctionCallTraceView, errors::CallFunctionError> {
+        let (outcome, host_calls, extended_gas_profile) = self.execute_view_call(
+            &mut state_update,
+            view_state,
+            contract_id,
+            method_name,
+            args,
+            epoch_info_provider,
+            true,
+            extended_gas_profile,
+        )?;
+        Ok(FunctionCallTraceView {
+            contract_id: contract_id.clone(),
+            method_name: method_name.to_string(),
+            gas_burnt: outcome.burnt_gas,
+            logs: outcome.logs,
+            error: outcome.aborted.as_ref().map(ToString::to_string),
+            host_calls,
+            extended_gas_profile,
+        })
+    }
+
+    fn execute_view_call(
+        &self,
+        state_update: &mut TrieUpdate,
+        view_state: ViewApplyState,
+        contract_id: &AccountId,
+        method_name: &str,
+        args: &[u8],
+        epoch_info_provider: &dyn EpochInfoProvider,
+        trace: bool,
+        extended_gas_profile: bool,
+    ) -> Result<
+        (VMOutcome, Vec<HostFunctionCallView>, Option<ExtendedGasProfileView>),
+        errors::CallFunctionError,
+    > {
         let root = state_update.get_root().clone();
-        let mut account = get_account(&state_update, contract_id)?.ok_or_else(|| {
+        let mut account = get_account(state_update, contract_id)?.ok_or_else(|| {
             errors::CallFunctionError::AccountDoesNotExist {
                 requested_account_id: contract_id.clone(),
             }
@@ -180,7 +309,7 @@ impl TrieViewer {
         let public_key = PublicKey::empty(KeyType::ED25519);
         let empty_hash = CryptoHash::default();
         let mut runtime_ext = RuntimeExt::new(
-            &mut state_update,
+            state_update,
             contract_id,
             &empty_hash,
             &view_state.epoch_id,
@@ -189,6 +318,12 @@ impl TrieViewer {
             epoch_info_provider,
             view_state.current_protocol_version,
         );
+        if trace {
+            runtime_ext.enable_execution_trace();
+        }
+        if extended_gas_profile {
+            runtime_ext.enable_extended_profile();
+        }
         let config_store = RuntimeConfigStore::new(None);
         let config = config_store.get_config(PROTOCOL_VERSION);
         let apply_state = ApplyState {
@@ -208,6 +343,8 @@ impl TrieViewer {
             is_new_chunk: false,
             migration_data: Arc::new(MigrationData::default()),
             migration_flags: MigrationFlags::default(),
+            verify_signatures: true,
+            process_delayed_receipts: true,
         };
         let action_receipt = ActionReceipt {
             signer_id: originator_id.clone(),
@@ -237,24 +374,7 @@ impl TrieViewer {
             Some(ViewConfig { max_gas_burnt: self.max_gas_burnt_view }),
         )
         .map_err(|e| errors::CallFunctionError::InternalError { error_message: e.to_string() })?;
-        let elapsed = now.elapsed();
-        let time_ms =
-            (elapsed.as_secs() as f64 / 1_000.0) + f64::from(elapsed.subsec_nanos()) / 1_000_000.0;
-        let time_str = format!("{:.*}ms", 2, time_ms);
-
-        if let Some(err) = outcome.aborted {
-            logs.extend(outcome.logs);
-            let message = format!("wasm execution failed with error: {:?}", err);
-            debug!(target: "runtime", "(exec time {}) {}", time_str, message);
-            Err(errors::CallFunctionError::VMError { error_message: message })
-        } else {
-            debug!(target: "runtime", "(exec time {}) result of execution: {:?}", time_str, outcome);
-            logs.extend(outcome.logs);
-            let result = match outcome.return_data {
-                ReturnData::Value(buf) => buf,
-                ReturnData::ReceiptIndex(_) | ReturnData::None => vec![],
-            };
-            Ok(result)
-        }
+        let extended_gas_profile = runtime_ext.take_extended_gas_profile(account.code_hash());
+        Ok((outcome, runtime_ext.take_execution_trace(), extended_gas_profile))
     }
 }
@@ -0,0 +1,203 @@
+use crate::{CallOutcome, ContractRunner, ContractState};
+use borsh::BorshSerialize;
+use near_crypto::{KeyType, PublicKey};
+use near_primitives::runtime::config::RuntimeConfig;
+use near_primitives::runtime::config_store::RuntimeConfigStore;
+use near_primitives::serialize::{from_base64, to_base64};
+use near_primitives::types::{AccountId, Balance, BlockHeight, EpochHeight, Gas, StorageUsage};
+use near_primitives::version::{ProtocolVersion, PROTOCOL_VERSION};
+use near_vm_logic::types::PromiseResult;
+use near_vm_logic::{VMContext, ViewConfig};
+use std::path::PathBuf;
+
+/// Executes a contract method locally, without a node.
+#[derive(clap::Parser)]
+pub struct RunContractCommand {
+    /// Path to the contract’s wasm file.
+    #[clap(long)]
+    wasm_file: PathBuf,
+    /// Name of the method to call.
+    #[clap(long)]
+    method: String,
+    /// Arguments of the call, usually JSON.
+    #[clap(long, default_value = "", conflicts_with = "args-base64")]
+    args: String,
+    /// Arguments of the call encoded as base64, for binary arguments.
+    #[clap(long)]
+    args_base64: Option<String>,
+    /// Initial contract state.  Either a JSON object mapping base64 encoded
+    /// keys to base64 encoded values or state records of the contract account
+    /// as written by `neard view-state dump-state`.
+    #[clap(long)]
+    state_json: Option<PathBuf>,
+    /// Account the contract is deployed to.
+    #[clap(long, default_value = "contract.test")]
+    account_id: AccountId,
+    /// Signer of the transaction, defaults to the predecessor.
+    #[clap(long)]
+    signer_id: Option<AccountId>,
+    /// Public key of the signer.
+    #[clap(long)]
+    signer_public_key: Option<PublicKey>,
+    /// Account calling the method, defaults to the contract account.
+    #[clap(long)]
+    predecessor_id: Option<AccountId>,
+    /// Deposit attached to the call, in yoctoNEAR.
+    #[clap(long, default_value = "0")]
+    attached_deposit: Balance,
+    /// Gas attached to the call.
+    #[clap(long, default_value = "300000000000000")]
+    prepaid_gas: Gas,
+    #[clap(long, default_value = "1")]
+    block_height: BlockHeight,
+    /// Block timestamp in nanoseconds.
+    #[clap(long, default_value = "0")]
+    block_timestamp: u64,
+    #[clap(long, default_value = "1")]
+    epoch_height: EpochHeight,
+    /// Balance of the contract account, in yoctoNEAR.
+    #[clap(long, default_value = "100000000000000000000000000")]
+    account_balance: Balance,
+    /// Storage usage of the contract account, in bytes.
+    #[clap(long, default_value = "0")]
+    storage_usage: StorageUsage,
+    /// Executes the method as a view call, which can’t modify the state.
+    #[clap(long)]
+    view: bool,
+    /// Results of the promises the call depends on, for callbacks.  Each is
+    /// a base64 encoded value or `failed`.
+    #[clap(long)]
+    promise_result: Vec<String>,
+    /// Protocol version whose VM and runtime config are used.
+    #[clap(long, default_value_t = PROTOCOL_VERSION)]
+    protocol_version: ProtocolVersion,
+    /// Chain whose runtime config is used.
+    #[clap(long, default_value = "mainnet")]
+    chain_id: String,
+    /// Reports gas used by each host function and wasm function.
+    #[clap(long)]
+    extended_gas_profile: bool,
+    /// Prints the outcome as JSON.
+    #[clap(long)]
+    json: bool,
+}
+
+impl RunContractCommand {
+    pub fn run(&self) -> anyhow::Result<()> {
+        let code = std::fs::read(&self.wasm_file)?;
+        let state = match &self.state_json {
+            Some(path) => {
+                ContractState::from_json(&std::fs::read_to_string(path)?, &self.account_id)?
+            }
+            None => ContractState::default(),
+        };
+        let config_store = match self.chain_id.as_str() {
+            "testnet" => RuntimeConfigStore::new(Some(&RuntimeConfig::initial_testnet_config())),
+            _ => RuntimeConfigStore::new(None),
+        };
+        let mut runner = ContractRunner::new(code)
+            .with_state(state)
+            .with_protocol_version(&config_store, self.protocol_version)
+            .with_extended_gas_profile(self.extended_gas_profile);
+
+        let input = match &self.args_base64 {
+            Some(args) => from_base64(args)
+                .map_err(|err| anyhow::anyhow!("invalid base64 arguments: {err}"))?,
+            None => self.args.as_bytes().to_vec(),
+        };
+        let promise_results = self
+            .promise_result
+            .iter()
+            .map(|result| match result.as_str() {
+                "failed" => Ok(PromiseResult::Failed),
+                value => from_base64(value)
+                    .map(PromiseResult::Successful)
+                    .map_err(|err| anyhow::anyhow!("invalid promise result {value}: {err}")),
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let predecessor_id = self.predecessor_id.clone().unwrap_or_else(|| self.account_id.clone());
+        let signer_public_key =
+            self.signer_public_key.clone().unwrap_or_else(|| PublicKey::empty(KeyType::ED25519));
+        let context = VMContext {
+            current_account_id: self.account_id.clone(),
+            signer_account_id: self.signer_id.clone().unwrap_or_else(|| predecessor_id.clone()),
+            signer_account_pk: signer_public_key.try_to_vec()?,
+            predecessor_account_id: predecessor_id,
+            input,
+            block_height: self.block_height,
+            block_timestamp: self.block_timestamp,
+            epoch_height: self.epoch_height,
+            account_balance: self.account_balance,
+            account_locked_balance: 0,
+            storage_usage: self.storage_usage,
+            attached_deposit: self.attached_deposit,
+            prepaid_gas: self.prepaid_gas,
+            random_seed: vec![0; 32],
+            view_config: self.view.then_some(ViewConfig { max_gas_burnt: self.prepaid_gas }),
+            output_data_receivers: vec![],
+        };
+
+        let outcome = runner.call(&self.method, context, &promise_results)?;
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&outcome)?);
+        } else {
+            print_outcome(&outcome)?;
+        }
+        Ok(())
+    }
+}
+
+fn print_outcome(outcome: &CallOutcome) -> anyhow::Result<()> {
+    match (&outcome.error, &outcome.return_value, outcome.return_receipt_index) {
+        (Some(err), _, _) => println!("Error: {err}"),
+        (None, Some(value), _) => println!("Return value: {}", display_bytes(value)),
+        (None, None, Some(index)) => println!("Returns result of receipt #{index}"),
+        (None, None, None) => println!("No return value"),
+    }
+    for log in &outcome.logs {
+        println!("Log: {log}");
+    }
+    println!("Burnt gas: {}", outcome.burnt_gas);
+    println!("Used gas: {}", outcome.used_gas);
+    println!("Balance: {}", outcome.balance);
+    println!("Storage usage: {}", outcome.storage_usage);
+    if !outcome.state_changes.is_empty() {
+        println!("State changes:");
+        for change in &outcome.state_changes {
+            let value = |value: &Option<Vec<u8>>| match value {
+                Some(value) => display_bytes(value),
+                None => "<none>".to_string(),
+            };
+            println!(
+                "  {}: {} -> {}",
+                display_bytes(&change.key),
+                value(&change.old_value),
+                value(&change.new_value)
+            );
+        }
+    }
+    for (index, receipt) in outcome.receipts.iter().enumerate() {
+        println!(
+            "Receipt #{index} to {}: {}",
+            receipt.receiver_id,
+            serde_json::to_string(&receipt.actions)?
+        );
+    }
+    println!("Gas profile:");
+    for cost in &outcome.gas_profile {
+        println!("  {:<40} {:>20}", cost.cost, cost.gas_used);
+    }
+    if let Some(profile) = &outcome.extended_gas_profile {
+        println!("Extended gas profile: {}", serde_json::to_string_pretty(profile)?);
+    }
+    Ok(())
+}
+
+/// Formats the bytes as a string if they're printable UTF-8, as base64
+/// otherwise.
+fn display_bytes(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(s) if !s.chars().any(char::is_control) => format!("{s:?}"),
+        _ => format!("base64:{}", to_base64(bytes)),
+    }
+}
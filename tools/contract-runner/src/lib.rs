@@ -0,0 +1,405 @@
+//! Executes contract methods locally, without a node.
+//!
+//! The contract runs in the same VM and with the same runtime config as on
+//! chain, but its storage is an in-memory map and nothing outside of the
+//! contract is simulated: receipts created by the call are only reported, not
+//! applied, and balances are not checked.  This makes it possible to test
+//! a contract call in milliseconds, e.g. as a regression test in CI.
+
+use near_primitives::contract::ContractCode;
+use near_primitives::hash::CryptoHash;
+use near_primitives::receipt::DataReceiver;
+use near_primitives::runtime::config::RuntimeConfig;
+use near_primitives::runtime::config_store::RuntimeConfigStore;
+use near_primitives::serialize::{from_base64, option_base64_format};
+use near_primitives::state_record::StateRecord;
+use near_primitives::transaction::ExecutionMetadata;
+use near_primitives::types::{AccountId, Balance, Gas, StorageUsage};
+use near_primitives::version::{ProtocolVersion, PROTOCOL_VERSION};
+use near_primitives::views::{
+    ActionView, CostGasUsed, ExecutionMetadataView, ExtendedGasProfileView,
+};
+use near_vm_logic::mocks::mock_external::MockedExternal;
+use near_vm_logic::types::PromiseResult;
+use near_vm_logic::{ReturnData, VMContext};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+pub mod cli;
+
+/// Contents of a contract’s storage, keyed by storage keys as seen by the
+/// contract.
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct ContractState {
+    data: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+impl ContractState {
+    /// Parses the state from JSON, which is either:
+    /// - an object mapping base64 encoded keys to base64 encoded values,
+    /// - an array of state records as written by `neard view-state dump-state
+    ///   --stream`, from which data records of `account_id` are taken, or
+    /// - a genesis file with such records as written by `neard view-state
+    ///   dump-state`.
+    pub fn from_json(json: &str, account_id: &AccountId) -> anyhow::Result<Self> {
+        let mut value: serde_json::Value = serde_json::from_str(json)?;
+        if let Some(records) = value.get_mut("records").filter(|records| records.is_array()) {
+            value = records.take();
+        }
+        let mut state = Self::default();
+        match value {
+            serde_json::Value::Array(_) => {
+                for record in serde_json::from_value::<Vec<StateRecord>>(value)? {
+                    if let StateRecord::Data { account_id: record_account_id, data_key, value } =
+                        record
+                    {
+                        if &record_account_id == account_id {
+                            state.insert(data_key, value);
+                        }
+                    }
+                }
+            }
+            serde_json::Value::Object(entries) => {
+                for (key, value) in entries {
+                    let value = value
+                        .as_str()
+                        .ok_or_else(|| anyhow::anyhow!("value of key {key} is not a string"))?;
+                    let key = from_base64(&key)
+                        .map_err(|err| anyhow::anyhow!("invalid base64 key {key}: {err}"))?;
+                    let value = from_base64(value)
+                        .map_err(|err| anyhow::anyhow!("invalid base64 value {value}: {err}"))?;
+                    state.insert(key, value);
+                }
+            }
+            _ => anyhow::bail!("expected an object or an array of state records"),
+        }
+        Ok(state)
+    }
+
+    pub fn insert(&mut self, key: Vec<u8>, value: Vec<u8>) {
+        self.data.insert(key, value);
+    }
+
+    pub fn get(&self, key: &[u8]) -> Option<&[u8]> {
+        self.data.get(key).map(Vec::as_slice)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&[u8], &[u8])> {
+        self.data.iter().map(|(key, value)| (key.as_slice(), value.as_slice()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
+/// Change of a single storage key made by a call.
+#[derive(serde::Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct StateChange {
+    #[serde(with = "near_primitives::serialize::base64_format")]
+    pub key: Vec<u8>,
+    /// Value before the call, `None` if the key was added.
+    #[serde(with = "option_base64_format")]
+    pub old_value: Option<Vec<u8>>,
+    /// Value after the call, `None` if the key was removed.
+    #[serde(with = "option_base64_format")]
+    pub new_value: Option<Vec<u8>>,
+}
+
+/// Receipt created by a call.
+#[derive(serde::Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct CreatedReceipt {
+    pub receiver_id: AccountId,
+    pub actions: Vec<ActionView>,
+    pub input_data_ids: Vec<CryptoHash>,
+    pub output_data_receivers: Vec<DataReceiver>,
+}
+
+/// Everything a call did.
+#[derive(serde::Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct CallOutcome {
+    /// Value returned by the method.
+    #[serde(with = "option_base64_format")]
+    pub return_value: Option<Vec<u8>>,
+    /// Index into `receipts` of the receipt whose result is returned instead
+    /// of a value.
+    pub return_receipt_index: Option<u64>,
+    /// Why the call failed, if it did.
+    pub error: Option<String>,
+    pub logs: Vec<String>,
+    pub burnt_gas: Gas,
+    pub used_gas: Gas,
+    /// Balance of the account after the call.
+    pub balance: Balance,
+    /// Storage usage of the account after the call.
+    pub storage_usage: StorageUsage,
+    /// Changes to the storage in key order.  Empty if the call failed since
+    /// the changes are then discarded.
+    pub state_changes: Vec<StateChange>,
+    pub receipts: Vec<CreatedReceipt>,
+    pub gas_profile: Vec<CostGasUsed>,
+    /// Only present if enabled with [`ContractRunner::with_extended_gas_profile`].
+    pub extended_gas_profile: Option<ExtendedGasProfileView>,
+}
+
+/// Runs calls of a single contract on top of an in-memory state.
+pub struct ContractRunner {
+    code: ContractCode,
+    state: ContractState,
+    runtime_config: Arc<RuntimeConfig>,
+    protocol_version: ProtocolVersion,
+    extended_gas_profile: bool,
+}
+
+impl ContractRunner {
+    /// Creates a runner with empty state using the mainnet runtime config of
+    /// the latest protocol version.
+    pub fn new(code: Vec<u8>) -> Self {
+        Self {
+            code: ContractCode::new(code, None),
+            state: ContractState::default(),
+            runtime_config: RuntimeConfigStore::new(None).get_config(PROTOCOL_VERSION).clone(),
+            protocol_version: PROTOCOL_VERSION,
+            extended_gas_profile: false,
+        }
+    }
+
+    pub fn with_state(mut self, state: ContractState) -> Self {
+        self.state = state;
+        self
+    }
+
+    /// Executes calls with the VM and runtime config of given protocol
+    /// version from `config_store`.
+    pub fn with_protocol_version(
+        mut self,
+        config_store: &RuntimeConfigStore,
+        protocol_version: ProtocolVersion,
+    ) -> Self {
+        self.runtime_config = config_store.get_config(protocol_version).clone();
+        self.protocol_version = protocol_version;
+        self
+    }
+
+    /// Reports gas per host function and per wasm function of the contract.
+    /// Executes the contract without the compilation cache, so calls are
+    /// slower.
+    pub fn with_extended_gas_profile(mut self, enabled: bool) -> Self {
+        self.extended_gas_profile = enabled;
+        self
+    }
+
+    /// State of the contract after all the successful calls so far.
+    pub fn state(&self) -> &ContractState {
+        &self.state
+    }
+
+    /// Calls the method.
+    ///
+    /// Same as on chain, changes to the storage are kept only if the call
+    /// succeeds, so subsequent calls see them.  An error is returned only if
+    /// the call couldn’t be executed at all, failures of the contract itself
+    /// are reported in [`CallOutcome::error`].
+    pub fn call(
+        &mut self,
+        method_name: &str,
+        context: VMContext,
+        promise_results: &[PromiseResult],
+    ) -> anyhow::Result<CallOutcome> {
+        let mut ext = MockedExternal::new();
+        ext.fake_trie = self.state.data.clone().into_iter().collect();
+        if self.extended_gas_profile {
+            ext.extended_profile = Some(Default::default());
+        }
+        let outcome = near_vm_runner::run(
+            &self.code,
+            method_name,
+            &mut ext,
+            context,
+            &self.runtime_config.wasm_config,
+            &self.runtime_config.fees,
+            promise_results,
+            self.protocol_version,
+            None,
+        )?;
+
+        let mut state_changes = Vec::new();
+        if outcome.aborted.is_none() {
+            let new_data: BTreeMap<_, _> = ext.fake_trie.into_iter().collect();
+            for (key, old_value) in &self.state.data {
+                let new_value = new_data.get(key);
+                if new_value != Some(old_value) {
+                    state_changes.push(StateChange {
+                        key: key.clone(),
+                        old_value: Some(old_value.clone()),
+                        new_value: new_value.cloned(),
+                    });
+                }
+            }
+            for (key, new_value) in &new_data {
+                if !self.state.data.contains_key(key) {
+                    state_changes.push(StateChange {
+                        key: key.clone(),
+                        old_value: None,
+                        new_value: Some(new_value.clone()),
+                    });
+                }
+            }
+            state_changes.sort_by(|a, b| a.key.cmp(&b.key));
+            self.state.data = new_data;
+        }
+
+        let (return_value, return_receipt_index) = match outcome.return_data {
+            ReturnData::Value(value) => (Some(value), None),
+            ReturnData::ReceiptIndex(index) => (None, Some(index)),
+            ReturnData::None => (None, None),
+        };
+        let receipts = outcome
+            .action_receipts
+            .into_iter()
+            .map(|(receiver_id, receipt)| CreatedReceipt {
+                receiver_id,
+                actions: receipt.actions.into_iter().map(ActionView::from).collect(),
+                input_data_ids: receipt.input_data_ids,
+                output_data_receivers: receipt.output_data_receivers,
+            })
+            .collect();
+        let gas_profile = ExecutionMetadataView::from(ExecutionMetadata::V3(outcome.profile))
+            .gas_profile
+            .unwrap_or_default();
+        let extended_gas_profile = ext
+            .extended_profile
+            .map(|profile| near_vm_runner::extended_gas_profile_view(&self.code, &profile));
+        Ok(CallOutcome {
+            return_value,
+            return_receipt_index,
+            error: outcome.aborted.map(|err| err.to_string()),
+            logs: outcome.logs,
+            burnt_gas: outcome.burnt_gas,
+            used_gas: outcome.used_gas,
+            balance: outcome.balance,
+            storage_usage: outcome.storage_usage,
+            state_changes,
+            receipts,
+            gas_profile,
+            extended_gas_profile,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+    use near_crypto::{KeyType, PublicKey};
+
+    fn context(input: &[u8]) -> VMContext {
+        VMContext {
+            current_account_id: "contract.test".parse().unwrap(),
+            signer_account_id: "signer.test".parse().unwrap(),
+            signer_account_pk: PublicKey::empty(KeyType::ED25519).try_to_vec().unwrap(),
+            predecessor_account_id: "signer.test".parse().unwrap(),
+            input: input.to_vec(),
+            block_height: 1,
+            block_timestamp: 0,
+            epoch_height: 1,
+            account_balance: 10u128.pow(24),
+            account_locked_balance: 0,
+            storage_usage: 100,
+            attached_deposit: 0,
+            prepaid_gas: 300 * 10u64.pow(12),
+            random_seed: vec![],
+            view_config: None,
+            output_data_receivers: vec![],
+        }
+    }
+
+    /// Contract whose `set` method stores its input under key "k", whose
+    /// `get` method returns it and whose `fail` method aborts after writing.
+    const CONTRACT: &str = r#"
+        (module
+          (import "env" "input" (func $input (param i64)))
+          (import "env" "storage_write" (func $storage_write (param i64 i64 i64 i64 i64) (result i64)))
+          (import "env" "storage_read" (func $storage_read (param i64 i64 i64) (result i64)))
+          (import "env" "value_return" (func $value_return (param i64 i64)))
+          (import "env" "read_register" (func $read_register (param i64 i64)))
+          (import "env" "register_len" (func $register_len (param i64) (result i64)))
+          (import "env" "log_utf8" (func $log_utf8 (param i64 i64)))
+          (import "env" "panic" (func $panic))
+          (memory (export "memory") 1)
+          (data (i32.const 0) "k")
+          (data (i32.const 8) "called")
+          (func (export "set")
+            (call $input (i64.const 0))
+            (call $read_register (i64.const 0) (i64.const 64))
+            (drop (call $storage_write
+              (i64.const 1) (i64.const 0) (call $register_len (i64.const 0)) (i64.const 64) (i64.const 1)))
+            (call $log_utf8 (i64.const 6) (i64.const 8)))
+          (func (export "get")
+            (drop (call $storage_read (i64.const 1) (i64.const 0) (i64.const 0)))
+            (call $read_register (i64.const 0) (i64.const 64))
+            (call $value_return (call $register_len (i64.const 0)) (i64.const 64)))
+          (func (export "fail")
+            (drop (call $storage_write
+              (i64.const 1) (i64.const 0) (i64.const 1) (i64.const 0) (i64.const 1)))
+            (call $panic)))
+        "#;
+
+    #[test]
+    fn test_calls() {
+        let mut runner =
+            ContractRunner::new(wat::parse_str(CONTRACT).unwrap()).with_extended_gas_profile(true);
+
+        let outcome = runner.call("set", context(b"hello"), &[]).unwrap();
+        assert_eq!(outcome.error, None);
+        assert_eq!(outcome.logs, ["called"]);
+        assert_eq!(
+            outcome.state_changes,
+            [StateChange {
+                key: b"k".to_vec(),
+                old_value: None,
+                new_value: Some(b"hello".to_vec())
+            }]
+        );
+        assert!(outcome.burnt_gas > 0);
+        assert!(outcome.gas_profile.iter().any(|cost| cost.cost == "STORAGE_WRITE_BASE"));
+        let extended = outcome.extended_gas_profile.unwrap();
+        assert!(extended.host_functions.iter().any(|stats| stats.name == "storage_write"));
+
+        let outcome = runner.call("get", context(b""), &[]).unwrap();
+        assert_eq!(outcome.return_value.as_deref(), Some(&b"hello"[..]));
+        assert!(outcome.state_changes.is_empty());
+
+        // Changes of failed calls are discarded.
+        let outcome = runner.call("fail", context(b""), &[]).unwrap();
+        assert!(outcome.error.is_some());
+        assert!(outcome.state_changes.is_empty());
+        assert_eq!(runner.state().get(b"k"), Some(&b"hello"[..]));
+
+        let outcome = runner.call("missing", context(b""), &[]).unwrap();
+        assert!(outcome.error.unwrap().contains("MethodNotFound"));
+    }
+
+    #[test]
+    fn test_state_from_json() {
+        let account_id: AccountId = "contract.test".parse().unwrap();
+        let state = ContractState::from_json(r#"{"aw==": "aGVsbG8="}"#, &account_id).unwrap();
+        assert_eq!(state.get(b"k"), Some(&b"hello"[..]));
+
+        let records = r#"[
+            {"Data": {"account_id": "contract.test", "data_key": "aw==", "value": "aGVsbG8="}},
+            {"Data": {"account_id": "other.test", "data_key": "bA==", "value": "aGVsbG8="}},
+            {"Contract": {"account_id": "contract.test", "code": ""}}
+        ]"#;
+        let state = ContractState::from_json(records, &account_id).unwrap();
+        assert_eq!(state.len(), 1);
+        assert_eq!(state.get(b"k"), Some(&b"hello"[..]));
+
+        let genesis = format!(r#"{{"chain_id": "test", "records": {records}}}"#);
+        assert_eq!(ContractState::from_json(&genesis, &account_id).unwrap(), state);
+    }
+}
@@ -76,6 +76,19 @@ pub fn map_records<P: AsRef<Path>>(
                 }
                 records_seq.serialize_element(&r).unwrap();
             }
+            StateRecord::PromiseYieldReceipt { account_id, .. } => {
+                if account_id.is_implicit() {
+                    *account_id = crate::key_mapping::map_account(&account_id, secret.as_ref());
+                }
+                records_seq.serialize_element(&r).unwrap();
+            }
+            StateRecord::PromiseYieldTimeout(timeout) => {
+                if timeout.account_id.is_implicit() {
+                    timeout.account_id =
+                        crate::key_mapping::map_account(&timeout.account_id, secret.as_ref());
+                }
+                records_seq.serialize_element(&r).unwrap();
+            }
         };
     })?;
     records_seq.end()?;
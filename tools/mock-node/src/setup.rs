@@ -419,8 +419,10 @@ mod tests {
         let dir1 = tempfile::Builder::new().prefix("test1").tempdir().unwrap();
         let mut near_config1 = load_test_config("", open_port(), genesis);
         near_config1.client_config.min_num_peers = 1;
-        near_config1.client_config.tracked_shards =
-            (0..near_config1.genesis.config.shard_layout.num_shards()).collect();
+        near_config1
+            .client_config
+            .tracked_shards
+            .update((0..near_config1.genesis.config.shard_layout.num_shards()).collect());
         let network_config = MockNetworkConfig::with_delay(Duration::from_millis(10));
         run_actix(async move {
             let MockNode { view_client, .. } = setup_mock_node(
@@ -0,0 +1,128 @@
+//! Remote signing of validator messages.  The validator key is kept by
+//! a separate signer daemon, see `SignerServer`, and the node talks to it
+//! over a Unix socket through `near_validator_signer::RemoteValidatorSigner`.
+//! The daemon keeps the same signing history as the node and refuses to sign
+//! conflicting blocks, chunks or approvals.
+
+mod server;
+
+pub use server::SignerServer;
+
+#[cfg(test)]
+mod tests {
+    use crate::SignerServer;
+    use borsh::BorshSerialize;
+    use near_crypto::{InMemorySigner, KeyType, Signer};
+    use near_primitives::block::{Approval, ApprovalInner, BlockHeader};
+    use near_primitives::block_header::BlockHeaderInnerLite;
+    use near_primitives::hash::CryptoHash;
+    use near_primitives::sharding::{ChunkHeaderToSign, ShardChunkHeaderInnerV1};
+    use near_primitives::types::{BlockHeight, EpochId, ShardId};
+    use near_primitives::validator_signer::{ValidatorSigner, ValidatorSignerError};
+    use near_validator_signer::{RemoteSignerConfig, RemoteValidatorSigner};
+    use std::os::unix::net::UnixListener;
+    use std::time::Duration;
+
+    fn block_inner_lite(height: BlockHeight, seed: &[u8]) -> Vec<u8> {
+        BlockHeaderInnerLite {
+            height,
+            epoch_id: EpochId::default(),
+            next_epoch_id: EpochId::default(),
+            prev_state_root: CryptoHash::hash_bytes(seed),
+            outcome_root: CryptoHash::default(),
+            timestamp: 0,
+            next_bp_hash: CryptoHash::default(),
+            block_merkle_root: CryptoHash::default(),
+        }
+        .try_to_vec()
+        .unwrap()
+    }
+
+    fn chunk_header(
+        height_created: BlockHeight,
+        shard_id: ShardId,
+        seed: &[u8],
+    ) -> ChunkHeaderToSign {
+        ChunkHeaderToSign::V1(ShardChunkHeaderInnerV1 {
+            prev_block_hash: CryptoHash::hash_bytes(seed),
+            prev_state_root: CryptoHash::default(),
+            outcome_root: CryptoHash::default(),
+            encoded_merkle_root: CryptoHash::default(),
+            encoded_length: 0,
+            height_created,
+            shard_id,
+            gas_used: 0,
+            gas_limit: 0,
+            balance_burnt: 0,
+            outgoing_receipts_root: CryptoHash::default(),
+            tx_root: CryptoHash::default(),
+            validator_proposals: vec![],
+        })
+    }
+
+    fn start_signer(dir: &std::path::Path) -> (InMemorySigner, RemoteValidatorSigner) {
+        let key_file = dir.join("validator_key.json");
+        let key = InMemorySigner::from_seed("test".parse().unwrap(), KeyType::ED25519, "test");
+        key.write_to_file(&key_file).unwrap();
+        let server = SignerServer::open(&key_file, &dir.join("signing_history.json")).unwrap();
+        let socket_path = dir.join("signer.sock");
+        let listener = UnixListener::bind(&socket_path).unwrap();
+        std::thread::spawn(move || server.serve(listener));
+
+        let config = RemoteSignerConfig { socket_path, timeout: Duration::from_secs(10) };
+        (key, RemoteValidatorSigner::connect(config).unwrap())
+    }
+
+    fn assert_refused<T: std::fmt::Debug>(result: Result<T, ValidatorSignerError>) {
+        match result {
+            Err(ValidatorSignerError::Refused(_)) => {}
+            result => panic!("Expected a refusal, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_remote_signer() {
+        let dir = tempfile::tempdir().unwrap();
+        let (key, signer) = start_signer(dir.path());
+        assert_eq!(signer.validator_id().as_str(), "test");
+        assert_eq!(signer.public_key(), key.public_key());
+        let epoch_id = EpochId::default();
+
+        let endorsement = ApprovalInner::Endorsement(CryptoHash::hash_bytes(b"a"));
+        let signature = signer.sign_approval(&endorsement, 10, &epoch_id).unwrap();
+        let data = Approval::get_data_for_sig(&endorsement, 10);
+        assert!(signature.verify(&data, &key.public_key()));
+
+        let prev_hash = CryptoHash::hash_bytes(b"prev");
+        let inner_lite = block_inner_lite(10, b"a");
+        let (hash, signature) =
+            signer.sign_block_header_parts(prev_hash, &inner_lite, &[]).unwrap();
+        assert_eq!(hash, BlockHeader::compute_hash(prev_hash, &inner_lite, &[]));
+        assert!(signature.verify(hash.as_ref(), &key.public_key()));
+
+        let chunk = chunk_header(10, 0, b"a");
+        let signature = signer.sign_chunk_header(&chunk, &epoch_id).unwrap();
+        assert!(signature.verify(chunk.chunk_hash().as_ref(), &key.public_key()));
+
+        let (value, proof) = signer.compute_vrf_with_proof(b"data").unwrap();
+        assert!((value, proof) == key.compute_vrf_with_proof(b"data"));
+    }
+
+    #[test]
+    fn test_remote_signer_refuses_double_sign() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_key, signer) = start_signer(dir.path());
+        let epoch_id = EpochId::default();
+
+        let endorsement = ApprovalInner::Endorsement(CryptoHash::hash_bytes(b"a"));
+        signer.sign_approval(&endorsement, 10, &epoch_id).unwrap();
+        assert_refused(signer.sign_approval(&ApprovalInner::Skip(5), 10, &epoch_id));
+
+        let prev_hash = CryptoHash::hash_bytes(b"prev");
+        signer.sign_block_header_parts(prev_hash, &block_inner_lite(10, b"a"), &[]).unwrap();
+        assert_refused(signer.sign_block_header_parts(prev_hash, &block_inner_lite(10, b"b"), &[]));
+
+        signer.sign_chunk_header(&chunk_header(10, 0, b"a"), &epoch_id).unwrap();
+        assert_refused(signer.sign_chunk_header(&chunk_header(10, 0, b"b"), &epoch_id));
+    }
+}
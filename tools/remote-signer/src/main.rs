@@ -0,0 +1,36 @@
+use clap::Parser;
+use near_remote_signer::SignerServer;
+use std::os::unix::net::UnixListener;
+use std::path::PathBuf;
+
+/// Signs validator messages on behalf of a node configured with
+/// `remote_signer`, so that the validator key never leaves this host.
+#[derive(Parser)]
+struct Cli {
+    /// Validator key file in the same format as `validator_key.json`.
+    #[clap(long)]
+    key_file: PathBuf,
+    /// Unix socket to listen on.
+    #[clap(long)]
+    socket: PathBuf,
+    /// Signing history in the same format as the node's
+    /// `signing_history.json`.  Keep it when moving the signer to another
+    /// machine.
+    #[clap(long)]
+    history_file: PathBuf,
+}
+
+fn main() -> anyhow::Result<()> {
+    let env_filter = near_o11y::EnvFilterBuilder::from_env().verbose(Some("")).finish().unwrap();
+    let _subscriber = near_o11y::default_subscriber(env_filter, &Default::default()).global();
+
+    let cli = Cli::parse();
+    let server = SignerServer::open(&cli.key_file, &cli.history_file)?;
+    if cli.socket.exists() {
+        std::fs::remove_file(&cli.socket)?;
+    }
+    let listener = UnixListener::bind(&cli.socket)?;
+    tracing::info!(target: "remote-signer", socket = %cli.socket.display(), "Listening");
+    server.serve(listener)?;
+    Ok(())
+}
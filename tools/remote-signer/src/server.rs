@@ -0,0 +1,136 @@
+//! Reference signing daemon which keeps the validator key away from the node.
+
+use near_crypto::{InMemorySigner, Signature, Signer};
+use near_primitives::block::{Approval, BlockHeader};
+use near_primitives::hash::CryptoHash;
+use near_primitives::network::AnnounceAccount;
+use near_primitives::validator_signer::ValidatorSignerError;
+use near_validator_signer::protocol::{read_message, write_message, SignRequest, SignResponse};
+use near_validator_signer::SafetyChecker;
+use std::io;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Lengths of data signed for consensus messages: hashes of blocks, chunks
+/// and challenges, and endorsement and skip approvals.
+const CONSENSUS_MESSAGE_LENGTHS: [usize; 3] = [32, 41, 17];
+
+pub struct SignerServer {
+    signer: InMemorySigner,
+    safety: SafetyChecker,
+}
+
+impl SignerServer {
+    /// Loads the validator key and the signing history, which is created if
+    /// it doesn't exist yet.
+    pub fn open(key_file: &Path, history_file: &Path) -> anyhow::Result<Self> {
+        let signer = InMemorySigner::from_file(key_file)?;
+        let safety = SafetyChecker::open(history_file)?;
+        Ok(Self { signer, safety })
+    }
+
+    /// Accepts connections until the listener fails.  Requests from all
+    /// connections are handled one at a time.
+    pub fn serve(self, listener: UnixListener) -> io::Result<()> {
+        let server = Arc::new(Mutex::new(self));
+        for stream in listener.incoming() {
+            let stream = stream?;
+            let server = Arc::clone(&server);
+            std::thread::spawn(move || {
+                if let Err(err) = handle_connection(&server, stream) {
+                    tracing::warn!(target: "remote-signer", ?err, "Connection closed");
+                }
+            });
+        }
+        Ok(())
+    }
+
+    pub fn handle(&mut self, request: SignRequest) -> SignResponse {
+        match self.sign(request) {
+            Ok(response) => response,
+            Err(reason) => {
+                tracing::warn!(target: "remote-signer", %reason, "Refused to sign");
+                SignResponse::Refused(reason)
+            }
+        }
+    }
+
+    fn sign(&mut self, request: SignRequest) -> Result<SignResponse, String> {
+        let signature = match request {
+            SignRequest::GetInfo => {
+                return Ok(SignResponse::Info {
+                    account_id: self.signer.account_id.clone(),
+                    public_key: self.signer.public_key(),
+                })
+            }
+            SignRequest::Telemetry { content } => {
+                if !serde_json::from_str::<serde_json::Value>(&content)
+                    .map_or(false, |value| value.is_object())
+                {
+                    return Err("Telemetry must be a JSON object".to_string());
+                }
+                self.sign_bytes(content.as_bytes())
+            }
+            SignRequest::BlockHeader { prev_hash, inner_lite, inner_rest } => {
+                self.safety.check_block(prev_hash, &inner_lite, &inner_rest).map_err(reason)?;
+                self.sign_bytes(
+                    BlockHeader::compute_hash(prev_hash, &inner_lite, &inner_rest).as_ref(),
+                )
+            }
+            SignRequest::ChunkHeader { header, epoch_id } => {
+                self.safety.check_chunk(&header, &epoch_id).map_err(reason)?;
+                self.sign_bytes(header.chunk_hash().as_ref())
+            }
+            SignRequest::Approval { inner, target_height, epoch_id } => {
+                self.safety.check_approval(&inner, target_height, &epoch_id).map_err(reason)?;
+                self.sign_bytes(&Approval::get_data_for_sig(&inner, target_height))
+            }
+            SignRequest::Challenge { challenge_body } => {
+                self.sign_bytes(CryptoHash::hash_borsh(&challenge_body).as_ref())
+            }
+            SignRequest::AccountAnnounce { account_id, peer_id, epoch_id } => self.sign_bytes(
+                AnnounceAccount::build_header_hash(&account_id, &peer_id, &epoch_id).as_ref(),
+            ),
+            SignRequest::AccountKeyPayload { proto_bytes } => {
+                // Arbitrary bytes must not be usable to obtain a signature of
+                // a consensus message bypassing the checks above.
+                if CONSENSUS_MESSAGE_LENGTHS.contains(&proto_bytes.len()) {
+                    return Err(format!(
+                        "Account key payload of {} bytes may be a consensus message",
+                        proto_bytes.len()
+                    ));
+                }
+                self.sign_bytes(&proto_bytes)
+            }
+            SignRequest::Vrf { data } => {
+                let (value, proof) = self.signer.compute_vrf_with_proof(&data);
+                return Ok(SignResponse::Vrf { value, proof });
+            }
+        };
+        Ok(SignResponse::Signature(signature))
+    }
+
+    fn sign_bytes(&self, data: &[u8]) -> Signature {
+        self.signer.sign(data)
+    }
+}
+
+/// Reason sent back to the node, which reports it as a refusal either way.
+fn reason(err: ValidatorSignerError) -> String {
+    match err {
+        ValidatorSignerError::Refused(reason) | ValidatorSignerError::Unavailable(reason) => reason,
+    }
+}
+
+fn handle_connection(server: &Mutex<SignerServer>, mut stream: UnixStream) -> io::Result<()> {
+    loop {
+        let request = match read_message::<SignRequest>(&mut stream) {
+            Ok(request) => request,
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(err) => return Err(err),
+        };
+        let response = server.lock().unwrap().handle(request);
+        write_message(&mut stream, &response)?;
+    }
+}
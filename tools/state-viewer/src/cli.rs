@@ -8,6 +8,7 @@ use near_primitives::account::id::AccountId;
 use near_primitives::hash::CryptoHash;
 use near_primitives::sharding::ChunkHash;
 use near_primitives::types::{BlockHeight, ShardId};
+use near_primitives::version::{ProtocolVersion, PROTOCOL_VERSION};
 use near_store::{Mode, NodeStorage, Store, Temperature};
 use nearcore::{load_config, NearConfig};
 use std::path::{Path, PathBuf};
@@ -16,6 +17,10 @@ use std::str::FromStr;
 #[derive(Subcommand)]
 #[clap(subcommand_required = true, arg_required_else_help = true)]
 pub enum StateViewerSubCommand {
+    /// Report properties of a contract relevant for its preparation and
+    /// whether each protocol version accepts it.
+    #[clap(alias = "analyze_contract")]
+    AnalyzeContract(AnalyzeContractCmd),
     /// Apply block at some height for shard.
     Apply(ApplyCmd),
     /// Apply a chunk, even if it's not included in any block on disk
@@ -71,11 +76,27 @@ pub enum StateViewerSubCommand {
     Receipts(ReceiptsCmd),
     /// Replay headers from chain.
     Replay(ReplayCmd),
+    /// Split the state according to a proposed shard layout in a temporary
+    /// database and report sizes and load of the new shards.
+    #[clap(alias = "resharding_plan")]
+    ReshardingPlan(ReshardingPlanCmd),
     /// Dump stats for the RocksDB storage.
     #[clap(name = "rocksdb-stats", alias = "rocksdb_stats")]
     RocksDBStats(RocksDBStatsCmd),
+    /// Run the validator selection for the epoch after next as if the current
+    /// epoch ended now, with hypothetical proposals added, and print the seat
+    /// price, validators, shard assignment and kickouts.
+    #[clap(alias = "simulate_validator_selection")]
+    SimulateValidatorSelection(SimulateValidatorSelectionCmd),
     /// Iterates over a trie and prints the StateRecords.
     State,
+    /// Break down an account's storage usage into code, access keys and
+    /// contract data and print the balance it locks.
+    #[clap(alias = "storage_usage")]
+    StorageUsage(StorageUsageCmd),
+    /// Print statistics about the contents of a shard's trie.
+    #[clap(alias = "trie_stats")]
+    TrieStats(TrieStatsCmd),
     /// View head of the storage.
     #[clap(alias = "view_chain")]
     ViewChain(ViewChainCmd),
@@ -105,6 +126,7 @@ impl StateViewerSubCommand {
         let store = storage.get_store(temperature);
         let db = storage.into_inner(temperature);
         match self {
+            StateViewerSubCommand::AnalyzeContract(cmd) => cmd.run(home_dir, near_config, store),
             StateViewerSubCommand::Apply(cmd) => cmd.run(home_dir, near_config, store),
             StateViewerSubCommand::ApplyChunk(cmd) => cmd.run(home_dir, near_config, store),
             StateViewerSubCommand::ApplyRange(cmd) => cmd.run(home_dir, near_config, store),
@@ -125,14 +147,46 @@ impl StateViewerSubCommand {
             StateViewerSubCommand::Peers => peers(db),
             StateViewerSubCommand::Receipts(cmd) => cmd.run(near_config, store),
             StateViewerSubCommand::Replay(cmd) => cmd.run(home_dir, near_config, store),
+            StateViewerSubCommand::ReshardingPlan(cmd) => cmd.run(home_dir, near_config, store),
             StateViewerSubCommand::RocksDBStats(cmd) => cmd.run(store_opener.path()),
+            StateViewerSubCommand::SimulateValidatorSelection(cmd) => cmd.run(near_config, store),
             StateViewerSubCommand::State => state(home_dir, near_config, store),
+            StateViewerSubCommand::StorageUsage(cmd) => cmd.run(home_dir, near_config, store),
+            StateViewerSubCommand::TrieStats(cmd) => cmd.run(home_dir, near_config, store),
             StateViewerSubCommand::ViewChain(cmd) => cmd.run(near_config, store),
             StateViewerSubCommand::ViewTrie(cmd) => cmd.run(store),
         }
     }
 }
 
+#[derive(Parser)]
+pub struct AnalyzeContractCmd {
+    /// Wasm file with the contract.
+    #[clap(long, parse(from_os_str))]
+    file: Option<PathBuf>,
+    /// Account whose contract deployed at the head to analyze.
+    #[clap(long)]
+    account_id: Option<AccountId>,
+    /// Protocol version whose limits to compare the contract with.  Defaults
+    /// to the latest supported version.
+    #[clap(long)]
+    protocol_version: Option<ProtocolVersion>,
+}
+
+impl AnalyzeContractCmd {
+    pub fn run(self, home_dir: &Path, near_config: NearConfig, store: Store) {
+        analyze_contract(
+            self.file,
+            self.account_id,
+            self.protocol_version.unwrap_or(PROTOCOL_VERSION),
+            home_dir,
+            near_config,
+            store,
+        )
+        .unwrap();
+    }
+}
+
 #[derive(Parser)]
 pub struct ApplyCmd {
     #[clap(long)]
@@ -510,6 +564,34 @@ impl ReplayCmd {
     }
 }
 
+#[derive(Parser)]
+pub struct ReshardingPlanCmd {
+    /// Comma-separated boundary accounts of the proposed layout.  Must include
+    /// all boundary accounts of the current layout.
+    #[clap(long, required = true, use_value_delimiter = true)]
+    boundary_accounts: Vec<AccountId>,
+    /// Height of the block whose pre-state to split.  Defaults to the head.
+    #[clap(long)]
+    height: Option<BlockHeight>,
+    /// Number of blocks ending with the selected one to collect load from.
+    #[clap(long, default_value = "100")]
+    num_blocks: u64,
+}
+
+impl ReshardingPlanCmd {
+    pub fn run(self, home_dir: &Path, near_config: NearConfig, store: Store) {
+        resharding_plan(
+            self.boundary_accounts,
+            self.height,
+            self.num_blocks,
+            home_dir,
+            near_config,
+            store,
+        )
+        .unwrap();
+    }
+}
+
 #[derive(Parser)]
 pub struct RocksDBStatsCmd {
     /// Location of the dumped Rocks DB stats.
@@ -523,6 +605,75 @@ impl RocksDBStatsCmd {
     }
 }
 
+#[derive(Parser)]
+pub struct SimulateValidatorSelectionCmd {
+    /// JSON file with a list of hypothetical proposals, e.g.
+    /// `[{"account_id": "pool.near", "stake": "1000000000000000000000000000000"}]`.
+    /// `public_key` defaults to the key the account stakes with and zero stake
+    /// unstakes.
+    #[clap(long, parse(from_os_str))]
+    proposals: Option<PathBuf>,
+    /// Height of the block to end the epoch at.  Defaults to the head.
+    #[clap(long)]
+    height: Option<BlockHeight>,
+}
+
+impl SimulateValidatorSelectionCmd {
+    pub fn run(self, near_config: NearConfig, store: Store) {
+        simulate_validator_selection(self.proposals, self.height, near_config, store).unwrap();
+    }
+}
+
+#[derive(Parser)]
+pub struct StorageUsageCmd {
+    /// Account whose storage usage to report.
+    #[clap(long)]
+    account_id: AccountId,
+    /// Height of the block whose pre-state to analyze.  Defaults to the head.
+    #[clap(long)]
+    height: Option<BlockHeight>,
+    /// Number of leading bytes of contract data keys to group the keys by.
+    #[clap(long, default_value = "1")]
+    prefix_len: usize,
+    /// Number of key prefixes to list.
+    #[clap(long, default_value = "10")]
+    top_n: usize,
+}
+
+impl StorageUsageCmd {
+    pub fn run(self, home_dir: &Path, near_config: NearConfig, store: Store) {
+        storage_usage(
+            self.account_id,
+            self.height,
+            self.prefix_len,
+            self.top_n,
+            home_dir,
+            near_config,
+            store,
+        )
+        .unwrap();
+    }
+}
+
+#[derive(Parser)]
+pub struct TrieStatsCmd {
+    /// Shard to collect the statistics for.
+    #[clap(long)]
+    shard_id: ShardId,
+    /// Height of the block whose pre-state to analyze.  Defaults to the head.
+    #[clap(long)]
+    height: Option<BlockHeight>,
+    /// Number of accounts to list in the top accounts tables.
+    #[clap(long, default_value = "10")]
+    top_n: usize,
+}
+
+impl TrieStatsCmd {
+    pub fn run(self, home_dir: &Path, near_config: NearConfig, store: Store) {
+        trie_stats(self.shard_id, self.height, self.top_n, home_dir, near_config, store).unwrap();
+    }
+}
+
 #[derive(Parser)]
 pub struct ViewChainCmd {
     #[clap(long)]
@@ -602,3 +753,31 @@ impl ViewTrieCmd {
         }
     }
 }
+
+/// Compiles all contracts deployed at the head into a filesystem compiled
+/// contract cache.  The database is opened read-only.
+#[derive(Parser)]
+pub struct PrecompileContractsCommand {
+    /// Directory of the cache.  Defaults to `store.compiled_contract_cache.path`
+    /// from the node config.
+    #[clap(long, parse(from_os_str))]
+    path: Option<PathBuf>,
+}
+
+impl PrecompileContractsCommand {
+    pub fn run(
+        self,
+        home_dir: &Path,
+        genesis_validation: GenesisValidationMode,
+    ) -> anyhow::Result<()> {
+        let near_config = load_config(home_dir, genesis_validation)?;
+        let store = NodeStorage::opener(
+            home_dir,
+            &near_config.config.store,
+            near_config.config.cold_store.as_ref(),
+        )
+        .open_in_mode(Mode::ReadOnly)?
+        .get_hot_store();
+        precompile_contracts(self.path, home_dir, near_config, store)
+    }
+}
@@ -1,8 +1,12 @@
 use crate::apply_chain_range::apply_chain_range;
 use crate::contract_accounts::ContractAccount;
+use crate::resharding_plan;
 use crate::state_dump::state_dump;
 use crate::state_dump::state_dump_redis;
+use crate::storage_usage::StorageUsage;
+use crate::trie_stats::TrieStats;
 use crate::tx_dump::dump_tx_from_block;
+use crate::validator_selection;
 use crate::{apply_chunk, epoch_info};
 use ansi_term::Color::Red;
 use near_chain::chain::collect_receipts_from_response;
@@ -17,22 +21,32 @@ use near_epoch_manager::{EpochManager, EpochManagerAdapter};
 use near_network::iter_peers_from_store;
 use near_primitives::account::id::AccountId;
 use near_primitives::block::{Block, BlockHeader};
+use near_primitives::contract::ContractCode;
 use near_primitives::hash::CryptoHash;
 use near_primitives::shard_layout::ShardLayout;
 use near_primitives::shard_layout::ShardUId;
 use near_primitives::sharding::ChunkHash;
 use near_primitives::state_record::StateRecord;
 use near_primitives::trie_key::TrieKey;
-use near_primitives::types::{chunk_extra::ChunkExtra, BlockHeight, ShardId, StateRoot};
+use near_primitives::types::{
+    chunk_extra::ChunkExtra, BlockHeight, CompiledContractCache, ShardId, StateRoot,
+};
+use near_primitives::version::{ProtocolVersion, PROTOCOL_VERSION};
 use near_primitives_core::types::Gas;
 use near_store::db::Database;
+use near_store::flat_state::FlatStateFactory;
 use near_store::test_utils::create_test_store;
 use near_store::TrieDBStorage;
-use near_store::{Store, Trie, TrieCache, TrieCachingStorage, TrieConfig};
+use near_store::{NodeStorage, ShardTries, Store, Trie, TrieCache, TrieCachingStorage, TrieConfig};
+use near_vm_runner::{
+    analyze_contract as analyze_contract_code, precompile_contract, ContractPrecompilatonResult,
+    FilesystemCompiledContractCache,
+};
 use nearcore::{NearConfig, NightshadeRuntime};
 use node_runtime::adapter::ViewRuntimeAdapter;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use serde_json::json;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::{self, File};
 use std::io::Write;
 use std::path::{Path, PathBuf};
@@ -736,6 +750,153 @@ pub(crate) fn print_epoch_info(
     );
 }
 
+pub(crate) fn trie_stats(
+    shard_id: ShardId,
+    height: Option<BlockHeight>,
+    top_n: usize,
+    home_dir: &Path,
+    near_config: NearConfig,
+    store: Store,
+) -> anyhow::Result<()> {
+    let mode = height.map_or(LoadTrieMode::Latest, LoadTrieMode::Height);
+    let (runtime, state_roots, header) =
+        load_trie_stop_at_height(store, home_dir, &near_config, mode);
+    let state_root = state_roots
+        .get(shard_id as usize)
+        .ok_or_else(|| anyhow::anyhow!("no shard {shard_id} at block {}", header.hash()))?;
+    let trie = runtime.get_trie_for_shard(shard_id, header.prev_hash(), *state_root, false)?;
+    eprintln!(
+        "Collecting statistics of shard {shard_id} at height {}, state root {state_root}",
+        header.height()
+    );
+    TrieStats::collect(&trie)?.print(top_n);
+    Ok(())
+}
+
+pub(crate) fn storage_usage(
+    account_id: AccountId,
+    height: Option<BlockHeight>,
+    prefix_len: usize,
+    top_n: usize,
+    home_dir: &Path,
+    near_config: NearConfig,
+    store: Store,
+) -> anyhow::Result<()> {
+    let mode = height.map_or(LoadTrieMode::Latest, LoadTrieMode::Height);
+    let (runtime, state_roots, header) =
+        load_trie_stop_at_height(store, home_dir, &near_config, mode);
+    let epoch_id = header.epoch_id();
+    let shard_id = runtime.account_id_to_shard_id(&account_id, epoch_id)?;
+    let trie = runtime.get_trie_for_shard(
+        shard_id,
+        header.prev_hash(),
+        state_roots[shard_id as usize],
+        false,
+    )?;
+    let config = runtime.get_protocol_config(epoch_id)?.runtime_config.fees.storage_usage_config;
+    let usage =
+        StorageUsage::collect(&trie, &account_id, &config, prefix_len)?.ok_or_else(|| {
+            anyhow::anyhow!("account {account_id} doesn't exist at height {}", header.height())
+        })?;
+    eprintln!("Storage usage of {account_id} in shard {shard_id} at height {}", header.height());
+    usage.print(config.storage_amount_per_byte, top_n);
+    Ok(())
+}
+
+pub(crate) fn resharding_plan(
+    boundary_accounts: Vec<AccountId>,
+    height: Option<BlockHeight>,
+    num_blocks: u64,
+    home_dir: &Path,
+    near_config: NearConfig,
+    store: Store,
+) -> anyhow::Result<()> {
+    let mode = height.map_or(LoadTrieMode::Latest, LoadTrieMode::Height);
+    let (runtime, state_roots, header) =
+        load_trie_stop_at_height(store.clone(), home_dir, &near_config, mode);
+    let current_layout = runtime.get_shard_layout(header.epoch_id())?;
+    let new_layout = resharding_plan::plan_shard_layout(&current_layout, boundary_accounts)?;
+
+    // Split states are written into a temporary database so that the node’s
+    // database is left intact.
+    let temp_dir = tempfile::Builder::new().prefix("resharding-plan").tempdir_in(home_dir)?;
+    let temp_store =
+        NodeStorage::opener(temp_dir.path(), &Default::default(), None).open()?.get_hot_store();
+    let target_tries = ShardTries::new(
+        temp_store.clone(),
+        TrieConfig::default(),
+        &new_layout.get_shard_uids(),
+        FlatStateFactory::new(temp_store),
+    );
+    let source_tries = runtime.get_tries();
+    let mut stats = BTreeMap::new();
+    for (shard_id, state_root) in state_roots.into_iter().enumerate() {
+        let shard_uid = ShardUId::from_shard_id_and_layout(shard_id as ShardId, &current_layout);
+        eprintln!("Splitting shard {shard_id} at height {}", header.height());
+        resharding_plan::split_shard(
+            &source_tries,
+            &target_tries,
+            shard_uid,
+            state_root,
+            &new_layout,
+            &mut stats,
+        )?;
+    }
+
+    let chain_store = ChainStore::new(
+        store,
+        near_config.genesis.config.genesis_height,
+        near_config.client_config.save_trie_changes,
+    );
+    let num_blocks = resharding_plan::collect_load(
+        &chain_store,
+        &new_layout,
+        *header.hash(),
+        num_blocks,
+        &mut stats,
+    )?;
+    resharding_plan::print_plan(&new_layout, &stats, num_blocks);
+    Ok(())
+}
+
+pub(crate) fn simulate_validator_selection(
+    proposals: Option<PathBuf>,
+    height: Option<BlockHeight>,
+    near_config: NearConfig,
+    store: Store,
+) -> anyhow::Result<()> {
+    let chain_store = ChainStore::new(
+        store.clone(),
+        near_config.genesis.config.genesis_height,
+        near_config.client_config.save_trie_changes,
+    );
+    let block_hash = match height {
+        Some(height) => chain_store.get_block_hash_by_height(height)?,
+        None => chain_store.head()?.last_block_hash,
+    };
+    let epoch_manager = EpochManager::new_from_genesis_config(store, &near_config.genesis.config)?;
+    let block_info = epoch_manager.get_block_info(&block_hash)?;
+    let epoch_info = epoch_manager.get_epoch_info(block_info.epoch_id())?;
+    let next_epoch_info =
+        epoch_manager.get_epoch_info(&epoch_manager.get_next_epoch_id(&block_hash)?)?;
+    let proposals = match proposals {
+        Some(path) => {
+            validator_selection::read_proposals(&path, &[&*next_epoch_info, &*epoch_info])?
+        }
+        None => vec![],
+    };
+    let next_next_epoch_info =
+        epoch_manager.simulate_validator_selection(&block_hash, proposals)?;
+    eprintln!(
+        "Selecting validators for epoch height {} as if epoch height {} ended at height {}",
+        next_next_epoch_info.epoch_height(),
+        epoch_info.epoch_height(),
+        block_info.height()
+    );
+    validator_selection::print_selection(&next_next_epoch_info, &next_epoch_info);
+    Ok(())
+}
+
 fn get_trie(store: Store, hash: CryptoHash, shard_id: u32, shard_version: u32) -> Trie {
     let shard_uid = ShardUId { version: shard_version, shard_id };
     let trie_config: TrieConfig = Default::default();
@@ -877,3 +1038,194 @@ pub(crate) fn contract_accounts(
     }
     Ok(())
 }
+
+pub(crate) fn precompile_contracts(
+    path: Option<PathBuf>,
+    home_dir: &Path,
+    near_config: NearConfig,
+    store: Store,
+) -> anyhow::Result<()> {
+    /// Number of contracts loaded into memory and compiled in parallel.
+    const BATCH_SIZE: usize = 256;
+
+    let cache_config = &near_config.config.store.compiled_contract_cache;
+    let path = match path.or_else(|| cache_config.get_path(home_dir)) {
+        Some(path) => path,
+        None => anyhow::bail!(
+            "no compiled contract cache configured, set store.compiled_contract_cache.path or pass --path"
+        ),
+    };
+    let cache = FilesystemCompiledContractCache::open(&path, cache_config.max_size.as_u64())?;
+    let (runtime, state_roots, header) = load_trie(store.clone(), home_dir, &near_config);
+    let epoch_id = header.epoch_id();
+    let protocol_version = runtime.get_epoch_protocol_version(epoch_id)?;
+    let wasm_config = runtime.get_protocol_config(epoch_id)?.runtime_config.wasm_config;
+    let shard_layout = runtime.get_shard_layout(epoch_id)?;
+
+    let precompile = |batch: &mut Vec<(AccountId, ContractCode)>| -> anyhow::Result<_> {
+        let results = batch
+            .par_iter()
+            .map(|(account_id, code)| {
+                let cache: &dyn CompiledContractCache = &cache;
+                let result = precompile_contract(code, &wasm_config, protocol_version, Some(cache));
+                (account_id, result)
+            })
+            .collect::<Vec<_>>();
+        let mut counts = (0, 0);
+        for (account_id, result) in results {
+            match result? {
+                Ok(ContractPrecompilatonResult::ContractCompiled) => counts.0 += 1,
+                Ok(ContractPrecompilatonResult::ContractAlreadyInCache) => {}
+                Ok(ContractPrecompilatonResult::CacheNotAvailable) => {
+                    unreachable!("cache is always passed")
+                }
+                Err(err) => {
+                    eprintln!("Failed to compile contract of {account_id}: {err}");
+                    counts.1 += 1;
+                }
+            }
+        }
+        batch.clear();
+        Ok(counts)
+    };
+
+    let mut seen = HashSet::new();
+    let (mut compiled, mut failed) = (0, 0);
+    for (shard_id, &state_root) in state_roots.iter().enumerate() {
+        eprintln!("Precompiling contracts of shard {shard_id} at height {}", header.height());
+        let shard_uid = ShardUId::from_shard_id_and_layout(shard_id as ShardId, &shard_layout);
+        // Use simple non-caching storage, we don't expect many duplicate lookups while iterating.
+        let storage = TrieDBStorage::new(store.clone(), shard_uid);
+        let trie = Trie::new(Box::new(storage), state_root, None);
+        let mut batch = Vec::with_capacity(BATCH_SIZE);
+        for contract in ContractAccount::in_trie(&trie)? {
+            let contract = match contract {
+                Ok(contract) => contract,
+                Err(err) => {
+                    eprintln!("{err}");
+                    continue;
+                }
+            };
+            let code = ContractCode::new(contract.source_wasm.to_vec(), None);
+            // Many accounts share the same code, compile it only once.
+            if seen.insert(*code.hash()) {
+                batch.push((contract.account_id, code));
+            }
+            if batch.len() == BATCH_SIZE {
+                let counts = precompile(&mut batch)?;
+                compiled += counts.0;
+                failed += counts.1;
+            }
+        }
+        let counts = precompile(&mut batch)?;
+        compiled += counts.0;
+        failed += counts.1;
+    }
+    println!(
+        "Found {} distinct contracts: {compiled} compiled, {} already cached, {failed} failed to compile",
+        seen.len(),
+        seen.len() - compiled - failed,
+    );
+    println!("Cache {} holds {} contracts, {} bytes", path.display(), cache.len(), cache.size());
+    Ok(())
+}
+
+pub(crate) fn analyze_contract(
+    file: Option<PathBuf>,
+    account_id: Option<AccountId>,
+    protocol_version: ProtocolVersion,
+    home_dir: &Path,
+    near_config: NearConfig,
+    store: Store,
+) -> anyhow::Result<()> {
+    let code = match (file, account_id) {
+        (Some(file), None) => fs::read(&file)?,
+        (None, Some(account_id)) => {
+            let (runtime, state_roots, header) = load_trie(store, home_dir, &near_config);
+            let epoch_id = header.epoch_id();
+            let shard_id = runtime.account_id_to_shard_id(&account_id, epoch_id)?;
+            let shard_uid = runtime.shard_id_to_uid(shard_id, epoch_id)?;
+            runtime
+                .view_contract_code(&shard_uid, state_roots[shard_id as usize], &account_id)?
+                .into_code()
+        }
+        _ => anyhow::bail!("exactly one of --file and --account-id must be specified"),
+    };
+    let analysis = analyze_contract_code(&code);
+    let runtime_config_store =
+        NightshadeRuntime::create_runtime_config_store(&near_config.genesis.config.chain_id);
+    let limits = &runtime_config_store.get_config(protocol_version).wasm_config.limit_config;
+
+    println!("Code size: {} bytes (limit {})", analysis.code_size, limits.max_contract_size);
+    if let Some(error) = &analysis.error {
+        println!("Error: {error}");
+    }
+    println!("Imported host functions ({}):", analysis.host_functions.len());
+    for name in &analysis.host_functions {
+        println!("  {name}");
+    }
+    if !analysis.other_imports.is_empty() {
+        println!("Other imports: {}", analysis.other_imports.join(", "));
+    }
+    println!("Exported methods ({}):", analysis.exported_methods.len());
+    for name in &analysis.exported_methods {
+        println!("  {name}");
+    }
+    for memory in &analysis.memories {
+        println!(
+            "Memory: {}, initial {} pages, maximum {} pages (replaced by {}..{} pages)",
+            if memory.imported { "imported" } else { "declared" },
+            memory.initial,
+            memory.maximum.map_or("unlimited".to_string(), |max| max.to_string()),
+            limits.initial_memory_pages,
+            limits.max_memory_pages,
+        );
+    }
+    for table in &analysis.tables {
+        println!(
+            "Table: {}, initial {} elements, maximum {} elements",
+            if table.imported { "imported" } else { "declared" },
+            table.initial,
+            table.maximum.map_or("unlimited".to_string(), |max| max.to_string()),
+        );
+    }
+    let format_count = |count: Option<u64>, limit: Option<u64>| {
+        format!(
+            "{} (limit {})",
+            count.map_or("unknown".to_string(), |count| count.to_string()),
+            limit.map_or("none".to_string(), |limit| limit.to_string()),
+        )
+    };
+    println!(
+        "Functions: {}",
+        format_count(analysis.function_count, limits.max_functions_number_per_contract)
+    );
+    println!("Locals: {}", format_count(analysis.local_count, limits.max_locals_per_contract));
+    if let Some(cost) = analysis.max_function_stack_cost {
+        println!("Max function stack cost: {cost} (stack limit {})", limits.max_stack_height);
+    }
+    if let Some(overhead) = analysis.stack_instrumentation_overhead {
+        println!("Stack height instrumentation overhead: {overhead} bytes");
+    }
+
+    println!("Preparation by protocol version:");
+    let mut configs = runtime_config_store
+        .versioned_configs()
+        .take_while(|(version, _)| *version <= PROTOCOL_VERSION)
+        .peekable();
+    while let Some((version, config)) = configs.next() {
+        let versions = match configs.peek() {
+            Some((next, _)) => format!("{version}..={}", next - 1),
+            None => format!("{version}..={PROTOCOL_VERSION}"),
+        };
+        let check = analysis.check_preparation(&code, &config.wasm_config);
+        match check.result {
+            Ok(size) => println!("  {versions}: ok, prepared size {size} bytes"),
+            Err(err) => println!("  {versions}: rejected, {err:?}"),
+        }
+        for limit in check.exceeded_limits {
+            println!("    exceeded: {limit}");
+        }
+    }
+    Ok(())
+}
@@ -7,8 +7,12 @@ mod commands;
 mod contract_accounts;
 mod dump_state_parts;
 mod epoch_info;
+mod resharding_plan;
 mod rocksdb_stats;
 mod state_dump;
+mod storage_usage;
+mod trie_stats;
 mod tx_dump;
+mod validator_selection;
 
-pub use cli::StateViewerSubCommand;
+pub use cli::{PrecompileContractsCommand, StateViewerSubCommand};
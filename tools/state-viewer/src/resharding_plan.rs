@@ -0,0 +1,352 @@
+//! Offline evaluation of candidate shard layouts.
+//!
+//! The state of the current shards is split according to a proposed layout the
+//! same way it’s done during resharding, but into a temporary database, and
+//! load observed in recent blocks is attributed to the new shards.
+
+use near_chain::{ChainStore, ChainStoreAccess};
+use near_primitives::account::id::AccountId;
+use near_primitives::hash::CryptoHash;
+use near_primitives::shard_layout::{account_id_to_shard_id, account_id_to_shard_uid, ShardLayout};
+use near_primitives::state_part::PartId;
+use near_primitives::syncing::{get_num_state_parts, STATE_PART_MEMORY_LIMIT};
+use near_primitives::trie_key::trie_key_parsers::parse_account_id_from_raw_key;
+use near_primitives::types::{Gas, ShardId, StateRoot};
+use near_store::split_state::get_delayed_receipts;
+use near_store::{ShardTries, ShardUId, Trie};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Statistics of a shard in the proposed layout.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct NewShardStats {
+    pub(crate) state_root: StateRoot,
+    /// Memory usage of the trie as accounted in trie nodes.  Determines the
+    /// number of state parts.
+    pub(crate) trie_memory_usage: u64,
+    pub(crate) keys: u64,
+    pub(crate) key_bytes: u64,
+    pub(crate) value_bytes: u64,
+    pub(crate) delayed_receipts: u64,
+    pub(crate) transactions: u64,
+    pub(crate) receipts: u64,
+    pub(crate) gas_burnt: Gas,
+}
+
+/// Builds a layout which splits shards of `current` layout at given boundary
+/// accounts.  Fixed shards are kept as they are.
+///
+/// Every boundary account of the current layout must be present in
+/// `boundary_accounts` since shards can be split but not merged.  Layouts
+/// mapping accounts by hash can’t be split into account ranges unless they
+/// have a single shard.
+pub(crate) fn plan_shard_layout(
+    current: &ShardLayout,
+    mut boundary_accounts: Vec<AccountId>,
+) -> anyhow::Result<ShardLayout> {
+    if let ShardLayout::V0(_) = current {
+        anyhow::ensure!(
+            current.num_shards() == 1,
+            "layout with {} shards assigned by account hash can't be split into account ranges",
+            current.num_shards()
+        );
+    }
+    boundary_accounts.sort();
+    boundary_accounts.dedup();
+    for account_id in current.boundary_accounts() {
+        anyhow::ensure!(
+            boundary_accounts.binary_search(account_id).is_ok(),
+            "boundary account {account_id} of the current layout is missing; shards can't be merged"
+        );
+    }
+
+    let fixed_shards = current.fixed_shards().to_vec();
+    let num_fixed = fixed_shards.len();
+    let mut split_map = vec![vec![]; current.num_shards() as usize];
+    for shard_id in 0..num_fixed {
+        split_map[shard_id].push(shard_id as ShardId);
+    }
+    for range in 0..=boundary_accounts.len() {
+        // The parent is the current range shard containing the lower end of
+        // the new range.
+        let parent_range = match range {
+            0 => 0,
+            _ => {
+                let lower = &boundary_accounts[range - 1];
+                current.boundary_accounts().iter().filter(|account_id| *account_id <= lower).count()
+            }
+        };
+        split_map[num_fixed + parent_range].push((num_fixed + range) as ShardId);
+    }
+    Ok(ShardLayout::v1(fixed_shards, boundary_accounts, Some(split_map), current.version() + 1))
+}
+
+/// Builds states of shards split from `shard_uid` in `target` tries the same
+/// way as it’s done during resharding and records their sizes in `stats`.
+pub(crate) fn split_shard(
+    source: &ShardTries,
+    target: &ShardTries,
+    shard_uid: ShardUId,
+    state_root: StateRoot,
+    new_layout: &ShardLayout,
+    stats: &mut BTreeMap<ShardId, NewShardStats>,
+) -> anyhow::Result<()> {
+    let new_shards = new_layout
+        .get_split_shard_uids(shard_uid.shard_id())
+        .ok_or_else(|| anyhow::anyhow!("shard {shard_uid:?} has no children in new layout"))?;
+    let mut state_roots: HashMap<_, _> =
+        new_shards.iter().map(|shard_uid| (*shard_uid, Trie::EMPTY_ROOT)).collect();
+    let to_new_shard_uid = |account_id: &AccountId| account_id_to_shard_uid(account_id, new_layout);
+
+    let trie = source.get_view_trie_for_shard(shard_uid, state_root);
+    let num_parts = get_num_state_parts(trie.retrieve_root_node()?.memory_usage);
+    for part_id in 0..num_parts {
+        let items = trie.get_trie_items_for_part(PartId::new(part_id, num_parts))?;
+        for (key, value) in &items {
+            // Delayed receipts aren’t associated with an account.  They are
+            // accounted for below.
+            if let Ok(Some(account_id)) = parse_account_id_from_raw_key(key) {
+                let shard_stats =
+                    stats.entry(account_id_to_shard_id(&account_id, new_layout)).or_default();
+                shard_stats.keys += 1;
+                shard_stats.key_bytes += key.len() as u64;
+                shard_stats.value_bytes += value.len() as u64;
+            }
+        }
+        let values = items.into_iter().map(|(key, value)| (key, Some(value))).collect();
+        let (store_update, new_state_roots) =
+            target.add_values_to_split_states(&state_roots, values, &to_new_shard_uid)?;
+        store_update.commit()?;
+        state_roots = new_state_roots;
+    }
+
+    let trie_update = source.new_trie_update_view(shard_uid, state_root);
+    let mut start_index = None;
+    while let Some((next_index, receipts)) =
+        get_delayed_receipts(&trie_update, start_index, STATE_PART_MEMORY_LIMIT)?
+    {
+        for receipt in &receipts {
+            stats
+                .entry(account_id_to_shard_id(&receipt.receiver_id, new_layout))
+                .or_default()
+                .delayed_receipts += 1;
+        }
+        let (store_update, new_state_roots) = target.apply_delayed_receipts_to_split_states(
+            &state_roots,
+            &receipts,
+            &to_new_shard_uid,
+        )?;
+        store_update.commit()?;
+        state_roots = new_state_roots;
+        start_index = Some(next_index);
+    }
+
+    for (new_shard_uid, state_root) in state_roots {
+        let memory_usage =
+            target.get_trie_for_shard(new_shard_uid, state_root).retrieve_root_node()?.memory_usage;
+        let shard_stats = stats.entry(new_shard_uid.shard_id()).or_default();
+        shard_stats.state_root = state_root;
+        shard_stats.trie_memory_usage = memory_usage;
+    }
+    Ok(())
+}
+
+/// Attributes transactions, receipts and gas burnt in up to `num_blocks`
+/// blocks ending with `last_block_hash` to shards of the new layout by their
+/// executor account.  Only shards tracked by the node have outcomes stored.
+///
+/// Returns number of blocks analysed.
+pub(crate) fn collect_load(
+    chain_store: &ChainStore,
+    new_layout: &ShardLayout,
+    last_block_hash: CryptoHash,
+    num_blocks: u64,
+    stats: &mut BTreeMap<ShardId, NewShardStats>,
+) -> anyhow::Result<u64> {
+    let mut block_hash = last_block_hash;
+    let mut num_analysed = 0;
+    while num_analysed < num_blocks && block_hash != CryptoHash::default() {
+        let block = match chain_store.get_block(&block_hash) {
+            Ok(block) => block,
+            // The block was garbage collected.
+            Err(near_chain::Error::DBNotFoundErr(_)) => break,
+            Err(err) => return Err(err.into()),
+        };
+        for (shard_id, chunk_header) in block.chunks().iter().enumerate() {
+            if chunk_header.height_included() != block.header().height() {
+                continue;
+            }
+            // Chunks of shards the node doesn’t track aren’t stored.
+            let tx_hashes: HashSet<CryptoHash> =
+                match chain_store.get_chunk(&chunk_header.chunk_hash()) {
+                    Ok(chunk) => chunk.transactions().iter().map(|tx| tx.get_hash()).collect(),
+                    Err(_) => continue,
+                };
+            let outcome_ids = chain_store
+                .get_outcomes_by_block_hash_and_shard_id(&block_hash, shard_id as ShardId)?;
+            for id in outcome_ids {
+                let outcome =
+                    match chain_store.get_outcome_by_id_and_block_hash(&id, &block_hash)? {
+                        Some(outcome) => outcome.outcome,
+                        None => continue,
+                    };
+                let shard_stats = stats
+                    .entry(account_id_to_shard_id(&outcome.executor_id, new_layout))
+                    .or_default();
+                if tx_hashes.contains(&id) {
+                    shard_stats.transactions += 1;
+                } else {
+                    shard_stats.receipts += 1;
+                }
+                shard_stats.gas_burnt += outcome.gas_burnt;
+            }
+        }
+        num_analysed += 1;
+        block_hash = *block.header().prev_hash();
+    }
+    Ok(num_analysed)
+}
+
+/// Prints the proposed layout and statistics of its shards.
+pub(crate) fn print_plan(
+    layout: &ShardLayout,
+    stats: &BTreeMap<ShardId, NewShardStats>,
+    num_blocks: u64,
+) {
+    println!("Shard layout version {} with {} shards", layout.version(), layout.num_shards());
+    for shard_id in 0..layout.num_shards() {
+        let parent = layout.get_parent_shard_id(shard_id).unwrap();
+        println!("  shard {shard_id} (parent {parent}): {}", shard_accounts(layout, shard_id));
+    }
+
+    let total_gas: Gas = stats.values().map(|stats| stats.gas_burnt).sum();
+    println!("\nState:");
+    println!(
+        "  {:>5} {:<44} {:>14} {:>12} {:>14} {:>14} {:>10}",
+        "shard", "state root", "trie memory", "keys", "key bytes", "value bytes", "delayed"
+    );
+    for (shard_id, stats) in stats {
+        println!(
+            "  {:>5} {:<44} {:>14} {:>12} {:>14} {:>14} {:>10}",
+            shard_id,
+            stats.state_root,
+            stats.trie_memory_usage,
+            stats.keys,
+            stats.key_bytes,
+            stats.value_bytes,
+            stats.delayed_receipts
+        );
+    }
+    println!("\nLoad in the last {num_blocks} blocks:");
+    println!(
+        "  {:>5} {:>12} {:>12} {:>20} {:>8}",
+        "shard", "txs", "receipts", "gas burnt", "gas %"
+    );
+    for (shard_id, stats) in stats {
+        let share = match total_gas {
+            0 => 0.0,
+            total => stats.gas_burnt as f64 * 100.0 / total as f64,
+        };
+        println!(
+            "  {:>5} {:>12} {:>12} {:>20} {:>7.2}%",
+            shard_id, stats.transactions, stats.receipts, stats.gas_burnt, share
+        );
+    }
+}
+
+/// Describes which accounts belong to given shard of a V1 layout.
+fn shard_accounts(layout: &ShardLayout, shard_id: ShardId) -> String {
+    let fixed_shards = layout.fixed_shards();
+    if let Some(account_id) = fixed_shards.get(shard_id as usize) {
+        return format!("{account_id} and its sub-accounts");
+    }
+    let range = shard_id as usize - fixed_shards.len();
+    let boundaries = layout.boundary_accounts();
+    match (range.checked_sub(1).map(|idx| &boundaries[idx]), boundaries.get(range)) {
+        (None, None) => "all accounts".to_string(),
+        (None, Some(upper)) => format!("accounts < {upper}"),
+        (Some(lower), None) => format!("accounts >= {lower}"),
+        (Some(lower), Some(upper)) => format!("{lower} <= accounts < {upper}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{plan_shard_layout, shard_accounts, split_shard, NewShardStats};
+    use near_primitives::shard_layout::{account_id_to_shard_id, ShardLayout};
+    use near_primitives::trie_key::TrieKey;
+    use near_primitives::types::{AccountId, ShardId};
+    use near_store::flat_state::FlatStateFactory;
+    use near_store::test_utils::{create_test_store, create_tries, test_populate_trie};
+    use near_store::{ShardTries, ShardUId, Trie, TrieConfig};
+    use std::collections::BTreeMap;
+
+    fn accounts(names: &[&str]) -> Vec<AccountId> {
+        names.iter().map(|name| name.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_plan_shard_layout() {
+        let current = ShardLayout::v1(accounts(&["aurora"]), accounts(&["bb", "dd"]), None, 1);
+        let layout = plan_shard_layout(&current, accounts(&["dd", "aa", "bb", "cc"])).unwrap();
+        assert_eq!(layout.version(), 2);
+        assert_eq!(layout.num_shards(), 6);
+        assert_eq!(layout.get_split_shard_ids(0), Some(vec![0]));
+        assert_eq!(layout.get_split_shard_ids(1), Some(vec![1, 2]));
+        assert_eq!(layout.get_split_shard_ids(2), Some(vec![3, 4]));
+        assert_eq!(layout.get_split_shard_ids(3), Some(vec![5]));
+        for account_id in
+            accounts(&["a0", "aa", "ab", "bb", "bc", "cc", "cd", "dd", "zz", "x.aurora"])
+        {
+            let new_shard_id = account_id_to_shard_id(&account_id, &layout);
+            assert_eq!(
+                layout.get_parent_shard_id(new_shard_id).unwrap(),
+                account_id_to_shard_id(&account_id, &current),
+                "{account_id}"
+            );
+        }
+        assert_eq!(shard_accounts(&layout, 0), "aurora and its sub-accounts");
+        assert_eq!(shard_accounts(&layout, 1), "accounts < aa");
+        assert_eq!(shard_accounts(&layout, 3), "bb <= accounts < cc");
+        assert_eq!(shard_accounts(&layout, 5), "accounts >= dd");
+
+        assert!(plan_shard_layout(&current, accounts(&["aa", "dd"])).is_err());
+        assert!(plan_shard_layout(&ShardLayout::v0(4, 0), accounts(&["aa"])).is_err());
+        let layout = plan_shard_layout(&ShardLayout::v0_single_shard(), accounts(&["m"])).unwrap();
+        assert_eq!(layout.get_split_shard_ids(0), Some(vec![0, 1]));
+    }
+
+    #[test]
+    fn test_split_shard() {
+        let source = create_tries();
+        let shard_uid = ShardUId::single_shard();
+        let account_key =
+            |name: &str| TrieKey::Account { account_id: name.parse().unwrap() }.to_vec();
+        let changes = vec![
+            (account_key("alice.near"), Some(vec![1; 10])),
+            (account_key("bob.near"), Some(vec![1; 10])),
+            (account_key("zoe.near"), Some(vec![1; 20])),
+        ];
+        let state_root = test_populate_trie(&source, &Trie::EMPTY_ROOT, shard_uid, changes);
+
+        let layout = plan_shard_layout(&ShardLayout::v0_single_shard(), accounts(&["m"])).unwrap();
+        let store = create_test_store();
+        let target = ShardTries::new(
+            store.clone(),
+            TrieConfig::default(),
+            &layout.get_shard_uids(),
+            FlatStateFactory::new(store),
+        );
+        let mut stats = BTreeMap::new();
+        split_shard(&source, &target, shard_uid, state_root, &layout, &mut stats).unwrap();
+
+        assert_eq!(stats.keys().copied().collect::<Vec<ShardId>>(), vec![0, 1]);
+        let left: &NewShardStats = &stats[&0];
+        assert_eq!((left.keys, left.value_bytes), (2, 20));
+        assert_eq!((stats[&1].keys, stats[&1].value_bytes), (1, 20));
+        let right_uid = ShardUId::from_shard_id_and_layout(1, &layout);
+        let right = target.get_trie_for_shard(right_uid, stats[&1].state_root);
+        assert_eq!(right.get(&account_key("zoe.near")).unwrap(), Some(vec![1; 20]));
+        assert_eq!(right.get(&account_key("bob.near")).unwrap(), None);
+        assert!(stats[&1].trie_memory_usage > 0);
+    }
+}
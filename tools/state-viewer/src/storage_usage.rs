@@ -0,0 +1,256 @@
+//! State viewer functions to explain how an account's `storage_usage` is
+//! composed and how much balance it locks.
+
+use borsh::BorshDeserialize;
+use near_primitives::account::Account;
+use near_primitives::runtime::fees::StorageUsageConfig;
+use near_primitives::trie_key::trie_key_parsers::{
+    get_raw_prefix_for_access_keys, get_raw_prefix_for_contract_data,
+};
+use near_primitives::trie_key::TrieKey;
+use near_primitives::types::{AccountId, Balance};
+use near_store::{StorageError, Trie};
+use std::collections::HashMap;
+
+/// Number of records and storage bytes charged for them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RecordStats {
+    pub(crate) records: u64,
+    pub(crate) bytes: u64,
+}
+
+impl RecordStats {
+    fn add(&mut self, bytes: u64) {
+        self.records += 1;
+        self.bytes += bytes;
+    }
+}
+
+/// Storage usage of an account broken down the same way the runtime charges
+/// it, see `StorageComputer` in the runtime.
+#[derive(Debug)]
+pub(crate) struct StorageUsage {
+    pub(crate) account: Account,
+    /// Fixed number of bytes charged for the account record.
+    pub(crate) account_bytes: u64,
+    pub(crate) code_bytes: u64,
+    pub(crate) access_keys: RecordStats,
+    pub(crate) contract_data: RecordStats,
+    /// Contract data statistics per key prefix of at most `prefix_len` bytes.
+    pub(crate) data_prefixes: HashMap<Vec<u8>, RecordStats>,
+}
+
+impl StorageUsage {
+    /// Reads all records of the account from the trie.  Returns `None` if the
+    /// account doesn't exist.
+    pub(crate) fn collect(
+        trie: &Trie,
+        account_id: &AccountId,
+        config: &StorageUsageConfig,
+        prefix_len: usize,
+    ) -> Result<Option<Self>, StorageError> {
+        let account =
+            match trie.get(&TrieKey::Account { account_id: account_id.clone() }.to_vec())? {
+                Some(bytes) => Account::try_from_slice(&bytes)
+                    .map_err(|err| StorageError::StorageInconsistentState(err.to_string()))?,
+                None => return Ok(None),
+            };
+        let code_bytes = trie
+            .get(&TrieKey::ContractCode { account_id: account_id.clone() }.to_vec())?
+            .map_or(0, |code| code.len() as u64);
+
+        // Access key records are keyed by the public key and data records by
+        // the data key, both following the account's prefix.
+        let mut access_keys = RecordStats::default();
+        let prefix = get_raw_prefix_for_access_keys(account_id);
+        let mut iter = trie.iter()?;
+        iter.seek_prefix(&prefix)?;
+        for item in iter {
+            let (key, value) = item?;
+            let public_key_len = (key.len() - prefix.len()) as u64;
+            access_keys.add(config.num_extra_bytes_record + public_key_len + value.len() as u64);
+        }
+
+        let mut contract_data = RecordStats::default();
+        let mut data_prefixes: HashMap<Vec<u8>, RecordStats> = HashMap::new();
+        let prefix = get_raw_prefix_for_contract_data(account_id, &[]);
+        let mut iter = trie.iter()?;
+        iter.seek_prefix(&prefix)?;
+        for item in iter {
+            let (key, value) = item?;
+            let data_key = &key[prefix.len()..];
+            let bytes = config.num_extra_bytes_record + data_key.len() as u64 + value.len() as u64;
+            contract_data.add(bytes);
+            let data_prefix = data_key[..data_key.len().min(prefix_len)].to_vec();
+            data_prefixes.entry(data_prefix).or_default().add(bytes);
+        }
+
+        Ok(Some(Self {
+            account,
+            account_bytes: config.num_bytes_account,
+            code_bytes,
+            access_keys,
+            contract_data,
+            data_prefixes,
+        }))
+    }
+
+    /// Storage usage computed from the records, which normally equals the
+    /// `storage_usage` recorded in the account.
+    pub(crate) fn computed(&self) -> u64 {
+        self.account_bytes + self.code_bytes + self.access_keys.bytes + self.contract_data.bytes
+    }
+
+    /// Returns up to `n` contract data prefixes with the most storage bytes.
+    pub(crate) fn top_prefixes(&self, n: usize) -> Vec<(&[u8], RecordStats)> {
+        let mut prefixes: Vec<_> =
+            self.data_prefixes.iter().map(|(prefix, stats)| (prefix.as_slice(), *stats)).collect();
+        prefixes.sort_by(|a, b| b.1.bytes.cmp(&a.1.bytes).then_with(|| a.0.cmp(b.0)));
+        prefixes.truncate(n);
+        prefixes
+    }
+
+    /// Balance which has to stay on the account to cover its recorded storage
+    /// usage, see `check_storage_stake` in the runtime.
+    pub(crate) fn required_balance(&self, storage_amount_per_byte: Balance) -> Balance {
+        Balance::from(self.account.storage_usage()).saturating_mul(storage_amount_per_byte)
+    }
+
+    /// Part of the liquid balance which can be transferred away without
+    /// failing the storage staking check.  Locked stake counts towards the
+    /// required balance.
+    pub(crate) fn withdrawable_balance(&self, storage_amount_per_byte: Balance) -> Balance {
+        let required = self.required_balance(storage_amount_per_byte);
+        self.account.amount().saturating_sub(required.saturating_sub(self.account.locked()))
+    }
+
+    pub(crate) fn print(&self, storage_amount_per_byte: Balance, top_n: usize) {
+        let row = |name: &str, records: Option<u64>, bytes: u64| {
+            let records = records.map_or(String::new(), |records| records.to_string());
+            println!("  {name:<16} {records:>12} {bytes:>14}");
+        };
+        println!("Storage usage:");
+        println!("  {:<16} {:>12} {:>14}", "", "records", "bytes");
+        row("account", None, self.account_bytes);
+        row("contract code", None, self.code_bytes);
+        row("access keys", Some(self.access_keys.records), self.access_keys.bytes);
+        row("contract data", Some(self.contract_data.records), self.contract_data.bytes);
+        row("total", None, self.computed());
+        println!("Recorded in account: {} bytes", self.account.storage_usage());
+        if self.computed() != self.account.storage_usage() {
+            println!(
+                "  differs from the records by {} bytes",
+                self.account.storage_usage().abs_diff(self.computed())
+            );
+        }
+
+        println!("\nTop {top_n} contract data prefixes by storage bytes:");
+        for (prefix, stats) in self.top_prefixes(top_n) {
+            println!(
+                "  {:<40} {:>12} {:>14}",
+                format!("\"{}\"", prefix.escape_ascii()),
+                stats.records,
+                stats.bytes
+            );
+        }
+
+        println!("\nStorage amount per byte: {storage_amount_per_byte} yoctoNEAR");
+        println!("Required balance:        {}", self.required_balance(storage_amount_per_byte));
+        println!("Amount:                  {}", self.account.amount());
+        println!("Locked:                  {}", self.account.locked());
+        println!("Withdrawable amount:     {}", self.withdrawable_balance(storage_amount_per_byte));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StorageUsage;
+    use borsh::BorshSerialize;
+    use near_crypto::{KeyType, PublicKey};
+    use near_primitives::account::{AccessKey, Account};
+    use near_primitives::hash::CryptoHash;
+    use near_primitives::runtime::fees::StorageUsageConfig;
+    use near_primitives::trie_key::TrieKey;
+    use near_primitives::types::Balance;
+    use near_store::test_utils::{create_tries, test_populate_trie};
+    use near_store::{ShardUId, Trie};
+
+    #[test]
+    fn test_storage_usage() {
+        let config = StorageUsageConfig {
+            storage_amount_per_byte: 10,
+            num_bytes_account: 100,
+            num_extra_bytes_record: 40,
+        };
+        let account_id = "alice.near".parse().unwrap();
+        let public_key = PublicKey::empty(KeyType::ED25519);
+        let access_key = AccessKey::full_access();
+        let key_bytes = 40
+            + public_key.try_to_vec().unwrap().len() as u64
+            + access_key.try_to_vec().unwrap().len() as u64;
+        let data_bytes = |key: &[u8], value_len: u64| 40 + key.len() as u64 + value_len;
+        let storage_usage = 100
+            + 5
+            + key_bytes
+            + data_bytes(b"aa", 10)
+            + data_bytes(b"ab", 20)
+            + data_bytes(b"b", 1);
+        let account = Account::new(10_000, 1_000, CryptoHash::default(), storage_usage);
+        let data_key = |key: &[u8]| {
+            TrieKey::ContractData { account_id: "alice.near".parse().unwrap(), key: key.to_vec() }
+                .to_vec()
+        };
+        let changes = vec![
+            (
+                TrieKey::Account { account_id: "alice.near".parse().unwrap() }.to_vec(),
+                Some(account.try_to_vec().unwrap()),
+            ),
+            (
+                TrieKey::ContractCode { account_id: "alice.near".parse().unwrap() }.to_vec(),
+                Some(vec![0; 5]),
+            ),
+            (
+                TrieKey::AccessKey { account_id: "alice.near".parse().unwrap(), public_key }
+                    .to_vec(),
+                Some(access_key.try_to_vec().unwrap()),
+            ),
+            (data_key(b"aa"), Some(vec![1; 10])),
+            (data_key(b"ab"), Some(vec![1; 20])),
+            (data_key(b"b"), Some(vec![1; 1])),
+            (
+                TrieKey::ContractData {
+                    account_id: "bob.near".parse().unwrap(),
+                    key: b"a".to_vec(),
+                }
+                .to_vec(),
+                Some(vec![1; 100]),
+            ),
+        ];
+        let tries = create_tries();
+        let root = test_populate_trie(&tries, &Trie::EMPTY_ROOT, ShardUId::single_shard(), changes);
+        let trie = tries.get_trie_for_shard(ShardUId::single_shard(), root);
+
+        let usage = StorageUsage::collect(&trie, &account_id, &config, 1).unwrap().unwrap();
+        assert_eq!(usage.code_bytes, 5);
+        assert_eq!(usage.access_keys.records, 1);
+        assert_eq!(usage.access_keys.bytes, key_bytes);
+        assert_eq!(usage.contract_data.records, 3);
+        assert_eq!(usage.computed(), storage_usage);
+        let top: Vec<_> = usage
+            .top_prefixes(10)
+            .into_iter()
+            .map(|(prefix, stats)| (prefix, stats.records))
+            .collect();
+        assert_eq!(top, vec![(&b"a"[..], 2), (&b"b"[..], 1)]);
+
+        // Locked stake covers part of the required balance.
+        assert_eq!(usage.required_balance(10), Balance::from(storage_usage) * 10);
+        assert_eq!(
+            usage.withdrawable_balance(10),
+            10_000 - (Balance::from(storage_usage) * 10 - 1_000)
+        );
+
+        let missing = "carol.near".parse().unwrap();
+        assert!(StorageUsage::collect(&trie, &missing, &config, 1).unwrap().is_none());
+    }
+}
@@ -0,0 +1,283 @@
+//! State viewer functions to collect statistics about the contents of a
+//! shard’s state trie.
+
+use near_primitives::trie_key::col;
+use near_primitives::trie_key::trie_key_parsers::parse_account_id_from_raw_key;
+use near_primitives::types::AccountId;
+use near_store::{RawTrieNode, RawTrieNodeWithSize, StorageError, Trie};
+use std::collections::{BTreeMap, HashMap};
+
+/// Number of trie nodes of each type.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct NodeCounts {
+    pub(crate) leaf: u64,
+    pub(crate) branch: u64,
+    pub(crate) branch_with_value: u64,
+    pub(crate) extension: u64,
+}
+
+impl NodeCounts {
+    fn total(&self) -> u64 {
+        self.leaf + self.branch + self.branch_with_value + self.extension
+    }
+}
+
+/// Number of keys and their total size.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct KeyStats {
+    pub(crate) keys: u64,
+    pub(crate) key_bytes: u64,
+    pub(crate) value_bytes: u64,
+}
+
+impl KeyStats {
+    fn add(&mut self, key_len: usize, value_len: u32) {
+        self.keys += 1;
+        self.key_bytes += key_len as u64;
+        self.value_bytes += u64::from(value_len);
+    }
+
+    fn merge(&mut self, other: &KeyStats) {
+        self.keys += other.keys;
+        self.key_bytes += other.key_bytes;
+        self.value_bytes += other.value_bytes;
+    }
+
+    fn bytes(&self) -> u64 {
+        self.key_bytes + self.value_bytes
+    }
+}
+
+/// Aggregated statistics of a trie.
+///
+/// Value sizes are taken from the trie nodes so the values themselves are
+/// never read from the storage.
+#[derive(Debug, Default)]
+pub(crate) struct TrieStats {
+    pub(crate) nodes: NodeCounts,
+    /// Number of nodes at each depth, where the root node has depth zero.
+    pub(crate) node_depths: BTreeMap<usize, u64>,
+    /// Number of values at each depth counted in trie nodes.
+    pub(crate) value_depths: BTreeMap<usize, u64>,
+    /// Number of values in power of two size buckets, i.e. bucket `n` counts
+    /// values whose size is in `[2^(n-1), 2^n)` range.  Bucket zero counts
+    /// empty values.
+    pub(crate) value_sizes: BTreeMap<u32, u64>,
+    /// Key statistics per `TrieKey` column.
+    pub(crate) columns: BTreeMap<u8, KeyStats>,
+    /// Key statistics per `TrieKey` column and account.  Columns which aren’t
+    /// associated with an account (e.g. delayed receipts) aren’t included.
+    pub(crate) accounts: BTreeMap<u8, HashMap<AccountId, KeyStats>>,
+}
+
+impl TrieStats {
+    /// Walks over all nodes of the trie and aggregates their statistics.
+    pub(crate) fn collect(trie: &Trie) -> Result<Self, StorageError> {
+        let mut stats = Self::default();
+        if trie.get_root() == &Trie::EMPTY_ROOT {
+            return Ok(stats);
+        }
+        // Stack of (node hash, key nibbles leading to the node, node depth).
+        let mut stack = vec![(*trie.get_root(), Vec::new(), 0)];
+        while let Some((hash, mut nibbles, depth)) = stack.pop() {
+            let bytes = trie.storage.retrieve_raw_bytes(&hash)?;
+            let node = RawTrieNodeWithSize::decode(&bytes)
+                .map_err(|err| StorageError::StorageInconsistentState(err.to_string()))?;
+            *stats.node_depths.entry(depth).or_default() += 1;
+            match node.node {
+                RawTrieNode::Leaf(key, value_length, _) => {
+                    stats.nodes.leaf += 1;
+                    nibbles.extend(near_store::NibbleSlice::from_encoded(&key).0.iter());
+                    stats.add_value(&nibbles, value_length, depth);
+                }
+                RawTrieNode::Branch(children, value) => {
+                    if let Some((value_length, _)) = value {
+                        stats.nodes.branch_with_value += 1;
+                        stats.add_value(&nibbles, value_length, depth);
+                    } else {
+                        stats.nodes.branch += 1;
+                    }
+                    // Push in reverse so that children are visited in key order.
+                    for (idx, child) in children.iter().enumerate().rev() {
+                        if let Some(child) = child {
+                            let mut child_nibbles = nibbles.clone();
+                            child_nibbles.push(idx as u8);
+                            stack.push((*child, child_nibbles, depth + 1));
+                        }
+                    }
+                }
+                RawTrieNode::Extension(key, child) => {
+                    stats.nodes.extension += 1;
+                    nibbles.extend(near_store::NibbleSlice::from_encoded(&key).0.iter());
+                    stack.push((child, nibbles, depth + 1));
+                }
+            }
+        }
+        Ok(stats)
+    }
+
+    fn add_value(&mut self, nibbles: &[u8], value_length: u32, depth: usize) {
+        let key: Vec<u8> = nibbles.chunks(2).map(|pair| pair[0] * 16 + pair[1]).collect();
+        *self.value_depths.entry(depth).or_default() += 1;
+        let bucket = u32::BITS - value_length.leading_zeros();
+        *self.value_sizes.entry(bucket).or_default() += 1;
+        let column = match key.first() {
+            Some(column) => *column,
+            None => return,
+        };
+        self.columns.entry(column).or_default().add(key.len(), value_length);
+        if let Ok(Some(account_id)) = parse_account_id_from_raw_key(&key) {
+            self.accounts
+                .entry(column)
+                .or_default()
+                .entry(account_id)
+                .or_default()
+                .add(key.len(), value_length);
+        }
+    }
+
+    /// Returns up to `n` accounts with the largest key statistics according to
+    /// `sort_key`.  If `column` is `None`, statistics of all columns are summed.
+    pub(crate) fn top_accounts(
+        &self,
+        column: Option<u8>,
+        n: usize,
+        sort_key: impl Fn(&KeyStats) -> u64,
+    ) -> Vec<(AccountId, KeyStats)> {
+        let mut accounts: HashMap<&AccountId, KeyStats> = HashMap::new();
+        for (_, per_account) in
+            self.accounts.iter().filter(|(col, _)| column.map_or(true, |c| c == **col))
+        {
+            for (account_id, stats) in per_account {
+                accounts.entry(account_id).or_default().merge(stats);
+            }
+        }
+        let mut accounts: Vec<_> = accounts.into_iter().collect();
+        accounts.sort_by(|(a_id, a), (b_id, b)| {
+            sort_key(b).cmp(&sort_key(a)).then_with(|| a_id.cmp(b_id))
+        });
+        accounts
+            .into_iter()
+            .take(n)
+            .map(|(account_id, stats)| (account_id.clone(), stats))
+            .collect()
+    }
+
+    /// Prints the statistics in human readable form.
+    pub(crate) fn print(&self, top_n: usize) {
+        println!("Nodes: {}", self.nodes.total());
+        println!("  leaf:              {:>12}", self.nodes.leaf);
+        println!("  branch:            {:>12}", self.nodes.branch);
+        println!("  branch with value: {:>12}", self.nodes.branch_with_value);
+        println!("  extension:         {:>12}", self.nodes.extension);
+
+        println!("\nNode depth histogram:");
+        for (depth, count) in &self.node_depths {
+            println!("  {depth:>4} {count:>12}");
+        }
+        println!("\nValue depth histogram:");
+        for (depth, count) in &self.value_depths {
+            println!("  {depth:>4} {count:>12}");
+        }
+        println!("\nValue size histogram:");
+        for (bucket, count) in &self.value_sizes {
+            let (lo, hi) = match bucket {
+                0 => (0, 0),
+                n => (1u64 << (n - 1), (1u64 << n) - 1),
+            };
+            println!("  {:>22} {count:>12}", format!("{lo}..={hi}"));
+        }
+
+        println!("\nColumns:");
+        println!("  {:<24} {:>12} {:>14} {:>14}", "column", "keys", "key bytes", "value bytes");
+        for (column, stats) in &self.columns {
+            println!(
+                "  {:<24} {:>12} {:>14} {:>14}",
+                column_name(*column),
+                stats.keys,
+                stats.key_bytes,
+                stats.value_bytes
+            );
+        }
+
+        let columns = std::iter::once(None).chain(self.accounts.keys().map(|column| Some(*column)));
+        for column in columns {
+            let name = column.map_or("all columns", column_name);
+            println!("\nTop {top_n} accounts by storage bytes ({name}):");
+            for (account_id, stats) in self.top_accounts(column, top_n, KeyStats::bytes) {
+                println!("  {:<64} {:>14}", account_id, stats.bytes());
+            }
+            println!("\nTop {top_n} accounts by number of keys ({name}):");
+            for (account_id, stats) in self.top_accounts(column, top_n, |stats| stats.keys) {
+                println!("  {:<64} {:>14}", account_id, stats.keys);
+            }
+        }
+    }
+}
+
+fn column_name(column: u8) -> &'static str {
+    match column {
+        col::DELAYED_RECEIPT_INDICES => "DelayedReceiptIndices",
+        col::DELAYED_RECEIPT => "DelayedReceipt",
+        col::PROMISE_YIELD_INDICES => "PromiseYieldIndices",
+        col::PROMISE_YIELD_TIMEOUT => "PromiseYieldTimeout",
+        column => col::NON_DELAYED_RECEIPT_COLUMNS
+            .iter()
+            .find(|(col, _)| *col == column)
+            .map_or("Unknown", |(_, name)| *name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KeyStats, TrieStats};
+    use near_primitives::trie_key::{col, TrieKey};
+    use near_store::test_utils::{create_tries, test_populate_trie};
+    use near_store::{ShardUId, Trie};
+
+    #[test]
+    fn test_trie_stats() {
+        let tries = create_tries();
+        let data_key = |account: &str, key: &[u8]| {
+            TrieKey::ContractData { account_id: account.parse().unwrap(), key: key.to_vec() }
+                .to_vec()
+        };
+        let initial = vec![
+            (
+                TrieKey::Account { account_id: "alice.near".parse().unwrap() }.to_vec(),
+                Some(vec![0; 10]),
+            ),
+            (
+                TrieKey::Account { account_id: "bob.near".parse().unwrap() }.to_vec(),
+                Some(vec![0; 10]),
+            ),
+            (data_key("alice.near", b"a"), Some(vec![1; 100])),
+            (data_key("bob.near", b"a"), Some(vec![1; 3])),
+            (data_key("bob.near", b"b"), Some(vec![1; 3])),
+            (TrieKey::DelayedReceiptIndices.to_vec(), Some(vec![2; 16])),
+        ];
+        let root = test_populate_trie(&tries, &Trie::EMPTY_ROOT, ShardUId::single_shard(), initial);
+        let trie = tries.get_trie_for_shard(ShardUId::single_shard(), root);
+        let stats = TrieStats::collect(&trie).unwrap();
+
+        assert_eq!(stats.nodes.leaf + stats.nodes.branch_with_value, 6);
+        assert_eq!(stats.value_depths.values().sum::<u64>(), 6);
+        assert_eq!(stats.node_depths.values().sum::<u64>(), stats.nodes.total());
+        assert_eq!(stats.node_depths.get(&0), Some(&1));
+        // 3-byte values fall into [2, 4) bucket, 100-byte one into [64, 128).
+        assert_eq!(stats.value_sizes.get(&2), Some(&2));
+        assert_eq!(stats.value_sizes.get(&7), Some(&1));
+
+        assert_eq!(stats.columns[&col::ACCOUNT].keys, 2);
+        assert_eq!(stats.columns[&col::CONTRACT_DATA].keys, 3);
+        assert_eq!(stats.columns[&col::DELAYED_RECEIPT_INDICES].keys, 1);
+        assert!(!stats.accounts.contains_key(&col::DELAYED_RECEIPT_INDICES));
+
+        let by_bytes = stats.top_accounts(Some(col::CONTRACT_DATA), 10, KeyStats::bytes);
+        let by_bytes: Vec<_> = by_bytes.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(by_bytes, ["alice.near", "bob.near"]);
+        let by_keys = stats.top_accounts(None, 1, |stats| stats.keys);
+        assert_eq!(by_keys[0].0.as_str(), "bob.near");
+        assert_eq!(by_keys[0].1.keys, 3);
+    }
+}
@@ -0,0 +1,103 @@
+//! State viewer functions to simulate the validator selection with
+//! hypothetical staking proposals.
+
+use near_crypto::PublicKey;
+use near_primitives::epoch_manager::epoch_info::EpochInfo;
+use near_primitives::serialize::dec_format;
+use near_primitives::types::validator_stake::ValidatorStake;
+use near_primitives::types::{AccountId, Balance, ShardId, ValidatorId};
+use serde::Deserialize;
+use std::path::Path;
+
+/// Hypothetical staking proposal as read from the proposals file.
+#[derive(Deserialize, Debug)]
+struct Proposal {
+    account_id: AccountId,
+    /// Defaults to the key the account currently stakes with.
+    #[serde(default)]
+    public_key: Option<PublicKey>,
+    /// Total amount to stake, zero to unstake.
+    #[serde(with = "dec_format")]
+    stake: Balance,
+}
+
+/// Reads proposals from a JSON file with a list of objects like
+/// `{"account_id": "pool.near", "stake": "1000000000000000000000000000000"}`.
+///
+/// Public keys which are not given are looked up in `epoch_infos`, in order.
+pub(crate) fn read_proposals(
+    path: &Path,
+    epoch_infos: &[&EpochInfo],
+) -> anyhow::Result<Vec<ValidatorStake>> {
+    let proposals: Vec<Proposal> = serde_json::from_slice(&std::fs::read(path)?)?;
+    proposals
+        .into_iter()
+        .map(|proposal| {
+            let public_key = match proposal.public_key {
+                Some(public_key) => public_key,
+                None => epoch_infos
+                    .iter()
+                    .find_map(|epoch_info| {
+                        epoch_info
+                            .get_validator_by_account(&proposal.account_id)
+                            .or_else(|| epoch_info.get_fisherman_by_account(&proposal.account_id))
+                    })
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "{} is not a validator, public_key of its proposal is required",
+                            proposal.account_id
+                        )
+                    })?
+                    .take_public_key(),
+            };
+            Ok(ValidatorStake::new(proposal.account_id, public_key, proposal.stake))
+        })
+        .collect()
+}
+
+/// Prints the result of the selection, comparing stakes with the validators
+/// of `prev_epoch_info`.
+pub(crate) fn print_selection(epoch_info: &EpochInfo, prev_epoch_info: &EpochInfo) {
+    println!("Seat price: {}", epoch_info.seat_price());
+    println!("Seat price of the previous epoch: {}", prev_epoch_info.seat_price());
+
+    let chunk_producers_settlement = epoch_info.chunk_producers_settlement();
+    println!("\nValidators:");
+    println!(
+        "  {:<40} {:>40} {:>40} {:>14}  {}",
+        "account", "stake", "previous stake", "block producer", "shards"
+    );
+    for (validator_id, validator) in epoch_info.validators_iter().enumerate() {
+        let validator_id = validator_id as ValidatorId;
+        let is_block_producer = epoch_info.block_producers_settlement().contains(&validator_id);
+        let shards: Vec<ShardId> = chunk_producers_settlement
+            .iter()
+            .enumerate()
+            .filter(|(_, producers)| producers.contains(&validator_id))
+            .map(|(shard_id, _)| shard_id as ShardId)
+            .collect();
+        let prev_stake = prev_epoch_info
+            .get_validator_by_account(validator.account_id())
+            .map_or_else(|| "-".to_string(), |prev| prev.stake().to_string());
+        println!(
+            "  {:<40} {:>40} {:>40} {:>14}  {:?}",
+            validator.account_id(),
+            validator.stake(),
+            prev_stake,
+            if is_block_producer { "yes" } else { "no" },
+            shards
+        );
+    }
+
+    println!("\nFishermen:");
+    for fisherman in epoch_info.fishermen_iter() {
+        println!("  {:<40} {:>40}", fisherman.account_id(), fisherman.stake());
+    }
+
+    println!("\nKickouts:");
+    let mut kickouts: Vec<_> = epoch_info.validator_kickout().iter().collect();
+    kickouts.sort_by_key(|(account_id, _)| *account_id);
+    for (account_id, reason) in kickouts {
+        println!("  {:<40} {:?}", account_id, reason);
+    }
+}